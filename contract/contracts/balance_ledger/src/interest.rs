@@ -0,0 +1,152 @@
+use crate::{checked_add, BalanceLedgerError, DataKey};
+use soroban_sdk::{contracttype, Address, Env};
+
+/// Fixed-point scale of the interest index.
+pub const INTEREST_INDEX_SCALE: i128 = 1_000_000_000_000_000_000;
+/// Highest APR the admin can set, in basis points.
+pub const MAX_INTEREST_APR_BPS: u32 = 10_000;
+
+const BPS_DENOMINATOR: i128 = 10_000;
+const SECONDS_PER_YEAR: i128 = 365 * 86_400;
+
+/// Interest earned per unit of withdrawable balance since launch, scaled by
+/// `INTEREST_INDEX_SCALE`. Interest is simple between credits.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InterestIndex {
+    pub value: i128,
+    pub updated_at: u64,
+}
+
+/// An enrolled user's checkpoint against the global index.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InterestAccount {
+    pub index: i128,
+    pub accrued: i128,
+}
+
+pub fn get_apr(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::InterestApr)
+        .unwrap_or(0)
+}
+
+/// Bring the index up to date at the old rate before switching to `apr_bps`.
+pub fn set_apr(env: &Env, apr_bps: u32) -> Result<(), BalanceLedgerError> {
+    if apr_bps > MAX_INTEREST_APR_BPS {
+        return Err(BalanceLedgerError::InvalidConfig);
+    }
+    update_index(env)?;
+    env.storage()
+        .persistent()
+        .set(&DataKey::InterestApr, &apr_bps);
+    Ok(())
+}
+
+pub fn get_account(env: &Env, user: &Address) -> Option<InterestAccount> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::InterestAccount(user.clone()))
+}
+
+pub fn enroll(env: &Env, user: &Address) -> Result<(), BalanceLedgerError> {
+    if get_account(env, user).is_some() {
+        return Err(BalanceLedgerError::DuplicateOperation);
+    }
+    let index = update_index(env)?;
+    store_account(
+        env,
+        user,
+        &InterestAccount {
+            index: index.value,
+            accrued: 0,
+        },
+    );
+    Ok(())
+}
+
+/// Interest owed to `user` right now, without writing anything.
+pub fn accrued(env: &Env, user: &Address, withdrawable: i128) -> Result<i128, BalanceLedgerError> {
+    match get_account(env, user) {
+        Some(account) => {
+            let index = current_index(env)?;
+            checked_add(
+                account.accrued,
+                interest_since(withdrawable, account.index, index.value)?,
+            )
+        }
+        None => Ok(0),
+    }
+}
+
+/// Settle interest on `withdrawable` up to now. Called before every change
+/// to an enrolled user's base-unit balance.
+pub fn checkpoint(env: &Env, user: &Address, withdrawable: i128) -> Result<(), BalanceLedgerError> {
+    if let Some(mut account) = get_account(env, user) {
+        let index = update_index(env)?;
+        account.accrued = checked_add(
+            account.accrued,
+            interest_since(withdrawable, account.index, index.value)?,
+        )?;
+        account.index = index.value;
+        store_account(env, user, &account);
+    }
+    Ok(())
+}
+
+/// Checkpoint `user` and hand back their accrued interest, resetting it.
+pub fn take_accrued(
+    env: &Env,
+    user: &Address,
+    withdrawable: i128,
+) -> Result<i128, BalanceLedgerError> {
+    checkpoint(env, user, withdrawable)?;
+    let mut account = get_account(env, user).ok_or(BalanceLedgerError::NotEnrolled)?;
+    let amount = account.accrued;
+    account.accrued = 0;
+    store_account(env, user, &account);
+    Ok(amount)
+}
+
+fn current_index(env: &Env) -> Result<InterestIndex, BalanceLedgerError> {
+    let stored: InterestIndex = env
+        .storage()
+        .persistent()
+        .get(&DataKey::InterestIndex)
+        .unwrap_or_default();
+    let now = env.ledger().timestamp();
+    let elapsed = now.saturating_sub(stored.updated_at) as i128;
+    let growth = (get_apr(env) as i128)
+        .checked_mul(elapsed)
+        .and_then(|value| value.checked_mul(INTEREST_INDEX_SCALE))
+        .ok_or(BalanceLedgerError::Overflow)?
+        / (BPS_DENOMINATOR * SECONDS_PER_YEAR);
+
+    Ok(InterestIndex {
+        value: checked_add(stored.value, growth)?,
+        updated_at: now,
+    })
+}
+
+fn update_index(env: &Env) -> Result<InterestIndex, BalanceLedgerError> {
+    let index = current_index(env)?;
+    env.storage()
+        .persistent()
+        .set(&DataKey::InterestIndex, &index);
+    Ok(index)
+}
+
+fn interest_since(withdrawable: i128, from: i128, to: i128) -> Result<i128, BalanceLedgerError> {
+    withdrawable
+        .checked_mul(to - from)
+        .map(|value| value / INTEREST_INDEX_SCALE)
+        .ok_or(BalanceLedgerError::Overflow)
+}
+
+fn store_account(env: &Env, user: &Address, account: &InterestAccount) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::InterestAccount(user.clone()), account);
+}
@@ -0,0 +1,70 @@
+use crate::{DataKey, UserBalance, MAX_PAGE_SIZE};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Symbol, Vec};
+
+/// One balance mutation. Replaying entries in `seq` order from an empty
+/// ledger reproduces every balance; a gap in `seq` means a missed change.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LedgerEntry {
+    pub seq: u64,
+    pub user: Address,
+    pub token: Address,
+    pub delta_w: i128,
+    pub delta_l: i128,
+    pub delta_bonus: i128,
+    pub reason: Symbol,
+    pub op_hash: Option<BytesN<32>>,
+    pub timestamp: u64,
+}
+
+/// Sequence number of the latest entry, or 0 before the first one.
+pub fn latest_seq(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::JournalSeq)
+        .unwrap_or(0)
+}
+
+pub fn append(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    previous: &UserBalance,
+    updated: &UserBalance,
+    reason: &str,
+    op_hash: Option<BytesN<32>>,
+) -> u64 {
+    let seq = latest_seq(env) + 1;
+    let entry = LedgerEntry {
+        seq,
+        user: user.clone(),
+        token: token.clone(),
+        delta_w: updated.withdrawable - previous.withdrawable,
+        delta_l: updated.locked - previous.locked,
+        delta_bonus: updated.bonus - previous.bonus,
+        reason: Symbol::new(env, reason),
+        op_hash,
+        timestamp: env.ledger().timestamp(),
+    };
+
+    let storage = env.storage().persistent();
+    storage.set(&DataKey::JournalEntry(seq), &entry);
+    storage.set(&DataKey::JournalSeq, &seq);
+    seq
+}
+
+/// Entries with `seq >= from_seq`, oldest first.
+pub fn get_entries(env: &Env, from_seq: u64, limit: u32) -> Vec<LedgerEntry> {
+    let storage = env.storage().persistent();
+    let latest = latest_seq(env);
+    let mut entries = Vec::new(env);
+    let mut seq = from_seq.max(1);
+    let end = seq.saturating_add(limit.min(MAX_PAGE_SIZE) as u64);
+    while seq < end && seq <= latest {
+        if let Some(entry) = storage.get(&DataKey::JournalEntry(seq)) {
+            entries.push_back(entry);
+        }
+        seq += 1;
+    }
+    entries
+}
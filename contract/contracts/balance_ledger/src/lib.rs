@@ -1,6 +1,31 @@
 #![no_std]
 
-use soroban_sdk::{contract, contracterror, contractimpl, contracttype, Address, Env, Symbol};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, Address, BytesN, Env, Map, Symbol,
+    Val, Vec,
+};
+
+use common::Role;
+
+mod interest;
+mod journal;
+mod limits;
+mod locks;
+mod self_exclusion;
+pub use interest::{InterestAccount, InterestIndex, INTEREST_INDEX_SCALE, MAX_INTEREST_APR_BPS};
+pub use journal::LedgerEntry;
+pub use limits::*;
+pub use locks::FundLock;
+pub use self_exclusion::SelfExclusion;
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Batch size limit used until the backend configures its own.
+pub const DEFAULT_MAX_BATCH_SIZE: u32 = 50;
+
+/// Upper bound on the page size accepted by the paginated queries.
+pub const MAX_PAGE_SIZE: u32 = 100;
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -12,13 +37,34 @@ pub enum BalanceLedgerError {
     InsufficientWithdrawable = 4,
     InsufficientLocked = 5,
     Overflow = 6,
+    BatchTooLarge = 7,
+    InvalidConfig = 8,
+    TokenNotAllowed = 9,
+    NoPendingSigner = 10,
+    TimelockActive = 11,
+    Paused = 12,
+    DuplicateOperation = 13,
+    NoPendingWithdrawal = 14,
+    WithdrawalsNotConfigured = 15,
+    LimitExceeded = 16,
+    NoExpiredBonus = 17,
+    LockNotFound = 18,
+    NotEnrolled = 19,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct UserBalance {
     pub withdrawable: i128,
+    /// All locked funds, including the bonus-funded part
     pub locked: i128,
+    /// Promotional funds; spent before `withdrawable` and never withdrawn
+    pub bonus: i128,
+    /// Part of `locked` that came from `bonus`. Any reduction of `locked`
+    /// consumes it first, and what of it is credited back to the user
+    /// returns to the bonus bucket.
+    pub locked_bonus: i128,
+    pub bonus_expires_at: u64,
 }
 
 #[contracttype]
@@ -29,17 +75,80 @@ pub struct UserMetrics {
     pub total_lost: i128,
 }
 
+/// Base-unit totals across all users, kept up to date on every mutation.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PlatformMetrics {
+    pub total_withdrawable: i128,
+    pub total_locked: i128,
+    pub total_bonus: i128,
+    /// Users with any withdrawable, locked or bonus funds
+    pub active_users: u32,
+    pub total_staked: i128,
+    pub total_won: i128,
+    pub total_lost: i128,
+}
+
+/// Where withdrawals are paid from. `treasury` must have approved this
+/// ledger to spend `token` on its behalf.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawalConfig {
+    pub token: Address,
+    pub treasury: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawalRequest {
+    pub amount: i128,
+    pub requested_at: u64,
+}
+
+/// Signer handover proposed by the current backend signer. The new signer
+/// must accept it themselves, and not before `effective_at`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingSigner {
+    pub signer: Address,
+    pub proposed_at: u64,
+    pub effective_at: u64,
+}
+
 #[contracttype]
 #[derive(Clone)]
 enum DataKey {
     BackendSigner,
-    Balance(Address),
+    Balance(Address, Address),
     Metrics(Address),
+    MaxBatchSize,
+    AllowedToken(Address),
+    PendingSigner,
+    RotationDelay,
+    WithdrawalConfig,
+    PendingWithdrawal(Address),
+    Limits(Address),
+    PendingLimits(Address),
+    DailyUsage(Address, u64),
+    WeeklyUsage(Address, u64),
+    LimitReporter(Address),
+    JournalSeq,
+    JournalEntry(u64),
+    FundLock(Address, BytesN<32>),
+    LockRefs(Address),
+    ReferencedLocked(Address),
+    PlatformMetrics,
+    InterestApr,
+    InterestIndex,
+    InterestAccount(Address),
+    SelfExclusion(Address),
 }
 
 #[contract]
 pub struct BalanceLedgerContract;
 
+/// Entrypoints without a `_for_token` suffix operate on the ledger's base
+/// unit, which is keyed by the ledger's own address and needs no allowlisting.
 #[contractimpl]
 impl BalanceLedgerContract {
     pub fn initialize(env: Env, backend_signer: Address) -> Result<(), BalanceLedgerError> {
@@ -50,29 +159,265 @@ impl BalanceLedgerContract {
         }
 
         storage.set(&DataKey::BackendSigner, &backend_signer);
+        // The signer starts out holding every administrative role and can
+        // hand them to narrower keys afterwards.
+        common::init_role(&env, Role::Admin, &backend_signer);
+        common::init_role(&env, Role::Pauser, &backend_signer);
+        common::init_role(&env, Role::Treasurer, &backend_signer);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), BalanceLedgerError> {
+        common::grant_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), BalanceLedgerError> {
+        common::revoke_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), BalanceLedgerError> {
+        common::renounce_role(&env, &account, role).map_err(role_error)
+    }
+
+    pub fn set_role_admin(
+        env: Env,
+        caller: Address,
+        role: Role,
+        admin_role: Role,
+    ) -> Result<(), BalanceLedgerError> {
+        common::set_role_admin(&env, &caller, role, admin_role).map_err(role_error)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), BalanceLedgerError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, BalanceLedgerError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Stop duplicating events under their pre-versioning topics from
+    /// `until` on; `None` keeps the duplicates indefinitely.
+    pub fn set_legacy_events_until(
+        env: Env,
+        admin: Address,
+        until: Option<u64>,
+    ) -> Result<(), BalanceLedgerError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        common::set_legacy_events_until(&env, until);
+        Ok(())
+    }
+
+    pub fn get_legacy_events_until(env: Env) -> Option<u64> {
+        common::get_legacy_events_until(&env)
+    }
+
+    pub fn get_role_admin(env: Env, role: Role) -> Role {
+        common::get_role_admin(&env, role)
+    }
+
+    /// Freeze every balance mutation; configuration and getters stay usable
+    /// so the incident can be handled (e.g. by rotating the signer).
+    pub fn pause(env: Env, pauser: Address) -> Result<(), BalanceLedgerError> {
+        common::pause_as_role(&env, &pauser).map_err(role_error)
+    }
+
+    /// Lifting a pause is reserved for admins.
+    pub fn unpause(env: Env, admin: Address) -> Result<(), BalanceLedgerError> {
+        common::unpause_as_role(&env, &admin).map_err(role_error)
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        common::is_paused(&env)
+    }
+
+    pub fn get_backend_signer(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::BackendSigner)
+    }
+
+    /// Delay between `propose_new_signer` and the earliest `accept_signer_role`.
+    pub fn set_rotation_delay(
+        env: Env,
+        admin: Address,
+        delay_seconds: u64,
+    ) -> Result<(), BalanceLedgerError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::RotationDelay, &delay_seconds);
+        Ok(())
+    }
+
+    pub fn get_rotation_delay(env: Env) -> u64 {
+        get_rotation_delay(&env)
+    }
+
+    /// First step of a signer rotation; replaces any pending proposal.
+    pub fn propose_new_signer(
+        env: Env,
+        admin: Address,
+        new_signer: Address,
+    ) -> Result<PendingSigner, BalanceLedgerError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+
+        let now = env.ledger().timestamp();
+        let pending = PendingSigner {
+            signer: new_signer,
+            proposed_at: now,
+            effective_at: now
+                .checked_add(get_rotation_delay(&env))
+                .ok_or(BalanceLedgerError::Overflow)?,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::PendingSigner, &pending);
+
+        publish_signer_proposed_event(&env, &pending);
+
+        Ok(pending)
+    }
+
+    /// Second step: the proposed signer takes over once the timelock expires,
+    /// together with the administrative roles the previous signer held.
+    pub fn accept_signer_role(env: Env) -> Result<Address, BalanceLedgerError> {
+        let storage = env.storage().persistent();
+        let pending: PendingSigner = storage
+            .get(&DataKey::PendingSigner)
+            .ok_or(BalanceLedgerError::NoPendingSigner)?;
+
+        pending.signer.require_auth();
+        if env.ledger().timestamp() < pending.effective_at {
+            return Err(BalanceLedgerError::TimelockActive);
+        }
+
+        let previous: Address = storage
+            .get(&DataKey::BackendSigner)
+            .ok_or(BalanceLedgerError::Unauthorized)?;
+        storage.set(&DataKey::BackendSigner, &pending.signer);
+        storage.remove(&DataKey::PendingSigner);
+
+        for role in [Role::Admin, Role::Pauser, Role::Treasurer] {
+            if common::has_role(&env, role, &previous) {
+                common::apply_role_change(&env, role, &previous, false, &pending.signer);
+                common::apply_role_change(&env, role, &pending.signer, true, &pending.signer);
+            }
+        }
+
+        publish_signer_rotated_event(&env, &previous, &pending.signer);
+
+        Ok(pending.signer)
+    }
+
+    pub fn cancel_signer_rotation(env: Env, admin: Address) -> Result<(), BalanceLedgerError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+
+        let storage = env.storage().persistent();
+        if !storage.has(&DataKey::PendingSigner) {
+            return Err(BalanceLedgerError::NoPendingSigner);
+        }
+        storage.remove(&DataKey::PendingSigner);
+        Ok(())
+    }
+
+    pub fn get_pending_signer(env: Env) -> Option<PendingSigner> {
+        env.storage().persistent().get(&DataKey::PendingSigner)
+    }
+
+    pub fn set_token_allowed(
+        env: Env,
+        treasurer: Address,
+        token: Address,
+        allowed: bool,
+    ) -> Result<(), BalanceLedgerError> {
+        Self::require_role(&env, Role::Treasurer, &treasurer)?;
+
+        let storage = env.storage().persistent();
+        if allowed {
+            storage.set(&DataKey::AllowedToken(token), &true);
+        } else {
+            storage.remove(&DataKey::AllowedToken(token));
+        }
         Ok(())
     }
 
+    pub fn is_token_allowed(env: Env, token: Address) -> bool {
+        is_token_allowed(&env, &token)
+    }
+
+    /// `operation_hash`, when given, makes a retried call fail with
+    /// `DuplicateOperation` instead of applying twice; `ttl_seconds` bounds
+    /// how long the hash is remembered. The same applies to
+    /// `set_balance_for_token`, `apply_delta` and `apply_delta_for_token`,
+    /// which share one hash scope.
     pub fn set_balance(
         env: Env,
         user: Address,
         withdrawable: i128,
         locked: i128,
+        operation_hash: Option<BytesN<32>>,
+        ttl_seconds: Option<u64>,
     ) -> Result<UserBalance, BalanceLedgerError> {
         Self::require_backend_auth(&env)?;
-        validate_non_negative(withdrawable)?;
-        validate_non_negative(locked)?;
-
-        let previous = get_user_balance(&env, &user);
-        let updated = UserBalance {
+        require_not_paused(&env)?;
+        ensure_balance_op_not_replayed(&env, &operation_hash, ttl_seconds)?;
+        set_user_balance(
+            &env,
+            &user,
+            &base_token(&env),
             withdrawable,
             locked,
-        };
-
-        store_user_balance(&env, &user, &updated);
-        publish_balance_updated_event(&env, &user, &previous, &updated);
+            operation_hash,
+        )
+    }
 
-        Ok(updated)
+    pub fn set_balance_for_token(
+        env: Env,
+        user: Address,
+        token: Address,
+        withdrawable: i128,
+        locked: i128,
+        operation_hash: Option<BytesN<32>>,
+        ttl_seconds: Option<u64>,
+    ) -> Result<UserBalance, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        require_token_allowed(&env, &token)?;
+        ensure_balance_op_not_replayed(&env, &operation_hash, ttl_seconds)?;
+        set_user_balance(&env, &user, &token, withdrawable, locked, operation_hash)
     }
 
     pub fn apply_delta(
@@ -80,16 +425,95 @@ impl BalanceLedgerContract {
         user: Address,
         withdrawable_delta: i128,
         locked_delta: i128,
+        operation_hash: Option<BytesN<32>>,
+        ttl_seconds: Option<u64>,
     ) -> Result<UserBalance, BalanceLedgerError> {
         Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        ensure_balance_op_not_replayed(&env, &operation_hash, ttl_seconds)?;
+        apply_user_delta(
+            &env,
+            &user,
+            &base_token(&env),
+            withdrawable_delta,
+            locked_delta,
+            "delta",
+            operation_hash,
+        )
+    }
 
-        let previous = get_user_balance(&env, &user);
-        let updated = apply_balance_delta(&previous, withdrawable_delta, locked_delta)?;
+    pub fn apply_delta_for_token(
+        env: Env,
+        user: Address,
+        token: Address,
+        withdrawable_delta: i128,
+        locked_delta: i128,
+        operation_hash: Option<BytesN<32>>,
+        ttl_seconds: Option<u64>,
+    ) -> Result<UserBalance, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        require_token_allowed(&env, &token)?;
+        ensure_balance_op_not_replayed(&env, &operation_hash, ttl_seconds)?;
+        apply_user_delta(
+            &env,
+            &user,
+            &token,
+            withdrawable_delta,
+            locked_delta,
+            "delta",
+            operation_hash,
+        )
+    }
 
-        store_user_balance(&env, &user, &updated);
-        publish_balance_updated_event(&env, &user, &previous, &updated);
+    pub fn is_balance_operation_executed(env: Env, operation_hash: BytesN<32>) -> bool {
+        common::is_operation_executed(&env, balance_op_scope(&env), operation_hash)
+    }
 
-        Ok(updated)
+    pub fn cleanup_balance_operation(env: Env, operation_hash: BytesN<32>) -> bool {
+        common::cleanup_operation(&env, balance_op_scope(&env), operation_hash)
+    }
+
+    /// Apply `(user, withdrawable_delta, locked_delta)` updates all-or-nothing.
+    /// A user may appear more than once; deltas are applied in order.
+    pub fn apply_deltas_batch(
+        env: Env,
+        updates: Vec<(Address, i128, i128)>,
+    ) -> Result<Vec<UserBalance>, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        apply_batch(&env, &base_token(&env), &updates)
+    }
+
+    pub fn apply_deltas_batch_for_token(
+        env: Env,
+        token: Address,
+        updates: Vec<(Address, i128, i128)>,
+    ) -> Result<Vec<UserBalance>, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        require_token_allowed(&env, &token)?;
+        apply_batch(&env, &token, &updates)
+    }
+
+    pub fn set_max_batch_size(
+        env: Env,
+        admin: Address,
+        max_batch_size: u32,
+    ) -> Result<(), BalanceLedgerError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        if max_batch_size == 0 {
+            return Err(BalanceLedgerError::InvalidConfig);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::MaxBatchSize, &max_batch_size);
+        Ok(())
+    }
+
+    pub fn get_max_batch_size(env: Env) -> u32 {
+        get_max_batch_size(&env)
     }
 
     pub fn lock_funds(
@@ -98,19 +522,25 @@ impl BalanceLedgerContract {
         amount: i128,
     ) -> Result<UserBalance, BalanceLedgerError> {
         Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
         validate_positive(amount)?;
+        limits::record_wager(&env, &user, amount)?;
+        lock_user_funds(&env, &user, &base_token(&env), amount, "lock")
+    }
 
-        let previous = get_user_balance(&env, &user);
-        if previous.withdrawable < amount {
-            return Err(BalanceLedgerError::InsufficientWithdrawable);
-        }
-
-        let updated = apply_balance_delta(&previous, -amount, amount)?;
-
-        store_user_balance(&env, &user, &updated);
-        publish_balance_updated_event(&env, &user, &previous, &updated);
-
-        Ok(updated)
+    /// Lock funds held in `token`. Refused with `LimitExceeded` for users
+    /// with gaming limits, which only count base-unit wagers.
+    pub fn lock_funds_for_token(
+        env: Env,
+        user: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<UserBalance, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        require_token_allowed(&env, &token)?;
+        limits::require_no_limits(&env, &user)?;
+        lock_user_funds(&env, &user, &token, amount, "lock")
     }
 
     pub fn unlock_funds(
@@ -119,38 +549,357 @@ impl BalanceLedgerContract {
         amount: i128,
     ) -> Result<UserBalance, BalanceLedgerError> {
         Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        unlock_user_funds(&env, &user, &base_token(&env), amount, "unlock")
+    }
+
+    pub fn unlock_funds_for_token(
+        env: Env,
+        user: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<UserBalance, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        require_token_allowed(&env, &token)?;
+        unlock_user_funds(&env, &user, &token, amount, "unlock")
+    }
+
+    /// Lock base-unit funds under `ref_id` (e.g. a bet id) so the lock can
+    /// be traced and released on its own. Locking an existing reference
+    /// adds to it. Plain `unlock_funds` and negative `apply_delta` calls
+    /// cannot release referenced funds.
+    pub fn lock_funds_ref(
+        env: Env,
+        user: Address,
+        amount: i128,
+        ref_id: BytesN<32>,
+    ) -> Result<FundLock, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
         validate_positive(amount)?;
+        limits::record_wager(&env, &user, amount)?;
+        lock_user_funds(&env, &user, &base_token(&env), amount, "lock_ref")?;
+        locks::add(&env, &user, &ref_id, amount)
+    }
 
-        let previous = get_user_balance(&env, &user);
-        if previous.locked < amount {
-            return Err(BalanceLedgerError::InsufficientLocked);
-        }
+    /// Return the whole lock for `ref_id` to the user, e.g. on a refund.
+    pub fn unlock_funds_ref(
+        env: Env,
+        user: Address,
+        ref_id: BytesN<32>,
+    ) -> Result<UserBalance, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        let lock = locks::remove(&env, &user, &ref_id)?;
+        unlock_user_funds(&env, &user, &base_token(&env), lock.amount, "unlock_ref")
+    }
 
-        let updated = apply_balance_delta(&previous, amount, -amount)?;
+    /// Close the lock for `ref_id` as settled: the locked stake is spent and
+    /// `payout` (possibly zero) is credited as withdrawable.
+    pub fn settle_funds_ref(
+        env: Env,
+        user: Address,
+        ref_id: BytesN<32>,
+        payout: i128,
+    ) -> Result<UserBalance, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        validate_non_negative(payout)?;
+        let lock = locks::remove(&env, &user, &ref_id)?;
+        apply_user_delta(
+            &env,
+            &user,
+            &base_token(&env),
+            payout,
+            -lock.amount,
+            "settle_ref",
+            None,
+        )
+    }
 
-        store_user_balance(&env, &user, &updated);
-        publish_balance_updated_event(&env, &user, &previous, &updated);
+    pub fn get_lock(env: Env, user: Address, ref_id: BytesN<32>) -> Option<FundLock> {
+        locks::get_lock(&env, &user, &ref_id)
+    }
 
-        Ok(updated)
+    /// Open referenced locks of `user`, oldest first and at most
+    /// `MAX_PAGE_SIZE` per call.
+    pub fn get_locks(env: Env, user: Address, offset: u32, limit: u32) -> Vec<FundLock> {
+        locks::get_locks(&env, &user, offset, limit)
+    }
+
+    /// Part of the user's base-unit `locked` that belongs to references.
+    pub fn get_referenced_locked(env: Env, user: Address) -> i128 {
+        locks::referenced_total(&env, &user)
     }
 
     pub fn get_balance(env: Env, user: Address) -> UserBalance {
-        get_user_balance(&env, &user)
+        get_user_balance(&env, &user, &base_token(&env))
+    }
+
+    pub fn get_balance_for_token(env: Env, user: Address, token: Address) -> UserBalance {
+        get_user_balance(&env, &user, &token)
     }
 
     pub fn get_withdrawable(env: Env, user: Address) -> i128 {
-        get_user_balance(&env, &user).withdrawable
+        get_user_balance(&env, &user, &base_token(&env)).withdrawable
+    }
+
+    pub fn get_withdrawable_for_token(env: Env, user: Address, token: Address) -> i128 {
+        get_user_balance(&env, &user, &token).withdrawable
     }
 
     pub fn get_locked(env: Env, user: Address) -> i128 {
-        get_user_balance(&env, &user).locked
+        get_user_balance(&env, &user, &base_token(&env)).locked
+    }
+
+    pub fn get_locked_for_token(env: Env, user: Address, token: Address) -> i128 {
+        get_user_balance(&env, &user, &token).locked
+    }
+
+    /// Bonus that can still be wagered; zero once it has expired.
+    pub fn get_bonus(env: Env, user: Address) -> i128 {
+        usable_bonus(&env, &get_user_balance(&env, &user, &base_token(&env)))
+    }
+
+    /// Credit promotional funds in the base unit. They are wagered before
+    /// withdrawable funds and can never be withdrawn. Crediting on top of
+    /// an unexpired bonus keeps the later expiry; an expired remainder is
+    /// forfeited first.
+    pub fn credit_bonus(
+        env: Env,
+        user: Address,
+        amount: i128,
+        expires_at: u64,
+    ) -> Result<UserBalance, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        validate_positive(amount)?;
+        if expires_at <= env.ledger().timestamp() {
+            return Err(BalanceLedgerError::InvalidConfig);
+        }
+
+        let token = base_token(&env);
+        let previous = get_user_balance(&env, &user, &token);
+        let current_bonus = usable_bonus(&env, &previous);
+        let updated = UserBalance {
+            bonus: checked_add(current_bonus, amount)?,
+            bonus_expires_at: if current_bonus > 0 {
+                expires_at.max(previous.bonus_expires_at)
+            } else {
+                expires_at
+            },
+            ..previous.clone()
+        };
+
+        commit_balance(
+            &env,
+            &user,
+            &token,
+            &previous,
+            &updated,
+            "bonus_credit",
+            None,
+        )?;
+        publish_bonus_event(
+            &env,
+            "bonus_credited",
+            &user,
+            amount,
+            updated.bonus_expires_at,
+        );
+
+        Ok(updated)
+    }
+
+    /// Reclaim a user's expired bonus for the platform. Bonus locked in
+    /// open wagers is untouched and returns to the bucket if refunded.
+    pub fn sweep_expired_bonus(env: Env, user: Address) -> Result<i128, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+
+        let token = base_token(&env);
+        let previous = get_user_balance(&env, &user, &token);
+        if previous.bonus == 0 || usable_bonus(&env, &previous) > 0 {
+            return Err(BalanceLedgerError::NoExpiredBonus);
+        }
+        let updated = UserBalance {
+            bonus: 0,
+            ..previous.clone()
+        };
+
+        commit_balance(
+            &env,
+            &user,
+            &token,
+            &previous,
+            &updated,
+            "bonus_sweep",
+            None,
+        )?;
+        publish_bonus_event(
+            &env,
+            "bonus_swept",
+            &user,
+            previous.bonus,
+            previous.bonus_expires_at,
+        );
+
+        Ok(previous.bonus)
+    }
+
+    /// Journal entries with `seq >= from_seq`, oldest first and at most
+    /// `MAX_PAGE_SIZE` per call.
+    pub fn get_entries(env: Env, from_seq: u64, limit: u32) -> Vec<LedgerEntry> {
+        journal::get_entries(&env, from_seq, limit)
+    }
+
+    /// Sequence number of the latest journal entry; 0 if there is none.
+    pub fn get_latest_seq(env: Env) -> u64 {
+        journal::latest_seq(&env)
     }
 
     pub fn get_total(env: Env, user: Address) -> Result<i128, BalanceLedgerError> {
-        let balance = get_user_balance(&env, &user);
+        let balance = get_user_balance(&env, &user, &base_token(&env));
+        checked_add(balance.withdrawable, balance.locked)
+    }
+
+    pub fn get_total_for_token(
+        env: Env,
+        user: Address,
+        token: Address,
+    ) -> Result<i128, BalanceLedgerError> {
+        let balance = get_user_balance(&env, &user, &token);
         checked_add(balance.withdrawable, balance.locked)
     }
 
+    pub fn set_withdrawal_config(
+        env: Env,
+        treasurer: Address,
+        token: Address,
+        treasury: Address,
+    ) -> Result<(), BalanceLedgerError> {
+        Self::require_role(&env, Role::Treasurer, &treasurer)?;
+
+        env.storage().persistent().set(
+            &DataKey::WithdrawalConfig,
+            &WithdrawalConfig { token, treasury },
+        );
+        Ok(())
+    }
+
+    pub fn get_withdrawal_config(env: Env) -> Option<WithdrawalConfig> {
+        env.storage().persistent().get(&DataKey::WithdrawalConfig)
+    }
+
+    /// User asks to cash out `amount` of the withdrawal token. Requests add up
+    /// until the backend executes them; nothing is debited yet.
+    pub fn request_withdrawal(
+        env: Env,
+        user: Address,
+        amount: i128,
+    ) -> Result<WithdrawalRequest, BalanceLedgerError> {
+        user.require_auth();
+        require_not_paused(&env)?;
+        validate_positive(amount)?;
+
+        let config = get_withdrawal_config(&env)?;
+        let pending = get_pending_withdrawal(&env, &user);
+        let requested = checked_add(pending.map(|p| p.amount).unwrap_or(0), amount)?;
+        if get_user_balance(&env, &user, &config.token).withdrawable < requested {
+            return Err(BalanceLedgerError::InsufficientWithdrawable);
+        }
+
+        let request = WithdrawalRequest {
+            amount: requested,
+            requested_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::PendingWithdrawal(user.clone()), &request);
+
+        publish_withdrawal_requested_event(&env, &user, &config.token, amount);
+
+        Ok(request)
+    }
+
+    pub fn cancel_withdrawal(env: Env, user: Address) -> Result<(), BalanceLedgerError> {
+        user.require_auth();
+
+        let key = DataKey::PendingWithdrawal(user);
+        let storage = env.storage().persistent();
+        if !storage.has(&key) {
+            return Err(BalanceLedgerError::NoPendingWithdrawal);
+        }
+        storage.remove(&key);
+        Ok(())
+    }
+
+    pub fn get_pending_withdrawal(env: Env, user: Address) -> Option<WithdrawalRequest> {
+        get_pending_withdrawal(&env, &user)
+    }
+
+    /// Pay out (part of) a user's pending request from the treasury. The
+    /// `operation_hash` makes retries by the backend safe.
+    pub fn execute_withdrawal(
+        env: Env,
+        operation_hash: BytesN<32>,
+        user: Address,
+        amount: i128,
+    ) -> Result<UserBalance, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        validate_positive(amount)?;
+
+        let config = get_withdrawal_config(&env)?;
+        let pending =
+            get_pending_withdrawal(&env, &user).ok_or(BalanceLedgerError::NoPendingWithdrawal)?;
+        if amount > pending.amount {
+            return Err(BalanceLedgerError::InvalidAmount);
+        }
+
+        common::ensure_not_replayed(
+            &env,
+            Symbol::new(&env, "withdrawal"),
+            operation_hash.clone(),
+            None,
+        )
+        .map_err(|_| BalanceLedgerError::DuplicateOperation)?;
+
+        let updated = apply_user_delta(
+            &env,
+            &user,
+            &config.token,
+            -amount,
+            0,
+            "withdrawal",
+            Some(operation_hash.clone()),
+        )?;
+
+        let key = DataKey::PendingWithdrawal(user.clone());
+        if amount == pending.amount {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(
+                &key,
+                &WithdrawalRequest {
+                    amount: pending.amount - amount,
+                    requested_at: pending.requested_at,
+                },
+            );
+        }
+
+        token::Client::new(&env, &config.token).transfer_from(
+            &env.current_contract_address(),
+            &config.treasury,
+            &user,
+            &amount,
+        );
+
+        publish_withdrawal_executed_event(&env, &user, &config.token, amount, &operation_hash);
+
+        Ok(updated)
+    }
+
     pub fn record_metrics(
         env: Env,
         user: Address,
@@ -159,6 +908,7 @@ impl BalanceLedgerContract {
         lost_delta: i128,
     ) -> Result<UserMetrics, BalanceLedgerError> {
         Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
         validate_non_negative(staked_delta)?;
         validate_non_negative(won_delta)?;
         validate_non_negative(lost_delta)?;
@@ -172,6 +922,12 @@ impl BalanceLedgerContract {
         };
 
         store_user_metrics(&env, &user, &updated);
+        let mut platform = get_platform_metrics(&env);
+        platform.total_staked = checked_add(platform.total_staked, staked_delta)?;
+        platform.total_won = checked_add(platform.total_won, won_delta)?;
+        platform.total_lost = checked_add(platform.total_lost, lost_delta)?;
+        store_platform_metrics(&env, &platform);
+        limits::record_result(&env, &user, won_delta, lost_delta);
         publish_metrics_updated_event(&env, &user, staked_delta, won_delta, lost_delta, &updated);
 
         Ok(updated)
@@ -181,14 +937,375 @@ impl BalanceLedgerContract {
         get_user_metrics(&env, &user)
     }
 
+    /// Aggregate base-unit balances and cumulative results for dashboards.
+    pub fn get_platform_metrics(env: Env) -> PlatformMetrics {
+        get_platform_metrics(&env)
+    }
+
+    /// Annual rate, in basis points, paid on enrolled users' base-unit
+    /// withdrawable balances. Interest earned so far is kept at the old rate.
+    pub fn set_interest_apr(
+        env: Env,
+        admin: Address,
+        apr_bps: u32,
+    ) -> Result<(), BalanceLedgerError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        interest::set_apr(&env, apr_bps)
+    }
+
+    pub fn get_interest_apr(env: Env) -> u32 {
+        interest::get_apr(&env)
+    }
+
+    /// Opt `user` into interest from now on.
+    pub fn enroll_interest(env: Env, user: Address) -> Result<(), BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+        interest::enroll(&env, &user)
+    }
+
+    pub fn get_interest_account(env: Env, user: Address) -> Option<InterestAccount> {
+        interest::get_account(&env, &user)
+    }
+
+    /// Interest earned and not yet credited; zero if not enrolled.
+    pub fn accrued_interest(env: Env, user: Address) -> Result<i128, BalanceLedgerError> {
+        let balance = get_user_balance(&env, &user, &base_token(&env));
+        interest::accrued(&env, &user, balance.withdrawable)
+    }
+
+    /// Move accrued interest into the user's withdrawable balance and
+    /// return the amount credited.
+    pub fn credit_interest(env: Env, user: Address) -> Result<i128, BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+
+        let token = base_token(&env);
+        let balance = get_user_balance(&env, &user, &token);
+        let amount = interest::take_accrued(&env, &user, balance.withdrawable)?;
+        if amount > 0 {
+            apply_user_delta(&env, &user, &token, amount, 0, "interest", None)?;
+        }
+        Ok(amount)
+    }
+
+    /// Let a user set their own wager and loss caps. Base-unit
+    /// `lock_funds` and reported wagers are checked against them. Lowering
+    /// a cap applies at once; raising or removing one waits out
+    /// `LIMIT_INCREASE_DELAY`.
+    pub fn set_limits(
+        env: Env,
+        user: Address,
+        limits: GamingLimits,
+    ) -> Result<(), BalanceLedgerError> {
+        user.require_auth();
+        update_limits(&env, &user, &limits)
+    }
+
+    /// Set caps on a user's behalf, e.g. from a support request.
+    pub fn set_limits_for_user(
+        env: Env,
+        user: Address,
+        limits: GamingLimits,
+    ) -> Result<(), BalanceLedgerError> {
+        Self::require_backend_auth(&env)?;
+        update_limits(&env, &user, &limits)
+    }
+
+    pub fn get_limits(env: Env, user: Address) -> GamingLimits {
+        get_limits(&env, &user)
+    }
+
+    /// Loosened limits still in their cooling-off period, if any
+    pub fn get_pending_limits(env: Env, user: Address) -> Option<PendingLimits> {
+        get_pending_limits(&env, &user)
+    }
+
+    pub fn get_daily_usage(env: Env, user: Address) -> LimitUsage {
+        get_usage(&env, &user, DAY_SECONDS)
+    }
+
+    pub fn get_weekly_usage(env: Env, user: Address) -> LimitUsage {
+        get_usage(&env, &user, WEEK_SECONDS)
+    }
+
+    /// Bar `user` from betting, spins and staking for `duration_seconds`.
+    /// Contracts that use this ledger as their self-exclusion registry check
+    /// `is_self_excluded`. The exclusion is irreversible: there is no way to
+    /// lift it early, and calling again can only push its end further out.
+    pub fn self_exclude(
+        env: Env,
+        user: Address,
+        duration_seconds: u64,
+    ) -> Result<SelfExclusion, BalanceLedgerError> {
+        user.require_auth();
+        self_exclusion::self_exclude(&env, &user, duration_seconds)
+    }
+
+    pub fn get_self_exclusion(env: Env, user: Address) -> Option<SelfExclusion> {
+        self_exclusion::get_self_exclusion(&env, &user)
+    }
+
+    pub fn is_self_excluded(env: Env, user: Address) -> bool {
+        self_exclusion::is_self_excluded(&env, &user)
+    }
+
+    /// Allow a contract that takes stakes outside the ledger (e.g. betting
+    /// in token escrow mode) to report wagers with `record_wager`.
+    pub fn set_limit_reporter(
+        env: Env,
+        admin: Address,
+        reporter: Address,
+        allowed: bool,
+    ) -> Result<(), BalanceLedgerError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+
+        let key = DataKey::LimitReporter(reporter);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_limit_reporter(env: Env, reporter: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::LimitReporter(reporter))
+    }
+
+    /// Hook for authorized reporters: count `amount` against `user`'s
+    /// caps, failing with `LimitExceeded` if it does not fit.
+    pub fn record_wager(
+        env: Env,
+        reporter: Address,
+        user: Address,
+        amount: i128,
+    ) -> Result<(), BalanceLedgerError> {
+        reporter.require_auth();
+        if !Self::is_limit_reporter(env.clone(), reporter) {
+            return Err(BalanceLedgerError::Unauthorized);
+        }
+        validate_positive(amount)?;
+        limits::record_wager(&env, &user, amount)
+    }
+
     fn require_backend_auth(env: &Env) -> Result<(), BalanceLedgerError> {
-        let storage = env.storage().persistent();
-        let backend_signer: Address = storage
-            .get(&DataKey::BackendSigner)
-            .ok_or(BalanceLedgerError::Unauthorized)?;
-        backend_signer.require_auth();
+        get_backend_signer(env)?.require_auth();
         Ok(())
     }
+
+    fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), BalanceLedgerError> {
+        common::require_role(env, role, account).map_err(role_error)
+    }
+}
+
+fn role_error(_: common::ContractError) -> BalanceLedgerError {
+    BalanceLedgerError::Unauthorized
+}
+
+fn balance_op_scope(env: &Env) -> Symbol {
+    Symbol::new(env, "balance_op")
+}
+
+fn ensure_balance_op_not_replayed(
+    env: &Env,
+    operation_hash: &Option<BytesN<32>>,
+    ttl_seconds: Option<u64>,
+) -> Result<(), BalanceLedgerError> {
+    match operation_hash {
+        Some(hash) => {
+            common::ensure_not_replayed(env, balance_op_scope(env), hash.clone(), ttl_seconds)
+                .map_err(|_| BalanceLedgerError::DuplicateOperation)
+        }
+        None => Ok(()),
+    }
+}
+
+fn get_backend_signer(env: &Env) -> Result<Address, BalanceLedgerError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::BackendSigner)
+        .ok_or(BalanceLedgerError::Unauthorized)
+}
+
+fn require_not_paused(env: &Env) -> Result<(), BalanceLedgerError> {
+    if common::is_paused(env) {
+        return Err(BalanceLedgerError::Paused);
+    }
+    Ok(())
+}
+
+fn base_token(env: &Env) -> Address {
+    env.current_contract_address()
+}
+
+fn is_token_allowed(env: &Env, token: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::AllowedToken(token.clone()))
+}
+
+fn require_token_allowed(env: &Env, token: &Address) -> Result<(), BalanceLedgerError> {
+    if !is_token_allowed(env, token) {
+        return Err(BalanceLedgerError::TokenNotAllowed);
+    }
+    Ok(())
+}
+
+fn set_user_balance(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    withdrawable: i128,
+    locked: i128,
+    op_hash: Option<BytesN<32>>,
+) -> Result<UserBalance, BalanceLedgerError> {
+    validate_non_negative(withdrawable)?;
+    validate_non_negative(locked)?;
+
+    let previous = get_user_balance(env, user, token);
+    let updated = UserBalance {
+        withdrawable,
+        locked,
+        locked_bonus: previous.locked_bonus.min(locked),
+        ..previous.clone()
+    };
+
+    commit_balance(
+        env,
+        user,
+        token,
+        &previous,
+        &updated,
+        "set_balance",
+        op_hash,
+    )?;
+
+    Ok(updated)
+}
+
+fn apply_user_delta(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    withdrawable_delta: i128,
+    locked_delta: i128,
+    reason: &str,
+    op_hash: Option<BytesN<32>>,
+) -> Result<UserBalance, BalanceLedgerError> {
+    let previous = get_user_balance(env, user, token);
+    let updated = apply_balance_delta(&previous, withdrawable_delta, locked_delta)?;
+
+    commit_balance(env, user, token, &previous, &updated, reason, op_hash)?;
+
+    Ok(updated)
+}
+
+fn lock_user_funds(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    amount: i128,
+    reason: &str,
+) -> Result<UserBalance, BalanceLedgerError> {
+    validate_positive(amount)?;
+
+    // Wagers draw from unexpired bonus before real money
+    let previous = get_user_balance(env, user, token);
+    let from_bonus = usable_bonus(env, &previous).min(amount);
+    let from_withdrawable = amount - from_bonus;
+    if previous.withdrawable < from_withdrawable {
+        return Err(BalanceLedgerError::InsufficientWithdrawable);
+    }
+
+    let mut updated = apply_balance_delta(&previous, -from_withdrawable, amount)?;
+    updated.bonus -= from_bonus;
+    updated.locked_bonus = checked_add(updated.locked_bonus, from_bonus)?;
+
+    commit_balance(env, user, token, &previous, &updated, reason, None)?;
+
+    Ok(updated)
+}
+
+fn unlock_user_funds(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    amount: i128,
+    reason: &str,
+) -> Result<UserBalance, BalanceLedgerError> {
+    validate_positive(amount)?;
+
+    let previous = get_user_balance(env, user, token);
+    if previous.locked < amount {
+        return Err(BalanceLedgerError::InsufficientLocked);
+    }
+
+    let updated = apply_balance_delta(&previous, amount, -amount)?;
+
+    commit_balance(env, user, token, &previous, &updated, reason, None)?;
+
+    Ok(updated)
+}
+
+fn usable_bonus(env: &Env, balance: &UserBalance) -> i128 {
+    if env.ledger().timestamp() < balance.bonus_expires_at {
+        balance.bonus
+    } else {
+        0
+    }
+}
+
+fn apply_batch(
+    env: &Env,
+    token: &Address,
+    updates: &Vec<(Address, i128, i128)>,
+) -> Result<Vec<UserBalance>, BalanceLedgerError> {
+    if updates.len() > get_max_batch_size(env) {
+        return Err(BalanceLedgerError::BatchTooLarge);
+    }
+
+    // Validate everything against a working copy before touching storage
+    let mut working: Map<Address, UserBalance> = Map::new(env);
+    let mut previous: Map<Address, UserBalance> = Map::new(env);
+    let mut results = Vec::new(env);
+    let mut total_withdrawable_delta: i128 = 0;
+    let mut total_locked_delta: i128 = 0;
+
+    for (user, withdrawable_delta, locked_delta) in updates.iter() {
+        let current = match working.get(user.clone()) {
+            Some(balance) => balance,
+            None => {
+                let stored = get_user_balance(env, &user, token);
+                previous.set(user.clone(), stored.clone());
+                stored
+            }
+        };
+        let updated = apply_balance_delta(&current, withdrawable_delta, locked_delta)?;
+
+        total_withdrawable_delta = checked_add(total_withdrawable_delta, withdrawable_delta)?;
+        total_locked_delta = checked_add(total_locked_delta, locked_delta)?;
+        working.set(user, updated.clone());
+        results.push_back(updated);
+    }
+
+    for (user, updated) in working.iter() {
+        if let Some(before) = previous.get(user.clone()) {
+            commit_balance(env, &user, token, &before, &updated, "batch", None)?;
+        }
+    }
+    publish_batch_applied_event(
+        env,
+        token,
+        updates.len(),
+        working.len(),
+        total_withdrawable_delta,
+        total_locked_delta,
+    );
+
+    Ok(results)
 }
 
 fn apply_balance_delta(
@@ -196,7 +1313,16 @@ fn apply_balance_delta(
     withdrawable_delta: i128,
     locked_delta: i128,
 ) -> Result<UserBalance, BalanceLedgerError> {
-    let next_withdrawable = checked_add(current.withdrawable, withdrawable_delta)?;
+    // Locked bonus is consumed first. What of it is handed back to the user,
+    // as in a refund `(amount, -amount)`, returns to the bonus bucket.
+    let released_bonus = if locked_delta < 0 {
+        current.locked_bonus.min(-locked_delta)
+    } else {
+        0
+    };
+    let to_bonus = released_bonus.min(withdrawable_delta.max(0));
+
+    let next_withdrawable = checked_add(current.withdrawable, withdrawable_delta - to_bonus)?;
     let next_locked = checked_add(current.locked, locked_delta)?;
 
     if next_withdrawable < 0 {
@@ -209,6 +1335,9 @@ fn apply_balance_delta(
     Ok(UserBalance {
         withdrawable: next_withdrawable,
         locked: next_locked,
+        bonus: checked_add(current.bonus, to_bonus)?,
+        locked_bonus: current.locked_bonus - released_bonus,
+        ..current.clone()
     })
 }
 
@@ -230,20 +1359,50 @@ fn validate_positive(amount: i128) -> Result<(), BalanceLedgerError> {
     Ok(())
 }
 
-fn get_user_balance(env: &Env, user: &Address) -> UserBalance {
+fn get_user_balance(env: &Env, user: &Address, token: &Address) -> UserBalance {
+    let key = DataKey::Balance(user.clone(), token.clone());
+    common::bump_persistent(env, &key);
+    env.storage().persistent().get(&key).unwrap_or(UserBalance {
+        withdrawable: 0,
+        locked: 0,
+        bonus: 0,
+        locked_bonus: 0,
+        bonus_expires_at: 0,
+    })
+}
+
+fn store_user_balance(env: &Env, user: &Address, token: &Address, balance: &UserBalance) {
+    let key = DataKey::Balance(user.clone(), token.clone());
+    env.storage().persistent().set(&key, balance);
+    common::bump_persistent(env, &key);
+    common::bump_instance(env);
+}
+
+fn get_withdrawal_config(env: &Env) -> Result<WithdrawalConfig, BalanceLedgerError> {
     env.storage()
         .persistent()
-        .get(&DataKey::Balance(user.clone()))
-        .unwrap_or(UserBalance {
-            withdrawable: 0,
-            locked: 0,
-        })
+        .get(&DataKey::WithdrawalConfig)
+        .ok_or(BalanceLedgerError::WithdrawalsNotConfigured)
+}
+
+fn get_pending_withdrawal(env: &Env, user: &Address) -> Option<WithdrawalRequest> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PendingWithdrawal(user.clone()))
+}
+
+fn get_rotation_delay(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RotationDelay)
+        .unwrap_or(0)
 }
 
-fn store_user_balance(env: &Env, user: &Address, balance: &UserBalance) {
+fn get_max_batch_size(env: &Env) -> u32 {
     env.storage()
         .persistent()
-        .set(&DataKey::Balance(user.clone()), balance);
+        .get(&DataKey::MaxBatchSize)
+        .unwrap_or(DEFAULT_MAX_BATCH_SIZE)
 }
 
 fn get_user_metrics(env: &Env, user: &Address) -> UserMetrics {
@@ -263,23 +1422,168 @@ fn store_user_metrics(env: &Env, user: &Address, metrics: &UserMetrics) {
         .set(&DataKey::Metrics(user.clone()), metrics);
 }
 
+/// Store `updated`, publish the change and append it to the journal.
+/// Base-unit `locked` must keep covering the user's referenced locks.
+fn commit_balance(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    previous: &UserBalance,
+    updated: &UserBalance,
+    reason: &str,
+    op_hash: Option<BytesN<32>>,
+) -> Result<(), BalanceLedgerError> {
+    if *token == base_token(env) {
+        locks::require_covered(env, user, updated)?;
+        update_platform_balances(env, previous, updated)?;
+        interest::checkpoint(env, user, previous.withdrawable)?;
+    }
+    store_user_balance(env, user, token, updated);
+    publish_balance_updated_event(env, user, token, previous, updated);
+    journal::append(env, user, token, previous, updated, reason, op_hash);
+    Ok(())
+}
+
+fn get_platform_metrics(env: &Env) -> PlatformMetrics {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PlatformMetrics)
+        .unwrap_or_default()
+}
+
+fn store_platform_metrics(env: &Env, metrics: &PlatformMetrics) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::PlatformMetrics, metrics);
+}
+
+fn update_platform_balances(
+    env: &Env,
+    previous: &UserBalance,
+    updated: &UserBalance,
+) -> Result<(), BalanceLedgerError> {
+    let mut metrics = get_platform_metrics(env);
+    metrics.total_withdrawable = checked_add(
+        metrics.total_withdrawable,
+        updated.withdrawable - previous.withdrawable,
+    )?;
+    metrics.total_locked = checked_add(metrics.total_locked, updated.locked - previous.locked)?;
+    metrics.total_bonus = checked_add(metrics.total_bonus, updated.bonus - previous.bonus)?;
+    match (has_funds(previous), has_funds(updated)) {
+        (false, true) => metrics.active_users += 1,
+        (true, false) => metrics.active_users -= 1,
+        _ => {}
+    }
+    store_platform_metrics(env, &metrics);
+    Ok(())
+}
+
+fn has_funds(balance: &UserBalance) -> bool {
+    balance.withdrawable != 0 || balance.locked != 0 || balance.bonus != 0
+}
+
+/// Base-unit updates keep the original `(balance_updated, user)` topics;
+/// token balances add the token as a third topic.
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
 fn publish_balance_updated_event(
     env: &Env,
     user: &Address,
+    token: &Address,
     previous: &UserBalance,
     updated: &UserBalance,
 ) {
+    let payload = (
+        previous.withdrawable,
+        previous.locked,
+        updated.withdrawable,
+        updated.locked,
+    );
+    let topic = Symbol::new(env, "balance_updated");
+
+    if *token == base_token(env) {
+        env.events().publish((topic, user.clone()), payload);
+    } else {
+        env.events()
+            .publish((topic, user.clone(), token.clone()), payload);
+    }
+
+    // Bonus movements get their own event so indexers can tell them apart
+    // from real-money balance changes
+    if previous.bonus != updated.bonus || previous.locked_bonus != updated.locked_bonus {
+        env.events().publish(
+            (Symbol::new(env, "bonus_updated"), user.clone()),
+            (
+                previous.bonus,
+                previous.locked_bonus,
+                updated.bonus,
+                updated.locked_bonus,
+            ),
+        );
+    }
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_bonus_event(env: &Env, topic: &str, user: &Address, amount: i128, expires_at: u64) {
     env.events().publish(
-        (Symbol::new(env, "balance_updated"), user.clone()),
-        (
-            previous.withdrawable,
-            previous.locked,
-            updated.withdrawable,
-            updated.locked,
-        ),
+        (Symbol::new(env, topic), user.clone()),
+        (amount, expires_at),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_batch_applied_event(
+    env: &Env,
+    token: &Address,
+    update_count: u32,
+    user_count: u32,
+    withdrawable_delta: i128,
+    locked_delta: i128,
+) {
+    env.events().publish(
+        (Symbol::new(env, "balance_batch_applied"), token.clone()),
+        (update_count, user_count, withdrawable_delta, locked_delta),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_withdrawal_requested_event(env: &Env, user: &Address, token: &Address, amount: i128) {
+    env.events().publish(
+        (Symbol::new(env, "withdrawal_requested"), user.clone()),
+        (token.clone(), amount),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_withdrawal_executed_event(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    amount: i128,
+    operation_hash: &BytesN<32>,
+) {
+    env.events().publish(
+        (Symbol::new(env, "withdrawal_executed"), user.clone()),
+        (token.clone(), amount, operation_hash.clone()),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_signer_proposed_event(env: &Env, pending: &PendingSigner) {
+    env.events().publish(
+        (Symbol::new(env, "signer_proposed"), pending.signer.clone()),
+        (pending.proposed_at, pending.effective_at),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_signer_rotated_event(env: &Env, previous: &Address, new_signer: &Address) {
+    env.events().publish(
+        (Symbol::new(env, "signer_rotated"),),
+        (previous.clone(), new_signer.clone()),
     );
 }
 
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
 fn publish_metrics_updated_event(
     env: &Env,
     user: &Address,
@@ -0,0 +1,211 @@
+use crate::{BalanceLedgerError, DataKey};
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+pub const DAY_SECONDS: u64 = 86_400;
+pub const WEEK_SECONDS: u64 = 7 * DAY_SECONDS;
+/// Cooling-off period before a raised or removed cap takes effect.
+pub const LIMIT_INCREASE_DELAY: u64 = DAY_SECONDS;
+
+/// Responsible-gaming caps in the ledger's base unit. `None` leaves that
+/// cap off. Loss caps apply to net losses (`lost - won`) and count every
+/// new wager as if it were lost.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GamingLimits {
+    pub daily_wager: Option<i128>,
+    pub weekly_wager: Option<i128>,
+    pub daily_loss: Option<i128>,
+    pub weekly_loss: Option<i128>,
+}
+
+/// Limits that loosen a cap, waiting out `LIMIT_INCREASE_DELAY`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingLimits {
+    pub limits: GamingLimits,
+    pub effective_at: u64,
+}
+
+/// Activity counted against the limits within one day or week.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LimitUsage {
+    pub wagered: i128,
+    pub won: i128,
+    pub lost: i128,
+}
+
+impl LimitUsage {
+    pub fn net_loss(&self) -> i128 {
+        self.lost.saturating_sub(self.won)
+    }
+}
+
+pub fn validate_limits(limits: &GamingLimits) -> Result<(), BalanceLedgerError> {
+    let caps = [
+        limits.daily_wager,
+        limits.weekly_wager,
+        limits.daily_loss,
+        limits.weekly_loss,
+    ];
+    if caps.iter().flatten().any(|cap| *cap <= 0) {
+        return Err(BalanceLedgerError::InvalidConfig);
+    }
+    Ok(())
+}
+
+/// Limits in force now, including a pending change whose delay has passed.
+pub fn get_limits(env: &Env, user: &Address) -> GamingLimits {
+    let storage = env.storage().persistent();
+    match storage.get::<_, PendingLimits>(&DataKey::PendingLimits(user.clone())) {
+        Some(pending) if pending.effective_at <= env.ledger().timestamp() => pending.limits,
+        _ => storage
+            .get(&DataKey::Limits(user.clone()))
+            .unwrap_or_default(),
+    }
+}
+
+pub fn get_pending_limits(env: &Env, user: &Address) -> Option<PendingLimits> {
+    env.storage()
+        .persistent()
+        .get::<_, PendingLimits>(&DataKey::PendingLimits(user.clone()))
+        .filter(|pending| pending.effective_at > env.ledger().timestamp())
+}
+
+/// Replace `user`'s limits. Tighter caps apply at once; any cap that is
+/// raised or removed keeps its current value until the cooling-off delay
+/// has passed, after which the full new set applies. A new call replaces
+/// any change still pending.
+pub fn update_limits(
+    env: &Env,
+    user: &Address,
+    limits: &GamingLimits,
+) -> Result<(), BalanceLedgerError> {
+    validate_limits(limits)?;
+
+    let current = get_limits(env, user);
+    let immediate = GamingLimits {
+        daily_wager: stricter(current.daily_wager, limits.daily_wager),
+        weekly_wager: stricter(current.weekly_wager, limits.weekly_wager),
+        daily_loss: stricter(current.daily_loss, limits.daily_loss),
+        weekly_loss: stricter(current.weekly_loss, limits.weekly_loss),
+    };
+
+    let storage = env.storage().persistent();
+    let limits_key = DataKey::Limits(user.clone());
+    let pending_key = DataKey::PendingLimits(user.clone());
+    storage.set(&limits_key, &immediate);
+    common::bump_persistent(env, &limits_key);
+    publish_limits_event(env, user, &immediate);
+
+    if immediate == *limits {
+        storage.remove(&pending_key);
+    } else {
+        let pending = PendingLimits {
+            limits: limits.clone(),
+            effective_at: env
+                .ledger()
+                .timestamp()
+                .checked_add(LIMIT_INCREASE_DELAY)
+                .ok_or(BalanceLedgerError::Overflow)?,
+        };
+        storage.set(&pending_key, &pending);
+        common::bump_persistent(env, &pending_key);
+        publish_limits_pending_event(env, user, &pending);
+    }
+    Ok(())
+}
+
+pub fn get_usage(env: &Env, user: &Address, period_seconds: u64) -> LimitUsage {
+    env.storage()
+        .persistent()
+        .get(&usage_key(env, user, period_seconds))
+        .unwrap_or_default()
+}
+
+/// Reject `amount` if it would take `user` past any of their caps,
+/// otherwise count it as wagered.
+pub fn record_wager(env: &Env, user: &Address, amount: i128) -> Result<(), BalanceLedgerError> {
+    let limits = get_limits(env, user);
+    let mut daily = get_usage(env, user, DAY_SECONDS);
+    let mut weekly = get_usage(env, user, WEEK_SECONDS);
+
+    check_cap(daily.wagered, amount, limits.daily_wager)?;
+    check_cap(weekly.wagered, amount, limits.weekly_wager)?;
+    check_cap(daily.net_loss(), amount, limits.daily_loss)?;
+    check_cap(weekly.net_loss(), amount, limits.weekly_loss)?;
+
+    daily.wagered = daily.wagered.saturating_add(amount);
+    weekly.wagered = weekly.wagered.saturating_add(amount);
+    store_usage(env, user, DAY_SECONDS, &daily);
+    store_usage(env, user, WEEK_SECONDS, &weekly);
+    Ok(())
+}
+
+/// Caps are counted in the base unit, so `user` may only lock other
+/// tokens while they have none.
+pub fn require_no_limits(env: &Env, user: &Address) -> Result<(), BalanceLedgerError> {
+    if get_limits(env, user) != GamingLimits::default() {
+        return Err(BalanceLedgerError::LimitExceeded);
+    }
+    Ok(())
+}
+
+/// Count settled wins and losses toward the current periods.
+pub fn record_result(env: &Env, user: &Address, won: i128, lost: i128) {
+    if won == 0 && lost == 0 {
+        return;
+    }
+    for period_seconds in [DAY_SECONDS, WEEK_SECONDS] {
+        let mut usage = get_usage(env, user, period_seconds);
+        usage.won = usage.won.saturating_add(won);
+        usage.lost = usage.lost.saturating_add(lost);
+        store_usage(env, user, period_seconds, &usage);
+    }
+}
+
+fn stricter(current: Option<i128>, requested: Option<i128>) -> Option<i128> {
+    match (current, requested) {
+        (Some(current), Some(requested)) => Some(current.min(requested)),
+        (current, None) => current,
+        (None, requested) => requested,
+    }
+}
+
+fn check_cap(used: i128, amount: i128, cap: Option<i128>) -> Result<(), BalanceLedgerError> {
+    match cap {
+        Some(cap) if used.saturating_add(amount) > cap => Err(BalanceLedgerError::LimitExceeded),
+        _ => Ok(()),
+    }
+}
+
+fn usage_key(env: &Env, user: &Address, period_seconds: u64) -> DataKey {
+    let period = env.ledger().timestamp() / period_seconds;
+    if period_seconds == DAY_SECONDS {
+        DataKey::DailyUsage(user.clone(), period)
+    } else {
+        DataKey::WeeklyUsage(user.clone(), period)
+    }
+}
+
+fn store_usage(env: &Env, user: &Address, period_seconds: u64, usage: &LimitUsage) {
+    let key = usage_key(env, user, period_seconds);
+    env.storage().persistent().set(&key, usage);
+    common::bump_persistent(env, &key);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_limits_event(env: &Env, user: &Address, limits: &GamingLimits) {
+    env.events().publish(
+        (Symbol::new(env, "limits_updated"), user.clone()),
+        limits.clone(),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_limits_pending_event(env: &Env, user: &Address, pending: &PendingLimits) {
+    env.events().publish(
+        (Symbol::new(env, "limits_pending"), user.clone()),
+        pending.clone(),
+    );
+}
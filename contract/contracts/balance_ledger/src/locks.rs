@@ -0,0 +1,116 @@
+use crate::{BalanceLedgerError, DataKey, UserBalance, MAX_PAGE_SIZE};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// Base-unit funds locked for one reference, e.g. a bet id.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundLock {
+    pub ref_id: BytesN<32>,
+    pub amount: i128,
+    pub locked_at: u64,
+}
+
+pub fn get_lock(env: &Env, user: &Address, ref_id: &BytesN<32>) -> Option<FundLock> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::FundLock(user.clone(), ref_id.clone()))
+}
+
+/// Sum of a user's referenced locks; never more than their `locked`.
+pub fn referenced_total(env: &Env, user: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ReferencedLocked(user.clone()))
+        .unwrap_or(0)
+}
+
+/// Reject a base-unit balance whose `locked` no longer covers its
+/// referenced locks.
+pub fn require_covered(
+    env: &Env,
+    user: &Address,
+    balance: &UserBalance,
+) -> Result<(), BalanceLedgerError> {
+    if balance.locked < referenced_total(env, user) {
+        return Err(BalanceLedgerError::InsufficientLocked);
+    }
+    Ok(())
+}
+
+/// Add `amount` to the lock for `ref_id`, creating it if needed.
+pub fn add(
+    env: &Env,
+    user: &Address,
+    ref_id: &BytesN<32>,
+    amount: i128,
+) -> Result<FundLock, BalanceLedgerError> {
+    let storage = env.storage().persistent();
+    let lock = match get_lock(env, user, ref_id) {
+        Some(mut lock) => {
+            lock.amount = crate::checked_add(lock.amount, amount)?;
+            lock
+        }
+        None => {
+            let refs_key = DataKey::LockRefs(user.clone());
+            let mut refs: Vec<BytesN<32>> = storage.get(&refs_key).unwrap_or(Vec::new(env));
+            refs.push_back(ref_id.clone());
+            storage.set(&refs_key, &refs);
+            FundLock {
+                ref_id: ref_id.clone(),
+                amount,
+                locked_at: env.ledger().timestamp(),
+            }
+        }
+    };
+
+    storage.set(&DataKey::FundLock(user.clone(), ref_id.clone()), &lock);
+    let total = crate::checked_add(referenced_total(env, user), amount)?;
+    storage.set(&DataKey::ReferencedLocked(user.clone()), &total);
+    Ok(lock)
+}
+
+/// Drop the lock for `ref_id` and return it.
+pub fn remove(
+    env: &Env,
+    user: &Address,
+    ref_id: &BytesN<32>,
+) -> Result<FundLock, BalanceLedgerError> {
+    let lock = get_lock(env, user, ref_id).ok_or(BalanceLedgerError::LockNotFound)?;
+    let storage = env.storage().persistent();
+    storage.remove(&DataKey::FundLock(user.clone(), ref_id.clone()));
+
+    let refs_key = DataKey::LockRefs(user.clone());
+    let mut refs: Vec<BytesN<32>> = storage.get(&refs_key).unwrap_or(Vec::new(env));
+    if let Some(index) = refs.first_index_of(ref_id) {
+        refs.remove(index);
+    }
+    if refs.is_empty() {
+        storage.remove(&refs_key);
+    } else {
+        storage.set(&refs_key, &refs);
+    }
+
+    let total = referenced_total(env, user) - lock.amount;
+    storage.set(&DataKey::ReferencedLocked(user.clone()), &total);
+    Ok(lock)
+}
+
+/// Open locks of `user`, oldest first.
+pub fn get_locks(env: &Env, user: &Address, offset: u32, limit: u32) -> Vec<FundLock> {
+    let refs: Vec<BytesN<32>> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::LockRefs(user.clone()))
+        .unwrap_or(Vec::new(env));
+    let end = offset
+        .saturating_add(limit.min(MAX_PAGE_SIZE))
+        .min(refs.len());
+
+    let mut locks = Vec::new(env);
+    for index in offset..end {
+        if let Some(lock) = get_lock(env, user, &refs.get_unchecked(index)) {
+            locks.push_back(lock);
+        }
+    }
+    locks
+}
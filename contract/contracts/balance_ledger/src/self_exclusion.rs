@@ -0,0 +1,63 @@
+use crate::{BalanceLedgerError, DataKey};
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+/// A period a user has barred themselves from wagering and staking.
+/// Nothing can end it before `ends_at`, not even the admin.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SelfExclusion {
+    pub started_at: u64,
+    pub ends_at: u64,
+}
+
+pub fn get_self_exclusion(env: &Env, user: &Address) -> Option<SelfExclusion> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SelfExclusion(user.clone()))
+}
+
+pub fn is_self_excluded(env: &Env, user: &Address) -> bool {
+    get_self_exclusion(env, user)
+        .is_some_and(|exclusion| env.ledger().timestamp() < exclusion.ends_at)
+}
+
+/// Exclude `user` for `duration_seconds` from now. A running exclusion can
+/// only be lengthened; asking for an earlier end fails with `InvalidConfig`.
+pub fn self_exclude(
+    env: &Env,
+    user: &Address,
+    duration_seconds: u64,
+) -> Result<SelfExclusion, BalanceLedgerError> {
+    if duration_seconds == 0 {
+        return Err(BalanceLedgerError::InvalidConfig);
+    }
+    let now = env.ledger().timestamp();
+    let ends_at = now.saturating_add(duration_seconds);
+
+    let exclusion = match get_self_exclusion(env, user) {
+        Some(current) if now < current.ends_at => {
+            if ends_at < current.ends_at {
+                return Err(BalanceLedgerError::InvalidConfig);
+            }
+            SelfExclusion { ends_at, ..current }
+        }
+        _ => SelfExclusion {
+            started_at: now,
+            ends_at,
+        },
+    };
+
+    let key = DataKey::SelfExclusion(user.clone());
+    env.storage().persistent().set(&key, &exclusion);
+    common::bump_persistent(env, &key);
+    publish_self_exclusion_event(env, user, &exclusion);
+    Ok(exclusion)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_self_exclusion_event(env: &Env, user: &Address, exclusion: &SelfExclusion) {
+    env.events().publish(
+        (Symbol::new(env, "self_excluded"), user.clone()),
+        exclusion.clone(),
+    );
+}
@@ -1,7 +1,10 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Address, Env};
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _, Ledger},
+    token, vec, Address, BytesN, Env, IntoVal,
+};
 
 #[test]
 fn initialize_only_once() {
@@ -28,13 +31,16 @@ fn set_and_query_split_balances() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    let updated = client.set_balance(&user, &1_000, &250);
+    let updated = client.set_balance(&user, &1_000, &250, &None, &None);
 
     assert_eq!(
         updated,
         UserBalance {
             withdrawable: 1_000,
             locked: 250,
+            bonus: 0,
+            locked_bonus: 0,
+            bonus_expires_at: 0,
         }
     );
     assert_eq!(client.get_withdrawable(&user), 1_000);
@@ -54,7 +60,7 @@ fn lock_and_unlock_funds_atomically() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &500, &100);
+    client.set_balance(&user, &500, &100, &None, &None);
 
     let locked = client.lock_funds(&user, &200);
     assert_eq!(locked.withdrawable, 300);
@@ -76,9 +82,9 @@ fn apply_delta_updates_both_buckets_atomically() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &200, &75);
+    client.set_balance(&user, &200, &75, &None, &None);
 
-    let updated = client.apply_delta(&user, &-25, &125);
+    let updated = client.apply_delta(&user, &-25, &125, &None, &None);
     assert_eq!(updated.withdrawable, 175);
     assert_eq!(updated.locked, 200);
 }
@@ -94,10 +100,10 @@ fn rejects_invalid_or_insufficient_updates() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &100, &10);
+    client.set_balance(&user, &100, &10, &None, &None);
 
     assert_eq!(
-        client.try_set_balance(&user, &-1, &10),
+        client.try_set_balance(&user, &-1, &10, &None, &None),
         Err(Ok(BalanceLedgerError::InvalidAmount))
     );
     assert_eq!(
@@ -109,7 +115,7 @@ fn rejects_invalid_or_insufficient_updates() {
         Err(Ok(BalanceLedgerError::InsufficientLocked))
     );
     assert_eq!(
-        client.try_apply_delta(&user, &-101, &0),
+        client.try_apply_delta(&user, &-101, &0, &None, &None),
         Err(Ok(BalanceLedgerError::InsufficientWithdrawable))
     );
 }
@@ -194,12 +200,12 @@ fn get_total_prevents_overflow() {
     // Set balance close to i128::MAX
     let max = i128::MAX;
     let half_max = max / 2;
-    client.set_balance(&user, &half_max, &half_max);
+    client.set_balance(&user, &half_max, &half_max, &None, &None);
 
     // get_total should overflow since half_max + half_max > i128::MAX (roughly)
     // Actually half_max + half_max = max - 1, which is fine
     // Let's set values that will definitely overflow
-    client.set_balance(&user, &max, &1);
+    client.set_balance(&user, &max, &1, &None, &None);
 
     let result = client.try_get_total(&user);
     assert_eq!(result, Err(Ok(BalanceLedgerError::Overflow)));
@@ -278,10 +284,10 @@ fn apply_delta_prevents_overflow_on_withdrawable() {
 
     // Set withdrawable close to max
     let large_value = i128::MAX - 100;
-    client.set_balance(&user, &large_value, &0);
+    client.set_balance(&user, &large_value, &0, &None, &None);
 
     // Adding more should overflow
-    let result = client.try_apply_delta(&user, &101, &0);
+    let result = client.try_apply_delta(&user, &101, &0, &None, &None);
     assert_eq!(result, Err(Ok(BalanceLedgerError::Overflow)));
 }
 
@@ -298,9 +304,9 @@ fn apply_delta_prevents_overflow_on_locked() {
     client.initialize(&backend);
 
     let large_value = i128::MAX - 50;
-    client.set_balance(&user, &0, &large_value);
+    client.set_balance(&user, &0, &large_value, &None, &None);
 
-    let result = client.try_apply_delta(&user, &0, &51);
+    let result = client.try_apply_delta(&user, &0, &51, &None, &None);
     assert_eq!(result, Err(Ok(BalanceLedgerError::Overflow)));
 }
 
@@ -320,7 +326,7 @@ fn set_balance_without_backend_auth_fails() {
     client.initialize(&backend);
 
     // Don't mock auths - should fail
-    client.set_balance(&user, &1000, &500);
+    client.set_balance(&user, &1000, &500, &None, &None);
 }
 
 #[test]
@@ -335,7 +341,7 @@ fn apply_delta_without_backend_auth_fails() {
     client.initialize(&backend);
 
     // Don't mock auths - should fail
-    client.apply_delta(&user, &100, &-50);
+    client.apply_delta(&user, &100, &-50, &None, &None);
 }
 
 #[test]
@@ -398,7 +404,7 @@ fn lock_funds_with_zero_amount_fails() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &1000, &0);
+    client.set_balance(&user, &1000, &0, &None, &None);
 
     let result = client.try_lock_funds(&user, &0);
     assert_eq!(result, Err(Ok(BalanceLedgerError::InvalidAmount)));
@@ -415,7 +421,7 @@ fn unlock_funds_with_zero_amount_fails() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &0, &1000);
+    client.set_balance(&user, &0, &1000, &None, &None);
 
     let result = client.try_unlock_funds(&user, &0);
     assert_eq!(result, Err(Ok(BalanceLedgerError::InvalidAmount)));
@@ -432,7 +438,7 @@ fn lock_funds_with_negative_amount_fails() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &1000, &0);
+    client.set_balance(&user, &1000, &0, &None, &None);
 
     let result = client.try_lock_funds(&user, &-100);
     assert_eq!(result, Err(Ok(BalanceLedgerError::InvalidAmount)));
@@ -449,7 +455,7 @@ fn unlock_funds_with_negative_amount_fails() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &0, &1000);
+    client.set_balance(&user, &0, &1000, &None, &None);
 
     let result = client.try_unlock_funds(&user, &-100);
     assert_eq!(result, Err(Ok(BalanceLedgerError::InvalidAmount)));
@@ -467,7 +473,7 @@ fn set_balance_with_negative_locked_fails() {
 
     client.initialize(&backend);
 
-    let result = client.try_set_balance(&user, &100, &-50);
+    let result = client.try_set_balance(&user, &100, &-50, &None, &None);
     assert_eq!(result, Err(Ok(BalanceLedgerError::InvalidAmount)));
 }
 
@@ -562,9 +568,9 @@ fn apply_delta_with_zero_values_succeeds() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &500, &200);
+    client.set_balance(&user, &500, &200, &None, &None);
 
-    let updated = client.apply_delta(&user, &0, &0);
+    let updated = client.apply_delta(&user, &0, &0, &None, &None);
     assert_eq!(updated.withdrawable, 500);
     assert_eq!(updated.locked, 200);
 }
@@ -584,9 +590,9 @@ fn multiple_users_balances_isolated() {
     client.initialize(&backend);
 
     // Set different balances for each user
-    client.set_balance(&user1, &1000, &500);
-    client.set_balance(&user2, &2000, &1000);
-    client.set_balance(&user3, &0, &0);
+    client.set_balance(&user1, &1000, &500, &None, &None);
+    client.set_balance(&user2, &2000, &1000, &None, &None);
+    client.set_balance(&user3, &0, &0, &None, &None);
 
     // Verify isolation
     let balance1 = client.get_balance(&user1);
@@ -649,7 +655,7 @@ fn lock_exact_withdrawable_amount_succeeds() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &500, &0);
+    client.set_balance(&user, &500, &0, &None, &None);
 
     // Lock exactly the withdrawable amount
     let result = client.lock_funds(&user, &500);
@@ -668,10 +674,1042 @@ fn unlock_exact_locked_amount_succeeds() {
     let client = BalanceLedgerContractClient::new(&env, &contract_id);
 
     client.initialize(&backend);
-    client.set_balance(&user, &0, &500);
+    client.set_balance(&user, &0, &500, &None, &None);
 
     // Unlock exactly the locked amount
     let result = client.unlock_funds(&user, &500);
     assert_eq!(result.withdrawable, 500);
     assert_eq!(result.locked, 0);
 }
+
+#[test]
+fn apply_deltas_batch_updates_all_users() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_balance(&alice, &100, &50, &None, &None);
+
+    let updates = vec![
+        &env,
+        (alice.clone(), 80i128, -50i128),
+        (bob.clone(), 200i128, 0i128),
+        (alice.clone(), -30i128, 10i128),
+    ];
+    let results = client.apply_deltas_batch(&updates);
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(
+        results.get(2).unwrap(),
+        UserBalance {
+            withdrawable: 150,
+            locked: 10,
+            bonus: 0,
+            locked_bonus: 0,
+            bonus_expires_at: 0,
+        }
+    );
+    assert_eq!(client.get_withdrawable(&alice), 150);
+    assert_eq!(client.get_locked(&alice), 10);
+    assert_eq!(client.get_withdrawable(&bob), 200);
+}
+
+#[test]
+fn apply_deltas_batch_is_all_or_nothing() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_balance(&alice, &100, &0, &None, &None);
+
+    let updates = vec![
+        &env,
+        (alice.clone(), 50i128, 0i128),
+        (bob.clone(), -1i128, 0i128),
+    ];
+    assert_eq!(
+        client.try_apply_deltas_batch(&updates),
+        Err(Ok(BalanceLedgerError::InsufficientWithdrawable))
+    );
+    assert_eq!(client.get_withdrawable(&alice), 100);
+    assert_eq!(client.get_withdrawable(&bob), 0);
+}
+
+#[test]
+fn apply_deltas_batch_respects_max_batch_size() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    assert_eq!(client.get_max_batch_size(), DEFAULT_MAX_BATCH_SIZE);
+    assert_eq!(
+        client.try_set_max_batch_size(&backend, &0),
+        Err(Ok(BalanceLedgerError::InvalidConfig))
+    );
+
+    client.set_max_batch_size(&backend, &2);
+    let updates = vec![
+        &env,
+        (user.clone(), 1i128, 0i128),
+        (user.clone(), 1i128, 0i128),
+        (user.clone(), 1i128, 0i128),
+    ];
+    assert_eq!(
+        client.try_apply_deltas_batch(&updates),
+        Err(Ok(BalanceLedgerError::BatchTooLarge))
+    );
+}
+
+#[test]
+#[should_panic]
+fn apply_deltas_batch_without_backend_auth_fails() {
+    let env = Env::default();
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.apply_deltas_batch(&vec![&env, (user, 100i128, 0i128)]);
+}
+
+#[test]
+fn token_balances_are_tracked_separately() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let usdc = Address::generate(&env);
+    let xlm = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_token_allowed(&backend, &usdc, &true);
+    client.set_token_allowed(&backend, &xlm, &true);
+
+    client.set_balance_for_token(&user, &usdc, &1_000, &0, &None, &None);
+    client.apply_delta_for_token(&user, &xlm, &300, &0, &None, &None);
+    client.lock_funds_for_token(&user, &usdc, &400);
+    client.unlock_funds_for_token(&user, &usdc, &100);
+
+    assert_eq!(client.get_withdrawable_for_token(&user, &usdc), 700);
+    assert_eq!(client.get_locked_for_token(&user, &usdc), 300);
+    assert_eq!(client.get_total_for_token(&user, &usdc), 1_000);
+    assert_eq!(
+        client.get_balance_for_token(&user, &xlm),
+        UserBalance {
+            withdrawable: 300,
+            locked: 0,
+            bonus: 0,
+            locked_bonus: 0,
+            bonus_expires_at: 0,
+        }
+    );
+
+    // The base unit is untouched by token updates
+    assert_eq!(client.get_total(&user), 0);
+}
+
+#[test]
+fn token_updates_require_allowlisted_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    assert!(!client.is_token_allowed(&token));
+    assert_eq!(
+        client.try_apply_delta_for_token(&user, &token, &100, &0, &None, &None),
+        Err(Ok(BalanceLedgerError::TokenNotAllowed))
+    );
+
+    client.set_token_allowed(&backend, &token, &true);
+    client.apply_delta_for_token(&user, &token, &100, &0, &None, &None);
+
+    client.set_token_allowed(&backend, &token, &false);
+    assert_eq!(
+        client.try_lock_funds_for_token(&user, &token, &50),
+        Err(Ok(BalanceLedgerError::TokenNotAllowed))
+    );
+    assert_eq!(
+        client.try_apply_deltas_batch_for_token(&token, &vec![&env, (user.clone(), 1i128, 0i128)]),
+        Err(Ok(BalanceLedgerError::TokenNotAllowed))
+    );
+    assert_eq!(client.get_withdrawable_for_token(&user, &token), 100);
+}
+
+#[test]
+fn signer_rotation_requires_acceptance_by_new_signer() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let new_backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    assert_eq!(
+        client.try_accept_signer_role(),
+        Err(Ok(BalanceLedgerError::NoPendingSigner))
+    );
+
+    let pending = client.propose_new_signer(&backend, &new_backend);
+    assert_eq!(pending.signer, new_backend);
+    assert_eq!(client.get_backend_signer(), Some(backend.clone()));
+
+    assert_eq!(client.accept_signer_role(), new_backend);
+    assert_eq!(client.get_backend_signer(), Some(new_backend.clone()));
+    assert_eq!(client.get_pending_signer(), None);
+
+    // The administrative roles move with the signer
+    for role in [Role::Admin, Role::Pauser, Role::Treasurer] {
+        assert!(!client.has_role(&role, &backend));
+        assert!(client.has_role(&role, &new_backend));
+    }
+    assert_eq!(
+        client.try_set_rotation_delay(&backend, &3_600),
+        Err(Ok(BalanceLedgerError::Unauthorized))
+    );
+
+    client.set_balance(&user, &10, &0, &None, &None);
+    assert_eq!(
+        env.auths().first().map(|(address, _)| address.clone()),
+        Some(new_backend)
+    );
+}
+
+#[test]
+fn signer_rotation_honours_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let new_backend = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_rotation_delay(&backend, &3_600);
+    client.propose_new_signer(&backend, &new_backend);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 3_599;
+    });
+    assert_eq!(
+        client.try_accept_signer_role(),
+        Err(Ok(BalanceLedgerError::TimelockActive))
+    );
+
+    // The current signer can still abort the handover
+    client.cancel_signer_rotation(&backend);
+    env.ledger().with_mut(|li| {
+        li.timestamp += 1;
+    });
+    assert_eq!(
+        client.try_accept_signer_role(),
+        Err(Ok(BalanceLedgerError::NoPendingSigner))
+    );
+    assert_eq!(client.get_backend_signer(), Some(backend));
+}
+
+#[test]
+#[should_panic]
+fn propose_new_signer_without_backend_auth_fails() {
+    let env = Env::default();
+    let backend = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.propose_new_signer(&backend, &Address::generate(&env));
+}
+
+#[test]
+fn pause_blocks_balance_mutations_but_not_getters() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_balance(&user, &500, &0, &None, &None);
+
+    client.pause(&backend);
+    assert!(client.is_paused());
+    assert_eq!(
+        client.try_apply_delta(&user, &100, &0, &None, &None),
+        Err(Ok(BalanceLedgerError::Paused))
+    );
+    assert_eq!(
+        client.try_lock_funds(&user, &100),
+        Err(Ok(BalanceLedgerError::Paused))
+    );
+    assert_eq!(
+        client.try_apply_deltas_batch(&vec![&env, (user.clone(), 1i128, 0i128)]),
+        Err(Ok(BalanceLedgerError::Paused))
+    );
+    assert_eq!(client.get_withdrawable(&user), 500);
+
+    client.unpause(&backend);
+    client.lock_funds(&user, &100);
+    assert_eq!(client.get_locked(&user), 100);
+}
+
+fn setup_withdrawals(
+    env: &Env,
+) -> (
+    BalanceLedgerContractClient<'_>,
+    token::Client<'_>,
+    Address,
+    Address,
+) {
+    env.mock_all_auths();
+
+    let backend = Address::generate(env);
+    let treasury = Address::generate(env);
+    let user = Address::generate(env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(env, &contract_id);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let token = token::Client::new(env, &token_id);
+    token::StellarAssetClient::new(env, &token_id).mint(&treasury, &10_000);
+    token.approve(&treasury, &contract_id, &10_000, &1_000);
+
+    client.initialize(&backend);
+    client.set_token_allowed(&backend, &token_id, &true);
+    client.set_withdrawal_config(&backend, &token_id, &treasury);
+    client.set_balance_for_token(&user, &token_id, &1_000, &0, &None, &None);
+
+    (client, token, treasury, user)
+}
+
+#[test]
+fn withdrawal_transfers_tokens_and_debits_ledger() {
+    let env = Env::default();
+    let (client, token, treasury, user) = setup_withdrawals(&env);
+    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+
+    assert_eq!(
+        client.try_execute_withdrawal(&operation_hash, &user, &400),
+        Err(Ok(BalanceLedgerError::NoPendingWithdrawal))
+    );
+
+    client.request_withdrawal(&user, &400);
+    let updated = client.execute_withdrawal(&operation_hash, &user, &400);
+
+    assert_eq!(updated.withdrawable, 600);
+    assert_eq!(token.balance(&user), 400);
+    assert_eq!(token.balance(&treasury), 9_600);
+    assert_eq!(client.get_pending_withdrawal(&user), None);
+}
+
+#[test]
+fn withdrawal_execution_is_idempotent() {
+    let env = Env::default();
+    let (client, token, _treasury, user) = setup_withdrawals(&env);
+    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+
+    client.request_withdrawal(&user, &300);
+    client.execute_withdrawal(&operation_hash, &user, &100);
+    assert_eq!(client.get_pending_withdrawal(&user).unwrap().amount, 200);
+
+    assert_eq!(
+        client.try_execute_withdrawal(&operation_hash, &user, &100),
+        Err(Ok(BalanceLedgerError::DuplicateOperation))
+    );
+    assert_eq!(token.balance(&user), 100);
+    assert_eq!(
+        client.get_withdrawable_for_token(&user, &token.address),
+        900
+    );
+}
+
+#[test]
+fn withdrawal_requests_are_bounded_by_withdrawable() {
+    let env = Env::default();
+    let (client, _token, _treasury, user) = setup_withdrawals(&env);
+
+    client.request_withdrawal(&user, &800);
+    assert_eq!(
+        client.try_request_withdrawal(&user, &201),
+        Err(Ok(BalanceLedgerError::InsufficientWithdrawable))
+    );
+    assert_eq!(
+        client.try_execute_withdrawal(&BytesN::from_array(&env, &[1u8; 32]), &user, &801),
+        Err(Ok(BalanceLedgerError::InvalidAmount))
+    );
+
+    client.cancel_withdrawal(&user);
+    assert_eq!(
+        client.try_cancel_withdrawal(&user),
+        Err(Ok(BalanceLedgerError::NoPendingWithdrawal))
+    );
+}
+
+#[test]
+fn scoped_roles_gate_administrative_entrypoints() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let pauser = Address::generate(&env);
+    let treasurer = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    assert!(client.has_role(&Role::Admin, &backend));
+    assert!(!client.has_role(&Role::Pauser, &pauser));
+
+    client.grant_role(&backend, &Role::Pauser, &pauser);
+    client.grant_role(&backend, &Role::Treasurer, &treasurer);
+
+    // A pauser can stop the ledger but not lift the pause or reconfigure it
+    client.pause(&pauser);
+    assert_eq!(
+        client.try_unpause(&pauser),
+        Err(Ok(BalanceLedgerError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_set_max_batch_size(&pauser, &10),
+        Err(Ok(BalanceLedgerError::Unauthorized))
+    );
+    client.unpause(&backend);
+
+    let token = Address::generate(&env);
+    client.set_token_allowed(&treasurer, &token, &true);
+    assert!(client.is_token_allowed(&token));
+    assert_eq!(
+        client.try_grant_role(&treasurer, &Role::Admin, &treasurer),
+        Err(Ok(BalanceLedgerError::Unauthorized))
+    );
+
+    client.revoke_role(&backend, &Role::Treasurer, &treasurer);
+    assert_eq!(
+        client.try_set_token_allowed(&treasurer, &token, &false),
+        Err(Ok(BalanceLedgerError::Unauthorized))
+    );
+
+    client.renounce_role(&pauser, &Role::Pauser);
+    assert!(!client.has_role(&Role::Pauser, &pauser));
+}
+
+#[test]
+fn role_admin_can_be_delegated() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let treasurer = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    assert_eq!(client.get_role_admin(&Role::Operator), Role::Admin);
+
+    client.set_role_admin(&backend, &Role::Operator, &Role::Treasurer);
+    client.grant_role(&backend, &Role::Treasurer, &treasurer);
+    client.renounce_role(&backend, &Role::Treasurer);
+
+    // Operators are now managed by treasurers, no longer by admins
+    client.grant_role(&treasurer, &Role::Operator, &operator);
+    assert!(client.has_role(&Role::Operator, &operator));
+    assert_eq!(
+        client.try_revoke_role(&backend, &Role::Operator, &operator),
+        Err(Ok(BalanceLedgerError::Unauthorized))
+    );
+}
+
+#[test]
+fn lock_funds_enforces_wager_and_loss_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_balance(&user, &10_000, &0, &None, &None);
+    assert_eq!(
+        client.try_set_limits(
+            &user,
+            &GamingLimits {
+                daily_wager: Some(0),
+                ..Default::default()
+            }
+        ),
+        Err(Ok(BalanceLedgerError::InvalidConfig))
+    );
+    client.set_limits(
+        &user,
+        &GamingLimits {
+            daily_wager: Some(1_000),
+            weekly_loss: Some(1_500),
+            ..Default::default()
+        },
+    );
+
+    client.lock_funds(&user, &600);
+    assert_eq!(
+        client.try_lock_funds(&user, &401),
+        Err(Ok(BalanceLedgerError::LimitExceeded))
+    );
+    client.lock_funds(&user, &400);
+    client.record_metrics(&user, &1_000, &0, &1_000);
+    assert_eq!(
+        client.get_daily_usage(&user),
+        LimitUsage {
+            wagered: 1_000,
+            won: 0,
+            lost: 1_000,
+        }
+    );
+
+    // The next day resets the wager cap, but the week's losses still count
+    env.ledger().with_mut(|l| l.timestamp += DAY_SECONDS);
+    assert_eq!(client.get_daily_usage(&user), LimitUsage::default());
+    assert_eq!(
+        client.try_lock_funds(&user, &501),
+        Err(Ok(BalanceLedgerError::LimitExceeded))
+    );
+    client.record_metrics(&user, &0, &300, &0);
+    client.lock_funds(&user, &800);
+    assert_eq!(client.get_weekly_usage(&user).wagered, 1_800);
+    assert_eq!(client.get_locked(&user), 1_800);
+
+    // Caps are in the base unit, so other tokens cannot be locked around them
+    let usdc = Address::generate(&env);
+    client.set_token_allowed(&backend, &usdc, &true);
+    client.set_balance_for_token(&user, &usdc, &1_000, &0, &None, &None);
+    assert_eq!(
+        client.try_lock_funds_for_token(&user, &usdc, &100),
+        Err(Ok(BalanceLedgerError::LimitExceeded))
+    );
+    assert_eq!(client.get_locked_for_token(&user, &usdc), 0);
+}
+
+#[test]
+fn only_limit_reporters_record_external_wagers() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let reporter = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_limits_for_user(
+        &user,
+        &GamingLimits {
+            daily_wager: Some(500),
+            ..Default::default()
+        },
+    );
+    assert_eq!(client.get_limits(&user).daily_wager, Some(500));
+    assert_eq!(
+        client.try_record_wager(&reporter, &user, &100),
+        Err(Ok(BalanceLedgerError::Unauthorized))
+    );
+
+    client.set_limit_reporter(&backend, &reporter, &true);
+    assert!(client.is_limit_reporter(&reporter));
+    client.record_wager(&reporter, &user, &500);
+    assert_eq!(
+        client.try_record_wager(&reporter, &user, &1),
+        Err(Ok(BalanceLedgerError::LimitExceeded))
+    );
+}
+
+#[test]
+fn raised_limits_wait_out_the_cooling_off_delay() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_balance(&user, &10_000, &0, &None, &None);
+    let strict = GamingLimits {
+        daily_wager: Some(500),
+        weekly_loss: Some(2_000),
+        ..Default::default()
+    };
+    client.set_limits(&user, &strict);
+    assert_eq!(client.get_limits(&user), strict);
+    assert_eq!(client.get_pending_limits(&user), None);
+
+    // Raising the wager cap and dropping the loss cap waits a day, while
+    // the new daily loss cap applies at once
+    let loose = GamingLimits {
+        daily_wager: Some(2_000),
+        daily_loss: Some(1_000),
+        ..Default::default()
+    };
+    client.set_limits(&user, &loose);
+    assert_eq!(
+        client.get_limits(&user),
+        GamingLimits {
+            daily_loss: Some(1_000),
+            ..strict.clone()
+        }
+    );
+    let pending = client.get_pending_limits(&user).unwrap();
+    assert_eq!(pending.limits, loose);
+    assert_eq!(
+        pending.effective_at,
+        env.ledger().timestamp() + LIMIT_INCREASE_DELAY
+    );
+    assert_eq!(
+        client.try_lock_funds(&user, &501),
+        Err(Ok(BalanceLedgerError::LimitExceeded))
+    );
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += LIMIT_INCREASE_DELAY);
+    assert_eq!(client.get_limits(&user), loose);
+    assert_eq!(client.get_pending_limits(&user), None);
+    client.lock_funds(&user, &900);
+
+    // Lowering a cap again applies at once and drops nothing else
+    client.set_limits_for_user(
+        &user,
+        &GamingLimits {
+            daily_wager: Some(1_000),
+            daily_loss: Some(1_000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(client.get_limits(&user).daily_wager, Some(1_000));
+    assert_eq!(client.get_pending_limits(&user), None);
+    assert_eq!(
+        client.try_lock_funds(&user, &101),
+        Err(Ok(BalanceLedgerError::LimitExceeded))
+    );
+}
+
+#[test]
+fn self_exclusion_can_only_be_extended_until_it_runs_out() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+    client.initialize(&backend);
+
+    assert!(!client.is_self_excluded(&user));
+    assert_eq!(
+        client.try_self_exclude(&user, &0),
+        Err(Ok(BalanceLedgerError::InvalidConfig))
+    );
+    client.self_exclude(&user, &WEEK_SECONDS);
+    assert!(client.is_self_excluded(&user));
+
+    // A shorter period cannot cut the running one short
+    env.ledger().set_timestamp(1_000 + DAY_SECONDS);
+    assert_eq!(
+        client.try_self_exclude(&user, &DAY_SECONDS),
+        Err(Ok(BalanceLedgerError::InvalidConfig))
+    );
+    let extended = client.self_exclude(&user, &WEEK_SECONDS);
+    assert_eq!(
+        extended,
+        SelfExclusion {
+            started_at: 1_000,
+            ends_at: 1_000 + DAY_SECONDS + WEEK_SECONDS,
+        }
+    );
+
+    env.ledger().set_timestamp(extended.ends_at);
+    assert!(!client.is_self_excluded(&user));
+    assert_eq!(client.get_self_exclusion(&user), Some(extended));
+}
+
+#[test]
+fn bonus_is_wagered_first_and_swept_after_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_balance(&user, &1_000, &0, &None, &None);
+    assert_eq!(
+        client.try_credit_bonus(&user, &300, &env.ledger().timestamp()),
+        Err(Ok(BalanceLedgerError::InvalidConfig))
+    );
+    let expires_at = env.ledger().timestamp() + 3_600;
+    client.credit_bonus(&user, &300, &expires_at);
+    assert_eq!(client.get_bonus(&user), 300);
+
+    // The stake takes all 300 bonus and 100 real money
+    let balance = client.lock_funds(&user, &400);
+    assert_eq!(balance.withdrawable, 900);
+    assert_eq!(balance.locked, 400);
+    assert_eq!(balance.bonus, 0);
+    assert_eq!(balance.locked_bonus, 300);
+
+    // A refund returns the bonus part to the bonus bucket
+    let balance = client.unlock_funds(&user, &200);
+    assert_eq!(balance.withdrawable, 900);
+    assert_eq!(balance.bonus, 200);
+    assert_eq!(balance.locked_bonus, 100);
+
+    // Settling the rest consumes the locked bonus first
+    let balance = client.apply_delta(&user, &0, &-150, &None, &None);
+    assert_eq!(balance.locked, 50);
+    assert_eq!(balance.locked_bonus, 0);
+
+    assert_eq!(
+        client.try_sweep_expired_bonus(&user),
+        Err(Ok(BalanceLedgerError::NoExpiredBonus))
+    );
+    env.ledger().with_mut(|l| l.timestamp = expires_at);
+    assert_eq!(client.get_bonus(&user), 0);
+
+    // Expired bonus is not wagered
+    let balance = client.lock_funds(&user, &100);
+    assert_eq!(balance.withdrawable, 800);
+    assert_eq!(balance.bonus, 200);
+
+    assert_eq!(client.sweep_expired_bonus(&user), 200);
+    let balance = client.get_balance(&user);
+    assert_eq!(balance.bonus, 0);
+    assert_eq!(balance.withdrawable, 800);
+    assert_eq!(
+        client.try_sweep_expired_bonus(&user),
+        Err(Ok(BalanceLedgerError::NoExpiredBonus))
+    );
+}
+
+#[test]
+fn settlement_refund_returns_locked_bonus_to_bonus() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_balance(&user, &1_000, &0, &None, &None);
+    client.credit_bonus(&user, &300, &(env.ledger().timestamp() + 3_600));
+    client.lock_funds(&user, &400);
+
+    // A draw hands the stake back; the bonus part stays bonus
+    let balance = client.apply_delta(&user, &400, &-400, &None, &None);
+    assert_eq!(balance.withdrawable, 1_000);
+    assert_eq!(balance.locked, 0);
+    assert_eq!(balance.bonus, 300);
+    assert_eq!(balance.locked_bonus, 0);
+}
+
+#[test]
+fn journal_records_every_mutation_in_sequence() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    assert_eq!(client.get_latest_seq(), 0);
+    client.set_balance(&alice, &1_000, &0, &None, &None);
+    client.lock_funds(&alice, &400);
+    client.apply_deltas_batch(&vec![
+        &env,
+        (alice.clone(), 100, -400),
+        (bob.clone(), 50, 0),
+    ]);
+    // Rejected mutations leave no entry
+    assert!(client.try_unlock_funds(&alice, &1).is_err());
+    assert_eq!(client.get_latest_seq(), 4);
+
+    let entries = client.get_entries(&0, &10);
+    assert_eq!(entries.len(), 4);
+    let lock = entries.get(1).unwrap();
+    assert_eq!(lock.seq, 2);
+    assert_eq!(lock.user, alice);
+    assert_eq!((lock.delta_w, lock.delta_l), (-400, 400));
+    assert_eq!(lock.reason, Symbol::new(&env, "lock"));
+    assert_eq!(lock.op_hash, None);
+
+    // Replaying the journal reproduces the stored balances
+    let (mut withdrawable, mut locked) = (0, 0);
+    for entry in entries.iter().filter(|entry| entry.user == alice) {
+        withdrawable += entry.delta_w;
+        locked += entry.delta_l;
+    }
+    assert_eq!(withdrawable, client.get_withdrawable(&alice));
+    assert_eq!(locked, client.get_locked(&alice));
+
+    let page = client.get_entries(&3, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().reason, Symbol::new(&env, "batch"));
+    assert_eq!(client.get_entries(&5, &10).len(), 0);
+}
+
+#[test]
+fn operation_hash_rejects_retried_mutations() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+    let set_hash = BytesN::from_array(&env, &[1; 32]);
+    let delta_hash = BytesN::from_array(&env, &[2; 32]);
+
+    client.initialize(&backend);
+    client.set_balance(&user, &1_000, &0, &Some(set_hash.clone()), &None);
+    assert!(client.is_balance_operation_executed(&set_hash));
+    assert_eq!(
+        client.try_set_balance(&user, &5_000, &0, &Some(set_hash.clone()), &None),
+        Err(Ok(BalanceLedgerError::DuplicateOperation))
+    );
+
+    // set_balance and apply_delta share one scope
+    assert_eq!(
+        client.try_apply_delta(&user, &100, &0, &Some(set_hash.clone()), &None),
+        Err(Ok(BalanceLedgerError::DuplicateOperation))
+    );
+    client.apply_delta(&user, &100, &0, &Some(delta_hash.clone()), &Some(60));
+    assert_eq!(client.get_withdrawable(&user), 1_100);
+    let entry = client
+        .get_entries(&client.get_latest_seq(), &1)
+        .get(0)
+        .unwrap();
+    assert_eq!(entry.op_hash, Some(delta_hash.clone()));
+
+    // Once the TTL has passed the hash can be cleaned up and reused
+    assert!(!client.cleanup_balance_operation(&delta_hash));
+    env.ledger().with_mut(|l| l.timestamp += 60);
+    assert!(!client.is_balance_operation_executed(&delta_hash));
+    assert!(client.cleanup_balance_operation(&delta_hash));
+    client.apply_delta(&user, &100, &0, &Some(delta_hash), &None);
+    assert_eq!(client.get_withdrawable(&user), 1_200);
+}
+
+#[test]
+fn referenced_locks_stay_consistent_with_locked() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+    let bet_a = BytesN::from_array(&env, &[1; 32]);
+    let bet_b = BytesN::from_array(&env, &[2; 32]);
+
+    client.initialize(&backend);
+    client.set_balance(&user, &1_000, &0, &None, &None);
+    client.lock_funds_ref(&user, &200, &bet_a);
+    client.lock_funds_ref(&user, &100, &bet_b);
+    let lock = client.lock_funds_ref(&user, &50, &bet_a);
+    assert_eq!(lock.amount, 250);
+    client.lock_funds(&user, &100);
+    assert_eq!(client.get_locked(&user), 450);
+    assert_eq!(client.get_referenced_locked(&user), 350);
+
+    let locks = client.get_locks(&user, &0, &10);
+    assert_eq!(locks.len(), 2);
+    assert_eq!(locks.get(0).unwrap().ref_id, bet_a);
+    assert_eq!(client.get_locks(&user, &1, &10).len(), 1);
+
+    // Only the unreferenced 100 can be released without a reference
+    assert_eq!(
+        client.try_unlock_funds(&user, &101),
+        Err(Ok(BalanceLedgerError::InsufficientLocked))
+    );
+    assert_eq!(
+        client.try_apply_delta(&user, &0, &-101, &None, &None),
+        Err(Ok(BalanceLedgerError::InsufficientLocked))
+    );
+
+    let balance = client.unlock_funds_ref(&user, &bet_b);
+    assert_eq!((balance.withdrawable, balance.locked), (650, 350));
+    assert_eq!(
+        client.try_unlock_funds_ref(&user, &bet_b),
+        Err(Ok(BalanceLedgerError::LockNotFound))
+    );
+
+    // Settling spends the stake and credits the payout
+    let balance = client.settle_funds_ref(&user, &bet_a, &500);
+    assert_eq!((balance.withdrawable, balance.locked), (1_150, 100));
+    assert_eq!(client.get_referenced_locked(&user), 0);
+    assert_eq!(client.get_lock(&user, &bet_a), None);
+    assert_eq!(client.get_locks(&user, &0, &10).len(), 0);
+}
+
+#[test]
+fn platform_metrics_track_every_mutation() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    assert_eq!(client.get_platform_metrics(), PlatformMetrics::default());
+
+    client.set_balance(&alice, &1_000, &0, &None, &None);
+    client.apply_delta(&bob, &500, &0, &None, &None);
+    client.lock_funds(&alice, &300);
+    client.credit_bonus(&bob, &50, &(env.ledger().timestamp() + 60));
+    client.record_metrics(&alice, &300, &0, &300);
+
+    let metrics = client.get_platform_metrics();
+    assert_eq!(metrics.total_withdrawable, 1_200);
+    assert_eq!(metrics.total_locked, 300);
+    assert_eq!(metrics.total_bonus, 50);
+    assert_eq!(metrics.active_users, 2);
+    assert_eq!(
+        (metrics.total_staked, metrics.total_won, metrics.total_lost),
+        (300, 0, 300)
+    );
+
+    // Emptying a balance (here through a batch) drops the user from the count
+    client.apply_deltas_batch(&vec![&env, (alice.clone(), -700, -300)]);
+    let metrics = client.get_platform_metrics();
+    assert_eq!(metrics.total_withdrawable, 500);
+    assert_eq!(metrics.total_locked, 0);
+    assert_eq!(metrics.active_users, 1);
+
+    // Token balances are not part of the base-unit totals
+    let token = Address::generate(&env);
+    client.set_token_allowed(&backend, &token, &true);
+    client.set_balance_for_token(&alice, &token, &900, &0, &None, &None);
+    assert_eq!(client.get_platform_metrics(), metrics);
+}
+
+#[test]
+fn interest_accrues_on_withdrawable_for_enrolled_users() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let saver = Address::generate(&env);
+    let other = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+    let year = 365 * 86_400;
+
+    client.initialize(&backend);
+    assert_eq!(
+        client.try_set_interest_apr(&backend, &(MAX_INTEREST_APR_BPS + 1)),
+        Err(Ok(BalanceLedgerError::InvalidConfig))
+    );
+    client.set_interest_apr(&backend, &1_000);
+    client.set_balance(&saver, &10_000, &0, &None, &None);
+    client.set_balance(&other, &10_000, &0, &None, &None);
+    client.enroll_interest(&saver);
+    assert_eq!(
+        client.try_enroll_interest(&saver),
+        Err(Ok(BalanceLedgerError::DuplicateOperation))
+    );
+
+    // Half a year at 10% on 10_000, then the balance doubles
+    env.ledger().with_mut(|l| l.timestamp += year / 2);
+    assert_eq!(client.accrued_interest(&saver), 500);
+    client.apply_delta(&saver, &10_000, &0, &None, &None);
+    env.ledger().with_mut(|l| l.timestamp += year / 2);
+    assert_eq!(client.accrued_interest(&saver), 1_500);
+
+    // A rate change only affects interest from then on
+    client.set_interest_apr(&backend, &0);
+    env.ledger().with_mut(|l| l.timestamp += year);
+    assert_eq!(client.accrued_interest(&saver), 1_500);
+
+    assert_eq!(client.credit_interest(&saver), 1_500);
+    assert_eq!(client.get_withdrawable(&saver), 21_500);
+    assert_eq!(client.accrued_interest(&saver), 0);
+    assert_eq!(client.accrued_interest(&other), 0);
+    assert_eq!(
+        client.try_credit_interest(&other),
+        Err(Ok(BalanceLedgerError::NotEnrolled))
+    );
+}
+
+#[test]
+fn balances_stay_alive_through_bumps() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend = Address::generate(&env);
+    let user = Address::generate(&env);
+    let contract_id = env.register(BalanceLedgerContract, ());
+    let client = BalanceLedgerContractClient::new(&env, &contract_id);
+
+    client.initialize(&backend);
+    client.set_balance(&user, &1_000, &0, &None, &None);
+
+    // Base-unit balances are keyed by the ledger's own address
+    let key = DataKey::Balance(user.clone(), contract_id.clone());
+    let ttl = || env.as_contract(&contract_id, || env.storage().persistent().get_ttl(&key));
+    assert_eq!(ttl(), common::TTL_EXTEND_TO);
+
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += 2 * common::DAY_IN_LEDGERS);
+    assert_eq!(ttl(), common::TTL_EXTEND_TO - 2 * common::DAY_IN_LEDGERS);
+
+    let missing = DataKey::Balance(Address::generate(&env), user.clone());
+    assert_eq!(
+        client.bump_storage(&vec![&env, key.into_val(&env), missing.into_val(&env)]),
+        1
+    );
+    assert_eq!(ttl(), common::TTL_EXTEND_TO);
+}
@@ -0,0 +1,458 @@
+#![no_std]
+
+use common::{init_role, require_role, ContractError, Role};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, token, Address, BytesN, Env, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Fixed-point scale of `get_share_price`: 1 token per share reads as
+/// `PRICE_SCALE`.
+pub const PRICE_SCALE: i128 = 10_000_000;
+
+/// Shares queued for withdrawal. They keep absorbing house PnL until the
+/// withdrawal is completed.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingExit {
+    pub shares: i128,
+    pub available_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Token,
+    ExitCooldown,
+    Hook(Address),
+    /// Tokens backing the shares; only deposits, withdrawals and hooks move
+    /// it, so tokens sent directly cannot skew the share price
+    Bankroll,
+    TotalShares,
+    Shares(Address),
+    PendingExit(Address),
+}
+
+#[contract]
+pub struct BankrollContract;
+
+#[contractimpl]
+impl BankrollContract {
+    /// `token` is what liquidity is provided and payouts are made in.
+    /// Providers wait `exit_cooldown` seconds between requesting and
+    /// completing a withdrawal.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        token: Address,
+        exit_cooldown: u64,
+    ) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::Token) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        storage.set(&DataKey::Token, &token);
+        storage.set(&DataKey::ExitCooldown, &exit_cooldown);
+        init_role(&env, Role::Admin, &admin);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::grant_role(&env, &caller, role, &account)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::revoke_role(&env, &caller, role, &account)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), ContractError> {
+        common::renounce_role(&env, &account, role)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn set_exit_cooldown(
+        env: Env,
+        admin: Address,
+        exit_cooldown: u64,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::ExitCooldown, &exit_cooldown);
+        Ok(())
+    }
+
+    pub fn get_exit_cooldown(env: Env) -> u64 {
+        get_exit_cooldown(&env)
+    }
+
+    /// Allow a platform contract (betting, settlement) to draw payouts from
+    /// and return losses to the bankroll.
+    pub fn set_hook(
+        env: Env,
+        admin: Address,
+        hook: Address,
+        allowed: bool,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let key = DataKey::Hook(hook);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_hook(env: Env, hook: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Hook(hook))
+    }
+
+    /// Provide `amount` of liquidity for shares at the current share price.
+    /// Returns the shares minted.
+    pub fn deposit(env: Env, provider: Address, amount: i128) -> Result<i128, ContractError> {
+        provider.require_auth();
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        let bankroll = get_bankroll(&env);
+        let total_shares = get_total_shares(&env);
+        let shares = if total_shares == 0 {
+            amount
+        } else if bankroll == 0 {
+            // Every share was wiped out by house losses; new liquidity
+            // would be diluted into worthless shares
+            return Err(ContractError::InvalidStatus);
+        } else {
+            mul_div(amount, total_shares, bankroll)?
+        };
+        if shares <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        token::Client::new(&env, &get_token(&env)?).transfer(
+            &provider,
+            env.current_contract_address(),
+            &amount,
+        );
+
+        set_bankroll(&env, checked_add(bankroll, amount)?);
+        set_total_shares(&env, checked_add(total_shares, shares)?);
+        let provider_shares = checked_add(get_shares(&env, &provider), shares)?;
+        set_shares(&env, &provider, provider_shares);
+
+        publish_liquidity_event(&env, "liquidity_added", &provider, amount, shares);
+
+        Ok(shares)
+    }
+
+    /// Queue `shares` for withdrawal; they can be redeemed with `withdraw`
+    /// once the exit cooldown has passed. Queuing more shares restarts the
+    /// cooldown for the whole pending amount.
+    pub fn request_withdrawal(
+        env: Env,
+        provider: Address,
+        shares: i128,
+    ) -> Result<PendingExit, ContractError> {
+        provider.require_auth();
+        let provider_shares = get_shares(&env, &provider);
+        if shares <= 0 || shares > provider_shares {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        let pending_shares = get_pending_exit(&env, &provider).map_or(0, |exit| exit.shares);
+        let exit = PendingExit {
+            shares: checked_add(pending_shares, shares)?,
+            available_at: env.ledger().timestamp() + get_exit_cooldown(&env),
+        };
+
+        let key = DataKey::PendingExit(provider.clone());
+        set_shares(&env, &provider, provider_shares - shares);
+        env.storage().persistent().set(&key, &exit);
+        common::bump_persistent(&env, &key);
+
+        publish_exit_event(&env, &provider, &exit);
+
+        Ok(exit)
+    }
+
+    /// Redeem the provider's pending shares at the current share price.
+    /// Returns the tokens paid out.
+    pub fn withdraw(env: Env, provider: Address) -> Result<i128, ContractError> {
+        provider.require_auth();
+        let exit = get_pending_exit(&env, &provider).ok_or(ContractError::InvalidStatus)?;
+        if env.ledger().timestamp() < exit.available_at {
+            return Err(ContractError::CooldownNotMet);
+        }
+
+        let bankroll = get_bankroll(&env);
+        let total_shares = get_total_shares(&env);
+        let amount = mul_div(exit.shares, bankroll, total_shares)?;
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PendingExit(provider.clone()));
+        set_bankroll(&env, bankroll - amount);
+        set_total_shares(&env, total_shares - exit.shares);
+
+        if amount > 0 {
+            token::Client::new(&env, &get_token(&env)?).transfer(
+                &env.current_contract_address(),
+                &provider,
+                &amount,
+            );
+        }
+
+        publish_liquidity_event(&env, "liquidity_removed", &provider, amount, exit.shares);
+
+        Ok(amount)
+    }
+
+    /// Hook for authorized contracts: pay a winner `amount` out of the
+    /// bankroll. The loss is shared by every provider through the share
+    /// price. Returns the remaining bankroll.
+    pub fn draw_payout(
+        env: Env,
+        hook: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        require_hook(&env, &hook)?;
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let bankroll = get_bankroll(&env);
+        if bankroll < amount {
+            return Err(ContractError::InsufficientBalance);
+        }
+
+        let remaining = bankroll - amount;
+        set_bankroll(&env, remaining);
+
+        token::Client::new(&env, &get_token(&env)?).transfer(
+            &env.current_contract_address(),
+            &to,
+            &amount,
+        );
+
+        publish_pnl_event(&env, "payout_drawn", &hook, -amount, remaining);
+
+        Ok(remaining)
+    }
+
+    /// Hook for authorized contracts: hand the house's winnings (lost
+    /// stakes) to the bankroll. Returns the new bankroll. Refused with
+    /// `InvalidStatus` while no shares are outstanding.
+    pub fn return_loss(env: Env, hook: Address, amount: i128) -> Result<i128, ContractError> {
+        require_hook(&env, &hook)?;
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        // Nobody would own it, and the next depositor would take it all
+        if get_total_shares(&env) == 0 {
+            return Err(ContractError::InvalidStatus);
+        }
+
+        token::Client::new(&env, &get_token(&env)?).transfer(
+            &hook,
+            env.current_contract_address(),
+            &amount,
+        );
+
+        let bankroll = checked_add(get_bankroll(&env), amount)?;
+        set_bankroll(&env, bankroll);
+
+        publish_pnl_event(&env, "loss_returned", &hook, amount, bankroll);
+
+        Ok(bankroll)
+    }
+
+    pub fn get_token(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Token)
+    }
+
+    pub fn get_bankroll(env: Env) -> i128 {
+        get_bankroll(&env)
+    }
+
+    /// Shares outstanding, including those pending withdrawal.
+    pub fn get_total_shares(env: Env) -> i128 {
+        get_total_shares(&env)
+    }
+
+    /// Shares `provider` holds outside any pending withdrawal.
+    pub fn get_shares(env: Env, provider: Address) -> i128 {
+        get_shares(&env, &provider)
+    }
+
+    pub fn get_pending_exit(env: Env, provider: Address) -> Option<PendingExit> {
+        get_pending_exit(&env, &provider)
+    }
+
+    /// Tokens per share, scaled by `PRICE_SCALE`. Rises with house profit
+    /// and falls with house losses.
+    pub fn get_share_price(env: Env) -> Result<i128, ContractError> {
+        let total_shares = get_total_shares(&env);
+        if total_shares == 0 {
+            return Ok(PRICE_SCALE);
+        }
+        mul_div(get_bankroll(&env), PRICE_SCALE, total_shares)
+    }
+}
+
+fn require_hook(env: &Env, hook: &Address) -> Result<(), ContractError> {
+    hook.require_auth();
+    if !env.storage().persistent().has(&DataKey::Hook(hook.clone())) {
+        return Err(ContractError::Unauthorized);
+    }
+    Ok(())
+}
+
+fn checked_add(a: i128, b: i128) -> Result<i128, ContractError> {
+    a.checked_add(b).ok_or(ContractError::InvalidAmount)
+}
+
+/// `amount * numerator / denominator`, rounded down.
+fn mul_div(amount: i128, numerator: i128, denominator: i128) -> Result<i128, ContractError> {
+    amount
+        .checked_mul(numerator)
+        .and_then(|scaled| scaled.checked_div(denominator))
+        .ok_or(ContractError::InvalidAmount)
+}
+
+fn get_token(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Token)
+        .ok_or(ContractError::NotInitialized)
+}
+
+fn get_exit_cooldown(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ExitCooldown)
+        .unwrap_or(0)
+}
+
+fn get_bankroll(env: &Env) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Bankroll)
+        .unwrap_or(0)
+}
+
+fn set_bankroll(env: &Env, bankroll: i128) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Bankroll, &bankroll);
+    common::bump_persistent(env, &DataKey::Bankroll);
+}
+
+fn get_total_shares(env: &Env) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TotalShares)
+        .unwrap_or(0)
+}
+
+fn set_total_shares(env: &Env, total_shares: i128) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::TotalShares, &total_shares);
+    common::bump_persistent(env, &DataKey::TotalShares);
+}
+
+fn get_shares(env: &Env, provider: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Shares(provider.clone()))
+        .unwrap_or(0)
+}
+
+fn set_shares(env: &Env, provider: &Address, shares: i128) {
+    let key = DataKey::Shares(provider.clone());
+    if shares == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &shares);
+        common::bump_persistent(env, &key);
+    }
+}
+
+fn get_pending_exit(env: &Env, provider: &Address) -> Option<PendingExit> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PendingExit(provider.clone()))
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_liquidity_event(env: &Env, topic: &str, provider: &Address, amount: i128, shares: i128) {
+    env.events().publish(
+        (Symbol::new(env, topic), provider.clone()),
+        (amount, shares),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_exit_event(env: &Env, provider: &Address, exit: &PendingExit) {
+    env.events().publish(
+        (Symbol::new(env, "withdrawal_requested"), provider.clone()),
+        exit.clone(),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_pnl_event(env: &Env, topic: &str, hook: &Address, delta: i128, bankroll: i128) {
+    env.events()
+        .publish((Symbol::new(env, topic), hook.clone()), (delta, bankroll));
+}
+
+#[cfg(test)]
+mod test;
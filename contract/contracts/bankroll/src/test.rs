@@ -0,0 +1,192 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, Env,
+};
+
+const COOLDOWN: u64 = 3_600;
+
+fn setup_test(
+    env: &Env,
+) -> (
+    BankrollContractClient<'_>,
+    Address,
+    Address,
+    token::Client<'_>,
+    token::StellarAssetClient<'_>,
+) {
+    env.mock_all_auths();
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let admin = Address::generate(env);
+    let hook = Address::generate(env);
+    let minter = token::StellarAssetClient::new(env, &token_id);
+    minter.mint(&hook, &100_000);
+
+    let contract_id = env.register(BankrollContract, ());
+    let client = BankrollContractClient::new(env, &contract_id);
+    client.initialize(&admin, &token_id, &COOLDOWN);
+    client.set_hook(&admin, &hook, &true);
+
+    let token = token::Client::new(env, &token_id);
+
+    (client, admin, hook, token, minter)
+}
+
+fn provider(env: &Env, minter: &token::StellarAssetClient, amount: i128) -> Address {
+    let provider = Address::generate(env);
+    minter.mint(&provider, &amount);
+    provider
+}
+
+#[test]
+fn test_initialize_twice_fails() {
+    let env = Env::default();
+    let (client, admin, _hook, token, _minter) = setup_test(&env);
+
+    assert_eq!(
+        client.try_initialize(&admin, &token.address, &COOLDOWN),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn test_shares_track_house_pnl() {
+    let env = Env::default();
+    let (client, _admin, hook, token, minter) = setup_test(&env);
+    let alice = provider(&env, &minter, 1_000);
+    let bob = provider(&env, &minter, 1_000);
+
+    assert_eq!(client.deposit(&alice, &1_000), 1_000);
+    assert_eq!(client.get_share_price(), PRICE_SCALE);
+
+    // The house wins 500: every share is now worth 1.5 tokens
+    assert_eq!(client.return_loss(&hook, &500), 1_500);
+    assert_eq!(client.get_share_price(), PRICE_SCALE * 3 / 2);
+
+    // Bob buys in at the higher price
+    assert_eq!(client.deposit(&bob, &900), 600);
+    assert_eq!(client.get_total_shares(), 1_600);
+
+    // A payout is a loss shared by both providers
+    let winner = Address::generate(&env);
+    assert_eq!(client.draw_payout(&hook, &winner, &800), 1_600);
+    assert_eq!(token.balance(&winner), 800);
+    assert_eq!(client.get_share_price(), PRICE_SCALE);
+    assert_eq!(
+        client.try_draw_payout(&hook, &winner, &1_601),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+}
+
+#[test]
+fn test_hooks_must_be_registered() {
+    let env = Env::default();
+    let (client, admin, hook, _token, minter) = setup_test(&env);
+    let outsider = provider(&env, &minter, 1_000);
+    client.deposit(&outsider, &500);
+
+    assert_eq!(
+        client.try_draw_payout(&outsider, &outsider, &100),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_return_loss(&outsider, &100),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    client.set_hook(&admin, &hook, &false);
+    assert!(!client.is_hook(&hook));
+    assert_eq!(
+        client.try_return_loss(&hook, &100),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_losses_need_outstanding_shares() {
+    let env = Env::default();
+    let (client, _admin, hook, token, minter) = setup_test(&env);
+
+    assert_eq!(
+        client.try_return_loss(&hook, &500),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    // Once someone holds shares the stake is theirs, not the next depositor's
+    let alice = provider(&env, &minter, 1_000);
+    let bob = provider(&env, &minter, 1_500);
+    client.deposit(&alice, &1_000);
+    client.return_loss(&hook, &500);
+    assert_eq!(client.deposit(&bob, &1_500), 1_000);
+    assert_eq!(token.balance(&client.address), 3_000);
+}
+
+#[test]
+fn test_withdrawal_waits_for_cooldown_and_pays_pro_rata() {
+    let env = Env::default();
+    let (client, _admin, hook, token, minter) = setup_test(&env);
+    let alice = provider(&env, &minter, 1_000);
+    let bob = provider(&env, &minter, 1_000);
+    client.deposit(&alice, &1_000);
+    client.deposit(&bob, &1_000);
+
+    let exit = client.request_withdrawal(&alice, &400);
+    assert_eq!(exit.shares, 400);
+    assert_eq!(exit.available_at, env.ledger().timestamp() + COOLDOWN);
+    assert_eq!(client.get_shares(&alice), 600);
+    assert_eq!(
+        client.try_request_withdrawal(&alice, &601),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_withdraw(&alice),
+        Err(Ok(ContractError::CooldownNotMet))
+    );
+
+    // Pending shares still carry the house's PnL
+    client.return_loss(&hook, &1_000);
+    env.ledger().with_mut(|ledger| ledger.timestamp += COOLDOWN);
+
+    assert_eq!(client.withdraw(&alice), 600);
+    assert_eq!(token.balance(&alice), 600);
+    assert_eq!(client.get_pending_exit(&alice), None);
+    assert_eq!(client.get_total_shares(), 1_600);
+    assert_eq!(client.get_bankroll(), 2_400);
+    assert_eq!(
+        client.try_withdraw(&alice),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+}
+
+#[test]
+fn test_direct_transfers_do_not_move_share_price() {
+    let env = Env::default();
+    let (client, _admin, _hook, _token, minter) = setup_test(&env);
+    let alice = provider(&env, &minter, 1_000);
+    client.deposit(&alice, &1_000);
+
+    minter.mint(&client.address, &5_000);
+    assert_eq!(client.get_bankroll(), 1_000);
+    assert_eq!(client.get_share_price(), PRICE_SCALE);
+}
+
+#[test]
+fn test_deposit_rejected_once_bankroll_is_wiped_out() {
+    let env = Env::default();
+    let (client, _admin, hook, _token, minter) = setup_test(&env);
+    let alice = provider(&env, &minter, 1_000);
+    let bob = provider(&env, &minter, 1_000);
+    client.deposit(&alice, &1_000);
+
+    client.draw_payout(&hook, &Address::generate(&env), &1_000);
+    assert_eq!(client.get_share_price(), 0);
+    assert_eq!(
+        client.try_deposit(&bob, &500),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+}
@@ -0,0 +1,68 @@
+use crate::{page, DataKey};
+use common::ContractError;
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+/// Running totals for bets tagged with one affiliate. `volume` is the sum
+/// of gross stakes, before fees, in each bet's own token; it is never
+/// reduced when a bet is later cancelled or refunded.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AffiliateStats {
+    pub affiliate: Address,
+    pub volume: i128,
+    pub bet_count: u32,
+}
+
+pub fn get_affiliate_stats(env: &Env, affiliate: &Address) -> AffiliateStats {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AffiliateStats(affiliate.clone()))
+        .unwrap_or(AffiliateStats {
+            affiliate: affiliate.clone(),
+            volume: 0,
+            bet_count: 0,
+        })
+}
+
+/// Count a bet of `amount` toward `affiliate`, indexing the affiliate the
+/// first time it is seen.
+pub fn record_affiliate_volume(
+    env: &Env,
+    affiliate: &Address,
+    amount: i128,
+) -> Result<AffiliateStats, ContractError> {
+    let key = DataKey::AffiliateStats(affiliate.clone());
+    let storage = env.storage().persistent();
+    if !storage.has(&key) {
+        let mut affiliates = get_affiliates(env);
+        affiliates.push_back(affiliate.clone());
+        storage.set(&DataKey::Affiliates, &affiliates);
+    }
+
+    let mut stats = get_affiliate_stats(env, affiliate);
+    stats.volume = stats
+        .volume
+        .checked_add(amount)
+        .ok_or(ContractError::InvalidAmount)?;
+    stats.bet_count += 1;
+    storage.set(&key, &stats);
+    common::bump_persistent(env, &key);
+    Ok(stats)
+}
+
+/// Stats of every affiliate in the order they were first tagged, `limit`
+/// capped at `MAX_PAGE_SIZE`.
+pub fn affiliates_page(env: &Env, offset: u32, limit: u32) -> Vec<AffiliateStats> {
+    let mut stats = Vec::new(env);
+    for affiliate in page(&get_affiliates(env), offset, limit).iter() {
+        stats.push_back(get_affiliate_stats(env, &affiliate));
+    }
+    stats
+}
+
+fn get_affiliates(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Affiliates)
+        .unwrap_or_else(|| Vec::new(env))
+}
@@ -0,0 +1,67 @@
+use crate::DataKey;
+pub use common::EscrowMode;
+use common::{lock_ledger_funds, record_ledger_wager, unlock_ledger_funds, ContractError};
+use soroban_sdk::{token, Address, Env};
+
+pub fn get_escrow_mode(env: &Env) -> EscrowMode {
+    env.storage()
+        .persistent()
+        .get(&DataKey::EscrowMode)
+        .unwrap_or(EscrowMode::Token)
+}
+
+/// Take `amount` from `bettor` into escrow. Ledger-mode bets are placed in
+/// the ledger's base unit, whose token id is the ledger's own address.
+pub fn escrow_stake(
+    env: &Env,
+    bettor: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    match get_escrow_mode(env) {
+        EscrowMode::Token => {
+            // Ledger-mode stakes are checked by `lock_funds` itself
+            if let Some(limits_ledger) = env
+                .storage()
+                .persistent()
+                .get::<_, Address>(&DataKey::LimitsLedger)
+            {
+                record_ledger_wager(env, &limits_ledger, bettor, amount)?;
+            }
+            token::Client::new(env, token).transfer(
+                bettor,
+                env.current_contract_address(),
+                &amount,
+            );
+            Ok(())
+        }
+        EscrowMode::Ledger(balance_ledger) => {
+            if *token != balance_ledger {
+                return Err(ContractError::TokenNotAllowed);
+            }
+            lock_ledger_funds(env, &balance_ledger, bettor, amount)
+        }
+    }
+}
+
+/// Return an escrowed stake to `bettor`.
+pub fn release_stake(
+    env: &Env,
+    bettor: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    match get_escrow_mode(env) {
+        EscrowMode::Token => {
+            token::Client::new(env, token).transfer(
+                &env.current_contract_address(),
+                bettor,
+                &amount,
+            );
+            Ok(())
+        }
+        EscrowMode::Ledger(balance_ledger) => {
+            unlock_ledger_funds(env, &balance_ledger, bettor, amount)
+        }
+    }
+}
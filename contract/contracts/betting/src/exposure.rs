@@ -0,0 +1,157 @@
+use crate::{potential_payout, Bet, DataKey, Parlay};
+use common::ContractError;
+use soroban_sdk::{contracttype, BytesN, Env};
+
+/// House risk tolerance for one match. Liability is the total potential
+/// payout of every open bet on it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchLimits {
+    pub max_stake: i128,
+    pub max_liability: i128,
+}
+
+/// Smallest and largest stake a single bet may carry, before fees.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BetLimits {
+    pub min_bet: i128,
+    pub max_bet: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MatchExposure {
+    pub total_stake: i128,
+    pub total_liability: i128,
+}
+
+pub fn get_match_limits(env: &Env, match_id: &BytesN<32>) -> Option<MatchLimits> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::MatchLimits(match_id.clone()))
+}
+
+/// The limits that apply on `match_id`: its own override, else the global ones.
+pub fn get_bet_limits(env: &Env, match_id: &BytesN<32>) -> Option<BetLimits> {
+    let storage = env.storage().persistent();
+    storage
+        .get(&DataKey::MatchBetLimits(match_id.clone()))
+        .or_else(|| storage.get(&DataKey::BetLimits))
+}
+
+pub fn set_bet_limits(
+    env: &Env,
+    match_id: Option<BytesN<32>>,
+    limits: Option<BetLimits>,
+) -> Result<(), ContractError> {
+    let key = match match_id {
+        Some(match_id) => DataKey::MatchBetLimits(match_id),
+        None => DataKey::BetLimits,
+    };
+    match limits {
+        Some(limits) => {
+            if limits.min_bet <= 0 || limits.max_bet < limits.min_bet {
+                return Err(ContractError::InvalidConfig);
+            }
+            env.storage().persistent().set(&key, &limits);
+        }
+        None => env.storage().persistent().remove(&key),
+    }
+    Ok(())
+}
+
+/// Fail with `BelowMinBet` or `AboveMaxBet` when `amount` is outside the
+/// limits that apply on `match_id`.
+pub fn check_bet_limits(
+    env: &Env,
+    match_id: &BytesN<32>,
+    amount: i128,
+) -> Result<(), ContractError> {
+    if let Some(limits) = get_bet_limits(env, match_id) {
+        if amount < limits.min_bet {
+            return Err(ContractError::BelowMinBet);
+        }
+        if amount > limits.max_bet {
+            return Err(ContractError::AboveMaxBet);
+        }
+    }
+    Ok(())
+}
+
+pub fn get_match_exposure(env: &Env, match_id: &BytesN<32>) -> MatchExposure {
+    env.storage()
+        .persistent()
+        .get(&DataKey::MatchExposure(match_id.clone()))
+        .unwrap_or_default()
+}
+
+/// Add a bet to its match's exposure, failing with `ExceedsLimit` when the
+/// match has limits and the bet would push it past either of them.
+pub fn add_bet_exposure(env: &Env, bet: &Bet) -> Result<MatchExposure, ContractError> {
+    let liability = potential_payout(bet.amount, bet.odds as u64)?;
+    add_exposure(env, &bet.match_id, bet.amount, liability)
+}
+
+pub fn remove_bet_exposure(env: &Env, bet: &Bet) {
+    let liability = potential_payout(bet.amount, bet.odds as u64).unwrap_or(0);
+    remove_exposure(env, &bet.match_id, bet.amount, liability);
+}
+
+/// A parlay loses if any leg loses, so each leg's match carries the whole
+/// stake and potential payout.
+pub fn add_parlay_exposure(env: &Env, parlay: &Parlay) -> Result<(), ContractError> {
+    let liability = potential_payout(parlay.amount, parlay.combined_odds)?;
+    for leg in parlay.legs.iter() {
+        add_exposure(env, &leg.match_id, parlay.amount, liability)?;
+    }
+    Ok(())
+}
+
+pub fn remove_parlay_exposure(env: &Env, parlay: &Parlay) {
+    let liability = potential_payout(parlay.amount, parlay.combined_odds).unwrap_or(0);
+    for leg in parlay.legs.iter() {
+        remove_exposure(env, &leg.match_id, parlay.amount, liability);
+    }
+}
+
+fn add_exposure(
+    env: &Env,
+    match_id: &BytesN<32>,
+    stake: i128,
+    liability: i128,
+) -> Result<MatchExposure, ContractError> {
+    let mut exposure = get_match_exposure(env, match_id);
+    exposure.total_stake = exposure
+        .total_stake
+        .checked_add(stake)
+        .ok_or(ContractError::ExceedsLimit)?;
+    exposure.total_liability = exposure
+        .total_liability
+        .checked_add(liability)
+        .ok_or(ContractError::ExceedsLimit)?;
+
+    if let Some(limits) = get_match_limits(env, match_id) {
+        if exposure.total_stake > limits.max_stake
+            || exposure.total_liability > limits.max_liability
+        {
+            return Err(ContractError::ExceedsLimit);
+        }
+    }
+
+    store_exposure(env, match_id, &exposure);
+    Ok(exposure)
+}
+
+fn remove_exposure(env: &Env, match_id: &BytesN<32>, stake: i128, liability: i128) {
+    let mut exposure = get_match_exposure(env, match_id);
+    exposure.total_stake = (exposure.total_stake - stake).max(0);
+    exposure.total_liability = (exposure.total_liability - liability).max(0);
+    store_exposure(env, match_id, &exposure);
+}
+
+fn store_exposure(env: &Env, match_id: &BytesN<32>, exposure: &MatchExposure) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::MatchExposure(match_id.clone()), exposure);
+}
@@ -1,9 +1,47 @@
 #![no_std]
 use common::{
-    cleanup_operation, ensure_not_replayed, is_operation_executed, BetPlacedEvent, ContractError,
-    SpinExecutedEvent,
+    approve_recovery, cancel_recovery, cleanup_operation, contribute_to_jackpot,
+    ensure_not_replayed, execute_recovery, freeze_operator, get_fee_config, get_guardian_config,
+    get_match_info, get_match_status, get_pending_recovery, guardian_pause, is_operation_executed,
+    is_operator_frozen, is_paused, pause, publish_versioned_event, quote_fee,
+    quote_jackpot_contribution, record_fee, report_referral_volume, require_match_open,
+    require_not_paused, require_operator_not_frozen, set_fee_config, set_guardian_config,
+    total_fees_collected, unfreeze_operator, unpause, BetCancelledEvent, BetPlacedEvent,
+    ContractError, FeeConfig, GuardianConfig, MatchStatus, RecoveryRequest, SpinExecutedEvent,
+    WagerPlacedEvent, BET_CANCELLED_EVENT, BET_PLACED_EVENT, EVENT_SCHEMA_VERSION,
+    SPIN_EXECUTED_EVENT, WAGER_PLACED_EVENT,
 };
-use soroban_sdk::{contract, contractimpl, contracttype, token, Address, BytesN, Env, Map, Symbol};
+pub use common::{
+    combine_odds, potential_payout, BetStatus, Parlay, ParlayLeg, MAX_PARLAY_LEGS, MIN_PARLAY_LEGS,
+    ODDS_SCALE,
+};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, token, xdr::ToXdr, Address, BytesN, Env,
+    IntoVal, Symbol, TryFromVal, Val, Vec,
+};
+
+mod affiliates;
+mod escrow;
+mod exposure;
+mod markets;
+pub use affiliates::*;
+pub use escrow::*;
+pub use exposure::*;
+pub use markets::*;
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// What a spin produced, as signed by the backend.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpinOutcome {
+    /// Hash of the reels or result the frontend renders
+    pub result_hash: BytesN<32>,
+    pub wager: i128,
+    pub payout: i128,
+    pub token: Address,
+}
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -11,40 +49,170 @@ pub struct SpinExecution {
     pub spin_id: BytesN<32>,
     pub executor: Address,
     pub timestamp: u64,
+    /// The outcome fields are only filled in for spins executed through
+    /// `execute_spin_with_outcome`
+    pub result_hash: Option<BytesN<32>>,
+    pub wager: i128,
+    pub payout: i128,
+    pub token: Option<Address>,
 }
 
 #[contracttype]
-#[derive(Clone)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Bet {
+    /// Sequential id, starting at 1
+    pub bet_id: u64,
     pub bettor: Address,
+    pub token: Address,
     pub amount: i128,
     pub match_id: BytesN<32>,
     pub bet_type: Symbol,
     pub odds: u32,
     pub timestamp: u64,
+    pub status: BetStatus,
+    /// Affiliate the bettor came through, if the bet was tagged
+    pub affiliate: Option<Address>,
 }
 
 #[contracttype]
 pub enum DataKey {
     BackendSigner,
     UsedSpinHashes,
-    SpinExecutions,
-    Bet(BytesN<32>, Address),
+    SpinExecution(BytesN<32>),
+    Bet(u64),
     PreventDoubleBetting,
+    MatchRegistry,
+    BackendPubkey,
+    BetLockTime(BytesN<32>),
+    MatchBets(BytesN<32>),
+    UserBets(Address),
+    LatestBet(BytesN<32>, Address),
+    AffiliateStats(Address),
+    Affiliates,
+    NextParlayId,
+    Parlay(u64),
+    Odds(BytesN<32>, Symbol),
+    Jackpot,
+    Referral,
+    MatchLimits(BytesN<32>),
+    MatchExposure(BytesN<32>),
+    EscrowMode,
+    PlayerSpinCount(Address),
+    PlayerSpin(Address, u32),
+    LimitsLedger,
+    SettlementContract,
+    SelfExclusionRegistry,
+    BetLimits,
+    MatchBetLimits(BytesN<32>),
+    CloseTime(BytesN<32>),
+    MarketClosed(BytesN<32>),
+    NextBetId,
 }
 
+// Each spin execution record keeps its own TTL (in ledgers, ~5s each):
+// bumped to ~30 days on write, so history never lands in one growing entry.
+const SPIN_EXECUTION_TTL_THRESHOLD: u32 = 17_280;
+const SPIN_EXECUTION_TTL_EXTEND_TO: u32 = 518_400;
+
+/// Shortest replay window, in seconds, a spin hash can be given. Shorter
+/// caller-supplied TTLs are raised to this.
+pub const MIN_SPIN_HASH_TTL_SECONDS: u64 = 86_400;
+
+/// Upper bound on the page size accepted by the paginated bet queries.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
 #[contract]
 pub struct BettingContract;
 
 #[contractimpl]
 impl BettingContract {
     /// Initialize the contract with the backend signer address
-    pub fn initialize(env: Env, backend_signer: Address) {
+    pub fn initialize(env: Env, backend_signer: Address) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::BackendSigner) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        backend_signer.require_auth();
+
+        storage.set(&DataKey::BackendSigner, &backend_signer);
+        Ok(())
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        Self::require_admin(&env, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Stop duplicating events under their pre-versioning topics from
+    /// `until` on; `None` keeps the duplicates indefinitely.
+    pub fn set_legacy_events_until(
+        env: Env,
+        admin: Address,
+        until: Option<u64>,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        common::set_legacy_events_until(&env, until);
+        Ok(())
+    }
+
+    pub fn get_legacy_events_until(env: Env) -> Option<u64> {
+        common::get_legacy_events_until(&env)
+    }
+
+    /// Initialize in ledger escrow mode: stakes are locked as internal
+    /// balances in `balance_ledger` instead of being transferred in, so the
+    /// ledger's backend signer must co-authorize every bet and refund.
+    pub fn initialize_with_ledger(
+        env: Env,
+        backend_signer: Address,
+        balance_ledger: Address,
+    ) -> Result<(), ContractError> {
         let storage = env.storage().persistent();
+        if storage.has(&DataKey::BackendSigner) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        backend_signer.require_auth();
+
         storage.set(&DataKey::BackendSigner, &backend_signer);
+        storage.set(&DataKey::EscrowMode, &EscrowMode::Ledger(balance_ledger));
+        Ok(())
+    }
+
+    pub fn get_escrow_mode(env: Env) -> EscrowMode {
+        get_escrow_mode(&env)
     }
 
     /// Place a bet and escrow funds
+    ///
+    /// The bet is taken at the market's published odds. `odds` is the price
+    /// the bettor accepted and only guards against the line moving below it.
+    /// `affiliate` tags the bet with the affiliate the bettor came through;
+    /// its gross stake is added to that affiliate's volume (see
+    /// `get_affiliate_stats`). Bettors cannot tag themselves.
+    /// Returns the bet id.
+    #[allow(clippy::too_many_arguments)]
     pub fn place_bet(
         env: Env,
         bettor: Address,
@@ -53,199 +221,1578 @@ impl BettingContract {
         match_id: BytesN<32>,
         bet_type: Symbol,
         odds: u32,
-    ) -> Result<(), ContractError> {
+        affiliate: Option<Address>,
+    ) -> Result<u64, ContractError> {
         bettor.require_auth();
+        require_not_paused(&env)?;
+        require_not_self_excluded(&env, &bettor)?;
 
         if amount <= 0 {
             return Err(ContractError::InvalidAmount);
         }
+        if affiliate.as_ref() == Some(&bettor) {
+            return Err(ContractError::SelfReferral);
+        }
+        check_bet_limits(&env, &match_id, amount)?;
+        require_betting_open(&env, &match_id)?;
 
         let storage = env.storage().persistent();
 
         // Check if double betting is prevented
         let prevent_double: bool = storage.get(&DataKey::PreventDoubleBetting).unwrap_or(false);
-        if prevent_double {
-            if storage.has(&DataKey::Bet(match_id.clone(), bettor.clone())) {
-                return Err(ContractError::BetAlreadyPlaced);
-            }
+        if prevent_double
+            && get_latest_bet(&env, &match_id, &bettor).is_some_and(|bet| is_active(&bet))
+        {
+            return Err(ContractError::BetAlreadyPlaced);
         }
 
-        // Lock funds (transfer from bettor to contract)
-        let token_client = token::Client::new(&env, &token_address);
-        token_client.transfer(&bettor, &env.current_contract_address(), &amount);
+        // Only accept bets on matches the registry still lists as open
+        if let Some(registry) = storage.get::<_, Address>(&DataKey::MatchRegistry) {
+            require_match_open(&env, &registry, &match_id)?;
+        }
+        let odds = odds_for_bet(&env, &match_id, &bet_type, odds)?;
+
+        // The platform fee and the jackpot's share come out of the stake
+        // before it is escrowed
+        let fee = quote_fee(&env, amount)?;
+        let jackpot_share = jackpot_share(&env, &token_address, amount)?;
+        let stake = amount - jackpot_share - fee_amount(&fee);
+
+        let bet_id: u64 = storage.get(&DataKey::NextBetId).unwrap_or(1);
+        storage.set(&DataKey::NextBetId, &(bet_id + 1));
 
-        // Store bet
         let timestamp = env.ledger().timestamp();
         let bet = Bet {
+            bet_id,
             bettor: bettor.clone(),
-            amount,
+            token: token_address.clone(),
+            amount: stake,
             match_id: match_id.clone(),
             bet_type: bet_type.clone(),
             odds,
             timestamp,
+            status: BetStatus::Open,
+            affiliate: affiliate.clone(),
         };
 
-        storage.set(&DataKey::Bet(match_id.clone(), bettor.clone()), &bet);
+        add_bet_exposure(&env, &bet)?;
 
-        // Emit bet placed event
+        // Lock funds (tokens into this contract, or locked ledger balance)
+        escrow_stake(&env, &bettor, &token_address, amount)?;
+        pay_jackpot_share(&env, &token_address, amount, jackpot_share)?;
+        forward_fee(&env, &bettor, &token_address, fee)?;
+        if let Some(affiliate) = &affiliate {
+            record_affiliate_volume(&env, affiliate, amount)?;
+        }
+
+        store_bet(&env, &bet);
+        add_to_bet_indexes(&env, &bet);
+        common::bump_instance(&env);
+
+        // The legacy event only identifies the bet by a fixed symbol;
+        // indexers should follow the full `WagerPlacedEvent` instead
         let event = BetPlacedEvent {
+            version: EVENT_SCHEMA_VERSION,
             bettor: bettor.clone(),
-            bet_id: Symbol::new(&env, "bet"), // Using a generic symbol or match_id as symbol
-            amount,
+            bet_id: Symbol::new(&env, "bet"),
+            amount: stake,
         };
-        // Note: The common::BetPlacedEvent uses Symbol for bet_id.
-        // We might want to emit a more detailed event or use Match ID.
-        // For now, let's satisfy the criteria with what's available.
-        env.events()
-            .publish((Symbol::new(&env, "bet_placed"), match_id.clone()), event);
+        publish_bet_placed_event(&env, &match_id, event);
+        publish_wager_placed_event(
+            &env,
+            WagerPlacedEvent {
+                version: EVENT_SCHEMA_VERSION,
+                bet_id,
+                bettor,
+                match_id,
+                bet_type,
+                odds,
+                token: token_address,
+                amount: stake,
+                timestamp,
+                affiliate,
+            },
+        );
 
-        Ok(())
+        Ok(bet_id)
     }
 
-    /// Configure double betting prevention
-    pub fn set_prevent_double_betting(
+    /// Volume and bet count of bets tagged with `affiliate`
+    pub fn get_affiliate_stats(env: Env, affiliate: Address) -> AffiliateStats {
+        get_affiliate_stats(&env, &affiliate)
+    }
+
+    /// Every tagged affiliate's stats in first-tagged order, `limit` capped
+    /// at `MAX_PAGE_SIZE`
+    pub fn get_affiliates(env: Env, offset: u32, limit: u32) -> Vec<AffiliateStats> {
+        affiliates_page(&env, offset, limit)
+    }
+
+    /// Escrow a single stake on several legs that must all win. Each leg's
+    /// match must be open and no match may appear twice. Legs are priced at
+    /// their markets' current odds, and the stake must fit every leg's bet
+    /// limits and match exposure, as in `place_bet`. Returns the parlay id.
+    pub fn place_parlay(
         env: Env,
-        admin: Address,
-        prevent: bool,
-    ) -> Result<(), ContractError> {
-        // Only backend signer (acting as admin) can change settings
-        // In a real scenario, you'd have a separate admin role
+        bettor: Address,
+        token_address: Address,
+        amount: i128,
+        legs: Vec<(BytesN<32>, Symbol, u32)>,
+    ) -> Result<u64, ContractError> {
+        bettor.require_auth();
+        require_not_paused(&env)?;
+        require_not_self_excluded(&env, &bettor)?;
+
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if legs.len() < MIN_PARLAY_LEGS {
+            return Err(ContractError::InvalidBet);
+        }
+        if legs.len() > MAX_PARLAY_LEGS {
+            return Err(ContractError::ExceedsLimit);
+        }
+
         let storage = env.storage().persistent();
-        let backend_signer: Address = storage
-            .get(&DataKey::BackendSigner)
-            .ok_or(ContractError::Unauthorized)?;
+        let registry: Option<Address> = storage.get(&DataKey::MatchRegistry);
 
-        admin.require_auth();
-        if admin != backend_signer {
-            return Err(ContractError::Unauthorized);
+        let mut parlay_legs: Vec<ParlayLeg> = Vec::new(&env);
+        for (match_id, bet_type, accepted_odds) in legs.iter() {
+            if parlay_legs.iter().any(|leg| leg.match_id == match_id) {
+                return Err(ContractError::InvalidBet);
+            }
+            check_bet_limits(&env, &match_id, amount)?;
+            require_betting_open(&env, &match_id)?;
+            if let Some(registry) = &registry {
+                require_match_open(&env, registry, &match_id)?;
+            }
+            let odds = odds_for_bet(&env, &match_id, &bet_type, accepted_odds)?;
+            parlay_legs.push_back(ParlayLeg {
+                match_id,
+                bet_type,
+                odds,
+            });
         }
+        let combined_odds = combine_odds(&parlay_legs)?;
+        let fee = quote_fee(&env, amount)?;
+        let jackpot_share = jackpot_share(&env, &token_address, amount)?;
+        let stake = amount - jackpot_share - fee_amount(&fee);
 
-        storage.set(&DataKey::PreventDoubleBetting, &prevent);
-        Ok(())
+        let parlay_id: u64 = storage.get(&DataKey::NextParlayId).unwrap_or(1);
+        storage.set(&DataKey::NextParlayId, &(parlay_id + 1));
+
+        let parlay = Parlay {
+            parlay_id,
+            bettor: bettor.clone(),
+            token: token_address.clone(),
+            amount: stake,
+            legs: parlay_legs,
+            combined_odds,
+            timestamp: env.ledger().timestamp(),
+            status: BetStatus::Open,
+        };
+        add_parlay_exposure(&env, &parlay)?;
+
+        // Lock funds once for the whole parlay
+        escrow_stake(&env, &bettor, &token_address, amount)?;
+        pay_jackpot_share(&env, &token_address, amount, jackpot_share)?;
+        forward_fee(&env, &bettor, &token_address, fee)?;
+
+        store_parlay(&env, &parlay);
+
+        publish_parlay_placed_event(&env, &parlay);
+
+        Ok(parlay_id)
     }
 
-    /// Check if double betting is prevented
-    pub fn is_double_betting_prevented(env: Env) -> bool {
-        env.storage()
-            .persistent()
-            .get(&DataKey::PreventDoubleBetting)
-            .unwrap_or(false)
+    pub fn get_parlay(env: Env, parlay_id: u64) -> Result<Parlay, ContractError> {
+        get_parlay(&env, parlay_id)
     }
 
-    /// Execute a spin with backend signature verification
-    ///
-    /// # Arguments
-    /// * `spin_id` - Unique identifier for the spin (32-byte hash)
-    /// * `spin_hash` - Hash of spin parameters for replay protection
-    /// * `signature` - Signature from backend signer
-    /// * `executor` - Address executing the spin
-    ///
-    /// # Returns
-    /// `Result<(), ContractError>`
-    /// Execute a spin with backend authorization
-    pub fn execute_spin(
+    /// Close an open parlay once its legs are resolved, paying `amount` to
+    /// each `(to, amount)` in `payouts` out of token escrow. An empty list
+    /// settles a losing parlay, or one already paid out on the ledger.
+    /// Payouts may not add up to more than the parlay's potential payout.
+    /// Only the registered settlement contract may settle, and each
+    /// operation hash is only accepted once.
+    pub fn settle_parlay(
         env: Env,
-        spin_id: BytesN<32>,
-        spin_hash: BytesN<32>,
-        signature: BytesN<64>,
-        executor: Address,
-    ) -> Result<(), ContractError> {
-        Self::execute_spin_with_ttl(env, spin_id, spin_hash, signature, executor, None)
+        operation_hash: BytesN<32>,
+        parlay_id: u64,
+        payouts: Vec<(Address, i128)>,
+    ) -> Result<Parlay, ContractError> {
+        require_settlement_contract(&env)?;
+        require_not_paused(&env)?;
+
+        let parlay = get_open_parlay(&env, parlay_id)?;
+        let mut total: i128 = 0;
+        for (_, amount) in payouts.iter() {
+            if amount <= 0 {
+                return Err(ContractError::InvalidAmount);
+            }
+            total = total
+                .checked_add(amount)
+                .ok_or(ContractError::InvalidAmount)?;
+        }
+        if total > potential_payout(parlay.amount, parlay.combined_odds)? {
+            return Err(ContractError::ExceedsLimit);
+        }
+        if !payouts.is_empty() {
+            require_token_escrow(&env)?;
+        }
+        ensure_not_replayed(
+            &env,
+            Symbol::new(&env, "parlay_settle"),
+            operation_hash,
+            None,
+        )?;
+
+        let parlay = close_parlay(&env, parlay, BetStatus::Settled);
+        report_bet_volume(&env, &parlay.bettor, parlay.amount)?;
+        let token = token::Client::new(&env, &parlay.token);
+        for (to, amount) in payouts.iter() {
+            token.transfer(&env.current_contract_address(), &to, &amount);
+        }
+        Ok(parlay)
     }
 
-    pub fn execute_spin_with_ttl(
+    /// Return an open parlay's stake to its bettor. The backend signer may
+    /// refund at any time and the parlay is marked `Cancelled`; anyone may
+    /// refund it once the registry reports one of its matches cancelled,
+    /// and it is marked `Refunded`.
+    pub fn refund_parlay(
         env: Env,
-        spin_id: BytesN<32>,
-        spin_hash: BytesN<32>,
-        signature: BytesN<64>,
-        executor: Address,
-        ttl_seconds: Option<u64>,
-    ) -> Result<(), ContractError> {
-        executor.require_auth();
+        caller: Address,
+        parlay_id: u64,
+    ) -> Result<Parlay, ContractError> {
+        caller.require_auth();
+        require_not_paused(&env)?;
+
+        let storage = env.storage().persistent();
+        let parlay = get_open_parlay(&env, parlay_id)?;
+        let backend_signer: Address = storage
+            .get(&DataKey::BackendSigner)
+            .ok_or(ContractError::NotInitialized)?;
+        let status = if caller == backend_signer {
+            BetStatus::Cancelled
+        } else {
+            let registry: Address = storage
+                .get(&DataKey::MatchRegistry)
+                .ok_or(ContractError::Unauthorized)?;
+            let mut cancelled = false;
+            for leg in parlay.legs.iter() {
+                if get_match_status(&env, &registry, &leg.match_id)? == MatchStatus::Cancelled {
+                    cancelled = true;
+                    break;
+                }
+            }
+            if !cancelled {
+                return Err(ContractError::InvalidStatus);
+            }
+            BetStatus::Refunded
+        };
+
+        let parlay = close_parlay(&env, parlay, status);
+        release_stake(&env, &parlay.bettor, &parlay.token, parlay.amount)?;
+        Ok(parlay)
+    }
+
+    /// Refund an escrowed bet and mark it cancelled.
+    ///
+    /// The backend signer may cancel at any time (e.g. a postponed match).
+    /// The bettor may cancel their own bet only before the match's lock time.
+    pub fn cancel_bet(env: Env, caller: Address, bet_id: u64) -> Result<Bet, ContractError> {
+        caller.require_auth();
+        require_not_paused(&env)?;
 
         let storage = env.storage().persistent();
+        let bet = get_active_bet(&env, bet_id)?;
 
-        // Get backend signer
         let backend_signer: Address = storage
             .get(&DataKey::BackendSigner)
-            .ok_or(ContractError::Unauthorized)?;
+            .ok_or(ContractError::NotInitialized)?;
+        if caller != backend_signer {
+            if caller != bet.bettor {
+                return Err(ContractError::Unauthorized);
+            }
+            if bet.status == BetStatus::Locked {
+                return Err(ContractError::InvalidStatus);
+            }
+            let lock_time: u64 = storage
+                .get(&DataKey::BetLockTime(bet.match_id.clone()))
+                .ok_or(ContractError::MatchNotOpen)?;
+            if env.ledger().timestamp() >= lock_time {
+                return Err(ContractError::MatchNotOpen);
+            }
+        }
 
-        // Signature verification hook; auth is currently enforced via backend signer auth.
-        let _ = signature;
-        backend_signer.require_auth();
+        refund_bet(&env, bet, BetStatus::Cancelled, &caller)
+    }
+
+    /// Stop the bettor from cancelling an open bet, e.g. at kick-off.
+    /// Admin only.
+    pub fn lock_bet(env: Env, admin: Address, bet_id: u64) -> Result<Bet, ContractError> {
+        Self::require_admin(&env, &admin)?;
+        let mut bet = get_active_bet(&env, bet_id)?;
+        if bet.status != BetStatus::Open {
+            return Err(ContractError::InvalidStatus);
+        }
+        bet.status = BetStatus::Locked;
+        store_bet(&env, &bet);
+        publish_bet_status_event(&env, &bet);
+        Ok(bet)
+    }
+
+    /// Record that a bet was lost, or paid out without `release_escrow`.
+    /// Only the registered settlement contract may settle bets.
+    pub fn mark_bet_settled(env: Env, bet_id: u64) -> Result<Bet, ContractError> {
+        require_settlement_contract(&env)?;
+        let bet = get_active_bet(&env, bet_id)?;
+        settle_bet(&env, bet)
+    }
+
+    pub fn get_bet_by_id(env: Env, bet_id: u64) -> Option<Bet> {
+        get_bet(&env, bet_id)
+    }
+
+    /// Latest bet of `bettor` on `match_id`, whatever its status. Earlier
+    /// bets on the same match are kept under their own ids.
+    pub fn get_bet(env: Env, match_id: BytesN<32>, bettor: Address) -> Option<Bet> {
+        get_latest_bet(&env, &match_id, &bettor)
+    }
+
+    pub fn get_bet_status(env: Env, bet_id: u64) -> Result<BetStatus, ContractError> {
+        get_bet(&env, bet_id)
+            .map(|bet| bet.status)
+            .ok_or(ContractError::BetNotFound)
+    }
+
+    /// Open and locked bets on `match_id` in placement order, `limit` capped at `MAX_PAGE_SIZE`
+    pub fn get_bets_for_match(env: Env, match_id: BytesN<32>, offset: u32, limit: u32) -> Vec<Bet> {
+        bets_page(&env, &DataKey::MatchBets(match_id), offset, limit)
+    }
+
+    /// Open and locked bets placed by `user` in placement order, `limit` capped at `MAX_PAGE_SIZE`
+    pub fn get_bets_for_user(env: Env, user: Address, offset: u32, limit: u32) -> Vec<Bet> {
+        bets_page(&env, &DataKey::UserBets(user), offset, limit)
+    }
+
+    pub fn get_bet_count_for_match(env: Env, match_id: BytesN<32>) -> u32 {
+        get_bet_ids(&env, &DataKey::MatchBets(match_id)).len()
+    }
+
+    pub fn get_bet_count_for_user(env: Env, user: Address) -> u32 {
+        get_bet_ids(&env, &DataKey::UserBets(user)).len()
+    }
+
+    /// Return the escrow of a bet whose match the registry reports as
+    /// cancelled. Anyone may trigger this; funds only go back to the bettor.
+    pub fn refund_cancelled_bet(
+        env: Env,
+        caller: Address,
+        bet_id: u64,
+    ) -> Result<Bet, ContractError> {
+        caller.require_auth();
+
+        let registry: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::MatchRegistry)
+            .ok_or(ContractError::NotInitialized)?;
+        let bet = get_active_bet(&env, bet_id)?;
+        if get_match_status(&env, &registry, &bet.match_id)? != MatchStatus::Cancelled {
+            return Err(ContractError::InvalidStatus);
+        }
 
+        refund_bet(&env, bet, BetStatus::Refunded, &caller)
+    }
+
+    /// Pay bet `bet_id` out of escrow, `amount` to each `(to, amount)` in
+    /// `payouts`, and mark the bet settled. Returns the bet's token. Only
+    /// the registered settlement contract may release escrow, and each
+    /// operation hash is only accepted once. Payouts are checked against
+    /// the stored bet for its `outcome`: a `WIN` may pay out up to the
+    /// stake at the bet's odds, since losing stakes stay in the same
+    /// escrow, a `LOSS` at most the stake, and a `DRAW` only the stake back
+    /// to the bettor.
+    pub fn release_escrow(
+        env: Env,
+        operation_hash: BytesN<32>,
+        bet_id: u64,
+        outcome: Symbol,
+        payouts: Vec<(Address, i128)>,
+    ) -> Result<Address, ContractError> {
+        require_settlement_contract(&env)?;
+        require_not_paused(&env)?;
+        require_token_escrow(&env)?;
+
+        if payouts.is_empty() {
+            return Err(ContractError::InvalidAmount);
+        }
+        let mut total: i128 = 0;
+        for (_, amount) in payouts.iter() {
+            if amount <= 0 {
+                return Err(ContractError::InvalidAmount);
+            }
+            total = total
+                .checked_add(amount)
+                .ok_or(ContractError::InvalidAmount)?;
+        }
         ensure_not_replayed(
             &env,
-            Symbol::new(&env, "spin_exec"),
-            spin_hash.clone(),
-            ttl_seconds,
+            Symbol::new(&env, "escrow_release"),
+            operation_hash.clone(),
+            None,
         )?;
+        let bet = get_active_bet(&env, bet_id)?;
+        if outcome == symbol_short!("WIN") {
+            if total > potential_payout(bet.amount, bet.odds as u64)? {
+                return Err(ContractError::ExceedsLimit);
+            }
+        } else if outcome == symbol_short!("LOSS") {
+            if total > bet.amount {
+                return Err(ContractError::ExceedsLimit);
+            }
+        } else if outcome == symbol_short!("DRAW") {
+            if payouts != Vec::from_array(&env, [(bet.bettor.clone(), bet.amount)]) {
+                return Err(ContractError::InvalidAmount);
+            }
+        } else {
+            return Err(ContractError::InvalidStatus);
+        }
+        let bet = settle_bet(&env, bet)?;
+
+        let token = token::Client::new(&env, &bet.token);
+        for (to, amount) in payouts.iter() {
+            token.transfer(&env.current_contract_address(), &to, &amount);
+            publish_escrow_released_event(&env, &bet, &to, amount, &operation_hash);
+        }
+        Ok(bet.token)
+    }
 
-        // Store spin execution
-        let executions: Map<BytesN<32>, SpinExecution> = storage
-            .get(&DataKey::SpinExecutions)
-            .unwrap_or_else(|| Map::new(&env));
+    /// Accept a cash-out offer: bet `bet_id` is settled early for
+    /// `offer_amount`, paid from escrow to the bettor. `backend_signature` is
+    /// the backend key's ed25519 signature over the XDR encoding of
+    /// `(contract, bettor, bet_id, offer_amount, expires_at, operation_hash)`,
+    /// where `contract` is this contract's address. Offers
+    /// are rejected with `Expired` from `expires_at` on, and each operation
+    /// hash is only accepted once.
+    pub fn cash_out(
+        env: Env,
+        bettor: Address,
+        bet_id: u64,
+        offer_amount: i128,
+        expires_at: u64,
+        operation_hash: BytesN<32>,
+        backend_signature: BytesN<64>,
+    ) -> Result<Bet, ContractError> {
+        bettor.require_auth();
+        require_not_paused(&env)?;
+        require_token_escrow(&env)?;
 
-        // Check for duplicate execution on spin ID
-        if executions.get(spin_id.clone()).is_some() {
-            return Err(ContractError::SpinAlreadyExecuted);
+        if offer_amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if env.ledger().timestamp() >= expires_at {
+            return Err(ContractError::Expired);
         }
 
-        let current_time = env.ledger().timestamp();
+        let storage = env.storage().persistent();
+        let backend_signer: Address = storage
+            .get(&DataKey::BackendSigner)
+            .ok_or(ContractError::NotInitialized)?;
+        require_operator_not_frozen(&env, &backend_signer)?;
+        let backend_pubkey: BytesN<32> = storage
+            .get(&DataKey::BackendPubkey)
+            .ok_or(ContractError::InvalidSignature)?;
+        let message = (
+            env.current_contract_address(),
+            bettor.clone(),
+            bet_id,
+            offer_amount,
+            expires_at,
+            operation_hash.clone(),
+        )
+            .to_xdr(&env);
+        env.crypto()
+            .ed25519_verify(&backend_pubkey, &message, &backend_signature);
+        ensure_not_replayed(
+            &env,
+            Symbol::new(&env, "cash_out"),
+            operation_hash.clone(),
+            None,
+        )?;
 
-        let execution = SpinExecution {
-            spin_id: spin_id.clone(),
-            executor: executor.clone(),
-            timestamp: current_time,
-        };
+        let bet = get_active_bet(&env, bet_id)?;
+        if bet.bettor != bettor {
+            return Err(ContractError::Unauthorized);
+        }
+        let bet = settle_bet(&env, bet)?;
+        release_stake(&env, &bettor, &bet.token, offer_amount)?;
 
-        // Update storage
-        let mut new_executions = executions.clone();
-        new_executions.set(spin_id.clone(), execution.clone());
-        storage.set(&DataKey::SpinExecutions, &new_executions);
-        // Emit execution event
-        let event = SpinExecutedEvent {
-            spin_id: spin_id.clone(),
-            executor: executor.clone(),
-            timestamp: current_time,
-        };
+        publish_cash_out_event(&env, &bet, offer_amount, &operation_hash);
+        Ok(bet)
+    }
 
-        env.events()
-            .publish((Symbol::new(&env, "spin_executed"),), event);
+    pub fn is_cash_out_executed(env: Env, operation_hash: BytesN<32>) -> bool {
+        is_operation_executed(&env, Symbol::new(&env, "cash_out"), operation_hash)
+    }
 
-        Ok(())
+    pub fn is_escrow_release_executed(env: Env, operation_hash: BytesN<32>) -> bool {
+        is_operation_executed(&env, Symbol::new(&env, "escrow_release"), operation_hash)
     }
 
-    /// Check if a spin has already been executed
-    pub fn is_spin_executed(env: Env, spin_id: BytesN<32>) -> bool {
-        let storage = env.storage().persistent();
-        let executions: Map<BytesN<32>, SpinExecution> = storage
-            .get(&DataKey::SpinExecutions)
-            .unwrap_or_else(|| Map::new(&env));
+    /// Publish or move the line for one market of a match.
+    pub fn set_odds(
+        env: Env,
+        admin: Address,
+        match_id: BytesN<32>,
+        bet_type: Symbol,
+        odds: u32,
+    ) -> Result<MarketOdds, ContractError> {
+        Self::require_admin(&env, &admin)?;
+        set_market_odds(&env, &match_id, &bet_type, odds)
+    }
+
+    pub fn remove_odds(
+        env: Env,
+        admin: Address,
+        match_id: BytesN<32>,
+        bet_type: Symbol,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        remove_market_odds(&env, &match_id, &bet_type)
+    }
 
-        executions.get(spin_id).is_some()
+    pub fn get_odds(env: Env, match_id: BytesN<32>, bet_type: Symbol) -> Option<MarketOdds> {
+        get_market_odds(&env, &match_id, &bet_type)
     }
 
-    /// Get spin execution details
-    pub fn get_spin_execution(
+    /// Cap the total stake and total potential payout the house accepts on a
+    /// match. Bets placed before the limits were set still count towards them.
+    pub fn set_match_limits(
         env: Env,
-        spin_id: BytesN<32>,
-    ) -> Result<SpinExecution, ContractError> {
-        let storage = env.storage().persistent();
-        let executions: Map<BytesN<32>, SpinExecution> = storage
-            .get(&DataKey::SpinExecutions)
-            .unwrap_or_else(|| Map::new(&env));
+        admin: Address,
+        match_id: BytesN<32>,
+        max_stake: i128,
+        max_liability: i128,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        if max_stake <= 0 || max_liability <= 0 {
+            return Err(ContractError::InvalidConfig);
+        }
 
-        executions.get(spin_id).ok_or(ContractError::SpinNotFound)
+        let limits = MatchLimits {
+            max_stake,
+            max_liability,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::MatchLimits(match_id), &limits);
+        Ok(())
     }
 
-    pub fn is_spin_hash_used(env: Env, spin_hash: BytesN<32>) -> bool {
-        is_operation_executed(&env, Symbol::new(&env, "spin_exec"), spin_hash)
+    pub fn get_match_limits(env: Env, match_id: BytesN<32>) -> Option<MatchLimits> {
+        get_match_limits(&env, &match_id)
     }
 
-    pub fn cleanup_spin_hash(env: Env, spin_hash: BytesN<32>) -> bool {
-        cleanup_operation(&env, Symbol::new(&env, "spin_exec"), spin_hash)
+    /// Bound the amount of each `place_bet`, before fees. With `match_id`
+    /// the limits override the global ones on that match only.
+    pub fn set_bet_limits(
+        env: Env,
+        admin: Address,
+        match_id: Option<BytesN<32>>,
+        min_bet: i128,
+        max_bet: i128,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        set_bet_limits(&env, match_id, Some(BetLimits { min_bet, max_bet }))
     }
-}
+
+    /// Drop the global limits, or the override on `match_id`.
+    pub fn remove_bet_limits(
+        env: Env,
+        admin: Address,
+        match_id: Option<BytesN<32>>,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        set_bet_limits(&env, match_id, None)
+    }
+
+    /// Limits `place_bet` enforces on `match_id`
+    pub fn get_bet_limits(env: Env, match_id: BytesN<32>) -> Option<BetLimits> {
+        get_bet_limits(&env, &match_id)
+    }
+
+    pub fn get_match_exposure(env: Env, match_id: BytesN<32>) -> MatchExposure {
+        get_match_exposure(&env, &match_id)
+    }
+
+    /// Set the timestamp after which bettors can no longer cancel their own
+    /// bets on `match_id`. Without a lock time only the backend can cancel.
+    pub fn set_bet_lock_time(
+        env: Env,
+        admin: Address,
+        match_id: BytesN<32>,
+        lock_time: u64,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::BetLockTime(match_id), &lock_time);
+        Ok(())
+    }
+
+    pub fn get_bet_lock_time(env: Env, match_id: BytesN<32>) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BetLockTime(match_id))
+    }
+
+    /// Stop taking bets on `match_id` from `close_time` on, normally its
+    /// kickoff. Admin only.
+    pub fn set_close_time(
+        env: Env,
+        admin: Address,
+        match_id: BytesN<32>,
+        close_time: u64,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        set_close_time(&env, &match_id, close_time);
+        Ok(())
+    }
+
+    /// Copy the close time of `match_id` from the match registry. Anyone
+    /// may sync it; the registry is the source of truth for kickoffs.
+    pub fn sync_close_time(env: Env, match_id: BytesN<32>) -> Result<u64, ContractError> {
+        let registry: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::MatchRegistry)
+            .ok_or(ContractError::NotInitialized)?;
+        let close_time = get_match_info(&env, &registry, &match_id)?.close_time;
+        set_close_time(&env, &match_id, close_time);
+        Ok(close_time)
+    }
+
+    /// Admin override to push the close time of `match_id` back, e.g. for
+    /// a delayed kickoff. Reopens the market if it had already closed.
+    pub fn extend_close_time(
+        env: Env,
+        admin: Address,
+        match_id: BytesN<32>,
+        new_close_time: u64,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        let close_time = get_close_time(&env, &match_id).ok_or(ContractError::MatchNotFound)?;
+        if new_close_time <= close_time {
+            return Err(ContractError::InvalidConfig);
+        }
+        set_close_time(&env, &match_id, new_close_time);
+        Ok(())
+    }
+
+    pub fn get_close_time(env: Env, match_id: BytesN<32>) -> Option<u64> {
+        get_close_time(&env, &match_id)
+    }
+
+    /// Emit `market_closed` for `match_id` once its close time has passed.
+    /// Anyone may trigger this; returns the close time.
+    pub fn close_market(env: Env, match_id: BytesN<32>) -> Result<u64, ContractError> {
+        close_market(&env, &match_id)
+    }
+
+    /// Configure double betting prevention
+    pub fn set_prevent_double_betting(
+        env: Env,
+        admin: Address,
+        prevent: bool,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::PreventDoubleBetting, &prevent);
+        Ok(())
+    }
+
+    /// Point betting at the shared match registry. Once set, bets are only
+    /// accepted while the registry reports the match as open.
+    pub fn set_match_registry(
+        env: Env,
+        admin: Address,
+        registry: Address,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::MatchRegistry, &registry);
+        Ok(())
+    }
+
+    pub fn get_match_registry(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::MatchRegistry)
+    }
+
+    /// Route a share of every stake to a jackpot contract. The jackpot must
+    /// list this contract as a contributor.
+    pub fn set_jackpot(env: Env, admin: Address, jackpot: Address) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        require_token_escrow(&env)?;
+
+        env.storage().persistent().set(&DataKey::Jackpot, &jackpot);
+        Ok(())
+    }
+
+    pub fn get_jackpot(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Jackpot)
+    }
+
+    /// Charge `fee_bps` of every stake at placement and forward it to
+    /// `fee_collector`, normally the treasury contract. Zero turns fees off.
+    pub fn set_fee_config(
+        env: Env,
+        admin: Address,
+        fee_bps: u32,
+        fee_collector: Address,
+    ) -> Result<FeeConfig, ContractError> {
+        Self::require_admin(&env, &admin)?;
+        require_token_escrow(&env)?;
+        set_fee_config(&env, fee_bps, &fee_collector)
+    }
+
+    pub fn get_fee_config(env: Env) -> Option<FeeConfig> {
+        get_fee_config(&env)
+    }
+
+    pub fn total_fees_collected(env: Env, token: Address) -> i128 {
+        total_fees_collected(&env, Some(token))
+    }
+
+    /// Report every stake to a referral contract so referrers earn
+    /// commission. The referral contract must list this contract as a
+    /// reporter.
+    pub fn set_referral(env: Env, admin: Address, referral: Address) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Referral, &referral);
+        Ok(())
+    }
+
+    pub fn get_referral(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Referral)
+    }
+
+    /// Check token-escrowed stakes against the wager and loss limits kept
+    /// in `balance_ledger`, which must list this contract as a limit
+    /// reporter. Ledger-mode stakes are always checked there.
+    pub fn set_limits_ledger(
+        env: Env,
+        admin: Address,
+        balance_ledger: Address,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        require_token_escrow(&env)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::LimitsLedger, &balance_ledger);
+        Ok(())
+    }
+
+    pub fn get_limits_ledger(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::LimitsLedger)
+    }
+
+    /// Register the settlement contract allowed to close bets with
+    /// `mark_bet_settled` and, in token escrow mode, to pay winnings out
+    /// through `release_escrow`.
+    pub fn set_settlement_contract(
+        env: Env,
+        admin: Address,
+        settlement: Address,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::SettlementContract, &settlement);
+        Ok(())
+    }
+
+    pub fn get_settlement_contract(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::SettlementContract)
+    }
+
+    /// Registry (the balance ledger) whose self-exclusions turn users away
+    /// from bets and spins with `SelfExcluded`.
+    pub fn set_self_exclusion_registry(
+        env: Env,
+        admin: Address,
+        registry: Address,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::SelfExclusionRegistry, &registry);
+        Ok(())
+    }
+
+    pub fn get_self_exclusion_registry(env: Env) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SelfExclusionRegistry)
+    }
+
+    /// Freeze bets and spins; getters keep working while paused
+    pub fn pause(env: Env, admin: Address) -> Result<(), ContractError> {
+        pause(&env, &Self::verify_admin(&env, &admin)?);
+        Ok(())
+    }
+
+    pub fn unpause(env: Env, admin: Address) -> Result<(), ContractError> {
+        unpause(&env, &Self::verify_admin(&env, &admin)?);
+        Ok(())
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        is_paused(&env)
+    }
+
+    /// Check if double betting is prevented
+    pub fn is_double_betting_prevented(env: Env) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PreventDoubleBetting)
+            .unwrap_or(false)
+    }
+
+    /// Set the ed25519 public key whose signatures authorize spins
+    pub fn set_backend_pubkey(
+        env: Env,
+        admin: Address,
+        backend_pubkey: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::BackendPubkey, &backend_pubkey);
+        Ok(())
+    }
+
+    pub fn get_backend_pubkey(env: Env) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::BackendPubkey)
+    }
+
+    /// Execute a spin with backend signature verification
+    ///
+    /// # Arguments
+    /// * `spin_id` - Unique identifier for the spin (32-byte hash)
+    /// * `spin_hash` - Hash of spin parameters for replay protection
+    /// * `signature` - ed25519 signature by the backend key over
+    ///   `(contract, spin_id, executor, spin_hash)` in XDR
+    /// * `executor` - Address executing the spin
+    ///
+    /// # Returns
+    /// `Result<(), ContractError>`; `InvalidSignature` if no backend key is set
+    pub fn execute_spin(
+        env: Env,
+        spin_id: BytesN<32>,
+        spin_hash: BytesN<32>,
+        signature: BytesN<64>,
+        executor: Address,
+    ) -> Result<(), ContractError> {
+        Self::execute_spin_with_ttl(env, spin_id, spin_hash, signature, executor, None)
+    }
+
+    pub fn execute_spin_with_ttl(
+        env: Env,
+        spin_id: BytesN<32>,
+        spin_hash: BytesN<32>,
+        signature: BytesN<64>,
+        executor: Address,
+        ttl_seconds: Option<u64>,
+    ) -> Result<(), ContractError> {
+        record_spin(
+            &env,
+            spin_id,
+            spin_hash,
+            signature,
+            executor,
+            None,
+            ttl_seconds,
+        )
+    }
+
+    /// Execute a spin and keep its outcome in the player's history. The
+    /// sha256 of the outcome's XDR is the spin hash the backend signs and
+    /// the one used for replay protection.
+    pub fn execute_spin_with_outcome(
+        env: Env,
+        spin_id: BytesN<32>,
+        outcome: SpinOutcome,
+        signature: BytesN<64>,
+        executor: Address,
+        ttl_seconds: Option<u64>,
+    ) -> Result<(), ContractError> {
+        if outcome.wager < 0 || outcome.payout < 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let spin_hash = env.crypto().sha256(&outcome.clone().to_xdr(&env)).into();
+        record_spin(
+            &env,
+            spin_id,
+            spin_hash,
+            signature,
+            executor,
+            Some(outcome),
+            ttl_seconds,
+        )
+    }
+
+    /// Check if a spin has already been executed
+    pub fn is_spin_executed(env: Env, spin_id: BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::SpinExecution(spin_id))
+    }
+
+    /// Page of `player`'s spins, oldest first. Spins whose records have
+    /// expired are skipped. `limit` is capped at `MAX_PAGE_SIZE`.
+    pub fn get_player_spins(
+        env: Env,
+        player: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<SpinExecution> {
+        let storage = env.storage().persistent();
+        let end = offset
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(get_player_spin_count(&env, &player));
+
+        let mut spins = Vec::new(&env);
+        for index in offset..end {
+            let spin_id: Option<BytesN<32>> =
+                storage.get(&DataKey::PlayerSpin(player.clone(), index));
+            if let Some(execution) =
+                spin_id.and_then(|spin_id| storage.get(&DataKey::SpinExecution(spin_id)))
+            {
+                spins.push_back(execution);
+            }
+        }
+        spins
+    }
+
+    pub fn get_player_spin_count(env: Env, player: Address) -> u32 {
+        get_player_spin_count(&env, &player)
+    }
+
+    /// Get spin execution details
+    pub fn get_spin_execution(
+        env: Env,
+        spin_id: BytesN<32>,
+    ) -> Result<SpinExecution, ContractError> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SpinExecution(spin_id))
+            .ok_or(ContractError::SpinNotFound)
+    }
+
+    pub fn is_spin_hash_used(env: Env, spin_hash: BytesN<32>) -> bool {
+        is_operation_executed(&env, Symbol::new(&env, "spin_exec"), spin_hash)
+    }
+
+    pub fn cleanup_spin_hash(env: Env, spin_hash: BytesN<32>) -> bool {
+        cleanup_operation(&env, Symbol::new(&env, "spin_exec"), spin_hash)
+    }
+
+    /// Configure the guardian set used for emergency response
+    pub fn set_guardians(
+        env: Env,
+        admin: Address,
+        guardians: Vec<Address>,
+        threshold: u32,
+        recovery_delay: u64,
+    ) -> Result<GuardianConfig, ContractError> {
+        Self::require_admin(&env, &admin)?;
+        set_guardian_config(&env, guardians, threshold, recovery_delay)
+    }
+
+    pub fn get_guardians(env: Env) -> Option<GuardianConfig> {
+        get_guardian_config(&env)
+    }
+
+    /// Halt betting and spins; only the admin can lift the pause
+    pub fn guardian_pause(env: Env, guardian: Address) -> Result<(), ContractError> {
+        guardian_pause(&env, &guardian)
+    }
+
+    /// Stop a compromised backend key from authorizing spins
+    pub fn freeze_operator(
+        env: Env,
+        guardian: Address,
+        operator: Address,
+    ) -> Result<(), ContractError> {
+        freeze_operator(&env, &guardian, &operator)
+    }
+
+    pub fn unfreeze_operator(
+        env: Env,
+        admin: Address,
+        operator: Address,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        unfreeze_operator(&env, &operator);
+        Ok(())
+    }
+
+    pub fn is_operator_frozen(env: Env, operator: Address) -> bool {
+        is_operator_frozen(&env, &operator)
+    }
+
+    /// Guardian vote to rotate the backend signer to `new_admin`
+    pub fn approve_recovery(
+        env: Env,
+        guardian: Address,
+        new_admin: Address,
+    ) -> Result<RecoveryRequest, ContractError> {
+        approve_recovery(&env, &guardian, &new_admin)
+    }
+
+    pub fn get_pending_recovery(env: Env) -> Option<RecoveryRequest> {
+        get_pending_recovery(&env)
+    }
+
+    pub fn cancel_recovery(env: Env, admin: Address) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        cancel_recovery(&env)
+    }
+
+    /// Install the recovered backend signer once the delay has passed.
+    /// Anyone may trigger this; the outcome was fixed by the guardians.
+    pub fn execute_recovery(env: Env) -> Result<Address, ContractError> {
+        let new_admin = execute_recovery(&env)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::BackendSigner, &new_admin);
+        Ok(new_admin)
+    }
+
+    // Only backend signer (acting as admin) can change settings
+    // In a real scenario, you'd have a separate admin role
+    fn verify_admin(env: &Env, admin: &Address) -> Result<Address, ContractError> {
+        let backend_signer: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::BackendSigner)
+            .ok_or(ContractError::Unauthorized)?;
+
+        if *admin != backend_signer {
+            return Err(ContractError::Unauthorized);
+        }
+        Ok(backend_signer)
+    }
+
+    // The pausable helpers take the verified admin and require its auth
+    // themselves; everything else goes through `require_admin`.
+    fn require_admin(env: &Env, admin: &Address) -> Result<(), ContractError> {
+        Self::verify_admin(env, admin)?.require_auth();
+        Ok(())
+    }
+}
+
+fn jackpot_share(env: &Env, token: &Address, amount: i128) -> Result<i128, ContractError> {
+    match env
+        .storage()
+        .persistent()
+        .get::<_, Address>(&DataKey::Jackpot)
+    {
+        Some(jackpot) => quote_jackpot_contribution(env, &jackpot, token, amount),
+        None => Ok(0),
+    }
+}
+
+fn pay_jackpot_share(
+    env: &Env,
+    token: &Address,
+    amount: i128,
+    expected_share: i128,
+) -> Result<(), ContractError> {
+    if expected_share == 0 {
+        return Ok(());
+    }
+    let jackpot: Address = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Jackpot)
+        .ok_or(ContractError::NotInitialized)?;
+    contribute_to_jackpot(env, &jackpot, token, amount, expected_share)
+}
+
+// Jackpot shares and fees are paid in tokens, which ledger-mode escrow
+// never holds
+fn require_token_escrow(env: &Env) -> Result<(), ContractError> {
+    match get_escrow_mode(env) {
+        EscrowMode::Token => Ok(()),
+        EscrowMode::Ledger(_) => Err(ContractError::InvalidConfig),
+    }
+}
+
+fn fee_amount(fee: &Option<(i128, Address)>) -> i128 {
+    fee.as_ref().map(|(amount, _)| *amount).unwrap_or(0)
+}
+
+fn forward_fee(
+    env: &Env,
+    bettor: &Address,
+    token: &Address,
+    fee: Option<(i128, Address)>,
+) -> Result<(), ContractError> {
+    if let Some((amount, fee_collector)) = fee {
+        token::Client::new(env, token).transfer(
+            &env.current_contract_address(),
+            &fee_collector,
+            &amount,
+        );
+        record_fee(env, bettor, Some(token.clone()), amount, &fee_collector)?;
+    }
+    Ok(())
+}
+
+fn report_bet_volume(env: &Env, bettor: &Address, amount: i128) -> Result<(), ContractError> {
+    if let Some(referral) = env
+        .storage()
+        .persistent()
+        .get::<_, Address>(&DataKey::Referral)
+    {
+        report_referral_volume(env, &referral, bettor, amount)?;
+    }
+    Ok(())
+}
+
+fn is_active(bet: &Bet) -> bool {
+    matches!(bet.status, BetStatus::Open | BetStatus::Locked)
+}
+
+fn get_bet(env: &Env, bet_id: u64) -> Option<Bet> {
+    let bet_key = DataKey::Bet(bet_id);
+    common::bump_persistent(env, &bet_key);
+    env.storage().persistent().get(&bet_key)
+}
+
+fn get_latest_bet(env: &Env, match_id: &BytesN<32>, bettor: &Address) -> Option<Bet> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::LatestBet(match_id.clone(), bettor.clone()))
+        .and_then(|bet_id| get_bet(env, bet_id))
+}
+
+// Bets that still hold escrow; final ones are reported as `InvalidStatus`
+fn get_active_bet(env: &Env, bet_id: u64) -> Result<Bet, ContractError> {
+    let bet = get_bet(env, bet_id).ok_or(ContractError::BetNotFound)?;
+    if !is_active(&bet) {
+        return Err(ContractError::InvalidStatus);
+    }
+    Ok(bet)
+}
+
+fn store_bet(env: &Env, bet: &Bet) {
+    let bet_key = DataKey::Bet(bet.bet_id);
+    env.storage().persistent().set(&bet_key, bet);
+    common::bump_persistent(env, &bet_key);
+}
+
+// Move an active bet to a final status: it stops counting towards the
+// match's exposure and drops out of the open-bet indexes, but its record
+// stays queryable.
+fn close_bet(env: &Env, mut bet: Bet, status: BetStatus) -> Bet {
+    bet.status = status;
+    store_bet(env, &bet);
+    remove_from_bet_indexes(env, &bet);
+    remove_bet_exposure(env, &bet);
+    publish_bet_status_event(env, &bet);
+    bet
+}
+
+fn get_parlay(env: &Env, parlay_id: u64) -> Result<Parlay, ContractError> {
+    let key = DataKey::Parlay(parlay_id);
+    common::bump_persistent(env, &key);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::BetNotFound)
+}
+
+fn get_open_parlay(env: &Env, parlay_id: u64) -> Result<Parlay, ContractError> {
+    let parlay = get_parlay(env, parlay_id)?;
+    if parlay.status != BetStatus::Open {
+        return Err(ContractError::InvalidStatus);
+    }
+    Ok(parlay)
+}
+
+fn store_parlay(env: &Env, parlay: &Parlay) {
+    let key = DataKey::Parlay(parlay.parlay_id);
+    env.storage().persistent().set(&key, parlay);
+    common::bump_persistent(env, &key);
+}
+
+// Parlays leave their legs' exposure as soon as they reach a final status
+fn close_parlay(env: &Env, mut parlay: Parlay, status: BetStatus) -> Parlay {
+    parlay.status = status;
+    store_parlay(env, &parlay);
+    remove_parlay_exposure(env, &parlay);
+    publish_parlay_status_event(env, &parlay);
+    parlay
+}
+
+// Settled stakes count as referral volume; cancelled and refunded ones never
+// do, so nothing has to be clawed back from referrers.
+fn settle_bet(env: &Env, bet: Bet) -> Result<Bet, ContractError> {
+    let bet = close_bet(env, bet, BetStatus::Settled);
+    report_bet_volume(env, &bet.bettor, bet.amount)?;
+    Ok(bet)
+}
+
+fn require_settlement_contract(env: &Env) -> Result<(), ContractError> {
+    let settlement: Address = env
+        .storage()
+        .persistent()
+        .get(&DataKey::SettlementContract)
+        .ok_or(ContractError::NotInitialized)?;
+    settlement.require_auth();
+    Ok(())
+}
+
+fn require_not_self_excluded(env: &Env, user: &Address) -> Result<(), ContractError> {
+    if let Some(registry) = env
+        .storage()
+        .persistent()
+        .get::<_, Address>(&DataKey::SelfExclusionRegistry)
+    {
+        common::require_not_self_excluded(env, &registry, user)?;
+    }
+    Ok(())
+}
+
+fn refund_bet(
+    env: &Env,
+    bet: Bet,
+    status: BetStatus,
+    cancelled_by: &Address,
+) -> Result<Bet, ContractError> {
+    let bet = close_bet(env, bet, status);
+    release_stake(env, &bet.bettor, &bet.token, bet.amount)?;
+
+    let event = BetCancelledEvent {
+        version: EVENT_SCHEMA_VERSION,
+        bettor: bet.bettor.clone(),
+        match_id: bet.match_id.clone(),
+        amount: bet.amount,
+        cancelled_by: cancelled_by.clone(),
+        timestamp: env.ledger().timestamp(),
+    };
+    publish_bet_cancelled_event(env, event);
+
+    Ok(bet)
+}
+
+// Open bets are indexed by id per match and per bettor, so any number of
+// bets by one bettor on one match are kept apart.
+fn add_to_bet_indexes(env: &Env, bet: &Bet) {
+    let storage = env.storage().persistent();
+    for key in [
+        DataKey::MatchBets(bet.match_id.clone()),
+        DataKey::UserBets(bet.bettor.clone()),
+    ] {
+        let mut bet_ids = get_bet_ids(env, &key);
+        bet_ids.push_back(bet.bet_id);
+        storage.set(&key, &bet_ids);
+    }
+    storage.set(
+        &DataKey::LatestBet(bet.match_id.clone(), bet.bettor.clone()),
+        &bet.bet_id,
+    );
+}
+
+fn remove_from_bet_indexes(env: &Env, bet: &Bet) {
+    let storage = env.storage().persistent();
+    for key in [
+        DataKey::MatchBets(bet.match_id.clone()),
+        DataKey::UserBets(bet.bettor.clone()),
+    ] {
+        let mut bet_ids = get_bet_ids(env, &key);
+        if let Some(index) = bet_ids.first_index_of(bet.bet_id) {
+            bet_ids.remove(index);
+            storage.set(&key, &bet_ids);
+        }
+    }
+}
+
+fn get_bet_ids(env: &Env, key: &DataKey) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(key)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn bets_page(env: &Env, key: &DataKey, offset: u32, limit: u32) -> Vec<Bet> {
+    let mut bets = Vec::new(env);
+    for bet_id in page(&get_bet_ids(env, key), offset, limit).iter() {
+        if let Some(bet) = env.storage().persistent().get(&DataKey::Bet(bet_id)) {
+            bets.push_back(bet);
+        }
+    }
+    bets
+}
+
+fn page<T>(items: &Vec<T>, offset: u32, limit: u32) -> Vec<T>
+where
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    let start = offset.min(items.len());
+    let end = start
+        .saturating_add(limit.min(MAX_PAGE_SIZE))
+        .min(items.len());
+    items.slice(start..end)
+}
+
+fn publish_bet_placed_event(env: &Env, match_id: &BytesN<32>, event: BetPlacedEvent) {
+    let subject = event.bettor.clone();
+    publish_versioned_event(
+        env,
+        (Symbol::new(env, "bet_placed"), match_id.clone()),
+        BET_PLACED_EVENT,
+        subject,
+        event,
+    );
+}
+
+fn publish_wager_placed_event(env: &Env, event: WagerPlacedEvent) {
+    publish_versioned_event(
+        env,
+        (
+            Symbol::new(env, "wager_placed"),
+            event.match_id.clone(),
+            event.bet_id,
+        ),
+        WAGER_PLACED_EVENT,
+        event.bettor.clone(),
+        event,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_parlay_placed_event(env: &Env, parlay: &Parlay) {
+    env.events().publish(
+        (
+            Symbol::new(env, "parlay_placed"),
+            parlay.bettor.clone(),
+            parlay.parlay_id,
+        ),
+        (parlay.amount, parlay.legs.len(), parlay.combined_odds),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_parlay_status_event(env: &Env, parlay: &Parlay) {
+    env.events().publish(
+        (
+            Symbol::new(env, "parlay_status"),
+            parlay.bettor.clone(),
+            parlay.parlay_id,
+        ),
+        parlay.status,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_bet_status_event(env: &Env, bet: &Bet) {
+    env.events().publish(
+        (
+            Symbol::new(env, "bet_status"),
+            bet.match_id.clone(),
+            bet.bettor.clone(),
+        ),
+        bet.status,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_cash_out_event(env: &Env, bet: &Bet, offer_amount: i128, operation_hash: &BytesN<32>) {
+    env.events().publish(
+        (
+            Symbol::new(env, "bet_cashed_out"),
+            bet.match_id.clone(),
+            bet.bettor.clone(),
+        ),
+        (
+            bet.token.clone(),
+            bet.amount,
+            offer_amount,
+            operation_hash.clone(),
+        ),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_escrow_released_event(
+    env: &Env,
+    bet: &Bet,
+    to: &Address,
+    amount: i128,
+    operation_hash: &BytesN<32>,
+) {
+    env.events().publish(
+        (
+            Symbol::new(env, "escrow_released"),
+            bet.match_id.clone(),
+            bet.bettor.clone(),
+        ),
+        (
+            to.clone(),
+            bet.token.clone(),
+            amount,
+            operation_hash.clone(),
+        ),
+    );
+}
+
+fn publish_bet_cancelled_event(env: &Env, event: BetCancelledEvent) {
+    publish_versioned_event(
+        env,
+        (
+            Symbol::new(env, "bet_cancelled"),
+            event.match_id.clone(),
+            event.bettor.clone(),
+        ),
+        BET_CANCELLED_EVENT,
+        event.bettor.clone(),
+        event,
+    );
+}
+
+// Shared by the spin entrypoints: checks the backend signature and replay
+// guards, then stores the execution and indexes it for the executor.
+fn record_spin(
+    env: &Env,
+    spin_id: BytesN<32>,
+    spin_hash: BytesN<32>,
+    signature: BytesN<64>,
+    executor: Address,
+    outcome: Option<SpinOutcome>,
+    ttl_seconds: Option<u64>,
+) -> Result<(), ContractError> {
+    executor.require_auth();
+    require_not_paused(env)?;
+    require_not_self_excluded(env, &executor)?;
+
+    let storage = env.storage().persistent();
+
+    // Get backend signer
+    let backend_signer: Address = storage
+        .get(&DataKey::BackendSigner)
+        .ok_or(ContractError::Unauthorized)?;
+    require_operator_not_frozen(env, &backend_signer)?;
+
+    // Binding the contract, spin id and executor keeps a signature from
+    // being replayed elsewhere; a mismatch traps in the host.
+    let backend_pubkey: BytesN<32> = storage
+        .get(&DataKey::BackendPubkey)
+        .ok_or(ContractError::InvalidSignature)?;
+    let message = (
+        env.current_contract_address(),
+        spin_id.clone(),
+        executor.clone(),
+        spin_hash.clone(),
+    )
+        .to_xdr(env);
+    env.crypto()
+        .ed25519_verify(&backend_pubkey, &message, &signature);
+
+    ensure_not_replayed(
+        env,
+        Symbol::new(env, "spin_exec"),
+        spin_hash.clone(),
+        ttl_seconds.map(|ttl| ttl.max(MIN_SPIN_HASH_TTL_SECONDS)),
+    )?;
+
+    // Check for duplicate execution on spin ID
+    let execution_key = DataKey::SpinExecution(spin_id.clone());
+    if storage.has(&execution_key) {
+        return Err(ContractError::SpinAlreadyExecuted);
+    }
+
+    let current_time = env.ledger().timestamp();
+
+    let execution = SpinExecution {
+        spin_id: spin_id.clone(),
+        executor: executor.clone(),
+        timestamp: current_time,
+        result_hash: outcome.as_ref().map(|outcome| outcome.result_hash.clone()),
+        wager: outcome.as_ref().map_or(0, |outcome| outcome.wager),
+        payout: outcome.as_ref().map_or(0, |outcome| outcome.payout),
+        token: outcome.map(|outcome| outcome.token),
+    };
+
+    storage.set(&execution_key, &execution);
+    storage.extend_ttl(
+        &execution_key,
+        SPIN_EXECUTION_TTL_THRESHOLD,
+        SPIN_EXECUTION_TTL_EXTEND_TO,
+    );
+    index_player_spin(env, &executor, &spin_id);
+
+    // Emit execution event
+    let event = SpinExecutedEvent {
+        version: EVENT_SCHEMA_VERSION,
+        spin_id: spin_id.clone(),
+        executor: executor.clone(),
+        timestamp: current_time,
+    };
+
+    publish_spin_executed_event(env, event);
+
+    Ok(())
+}
+
+fn get_player_spin_count(env: &Env, player: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PlayerSpinCount(player.clone()))
+        .unwrap_or(0)
+}
+
+// One entry per spin, with the same TTL as the execution record it points
+// to, so a long history never lands in one growing entry.
+fn index_player_spin(env: &Env, player: &Address, spin_id: &BytesN<32>) {
+    let storage = env.storage().persistent();
+    let index = get_player_spin_count(env, player);
+    let key = DataKey::PlayerSpin(player.clone(), index);
+    storage.set(&key, spin_id);
+    storage.extend_ttl(
+        &key,
+        SPIN_EXECUTION_TTL_THRESHOLD,
+        SPIN_EXECUTION_TTL_EXTEND_TO,
+    );
+    storage.set(&DataKey::PlayerSpinCount(player.clone()), &(index + 1));
+}
+
+fn publish_spin_executed_event(env: &Env, event: SpinExecutedEvent) {
+    publish_versioned_event(
+        env,
+        (Symbol::new(env, "spin_executed"),),
+        SPIN_EXECUTED_EVENT,
+        event.spin_id.clone(),
+        event,
+    );
+}
+
 #[cfg(test)]
 mod test;
@@ -0,0 +1,158 @@
+use crate::{DataKey, ODDS_SCALE};
+use common::{
+    publish_versioned_event, ContractError, OddsUpdatedEvent, EVENT_SCHEMA_VERSION,
+    ODDS_UPDATED_EVENT,
+};
+use soroban_sdk::{contracttype, BytesN, Env, Symbol};
+
+/// Current line for one (match, bet type) market, as published by the backend.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarketOdds {
+    pub odds: u32,
+    pub updated_at: u64,
+}
+
+pub fn get_market_odds(env: &Env, match_id: &BytesN<32>, bet_type: &Symbol) -> Option<MarketOdds> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Odds(match_id.clone(), bet_type.clone()))
+}
+
+/// Publish a new line. Odds must pay out more than the stake.
+pub fn set_market_odds(
+    env: &Env,
+    match_id: &BytesN<32>,
+    bet_type: &Symbol,
+    odds: u32,
+) -> Result<MarketOdds, ContractError> {
+    if (odds as u64) <= ODDS_SCALE {
+        return Err(ContractError::InvalidConfig);
+    }
+
+    let previous = get_market_odds(env, match_id, bet_type);
+    let market = MarketOdds {
+        odds,
+        updated_at: env.ledger().timestamp(),
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::Odds(match_id.clone(), bet_type.clone()), &market);
+
+    publish_odds_updated_event(
+        env,
+        OddsUpdatedEvent {
+            version: EVENT_SCHEMA_VERSION,
+            match_id: match_id.clone(),
+            bet_type: bet_type.clone(),
+            previous_odds: previous.map(|market| market.odds),
+            odds,
+            timestamp: market.updated_at,
+        },
+    );
+
+    Ok(market)
+}
+
+/// Take a market off the board; no new bets are accepted on it.
+pub fn remove_market_odds(
+    env: &Env,
+    match_id: &BytesN<32>,
+    bet_type: &Symbol,
+) -> Result<(), ContractError> {
+    let key = DataKey::Odds(match_id.clone(), bet_type.clone());
+    if !env.storage().persistent().has(&key) {
+        return Err(ContractError::MarketNotFound);
+    }
+    env.storage().persistent().remove(&key);
+    Ok(())
+}
+
+/// Odds a new bet is taken at. `accepted_odds` is the price the bettor saw;
+/// the bet fails with `OddsChanged` if the line has since moved below it.
+pub fn odds_for_bet(
+    env: &Env,
+    match_id: &BytesN<32>,
+    bet_type: &Symbol,
+    accepted_odds: u32,
+) -> Result<u32, ContractError> {
+    let market = get_market_odds(env, match_id, bet_type).ok_or(ContractError::MarketNotFound)?;
+    if market.odds < accepted_odds {
+        return Err(ContractError::OddsChanged);
+    }
+    Ok(market.odds)
+}
+
+fn publish_odds_updated_event(env: &Env, event: OddsUpdatedEvent) {
+    publish_versioned_event(
+        env,
+        (
+            Symbol::new(env, "odds_updated"),
+            event.match_id.clone(),
+            event.bet_type.clone(),
+        ),
+        ODDS_UPDATED_EVENT,
+        event.match_id.clone(),
+        event,
+    );
+}
+
+/// Time from which `match_id` takes no more bets, if one is set.
+pub fn get_close_time(env: &Env, match_id: &BytesN<32>) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::CloseTime(match_id.clone()))
+}
+
+/// Move the close time of `match_id`. A market that was announced as
+/// closed reopens if the new time is still ahead.
+pub fn set_close_time(env: &Env, match_id: &BytesN<32>, close_time: u64) {
+    let storage = env.storage().persistent();
+    storage.set(&DataKey::CloseTime(match_id.clone()), &close_time);
+    if close_time > env.ledger().timestamp() {
+        storage.remove(&DataKey::MarketClosed(match_id.clone()));
+    }
+    publish_close_time_event(env, match_id, close_time);
+}
+
+/// Fail with `BettingClosed` once `match_id` has reached its close time.
+pub fn require_betting_open(env: &Env, match_id: &BytesN<32>) -> Result<(), ContractError> {
+    if let Some(close_time) = get_close_time(env, match_id) {
+        if env.ledger().timestamp() >= close_time {
+            return Err(ContractError::BettingClosed);
+        }
+    }
+    Ok(())
+}
+
+/// Announce that `match_id` has closed, once per close time.
+pub fn close_market(env: &Env, match_id: &BytesN<32>) -> Result<u64, ContractError> {
+    let close_time = get_close_time(env, match_id).ok_or(ContractError::MatchNotFound)?;
+    if env.ledger().timestamp() < close_time {
+        return Err(ContractError::MatchNotOpen);
+    }
+    let storage = env.storage().persistent();
+    let closed_key = DataKey::MarketClosed(match_id.clone());
+    if storage.has(&closed_key) {
+        return Err(ContractError::InvalidStatus);
+    }
+    storage.set(&closed_key, &true);
+    publish_market_closed_event(env, match_id, close_time);
+    Ok(close_time)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_close_time_event(env: &Env, match_id: &BytesN<32>, close_time: u64) {
+    env.events().publish(
+        (Symbol::new(env, "close_time_updated"), match_id.clone()),
+        close_time,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_market_closed_event(env: &Env, match_id: &BytesN<32>, close_time: u64) {
+    env.events().publish(
+        (Symbol::new(env, "market_closed"), match_id.clone()),
+        close_time,
+    );
+}
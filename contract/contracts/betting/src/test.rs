@@ -1,9 +1,12 @@
 #![cfg(test)]
+extern crate std;
 
 use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use match_registry::{MatchRegistryContract, MatchRegistryContractClient};
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
-    token, Address, BytesN, Env, Symbol,
+    testutils::{storage::Persistent as _, Address as _, Events as _, Ledger},
+    token, vec, Address, BytesN, Env, Map, Symbol, Vec,
 };
 
 fn setup_test(env: &Env) -> (BettingContractClient<'_>, Address, Address) {
@@ -12,15 +15,71 @@ fn setup_test(env: &Env) -> (BettingContractClient<'_>, Address, Address) {
     let backend_signer = Address::generate(env);
     let bettor = Address::generate(env);
     client.initialize(&backend_signer);
+    client.set_backend_pubkey(&backend_signer, &backend_pubkey(env));
     (client, backend_signer, bettor)
 }
 
+fn publish_leg_odds(
+    client: &BettingContractClient<'_>,
+    backend_signer: &Address,
+    legs: &Vec<(BytesN<32>, Symbol, u32)>,
+) {
+    for (match_id, bet_type, odds) in legs.iter() {
+        client.set_odds(backend_signer, &match_id, &bet_type, &odds);
+    }
+}
+
+fn backend_key() -> SigningKey {
+    SigningKey::from_bytes(&[7u8; 32])
+}
+
+fn backend_pubkey(env: &Env) -> BytesN<32> {
+    BytesN::from_array(env, &backend_key().verifying_key().to_bytes())
+}
+
+fn sign_spin(
+    env: &Env,
+    client: &BettingContractClient<'_>,
+    spin_id: &BytesN<32>,
+    executor: &Address,
+    spin_hash: &BytesN<32>,
+) -> BytesN<64> {
+    let message = (
+        client.address.clone(),
+        spin_id.clone(),
+        executor.clone(),
+        spin_hash.clone(),
+    )
+        .to_xdr(env);
+    let mut bytes = std::vec![0u8; message.len() as usize];
+    message.copy_into_slice(&mut bytes);
+    BytesN::from_array(env, &backend_key().sign(&bytes).to_bytes())
+}
+
+#[test]
+fn initialize_only_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    assert_eq!(
+        client.try_initialize(&bettor),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+    assert_eq!(
+        client.try_initialize_with_ledger(&bettor, &bettor),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+    // The original signer stays in control
+    client.set_settlement_contract(&backend_signer, &bettor);
+}
+
 #[test]
 fn test_place_bet_success() {
     let env = Env::default();
     env.mock_all_auths();
 
-    let (client, _backend_signer, bettor) = setup_test(&env);
+    let (client, backend_signer, bettor) = setup_test(&env);
     let token_admin = Address::generate(&env);
     let token_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
     let token_id = token_contract.address();
@@ -33,8 +92,11 @@ fn test_place_bet_success() {
     let match_id = BytesN::from_array(&env, &[1u8; 32]);
     let bet_type = Symbol::new(&env, "win");
     let odds = 200; // 2.00
+    client.set_odds(&backend_signer, &match_id, &bet_type, &odds);
 
-    let result = client.try_place_bet(&bettor, &token_id, &amount, &match_id, &bet_type, &odds);
+    let result = client.try_place_bet(
+        &bettor, &token_id, &amount, &match_id, &bet_type, &odds, &None,
+    );
     assert!(result.is_ok());
 
     // Verify funds were transferred
@@ -59,16 +121,21 @@ fn test_prevent_double_betting() {
     let match_id = BytesN::from_array(&env, &[1u8; 32]);
     let bet_type = Symbol::new(&env, "win");
     let odds = 200;
+    client.set_odds(&backend_signer, &match_id, &bet_type, &odds);
 
     // Enable double betting prevention
     client.set_prevent_double_betting(&backend_signer, &true);
     assert!(client.is_double_betting_prevented());
 
     // First bet
-    client.place_bet(&bettor, &token_id, &amount, &match_id, &bet_type, &odds);
+    client.place_bet(
+        &bettor, &token_id, &amount, &match_id, &bet_type, &odds, &None,
+    );
 
     // Second bet on same match by same user should fail
-    let result = client.try_place_bet(&bettor, &token_id, &amount, &match_id, &bet_type, &odds);
+    let result = client.try_place_bet(
+        &bettor, &token_id, &amount, &match_id, &bet_type, &odds, &None,
+    );
     assert!(result.is_err());
 }
 
@@ -89,16 +156,133 @@ fn test_allow_double_betting_when_disabled() {
     let match_id = BytesN::from_array(&env, &[1u8; 32]);
     let bet_type = Symbol::new(&env, "win");
     let odds = 200;
+    client.set_odds(&backend_signer, &match_id, &bet_type, &odds);
 
     // Ensure double betting is allowed (default)
     assert!(!client.is_double_betting_prevented());
 
     // First bet
-    client.place_bet(&bettor, &token_id, &amount, &match_id, &bet_type, &odds);
+    let first = client.place_bet(
+        &bettor, &token_id, &amount, &match_id, &bet_type, &odds, &None,
+    );
 
     // Second bet on same match by same user should succeed
-    let result = client.try_place_bet(&bettor, &token_id, &amount, &match_id, &bet_type, &odds);
+    let result = client.try_place_bet(
+        &bettor, &token_id, &amount, &match_id, &bet_type, &odds, &None,
+    );
     assert!(result.is_ok());
+
+    // Both bets are kept and count towards the match on their own
+    let second = client.get_bet(&match_id, &bettor).unwrap();
+    assert_ne!(second.bet_id, first);
+    assert_eq!(client.get_bet_by_id(&first).unwrap().amount, amount);
+    assert_eq!(client.get_bet_count_for_user(&bettor), 2);
+    assert_eq!(client.get_match_exposure(&match_id).total_stake, amount * 2);
+}
+
+#[test]
+fn test_place_bet_requires_open_match_in_registry() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_admin = Address::generate(&env);
+    let token_contract = env.register_stellar_asset_contract_v2(token_admin);
+    let token_id = token_contract.address();
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&bettor, &3000);
+
+    let registry_id = env.register(MatchRegistryContract, ());
+    let registry = MatchRegistryContractClient::new(&env, &registry_id);
+    registry.initialize(&backend_signer);
+    client.set_match_registry(&backend_signer, &registry_id);
+    assert_eq!(client.get_match_registry(), Some(registry_id));
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+
+    // Unregistered matches are rejected
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::MatchNotFound))
+    );
+
+    registry.create_match(&match_id, &1_000, &900, &Map::new(&env));
+    client.place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None);
+
+    // Locked matches no longer accept bets
+    registry.lock_match(&match_id);
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::MatchNotOpen))
+    );
+}
+
+#[test]
+fn place_bet_rejected_after_registry_close_time() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &3000);
+
+    let registry_id = env.register(MatchRegistryContract, ());
+    let registry = MatchRegistryContractClient::new(&env, &registry_id);
+    registry.initialize(&backend_signer);
+    client.set_match_registry(&backend_signer, &registry_id);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    registry.create_match(&match_id, &1_000, &900, &Map::new(&env));
+
+    env.ledger().with_mut(|li| li.timestamp = 900);
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::MatchNotOpen))
+    );
+}
+
+#[test]
+fn bets_on_cancelled_matches_can_be_refunded() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &1000);
+
+    let registry_id = env.register(MatchRegistryContract, ());
+    let registry = MatchRegistryContractClient::new(&env, &registry_id);
+    registry.initialize(&backend_signer);
+    client.set_match_registry(&backend_signer, &registry_id);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    registry.create_match(&match_id, &1_000, &900, &Map::new(&env));
+    let bet_id = client.place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None);
+
+    let keeper = Address::generate(&env);
+    assert_eq!(
+        client.try_refund_cancelled_bet(&keeper, &bet_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    registry.cancel_match(&match_id);
+    client.refund_cancelled_bet(&keeper, &bet_id);
+    assert_eq!(token_client.balance(&bettor), 1000);
+    assert_eq!(
+        client.get_bet(&match_id, &bettor).unwrap().status,
+        BetStatus::Refunded
+    );
 }
 
 #[test]
@@ -110,7 +294,7 @@ fn executes_spin_once_per_spin_id() {
 
     let spin_id = BytesN::from_array(&env, &[1u8; 32]);
     let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
-    let signature = BytesN::from_array(&env, &[3u8; 64]);
+    let signature = sign_spin(&env, &client, &spin_id, &executor, &spin_hash);
 
     client.execute_spin(&spin_id, &spin_hash, &signature, &executor);
     assert_eq!(
@@ -127,20 +311,21 @@ fn rejects_replay_by_spin_hash() {
     let (client, _, executor) = setup_test(&env);
 
     let spin_hash = BytesN::from_array(&env, &[9u8; 32]);
-    let signature = BytesN::from_array(&env, &[4u8; 64]);
+    let first_spin = BytesN::from_array(&env, &[7u8; 32]);
+    let second_spin = BytesN::from_array(&env, &[8u8; 32]);
 
     client.execute_spin(
-        &BytesN::from_array(&env, &[7u8; 32]),
+        &first_spin,
         &spin_hash,
-        &signature,
+        &sign_spin(&env, &client, &first_spin, &executor, &spin_hash),
         &executor,
     );
 
     assert_eq!(
         client.try_execute_spin(
-            &BytesN::from_array(&env, &[8u8; 32]),
+            &second_spin,
             &spin_hash,
-            &signature,
+            &sign_spin(&env, &client, &second_spin, &executor, &spin_hash),
             &executor,
         ),
         Err(Ok(ContractError::DuplicateOperation))
@@ -156,7 +341,7 @@ fn reports_spin_hash_usage() {
 
     let spin_id = BytesN::from_array(&env, &[10u8; 32]);
     let spin_hash = BytesN::from_array(&env, &[11u8; 32]);
-    let signature = BytesN::from_array(&env, &[5u8; 64]);
+    let signature = sign_spin(&env, &client, &spin_id, &executor, &spin_hash);
 
     assert!(!client.is_spin_hash_used(&spin_hash));
     client.execute_spin(&spin_id, &spin_hash, &signature, &executor);
@@ -172,13 +357,13 @@ fn supports_ttl_cleanup_for_spin_hashes() {
 
     let spin_id = BytesN::from_array(&env, &[12u8; 32]);
     let spin_hash = BytesN::from_array(&env, &[13u8; 32]);
-    let signature = BytesN::from_array(&env, &[6u8; 64]);
+    let signature = sign_spin(&env, &client, &spin_id, &executor, &spin_hash);
 
     client.execute_spin_with_ttl(&spin_id, &spin_hash, &signature, &executor, &Some(5));
     assert!(client.is_spin_hash_used(&spin_hash));
 
     env.ledger().with_mut(|li| {
-        li.timestamp += 6;
+        li.timestamp += MIN_SPIN_HASH_TTL_SECONDS;
     });
 
     assert!(client.cleanup_spin_hash(&spin_hash));
@@ -202,7 +387,7 @@ fn execute_spin_without_auth_fails() {
 
     let spin_id = BytesN::from_array(&env, &[1u8; 32]);
     let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
-    let signature = BytesN::from_array(&env, &[3u8; 64]);
+    let signature = sign_spin(&env, &client, &spin_id, &executor, &spin_hash);
 
     // Don't mock any auths - should fail due to executor.require_auth()
     client.execute_spin(&spin_id, &spin_hash, &signature, &executor);
@@ -288,10 +473,11 @@ fn cleanup_spin_hash_returns_false_before_ttl_expires() {
     let client = BettingContractClient::new(&env, &contract_id);
 
     client.initialize(&backend_signer);
+    client.set_backend_pubkey(&backend_signer, &backend_pubkey(&env));
 
     let spin_id = BytesN::from_array(&env, &[1u8; 32]);
     let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
-    let signature = BytesN::from_array(&env, &[3u8; 64]);
+    let signature = sign_spin(&env, &client, &spin_id, &executor, &spin_hash);
 
     // Execute with 100 second TTL
     client.execute_spin_with_ttl(&spin_id, &spin_hash, &signature, &executor, &Some(100));
@@ -317,17 +503,27 @@ fn different_spin_ids_same_hash_replay_fails() {
     let client = BettingContractClient::new(&env, &contract_id);
 
     client.initialize(&backend_signer);
+    client.set_backend_pubkey(&backend_signer, &backend_pubkey(&env));
 
     let spin_id1 = BytesN::from_array(&env, &[1u8; 32]);
     let spin_id2 = BytesN::from_array(&env, &[2u8; 32]);
     let same_spin_hash = BytesN::from_array(&env, &[3u8; 32]);
-    let signature = BytesN::from_array(&env, &[4u8; 64]);
 
     // First execution succeeds
-    client.execute_spin(&spin_id1, &same_spin_hash, &signature, &executor);
+    client.execute_spin(
+        &spin_id1,
+        &same_spin_hash,
+        &sign_spin(&env, &client, &spin_id1, &executor, &same_spin_hash),
+        &executor,
+    );
 
     // Second execution with different spin_id but same hash should fail
-    let result = client.try_execute_spin(&spin_id2, &same_spin_hash, &signature, &executor);
+    let result = client.try_execute_spin(
+        &spin_id2,
+        &same_spin_hash,
+        &sign_spin(&env, &client, &spin_id2, &executor, &same_spin_hash),
+        &executor,
+    );
     assert_eq!(result, Err(Ok(ContractError::DuplicateOperation)));
 }
 
@@ -342,17 +538,27 @@ fn same_spin_id_different_hash_fails() {
     let client = BettingContractClient::new(&env, &contract_id);
 
     client.initialize(&backend_signer);
+    client.set_backend_pubkey(&backend_signer, &backend_pubkey(&env));
 
     let same_spin_id = BytesN::from_array(&env, &[1u8; 32]);
     let spin_hash1 = BytesN::from_array(&env, &[2u8; 32]);
     let spin_hash2 = BytesN::from_array(&env, &[3u8; 32]);
-    let signature = BytesN::from_array(&env, &[4u8; 64]);
 
     // First execution succeeds
-    client.execute_spin(&same_spin_id, &spin_hash1, &signature, &executor);
+    client.execute_spin(
+        &same_spin_id,
+        &spin_hash1,
+        &sign_spin(&env, &client, &same_spin_id, &executor, &spin_hash1),
+        &executor,
+    );
 
     // Second execution with same spin_id but different hash should fail
-    let result = client.try_execute_spin(&same_spin_id, &spin_hash2, &signature, &executor);
+    let result = client.try_execute_spin(
+        &same_spin_id,
+        &spin_hash2,
+        &sign_spin(&env, &client, &same_spin_id, &executor, &spin_hash2),
+        &executor,
+    );
     assert_eq!(result, Err(Ok(ContractError::SpinAlreadyExecuted)));
 }
 
@@ -367,10 +573,11 @@ fn get_spin_execution_returns_correct_data() {
     let client = BettingContractClient::new(&env, &contract_id);
 
     client.initialize(&backend_signer);
+    client.set_backend_pubkey(&backend_signer, &backend_pubkey(&env));
 
     let spin_id = BytesN::from_array(&env, &[1u8; 32]);
     let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
-    let signature = BytesN::from_array(&env, &[3u8; 64]);
+    let signature = sign_spin(&env, &client, &spin_id, &executor, &spin_hash);
 
     // Set a specific timestamp
     env.ledger().with_mut(|li| {
@@ -383,6 +590,66 @@ fn get_spin_execution_returns_correct_data() {
     assert_eq!(execution.spin_id, spin_id);
     assert_eq!(execution.executor, executor);
     assert_eq!(execution.timestamp, 12345);
+    assert_eq!(execution.result_hash, None);
+}
+
+#[test]
+fn spin_outcomes_build_player_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, player) = setup_test(&env);
+    let token = Address::generate(&env);
+
+    for seed in 1..=3u8 {
+        let outcome = SpinOutcome {
+            result_hash: BytesN::from_array(&env, &[seed; 32]),
+            wager: 10,
+            payout: 5 * seed as i128,
+            token: token.clone(),
+        };
+        let spin_hash: BytesN<32> = env.crypto().sha256(&outcome.clone().to_xdr(&env)).into();
+        let spin_id = BytesN::from_array(&env, &[seed + 100; 32]);
+        client.execute_spin_with_outcome(
+            &spin_id,
+            &outcome,
+            &sign_spin(&env, &client, &spin_id, &player, &spin_hash),
+            &player,
+            &None,
+        );
+        assert!(client.is_spin_hash_used(&spin_hash));
+    }
+    // Spins without an outcome still show up in the history
+    let spin_id = BytesN::from_array(&env, &[42u8; 32]);
+    let spin_hash = BytesN::from_array(&env, &[42u8; 32]);
+    client.execute_spin(
+        &spin_id,
+        &spin_hash,
+        &sign_spin(&env, &client, &spin_id, &player, &spin_hash),
+        &player,
+    );
+
+    assert_eq!(client.get_player_spin_count(&player), 4);
+    let page = client.get_player_spins(&player, &1, &2);
+    assert_eq!(page.len(), 2);
+    let spin = page.get_unchecked(0);
+    assert_eq!(spin.result_hash, Some(BytesN::from_array(&env, &[2; 32])));
+    assert_eq!((spin.wager, spin.payout), (10, 10));
+    assert_eq!(spin.token, Some(token));
+    assert_eq!(page.get_unchecked(1).payout, 15);
+    assert_eq!(
+        client
+            .get_player_spins(&player, &3, &10)
+            .get_unchecked(0)
+            .result_hash,
+        None
+    );
+    assert_eq!(
+        client
+            .get_player_spins(&Address::generate(&env), &0, &10)
+            .len(),
+        0
+    );
 }
 
 #[test]
@@ -397,16 +664,26 @@ fn multiple_executors_isolated() {
     let client = BettingContractClient::new(&env, &contract_id);
 
     client.initialize(&backend_signer);
+    client.set_backend_pubkey(&backend_signer, &backend_pubkey(&env));
 
     let spin_id1 = BytesN::from_array(&env, &[1u8; 32]);
     let spin_id2 = BytesN::from_array(&env, &[2u8; 32]);
     let spin_hash1 = BytesN::from_array(&env, &[3u8; 32]);
     let spin_hash2 = BytesN::from_array(&env, &[4u8; 32]);
-    let signature = BytesN::from_array(&env, &[5u8; 64]);
 
     // Both executors can execute spins
-    client.execute_spin(&spin_id1, &spin_hash1, &signature, &executor1);
-    client.execute_spin(&spin_id2, &spin_hash2, &signature, &executor2);
+    client.execute_spin(
+        &spin_id1,
+        &spin_hash1,
+        &sign_spin(&env, &client, &spin_id1, &executor1, &spin_hash1),
+        &executor1,
+    );
+    client.execute_spin(
+        &spin_id2,
+        &spin_hash2,
+        &sign_spin(&env, &client, &spin_id2, &executor2, &spin_hash2),
+        &executor2,
+    );
 
     let execution1 = client.get_spin_execution(&spin_id1);
     let execution2 = client.get_spin_execution(&spin_id2);
@@ -416,7 +693,7 @@ fn multiple_executors_isolated() {
 }
 
 #[test]
-fn execute_spin_with_zero_ttl_immediate_cleanup() {
+fn execute_spin_with_zero_ttl_keeps_minimum_replay_window() {
     let env = Env::default();
     env.mock_all_auths();
 
@@ -426,16 +703,1987 @@ fn execute_spin_with_zero_ttl_immediate_cleanup() {
     let client = BettingContractClient::new(&env, &contract_id);
 
     client.initialize(&backend_signer);
+    client.set_backend_pubkey(&backend_signer, &backend_pubkey(&env));
 
     let spin_id = BytesN::from_array(&env, &[1u8; 32]);
     let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
-    let signature = BytesN::from_array(&env, &[3u8; 64]);
+    let signature = sign_spin(&env, &client, &spin_id, &executor, &spin_hash);
 
-    // Execute with 0 TTL - operation is immediately expired per is_expired logic
-    // (timestamp - executed_at >= 0 is always true when timestamp >= executed_at)
+    // A zero TTL is raised to the contract's minimum window
     client.execute_spin_with_ttl(&spin_id, &spin_hash, &signature, &executor, &Some(0));
+    assert!(!client.cleanup_spin_hash(&spin_hash));
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += MIN_SPIN_HASH_TTL_SECONDS - 1;
+    });
+    assert!(!client.cleanup_spin_hash(&spin_hash));
+    assert!(client.is_spin_hash_used(&spin_hash));
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 1;
+    });
+    assert!(client.cleanup_spin_hash(&spin_hash));
+}
+
+fn setup_guardians(
+    env: &Env,
+    client: &BettingContractClient,
+    admin: &Address,
+) -> (Address, Address) {
+    let guardian1 = Address::generate(env);
+    let guardian2 = Address::generate(env);
+    client.set_guardians(
+        admin,
+        &Vec::from_array(env, [guardian1.clone(), guardian2.clone()]),
+        &2,
+        &common::MIN_RECOVERY_DELAY,
+    );
+    (guardian1, guardian2)
+}
+
+#[test]
+fn guardian_pause_blocks_bets_and_spins_until_admin_unpauses() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let (guardian, _) = setup_guardians(&env, &client, &backend_signer);
 
-    // With TTL of 0, the operation is considered expired immediately
-    // so it won't be stored (it gets cleaned up during ensure_not_replayed)
-    // This is existing contract behavior, not a bug
+    let token_admin = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &1000);
+
+    assert_eq!(
+        client.try_guardian_pause(&bettor),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.guardian_pause(&guardian);
+    assert!(client.is_paused());
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::Paused))
+    );
+    assert_eq!(
+        client.try_execute_spin(
+            &BytesN::from_array(&env, &[1u8; 32]),
+            &BytesN::from_array(&env, &[2u8; 32]),
+            &BytesN::from_array(&env, &[3u8; 64]),
+            &bettor,
+        ),
+        Err(Ok(ContractError::Paused))
+    );
+
+    client.unpause(&backend_signer);
+    client.place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None);
+}
+
+#[test]
+fn frozen_backend_signer_cannot_execute_spins() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, executor) = setup_test(&env);
+    let (guardian, _) = setup_guardians(&env, &client, &backend_signer);
+
+    client.freeze_operator(&guardian, &backend_signer);
+    assert!(client.is_operator_frozen(&backend_signer));
+
+    let spin_id = BytesN::from_array(&env, &[1u8; 32]);
+    let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let signature = sign_spin(&env, &client, &spin_id, &executor, &spin_hash);
+    assert_eq!(
+        client.try_execute_spin(&spin_id, &spin_hash, &signature, &executor),
+        Err(Ok(ContractError::OperatorFrozen))
+    );
+
+    client.unfreeze_operator(&backend_signer, &backend_signer);
+    client.execute_spin(&spin_id, &spin_hash, &signature, &executor);
+}
+
+#[test]
+fn recovery_rotates_backend_signer_after_delay() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, _) = setup_test(&env);
+    let (guardian1, guardian2) = setup_guardians(&env, &client, &backend_signer);
+    let new_admin = Address::generate(&env);
+
+    let request = client.approve_recovery(&guardian1, &new_admin);
+    assert_eq!(request.executable_at, None);
+    assert_eq!(
+        client.try_execute_recovery(),
+        Err(Ok(ContractError::RecoveryNotReady))
+    );
+
+    let request = client.approve_recovery(&guardian2, &new_admin);
+    assert_eq!(request.executable_at, Some(common::MIN_RECOVERY_DELAY));
+    assert_eq!(
+        client.try_execute_recovery(),
+        Err(Ok(ContractError::RecoveryNotReady))
+    );
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += common::MIN_RECOVERY_DELAY;
+    });
+    assert_eq!(client.execute_recovery(), new_admin);
+    assert_eq!(client.get_pending_recovery(), None);
+
+    // The old key lost its admin rights
+    assert_eq!(
+        client.try_set_prevent_double_betting(&backend_signer, &true),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.set_prevent_double_betting(&new_admin, &true);
+}
+
+#[test]
+fn admin_can_cancel_pending_recovery() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, _) = setup_test(&env);
+    let (guardian1, guardian2) = setup_guardians(&env, &client, &backend_signer);
+    let new_admin = Address::generate(&env);
+
+    client.approve_recovery(&guardian1, &new_admin);
+    client.approve_recovery(&guardian2, &new_admin);
+    client.cancel_recovery(&backend_signer);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += common::MIN_RECOVERY_DELAY;
+    });
+    assert_eq!(
+        client.try_execute_recovery(),
+        Err(Ok(ContractError::RecoveryNotFound))
+    );
+    assert_eq!(
+        client.try_set_guardians(
+            &backend_signer,
+            &Vec::new(&env),
+            &1,
+            &common::MIN_RECOVERY_DELAY
+        ),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+}
+
+#[test]
+fn execute_spin_without_backend_pubkey_is_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let backend_signer = Address::generate(&env);
+    let executor = Address::generate(&env);
+    let contract_id = env.register(BettingContract, ());
+    let client = BettingContractClient::new(&env, &contract_id);
+    client.initialize(&backend_signer);
+    assert_eq!(client.get_backend_pubkey(), None);
+
+    let spin_id = BytesN::from_array(&env, &[1u8; 32]);
+    let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
+    assert_eq!(
+        client.try_execute_spin(
+            &spin_id,
+            &spin_hash,
+            &sign_spin(&env, &client, &spin_id, &executor, &spin_hash),
+            &executor,
+        ),
+        Err(Ok(ContractError::InvalidSignature))
+    );
+}
+
+#[test]
+#[should_panic]
+fn execute_spin_with_forged_signature_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, executor) = setup_test(&env);
+    let spin_id = BytesN::from_array(&env, &[1u8; 32]);
+    let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let other_hash = BytesN::from_array(&env, &[3u8; 32]);
+
+    client.execute_spin(
+        &spin_id,
+        &spin_hash,
+        &sign_spin(&env, &client, &spin_id, &executor, &other_hash),
+        &executor,
+    );
+}
+
+#[test]
+#[should_panic]
+fn spin_signature_is_bound_to_the_executor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, executor) = setup_test(&env);
+    let intruder = Address::generate(&env);
+    let spin_id = BytesN::from_array(&env, &[1u8; 32]);
+    let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
+
+    client.execute_spin(
+        &spin_id,
+        &spin_hash,
+        &sign_spin(&env, &client, &spin_id, &executor, &spin_hash),
+        &intruder,
+    );
+}
+
+#[test]
+fn only_admin_sets_backend_pubkey() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, intruder) = setup_test(&env);
+    assert_eq!(
+        client.try_set_backend_pubkey(&intruder, &BytesN::from_array(&env, &[1u8; 32])),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(client.get_backend_pubkey(), Some(backend_pubkey(&env)));
+}
+
+#[test]
+fn admin_pause_freezes_mutations_but_not_getters() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, executor) = setup_test(&env);
+
+    assert_eq!(
+        client.try_pause(&executor),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.pause(&backend_signer);
+    assert!(client.is_paused());
+
+    let spin_id = BytesN::from_array(&env, &[1u8; 32]);
+    let spin_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let signature = sign_spin(&env, &client, &spin_id, &executor, &spin_hash);
+    assert_eq!(
+        client.try_execute_spin(&spin_id, &spin_hash, &signature, &executor),
+        Err(Ok(ContractError::Paused))
+    );
+    assert!(!client.is_spin_executed(&spin_id));
+
+    client.unpause(&backend_signer);
+    client.execute_spin(&spin_id, &spin_hash, &signature, &executor);
+}
+
+#[test]
+fn backend_can_cancel_bet_and_refund_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &1000);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    let bet_id = client.place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None);
+
+    let cancelled = client.cancel_bet(&backend_signer, &bet_id);
+    assert_eq!(cancelled.amount, 1000);
+    assert_eq!(token_client.balance(&bettor), 1000);
+    assert_eq!(token_client.balance(&client.address), 0);
+
+    assert_eq!(
+        client.try_cancel_bet(&backend_signer, &bet_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_cancel_bet(&backend_signer, &(bet_id + 1)),
+        Err(Ok(ContractError::BetNotFound))
+    );
+}
+
+#[test]
+fn bettor_can_cancel_only_before_lock_time() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &2000);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    let bet_id = client.place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None);
+
+    // No lock time configured: only the backend may cancel
+    assert_eq!(
+        client.try_cancel_bet(&bettor, &bet_id),
+        Err(Ok(ContractError::MatchNotOpen))
+    );
+    assert_eq!(
+        client.try_cancel_bet(&Address::generate(&env), &bet_id),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    client.set_bet_lock_time(&backend_signer, &match_id, &200);
+    client.cancel_bet(&bettor, &bet_id);
+    assert_eq!(token_client.balance(&bettor), 2000);
+
+    let bet_id = client.place_bet(&bettor, &token_id, &1000, &match_id, &bet_type, &200, &None);
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    assert_eq!(
+        client.try_cancel_bet(&bettor, &bet_id),
+        Err(Ok(ContractError::MatchNotOpen))
+    );
+}
+
+#[test]
+fn bets_are_indexed_by_match_and_user_with_pagination() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, _) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+    let bet_type = Symbol::new(&env, "win");
+    let match_a = BytesN::from_array(&env, &[1u8; 32]);
+    let match_b = BytesN::from_array(&env, &[2u8; 32]);
+    client.set_odds(&backend_signer, &match_a, &bet_type, &200);
+    client.set_odds(&backend_signer, &match_b, &bet_type, &150);
+
+    let mut bettors = Vec::new(&env);
+    for i in 0..3 {
+        let bettor = Address::generate(&env);
+        token_admin_client.mint(&bettor, &1000);
+        client.place_bet(
+            &bettor,
+            &token_id,
+            &(100 + i),
+            &match_a,
+            &bet_type,
+            &200,
+            &None,
+        );
+        bettors.push_back(bettor);
+    }
+    let first = bettors.get(0).unwrap();
+    client.place_bet(&first, &token_id, &50, &match_b, &bet_type, &150, &None);
+    // Re-betting on the same match adds a second, separate bet
+    let rebet = client.place_bet(&first, &token_id, &10, &match_a, &bet_type, &200, &None);
+
+    assert_eq!(client.get_bet_count_for_match(&match_a), 4);
+    assert_eq!(client.get_bet_count_for_user(&first), 3);
+
+    let page = client.get_bets_for_match(&match_a, &1, &5);
+    assert_eq!(page.len(), 3);
+    assert_eq!(page.get(0).unwrap().amount, 101);
+    assert_eq!(page.get(1).unwrap().amount, 102);
+    assert_eq!(page.get(2).unwrap().amount, 10);
+    assert_eq!(client.get_bets_for_match(&match_a, &4, &5).len(), 0);
+
+    let user_bets = client.get_bets_for_user(&first, &0, &10);
+    assert_eq!(user_bets.len(), 3);
+    assert_eq!(user_bets.get(0).unwrap().amount, 100);
+    assert_eq!(user_bets.get(1).unwrap().match_id, match_b);
+    assert_eq!(user_bets.get(2).unwrap().bet_id, rebet);
+
+    // Cancelling the first bet leaves the later one on the same match alone
+    client.cancel_bet(&backend_signer, &1);
+    assert_eq!(client.get_bet_count_for_match(&match_a), 3);
+    assert_eq!(client.get_bets_for_user(&first, &0, &10).len(), 2);
+    assert_eq!(client.get_bet_status(&1), BetStatus::Cancelled);
+    assert_eq!(client.get_bet_status(&rebet), BetStatus::Open);
+    assert_eq!(client.get_bet(&match_a, &first).unwrap().bet_id, rebet);
+}
+
+#[test]
+fn spin_executions_are_stored_per_key_with_own_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _, executor) = setup_test(&env);
+
+    for i in 0..3u8 {
+        let spin_id = BytesN::from_array(&env, &[i; 32]);
+        let spin_hash = BytesN::from_array(&env, &[100 + i; 32]);
+        client.execute_spin(
+            &spin_id,
+            &spin_hash,
+            &sign_spin(&env, &client, &spin_id, &executor, &spin_hash),
+            &executor,
+        );
+    }
+
+    let spin_id = BytesN::from_array(&env, &[1u8; 32]);
+    assert!(client.is_spin_executed(&spin_id));
+    assert_eq!(client.get_spin_execution(&spin_id).spin_id, spin_id);
+
+    let ttl = env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .get_ttl(&DataKey::SpinExecution(spin_id.clone()))
+    });
+    assert!(ttl >= SPIN_EXECUTION_TTL_THRESHOLD);
+}
+
+#[test]
+fn parlay_escrows_once_and_combines_odds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &1000);
+
+    let win = Symbol::new(&env, "win");
+    let legs = soroban_sdk::vec![
+        &env,
+        (BytesN::from_array(&env, &[1u8; 32]), win.clone(), 200u32),
+        (BytesN::from_array(&env, &[2u8; 32]), win.clone(), 150u32),
+        (BytesN::from_array(&env, &[3u8; 32]), win.clone(), 333u32),
+    ];
+    publish_leg_odds(&client, &backend_signer, &legs);
+
+    let parlay_id = client.place_parlay(&bettor, &token_id, &500, &legs);
+    assert_eq!(token_client.balance(&bettor), 500);
+    assert_eq!(token_client.balance(&client.address), 500);
+
+    let parlay = client.get_parlay(&parlay_id);
+    assert_eq!(parlay.legs.len(), 3);
+    // 2.00 * 1.50 * 3.33 = 9.99
+    assert_eq!(parlay.combined_odds, 999);
+    assert_eq!(
+        potential_payout(parlay.amount, parlay.combined_odds),
+        Ok(4995)
+    );
+
+    let next_id = client.place_parlay(&bettor, &token_id, &100, &legs);
+    assert_eq!(next_id, parlay_id + 1);
+    assert_eq!(
+        client.try_get_parlay(&(next_id + 1)),
+        Err(Ok(ContractError::BetNotFound))
+    );
+}
+
+#[test]
+fn parlay_rejects_invalid_legs() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &1000);
+
+    let win = Symbol::new(&env, "win");
+    let match_a = BytesN::from_array(&env, &[1u8; 32]);
+
+    let single = soroban_sdk::vec![&env, (match_a.clone(), win.clone(), 200u32)];
+    assert_eq!(
+        client.try_place_parlay(&bettor, &token_id, &100, &single),
+        Err(Ok(ContractError::InvalidBet))
+    );
+
+    let duplicate = soroban_sdk::vec![
+        &env,
+        (match_a.clone(), win.clone(), 200u32),
+        (match_a.clone(), Symbol::new(&env, "draw"), 300u32),
+    ];
+    publish_leg_odds(&client, &backend_signer, &duplicate);
+    assert_eq!(
+        client.try_place_parlay(&bettor, &token_id, &100, &duplicate),
+        Err(Ok(ContractError::InvalidBet))
+    );
+
+    let mut too_many = Vec::new(&env);
+    for i in 0..=MAX_PARLAY_LEGS {
+        too_many.push_back((
+            BytesN::from_array(&env, &[i as u8; 32]),
+            win.clone(),
+            200u32,
+        ));
+    }
+    assert_eq!(
+        client.try_place_parlay(&bettor, &token_id, &100, &too_many),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+
+    // Combined odds that overflow are rejected rather than wrapping
+    let huge = soroban_sdk::vec![
+        &env,
+        (match_a, win.clone(), u32::MAX),
+        (BytesN::from_array(&env, &[2u8; 32]), win.clone(), u32::MAX),
+        (BytesN::from_array(&env, &[3u8; 32]), win, u32::MAX),
+    ];
+    publish_leg_odds(&client, &backend_signer, &huge);
+    assert_eq!(
+        client.try_place_parlay(&bettor, &token_id, &100, &huge),
+        Err(Ok(ContractError::InvalidBet))
+    );
+}
+
+#[test]
+fn parlays_count_against_bet_limits_and_every_leg_exposure() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &10_000);
+
+    let win = Symbol::new(&env, "win");
+    let match_a = BytesN::from_array(&env, &[1u8; 32]);
+    let match_b = BytesN::from_array(&env, &[2u8; 32]);
+    let legs = vec![
+        &env,
+        (match_a.clone(), win.clone(), 200u32),
+        (match_b.clone(), win, 150u32),
+    ];
+    publish_leg_odds(&client, &backend_signer, &legs);
+
+    client.set_bet_limits(&backend_signer, &Some(match_b.clone()), &100, &500);
+    assert_eq!(
+        client.try_place_parlay(&bettor, &token_id, &600, &legs),
+        Err(Ok(ContractError::AboveMaxBet))
+    );
+
+    // 400 at 3.00 -> a liability of 1_200 on each leg's match
+    client.set_match_limits(&backend_signer, &match_a, &1_000, &2_000);
+    let parlay_id = client.place_parlay(&bettor, &token_id, &400, &legs);
+    for match_id in [&match_a, &match_b] {
+        let exposure = client.get_match_exposure(match_id);
+        assert_eq!(
+            (exposure.total_stake, exposure.total_liability),
+            (400, 1_200)
+        );
+    }
+    assert_eq!(
+        client.try_place_parlay(&bettor, &token_id, &400, &legs),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+
+    // A closed parlay frees its legs again
+    client.refund_parlay(&backend_signer, &parlay_id);
+    assert_eq!(client.get_match_exposure(&match_b).total_liability, 0);
+    client.place_parlay(&bettor, &token_id, &400, &legs);
+}
+
+#[test]
+fn settlement_contract_settles_parlays_out_of_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let settlement = Address::generate(&env);
+    client.set_settlement_contract(&backend_signer, &settlement);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &1_000);
+    // Losing stakes fund the winnings
+    token::StellarAssetClient::new(&env, &token_id).mint(&client.address, &5_000);
+
+    let win = Symbol::new(&env, "win");
+    let legs = vec![
+        &env,
+        (BytesN::from_array(&env, &[1u8; 32]), win.clone(), 200u32),
+        (BytesN::from_array(&env, &[2u8; 32]), win, 150u32),
+    ];
+    publish_leg_odds(&client, &backend_signer, &legs);
+    let won = client.place_parlay(&bettor, &token_id, &500, &legs);
+    let lost = client.place_parlay(&bettor, &token_id, &500, &legs);
+    assert_eq!(client.get_parlay(&won).status, BetStatus::Open);
+
+    let hash = |seed: u8| BytesN::from_array(&env, &[seed; 32]);
+    assert_eq!(
+        client.try_settle_parlay(&hash(1), &won, &vec![&env, (bettor.clone(), 1_501)]),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+    let parlay = client.settle_parlay(&hash(1), &won, &vec![&env, (bettor.clone(), 1_500)]);
+    assert_eq!(parlay.status, BetStatus::Settled);
+    assert_eq!(token_client.balance(&bettor), 1_500);
+
+    client.settle_parlay(&hash(2), &lost, &Vec::new(&env));
+    assert_eq!(client.get_parlay(&lost).status, BetStatus::Settled);
+    assert_eq!(token_client.balance(&bettor), 1_500);
+
+    // Final parlays can be neither settled nor refunded again
+    assert_eq!(
+        client.try_settle_parlay(&hash(3), &won, &Vec::new(&env)),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_refund_parlay(&backend_signer, &lost),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+}
+
+#[test]
+fn settlement_contract_pays_token_escrow_parlays_out_of_betting() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let loser = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&bettor, &1_000);
+    token_admin_client.mint(&loser, &1_000);
+
+    let ledger_id = env.register(balance_ledger::BalanceLedgerContract, ());
+    let settlement_id = env.register(settlement::SettlementContract, ());
+    let settlement = settlement::SettlementContractClient::new(&env, &settlement_id);
+    settlement.initialize(&backend_signer, &ledger_id);
+    let hash = |seed: u8| BytesN::from_array(&env, &[seed; 32]);
+    let win_sym = Symbol::new(&env, "WIN");
+    assert_eq!(
+        settlement.try_settle_parlay(&hash(1), &1, &win_sym, &None),
+        Err(Ok(ContractError::NotInitialized))
+    );
+    settlement.set_betting_contract(&backend_signer, &Some(client.address.clone()));
+    client.set_settlement_contract(&backend_signer, &settlement_id);
+    let fee_collector = Address::generate(&env);
+    settlement.set_fee_config(&backend_signer, &100, &fee_collector);
+
+    let win = Symbol::new(&env, "win");
+    let legs = vec![
+        &env,
+        (BytesN::from_array(&env, &[1u8; 32]), win.clone(), 200u32),
+        (BytesN::from_array(&env, &[2u8; 32]), win, 150u32),
+    ];
+    publish_leg_odds(&client, &backend_signer, &legs);
+    let won = client.place_parlay(&bettor, &token_id, &500, &legs);
+    let lost = client.place_parlay(&loser, &token_id, &1_000, &legs);
+    let drawn = client.place_parlay(&bettor, &token_id, &500, &legs);
+
+    // 3.00x pays 1_500, 1% of it to the fee collector; the losing stake
+    // funds the winnings and the drawn stake comes back
+    let parlay = settlement.settle_parlay(&hash(1), &won, &win_sym, &None);
+    assert_eq!(parlay.status, BetStatus::Settled);
+    settlement.settle_parlay(&hash(2), &lost, &Symbol::new(&env, "LOSS"), &None);
+    settlement.settle_parlay(&hash(3), &drawn, &Symbol::new(&env, "DRAW"), &None);
+    assert_eq!(token_client.balance(&bettor), 1_485 + 500);
+    assert_eq!(token_client.balance(&fee_collector), 15);
+    assert_eq!(token_client.balance(&loser), 0);
+    assert_eq!(token_client.balance(&client.address), 0);
+    for parlay_id in [won, lost, drawn] {
+        assert_eq!(client.get_parlay(&parlay_id).status, BetStatus::Settled);
+    }
+
+    assert_eq!(
+        settlement.try_settle_parlay(&hash(4), &won, &win_sym, &None),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        settlement.try_settle_parlay(&hash(5), &99, &win_sym, &None),
+        Err(Ok(ContractError::BetNotFound))
+    );
+    assert_eq!(
+        settlement.try_settle_parlay(&hash(1), &lost, &win_sym, &None),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+}
+
+#[test]
+fn settlement_contract_settles_ledger_escrow_parlays_on_the_ledger() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let backend_signer = Address::generate(&env);
+    let ledger_id = env.register(balance_ledger::BalanceLedgerContract, ());
+    let ledger = balance_ledger::BalanceLedgerContractClient::new(&env, &ledger_id);
+    ledger.initialize(&backend_signer);
+    let settlement_id = env.register(settlement::SettlementContract, ());
+    let settlement = settlement::SettlementContractClient::new(&env, &settlement_id);
+    settlement.initialize(&backend_signer, &ledger_id);
+
+    let client = BettingContractClient::new(&env, &env.register(BettingContract, ()));
+    client.initialize_with_ledger(&backend_signer, &ledger_id);
+    client.set_settlement_contract(&backend_signer, &settlement_id);
+    settlement.set_betting_contract(&backend_signer, &Some(client.address.clone()));
+
+    let bettor = Address::generate(&env);
+    ledger.set_balance(&bettor, &1_000, &0, &None, &None);
+    let win = Symbol::new(&env, "win");
+    let legs = vec![
+        &env,
+        (BytesN::from_array(&env, &[1u8; 32]), win.clone(), 200u32),
+        (BytesN::from_array(&env, &[2u8; 32]), win, 150u32),
+    ];
+    publish_leg_odds(&client, &backend_signer, &legs);
+    let won = client.place_parlay(&bettor, &ledger_id, &400, &legs);
+    let lost = client.place_parlay(&bettor, &ledger_id, &300, &legs);
+    assert_eq!(ledger.get_withdrawable(&bettor), 300);
+    assert_eq!(ledger.get_locked(&bettor), 700);
+
+    let hash = |seed: u8| BytesN::from_array(&env, &[seed; 32]);
+    settlement.settle_parlay(&hash(1), &won, &Symbol::new(&env, "WIN"), &None);
+    settlement.settle_parlay(&hash(2), &lost, &Symbol::new(&env, "LOSS"), &None);
+
+    // The winning parlay pays 3.00x of its stake on the ledger; the losing
+    // stake is kept
+    assert_eq!(ledger.get_withdrawable(&bettor), 300 + 1_200);
+    assert_eq!(ledger.get_locked(&bettor), 0);
+    assert_eq!(client.get_parlay(&won).status, BetStatus::Settled);
+    assert_eq!(client.get_parlay(&lost).status, BetStatus::Settled);
+}
+
+#[test]
+fn parlays_on_cancelled_matches_can_be_refunded() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &1_000);
+
+    let registry_id = env.register(MatchRegistryContract, ());
+    let registry = MatchRegistryContractClient::new(&env, &registry_id);
+    registry.initialize(&backend_signer);
+    client.set_match_registry(&backend_signer, &registry_id);
+
+    let win = Symbol::new(&env, "win");
+    let match_a = BytesN::from_array(&env, &[1u8; 32]);
+    let match_b = BytesN::from_array(&env, &[2u8; 32]);
+    registry.create_match(&match_a, &1_000, &900, &Map::new(&env));
+    registry.create_match(&match_b, &1_000, &900, &Map::new(&env));
+    let legs = vec![
+        &env,
+        (match_a, win.clone(), 200u32),
+        (match_b.clone(), win, 150u32),
+    ];
+    publish_leg_odds(&client, &backend_signer, &legs);
+    let parlay_id = client.place_parlay(&bettor, &token_id, &1_000, &legs);
+
+    let keeper = Address::generate(&env);
+    assert_eq!(
+        client.try_refund_parlay(&keeper, &parlay_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    registry.cancel_match(&match_b);
+    let parlay = client.refund_parlay(&keeper, &parlay_id);
+    assert_eq!(parlay.status, BetStatus::Refunded);
+    assert_eq!(token_client.balance(&bettor), 1_000);
+}
+
+#[test]
+fn match_limits_cap_stake_and_liability() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, _) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    token_admin_client.mint(&alice, &10_000);
+    token_admin_client.mint(&bob, &10_000);
+
+    client.set_match_limits(&backend_signer, &match_id, &1_500, &3_000);
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+
+    // 1_000 at 2.00 -> liability 2_000
+    let alice_bet = client.place_bet(&alice, &token_id, &1_000, &match_id, &bet_type, &200, &None);
+    let exposure = client.get_match_exposure(&match_id);
+    assert_eq!(exposure.total_stake, 1_000);
+    assert_eq!(exposure.total_liability, 2_000);
+
+    // 400 at 3.00 -> liability would reach 3_200
+    client.set_odds(&backend_signer, &match_id, &bet_type, &300);
+    assert_eq!(
+        client.try_place_bet(&bob, &token_id, &400, &match_id, &bet_type, &300, &None),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+    // 600 at 1.50 -> stake would reach 1_600
+    client.set_odds(&backend_signer, &match_id, &bet_type, &150);
+    assert_eq!(
+        client.try_place_bet(&bob, &token_id, &600, &match_id, &bet_type, &150, &None),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+    assert_eq!(token::Client::new(&env, &token_id).balance(&bob), 10_000);
+
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    client.place_bet(&bob, &token_id, &500, &match_id, &bet_type, &200, &None);
+
+    // Cancelling frees capacity again
+    client.cancel_bet(&backend_signer, &alice_bet);
+    assert_eq!(client.get_match_exposure(&match_id).total_stake, 500);
+    client.place_bet(&alice, &token_id, &1_000, &match_id, &bet_type, &200, &None);
+
+    assert_eq!(
+        client.try_set_match_limits(&backend_signer, &match_id, &0, &1),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+}
+
+#[test]
+fn bets_are_priced_from_published_market_odds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &1_000);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+
+    // No line published yet
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &100, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::MarketNotFound))
+    );
+    assert_eq!(
+        client.try_set_odds(&backend_signer, &match_id, &bet_type, &100),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let market = client.set_odds(&backend_signer, &match_id, &bet_type, &180);
+    assert_eq!(market.updated_at, 1_000);
+    assert_eq!(client.get_odds(&match_id, &bet_type), Some(market));
+
+    // The bettor asked for better than the line offers
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &100, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::OddsChanged))
+    );
+
+    // A client-supplied price below the line is taken at the line
+    client.place_bet(&bettor, &token_id, &100, &match_id, &bet_type, &150, &None);
+    assert_eq!(client.get_bet(&match_id, &bettor).unwrap().odds, 180);
+
+    client.remove_odds(&backend_signer, &match_id, &bet_type);
+    assert_eq!(client.get_odds(&match_id, &bet_type), None);
+    assert_eq!(
+        client.try_remove_odds(&backend_signer, &match_id, &bet_type),
+        Err(Ok(ContractError::MarketNotFound))
+    );
+}
+
+#[test]
+fn jackpot_share_is_taken_out_of_each_stake() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &2_000);
+
+    let jackpot_admin = Address::generate(&env);
+    let jackpot_id = env.register(jackpot::JackpotContract, ());
+    let jackpot = jackpot::JackpotContractClient::new(&env, &jackpot_id);
+    jackpot.initialize(&jackpot_admin, &token_id, &backend_pubkey(&env), &100);
+    jackpot.set_contributor(&jackpot_admin, &client.address, &true);
+    client.set_jackpot(&backend_signer, &jackpot_id);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    let bet_id = client.place_bet(
+        &bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None,
+    );
+
+    // 1% of the stake feeds the jackpot, the rest is escrowed
+    assert_eq!(client.get_bet(&match_id, &bettor).unwrap().amount, 990);
+    assert_eq!(token_client.balance(&client.address), 990);
+    assert_eq!(jackpot.get_pool(), 10);
+    assert_eq!(client.get_match_exposure(&match_id).total_stake, 990);
+
+    // Refunds return the escrowed part only
+    client.cancel_bet(&backend_signer, &bet_id);
+    assert_eq!(token_client.balance(&bettor), 1_990);
+    assert_eq!(token_client.balance(&client.address), 0);
+}
+
+#[test]
+fn fee_is_forwarded_to_the_collector_on_placement() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &2_000);
+
+    let treasury = Address::generate(&env);
+    client.set_fee_config(&backend_signer, &250, &treasury);
+    assert_eq!(client.get_fee_config().unwrap().fee_bps, 250);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    client.place_bet(
+        &bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None,
+    );
+
+    // 2.5% of the stake is forwarded, the rest is escrowed
+    assert_eq!(client.get_bet(&match_id, &bettor).unwrap().amount, 975);
+    assert_eq!(token_client.balance(&treasury), 25);
+    assert_eq!(token_client.balance(&client.address), 975);
+    assert_eq!(client.total_fees_collected(&token_id), 25);
+
+    // Stakes too small to owe a whole unit pay nothing
+    let other_match = BytesN::from_array(&env, &[2u8; 32]);
+    client.set_odds(&backend_signer, &other_match, &bet_type, &200);
+    client.place_bet(
+        &bettor,
+        &token_id,
+        &39,
+        &other_match,
+        &bet_type,
+        &200,
+        &None,
+    );
+    assert_eq!(client.total_fees_collected(&token_id), 25);
+}
+
+#[test]
+fn settled_stakes_are_reported_to_the_referral_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &2_000);
+
+    let referral_admin = Address::generate(&env);
+    let referral_id = env.register(referral::ReferralContract, ());
+    let referral = referral::ReferralContractClient::new(&env, &referral_id);
+    referral.initialize(
+        &referral_admin,
+        &token_id,
+        &soroban_sdk::vec![
+            &env,
+            referral::CommissionTier {
+                min_volume: 0,
+                bps: 500,
+            }
+        ],
+    );
+    let referrer = Address::generate(&env);
+    referral.register_referrer(&bettor, &referrer);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+
+    let settlement = Address::generate(&env);
+    client.set_settlement_contract(&backend_signer, &settlement);
+    client.set_referral(&backend_signer, &referral_id);
+
+    // Volume only counts once a bet is settled: cancelled stakes never do
+    let cancelled = client.place_bet(
+        &bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None,
+    );
+    client.cancel_bet(&backend_signer, &cancelled);
+    let bet_id = client.place_bet(
+        &bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None,
+    );
+    assert_eq!(referral.get_stats(&referrer).referred_volume, 0);
+
+    // Betting must be an approved reporter
+    assert_eq!(
+        client.try_mark_bet_settled(&bet_id),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    referral.set_reporter(&referral_admin, &client.address, &true);
+    client.mark_bet_settled(&bet_id);
+
+    let stats = referral.get_stats(&referrer);
+    assert_eq!(stats.referred_volume, 1_000);
+    assert_eq!(stats.accrued, 50);
+}
+
+#[test]
+fn ledger_escrow_locks_internal_balances_and_settles_through_the_ledger() {
+    let env = Env::default();
+    // The ledger's backend signer co-signs the nested lock and unlock calls
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let backend_signer = Address::generate(&env);
+    let ledger_id = env.register(balance_ledger::BalanceLedgerContract, ());
+    let ledger = balance_ledger::BalanceLedgerContractClient::new(&env, &ledger_id);
+    ledger.initialize(&backend_signer);
+    let settlement_id = env.register(settlement::SettlementContract, ());
+    let settlement = settlement::SettlementContractClient::new(&env, &settlement_id);
+    settlement.initialize(&backend_signer, &ledger_id);
+
+    let client = BettingContractClient::new(&env, &env.register(BettingContract, ()));
+    client.initialize_with_ledger(&backend_signer, &ledger_id);
+    client.set_settlement_contract(&backend_signer, &settlement_id);
+    settlement.set_betting_contract(&backend_signer, &Some(client.address.clone()));
+    assert_eq!(
+        client.get_escrow_mode(),
+        EscrowMode::Ledger(ledger_id.clone())
+    );
+    assert_eq!(
+        client.try_initialize_with_ledger(&backend_signer, &ledger_id),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+
+    let bettor = Address::generate(&env);
+    ledger.set_balance(&bettor, &1_000, &0, &None, &None);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+
+    // Bets are placed in the ledger's base unit, not in some token
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &400, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::TokenNotAllowed))
+    );
+    assert_eq!(
+        client.try_place_bet(&bettor, &ledger_id, &1_001, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+
+    let bet_id = client.place_bet(&bettor, &ledger_id, &400, &match_id, &bet_type, &200, &None);
+    assert_eq!(ledger.get_withdrawable(&bettor), 600);
+    assert_eq!(ledger.get_locked(&bettor), 400);
+
+    // Cancelling unlocks the stake again
+    client.cancel_bet(&backend_signer, &bet_id);
+    assert_eq!(ledger.get_withdrawable(&bettor), 1_000);
+    assert_eq!(ledger.get_locked(&bettor), 0);
+
+    // The settlement contract pays the same locked stake out on the ledger
+    let bet_id = client.place_bet(&bettor, &ledger_id, &400, &match_id, &bet_type, &200, &None);
+    settlement.settle_bet(
+        &BytesN::from_array(&env, &[9u8; 32]),
+        &settlement::SettlementInput {
+            bet_id,
+            bettor: bettor.clone(),
+            winner: Some(bettor.clone()),
+            bet_amount: 400,
+            payout: 800,
+            settlement_type: Symbol::new(&env, "WIN"),
+        },
+        &None,
+    );
+    assert_eq!(ledger.get_withdrawable(&bettor), 1_400);
+    assert_eq!(ledger.get_locked(&bettor), 0);
+    // ...and closes the same bet on this contract, so it can no longer be
+    // cancelled
+    assert_eq!(client.get_bet_status(&bet_id), BetStatus::Settled);
+    assert_eq!(
+        client.try_cancel_bet(&backend_signer, &bet_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    // Token-only features cannot be switched on
+    assert_eq!(
+        client.try_set_jackpot(&backend_signer, &Address::generate(&env)),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    assert_eq!(
+        client.try_set_fee_config(&backend_signer, &100, &Address::generate(&env)),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+}
+
+#[test]
+fn token_escrow_stakes_count_against_ledger_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let ledger_id = env.register(balance_ledger::BalanceLedgerContract, ());
+    let ledger = balance_ledger::BalanceLedgerContractClient::new(&env, &ledger_id);
+    ledger.initialize(&backend_signer);
+    ledger.set_limits(
+        &bettor,
+        &balance_ledger::GamingLimits {
+            daily_wager: Some(1_500),
+            ..Default::default()
+        },
+    );
+    client.set_limits_ledger(&backend_signer, &ledger_id);
+    assert_eq!(client.get_limits_ledger(), Some(ledger_id.clone()));
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &5_000);
+    let bet_type = Symbol::new(&env, "win");
+    let first = BytesN::from_array(&env, &[1u8; 32]);
+    let second = BytesN::from_array(&env, &[2u8; 32]);
+    client.set_odds(&backend_signer, &first, &bet_type, &200);
+    client.set_odds(&backend_signer, &second, &bet_type, &200);
+
+    // The betting contract has to be a reporter on the ledger
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &1_000, &first, &bet_type, &200, &None),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    ledger.set_limit_reporter(&backend_signer, &client.address, &true);
+    client.place_bet(&bettor, &token_id, &1_000, &first, &bet_type, &200, &None);
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &600, &second, &bet_type, &200, &None),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+    assert_eq!(ledger.get_daily_usage(&bettor).wagered, 1_000);
+}
+
+#[test]
+fn self_excluded_users_cannot_bet_or_spin() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let ledger_id = env.register(balance_ledger::BalanceLedgerContract, ());
+    let ledger = balance_ledger::BalanceLedgerContractClient::new(&env, &ledger_id);
+    ledger.initialize(&backend_signer);
+    client.set_self_exclusion_registry(&backend_signer, &ledger_id);
+    assert_eq!(client.get_self_exclusion_registry(), Some(ledger_id));
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &1_000);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    let spin_id = BytesN::from_array(&env, &[3u8; 32]);
+    let spin_hash = BytesN::from_array(&env, &[4u8; 32]);
+    let signature = sign_spin(&env, &client, &spin_id, &bettor, &spin_hash);
+
+    ledger.self_exclude(&bettor, &86_400);
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::SelfExcluded))
+    );
+    assert_eq!(
+        client.try_execute_spin(&spin_id, &spin_hash, &signature, &bettor),
+        Err(Ok(ContractError::SelfExcluded))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.place_bet(
+        &bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None,
+    );
+    client.execute_spin(&spin_id, &spin_hash, &signature, &bettor);
+}
+
+#[test]
+fn settlement_contract_releases_winnings_from_escrow_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let loser = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&bettor, &1_000);
+    token_admin_client.mint(&loser, &1_000);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    client.set_odds(&backend_signer, &match_id, &Symbol::new(&env, "home"), &200);
+    client.set_odds(&backend_signer, &match_id, &Symbol::new(&env, "away"), &200);
+    let bet_id = client.place_bet(
+        &bettor,
+        &token_id,
+        &1_000,
+        &match_id,
+        &Symbol::new(&env, "home"),
+        &200,
+        &None,
+    );
+    client.place_bet(
+        &loser,
+        &token_id,
+        &1_000,
+        &match_id,
+        &Symbol::new(&env, "away"),
+        &200,
+        &None,
+    );
+
+    let op = BytesN::from_array(&env, &[9u8; 32]);
+    let fee_collector = Address::generate(&env);
+    let win = Symbol::new(&env, "WIN");
+    let payouts = vec![&env, (bettor.clone(), 1_900), (fee_collector.clone(), 100)];
+    assert_eq!(
+        client.try_release_escrow(&op, &bet_id, &win, &payouts),
+        Err(Ok(ContractError::NotInitialized))
+    );
+
+    let settlement = Address::generate(&env);
+    client.set_settlement_contract(&backend_signer, &settlement);
+    assert_eq!(client.get_settlement_contract(), Some(settlement.clone()));
+
+    assert_eq!(
+        client.try_release_escrow(&op, &bet_id, &win, &vec![&env, (bettor.clone(), 0)]),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_release_escrow(&op, &99, &win, &payouts),
+        Err(Ok(ContractError::BetNotFound))
+    );
+
+    // Payouts are bounded by the stored bet, whatever the outcome
+    assert_eq!(
+        client.try_release_escrow(&op, &bet_id, &win, &vec![&env, (bettor.clone(), 2_001)]),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+    assert_eq!(
+        client.try_release_escrow(
+            &op,
+            &bet_id,
+            &Symbol::new(&env, "LOSS"),
+            &vec![&env, (settlement.clone(), 1_001)]
+        ),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+    let draw = Symbol::new(&env, "DRAW");
+    assert_eq!(
+        client.try_release_escrow(&op, &bet_id, &draw, &vec![&env, (loser.clone(), 1_000)]),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_release_escrow(&op, &bet_id, &draw, &vec![&env, (bettor.clone(), 1_001)]),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_release_escrow(&op, &bet_id, &Symbol::new(&env, "VOID"), &payouts),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    assert_eq!(
+        client.release_escrow(&op, &bet_id, &win, &payouts),
+        token_id
+    );
+    assert_eq!(token_client.balance(&bettor), 1_900);
+    assert_eq!(token_client.balance(&fee_collector), 100);
+    assert_eq!(token_client.balance(&client.address), 0);
+    assert!(client.is_escrow_release_executed(&op));
+    assert_eq!(
+        client.try_release_escrow(&op, &bet_id, &win, &payouts),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+}
+
+#[test]
+fn settlement_contract_pays_token_escrow_bets_out_of_betting() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let loser = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&bettor, &2_000);
+    token_admin_client.mint(&loser, &1_000);
+
+    let ledger_id = env.register(balance_ledger::BalanceLedgerContract, ());
+    let settlement_id = env.register(settlement::SettlementContract, ());
+    let settlement = settlement::SettlementContractClient::new(&env, &settlement_id);
+    settlement.initialize(&backend_signer, &ledger_id);
+    settlement.set_betting_contract(&backend_signer, &Some(client.address.clone()));
+    client.set_settlement_contract(&backend_signer, &settlement_id);
+    let fee_collector = Address::generate(&env);
+    settlement.set_fee_config(&backend_signer, &100, &fee_collector);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let home = Symbol::new(&env, "home");
+    let away = Symbol::new(&env, "away");
+    client.set_odds(&backend_signer, &match_id, &home, &200);
+    client.set_odds(&backend_signer, &match_id, &away, &200);
+    let won = client.place_bet(&bettor, &token_id, &1_000, &match_id, &home, &200, &None);
+    let lost = client.place_bet(&loser, &token_id, &1_000, &match_id, &away, &200, &None);
+    let drawn = client.place_bet(&bettor, &token_id, &1_000, &match_id, &away, &200, &None);
+
+    let settle = |seed: u8, bet_id: u64, bettor: &Address, outcome: &str, payout: i128| {
+        settlement.settle_bet(
+            &BytesN::from_array(&env, &[seed; 32]),
+            &settlement::SettlementInput {
+                bet_id,
+                bettor: bettor.clone(),
+                winner: Some(bettor.clone()),
+                bet_amount: 1_000,
+                payout,
+                settlement_type: Symbol::new(&env, outcome),
+            },
+            &None,
+        );
+    };
+    settle(1, won, &bettor, "WIN", 2_000);
+    settle(2, lost, &loser, "LOSS", 0);
+    settle(3, drawn, &bettor, "DRAW", 0);
+
+    // 1% of the payout goes to the fee collector, the rest to the winner,
+    // and the drawn stake comes back
+    assert_eq!(token_client.balance(&bettor), 1_980 + 1_000);
+    assert_eq!(token_client.balance(&fee_collector), 20);
+    assert_eq!(token_client.balance(&client.address), 0);
+    for bet_id in [won, lost, drawn] {
+        assert_eq!(client.get_bet_status(&bet_id), BetStatus::Settled);
+    }
+    assert_eq!(settlement.get_settlement(&won).unwrap().payout, 1_980);
+}
+
+#[test]
+fn bankroll_backs_token_escrow_winnings_and_keeps_losses() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let loser = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&bettor, &2_000);
+    token_admin_client.mint(&loser, &1_000);
+
+    let ledger_id = env.register(balance_ledger::BalanceLedgerContract, ());
+    let settlement_id = env.register(settlement::SettlementContract, ());
+    let settlement = settlement::SettlementContractClient::new(&env, &settlement_id);
+    settlement.initialize(&backend_signer, &ledger_id);
+    settlement.set_betting_contract(&backend_signer, &Some(client.address.clone()));
+    client.set_settlement_contract(&backend_signer, &settlement_id);
+
+    let bankroll_id = env.register(bankroll::BankrollContract, ());
+    let bankroll = bankroll::BankrollContractClient::new(&env, &bankroll_id);
+    bankroll.initialize(&backend_signer, &token_id, &0);
+    bankroll.set_hook(&backend_signer, &settlement_id, &true);
+    let provider = Address::generate(&env);
+    token_admin_client.mint(&provider, &5_000);
+    bankroll.deposit(&provider, &5_000);
+    settlement.set_bankroll(&backend_signer, &Some(bankroll_id.clone()));
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let home = Symbol::new(&env, "home");
+    let away = Symbol::new(&env, "away");
+    client.set_odds(&backend_signer, &match_id, &home, &300);
+    client.set_odds(&backend_signer, &match_id, &away, &200);
+    let won = client.place_bet(&bettor, &token_id, &1_000, &match_id, &home, &300, &None);
+    let lost = client.place_bet(&loser, &token_id, &1_000, &match_id, &away, &200, &None);
+
+    let settle = |seed: u8, bet_id: u64, bettor: &Address, outcome: &str, payout: i128| {
+        settlement.settle_bet(
+            &BytesN::from_array(&env, &[seed; 32]),
+            &settlement::SettlementInput {
+                bet_id,
+                bettor: bettor.clone(),
+                winner: Some(bettor.clone()),
+                bet_amount: 1_000,
+                payout,
+                settlement_type: Symbol::new(&env, outcome),
+            },
+            &None,
+        );
+    };
+
+    // The winner's stake comes out of escrow and the winnings beyond it
+    // out of the bankroll
+    settle(1, won, &bettor, "WIN", 3_000);
+    assert_eq!(token_client.balance(&bettor), 1_000 + 3_000);
+    assert_eq!(bankroll.get_bankroll(), 3_000);
+    assert_eq!(token_client.balance(&client.address), 1_000);
+
+    // The lost stake goes to the bankroll
+    settle(2, lost, &loser, "LOSS", 0);
+    assert_eq!(bankroll.get_bankroll(), 4_000);
+    assert_eq!(token_client.balance(&bankroll_id), 4_000);
+    assert_eq!(token_client.balance(&client.address), 0);
+    assert_eq!(token_client.balance(&settlement_id), 0);
+
+    // Parlays draw and return the same way
+    let legs = vec![
+        &env,
+        (match_id.clone(), home.clone(), 300u32),
+        (BytesN::from_array(&env, &[2u8; 32]), home.clone(), 300u32),
+    ];
+    publish_leg_odds(&client, &backend_signer, &legs);
+    let parlay_won = client.place_parlay(&bettor, &token_id, &100, &legs);
+    let parlay_lost = client.place_parlay(&bettor, &token_id, &100, &legs);
+    let outcome = |name: &str| Symbol::new(&env, name);
+    settlement.settle_parlay(
+        &BytesN::from_array(&env, &[4u8; 32]),
+        &parlay_won,
+        &outcome("WIN"),
+        &None,
+    );
+    settlement.settle_parlay(
+        &BytesN::from_array(&env, &[5u8; 32]),
+        &parlay_lost,
+        &outcome("LOSS"),
+        &None,
+    );
+    assert_eq!(token_client.balance(&bettor), 4_000 - 200 + 900);
+    assert_eq!(bankroll.get_bankroll(), 4_000 - 800 + 100);
+    assert_eq!(token_client.balance(&client.address), 0);
+
+    // Stakes in any other token cannot be backed by it
+    let other_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &other_id).mint(&loser, &1_000);
+    let other = client.place_bet(&loser, &other_id, &1_000, &match_id, &away, &200, &None);
+    assert_eq!(
+        settlement.try_settle_bet(
+            &BytesN::from_array(&env, &[3u8; 32]),
+            &settlement::SettlementInput {
+                bet_id: other,
+                bettor: loser.clone(),
+                winner: None,
+                bet_amount: 1_000,
+                payout: 0,
+                settlement_type: Symbol::new(&env, "LOSS"),
+            },
+            &None,
+        ),
+        Err(Ok(ContractError::TokenNotAllowed))
+    );
+}
+
+#[test]
+fn bet_status_moves_through_lock_and_settlement() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &2_000);
+    let settlement = Address::generate(&env);
+    client.set_settlement_contract(&backend_signer, &settlement);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    assert_eq!(
+        client.try_get_bet_status(&1),
+        Err(Ok(ContractError::BetNotFound))
+    );
+    client.set_prevent_double_betting(&backend_signer, &true);
+    let bet_id = client.place_bet(
+        &bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None,
+    );
+    assert_eq!(client.get_bet_status(&bet_id), BetStatus::Open);
+
+    let locked = client.lock_bet(&backend_signer, &bet_id);
+    assert_eq!(locked.status, BetStatus::Locked);
+    assert_eq!(
+        client.try_lock_bet(&backend_signer, &bet_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_lock_bet(&bettor, &bet_id),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    // A locked bet cannot be cancelled by its bettor and still blocks
+    // double betting
+    assert_eq!(
+        client.try_cancel_bet(&bettor, &bet_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::BetAlreadyPlaced))
+    );
+
+    let settled = client.mark_bet_settled(&bet_id);
+    assert_eq!(settled.status, BetStatus::Settled);
+    assert_eq!(client.get_bet_status(&bet_id), BetStatus::Settled);
+    assert_eq!(client.get_bet_count_for_match(&match_id), 0);
+    assert_eq!(
+        client.get_match_exposure(&match_id),
+        MatchExposure::default()
+    );
+    assert_eq!(
+        client.try_release_escrow(
+            &BytesN::from_array(&env, &[9u8; 32]),
+            &bet_id,
+            &Symbol::new(&env, "DRAW"),
+            &vec![&env, (bettor.clone(), 1_000)]
+        ),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    // A settled bet no longer blocks a new one on the same match
+    let next = client.place_bet(
+        &bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None,
+    );
+    assert_eq!(client.get_bet_status(&next), BetStatus::Open);
+    assert_eq!(client.get_bet(&match_id, &bettor).unwrap().bet_id, next);
+    assert_eq!(client.get_bet_count_for_match(&match_id), 1);
+}
+
+#[test]
+fn bet_limits_apply_globally_with_per_match_overrides() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &10_000);
+
+    let bet_type = Symbol::new(&env, "win");
+    let regular = BytesN::from_array(&env, &[1u8; 32]);
+    let final_match = BytesN::from_array(&env, &[2u8; 32]);
+    client.set_odds(&backend_signer, &regular, &bet_type, &200);
+    client.set_odds(&backend_signer, &final_match, &bet_type, &200);
+
+    assert_eq!(client.get_bet_limits(&regular), None);
+    assert_eq!(
+        client.try_set_bet_limits(&backend_signer, &None, &500, &100),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.set_bet_limits(&backend_signer, &None, &100, &1_000);
+    client.set_bet_limits(&backend_signer, &Some(final_match.clone()), &100, &5_000);
+    assert_eq!(
+        client.get_bet_limits(&regular),
+        Some(BetLimits {
+            min_bet: 100,
+            max_bet: 1_000
+        })
+    );
+
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &99, &regular, &bet_type, &200, &None),
+        Err(Ok(ContractError::BelowMinBet))
+    );
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &1_001, &regular, &bet_type, &200, &None),
+        Err(Ok(ContractError::AboveMaxBet))
+    );
+    client.place_bet(&bettor, &token_id, &1_000, &regular, &bet_type, &200, &None);
+    client.place_bet(
+        &bettor,
+        &token_id,
+        &3_000,
+        &final_match,
+        &bet_type,
+        &200,
+        &None,
+    );
+
+    // Without its override the match falls back to the global limits
+    client.remove_bet_limits(&backend_signer, &Some(final_match.clone()));
+    assert_eq!(
+        client.try_place_bet(
+            &bettor,
+            &token_id,
+            &3_000,
+            &final_match,
+            &bet_type,
+            &200,
+            &None
+        ),
+        Err(Ok(ContractError::AboveMaxBet))
+    );
+    client.remove_bet_limits(&backend_signer, &None);
+    assert_eq!(client.get_bet_limits(&final_match), None);
+    client.place_bet(
+        &bettor,
+        &token_id,
+        &3_000,
+        &final_match,
+        &bet_type,
+        &200,
+        &None,
+    );
+}
+
+#[test]
+fn betting_closes_at_close_time_until_extended() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &5_000);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    assert_eq!(
+        client.try_extend_close_time(&backend_signer, &match_id, &2_000),
+        Err(Ok(ContractError::MatchNotFound))
+    );
+    client.set_close_time(&backend_signer, &match_id, &1_000);
+    assert_eq!(client.get_close_time(&match_id), Some(1_000));
+    assert_eq!(
+        client.try_close_market(&match_id),
+        Err(Ok(ContractError::MatchNotOpen))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 999);
+    client.place_bet(
+        &bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert_eq!(
+        client.try_place_bet(&bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None),
+        Err(Ok(ContractError::BettingClosed))
+    );
+    let mut legs = Vec::new(&env);
+    legs.push_back((match_id.clone(), bet_type.clone(), 200u32));
+    legs.push_back((
+        BytesN::from_array(&env, &[2u8; 32]),
+        bet_type.clone(),
+        200u32,
+    ));
+    publish_leg_odds(&client, &backend_signer, &legs);
+    assert_eq!(
+        client.try_place_parlay(&bettor, &token_id, &1_000, &legs),
+        Err(Ok(ContractError::BettingClosed))
+    );
+
+    assert_eq!(client.close_market(&match_id), 1_000);
+    assert_eq!(
+        client.try_close_market(&match_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    // A delayed kickoff reopens the market
+    assert_eq!(
+        client.try_extend_close_time(&backend_signer, &match_id, &1_000),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.extend_close_time(&backend_signer, &match_id, &1_500);
+    client.place_bet(
+        &bettor, &token_id, &1_000, &match_id, &bet_type, &200, &None,
+    );
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    assert_eq!(client.close_market(&match_id), 1_500);
+}
+
+#[test]
+fn close_time_can_be_synced_from_the_registry() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, _) = setup_test(&env);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    assert_eq!(
+        client.try_sync_close_time(&match_id),
+        Err(Ok(ContractError::NotInitialized))
+    );
+
+    let registry_id = env.register(MatchRegistryContract, ());
+    let registry = MatchRegistryContractClient::new(&env, &registry_id);
+    registry.initialize(&backend_signer);
+    client.set_match_registry(&backend_signer, &registry_id);
+    assert_eq!(
+        client.try_sync_close_time(&match_id),
+        Err(Ok(ContractError::MatchNotFound))
+    );
+
+    registry.create_match(&match_id, &1_000, &900, &Map::new(&env));
+    assert_eq!(client.sync_close_time(&match_id), 900);
+    assert_eq!(client.get_close_time(&match_id), Some(900));
+}
+
+fn sign_cash_out(
+    env: &Env,
+    client: &BettingContractClient<'_>,
+    bettor: &Address,
+    bet_id: u64,
+    offer_amount: i128,
+    expires_at: u64,
+    operation_hash: &BytesN<32>,
+) -> BytesN<64> {
+    let message: std::vec::Vec<u8> = (
+        client.address.clone(),
+        bettor.clone(),
+        bet_id,
+        offer_amount,
+        expires_at,
+        operation_hash.clone(),
+    )
+        .to_xdr(env)
+        .iter()
+        .collect();
+    BytesN::from_array(env, &backend_key().sign(&message).to_bytes())
+}
+
+#[test]
+fn bettor_cashes_out_a_signed_offer_before_it_expires() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let loser = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_client = token::Client::new(&env, &token_id);
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+    token_admin_client.mint(&bettor, &1_000);
+    token_admin_client.mint(&loser, &1_000);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    client.set_odds(&backend_signer, &match_id, &Symbol::new(&env, "home"), &200);
+    client.set_odds(&backend_signer, &match_id, &Symbol::new(&env, "away"), &200);
+    let bet_id = client.place_bet(
+        &bettor,
+        &token_id,
+        &1_000,
+        &match_id,
+        &Symbol::new(&env, "home"),
+        &200,
+        &None,
+    );
+    let loser_bet = client.place_bet(
+        &loser,
+        &token_id,
+        &1_000,
+        &match_id,
+        &Symbol::new(&env, "away"),
+        &200,
+        &None,
+    );
+    client.lock_bet(&backend_signer, &bet_id);
+
+    let op = BytesN::from_array(&env, &[9u8; 32]);
+    let signature = sign_cash_out(&env, &client, &bettor, bet_id, 1_500, 100, &op);
+
+    // The offer only pays what the backend signed
+    assert!(client
+        .try_cash_out(&bettor, &bet_id, &1_600, &100, &op, &signature)
+        .is_err());
+
+    let bet = client.cash_out(&bettor, &bet_id, &1_500, &100, &op, &signature);
+    assert_eq!(bet.status, BetStatus::Settled);
+    assert_eq!(token_client.balance(&bettor), 1_500);
+    assert_eq!(token_client.balance(&client.address), 500);
+    assert!(client.is_cash_out_executed(&op));
+    assert_eq!(
+        client.try_cash_out(&bettor, &bet_id, &1_500, &100, &op, &signature),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+
+    // A fresh offer on a settled bet is refused
+    let next_op = BytesN::from_array(&env, &[10u8; 32]);
+    let signature = sign_cash_out(&env, &client, &bettor, bet_id, 1_500, 100, &next_op);
+    assert_eq!(
+        client.try_cash_out(&bettor, &bet_id, &1_500, &100, &next_op, &signature),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    // Offers are bound to the bettor who owns the bet
+    let signature = sign_cash_out(&env, &client, &bettor, loser_bet, 200, 100, &next_op);
+    assert_eq!(
+        client.try_cash_out(&bettor, &loser_bet, &200, &100, &next_op, &signature),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    // Offers signed for another deployment are rejected
+    let (other, _, _) = setup_test(&env);
+    let signature = sign_cash_out(&env, &other, &loser, loser_bet, 200, 100, &next_op);
+    assert!(client
+        .try_cash_out(&loser, &loser_bet, &200, &100, &next_op, &signature)
+        .is_err());
+
+    // Expired offers never reach the signature check
+    let loser_op = BytesN::from_array(&env, &[11u8; 32]);
+    let signature = sign_cash_out(&env, &client, &loser, loser_bet, 200, 100, &loser_op);
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(
+        client.try_cash_out(&loser, &loser_bet, &200, &100, &loser_op, &signature),
+        Err(Ok(ContractError::Expired))
+    );
+}
+
+#[test]
+fn placed_bets_get_sequential_ids_and_a_full_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&bettor, &2_000);
+
+    let bet_type = Symbol::new(&env, "win");
+    let first = BytesN::from_array(&env, &[1u8; 32]);
+    let second = BytesN::from_array(&env, &[2u8; 32]);
+    client.set_odds(&backend_signer, &first, &bet_type, &200);
+    client.set_odds(&backend_signer, &second, &bet_type, &250);
+
+    assert_eq!(
+        client.place_bet(&bettor, &token_id, &1_000, &first, &bet_type, &200, &None),
+        1
+    );
+    env.ledger().with_mut(|li| li.timestamp = 500);
+    assert_eq!(
+        client.place_bet(&bettor, &token_id, &1_000, &second, &bet_type, &250, &None),
+        2
+    );
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (WAGER_PLACED_EVENT, EVENT_SCHEMA_VERSION, bettor.clone()).into_val(&env)
+    );
+    let event: WagerPlacedEvent = data.into_val(&env);
+    assert_eq!(
+        event,
+        WagerPlacedEvent {
+            version: EVENT_SCHEMA_VERSION,
+            bet_id: 2,
+            bettor: bettor.clone(),
+            match_id: second,
+            bet_type,
+            odds: 250,
+            token: token_id,
+            amount: 1_000,
+            timestamp: 500,
+            affiliate: None,
+        }
+    );
+    assert_eq!(client.get_bet(&first, &bettor).unwrap().bet_id, 1);
+}
+
+#[test]
+fn affiliate_tagged_bets_accumulate_volume() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, backend_signer, bettor) = setup_test(&env);
+    let other = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_admin = token::StellarAssetClient::new(&env, &token_id);
+    token_admin.mint(&bettor, &5_000);
+    token_admin.mint(&other, &5_000);
+
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    let bet_type = Symbol::new(&env, "win");
+    client.set_odds(&backend_signer, &match_id, &bet_type, &200);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+
+    assert_eq!(
+        client.try_place_bet(
+            &bettor,
+            &token_id,
+            &1_000,
+            &match_id,
+            &bet_type,
+            &200,
+            &Some(bettor.clone())
+        ),
+        Err(Ok(ContractError::SelfReferral))
+    );
+
+    let bet_id = client.place_bet(
+        &bettor,
+        &token_id,
+        &1_000,
+        &match_id,
+        &bet_type,
+        &200,
+        &Some(first.clone()),
+    );
+    let (_, _, data) = env.events().all().last().unwrap();
+    let event: WagerPlacedEvent = data.into_val(&env);
+    assert_eq!(event.affiliate, Some(first.clone()));
+    assert_eq!(
+        client.get_bet_by_id(&bet_id).unwrap().affiliate,
+        Some(first.clone())
+    );
+
+    client.place_bet(
+        &other,
+        &token_id,
+        &400,
+        &match_id,
+        &bet_type,
+        &200,
+        &Some(first.clone()),
+    );
+    client.place_bet(
+        &other,
+        &token_id,
+        &300,
+        &match_id,
+        &bet_type,
+        &200,
+        &Some(second.clone()),
+    );
+    client.place_bet(&bettor, &token_id, &200, &match_id, &bet_type, &200, &None);
+
+    assert_eq!(
+        client.get_affiliate_stats(&first),
+        AffiliateStats {
+            affiliate: first.clone(),
+            volume: 1_400,
+            bet_count: 2,
+        }
+    );
+    let page = client.get_affiliates(&0, &10);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap().affiliate, first);
+    assert_eq!(
+        client.get_affiliates(&1, &10).get(0),
+        Some(AffiliateStats {
+            affiliate: second,
+            volume: 300,
+            bet_count: 1,
+        })
+    );
 }
@@ -0,0 +1,300 @@
+#![no_std]
+
+use common::{init_role, merkle_leaf, verify_merkle_proof, Role};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, Address, BytesN, Env, Symbol, Val,
+    Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ClaimsError {
+    Unauthorized = 1,
+    AlreadyInitialized = 2,
+    InvalidAmount = 3,
+    InvalidConfig = 4,
+    InvalidStatus = 5,
+    InsufficientBalance = 6,
+    DuplicateOperation = 7,
+    Expired = 8,
+    DistributionNotFound = 9,
+    InvalidProof = 10,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Distribution {
+    pub token: Address,
+    /// Root of the tree of `merkle_leaf(account, amount)` entries
+    pub merkle_root: BytesN<32>,
+    pub total_amount: i128,
+    pub claimed_amount: i128,
+    /// Claims are accepted strictly before this timestamp
+    pub expires_at: u64,
+    pub swept: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Distribution(u64),
+    Claimed(u64, Address),
+}
+
+#[contract]
+pub struct ClaimsContract;
+
+#[contractimpl]
+impl ClaimsContract {
+    /// `backend` receives `Role::Operator` and posts distributions.
+    pub fn initialize(env: Env, admin: Address, backend: Address) -> Result<(), ClaimsError> {
+        if common::has_role(&env, Role::Admin, &admin) {
+            return Err(ClaimsError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        init_role(&env, Role::Admin, &admin);
+        init_role(&env, Role::Operator, &backend);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ClaimsError> {
+        common::grant_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ClaimsError> {
+        common::revoke_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), ClaimsError> {
+        common::renounce_role(&env, &account, role).map_err(role_error)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), ClaimsError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ClaimsError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Post a payout tree and pull `total_amount` of `token` from the
+    /// operator to fund it.
+    pub fn create_distribution(
+        env: Env,
+        operator: Address,
+        distribution_id: u64,
+        token: Address,
+        merkle_root: BytesN<32>,
+        total_amount: i128,
+        expires_at: u64,
+    ) -> Result<(), ClaimsError> {
+        require_role(&env, Role::Operator, &operator)?;
+        if total_amount <= 0 {
+            return Err(ClaimsError::InvalidAmount);
+        }
+        if expires_at <= env.ledger().timestamp() {
+            return Err(ClaimsError::InvalidConfig);
+        }
+        let key = DataKey::Distribution(distribution_id);
+        if env.storage().persistent().has(&key) {
+            return Err(ClaimsError::DuplicateOperation);
+        }
+
+        token::Client::new(&env, &token).transfer(
+            &operator,
+            env.current_contract_address(),
+            &total_amount,
+        );
+        let distribution = Distribution {
+            token,
+            merkle_root,
+            total_amount,
+            claimed_amount: 0,
+            expires_at,
+            swept: false,
+        };
+        store_distribution(&env, distribution_id, &distribution);
+
+        publish_distribution_event(&env, distribution_id, &distribution);
+
+        Ok(())
+    }
+
+    pub fn get_distribution(env: Env, distribution_id: u64) -> Result<Distribution, ClaimsError> {
+        get_distribution(&env, distribution_id)
+    }
+
+    pub fn is_claimed(env: Env, distribution_id: u64, claimant: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::Claimed(distribution_id, claimant))
+    }
+
+    /// Pay `claimant` their entry in the distribution's tree. Each account
+    /// can claim once per distribution, and only before it expires.
+    pub fn claim(
+        env: Env,
+        claimant: Address,
+        distribution_id: u64,
+        amount: i128,
+        proof: Vec<BytesN<32>>,
+    ) -> Result<i128, ClaimsError> {
+        claimant.require_auth();
+
+        let mut distribution = get_distribution(&env, distribution_id)?;
+        if env.ledger().timestamp() >= distribution.expires_at {
+            return Err(ClaimsError::Expired);
+        }
+        let claimed_key = DataKey::Claimed(distribution_id, claimant.clone());
+        if env.storage().persistent().has(&claimed_key) {
+            return Err(ClaimsError::DuplicateOperation);
+        }
+        let leaf = merkle_leaf(&env, &claimant, amount);
+        if amount <= 0 || !verify_merkle_proof(&env, &distribution.merkle_root, &leaf, &proof) {
+            return Err(ClaimsError::InvalidProof);
+        }
+
+        // A root that promises more than was funded cannot drain other
+        // distributions
+        let claimed_amount = distribution
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(ClaimsError::InvalidAmount)?;
+        if claimed_amount > distribution.total_amount {
+            return Err(ClaimsError::InsufficientBalance);
+        }
+        distribution.claimed_amount = claimed_amount;
+
+        env.storage().persistent().set(&claimed_key, &true);
+        common::bump_persistent(&env, &claimed_key);
+        store_distribution(&env, distribution_id, &distribution);
+
+        token::Client::new(&env, &distribution.token).transfer(
+            &env.current_contract_address(),
+            &claimant,
+            &amount,
+        );
+
+        publish_claim_event(&env, distribution_id, &claimant, amount);
+
+        Ok(amount)
+    }
+
+    /// Send whatever an expired distribution did not pay out to `to`.
+    /// Returns the amount swept.
+    pub fn sweep(
+        env: Env,
+        admin: Address,
+        distribution_id: u64,
+        to: Address,
+    ) -> Result<i128, ClaimsError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let mut distribution = get_distribution(&env, distribution_id)?;
+        if env.ledger().timestamp() < distribution.expires_at || distribution.swept {
+            return Err(ClaimsError::InvalidStatus);
+        }
+
+        let unclaimed = distribution.total_amount - distribution.claimed_amount;
+        distribution.swept = true;
+        store_distribution(&env, distribution_id, &distribution);
+
+        if unclaimed > 0 {
+            token::Client::new(&env, &distribution.token).transfer(
+                &env.current_contract_address(),
+                &to,
+                &unclaimed,
+            );
+        }
+
+        publish_sweep_event(&env, distribution_id, &to, unclaimed);
+
+        Ok(unclaimed)
+    }
+}
+
+fn get_distribution(env: &Env, distribution_id: u64) -> Result<Distribution, ClaimsError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Distribution(distribution_id))
+        .ok_or(ClaimsError::DistributionNotFound)
+}
+
+fn store_distribution(env: &Env, distribution_id: u64, distribution: &Distribution) {
+    let key = DataKey::Distribution(distribution_id);
+    env.storage().persistent().set(&key, distribution);
+    common::bump_persistent(env, &key);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_distribution_event(env: &Env, distribution_id: u64, distribution: &Distribution) {
+    env.events().publish(
+        (Symbol::new(env, "distribution_created"), distribution_id),
+        distribution.clone(),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_claim_event(env: &Env, distribution_id: u64, claimant: &Address, amount: i128) {
+    env.events().publish(
+        (
+            Symbol::new(env, "distribution_claimed"),
+            distribution_id,
+            claimant.clone(),
+        ),
+        amount,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_sweep_event(env: &Env, distribution_id: u64, to: &Address, amount: i128) {
+    env.events().publish(
+        (Symbol::new(env, "distribution_swept"), distribution_id),
+        (to.clone(), amount),
+    );
+}
+
+fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), ClaimsError> {
+    common::require_role(env, role, account).map_err(role_error)
+}
+
+fn role_error(_: common::ContractError) -> ClaimsError {
+    ClaimsError::Unauthorized
+}
+
+#[cfg(test)]
+mod test;
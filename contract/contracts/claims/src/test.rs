@@ -0,0 +1,221 @@
+#![cfg(test)]
+
+use super::*;
+use common::merkle_hash_pair;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, vec, Address, BytesN, Env,
+};
+
+const EXPIRES_AT: u64 = 1_000;
+
+fn setup_test(
+    env: &Env,
+) -> (
+    ClaimsContractClient<'_>,
+    Address,
+    Address,
+    token::Client<'_>,
+) {
+    env.mock_all_auths();
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let backend = Address::generate(env);
+    token::StellarAssetClient::new(env, &token_id).mint(&backend, &10_000);
+
+    let contract_id = env.register(ClaimsContract, ());
+    let client = ClaimsContractClient::new(env, &contract_id);
+    let admin = Address::generate(env);
+    client.initialize(&admin, &backend);
+
+    let token = token::Client::new(env, &token_id);
+
+    (client, admin, backend, token)
+}
+
+/// Three-entry tree: root = H(H(a, b), c)
+struct Tree {
+    root: BytesN<32>,
+    leaves: [BytesN<32>; 3],
+    ab: BytesN<32>,
+}
+
+fn build_tree(env: &Env, entries: &[(Address, i128); 3]) -> Tree {
+    let leaves = [
+        merkle_leaf(env, &entries[0].0, entries[0].1),
+        merkle_leaf(env, &entries[1].0, entries[1].1),
+        merkle_leaf(env, &entries[2].0, entries[2].1),
+    ];
+    let ab = merkle_hash_pair(env, &leaves[0], &leaves[1]);
+    Tree {
+        root: merkle_hash_pair(env, &ab, &leaves[2]),
+        leaves,
+        ab,
+    }
+}
+
+#[test]
+fn entries_in_the_tree_claim_exactly_once() {
+    let env = Env::default();
+    let (client, _admin, backend, token) = setup_test(&env);
+    let entries = [
+        (Address::generate(&env), 100),
+        (Address::generate(&env), 250),
+        (Address::generate(&env), 650),
+    ];
+    let tree = build_tree(&env, &entries);
+    client.create_distribution(
+        &backend,
+        &1,
+        &token.address,
+        &tree.root,
+        &1_000,
+        &EXPIRES_AT,
+    );
+    assert_eq!(token.balance(&client.address), 1_000);
+
+    let (alice, alice_amount) = &entries[0];
+    let alice_proof = vec![&env, tree.leaves[1].clone(), tree.leaves[2].clone()];
+    assert_eq!(client.claim(alice, &1, alice_amount, &alice_proof), 100);
+    assert_eq!(token.balance(alice), 100);
+    assert!(client.is_claimed(&1, alice));
+    assert_eq!(
+        client.try_claim(alice, &1, alice_amount, &alice_proof),
+        Err(Ok(ClaimsError::DuplicateOperation))
+    );
+
+    let (carol, carol_amount) = &entries[2];
+    client.claim(carol, &1, carol_amount, &vec![&env, tree.ab.clone()]);
+    assert_eq!(client.get_distribution(&1).claimed_amount, 750);
+}
+
+#[test]
+fn forged_claims_are_rejected() {
+    let env = Env::default();
+    let (client, _admin, backend, token) = setup_test(&env);
+    let entries = [
+        (Address::generate(&env), 100),
+        (Address::generate(&env), 250),
+        (Address::generate(&env), 650),
+    ];
+    let tree = build_tree(&env, &entries);
+    client.create_distribution(
+        &backend,
+        &1,
+        &token.address,
+        &tree.root,
+        &1_000,
+        &EXPIRES_AT,
+    );
+
+    let (bob, _) = &entries[1];
+    let bob_proof = vec![&env, tree.leaves[0].clone(), tree.leaves[2].clone()];
+    // Inflated amount
+    assert_eq!(
+        client.try_claim(bob, &1, &1_000, &bob_proof),
+        Err(Ok(ClaimsError::InvalidProof))
+    );
+    // Someone else's proof
+    assert_eq!(
+        client.try_claim(&Address::generate(&env), &1, &250, &bob_proof),
+        Err(Ok(ClaimsError::InvalidProof))
+    );
+    assert_eq!(
+        client.try_claim(bob, &2, &250, &bob_proof),
+        Err(Ok(ClaimsError::DistributionNotFound))
+    );
+}
+
+#[test]
+fn distributions_need_an_operator_and_a_future_expiry() {
+    let env = Env::default();
+    let (client, _admin, backend, token) = setup_test(&env);
+    let root = BytesN::from_array(&env, &[7u8; 32]);
+
+    assert_eq!(
+        client.try_create_distribution(
+            &Address::generate(&env),
+            &1,
+            &token.address,
+            &root,
+            &1_000,
+            &EXPIRES_AT
+        ),
+        Err(Ok(ClaimsError::Unauthorized))
+    );
+    env.ledger().with_mut(|li| li.timestamp = EXPIRES_AT);
+    assert_eq!(
+        client.try_create_distribution(&backend, &1, &token.address, &root, &1_000, &EXPIRES_AT),
+        Err(Ok(ClaimsError::InvalidConfig))
+    );
+
+    client.create_distribution(
+        &backend,
+        &1,
+        &token.address,
+        &root,
+        &1_000,
+        &(EXPIRES_AT + 1),
+    );
+    assert_eq!(
+        client.try_create_distribution(
+            &backend,
+            &1,
+            &token.address,
+            &root,
+            &1_000,
+            &(EXPIRES_AT + 1)
+        ),
+        Err(Ok(ClaimsError::DuplicateOperation))
+    );
+}
+
+#[test]
+fn unclaimed_funds_are_swept_after_expiry() {
+    let env = Env::default();
+    let (client, admin, backend, token) = setup_test(&env);
+    let entries = [
+        (Address::generate(&env), 100),
+        (Address::generate(&env), 250),
+        (Address::generate(&env), 650),
+    ];
+    let tree = build_tree(&env, &entries);
+    client.create_distribution(
+        &backend,
+        &1,
+        &token.address,
+        &tree.root,
+        &1_000,
+        &EXPIRES_AT,
+    );
+    let (carol, carol_amount) = &entries[2];
+    client.claim(carol, &1, carol_amount, &vec![&env, tree.ab.clone()]);
+
+    let treasury = Address::generate(&env);
+    assert_eq!(
+        client.try_sweep(&admin, &1, &treasury),
+        Err(Ok(ClaimsError::InvalidStatus))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = EXPIRES_AT);
+    let (alice, alice_amount) = &entries[0];
+    assert_eq!(
+        client.try_claim(
+            alice,
+            &1,
+            alice_amount,
+            &vec![&env, tree.leaves[1].clone(), tree.leaves[2].clone()]
+        ),
+        Err(Ok(ClaimsError::Expired))
+    );
+
+    assert_eq!(client.sweep(&admin, &1, &treasury), 350);
+    assert_eq!(token.balance(&treasury), 350);
+    assert_eq!(token.balance(&client.address), 0);
+    assert_eq!(
+        client.try_sweep(&admin, &1, &treasury),
+        Err(Ok(ClaimsError::InvalidStatus))
+    );
+}
@@ -0,0 +1,148 @@
+use crate::ContractError;
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+/// Operational roles a contract can hand out instead of sharing one admin key.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// Manages the other roles and contract configuration
+    Admin,
+    /// Runs day-to-day backend operations (settlements, reward payouts)
+    Operator,
+    /// May stop the contract during an incident
+    Pauser,
+    /// Manages where funds come from and go to
+    Treasurer,
+    /// May halt sensitive changes, e.g. cancel timelocked actions or
+    /// challenge reported results
+    Guardian,
+    /// Settles disputes between two parties, e.g. over an escrow
+    Arbiter,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Member(Role, Address),
+    RoleAdmin(Role),
+}
+
+/// Grant `role` to `account` without any checks. Only meant for a contract's
+/// `initialize`, before any role holder exists.
+pub fn init_role(env: &Env, role: Role, account: &Address) {
+    set_member(env, role, account, true);
+    publish_role_event(env, "role_granted", role, account, account);
+}
+
+pub fn has_role(env: &Env, role: Role, account: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Member(role, account.clone()))
+}
+
+/// `account` must authorize the call and hold `role`.
+pub fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), ContractError> {
+    account.require_auth();
+    if !has_role(env, role, account) {
+        return Err(ContractError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Role whose holders may grant and revoke `role`. Defaults to `Admin`.
+pub fn get_role_admin(env: &Env, role: Role) -> Role {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RoleAdmin(role))
+        .unwrap_or(Role::Admin)
+}
+
+pub fn set_role_admin(
+    env: &Env,
+    caller: &Address,
+    role: Role,
+    admin_role: Role,
+) -> Result<(), ContractError> {
+    require_role(env, get_role_admin(env, role), caller)?;
+    env.storage()
+        .persistent()
+        .set(&DataKey::RoleAdmin(role), &admin_role);
+    publish_role_admin_event(env, caller, role, admin_role);
+    Ok(())
+}
+
+pub fn grant_role(
+    env: &Env,
+    caller: &Address,
+    role: Role,
+    account: &Address,
+) -> Result<(), ContractError> {
+    require_role(env, get_role_admin(env, role), caller)?;
+    if !has_role(env, role, account) {
+        set_member(env, role, account, true);
+        publish_role_event(env, "role_granted", role, account, caller);
+    }
+    Ok(())
+}
+
+pub fn revoke_role(
+    env: &Env,
+    caller: &Address,
+    role: Role,
+    account: &Address,
+) -> Result<(), ContractError> {
+    require_role(env, get_role_admin(env, role), caller)?;
+    if has_role(env, role, account) {
+        set_member(env, role, account, false);
+        publish_role_event(env, "role_revoked", role, account, caller);
+    }
+    Ok(())
+}
+
+/// Grant or revoke without checking the caller, for changes authorized
+/// another way, e.g. a timelocked action that has matured.
+pub fn apply_role_change(env: &Env, role: Role, account: &Address, granted: bool, by: &Address) {
+    if has_role(env, role, account) == granted {
+        return;
+    }
+    set_member(env, role, account, granted);
+    let topic = if granted {
+        "role_granted"
+    } else {
+        "role_revoked"
+    };
+    publish_role_event(env, topic, role, account, by);
+}
+
+/// Let a holder give up a role, e.g. after its key was exposed.
+pub fn renounce_role(env: &Env, account: &Address, role: Role) -> Result<(), ContractError> {
+    require_role(env, role, account)?;
+    set_member(env, role, account, false);
+    publish_role_event(env, "role_revoked", role, account, account);
+    Ok(())
+}
+
+fn set_member(env: &Env, role: Role, account: &Address, member: bool) {
+    let key = DataKey::Member(role, account.clone());
+    if member {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_role_event(env: &Env, topic: &str, role: Role, account: &Address, by: &Address) {
+    env.events().publish(
+        (Symbol::new(env, topic), role, account.clone()),
+        (by.clone(), env.ledger().timestamp()),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_role_admin_event(env: &Env, by: &Address, role: Role, admin_role: Role) {
+    env.events().publish(
+        (Symbol::new(env, "role_admin_changed"), role),
+        (admin_role, by.clone()),
+    );
+}
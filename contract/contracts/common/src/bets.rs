@@ -0,0 +1,89 @@
+use crate::ContractError;
+use soroban_sdk::{contracttype, vec, Address, BytesN, Env, IntoVal, Symbol, Vec};
+
+// ===== BET TYPES =====
+
+/// Where a bet stands. Only `Open` and `Locked` bets hold escrow; the
+/// others are final and kept for the record.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BetStatus {
+    /// Accepting changes: the bettor may still cancel before the lock time
+    Open,
+    /// The match is underway; only the backend can cancel
+    Locked,
+    /// Paid out or lost, as reported by the settlement contract
+    Settled,
+    /// Cancelled by the bettor or the backend, stake returned
+    Cancelled,
+    /// Stake returned because the registry cancelled the match
+    Refunded,
+}
+
+/// Odds are fixed-point with two decimals: 250 means 2.50x.
+pub const ODDS_SCALE: u64 = 100;
+pub const MIN_PARLAY_LEGS: u32 = 2;
+pub const MAX_PARLAY_LEGS: u32 = 10;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParlayLeg {
+    pub match_id: BytesN<32>,
+    pub bet_type: Symbol,
+    pub odds: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Parlay {
+    pub parlay_id: u64,
+    pub bettor: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub legs: Vec<ParlayLeg>,
+    /// Product of all leg odds, in the same fixed-point scale as a leg
+    pub combined_odds: u64,
+    pub timestamp: u64,
+    /// `Open` while escrowed, then `Settled`, `Cancelled` or `Refunded`
+    pub status: BetStatus,
+}
+
+/// Multiply leg odds together, rescaling after every step so the
+/// intermediate product stays in range. Rounds down; fails on overflow.
+pub fn combine_odds(legs: &Vec<ParlayLeg>) -> Result<u64, ContractError> {
+    let mut combined = ODDS_SCALE;
+    for leg in legs.iter() {
+        combined = combined
+            .checked_mul(leg.odds as u64)
+            .ok_or(ContractError::InvalidBet)?
+            / ODDS_SCALE;
+    }
+    Ok(combined)
+}
+
+/// Potential payout of a stake at `combined_odds`, failing on overflow.
+pub fn potential_payout(amount: i128, combined_odds: u64) -> Result<i128, ContractError> {
+    amount
+        .checked_mul(combined_odds as i128)
+        .map(|scaled| scaled / ODDS_SCALE as i128)
+        .ok_or(ContractError::InvalidBet)
+}
+
+// ===== BETTING LOOKUPS =====
+
+/// Read a parlay from the betting contract. Unknown parlays map to
+/// `BetNotFound` instead of trapping the caller.
+pub fn get_parlay_info(
+    env: &Env,
+    betting: &Address,
+    parlay_id: u64,
+) -> Result<Parlay, ContractError> {
+    match env.try_invoke_contract::<Parlay, ContractError>(
+        betting,
+        &Symbol::new(env, "get_parlay"),
+        vec![env, parlay_id.into_val(env)],
+    ) {
+        Ok(Ok(parlay)) => Ok(parlay),
+        _ => Err(ContractError::BetNotFound),
+    }
+}
@@ -0,0 +1,43 @@
+use crate::ContractError;
+use soroban_sdk::{vec, Address, Env, IntoVal, Symbol};
+
+// ===== CONFIG REGISTRY LOOKUPS =====
+
+/// Well-known `config_registry` address keys.
+pub const TREASURY_KEY: &str = "treasury";
+pub const BALANCE_LEDGER_KEY: &str = "balance_ledger";
+pub const ORACLE_KEY: &str = "oracle";
+pub const TOKEN_KEY: &str = "token";
+
+/// Look up the address registered under `key` in a `config_registry`
+/// contract; `None` if the key is unset.
+pub fn resolve_address(
+    env: &Env,
+    registry: &Address,
+    key: &str,
+) -> Result<Option<Address>, ContractError> {
+    match env.try_invoke_contract::<Option<Address>, ContractError>(
+        registry,
+        &Symbol::new(env, "get_address"),
+        vec![env, Symbol::new(env, key).into_val(env)],
+    ) {
+        Ok(Ok(address)) => Ok(address),
+        _ => Err(ContractError::InvalidConfig),
+    }
+}
+
+/// Look up the numeric parameter registered under `key`; `None` if unset.
+pub fn resolve_param(
+    env: &Env,
+    registry: &Address,
+    key: &str,
+) -> Result<Option<i128>, ContractError> {
+    match env.try_invoke_contract::<Option<i128>, ContractError>(
+        registry,
+        &Symbol::new(env, "get_param"),
+        vec![env, Symbol::new(env, key).into_val(env)],
+    ) {
+        Ok(Ok(value)) => Ok(value),
+        _ => Err(ContractError::InvalidConfig),
+    }
+}
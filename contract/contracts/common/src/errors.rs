@@ -1,5 +1,10 @@
 use soroban_sdk::contracterror;
 
+/// Shared error set. Codes are part of the public interface decoded by
+/// off-chain clients: never reuse or renumber one, only append. A spec
+/// holds at most 50 cases, so errors only one contract raises belong in
+/// that contract's own enum. Codes 35-37, 45 and 47-49 moved out that way
+/// and stay unused.
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -23,4 +28,31 @@ pub enum ContractError {
     AlreadyInitialized = 17,
     BetAlreadyPlaced = 18,
     DuplicateOperation = 19,
+    MatchNotFound = 20,
+    MatchAlreadyExists = 21,
+    MatchNotOpen = 22,
+    Paused = 23,
+    InvalidConfig = 24,
+    OperatorFrozen = 25,
+    RecoveryNotFound = 26,
+    RecoveryNotReady = 27,
+    ExceedsLimit = 28,
+    Expired = 29,
+    TokenNotAllowed = 30,
+    MarketNotFound = 31,
+    OddsChanged = 32,
+    SelfReferral = 33,
+    ReferrerAlreadySet = 34,
+    InvalidNonce = 38,
+    TokenNotTransferable = 39,
+    TierNotFound = 40,
+    InsufficientRewardPool = 41,
+    ActionNotFound = 42,
+    TimelockNotReady = 43,
+    TimelockRequired = 44,
+    ConditionNotMet = 46,
+    BelowMinBet = 50,
+    AboveMaxBet = 51,
+    BettingClosed = 52,
+    SelfExcluded = 53,
 }
@@ -1,10 +1,24 @@
-use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Map, String, Symbol, U256};
+use soroban_sdk::{
+    contracttype, symbol_short, Address, BytesN, Env, IntoVal, Map, String, Symbol, Topics, Val,
+    U256,
+};
+
+/// Schema version stamped into every event below and into its topics.
+/// Version 1 is the unversioned layout that predates the field.
+pub const EVENT_SCHEMA_VERSION: u32 = 2;
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    LegacyEventsUntil,
+}
 
 // ===== CORE EVENTS =====
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct StakeEvent {
+    pub version: u32,
     pub user: Address,
     pub amount: i128,
     pub token_address: Address,
@@ -16,6 +30,7 @@ pub struct StakeEvent {
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct UnstakeEvent {
+    pub version: u32,
     pub user: Address,
     pub amount: i128,
     pub token_address: Address,
@@ -23,11 +38,13 @@ pub struct UnstakeEvent {
     pub timestamp: u64,
     pub stake_id: U256,
     pub rewards: i128,
+    pub remaining_amount: i128,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BetEvent {
+    pub version: u32,
     pub bettor: Address,
     pub amount: i128,
     pub bet_id: U256,
@@ -41,6 +58,7 @@ pub struct BetEvent {
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SettlementEvent {
+    pub version: u32,
     pub bet_id: U256,
     pub winner: Address,
     pub payout: i128,
@@ -54,6 +72,7 @@ pub struct SettlementEvent {
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SpinRewardEvent {
+    pub version: u32,
     pub user: Address,
     pub reward_amount: i128,
     pub token_address: Address,
@@ -68,6 +87,7 @@ pub struct SpinRewardEvent {
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct NFTMintEvent {
+    pub version: u32,
     pub token_id: U256,
     pub to: Address,
     pub token_uri: String,
@@ -78,9 +98,61 @@ pub struct NFTMintEvent {
     pub price: Option<i128>,
 }
 
+// Stake withdrawn past its cooldown and lock, minus the emergency penalty.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmergencyUnstakeEvent {
+    pub version: u32,
+    pub user: Address,
+    pub stake_id: U256,
+    pub amount: i128,
+    pub forfeited: i128,
+    pub penalty_collector: Address,
+    pub timestamp: u64,
+}
+
+// Collateral taken from a stake by an authorized slasher.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StakeSlashedEvent {
+    pub version: u32,
+    pub user: Address,
+    pub stake_id: U256,
+    pub slasher: Address,
+    pub amount: i128,
+    pub remaining_amount: i128,
+    pub recipient: Address,
+    pub reason_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+// Transfer of an existing NFT from one holder to another.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NFTTransferEvent {
+    pub version: u32,
+    pub token_id: U256,
+    pub from: Address,
+    pub to: Address,
+    pub nft_contract: Address,
+    pub timestamp: u64,
+}
+
+// NFT destroyed by its owner or the contract admin.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NFTBurnEvent {
+    pub version: u32,
+    pub token_id: U256,
+    pub owner: Address,
+    pub nft_contract: Address,
+    pub timestamp: u64,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ReplayRejectedEvent {
+    pub version: u32,
     pub operation_hash: BytesN<32>,
     pub scope: Symbol,
     pub timestamp: u64,
@@ -90,6 +162,7 @@ pub struct ReplayRejectedEvent {
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SpinExecutedEvent {
+    pub version: u32,
     pub spin_id: BytesN<32>,
     pub executor: Address,
     pub timestamp: u64,
@@ -99,19 +172,168 @@ pub struct SpinExecutedEvent {
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BetPlacedEvent {
+    pub version: u32,
     pub bettor: Address,
     pub bet_id: Symbol,
     pub amount: i128,
 }
 
+// Full record of an accepted bet, emitted alongside the legacy event.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WagerPlacedEvent {
+    pub version: u32,
+    /// Sequential id, unique within the emitting betting contract
+    pub bet_id: u64,
+    pub bettor: Address,
+    pub match_id: BytesN<32>,
+    pub bet_type: Symbol,
+    /// Price the bet was taken at
+    pub odds: u32,
+    pub token: Address,
+    /// Escrowed stake, after the fee and the jackpot share
+    pub amount: i128,
+    pub timestamp: u64,
+    /// Affiliate the bet was tagged with
+    pub affiliate: Option<Address>,
+}
+
+// Funds moved by one settlement, so statements reconcile to the unit:
+// `payout + fee + rake` is the gross payout of a WIN.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementExecutedEvent {
+    pub version: u32,
+    pub bet_id: u64,
+    /// `WIN`, `LOSS` or `DRAW`
+    pub outcome: Symbol,
+    pub bettor: Address,
+    pub winner: Option<Address>,
+    pub bet_amount: i128,
+    /// Credited to the winner, net of `fee` and `rake`
+    pub payout: i128,
+    pub fee: i128,
+    /// Share of the winnings forwarded to the treasury
+    pub rake: i128,
+    pub timestamp: u64,
+}
+
+// Emitted when an escrowed bet is refunded before settlement.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BetCancelledEvent {
+    pub version: u32,
+    pub bettor: Address,
+    pub match_id: BytesN<32>,
+    pub amount: i128,
+    pub cancelled_by: Address,
+    pub timestamp: u64,
+}
+
+// Emitted whenever the backend moves (or first publishes) a market's line.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OddsUpdatedEvent {
+    pub version: u32,
+    pub match_id: BytesN<32>,
+    pub bet_type: Symbol,
+    pub previous_odds: Option<u32>,
+    pub odds: u32,
+    pub timestamp: u64,
+}
+
+// Emitted when the backend-selected winner is paid the progressive pool.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JackpotWonEvent {
+    pub version: u32,
+    pub winner: Address,
+    pub amount: i128,
+    pub operation_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+// Emitted whenever a bet or settlement fee is forwarded to the collector.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeCollectedEvent {
+    pub version: u32,
+    pub payer: Address,
+    /// `None` when the fee moved as a ledger balance rather than a token
+    pub token: Option<Address>,
+    pub amount: i128,
+    pub fee_collector: Address,
+    pub timestamp: u64,
+}
+
 // ===== EVENT CONSTANTS =====
 
 pub const STAKE_EVENT: Symbol = symbol_short!("STAKE");
 pub const UNSTAKE_EVENT: Symbol = symbol_short!("UNSTAKE");
+pub const EMERGENCY_UNSTAKE_EVENT: Symbol = symbol_short!("EMRG_UNST");
+pub const SLASH_EVENT: Symbol = symbol_short!("SLASH");
 pub const BET_EVENT: Symbol = symbol_short!("BET");
 pub const SETTLEMENT_EVENT: Symbol = symbol_short!("SETTLE");
 pub const SPIN_REWARD_EVENT: Symbol = symbol_short!("SPIN_RWD");
 pub const NFT_MINT_EVENT: Symbol = symbol_short!("NFT_MINT");
+pub const NFT_TRANSFER_EVENT: Symbol = symbol_short!("NFT_XFER");
+pub const NFT_BURN_EVENT: Symbol = symbol_short!("NFT_BURN");
+pub const BET_PLACED_EVENT: Symbol = symbol_short!("BET_PLACE");
+pub const WAGER_PLACED_EVENT: Symbol = symbol_short!("WAGER_PLC");
+pub const SETTLEMENT_EXECUTED_EVENT: Symbol = symbol_short!("SETL_EXEC");
+pub const BET_CANCELLED_EVENT: Symbol = symbol_short!("BET_CNCL");
+pub const SPIN_EXECUTED_EVENT: Symbol = symbol_short!("SPIN_EXEC");
+pub const ODDS_UPDATED_EVENT: Symbol = symbol_short!("ODDS_UPD");
+pub const JACKPOT_WON_EVENT: Symbol = symbol_short!("JKPT_WON");
+pub const FEE_COLLECTED_EVENT: Symbol = symbol_short!("FEE_COLL");
+pub const REPLAY_REJECTED_EVENT: Symbol = symbol_short!("REPLAY_RJ");
+
+// ===== VERSIONED PUBLISHING =====
+// Events go out under `(EVENT_SYMBOL, EVENT_SCHEMA_VERSION, subject)`. Until
+// the emitting contract closes its legacy window, each one is also emitted
+// under the topics it used before versioning so existing indexers keep
+// working while they migrate. Payloads are maps, so the added `version`
+// field does not change how older listeners decode them.
+
+/// Time at which legacy duplicates stop, or `None` while the window is open
+/// indefinitely.
+pub fn get_legacy_events_until(env: &Env) -> Option<u64> {
+    env.storage().instance().get(&DataKey::LegacyEventsUntil)
+}
+
+/// Stop emitting legacy duplicates from `until` on; `None` keeps them.
+pub fn set_legacy_events_until(env: &Env, until: Option<u64>) {
+    match until {
+        Some(until) => env
+            .storage()
+            .instance()
+            .set(&DataKey::LegacyEventsUntil, &until),
+        None => env.storage().instance().remove(&DataKey::LegacyEventsUntil),
+    }
+}
+
+pub fn legacy_events_enabled(env: &Env) -> bool {
+    get_legacy_events_until(env).is_none_or(|until| env.ledger().timestamp() < until)
+}
+
+#[allow(deprecated)] // topic layout is versioned by hand, see above
+pub fn publish_versioned_event<L, S, T>(
+    env: &Env,
+    legacy_topics: L,
+    event: Symbol,
+    subject: S,
+    payload: T,
+) where
+    L: Topics,
+    S: IntoVal<Env, Val>,
+    T: IntoVal<Env, Val> + Clone,
+{
+    if legacy_events_enabled(env) {
+        env.events().publish(legacy_topics, payload.clone());
+    }
+    env.events()
+        .publish((event, EVENT_SCHEMA_VERSION, subject), payload);
+}
 
 // ===== EVENT HELPERS =====
 
@@ -123,6 +345,7 @@ pub fn create_stake_event(
     stake_id: U256,
 ) -> StakeEvent {
     StakeEvent {
+        version: EVENT_SCHEMA_VERSION,
         user,
         amount,
         token_address,
@@ -141,6 +364,7 @@ pub fn create_unstake_event(
     rewards: i128,
 ) -> UnstakeEvent {
     UnstakeEvent {
+        version: EVENT_SCHEMA_VERSION,
         user,
         amount,
         token_address,
@@ -148,6 +372,7 @@ pub fn create_unstake_event(
         timestamp: 0, // Will be set by contract
         stake_id,
         rewards,
+        remaining_amount: 0, // Set by contract on partial unstakes
     }
 }
 
@@ -161,6 +386,7 @@ pub fn create_bet_event(
     odds: u32,
 ) -> BetEvent {
     BetEvent {
+        version: EVENT_SCHEMA_VERSION,
         bettor,
         amount,
         bet_id,
@@ -182,6 +408,7 @@ pub fn create_settlement_event(
     final_odds: u32,
 ) -> SettlementEvent {
     SettlementEvent {
+        version: EVENT_SCHEMA_VERSION,
         bet_id,
         winner,
         payout,
@@ -203,6 +430,7 @@ pub fn create_nft_mint_event(
     price: Option<i128>,
 ) -> NFTMintEvent {
     NFTMintEvent {
+        version: EVENT_SCHEMA_VERSION,
         token_id,
         to,
         token_uri,
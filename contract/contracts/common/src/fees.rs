@@ -0,0 +1,112 @@
+use crate::{
+    publish_versioned_event, ContractError, FeeCollectedEvent, EVENT_SCHEMA_VERSION,
+    FEE_COLLECTED_EVENT,
+};
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+/// Highest fee a contract may charge, in basis points (10%).
+pub const MAX_FEE_BPS: u32 = 1_000;
+
+const BPS_DENOMINATOR: i128 = 10_000;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeConfig {
+    pub fee_bps: u32,
+    /// Receives every fee, normally the treasury contract
+    pub fee_collector: Address,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    FeeConfig,
+    TotalFees(Option<Address>),
+}
+
+/// Callers own auth. A zero `fee_bps` turns fees off.
+pub fn set_fee_config(
+    env: &Env,
+    fee_bps: u32,
+    fee_collector: &Address,
+) -> Result<FeeConfig, ContractError> {
+    if fee_bps > MAX_FEE_BPS {
+        return Err(ContractError::InvalidConfig);
+    }
+    let config = FeeConfig {
+        fee_bps,
+        fee_collector: fee_collector.clone(),
+    };
+    env.storage().persistent().set(&DataKey::FeeConfig, &config);
+    Ok(config)
+}
+
+pub fn get_fee_config(env: &Env) -> Option<FeeConfig> {
+    env.storage().persistent().get(&DataKey::FeeConfig)
+}
+
+/// Fee owed on `amount` with its collector, rounded down. `None` while no
+/// fee is configured or it rounds to zero.
+pub fn quote_fee(env: &Env, amount: i128) -> Result<Option<(i128, Address)>, ContractError> {
+    if amount < 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    let config = match get_fee_config(env) {
+        Some(config) => config,
+        None => return Ok(None),
+    };
+    let fee = amount
+        .checked_mul(config.fee_bps as i128)
+        .ok_or(ContractError::InvalidAmount)?
+        / BPS_DENOMINATOR;
+    if fee == 0 {
+        return Ok(None);
+    }
+    Ok(Some((fee, config.fee_collector)))
+}
+
+/// Add a forwarded fee to the running total for `token` (`None` for
+/// contracts that only move ledger balances) and emit `fee_collected`.
+pub fn record_fee(
+    env: &Env,
+    payer: &Address,
+    token: Option<Address>,
+    fee: i128,
+    fee_collector: &Address,
+) -> Result<i128, ContractError> {
+    let key = DataKey::TotalFees(token.clone());
+    let total = total_fees_collected(env, token.clone())
+        .checked_add(fee)
+        .ok_or(ContractError::InvalidAmount)?;
+    env.storage().persistent().set(&key, &total);
+
+    publish_fee_collected_event(
+        env,
+        FeeCollectedEvent {
+            version: EVENT_SCHEMA_VERSION,
+            payer: payer.clone(),
+            token,
+            amount: fee,
+            fee_collector: fee_collector.clone(),
+            timestamp: env.ledger().timestamp(),
+        },
+    );
+    Ok(total)
+}
+
+pub fn total_fees_collected(env: &Env, token: Option<Address>) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TotalFees(token))
+        .unwrap_or(0)
+}
+
+fn publish_fee_collected_event(env: &Env, event: FeeCollectedEvent) {
+    publish_versioned_event(
+        env,
+        (Symbol::new(env, "fee_collected"), event.payer.clone()),
+        FEE_COLLECTED_EVENT,
+        event.payer.clone(),
+        event,
+    );
+}
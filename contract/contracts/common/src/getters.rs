@@ -90,6 +90,19 @@ pub struct UserPortfolio {
     pub last_updated: u64,
 }
 
+// ===== PLATFORM ACTIVITY =====
+
+/// Summarized activity pushed into the stats hub by platform contracts.
+/// Used both as a per-call delta and as the accumulated bucket value.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ActivityStats {
+    pub bets_placed: u64,
+    pub volume: i128,
+    pub payouts: i128,
+    pub mints: u64,
+}
+
 // ===== GETTER CONSTANTS =====
 
 pub const USER_BALANCE_PREFIX: &str = "USER_BALANCE";
@@ -0,0 +1,205 @@
+use crate::{pausable, ContractError};
+use soroban_sdk::{contracttype, Address, Env, Symbol, Vec};
+
+/// Shortest delay a recovery admin rotation can be queued with. Gives the
+/// current admin time to notice and cancel a rotation by colluding guardians.
+pub const MIN_RECOVERY_DELAY: u64 = 86_400;
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    GuardianConfig,
+    FrozenOperator(Address),
+    PendingRecovery,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GuardianConfig {
+    pub guardians: Vec<Address>,
+    /// Guardian approvals needed before a recovery rotation starts its delay
+    pub threshold: u32,
+    pub recovery_delay: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecoveryRequest {
+    pub new_admin: Address,
+    pub approvals: Vec<Address>,
+    pub queued_at: u64,
+    /// Set once `threshold` guardians have approved
+    pub executable_at: Option<u64>,
+}
+
+// ===== CONFIGURATION =====
+// Callers are expected to have checked their own admin auth first.
+
+pub fn set_guardian_config(
+    env: &Env,
+    guardians: Vec<Address>,
+    threshold: u32,
+    recovery_delay: u64,
+) -> Result<GuardianConfig, ContractError> {
+    if threshold == 0 || threshold > guardians.len() || recovery_delay < MIN_RECOVERY_DELAY {
+        return Err(ContractError::InvalidConfig);
+    }
+    for (i, guardian) in guardians.iter().enumerate() {
+        if guardians.first_index_of(&guardian) != Some(i as u32) {
+            return Err(ContractError::InvalidConfig);
+        }
+    }
+
+    let config = GuardianConfig {
+        guardians,
+        threshold,
+        recovery_delay,
+    };
+    let storage = env.storage().persistent();
+    storage.set(&DataKey::GuardianConfig, &config);
+    // Approvals collected under the old set no longer count
+    storage.remove(&DataKey::PendingRecovery);
+
+    Ok(config)
+}
+
+pub fn get_guardian_config(env: &Env) -> Option<GuardianConfig> {
+    env.storage().persistent().get(&DataKey::GuardianConfig)
+}
+
+pub fn is_guardian(env: &Env, address: &Address) -> bool {
+    get_guardian_config(env)
+        .map(|config| config.guardians.contains(address))
+        .unwrap_or(false)
+}
+
+pub fn require_guardian(env: &Env, guardian: &Address) -> Result<GuardianConfig, ContractError> {
+    guardian.require_auth();
+    let config = get_guardian_config(env).ok_or(ContractError::NotInitialized)?;
+    if !config.guardians.contains(guardian) {
+        return Err(ContractError::Unauthorized);
+    }
+    Ok(config)
+}
+
+// ===== EMERGENCY PAUSE =====
+
+/// Any single guardian can trip the shared `pausable` circuit breaker;
+/// lifting it is left to the admin via `pausable::unpause`.
+pub fn guardian_pause(env: &Env, guardian: &Address) -> Result<(), ContractError> {
+    require_guardian(env, guardian)?;
+    pausable::set_paused(env, true);
+    publish_guardian_event(env, "guardian_pause", guardian, None);
+    Ok(())
+}
+
+// ===== OPERATOR FREEZE =====
+
+/// Block a (possibly compromised) operator key from acting on the contract.
+pub fn freeze_operator(
+    env: &Env,
+    guardian: &Address,
+    operator: &Address,
+) -> Result<(), ContractError> {
+    require_guardian(env, guardian)?;
+    env.storage()
+        .persistent()
+        .set(&DataKey::FrozenOperator(operator.clone()), &true);
+    publish_guardian_event(env, "operator_frozen", guardian, Some(operator.clone()));
+    Ok(())
+}
+
+pub fn unfreeze_operator(env: &Env, operator: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::FrozenOperator(operator.clone()));
+}
+
+pub fn is_operator_frozen(env: &Env, operator: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::FrozenOperator(operator.clone()))
+}
+
+pub fn require_operator_not_frozen(env: &Env, operator: &Address) -> Result<(), ContractError> {
+    if is_operator_frozen(env, operator) {
+        return Err(ContractError::OperatorFrozen);
+    }
+    Ok(())
+}
+
+// ===== RECOVERY ROTATION =====
+
+/// Record a guardian's approval for rotating the admin to `new_admin`.
+/// Approving a different address than the pending request replaces it and
+/// resets the collected approvals.
+pub fn approve_recovery(
+    env: &Env,
+    guardian: &Address,
+    new_admin: &Address,
+) -> Result<RecoveryRequest, ContractError> {
+    let config = require_guardian(env, guardian)?;
+    let storage = env.storage().persistent();
+    let now = env.ledger().timestamp();
+
+    let mut request = match storage.get::<_, RecoveryRequest>(&DataKey::PendingRecovery) {
+        Some(request) if request.new_admin == *new_admin => request,
+        _ => RecoveryRequest {
+            new_admin: new_admin.clone(),
+            approvals: Vec::new(env),
+            queued_at: now,
+            executable_at: None,
+        },
+    };
+
+    if !request.approvals.contains(guardian) {
+        request.approvals.push_back(guardian.clone());
+    }
+    if request.executable_at.is_none() && request.approvals.len() >= config.threshold {
+        request.executable_at = Some(now + config.recovery_delay);
+    }
+    storage.set(&DataKey::PendingRecovery, &request);
+
+    publish_guardian_event(env, "recovery_approved", guardian, Some(new_admin.clone()));
+
+    Ok(request)
+}
+
+pub fn get_pending_recovery(env: &Env) -> Option<RecoveryRequest> {
+    env.storage().persistent().get(&DataKey::PendingRecovery)
+}
+
+/// Drop the pending rotation. Callers gate this on their current admin.
+pub fn cancel_recovery(env: &Env) -> Result<(), ContractError> {
+    let storage = env.storage().persistent();
+    if !storage.has(&DataKey::PendingRecovery) {
+        return Err(ContractError::RecoveryNotFound);
+    }
+    storage.remove(&DataKey::PendingRecovery);
+    Ok(())
+}
+
+/// Consume the pending rotation once its delay has elapsed and return the
+/// address the caller should install as its new admin.
+pub fn execute_recovery(env: &Env) -> Result<Address, ContractError> {
+    let storage = env.storage().persistent();
+    let request: RecoveryRequest = storage
+        .get(&DataKey::PendingRecovery)
+        .ok_or(ContractError::RecoveryNotFound)?;
+
+    match request.executable_at {
+        Some(at) if env.ledger().timestamp() >= at => {}
+        _ => return Err(ContractError::RecoveryNotReady),
+    }
+
+    storage.remove(&DataKey::PendingRecovery);
+    Ok(request.new_admin)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_guardian_event(env: &Env, topic: &str, guardian: &Address, target: Option<Address>) {
+    env.events().publish(
+        (Symbol::new(env, topic), guardian.clone()),
+        (target, env.ledger().timestamp()),
+    );
+}
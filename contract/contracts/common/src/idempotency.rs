@@ -1,5 +1,8 @@
-use crate::{ContractError, ReplayRejectedEvent};
-use soroban_sdk::{contracttype, BytesN, Env, Symbol};
+use crate::{
+    publish_versioned_event, ContractError, ReplayRejectedEvent, EVENT_SCHEMA_VERSION,
+    REPLAY_REJECTED_EVENT,
+};
+use soroban_sdk::{contracttype, Address, BytesN, Env, Symbol};
 
 const REPLAY_REJECTED_TOPIC: &str = "replay_rejected";
 
@@ -7,6 +10,7 @@ const REPLAY_REJECTED_TOPIC: &str = "replay_rejected";
 #[derive(Clone)]
 enum DataKey {
     ExecutedOp(Symbol, BytesN<32>),
+    Nonce(Symbol, Address),
 }
 
 #[contracttype]
@@ -65,6 +69,38 @@ pub fn is_operation_executed(env: &Env, scope: Symbol, operation_hash: BytesN<32
     }
 }
 
+/// Nonce-based alternative to `ensure_not_replayed` for backends that keep a
+/// simple counter instead of every operation hash. `nonce` must be exactly
+/// one above the last nonce accepted for `(scope, signer)`, starting at 1.
+/// Replays fail with `DuplicateOperation`, skipped values with `InvalidNonce`.
+pub fn ensure_nonce(
+    env: &Env,
+    scope: Symbol,
+    signer: &Address,
+    nonce: u64,
+) -> Result<(), ContractError> {
+    let current = get_current_nonce(env, scope.clone(), signer);
+    if nonce <= current {
+        return Err(ContractError::DuplicateOperation);
+    }
+    if nonce != current + 1 {
+        return Err(ContractError::InvalidNonce);
+    }
+
+    env.storage()
+        .persistent()
+        .set(&DataKey::Nonce(scope, signer.clone()), &nonce);
+    Ok(())
+}
+
+/// Last nonce accepted for `(scope, signer)`, 0 if none yet.
+pub fn get_current_nonce(env: &Env, scope: Symbol, signer: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Nonce(scope, signer.clone()))
+        .unwrap_or(0)
+}
+
 fn is_expired(env: &Env, record: &ExecutionRecord) -> bool {
     match record.ttl_seconds {
         Some(ttl) => env.ledger().timestamp().saturating_sub(record.executed_at) >= ttl,
@@ -72,14 +108,19 @@ fn is_expired(env: &Env, record: &ExecutionRecord) -> bool {
     }
 }
 
-#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
 fn emit_replay_rejected(env: &Env, scope: Symbol, operation_hash: BytesN<32>) {
     let event = ReplayRejectedEvent {
+        version: EVENT_SCHEMA_VERSION,
         operation_hash,
         scope,
         timestamp: env.ledger().timestamp(),
     };
 
-    env.events()
-        .publish((Symbol::new(env, REPLAY_REJECTED_TOPIC),), event);
+    publish_versioned_event(
+        env,
+        (Symbol::new(env, REPLAY_REJECTED_TOPIC),),
+        REPLAY_REJECTED_EVENT,
+        event.scope.clone(),
+        event,
+    );
 }
@@ -0,0 +1,61 @@
+use crate::ContractError;
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    vec, Address, Env, IntoVal, Symbol,
+};
+
+// ===== JACKPOT CONTRIBUTIONS =====
+
+/// Share of `amount` the jackpot would take, without moving any funds.
+/// Tokens other than the jackpot's own contribute nothing.
+pub fn quote_jackpot_contribution(
+    env: &Env,
+    jackpot: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<i128, ContractError> {
+    match env.try_invoke_contract::<i128, ContractError>(
+        jackpot,
+        &Symbol::new(env, "quote_contribution"),
+        vec![env, token.into_val(env), amount.into_val(env)],
+    ) {
+        Ok(Ok(share)) => Ok(share),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
+
+/// Have the jackpot pull `share` (as quoted for `amount`) from the calling
+/// contract. The transfer is pre-authorized for exactly that share.
+pub fn contribute_to_jackpot(
+    env: &Env,
+    jackpot: &Address,
+    token: &Address,
+    amount: i128,
+    share: i128,
+) -> Result<(), ContractError> {
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: token.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (env.current_contract_address(), jackpot.clone(), share).into_val(env),
+            },
+            sub_invocations: vec![env],
+        }),
+    ]);
+
+    match env.try_invoke_contract::<i128, ContractError>(
+        jackpot,
+        &Symbol::new(env, "contribute"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            token.into_val(env),
+            amount.into_val(env),
+        ],
+    ) {
+        Ok(Ok(taken)) if taken == share => Ok(()),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
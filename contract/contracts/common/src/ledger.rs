@@ -0,0 +1,101 @@
+use crate::ContractError;
+use soroban_sdk::{contracttype, vec, Address, Env, Error, IntoVal, Symbol, Val};
+
+// ===== BALANCE LEDGER ESCROW =====
+
+/// Where a betting contract holds stakes, fixed when it is initialized.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EscrowMode {
+    /// Bettors transfer real tokens into the betting contract
+    Token,
+    /// Stakes are locked as internal balances in this balance_ledger, and
+    /// the settlement contract pays them out there
+    Ledger(Address),
+}
+
+/// Move `amount` of `user`'s base-unit ledger balance from withdrawable to
+/// locked. The ledger's backend signer must authorize the call.
+pub fn lock_ledger_funds(
+    env: &Env,
+    balance_ledger: &Address,
+    user: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    invoke_ledger(env, balance_ledger, "lock_funds", user, amount)
+}
+
+/// Inverse of `lock_ledger_funds`, used when an escrowed stake is refunded.
+pub fn unlock_ledger_funds(
+    env: &Env,
+    balance_ledger: &Address,
+    user: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    invoke_ledger(env, balance_ledger, "unlock_funds", user, amount)
+}
+
+/// Count a stake taken outside the ledger against `user`'s wager and loss
+/// limits there. The calling contract must be a limit reporter on the
+/// ledger; fails with `ExceedsLimit` if the stake does not fit.
+pub fn record_ledger_wager(
+    env: &Env,
+    balance_ledger: &Address,
+    user: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    match env.try_invoke_contract::<Val, Error>(
+        balance_ledger,
+        &Symbol::new(env, "record_wager"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            user.into_val(env),
+            amount.into_val(env),
+        ],
+    ) {
+        Ok(Ok(_)) => Ok(()),
+        Err(Ok(err)) => Err(ledger_error(err)),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
+
+fn invoke_ledger(
+    env: &Env,
+    balance_ledger: &Address,
+    function: &str,
+    user: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    match env.try_invoke_contract::<Val, Error>(
+        balance_ledger,
+        &Symbol::new(env, function),
+        vec![env, user.into_val(env), amount.into_val(env)],
+    ) {
+        Ok(Ok(_)) => Ok(()),
+        Err(Ok(err)) => Err(ledger_error(err)),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
+
+// Codes from balance_ledger's own error enum
+fn ledger_error(err: Error) -> ContractError {
+    const UNAUTHORIZED: u32 = 1;
+    const INVALID_AMOUNT: u32 = 3;
+    const INSUFFICIENT_WITHDRAWABLE: u32 = 4;
+    const INSUFFICIENT_LOCKED: u32 = 5;
+    const PAUSED: u32 = 12;
+    const LIMIT_EXCEEDED: u32 = 16;
+
+    if !err.is_type(soroban_sdk::xdr::ScErrorType::Contract) {
+        return ContractError::TransferFailed;
+    }
+    match err.get_code() {
+        UNAUTHORIZED => ContractError::Unauthorized,
+        INVALID_AMOUNT => ContractError::InvalidAmount,
+        INSUFFICIENT_WITHDRAWABLE | INSUFFICIENT_LOCKED => ContractError::InsufficientBalance,
+        PAUSED => ContractError::Paused,
+        LIMIT_EXCEEDED => ContractError::ExceedsLimit,
+        _ => ContractError::TransferFailed,
+    }
+}
@@ -1,14 +1,46 @@
 #![no_std]
 
+pub mod access_control;
+pub mod bets;
+pub mod config;
 pub mod enums;
 pub mod errors;
 pub mod events;
+pub mod fees;
 pub mod getters;
+pub mod guardian;
 pub mod idempotency;
+pub mod jackpot;
+pub mod ledger;
+pub mod matches;
+pub mod merkle;
+pub mod pausable;
+pub mod player_card;
+pub mod referral;
+pub mod self_exclusion;
+pub mod timelock;
+pub mod ttl;
+pub mod upgrade;
 pub mod view_functions;
 
+pub use access_control::*;
+pub use bets::*;
+pub use config::*;
 pub use enums::*;
 pub use errors::*;
 pub use events::*;
+pub use fees::*;
 pub use getters::*;
+pub use guardian::*;
 pub use idempotency::*;
+pub use jackpot::*;
+pub use ledger::*;
+pub use matches::*;
+pub use merkle::*;
+pub use pausable::*;
+pub use player_card::*;
+pub use referral::*;
+pub use self_exclusion::*;
+pub use timelock::*;
+pub use ttl::*;
+pub use upgrade::*;
@@ -0,0 +1,90 @@
+use crate::ContractError;
+use soroban_sdk::{contracttype, vec, Address, BytesN, Env, IntoVal, Map, String, Symbol};
+
+// ===== MATCH REGISTRY TYPES =====
+
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MatchStatus {
+    Open,
+    Locked,
+    Settled,
+    Cancelled,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchInfo {
+    pub match_id: BytesN<32>,
+    pub metadata: Map<Symbol, String>,
+    /// Scheduled kickoff
+    pub start_time: u64,
+    /// Bets are rejected from this time on; an open match reads as locked
+    pub close_time: u64,
+    pub status: MatchStatus,
+    pub outcome: Option<Symbol>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+// ===== MATCH REGISTRY LOOKUPS =====
+
+/// Read a match's status from the registry contract.
+/// Unknown matches map to `MatchNotFound` instead of trapping the caller.
+pub fn get_match_status(
+    env: &Env,
+    registry: &Address,
+    match_id: &BytesN<32>,
+) -> Result<MatchStatus, ContractError> {
+    match env.try_invoke_contract::<MatchStatus, ContractError>(
+        registry,
+        &Symbol::new(env, "get_match_status"),
+        vec![env, match_id.into_val(env)],
+    ) {
+        Ok(Ok(status)) => Ok(status),
+        _ => Err(ContractError::MatchNotFound),
+    }
+}
+
+/// Read a match's full record from the registry contract.
+pub fn get_match_info(
+    env: &Env,
+    registry: &Address,
+    match_id: &BytesN<32>,
+) -> Result<MatchInfo, ContractError> {
+    match env.try_invoke_contract::<MatchInfo, ContractError>(
+        registry,
+        &Symbol::new(env, "get_match"),
+        vec![env, match_id.into_val(env)],
+    ) {
+        Ok(Ok(info)) => Ok(info),
+        _ => Err(ContractError::MatchNotFound),
+    }
+}
+
+/// Fail unless the registry reports the match as open for betting.
+pub fn require_match_open(
+    env: &Env,
+    registry: &Address,
+    match_id: &BytesN<32>,
+) -> Result<(), ContractError> {
+    if get_match_status(env, registry, match_id)? != MatchStatus::Open {
+        return Err(ContractError::MatchNotOpen);
+    }
+    Ok(())
+}
+
+// ===== ORACLE LOOKUPS =====
+
+/// Final outcome the oracle contract reports for a match, or `None` while
+/// it is still open to votes or challenges, or is unknown to the oracle.
+pub fn get_oracle_result(env: &Env, oracle: &Address, match_id: &BytesN<32>) -> Option<Symbol> {
+    match env.try_invoke_contract::<Symbol, ContractError>(
+        oracle,
+        &Symbol::new(env, "get_result"),
+        vec![env, match_id.into_val(env)],
+    ) {
+        Ok(Ok(outcome)) => Some(outcome),
+        _ => None,
+    }
+}
@@ -0,0 +1,35 @@
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+/// Leaf committed to by a payout tree: the SHA-256 of the XDR encoding of
+/// `(account, amount)`.
+pub fn merkle_leaf(env: &Env, account: &Address, amount: i128) -> BytesN<32> {
+    let encoded = (account.clone(), amount).to_xdr(env);
+    env.crypto().sha256(&encoded).to_bytes()
+}
+
+/// Parent of two nodes. The pair is sorted before hashing, so proofs do not
+/// need to say which side each sibling sits on.
+pub fn merkle_hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a.to_array() <= b.to_array() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let mut preimage = Bytes::from_array(env, &first.to_array());
+    preimage.append(&Bytes::from_array(env, &second.to_array()));
+    env.crypto().sha256(&preimage).to_bytes()
+}
+
+/// Fold `proof` into `leaf` and compare the result with `root`.
+pub fn verify_merkle_proof(
+    env: &Env,
+    root: &BytesN<32>,
+    leaf: &BytesN<32>,
+    proof: &Vec<BytesN<32>>,
+) -> bool {
+    let mut node = leaf.clone();
+    for sibling in proof.iter() {
+        node = merkle_hash_pair(env, &node, &sibling);
+    }
+    node == *root
+}
@@ -0,0 +1,70 @@
+use crate::{require_role, ContractError, Role};
+use soroban_sdk::{contracttype, Address, Env, Symbol};
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    ContractPaused,
+}
+
+/// Circuit breaker shared by every contract. State-mutating entrypoints call
+/// `require_not_paused`; read-only getters stay available while paused.
+pub fn is_paused(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ContractPaused)
+        .unwrap_or(false)
+}
+
+pub fn require_not_paused(env: &Env) -> Result<(), ContractError> {
+    if is_paused(env) {
+        return Err(ContractError::Paused);
+    }
+    Ok(())
+}
+
+/// `admin` must be the caller's configured admin; its auth is required here.
+pub fn pause(env: &Env, admin: &Address) {
+    admin.require_auth();
+    set_paused(env, true);
+    publish_pause_event(env, "paused", admin);
+}
+
+pub fn unpause(env: &Env, admin: &Address) {
+    admin.require_auth();
+    set_paused(env, false);
+    publish_pause_event(env, "unpaused", admin);
+}
+
+/// Role-gated `pause`: `pauser` must hold `Role::Pauser`.
+pub fn pause_as_role(env: &Env, pauser: &Address) -> Result<(), ContractError> {
+    require_role(env, Role::Pauser, pauser)?;
+    set_paused(env, true);
+    publish_pause_event(env, "paused", pauser);
+    Ok(())
+}
+
+/// Role-gated `unpause`: lifting a pause is reserved for `Role::Admin`.
+pub fn unpause_as_role(env: &Env, admin: &Address) -> Result<(), ContractError> {
+    require_role(env, Role::Admin, admin)?;
+    set_paused(env, false);
+    publish_pause_event(env, "unpaused", admin);
+    Ok(())
+}
+
+pub(crate) fn set_paused(env: &Env, paused: bool) {
+    let storage = env.storage().persistent();
+    if paused {
+        storage.set(&DataKey::ContractPaused, &true);
+    } else {
+        storage.remove(&DataKey::ContractPaused);
+    }
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_pause_event(env: &Env, topic: &str, by: &Address) {
+    env.events().publish(
+        (Symbol::new(env, topic), by.clone()),
+        env.ledger().timestamp(),
+    );
+}
@@ -0,0 +1,173 @@
+use crate::ContractError;
+use soroban_sdk::{contracttype, vec, Address, BytesN, Env, IntoVal, String, Symbol, Val, Vec};
+
+// ===== PLAYER CARD ATTRIBUTES =====
+
+/// Ratings are on a 0-100 scale.
+pub const MAX_RATING: u32 = 100;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rarity {
+    Common,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    /// Rarity produced by fusing cards of this rarity, if any.
+    pub fn next(self) -> Option<Rarity> {
+        match self {
+            Rarity::Common => Some(Rarity::Rare),
+            Rarity::Rare => Some(Rarity::Epic),
+            Rarity::Epic => Some(Rarity::Legendary),
+            Rarity::Legendary => None,
+        }
+    }
+}
+
+/// On-chain card traits, verifiable without fetching the token URI.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CardAttributes {
+    pub rarity: Rarity,
+    /// Playing position, e.g. `FWD` or `GK`
+    pub position: Symbol,
+    pub rating: u32,
+    pub season: u32,
+    pub edition: u32,
+}
+
+impl CardAttributes {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.rating > MAX_RATING {
+            return Err(ContractError::InvalidConfig);
+        }
+        Ok(())
+    }
+}
+
+// ===== PLAYER CARD LOCKS =====
+
+/// Attributes of `token_id`, or `None` for a plain card.
+pub fn get_card_attributes(
+    env: &Env,
+    player_card: &Address,
+    token_id: u64,
+) -> Result<Option<CardAttributes>, ContractError> {
+    match env.try_invoke_contract::<Option<CardAttributes>, ContractError>(
+        player_card,
+        &Symbol::new(env, "get_card_attributes"),
+        vec![env, token_id.into_val(env)],
+    ) {
+        Ok(Ok(attributes)) => Ok(attributes),
+        _ => Err(ContractError::InvalidConfig),
+    }
+}
+
+/// Lock `owner`'s card to the calling contract so it cannot be moved until
+/// that contract unlocks it. `owner` must authorize the call.
+pub fn lock_card(
+    env: &Env,
+    player_card: &Address,
+    owner: &Address,
+    token_id: u64,
+) -> Result<(), ContractError> {
+    invoke_card(
+        env,
+        player_card,
+        "lock",
+        vec![
+            env,
+            owner.into_val(env),
+            token_id.into_val(env),
+            env.current_contract_address().into_val(env),
+        ],
+    )
+}
+
+/// Release a card previously locked by `lock_card` from this contract.
+pub fn unlock_card(env: &Env, player_card: &Address, token_id: u64) -> Result<(), ContractError> {
+    invoke_card(
+        env,
+        player_card,
+        "unlock",
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            token_id.into_val(env),
+        ],
+    )
+}
+
+// ===== PLAYER CARD MINTS =====
+
+/// Mint a card to `to` from the calling contract, which must be a
+/// registered minter on `player_card`. `operation_hash` makes the mint
+/// idempotent on the card contract. Returns the new token id.
+pub fn mint_card(
+    env: &Env,
+    player_card: &Address,
+    operation_hash: &BytesN<32>,
+    to: &Address,
+    token_uri: &String,
+    transferable: bool,
+) -> Result<u64, ContractError> {
+    mint_card_with_attributes(
+        env,
+        player_card,
+        operation_hash,
+        to,
+        token_uri,
+        None,
+        transferable,
+    )
+}
+
+/// `mint_card` for a card with on-chain `attributes`, which count towards
+/// their rarity's cap.
+pub fn mint_card_with_attributes(
+    env: &Env,
+    player_card: &Address,
+    operation_hash: &BytesN<32>,
+    to: &Address,
+    token_uri: &String,
+    attributes: Option<CardAttributes>,
+    transferable: bool,
+) -> Result<u64, ContractError> {
+    match env.try_invoke_contract::<u64, ContractError>(
+        player_card,
+        &Symbol::new(env, "mint_from"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            operation_hash.into_val(env),
+            to.into_val(env),
+            token_uri.into_val(env),
+            attributes.into_val(env),
+            transferable.into_val(env),
+        ],
+    ) {
+        Ok(Ok(token_id)) => Ok(token_id),
+        Err(Ok(err)) => Err(err),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
+
+fn invoke_card(
+    env: &Env,
+    player_card: &Address,
+    function: &str,
+    args: Vec<Val>,
+) -> Result<(), ContractError> {
+    match env.try_invoke_contract::<(), ContractError>(
+        player_card,
+        &Symbol::new(env, function),
+        args,
+    ) {
+        Ok(Ok(())) => Ok(()),
+        Err(Ok(err)) => Err(err),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
@@ -0,0 +1,28 @@
+use crate::ContractError;
+use soroban_sdk::{vec, Address, Env, IntoVal, Symbol};
+
+// ===== REFERRAL VOLUME HOOK =====
+
+/// Report `amount` of `user` activity to the referral contract on behalf of
+/// the calling contract, which must be an authorized reporter there.
+pub fn report_referral_volume(
+    env: &Env,
+    referral: &Address,
+    user: &Address,
+    amount: i128,
+) -> Result<i128, ContractError> {
+    match env.try_invoke_contract::<i128, ContractError>(
+        referral,
+        &Symbol::new(env, "report_volume"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            user.into_val(env),
+            amount.into_val(env),
+        ],
+    ) {
+        Ok(Ok(commission)) => Ok(commission),
+        Err(Ok(err)) => Err(err),
+        _ => Err(ContractError::Unauthorized),
+    }
+}
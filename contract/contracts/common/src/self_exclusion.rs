@@ -0,0 +1,22 @@
+use crate::ContractError;
+use soroban_sdk::{vec, Address, Env, IntoVal, Symbol};
+
+// ===== SELF-EXCLUSION REGISTRY =====
+
+/// Turn `user` away with `SelfExcluded` while the registry (the balance
+/// ledger) holds an unexpired self-exclusion for them.
+pub fn require_not_self_excluded(
+    env: &Env,
+    registry: &Address,
+    user: &Address,
+) -> Result<(), ContractError> {
+    let excluded: bool = env.invoke_contract(
+        registry,
+        &Symbol::new(env, "is_self_excluded"),
+        vec![env, user.into_val(env)],
+    );
+    if excluded {
+        return Err(ContractError::SelfExcluded);
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use crate::ContractError;
+use soroban_sdk::{contracttype, Address, BytesN, Env, Symbol};
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Delay,
+    NextActionId,
+    Action(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimelockEntry {
+    /// Hash of the contract-specific action, checked again at execution
+    pub action_hash: BytesN<32>,
+    pub proposer: Address,
+    pub queued_at: u64,
+    pub executable_at: u64,
+}
+
+// ===== CONFIGURATION =====
+// Callers are expected to have checked their own auth first; changing the
+// delay should itself go through the timelock once one is in use.
+
+pub fn set_timelock_delay(env: &Env, delay: u64) {
+    env.storage().persistent().set(&DataKey::Delay, &delay);
+}
+
+pub fn get_timelock_delay(env: &Env) -> u64 {
+    env.storage().persistent().get(&DataKey::Delay).unwrap_or(0)
+}
+
+// ===== QUEUE =====
+
+/// Queue an action identified by `action_hash`; it becomes executable once
+/// the current delay has passed. Returns its id.
+pub fn queue_timelock_action(env: &Env, proposer: &Address, action_hash: BytesN<32>) -> u64 {
+    let id: u64 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::NextActionId)
+        .unwrap_or(1);
+    env.storage()
+        .persistent()
+        .set(&DataKey::NextActionId, &(id + 1));
+
+    let now = env.ledger().timestamp();
+    let entry = TimelockEntry {
+        action_hash,
+        proposer: proposer.clone(),
+        queued_at: now,
+        executable_at: now + get_timelock_delay(env),
+    };
+    env.storage().persistent().set(&DataKey::Action(id), &entry);
+
+    publish_timelock_event(env, "action_queued", id, &entry);
+    id
+}
+
+pub fn get_timelock_action(env: &Env, id: u64) -> Option<TimelockEntry> {
+    env.storage().persistent().get(&DataKey::Action(id))
+}
+
+/// Drop a queued action before it runs.
+pub fn cancel_timelock_action(env: &Env, id: u64) -> Result<(), ContractError> {
+    let entry = get_timelock_action(env, id).ok_or(ContractError::ActionNotFound)?;
+    env.storage().persistent().remove(&DataKey::Action(id));
+
+    publish_timelock_event(env, "action_cancelled", id, &entry);
+    Ok(())
+}
+
+/// Consume a matured action so the caller can apply it. `action_hash` must
+/// match what was queued.
+pub fn execute_timelock_action(
+    env: &Env,
+    id: u64,
+    action_hash: &BytesN<32>,
+) -> Result<TimelockEntry, ContractError> {
+    let entry = get_timelock_action(env, id).ok_or(ContractError::ActionNotFound)?;
+    if entry.action_hash != *action_hash {
+        return Err(ContractError::InvalidConfig);
+    }
+    if env.ledger().timestamp() < entry.executable_at {
+        return Err(ContractError::TimelockNotReady);
+    }
+    env.storage().persistent().remove(&DataKey::Action(id));
+
+    publish_timelock_event(env, "action_executed", id, &entry);
+    Ok(entry)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_timelock_event(env: &Env, topic: &str, id: u64, entry: &TimelockEntry) {
+    env.events()
+        .publish((Symbol::new(env, topic), id), entry.clone());
+}
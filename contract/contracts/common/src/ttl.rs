@@ -0,0 +1,41 @@
+use soroban_sdk::{Env, IntoVal, Val, Vec};
+
+/// Ledgers in a day, at ~5 seconds per ledger.
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+/// Entries are topped back up to this TTL whenever they are bumped.
+pub const TTL_EXTEND_TO: u32 = 30 * DAY_IN_LEDGERS;
+/// Bumps are skipped while an entry still has more TTL than this, so hot
+/// keys pay the extension fee at most about once a day.
+pub const TTL_THRESHOLD: u32 = TTL_EXTEND_TO - DAY_IN_LEDGERS;
+
+// ===== STORAGE TTL =====
+// Hot persistent entries (balances, bets, stakes) are bumped by their
+// contracts on every read and write. Anything that goes cold can still be
+// kept alive by the backend through each contract's `bump_storage`.
+
+pub fn bump_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+/// Extend `key` if it exists; returns whether it did.
+pub fn bump_persistent<K>(env: &Env, key: &K) -> bool
+where
+    K: IntoVal<Env, Val>,
+{
+    let storage = env.storage().persistent();
+    if !storage.has(key) {
+        return false;
+    }
+    storage.extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    true
+}
+
+/// Extend the contract instance and every existing persistent entry in
+/// `keys`, given as the contract's own storage keys encoded as values.
+/// Missing keys are skipped. Returns how many entries were extended.
+pub fn bump_storage_keys(env: &Env, keys: &Vec<Val>) -> u32 {
+    bump_instance(env);
+    keys.iter().filter(|key| bump_persistent(env, key)).count() as u32
+}
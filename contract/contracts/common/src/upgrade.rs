@@ -0,0 +1,58 @@
+use soroban_sdk::{contracttype, BytesN, Env, Symbol};
+
+/// Storage version of a contract that has never been migrated.
+pub const INITIAL_VERSION: u32 = 1;
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Version,
+}
+
+// ===== UPGRADES =====
+// Callers check their own admin auth first. Swapping the Wasm only changes
+// the code; the new code then runs its storage migration from `migrate`,
+// which it bumps to its own `CONTRACT_VERSION` with `complete_migration`.
+
+/// Version of the data currently in storage.
+pub fn get_version(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Version)
+        .unwrap_or(INITIAL_VERSION)
+}
+
+/// Replace the running contract's code with the uploaded `new_wasm_hash`.
+pub fn upgrade_contract(env: &Env, new_wasm_hash: BytesN<32>) {
+    publish_upgrade_event(env, &new_wasm_hash);
+    env.deployer().update_current_contract_wasm(new_wasm_hash);
+}
+
+/// Record that storage now matches `code_version`. Returns the version the
+/// data was migrated from, or `None` if it is already up to date, in which
+/// case nothing is written.
+pub fn complete_migration(env: &Env, code_version: u32) -> Option<u32> {
+    let stored = get_version(env);
+    if stored >= code_version {
+        return None;
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::Version, &code_version);
+    publish_migration_event(env, stored, code_version);
+    Some(stored)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_upgrade_event(env: &Env, new_wasm_hash: &BytesN<32>) {
+    env.events().publish(
+        (Symbol::new(env, "contract_upgraded"),),
+        (new_wasm_hash.clone(), get_version(env)),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_migration_event(env: &Env, from: u32, to: u32) {
+    env.events()
+        .publish((Symbol::new(env, "contract_migrated"),), (from, to));
+}
@@ -1,5 +1,5 @@
 use crate::getters::*;
-use soroban_sdk::{Address, Env, Map, String, Vec, U256};
+use soroban_sdk::{vec, Address, Env, IntoVal, Map, String, Symbol, Vec, U256};
 
 // ===== USER BALANCE GETTERS =====
 
@@ -230,20 +230,18 @@ pub fn get_user_key_metrics(
     (available, staked, locked_bets, rewards)
 }
 
-/// Get contract-level statistics
-/// Returns: (total_users, total_staked, total_locked_bets, total_supply)
-pub fn get_contract_stats(env: &Env, token_address: Address) -> (u32, i128, i128, i128) {
-    let token_balance = get_token_balance(env, token_address);
-
-    // These would be actual counts in a real implementation
-    let total_users = 0u32;
-    let total_staked = 0i128;
-    let total_locked_bets = 0i128;
+/// Get platform-wide activity statistics aggregated by the stats hub
+/// Returns: ActivityStats totals across every reporting contract
+pub fn get_contract_stats(env: &Env, stats_hub: Address) -> ActivityStats {
+    env.invoke_contract(&stats_hub, &Symbol::new(env, "get_totals"), Vec::new(env))
+}
 
-    (
-        total_users,
-        total_staked,
-        total_locked_bets,
-        token_balance.total_supply,
+/// Get activity statistics for a single epoch from the stats hub
+/// Returns: ActivityStats bucket for the given epoch
+pub fn get_epoch_stats(env: &Env, stats_hub: Address, epoch: u64) -> ActivityStats {
+    env.invoke_contract(
+        &stats_hub,
+        &Symbol::new(env, "get_epoch_stats"),
+        vec![env, epoch.into_val(env)],
     )
 }
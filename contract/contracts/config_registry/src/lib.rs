@@ -0,0 +1,257 @@
+#![no_std]
+
+use common::{init_role, require_role, ContractError, Role, TimelockEntry};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, Map, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Changes to the registry only take effect through the timelock.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConfigAction {
+    /// Point `key` at a contract or account; `None` removes it
+    SetAddress(Symbol, Option<Address>),
+    /// Set a numeric parameter; `None` removes it
+    SetParam(Symbol, Option<i128>),
+    GrantRole(Role, Address),
+    RevokeRole(Role, Address),
+    SetDelay(u64),
+    /// Replace this contract's code with the uploaded Wasm
+    Upgrade(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Address(Symbol),
+    AddressKeys,
+    Param(Symbol),
+    ParamKeys,
+}
+
+#[contract]
+pub struct ConfigRegistryContract;
+
+#[contractimpl]
+impl ConfigRegistryContract {
+    /// `addresses` and `params` seed the registry without waiting for the
+    /// timelock. `admin` also receives `Role::Guardian` and may cancel
+    /// queued changes.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        timelock_delay: u64,
+        addresses: Map<Symbol, Address>,
+        params: Map<Symbol, i128>,
+    ) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::AddressKeys) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        init_role(&env, Role::Admin, &admin);
+        init_role(&env, Role::Guardian, &admin);
+        common::set_timelock_delay(&env, timelock_delay);
+        storage.set(&DataKey::AddressKeys, &Vec::<Symbol>::new(&env));
+        storage.set(&DataKey::ParamKeys, &Vec::<Symbol>::new(&env));
+        for (key, address) in addresses.iter() {
+            set_address(&env, &key, Some(address));
+        }
+        for (key, value) in params.iter() {
+            set_param(&env, &key, Some(value));
+        }
+        Ok(())
+    }
+
+    /// Admin and guardian seats only change through `queue_action`.
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        require_untimelocked_role(role)?;
+        common::grant_role(&env, &caller, role, &account)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        require_untimelocked_role(role)?;
+        common::revoke_role(&env, &caller, role, &account)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), ContractError> {
+        common::renounce_role(&env, &account, role)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION` once a queued `Upgrade` has
+    /// executed. Returns the version migrated from, or `None` if storage was
+    /// already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Queue a change; it can be executed once the timelock delay has
+    /// passed. Requires `Role::Admin`. Returns the action id.
+    pub fn queue_action(
+        env: Env,
+        admin: Address,
+        action: ConfigAction,
+    ) -> Result<u64, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        Ok(common::queue_timelock_action(
+            &env,
+            &admin,
+            action_hash(&env, &action),
+        ))
+    }
+
+    /// Apply a matured change. `action` must be the one that was queued.
+    pub fn execute_action(
+        env: Env,
+        admin: Address,
+        action_id: u64,
+        action: ConfigAction,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::execute_timelock_action(&env, action_id, &action_hash(&env, &action))?;
+
+        match action {
+            ConfigAction::SetAddress(key, address) => set_address(&env, &key, address),
+            ConfigAction::SetParam(key, value) => set_param(&env, &key, value),
+            ConfigAction::GrantRole(role, account) => {
+                common::apply_role_change(&env, role, &account, true, &admin);
+            }
+            ConfigAction::RevokeRole(role, account) => {
+                common::apply_role_change(&env, role, &account, false, &admin);
+            }
+            ConfigAction::SetDelay(delay) => common::set_timelock_delay(&env, delay),
+            ConfigAction::Upgrade(new_wasm_hash) => common::upgrade_contract(&env, new_wasm_hash),
+        }
+        Ok(())
+    }
+
+    /// Drop a queued change before it executes; requires `Role::Guardian`
+    pub fn cancel_action(env: Env, guardian: Address, action_id: u64) -> Result<(), ContractError> {
+        require_role(&env, Role::Guardian, &guardian)?;
+        common::cancel_timelock_action(&env, action_id)
+    }
+
+    pub fn get_queued_action(env: Env, action_id: u64) -> Option<TimelockEntry> {
+        common::get_timelock_action(&env, action_id)
+    }
+
+    pub fn get_timelock_delay(env: Env) -> u64 {
+        common::get_timelock_delay(&env)
+    }
+
+    pub fn get_address(env: Env, key: Symbol) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Address(key))
+    }
+
+    pub fn get_param(env: Env, key: Symbol) -> Option<i128> {
+        env.storage().persistent().get(&DataKey::Param(key))
+    }
+
+    /// Every key with an address registered, in registration order.
+    pub fn get_address_keys(env: Env) -> Vec<Symbol> {
+        get_keys(&env, true)
+    }
+
+    pub fn get_param_keys(env: Env) -> Vec<Symbol> {
+        get_keys(&env, false)
+    }
+}
+
+fn require_untimelocked_role(role: Role) -> Result<(), ContractError> {
+    match role {
+        Role::Admin | Role::Guardian => Err(ContractError::TimelockRequired),
+        _ => Ok(()),
+    }
+}
+
+fn action_hash(env: &Env, action: &ConfigAction) -> BytesN<32> {
+    env.crypto().sha256(&action.clone().to_xdr(env)).into()
+}
+
+fn keys_key(addresses: bool) -> DataKey {
+    if addresses {
+        DataKey::AddressKeys
+    } else {
+        DataKey::ParamKeys
+    }
+}
+
+fn get_keys(env: &Env, addresses: bool) -> Vec<Symbol> {
+    env.storage()
+        .persistent()
+        .get(&keys_key(addresses))
+        .unwrap_or(Vec::new(env))
+}
+
+fn update_keys(env: &Env, addresses: bool, key: &Symbol, present: bool) {
+    let mut keys = get_keys(env, addresses);
+    match (keys.first_index_of(key), present) {
+        (None, true) => keys.push_back(key.clone()),
+        (Some(index), false) => {
+            keys.remove(index);
+        }
+        _ => return,
+    }
+    env.storage().persistent().set(&keys_key(addresses), &keys);
+}
+
+fn set_address(env: &Env, key: &Symbol, address: Option<Address>) {
+    let storage_key = DataKey::Address(key.clone());
+    match &address {
+        Some(address) => env.storage().persistent().set(&storage_key, address),
+        None => env.storage().persistent().remove(&storage_key),
+    }
+    update_keys(env, true, key, address.is_some());
+    publish_config_event(env, "config_address_set", key, address);
+}
+
+fn set_param(env: &Env, key: &Symbol, value: Option<i128>) {
+    let storage_key = DataKey::Param(key.clone());
+    match value {
+        Some(value) => env.storage().persistent().set(&storage_key, &value),
+        None => env.storage().persistent().remove(&storage_key),
+    }
+    update_keys(env, false, key, value.is_some());
+    publish_config_event(env, "config_param_set", key, value);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_config_event<T>(env: &Env, topic: &str, key: &Symbol, value: T)
+where
+    T: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+{
+    env.events()
+        .publish((Symbol::new(env, topic), key.clone()), value);
+}
+
+#[cfg(test)]
+mod test;
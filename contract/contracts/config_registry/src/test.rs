@@ -0,0 +1,100 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    map, symbol_short,
+    testutils::{Address as _, Ledger},
+    Address, Env,
+};
+
+const DELAY: u64 = 3_600;
+
+fn setup(env: &Env) -> (ConfigRegistryContractClient<'_>, Address, Address) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let treasury = Address::generate(env);
+    let contract_id = env.register(ConfigRegistryContract, ());
+    let client = ConfigRegistryContractClient::new(env, &contract_id);
+    client.initialize(
+        &admin,
+        &DELAY,
+        &map![env, (symbol_short!("treasury"), treasury.clone())],
+        &map![env, (symbol_short!("fee_bps"), 250)],
+    );
+    (client, admin, treasury)
+}
+
+#[test]
+fn test_initialize_seeds_values_once() {
+    let env = Env::default();
+    let (client, admin, treasury) = setup(&env);
+
+    assert_eq!(
+        client.get_address(&symbol_short!("treasury")),
+        Some(treasury)
+    );
+    assert_eq!(client.get_param(&symbol_short!("fee_bps")), Some(250));
+    assert_eq!(client.get_address(&symbol_short!("oracle")), None);
+    assert_eq!(client.get_timelock_delay(), DELAY);
+    assert_eq!(
+        client.try_initialize(&admin, &0, &Map::new(&env), &Map::new(&env)),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn test_changes_wait_for_the_timelock() {
+    let env = Env::default();
+    let (client, admin, _) = setup(&env);
+    let ledger = Address::generate(&env);
+    let key = Symbol::new(&env, "balance_ledger");
+    let action = ConfigAction::SetAddress(key.clone(), Some(ledger.clone()));
+
+    let id = client.queue_action(&admin, &action);
+    assert_eq!(
+        client.try_execute_action(&admin, &id, &action),
+        Err(Ok(ContractError::TimelockNotReady))
+    );
+    env.ledger().with_mut(|l| l.timestamp += DELAY);
+
+    // The executed action must match the queued one
+    let other = ConfigAction::SetAddress(key.clone(), Some(Address::generate(&env)));
+    assert!(client.try_execute_action(&admin, &id, &other).is_err());
+    client.execute_action(&admin, &id, &action);
+    assert_eq!(client.get_address(&key), Some(ledger));
+    assert_eq!(client.get_address_keys().len(), 2);
+
+    let action = ConfigAction::SetParam(symbol_short!("fee_bps"), None);
+    let id = client.queue_action(&admin, &action);
+    env.ledger().with_mut(|l| l.timestamp += DELAY);
+    client.execute_action(&admin, &id, &action);
+    assert_eq!(client.get_param(&symbol_short!("fee_bps")), None);
+    assert_eq!(client.get_param_keys().len(), 0);
+}
+
+#[test]
+fn test_guardian_cancels_and_admin_seats_are_timelocked() {
+    let env = Env::default();
+    let (client, admin, _) = setup(&env);
+    let outsider = Address::generate(&env);
+
+    assert_eq!(
+        client.try_grant_role(&admin, &Role::Admin, &outsider),
+        Err(Ok(ContractError::TimelockRequired))
+    );
+    assert_eq!(
+        client.try_queue_action(&outsider, &ConfigAction::SetDelay(0)),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    let action = ConfigAction::GrantRole(Role::Admin, outsider.clone());
+    let id = client.queue_action(&admin, &action);
+    client.cancel_action(&admin, &id);
+    env.ledger().with_mut(|l| l.timestamp += DELAY);
+    assert_eq!(
+        client.try_execute_action(&admin, &id, &action),
+        Err(Ok(ContractError::ActionNotFound))
+    );
+    assert!(!client.has_role(&Role::Admin, &outsider));
+}
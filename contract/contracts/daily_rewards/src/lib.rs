@@ -0,0 +1,400 @@
+#![no_std]
+
+use common::{init_role, mint_card, require_role, ContractError, Role};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env, String, Symbol,
+    Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+/// Check-in days are UTC days since the Unix epoch.
+pub const DAY_SECONDS: u64 = 86_400;
+const BASIS_POINTS: u64 = 10_000;
+
+/// Token payout per check-in. The multiplier starts at 1x on the first day
+/// of a streak and grows by `bonus_bps_per_day` for every further
+/// consecutive day, up to `max_multiplier_bps`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RewardConfig {
+    pub base_reward: i128,
+    pub bonus_bps_per_day: u32,
+    pub max_multiplier_bps: u32,
+}
+
+/// Card minted through the player card contract when a streak reaches a
+/// milestone length.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneCard {
+    pub token_uri: String,
+    pub transferable: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StreakState {
+    pub current: u32,
+    pub longest: u32,
+    /// Day of the latest check-in
+    pub last_day: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckInReceipt {
+    pub day: u64,
+    pub streak: u32,
+    pub reward: i128,
+    /// Card minted for reaching a milestone, if any
+    pub card_id: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    BackendPubkey,
+    Token,
+    Config,
+    Pool,
+    PlayerCard,
+    Milestone(u32),
+    Streak(Address),
+    CheckIn(Address, u64),
+}
+
+#[contract]
+pub struct DailyRewardsContract;
+
+#[contractimpl]
+impl DailyRewardsContract {
+    /// `backend_pubkey` signs check-ins; rewards are paid in `token` from
+    /// the pool filled by `fund`.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        backend_pubkey: BytesN<32>,
+        token: Address,
+        config: RewardConfig,
+    ) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::BackendPubkey) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        validate_config(&config)?;
+
+        storage.set(&DataKey::BackendPubkey, &backend_pubkey);
+        storage.set(&DataKey::Token, &token);
+        storage.set(&DataKey::Config, &config);
+        init_role(&env, Role::Admin, &admin);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::grant_role(&env, &caller, role, &account)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::revoke_role(&env, &caller, role, &account)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), ContractError> {
+        common::renounce_role(&env, &account, role)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn set_backend_pubkey(
+        env: Env,
+        admin: Address,
+        backend_pubkey: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::BackendPubkey, &backend_pubkey);
+        Ok(())
+    }
+
+    pub fn set_config(env: Env, admin: Address, config: RewardConfig) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        validate_config(&config)?;
+        env.storage().persistent().set(&DataKey::Config, &config);
+        Ok(())
+    }
+
+    pub fn get_config(env: Env) -> Result<RewardConfig, ContractError> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Config)
+            .ok_or(ContractError::NotInitialized)
+    }
+
+    /// Player card contract that mints milestone cards. This contract must
+    /// be registered as a minter on it.
+    pub fn set_player_card(
+        env: Env,
+        admin: Address,
+        player_card: Address,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::PlayerCard, &player_card);
+        Ok(())
+    }
+
+    /// Mint `card` to users whose streak reaches exactly `streak` days, or
+    /// stop doing so when `card` is `None`.
+    pub fn set_milestone(
+        env: Env,
+        admin: Address,
+        streak: u32,
+        card: Option<MilestoneCard>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        if streak == 0 {
+            return Err(ContractError::InvalidConfig);
+        }
+        let storage = env.storage().persistent();
+        match card {
+            Some(card) => {
+                if !storage.has(&DataKey::PlayerCard) {
+                    return Err(ContractError::InvalidConfig);
+                }
+                storage.set(&DataKey::Milestone(streak), &card);
+            }
+            None => storage.remove(&DataKey::Milestone(streak)),
+        }
+        Ok(())
+    }
+
+    pub fn get_milestone(env: Env, streak: u32) -> Option<MilestoneCard> {
+        env.storage().persistent().get(&DataKey::Milestone(streak))
+    }
+
+    /// Move `amount` of the reward token from `funder` into the pool.
+    pub fn fund(env: Env, funder: Address, amount: i128) -> Result<(), ContractError> {
+        funder.require_auth();
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        token::Client::new(&env, &get_token(&env)?).transfer(
+            &funder,
+            env.current_contract_address(),
+            &amount,
+        );
+        set_pool(&env, get_pool(&env) + amount);
+        Ok(())
+    }
+
+    pub fn get_pool(env: Env) -> i128 {
+        get_pool(&env)
+    }
+
+    /// Record today's check-in for `user` and pay its reward. `signature`
+    /// is the backend's ed25519 signature over the XDR encoding of
+    /// `(contract, user, day)`, where `contract` is this contract's address
+    /// and `day` is the current UTC day. Each user can
+    /// check in once per day; missing a day restarts the streak.
+    pub fn check_in(
+        env: Env,
+        user: Address,
+        signature: BytesN<64>,
+    ) -> Result<CheckInReceipt, ContractError> {
+        user.require_auth();
+        let day = current_day(&env);
+        let check_in_key = DataKey::CheckIn(user.clone(), day);
+        let storage = env.storage().persistent();
+        if storage.has(&check_in_key) {
+            return Err(ContractError::DuplicateOperation);
+        }
+
+        let backend_pubkey: BytesN<32> = storage
+            .get(&DataKey::BackendPubkey)
+            .ok_or(ContractError::NotInitialized)?;
+        let message = (env.current_contract_address(), user.clone(), day).to_xdr(&env);
+        env.crypto()
+            .ed25519_verify(&backend_pubkey, &message, &signature);
+
+        let mut streak = get_streak(&env, &user);
+        streak.current = if streak.current > 0 && streak.last_day + 1 == day {
+            streak.current + 1
+        } else {
+            1
+        };
+        streak.longest = streak.longest.max(streak.current);
+        streak.last_day = day;
+
+        let reward = reward_for(&Self::get_config(env.clone())?, streak.current);
+        if reward > 0 {
+            let pool = get_pool(&env);
+            if pool < reward {
+                return Err(ContractError::InsufficientRewardPool);
+            }
+            set_pool(&env, pool - reward);
+        }
+
+        storage.set(&check_in_key, &true);
+        common::bump_persistent(&env, &check_in_key);
+        let streak_key = DataKey::Streak(user.clone());
+        storage.set(&streak_key, &streak);
+        common::bump_persistent(&env, &streak_key);
+
+        if reward > 0 {
+            token::Client::new(&env, &get_token(&env)?).transfer(
+                &env.current_contract_address(),
+                &user,
+                &reward,
+            );
+        }
+        let card_id = match Self::get_milestone(env.clone(), streak.current) {
+            Some(card) => {
+                let player_card: Address = storage
+                    .get(&DataKey::PlayerCard)
+                    .ok_or(ContractError::InvalidConfig)?;
+                // Unique per contract, user and day, so the card contract
+                // never mints the same milestone twice
+                let operation_hash = env
+                    .crypto()
+                    .sha256(&(env.current_contract_address(), user.clone(), day).to_xdr(&env))
+                    .into();
+                Some(mint_card(
+                    &env,
+                    &player_card,
+                    &operation_hash,
+                    &user,
+                    &card.token_uri,
+                    card.transferable,
+                )?)
+            }
+            None => None,
+        };
+
+        let receipt = CheckInReceipt {
+            day,
+            streak: streak.current,
+            reward,
+            card_id,
+        };
+        publish_check_in_event(&env, &user, &receipt);
+        Ok(receipt)
+    }
+
+    /// Stored streak of `user`, as of their latest check-in.
+    pub fn get_streak(env: Env, user: Address) -> StreakState {
+        get_streak(&env, &user)
+    }
+
+    /// Length of the streak `user` can still extend: zero once a full day
+    /// has been missed.
+    pub fn get_current_streak(env: Env, user: Address) -> u32 {
+        let streak = get_streak(&env, &user);
+        if streak.last_day + 1 < current_day(&env) {
+            0
+        } else {
+            streak.current
+        }
+    }
+
+    pub fn has_checked_in(env: Env, user: Address, day: u64) -> bool {
+        env.storage().persistent().has(&DataKey::CheckIn(user, day))
+    }
+
+    pub fn current_day(env: Env) -> u64 {
+        current_day(&env)
+    }
+}
+
+fn validate_config(config: &RewardConfig) -> Result<(), ContractError> {
+    if config.base_reward < 0 || (config.max_multiplier_bps as u64) < BASIS_POINTS {
+        return Err(ContractError::InvalidConfig);
+    }
+    Ok(())
+}
+
+fn reward_for(config: &RewardConfig, streak: u32) -> i128 {
+    let multiplier_bps = (streak.saturating_sub(1) as u64)
+        .saturating_mul(config.bonus_bps_per_day as u64)
+        .saturating_add(BASIS_POINTS)
+        .min(config.max_multiplier_bps as u64);
+    config.base_reward * multiplier_bps as i128 / BASIS_POINTS as i128
+}
+
+fn current_day(env: &Env) -> u64 {
+    env.ledger().timestamp() / DAY_SECONDS
+}
+
+fn get_streak(env: &Env, user: &Address) -> StreakState {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Streak(user.clone()))
+        .unwrap_or_default()
+}
+
+fn get_token(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Token)
+        .ok_or(ContractError::NotInitialized)
+}
+
+fn get_pool(env: &Env) -> i128 {
+    env.storage().persistent().get(&DataKey::Pool).unwrap_or(0)
+}
+
+fn set_pool(env: &Env, amount: i128) {
+    env.storage().persistent().set(&DataKey::Pool, &amount);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_check_in_event(env: &Env, user: &Address, receipt: &CheckInReceipt) {
+    env.events().publish(
+        (Symbol::new(env, "daily_check_in"), user.clone()),
+        receipt.clone(),
+    );
+}
+
+#[cfg(test)]
+mod test;
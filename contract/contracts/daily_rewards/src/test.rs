@@ -0,0 +1,188 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use player_card::{PlayerCardContract, PlayerCardContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, BytesN, Env,
+};
+
+fn setup_test(
+    env: &Env,
+) -> (
+    DailyRewardsContractClient<'_>,
+    Address,
+    SigningKey,
+    token::Client<'_>,
+    Address,
+) {
+    env.mock_all_auths();
+    env.ledger()
+        .with_mut(|ledger| ledger.timestamp = 100 * DAY_SECONDS);
+
+    let admin = Address::generate(env);
+    let signer = SigningKey::from_bytes(&[9u8; 32]);
+    let pubkey = BytesN::from_array(env, &signer.verifying_key().to_bytes());
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    token::StellarAssetClient::new(env, &token_id).mint(&admin, &10_000);
+
+    let contract_id = env.register(DailyRewardsContract, ());
+    let client = DailyRewardsContractClient::new(env, &contract_id);
+    client.initialize(
+        &admin,
+        &pubkey,
+        &token_id,
+        &RewardConfig {
+            base_reward: 100,
+            bonus_bps_per_day: 5_000,
+            max_multiplier_bps: 20_000,
+        },
+    );
+    client.fund(&admin, &1_000);
+
+    let token = token::Client::new(env, &token_id);
+    let user = Address::generate(env);
+
+    (client, admin, signer, token, user)
+}
+
+fn sign(
+    env: &Env,
+    client: &DailyRewardsContractClient,
+    signer: &SigningKey,
+    user: &Address,
+    day: u64,
+) -> BytesN<64> {
+    let message: std::vec::Vec<u8> = (client.address.clone(), user.clone(), day)
+        .to_xdr(env)
+        .iter()
+        .collect();
+    BytesN::from_array(env, &signer.sign(&message).to_bytes())
+}
+
+fn check_in(
+    env: &Env,
+    client: &DailyRewardsContractClient,
+    signer: &SigningKey,
+    user: &Address,
+) -> CheckInReceipt {
+    let day = client.current_day();
+    client.check_in(user, &sign(env, client, signer, user, day))
+}
+
+fn next_day(env: &Env, days: u64) {
+    env.ledger()
+        .with_mut(|ledger| ledger.timestamp += days * DAY_SECONDS);
+}
+
+#[test]
+fn streaks_escalate_rewards_once_per_day() {
+    let env = Env::default();
+    let (client, _admin, signer, token, user) = setup_test(&env);
+
+    // A signature for another deployment does not verify
+    let (other, ..) = setup_test(&env);
+    let day = client.current_day();
+    assert!(client
+        .try_check_in(&user, &sign(&env, &other, &signer, &user, day))
+        .is_err());
+
+    assert_eq!(check_in(&env, &client, &signer, &user).reward, 100);
+    assert!(client.has_checked_in(&user, &day));
+    assert_eq!(
+        client.try_check_in(&user, &sign(&env, &client, &signer, &user, day)),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+
+    next_day(&env, 1);
+    // A signature for another day does not verify
+    assert!(client
+        .try_check_in(&user, &sign(&env, &client, &signer, &user, day))
+        .is_err());
+    assert_eq!(check_in(&env, &client, &signer, &user).reward, 150);
+    next_day(&env, 1);
+    assert_eq!(check_in(&env, &client, &signer, &user).reward, 200);
+    next_day(&env, 1);
+    // Capped at the 2x maximum
+    assert_eq!(check_in(&env, &client, &signer, &user).reward, 200);
+    assert_eq!(token.balance(&user), 650);
+    assert_eq!(client.get_pool(), 350);
+
+    let streak = client.get_streak(&user);
+    assert_eq!(streak.current, 4);
+    assert_eq!(streak.longest, 4);
+    assert_eq!(client.get_current_streak(&user), 4);
+}
+
+#[test]
+fn missing_a_day_resets_the_streak() {
+    let env = Env::default();
+    let (client, _admin, signer, _token, user) = setup_test(&env);
+
+    check_in(&env, &client, &signer, &user);
+    next_day(&env, 1);
+    check_in(&env, &client, &signer, &user);
+    next_day(&env, 2);
+    assert_eq!(client.get_current_streak(&user), 0);
+
+    let receipt = check_in(&env, &client, &signer, &user);
+    assert_eq!(receipt.streak, 1);
+    assert_eq!(receipt.reward, 100);
+    assert_eq!(client.get_streak(&user).longest, 2);
+}
+
+#[test]
+fn empty_pool_rejects_check_ins() {
+    let env = Env::default();
+    let (client, admin, signer, _token, user) = setup_test(&env);
+
+    client.set_config(
+        &admin,
+        &RewardConfig {
+            base_reward: 2_000,
+            bonus_bps_per_day: 0,
+            max_multiplier_bps: 10_000,
+        },
+    );
+    let day = client.current_day();
+    assert_eq!(
+        client.try_check_in(&user, &sign(&env, &client, &signer, &user, day)),
+        Err(Ok(ContractError::InsufficientRewardPool))
+    );
+    assert!(!client.has_checked_in(&user, &day));
+
+    client.fund(&admin, &1_000);
+    assert_eq!(check_in(&env, &client, &signer, &user).reward, 2_000);
+}
+
+#[test]
+fn milestones_mint_player_cards() {
+    let env = Env::default();
+    let (client, admin, signer, _token, user) = setup_test(&env);
+
+    let card = MilestoneCard {
+        token_uri: String::from_str(&env, "ipfs://streak-2"),
+        transferable: false,
+    };
+    assert_eq!(
+        client.try_set_milestone(&admin, &2, &Some(card.clone())),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    let player_card_id = env.register(PlayerCardContract, ());
+    let player_card = PlayerCardContractClient::new(&env, &player_card_id);
+    player_card.initialize(&admin);
+    player_card.add_minter(&client.address);
+    client.set_player_card(&admin, &player_card_id);
+    client.set_milestone(&admin, &2, &Some(card));
+
+    assert_eq!(check_in(&env, &client, &signer, &user).card_id, None);
+    next_day(&env, 1);
+    let token_id = check_in(&env, &client, &signer, &user).card_id.unwrap();
+    assert_eq!(player_card.owner_of(&token_id), user);
+    next_day(&env, 1);
+    assert_eq!(check_in(&env, &client, &signer, &user).card_id, None);
+}
@@ -0,0 +1,237 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, vec, xdr::ToXdr, Address, Bytes, BytesN,
+    Env, IntoVal, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum DepositGatewayError {
+    Unauthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    InvalidAmount = 4,
+    TokenNotAllowed = 5,
+    NonceAlreadyUsed = 6,
+    Overflow = 7,
+}
+
+/// Credit the backend vouches for, e.g. funds received on another chain or
+/// through a fiat on-ramp. Each `(user, nonce)` pair can be claimed once.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositVoucher {
+    pub user: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub nonce: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Admin,
+    BackendPubkey,
+    BalanceLedger,
+    AllowedToken(Address),
+    UsedNonce(Address, u64),
+    TotalClaimed(Address),
+}
+
+#[contract]
+pub struct DepositGatewayContract;
+
+#[contractimpl]
+impl DepositGatewayContract {
+    /// The gateway credits users through `balance_ledger.apply_delta_for_token`,
+    /// so it must be registered as that ledger's backend signer and every
+    /// voucher token must also be allowlisted in the ledger.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        backend_pubkey: BytesN<32>,
+        balance_ledger: Address,
+    ) -> Result<(), DepositGatewayError> {
+        let storage = env.storage().persistent();
+
+        if storage.has(&DataKey::Admin) {
+            return Err(DepositGatewayError::AlreadyInitialized);
+        }
+
+        storage.set(&DataKey::Admin, &admin);
+        storage.set(&DataKey::BackendPubkey, &backend_pubkey);
+        storage.set(&DataKey::BalanceLedger, &balance_ledger);
+        Ok(())
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), DepositGatewayError> {
+        Self::require_admin_auth(&env)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env) -> Result<Option<u32>, DepositGatewayError> {
+        Self::require_admin_auth(&env)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn set_backend_pubkey(
+        env: Env,
+        backend_pubkey: BytesN<32>,
+    ) -> Result<(), DepositGatewayError> {
+        Self::require_admin_auth(&env)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::BackendPubkey, &backend_pubkey);
+        Ok(())
+    }
+
+    pub fn set_token_allowed(
+        env: Env,
+        token: Address,
+        allowed: bool,
+    ) -> Result<(), DepositGatewayError> {
+        Self::require_admin_auth(&env)?;
+
+        let storage = env.storage().persistent();
+        if allowed {
+            storage.set(&DataKey::AllowedToken(token), &true);
+        } else {
+            storage.remove(&DataKey::AllowedToken(token));
+        }
+        Ok(())
+    }
+
+    /// Redeem a backend-signed voucher and credit the user's withdrawable
+    /// balance in the ledger. The signature must cover `voucher_payload`.
+    pub fn claim(
+        env: Env,
+        voucher: DepositVoucher,
+        signature: BytesN<64>,
+    ) -> Result<i128, DepositGatewayError> {
+        voucher.user.require_auth();
+
+        if voucher.amount <= 0 {
+            return Err(DepositGatewayError::InvalidAmount);
+        }
+        if !is_token_allowed(&env, &voucher.token) {
+            return Err(DepositGatewayError::TokenNotAllowed);
+        }
+
+        let storage = env.storage().persistent();
+        let nonce_key = DataKey::UsedNonce(voucher.user.clone(), voucher.nonce);
+        if storage.has(&nonce_key) {
+            return Err(DepositGatewayError::NonceAlreadyUsed);
+        }
+
+        let pubkey: BytesN<32> = storage
+            .get(&DataKey::BackendPubkey)
+            .ok_or(DepositGatewayError::NotInitialized)?;
+        env.crypto()
+            .ed25519_verify(&pubkey, &voucher_payload(&env, &voucher), &signature);
+
+        storage.set(&nonce_key, &true);
+
+        let total_key = DataKey::TotalClaimed(voucher.token.clone());
+        let total: i128 = storage.get(&total_key).unwrap_or(0);
+        let total = total
+            .checked_add(voucher.amount)
+            .ok_or(DepositGatewayError::Overflow)?;
+        storage.set(&total_key, &total);
+
+        let ledger: Address = storage
+            .get(&DataKey::BalanceLedger)
+            .ok_or(DepositGatewayError::NotInitialized)?;
+        env.invoke_contract::<Val>(
+            &ledger,
+            &Symbol::new(&env, "apply_delta_for_token"),
+            vec![
+                &env,
+                voucher.user.into_val(&env),
+                voucher.token.into_val(&env),
+                voucher.amount.into_val(&env),
+                0i128.into_val(&env),
+                // The voucher nonce already prevents double claims
+                None::<BytesN<32>>.into_val(&env),
+                None::<u64>.into_val(&env),
+            ],
+        );
+
+        publish_deposit_claimed_event(&env, &voucher);
+
+        Ok(voucher.amount)
+    }
+
+    /// Bytes the backend must sign for `voucher` to be claimable here.
+    pub fn voucher_payload(env: Env, voucher: DepositVoucher) -> Bytes {
+        voucher_payload(&env, &voucher)
+    }
+
+    pub fn is_nonce_used(env: Env, user: Address, nonce: u64) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::UsedNonce(user, nonce))
+    }
+
+    pub fn is_token_allowed(env: Env, token: Address) -> bool {
+        is_token_allowed(&env, &token)
+    }
+
+    pub fn get_total_claimed(env: Env, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TotalClaimed(token))
+            .unwrap_or(0)
+    }
+
+    fn require_admin_auth(env: &Env) -> Result<(), DepositGatewayError> {
+        let admin: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .ok_or(DepositGatewayError::NotInitialized)?;
+        admin.require_auth();
+        Ok(())
+    }
+}
+
+/// Binds the voucher to this gateway instance so a signature cannot be
+/// replayed against another deployment.
+fn voucher_payload(env: &Env, voucher: &DepositVoucher) -> Bytes {
+    (env.current_contract_address(), voucher.clone()).to_xdr(env)
+}
+
+fn is_token_allowed(env: &Env, token: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::AllowedToken(token.clone()))
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_deposit_claimed_event(env: &Env, voucher: &DepositVoucher) {
+    env.events().publish(
+        (Symbol::new(env, "deposit_claimed"), voucher.user.clone()),
+        (voucher.token.clone(), voucher.amount, voucher.nonce),
+    );
+}
+
+#[cfg(test)]
+mod test;
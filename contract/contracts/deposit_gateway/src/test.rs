@@ -0,0 +1,157 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use balance_ledger::{BalanceLedgerContract, BalanceLedgerContractClient};
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{testutils::Address as _, Address, BytesN, Env};
+
+fn setup_test(
+    env: &Env,
+) -> (
+    DepositGatewayContractClient<'_>,
+    BalanceLedgerContractClient<'_>,
+    SigningKey,
+    Address,
+) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let signer = SigningKey::from_bytes(&[7u8; 32]);
+    let pubkey = BytesN::from_array(env, &signer.verifying_key().to_bytes());
+
+    let gateway_id = env.register(DepositGatewayContract, ());
+    let ledger_id = env.register(BalanceLedgerContract, ());
+    let gateway = DepositGatewayContractClient::new(env, &gateway_id);
+    let ledger = BalanceLedgerContractClient::new(env, &ledger_id);
+
+    ledger.initialize(&gateway_id);
+    gateway.initialize(&admin, &pubkey, &ledger_id);
+
+    let token = Address::generate(env);
+    gateway.set_token_allowed(&token, &true);
+    ledger.set_token_allowed(&gateway_id, &token, &true);
+
+    (gateway, ledger, signer, token)
+}
+
+fn sign(
+    env: &Env,
+    gateway: &DepositGatewayContractClient,
+    signer: &SigningKey,
+    voucher: &DepositVoucher,
+) -> BytesN<64> {
+    let payload: std::vec::Vec<u8> = gateway.voucher_payload(voucher).iter().collect();
+    BytesN::from_array(env, &signer.sign(&payload).to_bytes())
+}
+
+#[test]
+fn initialize_only_once() {
+    let env = Env::default();
+    let (gateway, ledger, _signer, _token) = setup_test(&env);
+
+    assert_eq!(
+        gateway.try_initialize(
+            &Address::generate(&env),
+            &BytesN::from_array(&env, &[0u8; 32]),
+            &ledger.address
+        ),
+        Err(Ok(DepositGatewayError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn claim_credits_balance_ledger() {
+    let env = Env::default();
+    let (gateway, ledger, signer, token) = setup_test(&env);
+    let user = Address::generate(&env);
+
+    let voucher = DepositVoucher {
+        user: user.clone(),
+        token: token.clone(),
+        amount: 2_500,
+        nonce: 1,
+    };
+    let signature = sign(&env, &gateway, &signer, &voucher);
+
+    assert_eq!(gateway.claim(&voucher, &signature), 2_500);
+    assert_eq!(ledger.get_withdrawable_for_token(&user, &token), 2_500);
+    assert!(gateway.is_nonce_used(&user, &1));
+    assert_eq!(gateway.get_total_claimed(&token), 2_500);
+}
+
+#[test]
+fn rejects_nonce_replay() {
+    let env = Env::default();
+    let (gateway, ledger, signer, token) = setup_test(&env);
+
+    let voucher = DepositVoucher {
+        user: Address::generate(&env),
+        token: token.clone(),
+        amount: 100,
+        nonce: 9,
+    };
+    let signature = sign(&env, &gateway, &signer, &voucher);
+
+    gateway.claim(&voucher, &signature);
+    assert_eq!(
+        gateway.try_claim(&voucher, &signature),
+        Err(Ok(DepositGatewayError::NonceAlreadyUsed))
+    );
+    assert_eq!(
+        ledger.get_withdrawable_for_token(&voucher.user, &voucher.token),
+        100
+    );
+}
+
+#[test]
+#[should_panic]
+fn rejects_tampered_voucher() {
+    let env = Env::default();
+    let (gateway, _ledger, signer, token) = setup_test(&env);
+
+    let voucher = DepositVoucher {
+        user: Address::generate(&env),
+        token: token.clone(),
+        amount: 100,
+        nonce: 1,
+    };
+    let signature = sign(&env, &gateway, &signer, &voucher);
+
+    let tampered = DepositVoucher {
+        amount: 1_000_000,
+        ..voucher
+    };
+    gateway.claim(&tampered, &signature);
+}
+
+#[test]
+fn rejects_disallowed_tokens_and_invalid_amounts() {
+    let env = Env::default();
+    let (gateway, _ledger, signer, token) = setup_test(&env);
+    let user = Address::generate(&env);
+
+    let other_token = DepositVoucher {
+        user: user.clone(),
+        token: Address::generate(&env),
+        amount: 100,
+        nonce: 1,
+    };
+    let signature = sign(&env, &gateway, &signer, &other_token);
+    assert_eq!(
+        gateway.try_claim(&other_token, &signature),
+        Err(Ok(DepositGatewayError::TokenNotAllowed))
+    );
+
+    let zero = DepositVoucher {
+        user,
+        token: token.clone(),
+        amount: 0,
+        nonce: 2,
+    };
+    let signature = sign(&env, &gateway, &signer, &zero);
+    assert_eq!(
+        gateway.try_claim(&zero, &signature),
+        Err(Ok(DepositGatewayError::InvalidAmount))
+    );
+}
@@ -0,0 +1,406 @@
+#![no_std]
+
+use common::{get_oracle_result, init_role, Role};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env,
+    Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum EscrowError {
+    Unauthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    InvalidAmount = 4,
+    InvalidConfig = 5,
+    InvalidSignature = 6,
+    InvalidStatus = 7,
+    ConditionNotMet = 8,
+    EscrowNotFound = 9,
+}
+
+/// What lets one party settle an escrow without the other's consent. The
+/// payer may always release and the payee may always refund.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReleaseCondition {
+    /// The backend signs the XDR encoding of `(escrow_id, release)`, where
+    /// `release` is `true` to pay the payee and `false` to refund the payer
+    BackendSignature,
+    /// The payer may take the funds back from this timestamp on
+    Timeout(u64),
+    /// Once the configured oracle has a final result for the match, the
+    /// payee is paid if it equals the outcome and the payer is refunded
+    /// otherwise
+    OracleResult(BytesN<32>, Symbol),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EscrowStatus {
+    Pending,
+    /// Frozen until an arbiter resolves it
+    Disputed,
+    Released,
+    Refunded,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Escrow {
+    pub id: u64,
+    pub payer: Address,
+    pub payee: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub condition: ReleaseCondition,
+    pub status: EscrowStatus,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    BackendPubkey,
+    Oracle,
+    EscrowCount,
+    Escrow(u64),
+}
+
+#[contract]
+pub struct EscrowContract;
+
+#[contractimpl]
+impl EscrowContract {
+    /// `backend_pubkey` verifies `BackendSignature` conditions. `admin`
+    /// also receives `Role::Arbiter`.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        backend_pubkey: BytesN<32>,
+    ) -> Result<(), EscrowError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::BackendPubkey) {
+            return Err(EscrowError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        storage.set(&DataKey::BackendPubkey, &backend_pubkey);
+        init_role(&env, Role::Admin, &admin);
+        init_role(&env, Role::Arbiter, &admin);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), EscrowError> {
+        common::grant_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), EscrowError> {
+        common::revoke_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), EscrowError> {
+        common::renounce_role(&env, &account, role).map_err(role_error)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), EscrowError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, EscrowError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn set_backend_pubkey(
+        env: Env,
+        admin: Address,
+        backend_pubkey: BytesN<32>,
+    ) -> Result<(), EscrowError> {
+        require_role(&env, Role::Admin, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::BackendPubkey, &backend_pubkey);
+        Ok(())
+    }
+
+    /// Oracle contract read by `OracleResult` escrows. Escrows already
+    /// created follow a change.
+    pub fn set_oracle(env: Env, admin: Address, oracle: Address) -> Result<(), EscrowError> {
+        require_role(&env, Role::Admin, &admin)?;
+        env.storage().persistent().set(&DataKey::Oracle, &oracle);
+        Ok(())
+    }
+
+    pub fn get_oracle(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Oracle)
+    }
+
+    /// Move `amount` of `token` from `payer` into escrow for `payee`.
+    /// Returns the escrow id.
+    pub fn create_escrow(
+        env: Env,
+        payer: Address,
+        payee: Address,
+        token: Address,
+        amount: i128,
+        condition: ReleaseCondition,
+    ) -> Result<u64, EscrowError> {
+        payer.require_auth();
+        if amount <= 0 {
+            return Err(EscrowError::InvalidAmount);
+        }
+        if payer == payee {
+            return Err(EscrowError::InvalidConfig);
+        }
+        match condition {
+            ReleaseCondition::Timeout(at) if at <= env.ledger().timestamp() => {
+                return Err(EscrowError::InvalidConfig);
+            }
+            ReleaseCondition::OracleResult(..) if Self::get_oracle(env.clone()).is_none() => {
+                return Err(EscrowError::InvalidConfig);
+            }
+            _ => {}
+        }
+
+        let storage = env.storage().persistent();
+        let id = storage.get(&DataKey::EscrowCount).unwrap_or(0u64) + 1;
+        storage.set(&DataKey::EscrowCount, &id);
+
+        token::Client::new(&env, &token).transfer(&payer, env.current_contract_address(), &amount);
+
+        let escrow = Escrow {
+            id,
+            payer,
+            payee,
+            token,
+            amount,
+            condition,
+            status: EscrowStatus::Pending,
+            created_at: env.ledger().timestamp(),
+        };
+        store_escrow(&env, &escrow);
+        publish_escrow_event(&env, "escrow_created", &escrow);
+        Ok(id)
+    }
+
+    /// Pay the escrow to the payee. `caller` must be the payer, or the
+    /// payee once the condition allows it. `signature` is only read for
+    /// `BackendSignature` escrows.
+    pub fn release(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        signature: Option<BytesN<64>>,
+    ) -> Result<(), EscrowError> {
+        caller.require_auth();
+        let escrow = get_pending_escrow(&env, escrow_id)?;
+        if caller != escrow.payer {
+            if caller != escrow.payee {
+                return Err(EscrowError::Unauthorized);
+            }
+            require_condition(&env, &escrow, true, signature)?;
+        }
+        settle(&env, escrow, true)
+    }
+
+    /// Return the escrow to the payer. `caller` must be the payee, or the
+    /// payer once the condition allows it.
+    pub fn refund(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        signature: Option<BytesN<64>>,
+    ) -> Result<(), EscrowError> {
+        caller.require_auth();
+        let escrow = get_pending_escrow(&env, escrow_id)?;
+        if caller != escrow.payee {
+            if caller != escrow.payer {
+                return Err(EscrowError::Unauthorized);
+            }
+            require_condition(&env, &escrow, false, signature)?;
+        }
+        settle(&env, escrow, false)
+    }
+
+    /// Freeze a pending escrow until an arbiter decides it. Either party may
+    /// escalate.
+    pub fn dispute(env: Env, caller: Address, escrow_id: u64) -> Result<(), EscrowError> {
+        caller.require_auth();
+        let mut escrow = get_pending_escrow(&env, escrow_id)?;
+        if caller != escrow.payer && caller != escrow.payee {
+            return Err(EscrowError::Unauthorized);
+        }
+
+        escrow.status = EscrowStatus::Disputed;
+        store_escrow(&env, &escrow);
+        publish_escrow_event(&env, "escrow_disputed", &escrow);
+        Ok(())
+    }
+
+    /// Settle a disputed escrow; requires `Role::Arbiter`. `release` pays
+    /// the payee, otherwise the payer is refunded.
+    pub fn resolve_dispute(
+        env: Env,
+        arbiter: Address,
+        escrow_id: u64,
+        release: bool,
+    ) -> Result<(), EscrowError> {
+        require_role(&env, Role::Arbiter, &arbiter)?;
+        let escrow = get_escrow(&env, escrow_id)?;
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(EscrowError::InvalidStatus);
+        }
+        settle(&env, escrow, release)
+    }
+
+    pub fn get_escrow(env: Env, escrow_id: u64) -> Result<Escrow, EscrowError> {
+        get_escrow(&env, escrow_id)
+    }
+
+    pub fn get_escrow_count(env: Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EscrowCount)
+            .unwrap_or(0)
+    }
+}
+
+fn get_escrow(env: &Env, escrow_id: u64) -> Result<Escrow, EscrowError> {
+    let key = DataKey::Escrow(escrow_id);
+    common::bump_persistent(env, &key);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .ok_or(EscrowError::EscrowNotFound)
+}
+
+fn get_pending_escrow(env: &Env, escrow_id: u64) -> Result<Escrow, EscrowError> {
+    let escrow = get_escrow(env, escrow_id)?;
+    if escrow.status != EscrowStatus::Pending {
+        return Err(EscrowError::InvalidStatus);
+    }
+    Ok(escrow)
+}
+
+fn store_escrow(env: &Env, escrow: &Escrow) {
+    let key = DataKey::Escrow(escrow.id);
+    env.storage().persistent().set(&key, escrow);
+    common::bump_persistent(env, &key);
+}
+
+/// Fail unless the escrow's condition lets it be settled in the direction
+/// of `release` without the other party.
+fn require_condition(
+    env: &Env,
+    escrow: &Escrow,
+    release: bool,
+    signature: Option<BytesN<64>>,
+) -> Result<(), EscrowError> {
+    let met = match &escrow.condition {
+        ReleaseCondition::BackendSignature => {
+            let signature = signature.ok_or(EscrowError::InvalidSignature)?;
+            let backend_pubkey: BytesN<32> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::BackendPubkey)
+                .ok_or(EscrowError::NotInitialized)?;
+            let message = (env.current_contract_address(), escrow.id, release).to_xdr(env);
+            env.crypto()
+                .ed25519_verify(&backend_pubkey, &message, &signature);
+            true
+        }
+        ReleaseCondition::Timeout(at) => !release && env.ledger().timestamp() >= *at,
+        ReleaseCondition::OracleResult(match_id, outcome) => {
+            let oracle: Address = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Oracle)
+                .ok_or(EscrowError::InvalidConfig)?;
+            get_oracle_result(env, &oracle, match_id)
+                .is_some_and(|result| (result == *outcome) == release)
+        }
+    };
+    if !met {
+        return Err(EscrowError::ConditionNotMet);
+    }
+    Ok(())
+}
+
+fn settle(env: &Env, mut escrow: Escrow, release: bool) -> Result<(), EscrowError> {
+    let (recipient, status, topic) = if release {
+        (
+            escrow.payee.clone(),
+            EscrowStatus::Released,
+            "escrow_released",
+        )
+    } else {
+        (
+            escrow.payer.clone(),
+            EscrowStatus::Refunded,
+            "escrow_refunded",
+        )
+    };
+
+    // Close the escrow before paying out so it can never settle twice
+    escrow.status = status;
+    store_escrow(env, &escrow);
+
+    token::Client::new(env, &escrow.token).transfer(
+        &env.current_contract_address(),
+        &recipient,
+        &escrow.amount,
+    );
+    publish_escrow_event(env, topic, &escrow);
+    Ok(())
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_escrow_event(env: &Env, topic: &str, escrow: &Escrow) {
+    env.events()
+        .publish((Symbol::new(env, topic), escrow.id), escrow.clone());
+}
+
+fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), EscrowError> {
+    common::require_role(env, role, account).map_err(role_error)
+}
+
+fn role_error(_: common::ContractError) -> EscrowError {
+    EscrowError::Unauthorized
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,240 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use oracle::{OracleConfig, OracleContract, OracleContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, BytesN, Env,
+};
+
+fn setup_test(
+    env: &Env,
+) -> (
+    EscrowContractClient<'_>,
+    Address,
+    SigningKey,
+    token::Client<'_>,
+    Address,
+    Address,
+) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let signer = SigningKey::from_bytes(&[9u8; 32]);
+    let pubkey = BytesN::from_array(env, &signer.verifying_key().to_bytes());
+    let contract_id = env.register(EscrowContract, ());
+    let client = EscrowContractClient::new(env, &contract_id);
+    client.initialize(&admin, &pubkey);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let payer = Address::generate(env);
+    token::StellarAssetClient::new(env, &token_id).mint(&payer, &1_000);
+
+    let token = token::Client::new(env, &token_id);
+    let payee = Address::generate(env);
+
+    (client, admin, signer, token, payer, payee)
+}
+
+fn create(
+    client: &EscrowContractClient,
+    token: &token::Client,
+    payer: &Address,
+    payee: &Address,
+    condition: ReleaseCondition,
+) -> u64 {
+    client.create_escrow(payer, payee, &token.address, &400, &condition)
+}
+
+fn sign(
+    env: &Env,
+    client: &EscrowContractClient,
+    signer: &SigningKey,
+    escrow_id: u64,
+    release: bool,
+) -> BytesN<64> {
+    let message: std::vec::Vec<u8> = (client.address.clone(), escrow_id, release)
+        .to_xdr(env)
+        .iter()
+        .collect();
+    BytesN::from_array(env, &signer.sign(&message).to_bytes())
+}
+
+#[test]
+fn payee_releases_with_backend_signature() {
+    let env = Env::default();
+    let (client, _admin, signer, token, payer, payee) = setup_test(&env);
+
+    let id = create(
+        &client,
+        &token,
+        &payer,
+        &payee,
+        ReleaseCondition::BackendSignature,
+    );
+    assert_eq!(token.balance(&payer), 600);
+    assert_eq!(token.balance(&client.address), 400);
+
+    assert_eq!(
+        client.try_release(&payee, &id, &None),
+        Err(Ok(EscrowError::InvalidSignature))
+    );
+    assert_eq!(
+        client.try_release(
+            &Address::generate(&env),
+            &id,
+            &Some(sign(&env, &client, &signer, id, true))
+        ),
+        Err(Ok(EscrowError::Unauthorized))
+    );
+    // A refund signature cannot be used to release
+    assert!(client
+        .try_release(&payee, &id, &Some(sign(&env, &client, &signer, id, false)))
+        .is_err());
+
+    client.release(&payee, &id, &Some(sign(&env, &client, &signer, id, true)));
+    assert_eq!(token.balance(&payee), 400);
+    assert_eq!(client.get_escrow(&id).status, EscrowStatus::Released);
+    assert_eq!(
+        client.try_refund(&payee, &id, &None),
+        Err(Ok(EscrowError::InvalidStatus))
+    );
+}
+
+#[test]
+fn payer_refunds_only_after_timeout() {
+    let env = Env::default();
+    let (client, _admin, _signer, token, payer, payee) = setup_test(&env);
+
+    assert_eq!(
+        client.try_create_escrow(
+            &payer,
+            &payee,
+            &token.address,
+            &400,
+            &ReleaseCondition::Timeout(env.ledger().timestamp())
+        ),
+        Err(Ok(EscrowError::InvalidConfig))
+    );
+    let deadline = env.ledger().timestamp() + 3_600;
+    let id = create(
+        &client,
+        &token,
+        &payer,
+        &payee,
+        ReleaseCondition::Timeout(deadline),
+    );
+
+    assert_eq!(
+        client.try_refund(&payer, &id, &None),
+        Err(Ok(EscrowError::ConditionNotMet))
+    );
+    // Waiting out the deadline only ever favours the payer
+    env.ledger().with_mut(|ledger| ledger.timestamp = deadline);
+    assert_eq!(
+        client.try_release(&payee, &id, &None),
+        Err(Ok(EscrowError::ConditionNotMet))
+    );
+    client.refund(&payer, &id, &None);
+    assert_eq!(token.balance(&payer), 1_000);
+    assert_eq!(client.get_escrow(&id).status, EscrowStatus::Refunded);
+
+    // Either side can always settle in the other's favour
+    let id = create(
+        &client,
+        &token,
+        &payer,
+        &payee,
+        ReleaseCondition::Timeout(deadline + 3_600),
+    );
+    client.release(&payer, &id, &None);
+    assert_eq!(token.balance(&payee), 400);
+}
+
+#[test]
+fn oracle_result_decides_direction() {
+    let env = Env::default();
+    let (client, admin, _signer, token, payer, payee) = setup_test(&env);
+
+    let oracle_id = env.register(OracleContract, ());
+    let oracle = OracleContractClient::new(&env, &oracle_id);
+    let reporter = Address::generate(&env);
+    oracle.initialize(
+        &admin,
+        &OracleConfig {
+            quorum: 1,
+            challenge_window: 0,
+        },
+    );
+    oracle.set_reporter(&admin, &reporter, &true);
+
+    let match_id = BytesN::from_array(&env, &[3; 32]);
+    let condition = ReleaseCondition::OracleResult(match_id.clone(), Symbol::new(&env, "home"));
+    assert_eq!(
+        client.try_create_escrow(&payer, &payee, &token.address, &400, &condition),
+        Err(Ok(EscrowError::InvalidConfig))
+    );
+    client.set_oracle(&admin, &oracle_id);
+    let win = create(&client, &token, &payer, &payee, condition.clone());
+    let loss = create(&client, &token, &payer, &payee, condition);
+
+    assert_eq!(
+        client.try_release(&payee, &win, &None),
+        Err(Ok(EscrowError::ConditionNotMet))
+    );
+    oracle.submit_result(&reporter, &match_id, &Symbol::new(&env, "away"));
+
+    assert_eq!(
+        client.try_release(&payee, &win, &None),
+        Err(Ok(EscrowError::ConditionNotMet))
+    );
+    client.refund(&payer, &win, &None);
+    client.refund(&payer, &loss, &None);
+    assert_eq!(token.balance(&payer), 1_000);
+}
+
+#[test]
+fn disputes_freeze_the_escrow_until_an_arbiter_decides() {
+    let env = Env::default();
+    let (client, admin, _signer, token, payer, payee) = setup_test(&env);
+
+    let id = create(
+        &client,
+        &token,
+        &payer,
+        &payee,
+        ReleaseCondition::BackendSignature,
+    );
+    assert_eq!(
+        client.try_dispute(&Address::generate(&env), &id),
+        Err(Ok(EscrowError::Unauthorized))
+    );
+    client.dispute(&payee, &id);
+    assert_eq!(client.get_escrow(&id).status, EscrowStatus::Disputed);
+
+    assert_eq!(
+        client.try_release(&payer, &id, &None),
+        Err(Ok(EscrowError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_resolve_dispute(&payee, &id, &true),
+        Err(Ok(EscrowError::Unauthorized))
+    );
+
+    let arbiter = Address::generate(&env);
+    client.grant_role(&admin, &Role::Arbiter, &arbiter);
+    client.resolve_dispute(&arbiter, &id, &true);
+    assert_eq!(token.balance(&payee), 400);
+    assert_eq!(
+        client.try_resolve_dispute(&arbiter, &id, &false),
+        Err(Ok(EscrowError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_get_escrow(&99),
+        Err(Ok(EscrowError::EscrowNotFound))
+    );
+}
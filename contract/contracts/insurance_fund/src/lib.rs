@@ -0,0 +1,593 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, Address, BytesN, Env, Symbol, Val,
+    Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum InsuranceFundError {
+    Unauthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    InvalidAmount = 4,
+    InvalidConfig = 5,
+    NoShortfall = 6,
+    ExceedsEventCap = 7,
+    ExceedsPeriodCap = 8,
+    InsufficientFunds = 9,
+    Overflow = 10,
+    DrawNotFound = 11,
+    NotCommitteeMember = 12,
+    ClaimAlreadyPaid = 13,
+    ExceedsClaimCap = 14,
+    ReimbursementNotFound = 15,
+}
+
+/// Caps applied to every draw made by the settlement contract.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundConfig {
+    /// Maximum total amount that may be drawn for a single settlement event.
+    pub max_per_event: i128,
+    /// Maximum total amount that may be drawn within one period.
+    pub max_per_period: i128,
+    /// Length of a cap period in seconds.
+    pub period_seconds: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DrawRecord {
+    pub id: u64,
+    pub event_id: BytesN<32>,
+    pub recipient: Address,
+    pub amount: i128,
+    pub required_payout: i128,
+    pub available: i128,
+    pub period: u64,
+    pub timestamp: u64,
+}
+
+/// Payout approved by the claims committee for a user affected by a bug or
+/// failed settlement.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReimbursementRecord {
+    pub id: u64,
+    /// Off-chain claim reference; each is paid at most once
+    pub claim_id: BytesN<32>,
+    pub recipient: Address,
+    pub amount: i128,
+    /// Hash of the evidence or incident report behind the claim
+    pub reason_hash: BytesN<32>,
+    pub approved_by: Address,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Admin,
+    Token,
+    SettlementContract,
+    Config,
+    FundBalance,
+    Contributions(Symbol),
+    DrawCount,
+    Draw(u64),
+    EventDrawn(BytesN<32>),
+    PeriodDrawn(u64),
+    CommitteeMember(Address),
+    MaxPerClaim,
+    ReimbursementCount,
+    Reimbursement(u64),
+    ClaimPaid(BytesN<32>),
+}
+
+#[contract]
+pub struct InsuranceFundContract;
+
+#[contractimpl]
+impl InsuranceFundContract {
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        token: Address,
+        settlement_contract: Address,
+        config: FundConfig,
+    ) -> Result<(), InsuranceFundError> {
+        let storage = env.storage().persistent();
+
+        if storage.has(&DataKey::Admin) {
+            return Err(InsuranceFundError::AlreadyInitialized);
+        }
+        validate_config(&config)?;
+
+        storage.set(&DataKey::Admin, &admin);
+        storage.set(&DataKey::Token, &token);
+        storage.set(&DataKey::SettlementContract, &settlement_contract);
+        storage.set(&DataKey::Config, &config);
+        Ok(())
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), InsuranceFundError> {
+        Self::require_admin_auth(&env)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env) -> Result<Option<u32>, InsuranceFundError> {
+        Self::require_admin_auth(&env)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn update_config(env: Env, config: FundConfig) -> Result<(), InsuranceFundError> {
+        Self::require_admin_auth(&env)?;
+        validate_config(&config)?;
+
+        env.storage().persistent().set(&DataKey::Config, &config);
+        Ok(())
+    }
+
+    pub fn set_settlement_contract(
+        env: Env,
+        settlement_contract: Address,
+    ) -> Result<(), InsuranceFundError> {
+        Self::require_admin_auth(&env)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::SettlementContract, &settlement_contract);
+        Ok(())
+    }
+
+    /// Pay tokens into the fund. `source` tags the inflow (e.g. `fee`,
+    /// `slash`) so contributions can be reported per origin.
+    pub fn contribute(
+        env: Env,
+        from: Address,
+        amount: i128,
+        source: Symbol,
+    ) -> Result<i128, InsuranceFundError> {
+        from.require_auth();
+        validate_positive(amount)?;
+
+        let token_address = get_token(&env)?;
+        token::Client::new(&env, &token_address).transfer(
+            &from,
+            env.current_contract_address(),
+            &amount,
+        );
+
+        let balance = checked_add(get_fund_balance(&env), amount)?;
+        let contributed = checked_add(get_contributions(&env, &source), amount)?;
+
+        let storage = env.storage().persistent();
+        storage.set(&DataKey::FundBalance, &balance);
+        common::bump_persistent(&env, &DataKey::FundBalance);
+        storage.set(&DataKey::Contributions(source.clone()), &contributed);
+        common::bump_persistent(&env, &DataKey::Contributions(source.clone()));
+
+        publish_contributed_event(&env, &from, &source, amount, balance);
+
+        Ok(balance)
+    }
+
+    /// Cover the gap between what a settlement owes and what it can pay.
+    /// Only the registered settlement contract may draw, and only for the
+    /// verified shortfall `required_payout - available`, within the caps.
+    pub fn cover_shortfall(
+        env: Env,
+        event_id: BytesN<32>,
+        recipient: Address,
+        required_payout: i128,
+        available: i128,
+    ) -> Result<i128, InsuranceFundError> {
+        Self::require_settlement_auth(&env)?;
+        validate_non_negative(available)?;
+
+        let shortfall = required_payout
+            .checked_sub(available)
+            .ok_or(InsuranceFundError::Overflow)?;
+        if shortfall <= 0 {
+            return Err(InsuranceFundError::NoShortfall);
+        }
+
+        let config = get_config(&env)?;
+        let event_drawn = checked_add(get_event_drawn(&env, &event_id), shortfall)?;
+        if event_drawn > config.max_per_event {
+            return Err(InsuranceFundError::ExceedsEventCap);
+        }
+
+        let period = current_period(&env, &config);
+        let period_drawn = checked_add(get_period_drawn(&env, period), shortfall)?;
+        if period_drawn > config.max_per_period {
+            return Err(InsuranceFundError::ExceedsPeriodCap);
+        }
+
+        let balance = get_fund_balance(&env);
+        if balance < shortfall {
+            return Err(InsuranceFundError::InsufficientFunds);
+        }
+
+        let token_address = get_token(&env)?;
+        token::Client::new(&env, &token_address).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &shortfall,
+        );
+
+        let id = get_draw_count(&env);
+        let record = DrawRecord {
+            id,
+            event_id: event_id.clone(),
+            recipient,
+            amount: shortfall,
+            required_payout,
+            available,
+            period,
+            timestamp: env.ledger().timestamp(),
+        };
+
+        let storage = env.storage().persistent();
+        storage.set(&DataKey::FundBalance, &(balance - shortfall));
+        common::bump_persistent(&env, &DataKey::FundBalance);
+        storage.set(&DataKey::EventDrawn(event_id.clone()), &event_drawn);
+        common::bump_persistent(&env, &DataKey::EventDrawn(event_id));
+        storage.set(&DataKey::PeriodDrawn(period), &period_drawn);
+        common::bump_persistent(&env, &DataKey::PeriodDrawn(period));
+        storage.set(&DataKey::Draw(id), &record);
+        common::bump_persistent(&env, &DataKey::Draw(id));
+        storage.set(&DataKey::DrawCount, &(id + 1));
+        common::bump_persistent(&env, &DataKey::DrawCount);
+
+        publish_shortfall_covered_event(&env, &record);
+
+        Ok(shortfall)
+    }
+
+    /// Add or remove a member of the claims committee.
+    pub fn set_committee_member(
+        env: Env,
+        member: Address,
+        allowed: bool,
+    ) -> Result<(), InsuranceFundError> {
+        Self::require_admin_auth(&env)?;
+
+        let key = DataKey::CommitteeMember(member);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_committee_member(env: Env, member: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::CommitteeMember(member))
+    }
+
+    /// Cap on a single reimbursement. Reimbursements are refused until one
+    /// is set.
+    pub fn set_max_per_claim(env: Env, max_per_claim: i128) -> Result<(), InsuranceFundError> {
+        Self::require_admin_auth(&env)?;
+        validate_positive(max_per_claim)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::MaxPerClaim, &max_per_claim);
+        Ok(())
+    }
+
+    pub fn get_max_per_claim(env: Env) -> Option<i128> {
+        env.storage().persistent().get(&DataKey::MaxPerClaim)
+    }
+
+    /// Pay `recipient` for an approved claim. `claim_id` is honoured once,
+    /// so a retried approval cannot pay twice. Returns the record id.
+    pub fn approve_reimbursement(
+        env: Env,
+        member: Address,
+        claim_id: BytesN<32>,
+        recipient: Address,
+        amount: i128,
+        reason_hash: BytesN<32>,
+    ) -> Result<u64, InsuranceFundError> {
+        member.require_auth();
+        if !Self::is_committee_member(env.clone(), member.clone()) {
+            return Err(InsuranceFundError::NotCommitteeMember);
+        }
+        validate_positive(amount)?;
+
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::ClaimPaid(claim_id.clone())) {
+            return Err(InsuranceFundError::ClaimAlreadyPaid);
+        }
+        let max_per_claim =
+            Self::get_max_per_claim(env.clone()).ok_or(InsuranceFundError::InvalidConfig)?;
+        if amount > max_per_claim {
+            return Err(InsuranceFundError::ExceedsClaimCap);
+        }
+        let balance = get_fund_balance(&env);
+        if balance < amount {
+            return Err(InsuranceFundError::InsufficientFunds);
+        }
+
+        let id = get_reimbursement_count(&env);
+        let record = ReimbursementRecord {
+            id,
+            claim_id: claim_id.clone(),
+            recipient: recipient.clone(),
+            amount,
+            reason_hash,
+            approved_by: member,
+            timestamp: env.ledger().timestamp(),
+        };
+        storage.set(&DataKey::FundBalance, &(balance - amount));
+        common::bump_persistent(&env, &DataKey::FundBalance);
+        storage.set(&DataKey::ClaimPaid(claim_id.clone()), &id);
+        common::bump_persistent(&env, &DataKey::ClaimPaid(claim_id));
+        storage.set(&DataKey::Reimbursement(id), &record);
+        common::bump_persistent(&env, &DataKey::Reimbursement(id));
+        storage.set(&DataKey::ReimbursementCount, &(id + 1));
+        common::bump_persistent(&env, &DataKey::ReimbursementCount);
+
+        let token_address = get_token(&env)?;
+        token::Client::new(&env, &token_address).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &amount,
+        );
+
+        publish_reimbursed_event(&env, &record);
+
+        Ok(id)
+    }
+
+    /// Record id of the reimbursement paid for `claim_id`, if any.
+    pub fn get_claim_payout(env: Env, claim_id: BytesN<32>) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ClaimPaid(claim_id))
+    }
+
+    pub fn get_reimbursement_count(env: Env) -> u64 {
+        get_reimbursement_count(&env)
+    }
+
+    pub fn get_reimbursement(env: Env, id: u64) -> Result<ReimbursementRecord, InsuranceFundError> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Reimbursement(id))
+            .ok_or(InsuranceFundError::ReimbursementNotFound)
+    }
+
+    /// Reimbursement history in insertion order, `limit` records starting
+    /// at `offset`.
+    pub fn get_reimbursements(env: Env, offset: u64, limit: u32) -> Vec<ReimbursementRecord> {
+        let storage = env.storage().persistent();
+        let end = get_reimbursement_count(&env).min(offset.saturating_add(limit as u64));
+
+        let mut records = Vec::new(&env);
+        for id in offset..end {
+            if let Some(record) = storage.get(&DataKey::Reimbursement(id)) {
+                records.push_back(record);
+            }
+        }
+        records
+    }
+
+    pub fn get_config(env: Env) -> Result<FundConfig, InsuranceFundError> {
+        get_config(&env)
+    }
+
+    pub fn get_fund_balance(env: Env) -> i128 {
+        get_fund_balance(&env)
+    }
+
+    pub fn get_contributions(env: Env, source: Symbol) -> i128 {
+        get_contributions(&env, &source)
+    }
+
+    pub fn get_event_drawn(env: Env, event_id: BytesN<32>) -> i128 {
+        get_event_drawn(&env, &event_id)
+    }
+
+    pub fn get_period_drawn(env: Env, period: u64) -> i128 {
+        get_period_drawn(&env, period)
+    }
+
+    pub fn get_current_period(env: Env) -> Result<u64, InsuranceFundError> {
+        let config = get_config(&env)?;
+        Ok(current_period(&env, &config))
+    }
+
+    pub fn get_draw_count(env: Env) -> u64 {
+        get_draw_count(&env)
+    }
+
+    pub fn get_draw(env: Env, id: u64) -> Result<DrawRecord, InsuranceFundError> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Draw(id))
+            .ok_or(InsuranceFundError::DrawNotFound)
+    }
+
+    /// Draw history in insertion order, `limit` records starting at `offset`.
+    pub fn get_draws(env: Env, offset: u64, limit: u32) -> Vec<DrawRecord> {
+        let storage = env.storage().persistent();
+        let end = get_draw_count(&env).min(offset.saturating_add(limit as u64));
+
+        let mut draws = Vec::new(&env);
+        for id in offset..end {
+            if let Some(record) = storage.get(&DataKey::Draw(id)) {
+                draws.push_back(record);
+            }
+        }
+        draws
+    }
+
+    fn require_admin_auth(env: &Env) -> Result<(), InsuranceFundError> {
+        let admin: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .ok_or(InsuranceFundError::NotInitialized)?;
+        admin.require_auth();
+        Ok(())
+    }
+
+    fn require_settlement_auth(env: &Env) -> Result<(), InsuranceFundError> {
+        let settlement: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::SettlementContract)
+            .ok_or(InsuranceFundError::NotInitialized)?;
+        settlement.require_auth();
+        Ok(())
+    }
+}
+
+fn validate_config(config: &FundConfig) -> Result<(), InsuranceFundError> {
+    if config.max_per_event <= 0 || config.max_per_period <= 0 || config.period_seconds == 0 {
+        return Err(InsuranceFundError::InvalidConfig);
+    }
+    Ok(())
+}
+
+fn checked_add(a: i128, b: i128) -> Result<i128, InsuranceFundError> {
+    a.checked_add(b).ok_or(InsuranceFundError::Overflow)
+}
+
+fn validate_non_negative(amount: i128) -> Result<(), InsuranceFundError> {
+    if amount < 0 {
+        return Err(InsuranceFundError::InvalidAmount);
+    }
+    Ok(())
+}
+
+fn validate_positive(amount: i128) -> Result<(), InsuranceFundError> {
+    if amount <= 0 {
+        return Err(InsuranceFundError::InvalidAmount);
+    }
+    Ok(())
+}
+
+fn current_period(env: &Env, config: &FundConfig) -> u64 {
+    env.ledger().timestamp() / config.period_seconds
+}
+
+fn get_token(env: &Env) -> Result<Address, InsuranceFundError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Token)
+        .ok_or(InsuranceFundError::NotInitialized)
+}
+
+fn get_config(env: &Env) -> Result<FundConfig, InsuranceFundError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Config)
+        .ok_or(InsuranceFundError::NotInitialized)
+}
+
+fn get_fund_balance(env: &Env) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::FundBalance)
+        .unwrap_or(0)
+}
+
+fn get_contributions(env: &Env, source: &Symbol) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Contributions(source.clone()))
+        .unwrap_or(0)
+}
+
+fn get_event_drawn(env: &Env, event_id: &BytesN<32>) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::EventDrawn(event_id.clone()))
+        .unwrap_or(0)
+}
+
+fn get_period_drawn(env: &Env, period: u64) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PeriodDrawn(period))
+        .unwrap_or(0)
+}
+
+fn get_draw_count(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DrawCount)
+        .unwrap_or(0)
+}
+
+fn get_reimbursement_count(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ReimbursementCount)
+        .unwrap_or(0)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_contributed_event(
+    env: &Env,
+    from: &Address,
+    source: &Symbol,
+    amount: i128,
+    balance: i128,
+) {
+    env.events().publish(
+        (Symbol::new(env, "fund_contributed"), from.clone()),
+        (source.clone(), amount, balance),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_shortfall_covered_event(env: &Env, record: &DrawRecord) {
+    env.events().publish(
+        (
+            Symbol::new(env, "shortfall_covered"),
+            record.event_id.clone(),
+        ),
+        record.clone(),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_reimbursed_event(env: &Env, record: &ReimbursementRecord) {
+    env.events().publish(
+        (
+            Symbol::new(env, "claim_reimbursed"),
+            record.claim_id.clone(),
+        ),
+        record.clone(),
+    );
+}
+
+#[cfg(test)]
+mod test;
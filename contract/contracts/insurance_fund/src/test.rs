@@ -0,0 +1,278 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Ledger},
+    token, Address, BytesN, Env,
+};
+
+fn default_config() -> FundConfig {
+    FundConfig {
+        max_per_event: 500,
+        max_per_period: 800,
+        period_seconds: 86_400,
+    }
+}
+
+fn setup_test(
+    env: &Env,
+) -> (
+    InsuranceFundContractClient<'_>,
+    token::Client<'_>,
+    token::StellarAssetClient<'_>,
+) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let settlement = Address::generate(env);
+    let token_contract = env.register_stellar_asset_contract_v2(Address::generate(env));
+    let token_id = token_contract.address();
+
+    let contract_id = env.register(InsuranceFundContract, ());
+    let client = InsuranceFundContractClient::new(env, &contract_id);
+    client.initialize(&admin, &token_id, &settlement, &default_config());
+
+    let token = token::Client::new(env, &token_id);
+    let token_admin = token::StellarAssetClient::new(env, &token_id);
+
+    (client, token, token_admin)
+}
+
+fn fund(
+    env: &Env,
+    client: &InsuranceFundContractClient,
+    token_admin: &token::StellarAssetClient,
+    amount: i128,
+) {
+    let funder = Address::generate(env);
+    token_admin.mint(&funder, &amount);
+    client.contribute(&funder, &amount, &symbol_short!("fee"));
+}
+
+#[test]
+fn initialize_only_once() {
+    let env = Env::default();
+    let (client, token, _token_admin) = setup_test(&env);
+
+    assert_eq!(
+        client.try_initialize(
+            &Address::generate(&env),
+            &token.address,
+            &Address::generate(&env),
+            &default_config()
+        ),
+        Err(Ok(InsuranceFundError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn contributions_are_tracked_per_source() {
+    let env = Env::default();
+    let (client, token, token_admin) = setup_test(&env);
+
+    let slasher = Address::generate(&env);
+    token_admin.mint(&slasher, &300);
+
+    fund(&env, &client, &token_admin, 1_000);
+    let balance = client.contribute(&slasher, &300, &symbol_short!("slash"));
+
+    assert_eq!(balance, 1_300);
+    assert_eq!(client.get_fund_balance(), 1_300);
+    assert_eq!(client.get_contributions(&symbol_short!("fee")), 1_000);
+    assert_eq!(client.get_contributions(&symbol_short!("slash")), 300);
+    assert_eq!(token.balance(&client.address), 1_300);
+    assert_eq!(
+        client.try_contribute(&slasher, &0, &symbol_short!("slash")),
+        Err(Ok(InsuranceFundError::InvalidAmount))
+    );
+}
+
+#[test]
+fn covers_only_the_verified_shortfall() {
+    let env = Env::default();
+    let (client, token, token_admin) = setup_test(&env);
+    fund(&env, &client, &token_admin, 1_000);
+
+    let event_id = BytesN::from_array(&env, &[1u8; 32]);
+    let winner = Address::generate(&env);
+
+    let drawn = client.cover_shortfall(&event_id, &winner, &750, &500);
+    assert_eq!(drawn, 250);
+    assert_eq!(token.balance(&winner), 250);
+    assert_eq!(client.get_fund_balance(), 750);
+    assert_eq!(client.get_event_drawn(&event_id), 250);
+
+    assert_eq!(
+        client.try_cover_shortfall(&event_id, &winner, &500, &500),
+        Err(Ok(InsuranceFundError::NoShortfall))
+    );
+}
+
+#[test]
+fn enforces_event_and_period_caps() {
+    let env = Env::default();
+    let (client, _token, token_admin) = setup_test(&env);
+    fund(&env, &client, &token_admin, 2_000);
+
+    let first = BytesN::from_array(&env, &[1u8; 32]);
+    let second = BytesN::from_array(&env, &[2u8; 32]);
+    let recipient = Address::generate(&env);
+
+    client.cover_shortfall(&first, &recipient, &400, &0);
+    assert_eq!(
+        client.try_cover_shortfall(&first, &recipient, &101, &0),
+        Err(Ok(InsuranceFundError::ExceedsEventCap))
+    );
+
+    client.cover_shortfall(&second, &recipient, &400, &0);
+    let third = BytesN::from_array(&env, &[3u8; 32]);
+    assert_eq!(
+        client.try_cover_shortfall(&third, &recipient, &1, &0),
+        Err(Ok(InsuranceFundError::ExceedsPeriodCap))
+    );
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 86_400;
+    });
+    assert_eq!(client.cover_shortfall(&third, &recipient, &100, &0), 100);
+    assert_eq!(client.get_period_drawn(&0), 800);
+    assert_eq!(client.get_period_drawn(&1), 100);
+}
+
+#[test]
+fn rejects_draws_beyond_fund_balance() {
+    let env = Env::default();
+    let (client, _token, token_admin) = setup_test(&env);
+    fund(&env, &client, &token_admin, 100);
+
+    let event_id = BytesN::from_array(&env, &[9u8; 32]);
+    assert_eq!(
+        client.try_cover_shortfall(&event_id, &Address::generate(&env), &150, &0),
+        Err(Ok(InsuranceFundError::InsufficientFunds))
+    );
+}
+
+#[test]
+#[should_panic]
+fn cover_shortfall_requires_settlement_auth() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let settlement = Address::generate(&env);
+    let token_contract = env.register_stellar_asset_contract_v2(Address::generate(&env));
+
+    let contract_id = env.register(InsuranceFundContract, ());
+    let client = InsuranceFundContractClient::new(&env, &contract_id);
+    client.initialize(
+        &admin,
+        &token_contract.address(),
+        &settlement,
+        &default_config(),
+    );
+
+    client.cover_shortfall(
+        &BytesN::from_array(&env, &[1u8; 32]),
+        &Address::generate(&env),
+        &10,
+        &0,
+    );
+}
+
+#[test]
+fn records_full_draw_history() {
+    let env = Env::default();
+    let (client, _token, token_admin) = setup_test(&env);
+    fund(&env, &client, &token_admin, 1_000);
+
+    let recipient = Address::generate(&env);
+    for i in 0..3u8 {
+        let event_id = BytesN::from_array(&env, &[i + 1; 32]);
+        client.cover_shortfall(&event_id, &recipient, &(100 + i as i128), &0);
+    }
+
+    assert_eq!(client.get_draw_count(), 3);
+
+    let record = client.get_draw(&1);
+    assert_eq!(record.id, 1);
+    assert_eq!(record.amount, 101);
+    assert_eq!(record.recipient, recipient);
+    assert_eq!(record.event_id, BytesN::from_array(&env, &[2u8; 32]));
+
+    let page = client.get_draws(&1, &5);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(1).unwrap().amount, 102);
+
+    assert_eq!(
+        client.try_get_draw(&3),
+        Err(Ok(InsuranceFundError::DrawNotFound))
+    );
+}
+
+#[test]
+fn committee_reimburses_each_claim_once_within_cap() {
+    let env = Env::default();
+    let (client, token, token_admin) = setup_test(&env);
+    fund(&env, &client, &token_admin, 1_000);
+
+    let member = Address::generate(&env);
+    let user = Address::generate(&env);
+    let claim_id = BytesN::from_array(&env, &[1; 32]);
+    let reason = BytesN::from_array(&env, &[9; 32]);
+
+    assert_eq!(
+        client.try_approve_reimbursement(&member, &claim_id, &user, &100, &reason),
+        Err(Ok(InsuranceFundError::NotCommitteeMember))
+    );
+    client.set_committee_member(&member, &true);
+    assert_eq!(
+        client.try_approve_reimbursement(&member, &claim_id, &user, &100, &reason),
+        Err(Ok(InsuranceFundError::InvalidConfig))
+    );
+
+    client.set_max_per_claim(&250);
+    assert_eq!(
+        client.try_approve_reimbursement(&member, &claim_id, &user, &251, &reason),
+        Err(Ok(InsuranceFundError::ExceedsClaimCap))
+    );
+    assert_eq!(
+        client.approve_reimbursement(&member, &claim_id, &user, &200, &reason),
+        0
+    );
+    assert_eq!(
+        client.try_approve_reimbursement(&member, &claim_id, &user, &200, &reason),
+        Err(Ok(InsuranceFundError::ClaimAlreadyPaid))
+    );
+
+    assert_eq!(token.balance(&user), 200);
+    assert_eq!(client.get_fund_balance(), 800);
+    assert_eq!(client.get_claim_payout(&claim_id), Some(0));
+
+    let record = client.get_reimbursement(&0);
+    assert_eq!(record.recipient, user);
+    assert_eq!(record.approved_by, member);
+    assert_eq!(record.reason_hash, reason);
+    assert_eq!(client.get_reimbursements(&0, &10).len(), 1);
+}
+
+#[test]
+fn reimbursement_cannot_exceed_fund_balance() {
+    let env = Env::default();
+    let (client, _token, token_admin) = setup_test(&env);
+    fund(&env, &client, &token_admin, 100);
+
+    let member = Address::generate(&env);
+    client.set_committee_member(&member, &true);
+    client.set_max_per_claim(&500);
+
+    assert_eq!(
+        client.try_approve_reimbursement(
+            &member,
+            &BytesN::from_array(&env, &[2; 32]),
+            &Address::generate(&env),
+            &101,
+            &BytesN::from_array(&env, &[0; 32])
+        ),
+        Err(Ok(InsuranceFundError::InsufficientFunds))
+    );
+    assert_eq!(client.get_reimbursement_count(), 0);
+}
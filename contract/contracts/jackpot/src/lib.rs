@@ -0,0 +1,392 @@
+#![no_std]
+
+use common::{
+    ensure_not_replayed, init_role, is_operation_executed, publish_versioned_event, require_role,
+    ContractError, JackpotWonEvent, Role, EVENT_SCHEMA_VERSION, JACKPOT_WON_EVENT,
+};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Contribution rates are expressed in basis points of the reported amount.
+pub const MAX_CONTRIBUTION_BPS: u32 = 10_000;
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Token,
+    BackendPubkey,
+    ContributionBps,
+    Contributor(Address),
+    Pool,
+    TotalAwarded,
+    LastAward,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JackpotAward {
+    pub winner: Address,
+    pub amount: i128,
+    pub operation_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+#[contract]
+pub struct JackpotContract;
+
+#[contractimpl]
+impl JackpotContract {
+    /// `token` is the only asset the pool accumulates and pays out.
+    /// `backend_pubkey` is the ed25519 key whose signatures select winners.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        token: Address,
+        backend_pubkey: BytesN<32>,
+        contribution_bps: u32,
+    ) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::Token) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        if contribution_bps > MAX_CONTRIBUTION_BPS {
+            return Err(ContractError::InvalidConfig);
+        }
+
+        storage.set(&DataKey::Token, &token);
+        storage.set(&DataKey::BackendPubkey, &backend_pubkey);
+        storage.set(&DataKey::ContributionBps, &contribution_bps);
+        init_role(&env, Role::Admin, &admin);
+        Ok(())
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Stop duplicating events under their pre-versioning topics from
+    /// `until` on; `None` keeps the duplicates indefinitely.
+    pub fn set_legacy_events_until(
+        env: Env,
+        admin: Address,
+        until: Option<u64>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::set_legacy_events_until(&env, until);
+        Ok(())
+    }
+
+    pub fn get_legacy_events_until(env: Env) -> Option<u64> {
+        common::get_legacy_events_until(&env)
+    }
+
+    /// Allow or disallow a contract (betting, spin rewards) to feed the pool.
+    pub fn set_contributor(
+        env: Env,
+        admin: Address,
+        contributor: Address,
+        allowed: bool,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let key = DataKey::Contributor(contributor);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_contributor(env: Env, contributor: Address) -> bool {
+        is_contributor(&env, &contributor)
+    }
+
+    pub fn set_contribution_bps(
+        env: Env,
+        admin: Address,
+        contribution_bps: u32,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        if contribution_bps > MAX_CONTRIBUTION_BPS {
+            return Err(ContractError::InvalidConfig);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::ContributionBps, &contribution_bps);
+        Ok(())
+    }
+
+    pub fn get_contribution_bps(env: Env) -> u32 {
+        get_contribution_bps(&env)
+    }
+
+    pub fn set_backend_pubkey(
+        env: Env,
+        admin: Address,
+        backend_pubkey: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::BackendPubkey, &backend_pubkey);
+        Ok(())
+    }
+
+    pub fn get_token(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Token)
+    }
+
+    /// Share of `amount` a contribution in `token` would add to the pool.
+    pub fn quote_contribution(
+        env: Env,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        contribution_for(&env, &token, amount)
+    }
+
+    /// Pull the configured share of `amount` from `contributor` into the pool.
+    /// Amounts in other tokens are ignored. Returns the share taken.
+    pub fn contribute(
+        env: Env,
+        contributor: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        contributor.require_auth();
+        if !is_contributor(&env, &contributor) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let share = contribution_for(&env, &token, amount)?;
+        if share == 0 {
+            return Ok(0);
+        }
+
+        token::Client::new(&env, &token).transfer(
+            &contributor,
+            env.current_contract_address(),
+            &share,
+        );
+        let pool = adjust_pool(&env, share)?;
+
+        publish_contribution_event(&env, &contributor, share, pool);
+
+        Ok(share)
+    }
+
+    /// Top up the pool directly, e.g. to seed a fresh jackpot after a win.
+    pub fn seed_pool(env: Env, funder: Address, amount: i128) -> Result<i128, ContractError> {
+        funder.require_auth();
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        token::Client::new(&env, &get_token(&env)?).transfer(
+            &funder,
+            env.current_contract_address(),
+            &amount,
+        );
+        let pool = adjust_pool(&env, amount)?;
+
+        publish_contribution_event(&env, &funder, amount, pool);
+
+        Ok(pool)
+    }
+
+    pub fn get_pool(env: Env) -> i128 {
+        get_pool(&env)
+    }
+
+    pub fn get_total_awarded(env: Env) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TotalAwarded)
+            .unwrap_or(0)
+    }
+
+    pub fn get_last_award(env: Env) -> Option<JackpotAward> {
+        env.storage().persistent().get(&DataKey::LastAward)
+    }
+
+    pub fn is_award_executed(env: Env, operation_hash: BytesN<32>) -> bool {
+        is_operation_executed(&env, jackpot_scope(&env), operation_hash)
+    }
+
+    /// Pay the whole pool to `winner`. The backend signs the XDR encoding
+    /// of `(jackpot_contract, operation_hash, winner)` offline, so the
+    /// signature cannot be reused for another winner or another jackpot, or
+    /// replayed under the same hash.
+    pub fn award_jackpot(
+        env: Env,
+        operation_hash: BytesN<32>,
+        winner: Address,
+        signature: BytesN<64>,
+    ) -> Result<JackpotAward, ContractError> {
+        let backend_pubkey: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::BackendPubkey)
+            .ok_or(ContractError::NotInitialized)?;
+        // A mismatching signature traps in the host and aborts the call
+        let message = (
+            env.current_contract_address(),
+            operation_hash.clone(),
+            winner.clone(),
+        )
+            .to_xdr(&env);
+        env.crypto()
+            .ed25519_verify(&backend_pubkey, &message, &signature);
+
+        ensure_not_replayed(&env, jackpot_scope(&env), operation_hash.clone(), None)?;
+
+        let amount = get_pool(&env);
+        if amount <= 0 {
+            return Err(ContractError::InsufficientBalance);
+        }
+
+        let storage = env.storage().persistent();
+        storage.set(&DataKey::Pool, &0i128);
+        let total_awarded: i128 = storage.get(&DataKey::TotalAwarded).unwrap_or(0);
+        storage.set(
+            &DataKey::TotalAwarded,
+            &total_awarded
+                .checked_add(amount)
+                .ok_or(ContractError::InvalidAmount)?,
+        );
+
+        let award = JackpotAward {
+            winner: winner.clone(),
+            amount,
+            operation_hash: operation_hash.clone(),
+            timestamp: env.ledger().timestamp(),
+        };
+        storage.set(&DataKey::LastAward, &award);
+        for key in [DataKey::Pool, DataKey::TotalAwarded, DataKey::LastAward] {
+            common::bump_persistent(&env, &key);
+        }
+
+        token::Client::new(&env, &get_token(&env)?).transfer(
+            &env.current_contract_address(),
+            &winner,
+            &amount,
+        );
+
+        publish_jackpot_won_event(
+            &env,
+            JackpotWonEvent {
+                version: EVENT_SCHEMA_VERSION,
+                winner,
+                amount,
+                operation_hash,
+                timestamp: award.timestamp,
+            },
+        );
+
+        Ok(award)
+    }
+}
+
+fn jackpot_scope(env: &Env) -> Symbol {
+    Symbol::new(env, "jackpot")
+}
+
+fn get_token(env: &Env) -> Result<Address, ContractError> {
+    // Read by every contribution
+    common::bump_persistent(env, &DataKey::Token);
+    env.storage()
+        .persistent()
+        .get(&DataKey::Token)
+        .ok_or(ContractError::NotInitialized)
+}
+
+fn get_contribution_bps(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ContributionBps)
+        .unwrap_or(0)
+}
+
+fn is_contributor(env: &Env, contributor: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Contributor(contributor.clone()))
+}
+
+fn contribution_for(env: &Env, token: &Address, amount: i128) -> Result<i128, ContractError> {
+    if amount < 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    if *token != get_token(env)? {
+        return Ok(0);
+    }
+    amount
+        .checked_mul(get_contribution_bps(env) as i128)
+        .map(|scaled| scaled / MAX_CONTRIBUTION_BPS as i128)
+        .ok_or(ContractError::InvalidAmount)
+}
+
+fn get_pool(env: &Env) -> i128 {
+    env.storage().persistent().get(&DataKey::Pool).unwrap_or(0)
+}
+
+fn adjust_pool(env: &Env, delta: i128) -> Result<i128, ContractError> {
+    let pool = get_pool(env)
+        .checked_add(delta)
+        .ok_or(ContractError::InvalidAmount)?;
+    env.storage().persistent().set(&DataKey::Pool, &pool);
+    common::bump_persistent(env, &DataKey::Pool);
+    Ok(pool)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_contribution_event(env: &Env, from: &Address, amount: i128, pool: i128) {
+    env.events().publish(
+        (Symbol::new(env, "jackpot_contribution"), from.clone()),
+        (amount, pool),
+    );
+}
+
+fn publish_jackpot_won_event(env: &Env, event: JackpotWonEvent) {
+    publish_versioned_event(
+        env,
+        (Symbol::new(env, "jackpot_won"), event.winner.clone()),
+        JACKPOT_WON_EVENT,
+        event.winner.clone(),
+        event,
+    );
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,164 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{testutils::Address as _, token, Address, BytesN, Env};
+
+fn backend_key() -> SigningKey {
+    SigningKey::from_bytes(&[9u8; 32])
+}
+
+fn setup_test(
+    env: &Env,
+) -> (
+    JackpotContractClient<'_>,
+    Address,
+    Address,
+    token::Client<'_>,
+) {
+    env.mock_all_auths();
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let contributor = Address::generate(env);
+    token::StellarAssetClient::new(env, &token_id).mint(&contributor, &100_000);
+
+    let admin = Address::generate(env);
+    let contract_id = env.register(JackpotContract, ());
+    let client = JackpotContractClient::new(env, &contract_id);
+    let pubkey = BytesN::from_array(env, &backend_key().verifying_key().to_bytes());
+    // 2.5% of every reported amount
+    client.initialize(&admin, &token_id, &pubkey, &250);
+    client.set_contributor(&admin, &contributor, &true);
+
+    let token = token::Client::new(env, &token_id);
+
+    (client, admin, contributor, token)
+}
+
+fn sign_award(
+    env: &Env,
+    client: &JackpotContractClient,
+    operation_hash: &BytesN<32>,
+    winner: &Address,
+) -> BytesN<64> {
+    let message = (
+        client.address.clone(),
+        operation_hash.clone(),
+        winner.clone(),
+    )
+        .to_xdr(env);
+    let bytes: std::vec::Vec<u8> = message.iter().collect();
+    BytesN::from_array(env, &backend_key().sign(&bytes).to_bytes())
+}
+
+#[test]
+fn initialize_only_once() {
+    let env = Env::default();
+    let (client, admin, _contributor, token) = setup_test(&env);
+    let pubkey = BytesN::from_array(&env, &[0u8; 32]);
+
+    assert_eq!(
+        client.try_initialize(&admin, &token.address, &pubkey, &100),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn contributions_accumulate_the_configured_share() {
+    let env = Env::default();
+    let (client, admin, contributor, token) = setup_test(&env);
+
+    assert_eq!(client.quote_contribution(&token.address, &10_000), 250);
+    assert_eq!(
+        client.contribute(&contributor, &token.address, &10_000),
+        250
+    );
+    assert_eq!(client.contribute(&contributor, &token.address, &2_000), 50);
+    assert_eq!(client.get_pool(), 300);
+    assert_eq!(token.balance(&client.address), 300);
+
+    // Amounts in other tokens are not the jackpot's business
+    let other = Address::generate(&env);
+    assert_eq!(client.contribute(&contributor, &other, &10_000), 0);
+
+    assert_eq!(
+        client.try_contribute(&Address::generate(&env), &token.address, &10_000),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_set_contribution_bps(&admin, &10_001),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+}
+
+#[test]
+fn signed_award_pays_the_whole_pool_once() {
+    let env = Env::default();
+    let (client, _admin, contributor, token) = setup_test(&env);
+    let winner = Address::generate(&env);
+    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+
+    client.contribute(&contributor, &token.address, &40_000);
+    client.seed_pool(&contributor, &500);
+    assert_eq!(client.get_pool(), 1_500);
+
+    let signature = sign_award(&env, &client, &operation_hash, &winner);
+    let award = client.award_jackpot(&operation_hash, &winner, &signature);
+    assert_eq!(award.amount, 1_500);
+    assert_eq!(token.balance(&winner), 1_500);
+    assert_eq!(client.get_pool(), 0);
+    assert_eq!(client.get_total_awarded(), 1_500);
+    assert_eq!(client.get_last_award(), Some(award));
+    assert!(client.is_award_executed(&operation_hash));
+
+    client.seed_pool(&contributor, &100);
+    assert_eq!(
+        client.try_award_jackpot(&operation_hash, &winner, &signature),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+
+    let empty_hash = BytesN::from_array(&env, &[2u8; 32]);
+    client.award_jackpot(
+        &empty_hash,
+        &winner,
+        &sign_award(&env, &client, &empty_hash, &winner),
+    );
+    let next_hash = BytesN::from_array(&env, &[3u8; 32]);
+    assert_eq!(
+        client.try_award_jackpot(
+            &next_hash,
+            &winner,
+            &sign_award(&env, &client, &next_hash, &winner)
+        ),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+}
+
+#[test]
+#[should_panic]
+fn award_signed_for_another_winner_is_rejected() {
+    let env = Env::default();
+    let (client, _admin, contributor, _token) = setup_test(&env);
+    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+    client.seed_pool(&contributor, &500);
+
+    let signature = sign_award(&env, &client, &operation_hash, &Address::generate(&env));
+    client.award_jackpot(&operation_hash, &Address::generate(&env), &signature);
+}
+
+#[test]
+#[should_panic]
+fn award_signed_for_another_jackpot_is_rejected() {
+    let env = Env::default();
+    let (client, _admin, contributor, _token) = setup_test(&env);
+    let (other, ..) = setup_test(&env);
+    let winner = Address::generate(&env);
+    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+    client.seed_pool(&contributor, &500);
+
+    let signature = sign_award(&env, &other, &operation_hash, &winner);
+    client.award_jackpot(&operation_hash, &winner, &signature);
+}
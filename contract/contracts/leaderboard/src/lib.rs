@@ -0,0 +1,413 @@
+#![no_std]
+
+use common::{init_role, Role};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, Address, BytesN, Env, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum LeaderboardError {
+    Unauthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    InvalidAmount = 4,
+    InvalidStatus = 5,
+    SeasonNotFound = 6,
+}
+
+/// Upper bound on the number of entries a single `top_n` call returns.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SeasonStatus {
+    /// Reporters may still post results
+    Open,
+    /// No more results; rankings can still move until finalized
+    Closed,
+    /// Final rankings are frozen for prize contracts
+    Finalized,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Season {
+    pub id: u32,
+    pub status: SeasonStatus,
+    pub opened_at: u64,
+    pub closed_at: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PlayerScore {
+    pub wagered: i128,
+    pub won: i128,
+    /// `won - wagered`; players are ranked by this
+    pub net_pnl: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RankEntry {
+    /// 1-based position in the season
+    pub rank: u32,
+    pub player: Address,
+    pub score: PlayerScore,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    CurrentSeason,
+    Season(u32),
+    Score(u32, Address),
+    Players(u32),
+    Rankings(u32),
+    FinalRank(u32, Address),
+}
+
+#[contract]
+pub struct LeaderboardContract;
+
+#[contractimpl]
+impl LeaderboardContract {
+    pub fn initialize(env: Env, admin: Address) -> Result<(), LeaderboardError> {
+        if env.storage().persistent().has(&DataKey::CurrentSeason) {
+            return Err(LeaderboardError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::CurrentSeason, &0u32);
+        init_role(&env, Role::Admin, &admin);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), LeaderboardError> {
+        common::grant_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), LeaderboardError> {
+        common::revoke_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), LeaderboardError> {
+        common::renounce_role(&env, &account, role).map_err(role_error)
+    }
+
+    pub fn set_role_admin(
+        env: Env,
+        caller: Address,
+        role: Role,
+        admin_role: Role,
+    ) -> Result<(), LeaderboardError> {
+        common::set_role_admin(&env, &caller, role, admin_role).map_err(role_error)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), LeaderboardError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, LeaderboardError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn get_role_admin(env: Env, role: Role) -> Role {
+        common::get_role_admin(&env, role)
+    }
+
+    /// Start the next season. The previous one must be closed first.
+    pub fn open_season(env: Env, admin: Address) -> Result<u32, LeaderboardError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let current = current_season_id(&env)?;
+        if let Some(season) = get_season(&env, current) {
+            if season.status == SeasonStatus::Open {
+                return Err(LeaderboardError::InvalidStatus);
+            }
+        }
+
+        let id = current + 1;
+        let season = Season {
+            id,
+            status: SeasonStatus::Open,
+            opened_at: env.ledger().timestamp(),
+            closed_at: None,
+        };
+        let storage = env.storage().persistent();
+        storage.set(&DataKey::Season(id), &season);
+        storage.set(&DataKey::CurrentSeason, &id);
+
+        publish_season_event(&env, "season_opened", id);
+
+        Ok(id)
+    }
+
+    /// Stop accepting results for `season`.
+    pub fn close_season(env: Env, admin: Address, season: u32) -> Result<(), LeaderboardError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let mut record = get_season(&env, season).ok_or(LeaderboardError::SeasonNotFound)?;
+        if record.status != SeasonStatus::Open {
+            return Err(LeaderboardError::InvalidStatus);
+        }
+        record.status = SeasonStatus::Closed;
+        record.closed_at = Some(env.ledger().timestamp());
+        env.storage()
+            .persistent()
+            .set(&DataKey::Season(season), &record);
+
+        publish_season_event(&env, "season_closed", season);
+
+        Ok(())
+    }
+
+    /// Snapshot the final rankings of a closed season. Returns the number of
+    /// ranked players.
+    pub fn finalize_season(env: Env, admin: Address, season: u32) -> Result<u32, LeaderboardError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let mut record = get_season(&env, season).ok_or(LeaderboardError::SeasonNotFound)?;
+        if record.status != SeasonStatus::Closed {
+            return Err(LeaderboardError::InvalidStatus);
+        }
+
+        let rankings = rank_players(&env, season);
+        let storage = env.storage().persistent();
+        for entry in rankings.iter() {
+            storage.set(
+                &DataKey::FinalRank(season, entry.player.clone()),
+                &entry.rank,
+            );
+        }
+        storage.set(&DataKey::Rankings(season), &rankings);
+
+        record.status = SeasonStatus::Finalized;
+        storage.set(&DataKey::Season(season), &record);
+
+        publish_season_event(&env, "season_finalized", season);
+
+        Ok(rankings.len())
+    }
+
+    /// Add a settled result for `player` to the open season. Callers need
+    /// `Role::Operator`. Returns the player's updated season score.
+    pub fn record_result(
+        env: Env,
+        reporter: Address,
+        player: Address,
+        wagered: i128,
+        won: i128,
+    ) -> Result<PlayerScore, LeaderboardError> {
+        require_role(&env, Role::Operator, &reporter)?;
+        if wagered < 0 || won < 0 {
+            return Err(LeaderboardError::InvalidAmount);
+        }
+
+        let season = current_season_id(&env)?;
+        let record = get_season(&env, season).ok_or(LeaderboardError::SeasonNotFound)?;
+        if record.status != SeasonStatus::Open {
+            return Err(LeaderboardError::InvalidStatus);
+        }
+
+        let storage = env.storage().persistent();
+        let key = DataKey::Score(season, player.clone());
+        let mut score = match storage.get::<_, PlayerScore>(&key) {
+            Some(score) => score,
+            None => {
+                let mut players = get_players(&env, season);
+                players.push_back(player.clone());
+                storage.set(&DataKey::Players(season), &players);
+                PlayerScore::default()
+            }
+        };
+
+        score.wagered = score
+            .wagered
+            .checked_add(wagered)
+            .ok_or(LeaderboardError::InvalidAmount)?;
+        score.won = score
+            .won
+            .checked_add(won)
+            .ok_or(LeaderboardError::InvalidAmount)?;
+        score.net_pnl = score
+            .won
+            .checked_sub(score.wagered)
+            .ok_or(LeaderboardError::InvalidAmount)?;
+        storage.set(&key, &score);
+
+        publish_score_event(&env, season, &player, &score);
+
+        Ok(score)
+    }
+
+    /// Most recently opened season, or `None` before the first.
+    pub fn get_current_season(env: Env) -> Option<Season> {
+        get_season(&env, current_season_id(&env).ok()?)
+    }
+
+    pub fn get_season(env: Env, season: u32) -> Result<Season, LeaderboardError> {
+        get_season(&env, season).ok_or(LeaderboardError::SeasonNotFound)
+    }
+
+    pub fn get_score(env: Env, season: u32, player: Address) -> PlayerScore {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Score(season, player))
+            .unwrap_or_default()
+    }
+
+    pub fn get_player_count(env: Env, season: u32) -> u32 {
+        get_players(&env, season).len()
+    }
+
+    /// Page of the season's ranking ordered by net PnL, then volume wagered.
+    /// Finalized seasons are served from their snapshot; others are ranked
+    /// live. `limit` is capped at `MAX_PAGE_SIZE`.
+    pub fn top_n(
+        env: Env,
+        season: u32,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<RankEntry>, LeaderboardError> {
+        let record = get_season(&env, season).ok_or(LeaderboardError::SeasonNotFound)?;
+        let rankings = match record.status {
+            SeasonStatus::Finalized => env
+                .storage()
+                .persistent()
+                .get(&DataKey::Rankings(season))
+                .unwrap_or(Vec::new(&env)),
+            _ => rank_players(&env, season),
+        };
+
+        let mut page = Vec::new(&env);
+        let end = offset
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(rankings.len());
+        for index in offset..end {
+            page.push_back(rankings.get_unchecked(index));
+        }
+        Ok(page)
+    }
+
+    /// Final rank of `player` in a finalized season, for prize contracts.
+    pub fn get_final_rank(env: Env, season: u32, player: Address) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::FinalRank(season, player))
+    }
+}
+
+fn current_season_id(env: &Env) -> Result<u32, LeaderboardError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::CurrentSeason)
+        .ok_or(LeaderboardError::NotInitialized)
+}
+
+fn get_season(env: &Env, season: u32) -> Option<Season> {
+    env.storage().persistent().get(&DataKey::Season(season))
+}
+
+fn get_players(env: &Env, season: u32) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Players(season))
+        .unwrap_or(Vec::new(env))
+}
+
+fn ranks_above(a: &PlayerScore, b: &PlayerScore) -> bool {
+    a.net_pnl > b.net_pnl || (a.net_pnl == b.net_pnl && a.wagered > b.wagered)
+}
+
+/// Insertion sort of every player in the season. Ties keep the order in
+/// which players first appeared.
+fn rank_players(env: &Env, season: u32) -> Vec<RankEntry> {
+    let storage = env.storage().persistent();
+    let mut sorted: Vec<(Address, PlayerScore)> = Vec::new(env);
+    for player in get_players(env, season).iter() {
+        let score: PlayerScore = storage
+            .get(&DataKey::Score(season, player.clone()))
+            .unwrap_or_default();
+        let mut index = sorted.len();
+        while index > 0 && ranks_above(&score, &sorted.get_unchecked(index - 1).1) {
+            index -= 1;
+        }
+        sorted.insert(index, (player, score));
+    }
+
+    let mut rankings = Vec::new(env);
+    for (index, (player, score)) in sorted.iter().enumerate() {
+        rankings.push_back(RankEntry {
+            rank: index as u32 + 1,
+            player,
+            score,
+        });
+    }
+    rankings
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_season_event(env: &Env, topic: &str, season: u32) {
+    env.events()
+        .publish((Symbol::new(env, topic), season), env.ledger().timestamp());
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_score_event(env: &Env, season: u32, player: &Address, score: &PlayerScore) {
+    env.events().publish(
+        (Symbol::new(env, "score_updated"), season, player.clone()),
+        score.clone(),
+    );
+}
+
+fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), LeaderboardError> {
+    common::require_role(env, role, account).map_err(role_error)
+}
+
+fn role_error(_: common::ContractError) -> LeaderboardError {
+    LeaderboardError::Unauthorized
+}
+
+#[cfg(test)]
+mod test;
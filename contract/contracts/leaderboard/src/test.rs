@@ -0,0 +1,140 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{testutils::Address as _, Address, Env};
+
+fn setup_test(env: &Env) -> (LeaderboardContractClient<'_>, Address, Address) {
+    env.mock_all_auths();
+
+    let contract_id = env.register(LeaderboardContract, ());
+    let client = LeaderboardContractClient::new(env, &contract_id);
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+    let reporter = Address::generate(env);
+    client.grant_role(&admin, &Role::Operator, &reporter);
+
+    (client, admin, reporter)
+}
+
+#[test]
+fn seasons_open_and_close_in_turn() {
+    let env = Env::default();
+    let (client, admin, _reporter) = setup_test(&env);
+    assert_eq!(client.get_current_season(), None);
+    assert_eq!(
+        client.try_initialize(&admin),
+        Err(Ok(LeaderboardError::AlreadyInitialized))
+    );
+
+    assert_eq!(client.open_season(&admin), 1);
+    assert_eq!(
+        client.try_open_season(&admin),
+        Err(Ok(LeaderboardError::InvalidStatus))
+    );
+    client.close_season(&admin, &1);
+    assert_eq!(client.get_season(&1).status, SeasonStatus::Closed);
+    assert_eq!(client.open_season(&admin), 2);
+    assert_eq!(client.get_current_season().unwrap().id, 2);
+
+    assert_eq!(
+        client.try_close_season(&admin, &7),
+        Err(Ok(LeaderboardError::SeasonNotFound))
+    );
+}
+
+#[test]
+fn only_reporters_record_results_in_open_seasons() {
+    let env = Env::default();
+    let (client, admin, reporter) = setup_test(&env);
+    let player = Address::generate(&env);
+
+    assert_eq!(
+        client.try_record_result(&reporter, &player, &100, &0),
+        Err(Ok(LeaderboardError::SeasonNotFound))
+    );
+    client.open_season(&admin);
+    assert_eq!(
+        client.try_record_result(&Address::generate(&env), &player, &100, &0),
+        Err(Ok(LeaderboardError::Unauthorized))
+    );
+
+    client.record_result(&reporter, &player, &100, &0);
+    let score = client.record_result(&reporter, &player, &50, &300);
+    assert_eq!(
+        score,
+        PlayerScore {
+            wagered: 150,
+            won: 300,
+            net_pnl: 150,
+        }
+    );
+    assert_eq!(client.get_player_count(&1), 1);
+
+    client.close_season(&admin, &1);
+    assert_eq!(
+        client.try_record_result(&reporter, &player, &100, &0),
+        Err(Ok(LeaderboardError::InvalidStatus))
+    );
+}
+
+#[test]
+fn top_n_ranks_by_net_pnl_and_pages() {
+    let env = Env::default();
+    let (client, admin, reporter) = setup_test(&env);
+    client.open_season(&admin);
+
+    let loser = Address::generate(&env);
+    let winner = Address::generate(&env);
+    let big_even = Address::generate(&env);
+    let small_even = Address::generate(&env);
+    client.record_result(&reporter, &loser, &100, &0);
+    client.record_result(&reporter, &small_even, &10, &10);
+    client.record_result(&reporter, &winner, &100, &500);
+    client.record_result(&reporter, &big_even, &200, &200);
+
+    let top = client.top_n(&1, &0, &10);
+    assert_eq!(top.len(), 4);
+    assert_eq!(top.get(0).unwrap().player, winner);
+    // Equal PnL is broken by volume wagered
+    assert_eq!(top.get(1).unwrap().player, big_even);
+    assert_eq!(top.get(2).unwrap().player, small_even);
+    assert_eq!(top.get(3).unwrap().player, loser);
+    assert_eq!(top.get(3).unwrap().rank, 4);
+
+    let page = client.top_n(&1, &1, &2);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap().rank, 2);
+    assert_eq!(client.top_n(&1, &4, &2).len(), 0);
+}
+
+#[test]
+fn finalize_snapshots_the_closed_season() {
+    let env = Env::default();
+    let (client, admin, reporter) = setup_test(&env);
+    client.open_season(&admin);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    client.record_result(&reporter, &second, &100, &150);
+    client.record_result(&reporter, &first, &100, &400);
+
+    assert_eq!(
+        client.try_finalize_season(&admin, &1),
+        Err(Ok(LeaderboardError::InvalidStatus))
+    );
+    client.close_season(&admin, &1);
+    assert_eq!(client.finalize_season(&admin, &1), 2);
+    assert_eq!(
+        client.try_finalize_season(&admin, &1),
+        Err(Ok(LeaderboardError::InvalidStatus))
+    );
+
+    assert_eq!(client.get_final_rank(&1, &first), Some(1));
+    assert_eq!(client.get_final_rank(&1, &second), Some(2));
+    assert_eq!(client.get_final_rank(&1, &admin), None);
+
+    // A new season starts from scratch and leaves the snapshot untouched
+    client.open_season(&admin);
+    client.record_result(&reporter, &second, &0, &1_000);
+    assert_eq!(client.top_n(&1, &0, &1).get(0).unwrap().player, first);
+    assert_eq!(client.top_n(&2, &0, &1).get(0).unwrap().player, second);
+}
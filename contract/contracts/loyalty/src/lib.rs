@@ -0,0 +1,377 @@
+#![no_std]
+
+use common::{init_role, require_role, ContractError, Role};
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Symbol, Val, Vec};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+pub const BASIS_POINTS: u32 = 10_000;
+
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum LoyaltyTier {
+    None,
+    Bronze,
+    Silver,
+    Gold,
+}
+
+/// Reached once a user has earned `min_points` in total; grants
+/// `discount_bps` off platform fees.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TierRule {
+    pub min_points: i128,
+    pub discount_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoyaltyConfig {
+    /// Points earned per unit wagered, in basis points
+    pub points_bps: u32,
+    pub bronze: TierRule,
+    pub silver: TierRule,
+    pub gold: TierRule,
+}
+
+/// Points are held here only; there is no way to transfer them.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LoyaltyAccount {
+    /// Spendable points
+    pub balance: i128,
+    /// Every point ever earned; decides the tier, so redeeming never
+    /// demotes a user
+    pub lifetime: i128,
+    pub redeemed: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Redemption {
+    pub id: u64,
+    pub user: Address,
+    pub points: i128,
+    pub reward_id: u32,
+    pub redeemed_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Config,
+    Reporter(Address),
+    Account(Address),
+    RedemptionCount,
+}
+
+#[contract]
+pub struct LoyaltyContract;
+
+#[contractimpl]
+impl LoyaltyContract {
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        config: LoyaltyConfig,
+    ) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::Config) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        validate_config(&config)?;
+
+        storage.set(&DataKey::Config, &config);
+        init_role(&env, Role::Admin, &admin);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::grant_role(&env, &caller, role, &account)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::revoke_role(&env, &caller, role, &account)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), ContractError> {
+        common::renounce_role(&env, &account, role)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Replace the earn rate and tier rules. Tiers are recomputed from
+    /// lifetime points, so users move with the new thresholds immediately.
+    pub fn set_config(
+        env: Env,
+        admin: Address,
+        config: LoyaltyConfig,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        validate_config(&config)?;
+        env.storage().persistent().set(&DataKey::Config, &config);
+        Ok(())
+    }
+
+    pub fn get_config(env: Env) -> Result<LoyaltyConfig, ContractError> {
+        get_config(&env)
+    }
+
+    /// Allow a platform contract (betting, spin rewards) to report wagers.
+    pub fn set_reporter(
+        env: Env,
+        admin: Address,
+        reporter: Address,
+        allowed: bool,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let key = DataKey::Reporter(reporter);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_reporter(env: Env, reporter: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Reporter(reporter))
+    }
+
+    /// Hook for authorized reporters: credit `user` with points for a
+    /// wager of `amount`. Returns the points earned.
+    pub fn report_wager(
+        env: Env,
+        reporter: Address,
+        user: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        reporter.require_auth();
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Reporter(reporter.clone()))
+        {
+            return Err(ContractError::Unauthorized);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        let config = get_config(&env)?;
+        let points = amount
+            .checked_mul(config.points_bps as i128)
+            .map(|scaled| scaled / BASIS_POINTS as i128)
+            .ok_or(ContractError::InvalidAmount)?;
+        if points == 0 {
+            return Ok(0);
+        }
+
+        let mut account = get_account(&env, &user);
+        let previous_tier = tier_for(&config, account.lifetime);
+        account.balance = account
+            .balance
+            .checked_add(points)
+            .ok_or(ContractError::InvalidAmount)?;
+        account.lifetime = account
+            .lifetime
+            .checked_add(points)
+            .ok_or(ContractError::InvalidAmount)?;
+        set_account(&env, &user, &account);
+
+        publish_points_event(&env, &user, &reporter, points);
+        let tier = tier_for(&config, account.lifetime);
+        if tier != previous_tier {
+            publish_tier_event(&env, &user, tier);
+        }
+        Ok(points)
+    }
+
+    /// Burn `points` of `user`'s balance for catalogue reward `reward_id`.
+    /// Fulfilment happens off-chain from the `loyalty_redeemed` event.
+    /// Returns the redemption id.
+    pub fn redeem(
+        env: Env,
+        user: Address,
+        points: i128,
+        reward_id: u32,
+    ) -> Result<u64, ContractError> {
+        user.require_auth();
+        if points <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let mut account = get_account(&env, &user);
+        if account.balance < points {
+            return Err(ContractError::InsufficientBalance);
+        }
+        account.balance -= points;
+        account.redeemed += points;
+        set_account(&env, &user, &account);
+
+        let storage = env.storage().persistent();
+        let id = storage.get(&DataKey::RedemptionCount).unwrap_or(0u64) + 1;
+        storage.set(&DataKey::RedemptionCount, &id);
+
+        publish_redemption_event(
+            &env,
+            &Redemption {
+                id,
+                user,
+                points,
+                reward_id,
+                redeemed_at: env.ledger().timestamp(),
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn get_account(env: Env, user: Address) -> LoyaltyAccount {
+        get_account(&env, &user)
+    }
+
+    pub fn get_points(env: Env, user: Address) -> i128 {
+        get_account(&env, &user).balance
+    }
+
+    pub fn get_tier(env: Env, user: Address) -> Result<LoyaltyTier, ContractError> {
+        Ok(tier_for(
+            &get_config(&env)?,
+            get_account(&env, &user).lifetime,
+        ))
+    }
+
+    /// Fee discount `user`'s tier earns, for other contracts to apply.
+    pub fn get_discount_bps(env: Env, user: Address) -> Result<u32, ContractError> {
+        let config = get_config(&env)?;
+        Ok(match tier_for(&config, get_account(&env, &user).lifetime) {
+            LoyaltyTier::None => 0,
+            LoyaltyTier::Bronze => config.bronze.discount_bps,
+            LoyaltyTier::Silver => config.silver.discount_bps,
+            LoyaltyTier::Gold => config.gold.discount_bps,
+        })
+    }
+
+    pub fn get_redemption_count(env: Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RedemptionCount)
+            .unwrap_or(0)
+    }
+}
+
+/// Thresholds must rise from Bronze to Gold, and so must discounts.
+fn validate_config(config: &LoyaltyConfig) -> Result<(), ContractError> {
+    let rules = [&config.bronze, &config.silver, &config.gold];
+    if config.bronze.min_points <= 0 || config.gold.discount_bps > BASIS_POINTS {
+        return Err(ContractError::InvalidConfig);
+    }
+    for pair in rules.windows(2) {
+        if pair[1].min_points <= pair[0].min_points || pair[1].discount_bps < pair[0].discount_bps {
+            return Err(ContractError::InvalidConfig);
+        }
+    }
+    Ok(())
+}
+
+fn tier_for(config: &LoyaltyConfig, lifetime: i128) -> LoyaltyTier {
+    if lifetime >= config.gold.min_points {
+        LoyaltyTier::Gold
+    } else if lifetime >= config.silver.min_points {
+        LoyaltyTier::Silver
+    } else if lifetime >= config.bronze.min_points {
+        LoyaltyTier::Bronze
+    } else {
+        LoyaltyTier::None
+    }
+}
+
+fn get_config(env: &Env) -> Result<LoyaltyConfig, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Config)
+        .ok_or(ContractError::NotInitialized)
+}
+
+fn get_account(env: &Env, user: &Address) -> LoyaltyAccount {
+    let key = DataKey::Account(user.clone());
+    common::bump_persistent(env, &key);
+    env.storage().persistent().get(&key).unwrap_or_default()
+}
+
+fn set_account(env: &Env, user: &Address, account: &LoyaltyAccount) {
+    let key = DataKey::Account(user.clone());
+    env.storage().persistent().set(&key, account);
+    common::bump_persistent(env, &key);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_points_event(env: &Env, user: &Address, reporter: &Address, points: i128) {
+    env.events().publish(
+        (Symbol::new(env, "loyalty_earned"), user.clone()),
+        (reporter.clone(), points),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_tier_event(env: &Env, user: &Address, tier: LoyaltyTier) {
+    env.events()
+        .publish((Symbol::new(env, "loyalty_tier"), user.clone()), tier);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_redemption_event(env: &Env, redemption: &Redemption) {
+    env.events().publish(
+        (
+            Symbol::new(env, "loyalty_redeemed"),
+            redemption.user.clone(),
+            redemption.reward_id,
+        ),
+        redemption.clone(),
+    );
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,111 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{testutils::Address as _, Address, Env};
+
+fn config() -> LoyaltyConfig {
+    LoyaltyConfig {
+        points_bps: 1_000,
+        bronze: TierRule {
+            min_points: 100,
+            discount_bps: 500,
+        },
+        silver: TierRule {
+            min_points: 500,
+            discount_bps: 1_000,
+        },
+        gold: TierRule {
+            min_points: 2_000,
+            discount_bps: 2_500,
+        },
+    }
+}
+
+fn setup(env: &Env) -> (LoyaltyContractClient<'_>, Address, Address) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let contract_id = env.register(LoyaltyContract, ());
+    let client = LoyaltyContractClient::new(env, &contract_id);
+    client.initialize(&admin, &config());
+    let reporter = Address::generate(env);
+    client.set_reporter(&admin, &reporter, &true);
+    (client, admin, reporter)
+}
+
+#[test]
+fn wagers_earn_points_and_tiers() {
+    let env = Env::default();
+    let (client, _, reporter) = setup(&env);
+    let user = Address::generate(&env);
+
+    assert_eq!(client.get_tier(&user), LoyaltyTier::None);
+    assert_eq!(client.get_discount_bps(&user), 0);
+    assert_eq!(
+        client.try_report_wager(&user, &user, &1_000),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    // 10% of the wagered amount
+    assert_eq!(client.report_wager(&reporter, &user, &1_000), 100);
+    assert_eq!(client.get_tier(&user), LoyaltyTier::Bronze);
+    assert_eq!(client.get_discount_bps(&user), 500);
+
+    client.report_wager(&reporter, &user, &4_000);
+    assert_eq!(client.get_points(&user), 500);
+    assert_eq!(client.get_tier(&user), LoyaltyTier::Silver);
+
+    client.report_wager(&reporter, &user, &15_000);
+    assert_eq!(client.get_tier(&user), LoyaltyTier::Gold);
+    assert_eq!(client.get_discount_bps(&user), 2_500);
+}
+
+#[test]
+fn redeeming_burns_points_but_keeps_the_tier() {
+    let env = Env::default();
+    let (client, _, reporter) = setup(&env);
+    let user = Address::generate(&env);
+
+    client.report_wager(&reporter, &user, &6_000);
+    assert_eq!(
+        client.try_redeem(&user, &601, &7),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+    assert_eq!(client.redeem(&user, &400, &7), 1);
+    assert_eq!(client.redeem(&user, &100, &8), 2);
+
+    let account = client.get_account(&user);
+    assert_eq!(account.balance, 100);
+    assert_eq!(account.redeemed, 500);
+    assert_eq!(account.lifetime, 600);
+    assert_eq!(client.get_tier(&user), LoyaltyTier::Silver);
+    assert_eq!(client.get_redemption_count(), 2);
+}
+
+#[test]
+fn tier_rules_must_escalate() {
+    let env = Env::default();
+    let (client, admin, _) = setup(&env);
+
+    let mut unordered = config();
+    unordered.silver.min_points = 50;
+    assert_eq!(
+        client.try_set_config(&admin, &unordered),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    let mut shrinking = config();
+    shrinking.gold.discount_bps = 100;
+    assert_eq!(
+        client.try_set_config(&admin, &shrinking),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+
+    let mut stranger_config = config();
+    stranger_config.points_bps = 2_000;
+    assert_eq!(
+        client.try_set_config(&Address::generate(&env), &stranger_config),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.set_config(&admin, &stranger_config);
+    assert_eq!(client.get_config().points_bps, 2_000);
+}
@@ -0,0 +1,248 @@
+#![no_std]
+
+use common::{ContractError, MatchInfo, MatchStatus};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, Address, BytesN, Env, Map, String, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Admin,
+    Match(BytesN<32>),
+}
+
+#[contract]
+pub struct MatchRegistryContract;
+
+#[contractimpl]
+impl MatchRegistryContract {
+    /// Initialize the registry with the backend address allowed to manage matches
+    pub fn initialize(env: Env, admin: Address) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+
+        if storage.has(&DataKey::Admin) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+
+        storage.set(&DataKey::Admin, &admin);
+        Ok(())
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), ContractError> {
+        Self::require_admin_auth(&env)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env) -> Result<Option<u32>, ContractError> {
+        Self::require_admin_auth(&env)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Register a new match; it is open for betting until `close_time`
+    pub fn create_match(
+        env: Env,
+        match_id: BytesN<32>,
+        start_time: u64,
+        close_time: u64,
+        metadata: Map<Symbol, String>,
+    ) -> Result<MatchInfo, ContractError> {
+        Self::require_admin_auth(&env)?;
+
+        let key = DataKey::Match(match_id.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(ContractError::MatchAlreadyExists);
+        }
+
+        let now = env.ledger().timestamp();
+        if close_time <= now || close_time > start_time {
+            return Err(ContractError::InvalidConfig);
+        }
+
+        let info = MatchInfo {
+            match_id: match_id.clone(),
+            metadata,
+            start_time,
+            close_time,
+            status: MatchStatus::Open,
+            outcome: None,
+            created_at: now,
+            updated_at: now,
+        };
+        env.storage().persistent().set(&key, &info);
+
+        publish_match_created_event(&env, &info);
+
+        Ok(info)
+    }
+
+    /// Replace the descriptive metadata of a match that is not yet final
+    pub fn update_metadata(
+        env: Env,
+        match_id: BytesN<32>,
+        metadata: Map<Symbol, String>,
+    ) -> Result<MatchInfo, ContractError> {
+        Self::require_admin_auth(&env)?;
+
+        let mut info = get_match(&env, &match_id)?;
+        if is_final(info.status) {
+            return Err(ContractError::InvalidStatus);
+        }
+
+        info.metadata = metadata;
+        info.updated_at = env.ledger().timestamp();
+        store_match(&env, &info);
+
+        Ok(info)
+    }
+
+    /// Stop accepting bets (e.g. at kickoff) while the result is pending
+    pub fn lock_match(env: Env, match_id: BytesN<32>) -> Result<MatchInfo, ContractError> {
+        Self::require_admin_auth(&env)?;
+        transition(&env, &match_id, MatchStatus::Locked, None)
+    }
+
+    /// Record the final outcome. This can only happen once per match.
+    pub fn report_outcome(
+        env: Env,
+        match_id: BytesN<32>,
+        outcome: Symbol,
+    ) -> Result<MatchInfo, ContractError> {
+        Self::require_admin_auth(&env)?;
+        transition(&env, &match_id, MatchStatus::Settled, Some(outcome))
+    }
+
+    /// Void a match so that every bet on it is refunded
+    pub fn cancel_match(env: Env, match_id: BytesN<32>) -> Result<MatchInfo, ContractError> {
+        Self::require_admin_auth(&env)?;
+        transition(&env, &match_id, MatchStatus::Cancelled, None)
+    }
+
+    /// Match record with the status as seen at the current ledger time
+    pub fn get_match(env: Env, match_id: BytesN<32>) -> Result<MatchInfo, ContractError> {
+        let mut info = get_match(&env, &match_id)?;
+        info.status = effective_status(&env, &info);
+        Ok(info)
+    }
+
+    /// An open match reports `Locked` once its close time has passed, even
+    /// before the backend calls `lock_match`.
+    pub fn get_match_status(env: Env, match_id: BytesN<32>) -> Result<MatchStatus, ContractError> {
+        Ok(effective_status(&env, &get_match(&env, &match_id)?))
+    }
+
+    /// Reported outcome of a settled match
+    pub fn get_outcome(env: Env, match_id: BytesN<32>) -> Result<Symbol, ContractError> {
+        get_match(&env, &match_id)?
+            .outcome
+            .ok_or(ContractError::InvalidStatus)
+    }
+
+    pub fn match_exists(env: Env, match_id: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::Match(match_id))
+    }
+
+    fn require_admin_auth(env: &Env) -> Result<(), ContractError> {
+        let admin: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .ok_or(ContractError::NotInitialized)?;
+        admin.require_auth();
+        Ok(())
+    }
+}
+
+fn is_final(status: MatchStatus) -> bool {
+    matches!(status, MatchStatus::Settled | MatchStatus::Cancelled)
+}
+
+fn effective_status(env: &Env, info: &MatchInfo) -> MatchStatus {
+    if info.status == MatchStatus::Open && env.ledger().timestamp() >= info.close_time {
+        return MatchStatus::Locked;
+    }
+    info.status
+}
+
+fn is_allowed_transition(from: MatchStatus, to: MatchStatus) -> bool {
+    matches!(
+        (from, to),
+        (MatchStatus::Open, MatchStatus::Locked)
+            | (MatchStatus::Open, MatchStatus::Settled)
+            | (MatchStatus::Open, MatchStatus::Cancelled)
+            | (MatchStatus::Locked, MatchStatus::Settled)
+            | (MatchStatus::Locked, MatchStatus::Cancelled)
+    )
+}
+
+fn transition(
+    env: &Env,
+    match_id: &BytesN<32>,
+    to: MatchStatus,
+    outcome: Option<Symbol>,
+) -> Result<MatchInfo, ContractError> {
+    let mut info = get_match(env, match_id)?;
+    if !is_allowed_transition(info.status, to) {
+        return Err(ContractError::InvalidStatus);
+    }
+
+    let from = info.status;
+    info.status = to;
+    if outcome.is_some() {
+        info.outcome = outcome;
+    }
+    info.updated_at = env.ledger().timestamp();
+    store_match(env, &info);
+
+    publish_status_changed_event(env, &info, from);
+
+    Ok(info)
+}
+
+fn get_match(env: &Env, match_id: &BytesN<32>) -> Result<MatchInfo, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Match(match_id.clone()))
+        .ok_or(ContractError::MatchNotFound)
+}
+
+fn store_match(env: &Env, info: &MatchInfo) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Match(info.match_id.clone()), info);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_match_created_event(env: &Env, info: &MatchInfo) {
+    env.events().publish(
+        (Symbol::new(env, "match_created"), info.match_id.clone()),
+        info.metadata.clone(),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_status_changed_event(env: &Env, info: &MatchInfo, from: MatchStatus) {
+    env.events().publish(
+        (Symbol::new(env, "match_status"), info.match_id.clone()),
+        (from, info.status, info.outcome.clone()),
+    );
+}
+
+#[cfg(test)]
+mod test;
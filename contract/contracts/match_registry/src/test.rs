@@ -0,0 +1,150 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Ledger},
+    Address, BytesN, Env, Map, String,
+};
+
+fn setup(env: &Env) -> (MatchRegistryContractClient<'_>, Address) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let contract_id = env.register(MatchRegistryContract, ());
+    let client = MatchRegistryContractClient::new(env, &contract_id);
+    client.initialize(&admin);
+
+    (client, admin)
+}
+
+fn metadata(env: &Env) -> Map<Symbol, String> {
+    let mut metadata = Map::new(env);
+    metadata.set(symbol_short!("home"), String::from_str(env, "Lions"));
+    metadata.set(symbol_short!("away"), String::from_str(env, "Tigers"));
+    metadata
+}
+
+#[test]
+fn initialize_only_once() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    assert_eq!(
+        client.try_initialize(&admin),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn create_match_starts_open() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+
+    let info = client.create_match(&match_id, &1_000, &900, &metadata(&env));
+    assert_eq!(info.status, MatchStatus::Open);
+    assert_eq!(info.outcome, None);
+    assert!(client.match_exists(&match_id));
+    assert_eq!(client.get_match_status(&match_id), MatchStatus::Open);
+
+    assert_eq!(
+        client.try_create_match(&match_id, &1_000, &900, &metadata(&env)),
+        Err(Ok(ContractError::MatchAlreadyExists))
+    );
+    assert_eq!(
+        client.try_get_match_status(&BytesN::from_array(&env, &[2u8; 32])),
+        Err(Ok(ContractError::MatchNotFound))
+    );
+}
+
+#[test]
+fn outcome_is_reported_once() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    client.create_match(&match_id, &1_000, &900, &metadata(&env));
+
+    assert_eq!(
+        client.try_get_outcome(&match_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    client.lock_match(&match_id);
+    assert_eq!(client.get_match_status(&match_id), MatchStatus::Locked);
+
+    client.report_outcome(&match_id, &symbol_short!("home"));
+    assert_eq!(client.get_match_status(&match_id), MatchStatus::Settled);
+    assert_eq!(client.get_outcome(&match_id), symbol_short!("home"));
+
+    assert_eq!(
+        client.try_report_outcome(&match_id, &symbol_short!("away")),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_cancel_match(&match_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+}
+
+#[test]
+fn cancelled_matches_are_final() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    client.create_match(&match_id, &1_000, &900, &metadata(&env));
+
+    client.cancel_match(&match_id);
+    assert_eq!(client.get_match_status(&match_id), MatchStatus::Cancelled);
+
+    assert_eq!(
+        client.try_lock_match(&match_id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_update_metadata(&match_id, &Map::new(&env)),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+}
+
+#[test]
+fn create_match_validates_schedule() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    env.ledger().with_mut(|li| li.timestamp = 500);
+
+    // Close time already passed
+    assert_eq!(
+        client.try_create_match(&match_id, &1_000, &500, &metadata(&env)),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    // Betting closes after kickoff
+    assert_eq!(
+        client.try_create_match(&match_id, &1_000, &1_001, &metadata(&env)),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+
+    let info = client.create_match(&match_id, &1_000, &900, &metadata(&env));
+    assert_eq!(info.start_time, 1_000);
+    assert_eq!(info.close_time, 900);
+}
+
+#[test]
+fn open_match_reads_locked_after_close_time() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let match_id = BytesN::from_array(&env, &[1u8; 32]);
+    client.create_match(&match_id, &1_000, &900, &metadata(&env));
+
+    env.ledger().with_mut(|li| li.timestamp = 899);
+    assert_eq!(client.get_match_status(&match_id), MatchStatus::Open);
+
+    env.ledger().with_mut(|li| li.timestamp = 900);
+    assert_eq!(client.get_match_status(&match_id), MatchStatus::Locked);
+    assert_eq!(client.get_match(&match_id).status, MatchStatus::Locked);
+
+    // The result can still be reported once the match is underway
+    client.report_outcome(&match_id, &symbol_short!("home"));
+    assert_eq!(client.get_match_status(&match_id), MatchStatus::Settled);
+}
@@ -0,0 +1,369 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, Address, BytesN, Env, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum MultisigError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotOwner = 3,
+    InvalidThreshold = 4,
+    DuplicateOwner = 5,
+    ProposalNotFound = 6,
+    AlreadyConfirmed = 7,
+    NotConfirmed = 8,
+    ThresholdNotMet = 9,
+    ProposalExpired = 10,
+    ProposalClosed = 11,
+    InvalidExpiry = 12,
+}
+
+/// What a proposal does once enough owners have confirmed it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProposalAction {
+    /// Invoke `function` on `contract` with `args`, authorized as this wallet.
+    Call(Address, Symbol, Vec<Val>),
+    AddOwner(Address),
+    RemoveOwner(Address),
+    SetThreshold(u32),
+    /// Replace this wallet's own code with the uploaded Wasm
+    Upgrade(BytesN<32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Address,
+    pub action: ProposalAction,
+    pub confirmations: Vec<Address>,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub executed: bool,
+    pub cancelled: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Owners,
+    Threshold,
+    ProposalCount,
+    Proposal(u64),
+}
+
+#[contract]
+pub struct MultisigContract;
+
+#[contractimpl]
+impl MultisigContract {
+    pub fn initialize(env: Env, owners: Vec<Address>, threshold: u32) -> Result<(), MultisigError> {
+        let storage = env.storage().persistent();
+
+        if storage.has(&DataKey::Owners) {
+            return Err(MultisigError::AlreadyInitialized);
+        }
+        validate_owners(&owners)?;
+        validate_threshold(threshold, owners.len())?;
+
+        storage.set(&DataKey::Owners, &owners);
+        storage.set(&DataKey::Threshold, &threshold);
+        Ok(())
+    }
+
+    /// Open a proposal that stays confirmable until `expires_at`. The
+    /// proposer's confirmation is recorded immediately.
+    pub fn propose(
+        env: Env,
+        proposer: Address,
+        action: ProposalAction,
+        expires_at: u64,
+    ) -> Result<u64, MultisigError> {
+        proposer.require_auth();
+        require_owner(&env, &proposer)?;
+
+        let now = env.ledger().timestamp();
+        if expires_at <= now {
+            return Err(MultisigError::InvalidExpiry);
+        }
+
+        let storage = env.storage().persistent();
+        let id: u64 = storage.get(&DataKey::ProposalCount).unwrap_or(0);
+
+        let mut confirmations = Vec::new(&env);
+        confirmations.push_back(proposer.clone());
+
+        let proposal = Proposal {
+            id,
+            proposer: proposer.clone(),
+            action,
+            confirmations,
+            created_at: now,
+            expires_at,
+            executed: false,
+            cancelled: false,
+        };
+
+        store_proposal(&env, &proposal);
+        storage.set(&DataKey::ProposalCount, &(id + 1));
+        common::bump_persistent(&env, &DataKey::ProposalCount);
+
+        publish_proposal_event(&env, "proposal_created", id, &proposer);
+
+        Ok(id)
+    }
+
+    pub fn confirm(env: Env, owner: Address, proposal_id: u64) -> Result<u32, MultisigError> {
+        owner.require_auth();
+        require_owner(&env, &owner)?;
+
+        let mut proposal = get_open_proposal(&env, proposal_id)?;
+        if proposal.confirmations.contains(&owner) {
+            return Err(MultisigError::AlreadyConfirmed);
+        }
+
+        proposal.confirmations.push_back(owner.clone());
+        store_proposal(&env, &proposal);
+
+        publish_proposal_event(&env, "proposal_confirmed", proposal_id, &owner);
+
+        Ok(proposal.confirmations.len())
+    }
+
+    pub fn revoke_confirmation(
+        env: Env,
+        owner: Address,
+        proposal_id: u64,
+    ) -> Result<u32, MultisigError> {
+        owner.require_auth();
+        require_owner(&env, &owner)?;
+
+        let mut proposal = get_open_proposal(&env, proposal_id)?;
+        let index = proposal
+            .confirmations
+            .first_index_of(&owner)
+            .ok_or(MultisigError::NotConfirmed)?;
+
+        proposal.confirmations.remove(index);
+        store_proposal(&env, &proposal);
+
+        publish_proposal_event(&env, "proposal_revoked", proposal_id, &owner);
+
+        Ok(proposal.confirmations.len())
+    }
+
+    /// Withdraw a proposal. Only its proposer may cancel it.
+    pub fn cancel(env: Env, proposer: Address, proposal_id: u64) -> Result<(), MultisigError> {
+        proposer.require_auth();
+
+        let mut proposal = get_proposal(&env, proposal_id)?;
+        if proposal.proposer != proposer {
+            return Err(MultisigError::NotOwner);
+        }
+        if proposal.executed || proposal.cancelled {
+            return Err(MultisigError::ProposalClosed);
+        }
+
+        proposal.cancelled = true;
+        store_proposal(&env, &proposal);
+
+        publish_proposal_event(&env, "proposal_cancelled", proposal_id, &proposer);
+        Ok(())
+    }
+
+    /// Run a proposal once it has reached the threshold. Confirmations from
+    /// addresses that are no longer owners do not count.
+    pub fn execute(env: Env, executor: Address, proposal_id: u64) -> Result<Val, MultisigError> {
+        executor.require_auth();
+        require_owner(&env, &executor)?;
+
+        let mut proposal = get_open_proposal(&env, proposal_id)?;
+
+        let owners = get_owners(&env)?;
+        let confirmed = proposal
+            .confirmations
+            .iter()
+            .filter(|addr| owners.contains(addr))
+            .count() as u32;
+        if confirmed < get_threshold(&env)? {
+            return Err(MultisigError::ThresholdNotMet);
+        }
+
+        // Close the proposal before acting so it can never run twice.
+        proposal.executed = true;
+        store_proposal(&env, &proposal);
+
+        let result = match proposal.action.clone() {
+            ProposalAction::Call(contract, function, args) => {
+                env.invoke_contract::<Val>(&contract, &function, args)
+            }
+            ProposalAction::AddOwner(owner) => {
+                let mut owners = owners;
+                if owners.contains(&owner) {
+                    return Err(MultisigError::DuplicateOwner);
+                }
+                owners.push_back(owner);
+                env.storage().persistent().set(&DataKey::Owners, &owners);
+                common::bump_persistent(&env, &DataKey::Owners);
+                Val::VOID.to_val()
+            }
+            ProposalAction::RemoveOwner(owner) => {
+                let mut owners = owners;
+                let index = owners
+                    .first_index_of(&owner)
+                    .ok_or(MultisigError::NotOwner)?;
+                owners.remove(index);
+                validate_threshold(get_threshold(&env)?, owners.len())?;
+                env.storage().persistent().set(&DataKey::Owners, &owners);
+                common::bump_persistent(&env, &DataKey::Owners);
+                Val::VOID.to_val()
+            }
+            ProposalAction::SetThreshold(threshold) => {
+                validate_threshold(threshold, owners.len())?;
+                env.storage()
+                    .persistent()
+                    .set(&DataKey::Threshold, &threshold);
+                common::bump_persistent(&env, &DataKey::Threshold);
+                Val::VOID.to_val()
+            }
+            ProposalAction::Upgrade(new_wasm_hash) => {
+                common::upgrade_contract(&env, new_wasm_hash);
+                Val::VOID.to_val()
+            }
+        };
+
+        publish_proposal_event(&env, "proposal_executed", proposal_id, &executor);
+
+        Ok(result)
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION` after an `Upgrade` proposal
+    /// ran. Any owner may call it; returns the version migrated from, or
+    /// `None` if storage was already current.
+    pub fn migrate(env: Env, owner: Address) -> Result<Option<u32>, MultisigError> {
+        owner.require_auth();
+        require_owner(&env, &owner)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn get_owners(env: Env) -> Result<Vec<Address>, MultisigError> {
+        get_owners(&env)
+    }
+
+    pub fn get_threshold(env: Env) -> Result<u32, MultisigError> {
+        get_threshold(&env)
+    }
+
+    pub fn is_owner(env: Env, address: Address) -> bool {
+        get_owners(&env)
+            .map(|owners| owners.contains(&address))
+            .unwrap_or(false)
+    }
+
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Result<Proposal, MultisigError> {
+        get_proposal(&env, proposal_id)
+    }
+
+    pub fn get_proposal_count(env: Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ProposalCount)
+            .unwrap_or(0)
+    }
+
+    pub fn is_confirmed_by(env: Env, proposal_id: u64, owner: Address) -> bool {
+        get_proposal(&env, proposal_id)
+            .map(|proposal| proposal.confirmations.contains(&owner))
+            .unwrap_or(false)
+    }
+}
+
+fn validate_owners(owners: &Vec<Address>) -> Result<(), MultisigError> {
+    for (index, owner) in owners.iter().enumerate() {
+        if owners.first_index_of(&owner) != Some(index as u32) {
+            return Err(MultisigError::DuplicateOwner);
+        }
+    }
+    Ok(())
+}
+
+fn validate_threshold(threshold: u32, owner_count: u32) -> Result<(), MultisigError> {
+    if threshold == 0 || threshold > owner_count {
+        return Err(MultisigError::InvalidThreshold);
+    }
+    Ok(())
+}
+
+fn get_owners(env: &Env) -> Result<Vec<Address>, MultisigError> {
+    // Read by every proposal, confirmation and execution
+    common::bump_persistent(env, &DataKey::Owners);
+    env.storage()
+        .persistent()
+        .get(&DataKey::Owners)
+        .ok_or(MultisigError::NotInitialized)
+}
+
+fn get_threshold(env: &Env) -> Result<u32, MultisigError> {
+    common::bump_persistent(env, &DataKey::Threshold);
+    env.storage()
+        .persistent()
+        .get(&DataKey::Threshold)
+        .ok_or(MultisigError::NotInitialized)
+}
+
+fn require_owner(env: &Env, address: &Address) -> Result<(), MultisigError> {
+    if !get_owners(env)?.contains(address) {
+        return Err(MultisigError::NotOwner);
+    }
+    Ok(())
+}
+
+fn get_proposal(env: &Env, proposal_id: u64) -> Result<Proposal, MultisigError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Proposal(proposal_id))
+        .ok_or(MultisigError::ProposalNotFound)
+}
+
+fn get_open_proposal(env: &Env, proposal_id: u64) -> Result<Proposal, MultisigError> {
+    let proposal = get_proposal(env, proposal_id)?;
+    if proposal.executed || proposal.cancelled {
+        return Err(MultisigError::ProposalClosed);
+    }
+    if env.ledger().timestamp() > proposal.expires_at {
+        return Err(MultisigError::ProposalExpired);
+    }
+    Ok(proposal)
+}
+
+fn store_proposal(env: &Env, proposal: &Proposal) {
+    let key = DataKey::Proposal(proposal.id);
+    env.storage().persistent().set(&key, proposal);
+    common::bump_persistent(env, &key);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_proposal_event(env: &Env, topic: &str, proposal_id: u64, actor: &Address) {
+    env.events()
+        .publish((Symbol::new(env, topic), proposal_id), actor.clone());
+}
+
+#[cfg(test)]
+mod test;
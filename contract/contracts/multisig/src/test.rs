@@ -0,0 +1,213 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, IntoVal,
+};
+
+/// Minimal admin-gated contract standing in for a platform contract whose
+/// admin role is held by the multisig.
+#[contract]
+pub struct GuardedContract;
+
+#[contractimpl]
+impl GuardedContract {
+    pub fn set_value(env: Env, admin: Address, value: u32) -> u32 {
+        admin.require_auth();
+        env.storage()
+            .instance()
+            .set(&symbol_short!("value"), &value);
+        value
+    }
+
+    pub fn value(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("value"))
+            .unwrap_or(0)
+    }
+}
+
+fn setup(env: &Env) -> (MultisigContractClient<'_>, Vec<Address>) {
+    env.mock_all_auths();
+
+    let owners = vec![
+        env,
+        Address::generate(env),
+        Address::generate(env),
+        Address::generate(env),
+    ];
+    let contract_id = env.register(MultisigContract, ());
+    let client = MultisigContractClient::new(env, &contract_id);
+    client.initialize(&owners, &2);
+
+    (client, owners)
+}
+
+#[test]
+fn rejects_invalid_initialization() {
+    let env = Env::default();
+    let owner = Address::generate(&env);
+    let contract_id = env.register(MultisigContract, ());
+    let client = MultisigContractClient::new(&env, &contract_id);
+
+    assert_eq!(
+        client.try_initialize(&vec![&env, owner.clone()], &2),
+        Err(Ok(MultisigError::InvalidThreshold))
+    );
+    assert_eq!(
+        client.try_initialize(&vec![&env, owner.clone(), owner.clone()], &1),
+        Err(Ok(MultisigError::DuplicateOwner))
+    );
+
+    client.initialize(&vec![&env, owner.clone()], &1);
+    assert_eq!(
+        client.try_initialize(&vec![&env, owner], &1),
+        Err(Ok(MultisigError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn executes_cross_contract_call_after_threshold() {
+    let env = Env::default();
+    let (client, owners) = setup(&env);
+
+    let target = env.register(GuardedContract, ());
+    let target_client = GuardedContractClient::new(&env, &target);
+
+    let args: Vec<Val> = vec![&env, client.address.into_val(&env), 42u32.into_val(&env)];
+    let action = ProposalAction::Call(target.clone(), Symbol::new(&env, "set_value"), args);
+    let id = client.propose(&owners.get(0).unwrap(), &action, &1_000);
+
+    assert_eq!(
+        client.try_execute(&owners.get(0).unwrap(), &id).err(),
+        Some(Ok(MultisigError::ThresholdNotMet))
+    );
+
+    assert_eq!(client.confirm(&owners.get(1).unwrap(), &id), 2);
+    client.execute(&owners.get(2).unwrap(), &id);
+
+    assert_eq!(target_client.value(), 42);
+    assert!(client.get_proposal(&id).executed);
+    assert_eq!(
+        client.try_execute(&owners.get(0).unwrap(), &id).err(),
+        Some(Ok(MultisigError::ProposalClosed))
+    );
+}
+
+#[test]
+fn rejects_non_owners_and_double_confirmation() {
+    let env = Env::default();
+    let (client, owners) = setup(&env);
+    let outsider = Address::generate(&env);
+
+    let action = ProposalAction::SetThreshold(3);
+    assert_eq!(
+        client.try_propose(&outsider, &action, &1_000),
+        Err(Ok(MultisigError::NotOwner))
+    );
+
+    let id = client.propose(&owners.get(0).unwrap(), &action, &1_000);
+    assert_eq!(
+        client.try_confirm(&owners.get(0).unwrap(), &id),
+        Err(Ok(MultisigError::AlreadyConfirmed))
+    );
+    assert_eq!(
+        client.try_confirm(&outsider, &id),
+        Err(Ok(MultisigError::NotOwner))
+    );
+}
+
+#[test]
+fn expired_proposals_cannot_be_confirmed_or_executed() {
+    let env = Env::default();
+    let (client, owners) = setup(&env);
+
+    let id = client.propose(
+        &owners.get(0).unwrap(),
+        &ProposalAction::SetThreshold(1),
+        &100,
+    );
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = 101;
+    });
+
+    assert_eq!(
+        client.try_confirm(&owners.get(1).unwrap(), &id),
+        Err(Ok(MultisigError::ProposalExpired))
+    );
+    assert_eq!(
+        client.try_propose(
+            &owners.get(0).unwrap(),
+            &ProposalAction::SetThreshold(1),
+            &50
+        ),
+        Err(Ok(MultisigError::InvalidExpiry))
+    );
+}
+
+#[test]
+fn revoked_and_cancelled_proposals() {
+    let env = Env::default();
+    let (client, owners) = setup(&env);
+
+    let id = client.propose(
+        &owners.get(0).unwrap(),
+        &ProposalAction::SetThreshold(3),
+        &1_000,
+    );
+    client.confirm(&owners.get(1).unwrap(), &id);
+    assert_eq!(client.revoke_confirmation(&owners.get(1).unwrap(), &id), 1);
+    assert!(!client.is_confirmed_by(&id, &owners.get(1).unwrap()));
+    assert_eq!(
+        client.try_execute(&owners.get(0).unwrap(), &id).err(),
+        Some(Ok(MultisigError::ThresholdNotMet))
+    );
+
+    assert_eq!(
+        client.try_cancel(&owners.get(1).unwrap(), &id),
+        Err(Ok(MultisigError::NotOwner))
+    );
+    client.cancel(&owners.get(0).unwrap(), &id);
+    assert_eq!(
+        client.try_confirm(&owners.get(1).unwrap(), &id),
+        Err(Ok(MultisigError::ProposalClosed))
+    );
+}
+
+#[test]
+fn manages_owner_set_through_proposals() {
+    let env = Env::default();
+    let (client, owners) = setup(&env);
+    let newcomer = Address::generate(&env);
+
+    let add = client.propose(
+        &owners.get(0).unwrap(),
+        &ProposalAction::AddOwner(newcomer.clone()),
+        &1_000,
+    );
+    client.confirm(&owners.get(1).unwrap(), &add);
+    client.execute(&owners.get(0).unwrap(), &add);
+    assert!(client.is_owner(&newcomer));
+    assert_eq!(client.get_owners().len(), 4);
+
+    let raise = client.propose(&newcomer, &ProposalAction::SetThreshold(5), &1_000);
+    client.confirm(&owners.get(0).unwrap(), &raise);
+    assert_eq!(
+        client.try_execute(&newcomer, &raise).err(),
+        Some(Ok(MultisigError::InvalidThreshold))
+    );
+
+    let remove = client.propose(
+        &newcomer,
+        &ProposalAction::RemoveOwner(owners.get(2).unwrap()),
+        &1_000,
+    );
+    client.confirm(&owners.get(2).unwrap(), &remove);
+    client.execute(&newcomer, &remove);
+    assert!(!client.is_owner(&owners.get(2).unwrap()));
+    assert_eq!(client.get_threshold(), 2);
+}
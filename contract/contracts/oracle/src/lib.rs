@@ -0,0 +1,340 @@
+#![no_std]
+
+use common::{init_role, require_role, ContractError, Role};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, Address, BytesN, Env, Map, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ResultStatus {
+    /// Reporters are still voting
+    Pending,
+    /// An outcome reached the quorum; it can be challenged until the
+    /// window closes
+    Proposed,
+    /// A guardian challenged the proposal; only the admin can resolve it
+    Disputed,
+    /// Set by the admin after a dispute; final immediately
+    Resolved,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchResult {
+    pub match_id: BytesN<32>,
+    pub status: ResultStatus,
+    pub outcome: Option<Symbol>,
+    /// Number of reporters backing each submitted outcome
+    pub votes: Map<Symbol, u32>,
+    /// End of the challenge window, fixed when the quorum is reached
+    pub challenge_ends_at: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleConfig {
+    /// Matching reports needed before an outcome is proposed
+    pub quorum: u32,
+    /// Seconds a proposed outcome stays open to challenge
+    pub challenge_window: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Config,
+    Reporter(Address),
+    Result(BytesN<32>),
+    Vote(BytesN<32>, Address),
+}
+
+#[contract]
+pub struct OracleContract;
+
+#[contractimpl]
+impl OracleContract {
+    /// `admin` also receives `Role::Guardian` and may challenge results.
+    pub fn initialize(env: Env, admin: Address, config: OracleConfig) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::Config) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        validate_config(&config)?;
+
+        storage.set(&DataKey::Config, &config);
+        init_role(&env, Role::Admin, &admin);
+        init_role(&env, Role::Guardian, &admin);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::grant_role(&env, &caller, role, &account)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::revoke_role(&env, &caller, role, &account)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), ContractError> {
+        common::renounce_role(&env, &account, role)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Applies to matches whose outcome has not been proposed yet.
+    pub fn set_config(env: Env, admin: Address, config: OracleConfig) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        validate_config(&config)?;
+
+        env.storage().persistent().set(&DataKey::Config, &config);
+        Ok(())
+    }
+
+    pub fn get_config(env: Env) -> Result<OracleConfig, ContractError> {
+        get_config(&env)
+    }
+
+    pub fn set_reporter(
+        env: Env,
+        admin: Address,
+        reporter: Address,
+        allowed: bool,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let key = DataKey::Reporter(reporter);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_reporter(env: Env, reporter: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Reporter(reporter))
+    }
+
+    /// Report `outcome` for a match. Each reporter votes once per match;
+    /// the first outcome to gather `quorum` votes is proposed and opens the
+    /// challenge window.
+    pub fn submit_result(
+        env: Env,
+        reporter: Address,
+        match_id: BytesN<32>,
+        outcome: Symbol,
+    ) -> Result<MatchResult, ContractError> {
+        reporter.require_auth();
+        if !Self::is_reporter(env.clone(), reporter.clone()) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let mut result = get_result(&env, &match_id).unwrap_or(MatchResult {
+            match_id: match_id.clone(),
+            status: ResultStatus::Pending,
+            outcome: None,
+            votes: Map::new(&env),
+            challenge_ends_at: None,
+        });
+        if result.status != ResultStatus::Pending {
+            return Err(ContractError::InvalidStatus);
+        }
+        let storage = env.storage().persistent();
+        let vote_key = DataKey::Vote(match_id.clone(), reporter.clone());
+        if storage.has(&vote_key) {
+            return Err(ContractError::DuplicateOperation);
+        }
+        storage.set(&vote_key, &outcome);
+        common::bump_persistent(&env, &vote_key);
+
+        let votes = result.votes.get(outcome.clone()).unwrap_or(0) + 1;
+        result.votes.set(outcome.clone(), votes);
+        let config = get_config(&env)?;
+        if votes >= config.quorum {
+            result.status = ResultStatus::Proposed;
+            result.outcome = Some(outcome.clone());
+            result.challenge_ends_at = Some(
+                env.ledger()
+                    .timestamp()
+                    .saturating_add(config.challenge_window),
+            );
+        }
+        store_result(&env, &result);
+
+        publish_report_event(&env, &match_id, &reporter, &outcome);
+        if result.status == ResultStatus::Proposed {
+            publish_result_event(&env, "result_proposed", &result);
+        }
+
+        Ok(result)
+    }
+
+    /// Hold a proposed outcome for admin review; requires `Role::Guardian`
+    /// and must happen inside the challenge window.
+    pub fn challenge_result(
+        env: Env,
+        guardian: Address,
+        match_id: BytesN<32>,
+    ) -> Result<MatchResult, ContractError> {
+        require_role(&env, Role::Guardian, &guardian)?;
+
+        let mut result = get_result(&env, &match_id).ok_or(ContractError::MatchNotFound)?;
+        if result.status != ResultStatus::Proposed || is_challenge_window_over(&env, &result) {
+            return Err(ContractError::InvalidStatus);
+        }
+        result.status = ResultStatus::Disputed;
+        store_result(&env, &result);
+
+        publish_result_event(&env, "result_challenged", &result);
+
+        Ok(result)
+    }
+
+    /// Settle a disputed result with the admin's `outcome`.
+    pub fn resolve_dispute(
+        env: Env,
+        admin: Address,
+        match_id: BytesN<32>,
+        outcome: Symbol,
+    ) -> Result<MatchResult, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let mut result = get_result(&env, &match_id).ok_or(ContractError::MatchNotFound)?;
+        if result.status != ResultStatus::Disputed {
+            return Err(ContractError::InvalidStatus);
+        }
+        result.status = ResultStatus::Resolved;
+        result.outcome = Some(outcome);
+        store_result(&env, &result);
+
+        publish_result_event(&env, "result_resolved", &result);
+
+        Ok(result)
+    }
+
+    /// Voting state of a match, whether or not it is final.
+    pub fn get_match_result(env: Env, match_id: BytesN<32>) -> Result<MatchResult, ContractError> {
+        get_result(&env, &match_id).ok_or(ContractError::MatchNotFound)
+    }
+
+    /// Final outcome for settlement and pool contracts. Fails with
+    /// `InvalidStatus` until the outcome is resolved or has outlived its
+    /// challenge window.
+    pub fn get_result(env: Env, match_id: BytesN<32>) -> Result<Symbol, ContractError> {
+        let result = get_result(&env, &match_id).ok_or(ContractError::MatchNotFound)?;
+        let is_final = match result.status {
+            ResultStatus::Resolved => true,
+            ResultStatus::Proposed => is_challenge_window_over(&env, &result),
+            _ => false,
+        };
+        if !is_final {
+            return Err(ContractError::InvalidStatus);
+        }
+        result.outcome.ok_or(ContractError::InvalidStatus)
+    }
+
+    pub fn get_vote(env: Env, match_id: BytesN<32>, reporter: Address) -> Option<Symbol> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Vote(match_id, reporter))
+    }
+}
+
+fn validate_config(config: &OracleConfig) -> Result<(), ContractError> {
+    if config.quorum == 0 {
+        return Err(ContractError::InvalidConfig);
+    }
+    Ok(())
+}
+
+fn get_config(env: &Env) -> Result<OracleConfig, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Config)
+        .ok_or(ContractError::NotInitialized)
+}
+
+fn get_result(env: &Env, match_id: &BytesN<32>) -> Option<MatchResult> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Result(match_id.clone()))
+}
+
+fn store_result(env: &Env, result: &MatchResult) {
+    let key = DataKey::Result(result.match_id.clone());
+    env.storage().persistent().set(&key, result);
+    common::bump_persistent(env, &key);
+}
+
+fn is_challenge_window_over(env: &Env, result: &MatchResult) -> bool {
+    result
+        .challenge_ends_at
+        .is_some_and(|ends_at| env.ledger().timestamp() >= ends_at)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_report_event(env: &Env, match_id: &BytesN<32>, reporter: &Address, outcome: &Symbol) {
+    env.events().publish(
+        (
+            Symbol::new(env, "result_reported"),
+            match_id.clone(),
+            reporter.clone(),
+        ),
+        outcome.clone(),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_result_event(env: &Env, topic: &str, result: &MatchResult) {
+    env.events().publish(
+        (Symbol::new(env, topic), result.match_id.clone()),
+        (result.outcome.clone(), env.ledger().timestamp()),
+    );
+}
+
+#[cfg(test)]
+mod test;
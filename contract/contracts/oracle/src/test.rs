@@ -0,0 +1,171 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Ledger},
+    Address, BytesN, Env,
+};
+
+const WINDOW: u64 = 600;
+
+fn setup_test(env: &Env, quorum: u32) -> (OracleContractClient<'_>, Address, [Address; 3]) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let contract_id = env.register(OracleContract, ());
+    let client = OracleContractClient::new(env, &contract_id);
+    client.initialize(
+        &admin,
+        &OracleConfig {
+            quorum,
+            challenge_window: WINDOW,
+        },
+    );
+
+    let reporters = [
+        Address::generate(env),
+        Address::generate(env),
+        Address::generate(env),
+    ];
+    for reporter in reporters.iter() {
+        client.set_reporter(&admin, reporter, &true);
+    }
+
+    (client, admin, reporters)
+}
+
+fn match_id(env: &Env) -> BytesN<32> {
+    BytesN::from_array(env, &[7; 32])
+}
+
+fn warp(env: &Env, seconds: u64) {
+    env.ledger().with_mut(|ledger| ledger.timestamp += seconds);
+}
+
+#[test]
+fn test_initialize_rejects_zero_quorum_and_repeats() {
+    let env = Env::default();
+    let (client, admin, _reporters) = setup_test(&env, 1);
+
+    assert_eq!(
+        client.try_initialize(
+            &admin,
+            &OracleConfig {
+                quorum: 1,
+                challenge_window: 0,
+            }
+        ),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+    assert_eq!(
+        client.try_set_config(
+            &admin,
+            &OracleConfig {
+                quorum: 0,
+                challenge_window: 0,
+            }
+        ),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+}
+
+#[test]
+fn test_quorum_then_challenge_window_finalizes() {
+    let env = Env::default();
+    let (client, admin, reporters) = setup_test(&env, 2);
+    let id = match_id(&env);
+    let home = symbol_short!("home");
+
+    let result = client.submit_result(&reporters[0], &id, &home);
+    assert_eq!(result.status, ResultStatus::Pending);
+    assert_eq!(
+        client.try_submit_result(&reporters[0], &id, &home),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+
+    // A dissenting report does not count toward the other outcome
+    client.submit_result(&reporters[1], &id, &symbol_short!("away"));
+    let result = client.submit_result(&reporters[2], &id, &home);
+    assert_eq!(result.status, ResultStatus::Proposed);
+    assert_eq!(result.outcome, Some(home.clone()));
+    assert_eq!(result.votes.get(home.clone()), Some(2));
+
+    assert_eq!(
+        client.try_get_result(&id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    warp(&env, WINDOW);
+    assert_eq!(client.get_result(&id), home);
+    assert_eq!(
+        client.try_challenge_result(&admin, &id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+}
+
+#[test]
+fn test_only_whitelisted_reporters_submit() {
+    let env = Env::default();
+    let (client, _admin, _reporters) = setup_test(&env, 1);
+    let outsider = Address::generate(&env);
+
+    assert_eq!(
+        client.try_submit_result(&outsider, &match_id(&env), &symbol_short!("home")),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_get_result(&match_id(&env)),
+        Err(Ok(ContractError::MatchNotFound))
+    );
+}
+
+#[test]
+fn test_guardian_challenge_goes_to_admin_resolution() {
+    let env = Env::default();
+    let (client, admin, reporters) = setup_test(&env, 1);
+    let id = match_id(&env);
+    let guardian = Address::generate(&env);
+    client.grant_role(&admin, &Role::Guardian, &guardian);
+
+    client.submit_result(&reporters[0], &id, &symbol_short!("home"));
+    assert_eq!(
+        client.try_challenge_result(&Address::generate(&env), &id),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    let result = client.challenge_result(&guardian, &id);
+    assert_eq!(result.status, ResultStatus::Disputed);
+
+    // A disputed result stays unusable after the window and takes no votes
+    warp(&env, WINDOW);
+    assert_eq!(
+        client.try_get_result(&id),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_submit_result(&reporters[1], &id, &symbol_short!("home")),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    let result = client.resolve_dispute(&admin, &id, &symbol_short!("draw"));
+    assert_eq!(result.status, ResultStatus::Resolved);
+    assert_eq!(client.get_result(&id), symbol_short!("draw"));
+}
+
+#[test]
+fn test_upgrade_requires_admin_and_migrate_is_idempotent() {
+    let env = Env::default();
+    let (client, admin, reporters) = setup_test(&env, 1);
+
+    assert_eq!(
+        client.try_upgrade(&reporters[0], &BytesN::from_array(&env, &[1; 32])),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_migrate(&reporters[0]),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    assert_eq!(client.get_version(), CONTRACT_VERSION);
+    assert_eq!(client.migrate(&admin), None);
+    assert_eq!(client.get_version(), CONTRACT_VERSION);
+}
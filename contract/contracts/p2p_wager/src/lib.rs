@@ -0,0 +1,413 @@
+#![no_std]
+
+use common::{
+    get_fee_config, get_oracle_result, init_role, quote_fee, record_fee, set_fee_config,
+    total_fees_collected, FeeConfig, Role,
+};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, Address, BytesN, Env, Symbol, Val,
+    Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum P2pWagerError {
+    Unauthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    InvalidAmount = 4,
+    InvalidConfig = 5,
+    InvalidStatus = 6,
+    Expired = 7,
+    ConditionNotMet = 8,
+    ChallengeNotFound = 9,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChallengeStatus {
+    /// Waiting for the opponent to match the stake
+    Open,
+    /// Both stakes are in; waiting for the result
+    Accepted,
+    Resolved,
+    /// Stake returned to the creator, unaccepted
+    Refunded,
+}
+
+/// What a creator offers when opening a challenge.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChallengeTerms {
+    pub opponent: Address,
+    pub token: Address,
+    /// Put up by each side
+    pub stake: i128,
+    pub match_id: BytesN<32>,
+    pub pick: Symbol,
+    /// Last moment the opponent can accept
+    pub expires_at: u64,
+}
+
+/// A one-on-one wager. The creator backs `pick` for `match_id`; the
+/// opponent takes every other outcome.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Challenge {
+    pub id: u64,
+    pub creator: Address,
+    pub opponent: Address,
+    pub token: Address,
+    /// Put up by each side
+    pub stake: i128,
+    pub match_id: BytesN<32>,
+    pub pick: Symbol,
+    /// Last moment the opponent can accept
+    pub expires_at: u64,
+    pub status: ChallengeStatus,
+    pub winner: Option<Address>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Oracle,
+    ChallengeCount,
+    Challenge(u64),
+}
+
+#[contract]
+pub struct P2pWagerContract;
+
+#[contractimpl]
+impl P2pWagerContract {
+    /// `oracle` decides challenges unless an operator resolves them first.
+    /// `admin` also receives `Role::Operator`.
+    pub fn initialize(env: Env, admin: Address, oracle: Address) -> Result<(), P2pWagerError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::Oracle) {
+            return Err(P2pWagerError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        storage.set(&DataKey::Oracle, &oracle);
+        init_role(&env, Role::Admin, &admin);
+        init_role(&env, Role::Operator, &admin);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), P2pWagerError> {
+        common::grant_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), P2pWagerError> {
+        common::revoke_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), P2pWagerError> {
+        common::renounce_role(&env, &account, role).map_err(role_error)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), P2pWagerError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, P2pWagerError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn set_oracle(env: Env, admin: Address, oracle: Address) -> Result<(), P2pWagerError> {
+        require_role(&env, Role::Admin, &admin)?;
+        env.storage().persistent().set(&DataKey::Oracle, &oracle);
+        Ok(())
+    }
+
+    pub fn get_oracle(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Oracle)
+    }
+
+    /// Rake taken from each pot, at most `common::MAX_FEE_BPS`; requires
+    /// `Role::Admin`. A zero `fee_bps` turns it off.
+    pub fn set_fee_config(
+        env: Env,
+        admin: Address,
+        fee_bps: u32,
+        fee_collector: Address,
+    ) -> Result<FeeConfig, P2pWagerError> {
+        require_role(&env, Role::Admin, &admin)?;
+        set_fee_config(&env, fee_bps, &fee_collector).map_err(fee_error)
+    }
+
+    pub fn get_fee_config(env: Env) -> Option<FeeConfig> {
+        get_fee_config(&env)
+    }
+
+    pub fn total_fees_collected(env: Env, token: Address) -> i128 {
+        total_fees_collected(&env, Some(token))
+    }
+
+    /// Challenge `terms.opponent`, escrowing the stake from `creator`.
+    /// Returns the challenge id.
+    pub fn create_challenge(
+        env: Env,
+        creator: Address,
+        terms: ChallengeTerms,
+    ) -> Result<u64, P2pWagerError> {
+        creator.require_auth();
+        if terms.stake <= 0 {
+            return Err(P2pWagerError::InvalidAmount);
+        }
+        if creator == terms.opponent || terms.expires_at <= env.ledger().timestamp() {
+            return Err(P2pWagerError::InvalidConfig);
+        }
+
+        let storage = env.storage().persistent();
+        let id = storage.get(&DataKey::ChallengeCount).unwrap_or(0u64) + 1;
+        storage.set(&DataKey::ChallengeCount, &id);
+
+        token::Client::new(&env, &terms.token).transfer(
+            &creator,
+            env.current_contract_address(),
+            &terms.stake,
+        );
+
+        let challenge = Challenge {
+            id,
+            creator,
+            opponent: terms.opponent,
+            token: terms.token,
+            stake: terms.stake,
+            match_id: terms.match_id,
+            pick: terms.pick,
+            expires_at: terms.expires_at,
+            status: ChallengeStatus::Open,
+            winner: None,
+        };
+        store_challenge(&env, &challenge);
+        publish_challenge_event(&env, "challenge_created", &challenge);
+        Ok(id)
+    }
+
+    /// Match the creator's stake. Only the named opponent can accept, and
+    /// only until the challenge expires.
+    pub fn accept_challenge(
+        env: Env,
+        opponent: Address,
+        challenge_id: u64,
+    ) -> Result<(), P2pWagerError> {
+        opponent.require_auth();
+        let mut challenge = get_challenge_with_status(&env, challenge_id, ChallengeStatus::Open)?;
+        if opponent != challenge.opponent {
+            return Err(P2pWagerError::Unauthorized);
+        }
+        if env.ledger().timestamp() > challenge.expires_at {
+            return Err(P2pWagerError::Expired);
+        }
+
+        token::Client::new(&env, &challenge.token).transfer(
+            &opponent,
+            env.current_contract_address(),
+            &challenge.stake,
+        );
+
+        challenge.status = ChallengeStatus::Accepted;
+        store_challenge(&env, &challenge);
+        publish_challenge_event(&env, "challenge_accepted", &challenge);
+        Ok(())
+    }
+
+    /// Return the stake of a challenge nobody accepted. The creator may
+    /// withdraw at any time; anyone may trigger the refund after expiry.
+    pub fn refund_challenge(
+        env: Env,
+        caller: Address,
+        challenge_id: u64,
+    ) -> Result<(), P2pWagerError> {
+        caller.require_auth();
+        let mut challenge = get_challenge_with_status(&env, challenge_id, ChallengeStatus::Open)?;
+        if caller != challenge.creator && env.ledger().timestamp() <= challenge.expires_at {
+            return Err(P2pWagerError::Unauthorized);
+        }
+
+        challenge.status = ChallengeStatus::Refunded;
+        store_challenge(&env, &challenge);
+        token::Client::new(&env, &challenge.token).transfer(
+            &env.current_contract_address(),
+            &challenge.creator,
+            &challenge.stake,
+        );
+        publish_challenge_event(&env, "challenge_refunded", &challenge);
+        Ok(())
+    }
+
+    /// Settle an accepted challenge from the oracle's final result. Anyone
+    /// may call it; fails with `ConditionNotMet` until the result is final.
+    pub fn resolve(env: Env, challenge_id: u64) -> Result<Address, P2pWagerError> {
+        let challenge = get_challenge_with_status(&env, challenge_id, ChallengeStatus::Accepted)?;
+        let oracle: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Oracle)
+            .ok_or(P2pWagerError::NotInitialized)?;
+        let result = get_oracle_result(&env, &oracle, &challenge.match_id)
+            .ok_or(P2pWagerError::ConditionNotMet)?;
+
+        let winner = if result == challenge.pick {
+            challenge.creator.clone()
+        } else {
+            challenge.opponent.clone()
+        };
+        pay_winner(&env, challenge, winner)
+    }
+
+    /// Settle an accepted challenge for `winner`, e.g. for a match the
+    /// oracle does not cover; requires `Role::Operator`.
+    pub fn resolve_by_operator(
+        env: Env,
+        operator: Address,
+        challenge_id: u64,
+        winner: Address,
+    ) -> Result<Address, P2pWagerError> {
+        require_role(&env, Role::Operator, &operator)?;
+        let challenge = get_challenge_with_status(&env, challenge_id, ChallengeStatus::Accepted)?;
+        if winner != challenge.creator && winner != challenge.opponent {
+            return Err(P2pWagerError::InvalidConfig);
+        }
+        pay_winner(&env, challenge, winner)
+    }
+
+    pub fn get_challenge(env: Env, challenge_id: u64) -> Result<Challenge, P2pWagerError> {
+        get_challenge(&env, challenge_id)
+    }
+
+    pub fn get_challenge_count(env: Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ChallengeCount)
+            .unwrap_or(0)
+    }
+}
+
+fn get_challenge(env: &Env, challenge_id: u64) -> Result<Challenge, P2pWagerError> {
+    let key = DataKey::Challenge(challenge_id);
+    common::bump_persistent(env, &key);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .ok_or(P2pWagerError::ChallengeNotFound)
+}
+
+fn get_challenge_with_status(
+    env: &Env,
+    challenge_id: u64,
+    status: ChallengeStatus,
+) -> Result<Challenge, P2pWagerError> {
+    let challenge = get_challenge(env, challenge_id)?;
+    if challenge.status != status {
+        return Err(P2pWagerError::InvalidStatus);
+    }
+    Ok(challenge)
+}
+
+fn store_challenge(env: &Env, challenge: &Challenge) {
+    let key = DataKey::Challenge(challenge.id);
+    env.storage().persistent().set(&key, challenge);
+    common::bump_persistent(env, &key);
+}
+
+/// Close the challenge and pay `winner` the pot minus the rake.
+fn pay_winner(
+    env: &Env,
+    mut challenge: Challenge,
+    winner: Address,
+) -> Result<Address, P2pWagerError> {
+    let pot = challenge
+        .stake
+        .checked_mul(2)
+        .ok_or(P2pWagerError::InvalidAmount)?;
+
+    challenge.status = ChallengeStatus::Resolved;
+    challenge.winner = Some(winner.clone());
+    store_challenge(env, &challenge);
+
+    let token_client = token::Client::new(env, &challenge.token);
+    let contract = env.current_contract_address();
+    let mut payout = pot;
+    if let Some((rake, fee_collector)) = quote_fee(env, pot).map_err(fee_error)? {
+        payout -= rake;
+        token_client.transfer(&contract, &fee_collector, &rake);
+        record_fee(
+            env,
+            &winner,
+            Some(challenge.token.clone()),
+            rake,
+            &fee_collector,
+        )
+        .map_err(fee_error)?;
+    }
+    token_client.transfer(&contract, &winner, &payout);
+
+    publish_challenge_event(env, "challenge_resolved", &challenge);
+    Ok(winner)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_challenge_event(env: &Env, topic: &str, challenge: &Challenge) {
+    env.events()
+        .publish((Symbol::new(env, topic), challenge.id), challenge.clone());
+}
+
+fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), P2pWagerError> {
+    common::require_role(env, role, account).map_err(role_error)
+}
+
+fn role_error(_: common::ContractError) -> P2pWagerError {
+    P2pWagerError::Unauthorized
+}
+
+fn fee_error(err: common::ContractError) -> P2pWagerError {
+    match err {
+        common::ContractError::InvalidConfig => P2pWagerError::InvalidConfig,
+        _ => P2pWagerError::InvalidAmount,
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,167 @@
+#![cfg(test)]
+
+use super::*;
+use oracle::{OracleConfig, OracleContract, OracleContractClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, BytesN, Env,
+};
+
+const STAKE: i128 = 500;
+
+fn setup_test(
+    env: &Env,
+) -> (
+    P2pWagerContractClient<'_>,
+    OracleContractClient<'_>,
+    Address,
+    Address,
+    token::Client<'_>,
+    Address,
+    Address,
+) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let oracle_id = env.register(OracleContract, ());
+    let oracle = OracleContractClient::new(env, &oracle_id);
+    oracle.initialize(
+        &admin,
+        &OracleConfig {
+            quorum: 1,
+            challenge_window: 0,
+        },
+    );
+    let reporter = Address::generate(env);
+    oracle.set_reporter(&admin, &reporter, &true);
+
+    let contract_id = env.register(P2pWagerContract, ());
+    let client = P2pWagerContractClient::new(env, &contract_id);
+    client.initialize(&admin, &oracle_id);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let creator = Address::generate(env);
+    let opponent = Address::generate(env);
+    let minter = token::StellarAssetClient::new(env, &token_id);
+    minter.mint(&creator, &1_000);
+    minter.mint(&opponent, &1_000);
+
+    let token = token::Client::new(env, &token_id);
+
+    (client, oracle, admin, reporter, token, creator, opponent)
+}
+
+fn match_id(env: &Env) -> BytesN<32> {
+    BytesN::from_array(env, &[5; 32])
+}
+
+fn terms(env: &Env, token: &token::Client, opponent: &Address) -> ChallengeTerms {
+    ChallengeTerms {
+        opponent: opponent.clone(),
+        token: token.address.clone(),
+        stake: STAKE,
+        match_id: match_id(env),
+        pick: Symbol::new(env, "home"),
+        expires_at: env.ledger().timestamp() + 600,
+    }
+}
+
+#[test]
+fn oracle_winner_takes_pot_minus_rake() {
+    let env = Env::default();
+    let (client, oracle, admin, reporter, token, creator, opponent) = setup_test(&env);
+    let collector = Address::generate(&env);
+    client.set_fee_config(&admin, &500, &collector);
+
+    let id = client.create_challenge(&creator, &terms(&env, &token, &opponent));
+    assert_eq!(
+        client.try_accept_challenge(&creator, &id),
+        Err(Ok(P2pWagerError::Unauthorized))
+    );
+    client.accept_challenge(&opponent, &id);
+    assert_eq!(token.balance(&client.address), 2 * STAKE);
+    assert_eq!(
+        client.try_refund_challenge(&creator, &id),
+        Err(Ok(P2pWagerError::InvalidStatus))
+    );
+
+    assert_eq!(
+        client.try_resolve(&id),
+        Err(Ok(P2pWagerError::ConditionNotMet))
+    );
+    oracle.submit_result(&reporter, &match_id(&env), &Symbol::new(&env, "away"));
+    assert_eq!(client.resolve(&id), opponent);
+
+    // 5% of the 1_000 pot goes to the collector
+    assert_eq!(token.balance(&collector), 50);
+    assert_eq!(token.balance(&opponent), 1_000 - STAKE + 950);
+    assert_eq!(client.total_fees_collected(&token.address), 50);
+    let challenge = client.get_challenge(&id);
+    assert_eq!(challenge.status, ChallengeStatus::Resolved);
+    assert_eq!(challenge.winner, Some(opponent.clone()));
+    assert_eq!(
+        client.try_resolve(&id),
+        Err(Ok(P2pWagerError::InvalidStatus))
+    );
+}
+
+#[test]
+fn operator_can_resolve_for_either_player() {
+    let env = Env::default();
+    let (client, _oracle, admin, _reporter, token, creator, opponent) = setup_test(&env);
+
+    let id = client.create_challenge(&creator, &terms(&env, &token, &opponent));
+    client.accept_challenge(&opponent, &id);
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_resolve_by_operator(&stranger, &id, &creator),
+        Err(Ok(P2pWagerError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_resolve_by_operator(&admin, &id, &stranger),
+        Err(Ok(P2pWagerError::InvalidConfig))
+    );
+    client.resolve_by_operator(&admin, &id, &creator);
+    assert_eq!(token.balance(&creator), 1_000 + STAKE);
+}
+
+#[test]
+fn unaccepted_challenges_refund_after_expiry() {
+    let env = Env::default();
+    let (client, _oracle, _admin, _reporter, token, creator, opponent) = setup_test(&env);
+
+    let mut expired = terms(&env, &token, &opponent);
+    expired.expires_at = env.ledger().timestamp();
+    assert_eq!(
+        client.try_create_challenge(&creator, &expired),
+        Err(Ok(P2pWagerError::InvalidConfig))
+    );
+
+    let id = client.create_challenge(&creator, &terms(&env, &token, &opponent));
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_refund_challenge(&stranger, &id),
+        Err(Ok(P2pWagerError::Unauthorized))
+    );
+
+    env.ledger().with_mut(|ledger| ledger.timestamp += 601);
+    assert_eq!(
+        client.try_accept_challenge(&opponent, &id),
+        Err(Ok(P2pWagerError::Expired))
+    );
+    client.refund_challenge(&stranger, &id);
+    assert_eq!(token.balance(&creator), 1_000);
+    assert_eq!(client.get_challenge(&id).status, ChallengeStatus::Refunded);
+
+    // The creator may withdraw an open challenge before it expires
+    let id = client.create_challenge(&creator, &terms(&env, &token, &opponent));
+    client.refund_challenge(&creator, &id);
+    assert_eq!(token.balance(&creator), 1_000);
+    assert_eq!(
+        client.try_get_challenge(&99),
+        Err(Ok(P2pWagerError::ChallengeNotFound))
+    );
+}
@@ -1,22 +1,51 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, String, Symbol, Vec, U256};
+use soroban_sdk::{
+    contract, contractimpl, token as sac, xdr::ToXdr, Address, BytesN, Env, String, Symbol, Val,
+    Vec, U256,
+};
 
 mod errors;
 mod events;
+mod metadata;
+mod rental;
+mod royalty;
 mod storage;
 mod token;
+mod voucher;
 
 pub use errors::*;
 pub use events::*;
+pub use metadata::*;
+pub use rental::*;
+pub use royalty::*;
 pub use storage::*;
 pub use token::*;
+pub use voucher::*;
+
+pub use common::{CardAttributes, Rarity, MAX_RATING};
 
 use common::{
     cleanup_operation, create_nft_mint_event, ensure_not_replayed, is_operation_executed,
-    ContractError, NFTMintEvent, NFT_MINT_EVENT,
+    publish_versioned_event, ContractError, NFTBurnEvent, NFTMintEvent, NFTTransferEvent,
+    EVENT_SCHEMA_VERSION, NFT_BURN_EVENT, NFT_MINT_EVENT, NFT_TRANSFER_EVENT,
 };
 
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 2;
+
+/// Upper bound on the number of cards a single `mint_batch` call mints.
+pub const MAX_MINT_BATCH: u32 = 20;
+
+/// Upper bound on `limit` in the paged enumeration views.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Number of same-rarity cards consumed by one `fuse`.
+pub const FUSION_INPUTS: u32 = 3;
+
+/// Rating added to the inputs' average when fusing.
+pub const FUSION_RATING_BONUS: u32 = 5;
+
 #[contract]
 pub struct PlayerCardContract;
 
@@ -30,28 +59,65 @@ impl PlayerCardContract {
 
         storage::set_admin(&env, &admin);
         storage::set_next_token_id(&env, 1);
+        storage::set_total_supply(&env, 0);
 
-        let event = NFTMintEvent {
-            token_id: U256::from_u32(&env, 0),
-            to: admin.clone(),
-            token_uri: String::from_str(&env, "contract_initialized"),
-            nft_contract: env.current_contract_address(),
-            timestamp: env.ledger().timestamp(),
-            mint_type: Symbol::short("INIT"),
-            metadata: soroban_sdk::Map::new(&env),
-            price: None,
-        };
+        publish_initialized_event(&env, &admin);
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), ContractError> {
+        storage::get_admin(&env).require_auth();
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current. Version 2 moves card
+    /// state out of instance storage; see `storage::legacy`.
+    pub fn migrate(env: Env) -> Result<Option<u32>, ContractError> {
+        let legacy_admin = storage::legacy::get_admin(&env);
+        match &legacy_admin {
+            Some(admin) => admin.require_auth(),
+            None => storage::get_admin(&env).require_auth(),
+        }
+        if legacy_admin.is_some() {
+            storage::legacy::migrate(&env);
+        }
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Stop duplicating events under their pre-versioning topics from
+    /// `until` on; `None` keeps the duplicates indefinitely.
+    pub fn set_legacy_events_until(env: Env, until: Option<u64>) -> Result<(), ContractError> {
+        storage::get_admin(&env).require_auth();
+        common::set_legacy_events_until(&env, until);
+        Ok(())
+    }
 
-        env.events().publish((NFT_MINT_EVENT,), event);
+    pub fn get_legacy_events_until(env: Env) -> Option<u64> {
+        common::get_legacy_events_until(&env)
     }
 
     /// Mint a new player card NFT to the specified recipient.
     /// operation_hash must be unique to guarantee idempotent execution.
+    /// Cards minted with attributes count towards their rarity's cap.
+    /// Non-`transferable` (soulbound) cards stay with `to`.
     pub fn mint(
         env: Env,
         operation_hash: BytesN<32>,
         to: Address,
         token_uri: String,
+        attributes: Option<CardAttributes>,
+        transferable: bool,
         ttl_seconds: Option<u64>,
     ) -> Result<u64, ContractError> {
         let admin = storage::get_admin(&env);
@@ -63,31 +129,155 @@ impl PlayerCardContract {
             ttl_seconds,
         )?;
 
-        let token_id = storage::get_next_token_id(&env);
-        storage::increment_next_token_id(&env);
+        let token_id = mint_token(&env, &to, &token_uri, attributes, transferable)?;
+        record_mints(&env, &admin, 1);
+        Ok(token_id)
+    }
 
-        storage::set_owner(&env, token_id, &to);
-        storage::set_token_uri(&env, token_id, &token_uri);
+    /// `mint` on behalf of a registered minter (or the admin), e.g. a
+    /// platform contract handing out reward cards. `minter` must authorize
+    /// the call; its operation hashes never expire.
+    pub fn mint_from(
+        env: Env,
+        minter: Address,
+        operation_hash: BytesN<32>,
+        to: Address,
+        token_uri: String,
+        attributes: Option<CardAttributes>,
+        transferable: bool,
+    ) -> Result<u64, ContractError> {
+        minter.require_auth();
+        if minter != storage::get_admin(&env) && !storage::is_minter(&env, &minter) {
+            return Err(ContractError::Unauthorized);
+        }
+        ensure_not_replayed(&env, Symbol::new(&env, "nft_mint"), operation_hash, None)?;
+
+        let token_id = mint_token(&env, &to, &token_uri, attributes, transferable)?;
+        record_mints(&env, &minter, 1);
+        Ok(token_id)
+    }
 
-        let event = create_nft_mint_event(
+    /// Allow `minter` to mint through `mint_from`. Admin only.
+    pub fn add_minter(env: Env, minter: Address) {
+        storage::get_admin(&env).require_auth();
+        storage::set_minter(&env, &minter, true);
+        publish_minter_event(&env, &minter, true);
+    }
+
+    pub fn remove_minter(env: Env, minter: Address) {
+        storage::get_admin(&env).require_auth();
+        storage::set_minter(&env, &minter, false);
+        publish_minter_event(&env, &minter, false);
+    }
+
+    pub fn is_minter(env: Env, minter: Address) -> bool {
+        storage::is_minter(&env, &minter)
+    }
+
+    /// Cards minted by `minter` so far; the admin's own mints count too.
+    pub fn get_minter_mints(env: Env, minter: Address) -> u64 {
+        storage::get_minter_mints(&env, &minter)
+    }
+
+    /// Mint one card per `(recipients[i], uris[i])` pair under a single
+    /// idempotency hash, e.g. for a pack opening. At most `MAX_MINT_BATCH`
+    /// cards per call; returns the ids in order. `attributes`, when given,
+    /// must line up with `recipients`. `transferable` applies to every card.
+    pub fn mint_batch(
+        env: Env,
+        operation_hash: BytesN<32>,
+        recipients: Vec<Address>,
+        uris: Vec<String>,
+        attributes: Option<Vec<CardAttributes>>,
+        transferable: bool,
+        ttl_seconds: Option<u64>,
+    ) -> Result<Vec<u64>, ContractError> {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+        if recipients.is_empty() {
+            return Err(ContractError::InvalidAmount);
+        }
+        if recipients.len() != uris.len()
+            || attributes
+                .as_ref()
+                .is_some_and(|attributes| attributes.len() != recipients.len())
+        {
+            return Err(ContractError::InvalidConfig);
+        }
+        if recipients.len() > MAX_MINT_BATCH {
+            return Err(ContractError::ExceedsLimit);
+        }
+        ensure_not_replayed(
             &env,
-            U256::from_u32(&env, token_id as u32),
-            to.clone(),
-            token_uri.clone(),
-            env.current_contract_address(),
-            Symbol::short("PLAYER_CARD"),
-            None,
-        );
+            Symbol::new(&env, "nft_mint"),
+            operation_hash,
+            ttl_seconds,
+        )?;
 
-        let mut event_with_timestamp = event;
-        event_with_timestamp.timestamp = env.ledger().timestamp();
+        let mut token_ids = Vec::new(&env);
+        for (index, (to, token_uri)) in recipients.iter().zip(uris.iter()).enumerate() {
+            let card_attributes = attributes
+                .as_ref()
+                .map(|attributes| attributes.get_unchecked(index as u32));
+            token_ids.push_back(mint_token(
+                &env,
+                &to,
+                &token_uri,
+                card_attributes,
+                transferable,
+            )?);
+        }
+        record_mints(&env, &admin, token_ids.len() as u64);
+        Ok(token_ids)
+    }
 
-        env.events()
-            .publish((NFT_MINT_EVENT,), event_with_timestamp);
+    /// Key whose ed25519 signatures authorize `mint_with_voucher`. `None`
+    /// stops accepting vouchers. Admin only.
+    pub fn set_voucher_signer(env: Env, signer: Option<BytesN<32>>) {
+        storage::get_admin(&env).require_auth();
+        storage::set_voucher_signer(&env, signer.as_ref());
+    }
 
+    pub fn get_voucher_signer(env: Env) -> Option<BytesN<32>> {
+        storage::get_voucher_signer(&env)
+    }
+
+    /// Mint the card described by `voucher` and `attributes` to
+    /// `voucher.to`. `signature` is the voucher signer's signature over the
+    /// XDR encoding of `(contract_address, voucher, attributes)`. Anyone can
+    /// submit a voucher; each nonce mints once and only until `expiry`. The
+    /// card is transferable.
+    pub fn mint_with_voucher(
+        env: Env,
+        voucher: MintVoucher,
+        attributes: Option<CardAttributes>,
+        signature: BytesN<64>,
+    ) -> Result<u64, ContractError> {
+        let signer = storage::get_voucher_signer(&env).ok_or(ContractError::NotInitialized)?;
+        if env.ledger().timestamp() > voucher.expiry {
+            return Err(ContractError::Expired);
+        }
+        if storage::is_voucher_nonce_used(&env, voucher.nonce) {
+            return Err(ContractError::DuplicateOperation);
+        }
+        let message = (
+            env.current_contract_address(),
+            voucher.clone(),
+            attributes.clone(),
+        )
+            .to_xdr(&env);
+        env.crypto().ed25519_verify(&signer, &message, &signature);
+        storage::set_voucher_nonce_used(&env, voucher.nonce);
+
+        let token_id = mint_token(&env, &voucher.to, &voucher.uri, attributes, true)?;
+        publish_voucher_event(&env, voucher.nonce, token_id);
         Ok(token_id)
     }
 
+    pub fn is_voucher_nonce_used(env: Env, nonce: u64) -> bool {
+        storage::is_voucher_nonce_used(&env, nonce)
+    }
+
     pub fn is_mint_operation_executed(env: Env, operation_hash: BytesN<32>) -> bool {
         is_operation_executed(&env, Symbol::new(&env, "nft_mint"), operation_hash)
     }
@@ -96,8 +286,348 @@ impl PlayerCardContract {
         cleanup_operation(&env, Symbol::new(&env, "nft_mint"), operation_hash)
     }
 
+    /// Burn `FUSION_INPUTS` cards of one rarity held by `owner` and mint
+    /// them a card of the next rarity. The new card takes the first input's
+    /// position and URI, the latest season and edition among the inputs and
+    /// their average rating plus `FUSION_RATING_BONUS`. It is soulbound if
+    /// any input was. Returns its id.
+    pub fn fuse(
+        env: Env,
+        owner: Address,
+        token_ids: Vec<u64>,
+        operation_hash: BytesN<32>,
+    ) -> Result<u64, ContractError> {
+        owner.require_auth();
+        if token_ids.len() != FUSION_INPUTS {
+            return Err(ContractError::InvalidAmount);
+        }
+        ensure_not_replayed(&env, Symbol::new(&env, "nft_fuse"), operation_hash, None)?;
+
+        let mut inputs: Vec<CardAttributes> = Vec::new(&env);
+        let mut transferable = true;
+        for (index, token_id) in token_ids.iter().enumerate() {
+            if storage::get_owner(&env, token_id) != owner {
+                return Err(ContractError::Unauthorized);
+            }
+            require_movable(&env, token_id)?;
+            if token_ids.first_index_of(token_id) != Some(index as u32) {
+                return Err(ContractError::InvalidConfig);
+            }
+            let attributes =
+                storage::get_card_attributes(&env, token_id).ok_or(ContractError::InvalidConfig)?;
+            if !inputs.is_empty() && inputs.get_unchecked(0).rarity != attributes.rarity {
+                return Err(ContractError::InvalidConfig);
+            }
+            inputs.push_back(attributes);
+            transferable &= storage::is_transferable(&env, token_id);
+        }
+
+        let fused = fused_attributes(&inputs)?;
+        let token_uri = storage::get_token_uri(&env, token_ids.get_unchecked(0));
+        for token_id in token_ids.iter() {
+            burn_token(&env, token_id);
+        }
+        let token_id = mint_token(&env, &owner, &token_uri, Some(fused), transferable)?;
+
+        publish_fused_event(&env, &owner, &token_ids, token_id);
+        Ok(token_id)
+    }
+
+    pub fn is_fuse_operation_executed(env: Env, operation_hash: BytesN<32>) -> bool {
+        is_operation_executed(&env, Symbol::new(&env, "nft_fuse"), operation_hash)
+    }
+
+    /// Destroy a card held by `owner`
+    pub fn burn(env: Env, owner: Address, token_id: u64) -> Result<(), ContractError> {
+        owner.require_auth();
+
+        if storage::get_owner(&env, token_id) != owner {
+            return Err(ContractError::Unauthorized);
+        }
+        require_movable(&env, token_id)?;
+
+        burn_token(&env, token_id);
+        Ok(())
+    }
+
+    /// Destroy any card, e.g. one minted in error. Cards locked by another
+    /// contract must be released first. Admin only.
+    pub fn force_burn(env: Env, token_id: u64) -> Result<(), ContractError> {
+        storage::get_admin(&env).require_auth();
+        if storage::get_locker(&env, token_id).is_some() {
+            return Err(ContractError::TokenNotTransferable);
+        }
+
+        burn_token(&env, token_id);
+        Ok(())
+    }
+
+    /// Replace a card's on-chain attributes. Moving a card to another rarity
+    /// counts against that rarity's cap. Admin only.
+    pub fn update_attributes(
+        env: Env,
+        token_id: u64,
+        attributes: CardAttributes,
+    ) -> Result<(), ContractError> {
+        storage::get_admin(&env).require_auth();
+        storage::get_owner(&env, token_id);
+        attributes.validate()?;
+
+        match storage::get_card_attributes(&env, token_id) {
+            Some(previous) if previous.rarity == attributes.rarity => {}
+            Some(previous) => {
+                assign_rarity(&env, attributes.rarity)?;
+                let minted = storage::get_rarity_minted(&env, previous.rarity);
+                storage::set_rarity_minted(&env, previous.rarity, minted - 1);
+            }
+            None => assign_rarity(&env, attributes.rarity)?,
+        }
+        storage::set_card_attributes(&env, token_id, &attributes);
+
+        publish_attributes_event(&env, token_id, &attributes);
+        Ok(())
+    }
+
+    pub fn get_card_attributes(env: Env, token_id: u64) -> Option<CardAttributes> {
+        storage::get_card_attributes(&env, token_id)
+    }
+
+    /// Limit how many cards of `rarity` can ever be minted. `None` lifts the
+    /// cap. A cap below the number already minted only blocks new mints.
+    pub fn set_rarity_cap(env: Env, rarity: Rarity, cap: Option<u32>) {
+        storage::get_admin(&env).require_auth();
+        storage::set_rarity_cap(&env, rarity, cap);
+    }
+
+    pub fn get_rarity_cap(env: Env, rarity: Rarity) -> Option<u32> {
+        storage::get_rarity_cap(&env, rarity)
+    }
+
+    /// Number of cards minted with, or moved to, `rarity`
+    pub fn get_rarity_minted(env: Env, rarity: Rarity) -> u32 {
+        storage::get_rarity_minted(&env, rarity)
+    }
+
+    /// Mark a card transferable or soulbound, e.g. to migrate a soulbound
+    /// badge to a holder's new wallet. Admin only.
+    pub fn set_transferable(env: Env, token_id: u64, transferable: bool) {
+        storage::get_admin(&env).require_auth();
+        storage::get_owner(&env, token_id);
+
+        storage::set_transferable(&env, token_id, transferable);
+        publish_transferable_event(&env, token_id, transferable);
+    }
+
+    pub fn is_transferable(env: Env, token_id: u64) -> bool {
+        storage::is_transferable(&env, token_id)
+    }
+
+    /// Pin a card to `locker`, e.g. a staking contract using it as a boost.
+    /// The card cannot be transferred, burned or fused until `locker`
+    /// releases it. Frozen and rented-out cards cannot be locked.
+    pub fn lock(
+        env: Env,
+        owner: Address,
+        token_id: u64,
+        locker: Address,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+
+        if storage::get_owner(&env, token_id) != owner {
+            return Err(ContractError::Unauthorized);
+        }
+        if storage::get_locker(&env, token_id).is_some()
+            || storage::is_frozen(&env, token_id)
+            || active_rental(&env, token_id).is_some()
+        {
+            return Err(ContractError::InvalidStatus);
+        }
+
+        storage::set_locker(&env, token_id, &locker);
+        publish_lock_event(&env, token_id, Some(locker));
+        Ok(())
+    }
+
+    /// Release a card; only the contract that locked it can
+    pub fn unlock(env: Env, locker: Address, token_id: u64) -> Result<(), ContractError> {
+        locker.require_auth();
+
+        if storage::get_locker(&env, token_id) != Some(locker) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        storage::remove_locker(&env, token_id);
+        publish_lock_event(&env, token_id, None);
+        Ok(())
+    }
+
+    pub fn get_locker(env: Env, token_id: u64) -> Option<Address> {
+        storage::get_locker(&env, token_id)
+    }
+
+    /// Stop a card from being transferred, burned or fused, e.g. while a
+    /// reported theft is investigated. Admin only.
+    pub fn freeze_token(env: Env, token_id: u64) {
+        storage::get_admin(&env).require_auth();
+        storage::get_owner(&env, token_id);
+        storage::set_frozen(&env, token_id, true);
+        publish_frozen_event(&env, token_id, true);
+    }
+
+    pub fn unfreeze_token(env: Env, token_id: u64) {
+        storage::get_admin(&env).require_auth();
+        storage::get_owner(&env, token_id);
+        storage::set_frozen(&env, token_id, false);
+        publish_frozen_event(&env, token_id, false);
+    }
+
+    pub fn is_frozen(env: Env, token_id: u64) -> bool {
+        storage::is_frozen(&env, token_id)
+    }
+
+    /// Offer a card for rent at `price` of `payment_token` per rental of
+    /// `duration` seconds. Replaces any earlier listing; a rental already
+    /// running is not affected.
+    pub fn list_for_rent(
+        env: Env,
+        owner: Address,
+        token_id: u64,
+        payment_token: Address,
+        price: i128,
+        duration: u64,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+
+        if storage::get_owner(&env, token_id) != owner {
+            return Err(ContractError::Unauthorized);
+        }
+        if price < 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if duration == 0 {
+            return Err(ContractError::InvalidConfig);
+        }
+
+        let listing = RentalListing {
+            owner,
+            payment_token,
+            price,
+            duration,
+        };
+        storage::set_rental_listing(&env, token_id, &listing);
+        publish_rental_listing_event(&env, token_id, Some(listing));
+        Ok(())
+    }
+
+    pub fn cancel_rental_listing(
+        env: Env,
+        owner: Address,
+        token_id: u64,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+
+        if storage::get_owner(&env, token_id) != owner {
+            return Err(ContractError::Unauthorized);
+        }
+
+        storage::remove_rental_listing(&env, token_id);
+        publish_rental_listing_event(&env, token_id, None);
+        Ok(())
+    }
+
+    pub fn get_rental_listing(env: Env, token_id: u64) -> Option<RentalListing> {
+        storage::get_rental_listing(&env, token_id)
+            .filter(|listing| listing.owner == storage::get_owner(&env, token_id))
+    }
+
+    /// Rent a listed card: `renter` pays the owner the listed price and
+    /// becomes the card's user for the listed duration. While the rental
+    /// runs the card cannot be transferred, burned or fused.
+    pub fn rent(env: Env, renter: Address, token_id: u64) -> Result<Rental, ContractError> {
+        renter.require_auth();
+
+        let owner = storage::get_owner(&env, token_id);
+        let listing = storage::get_rental_listing(&env, token_id)
+            .filter(|listing| listing.owner == owner)
+            .ok_or(ContractError::InvalidStatus)?;
+        if renter == owner {
+            return Err(ContractError::InvalidConfig);
+        }
+        if storage::is_frozen(&env, token_id)
+            || storage::get_locker(&env, token_id).is_some()
+            || active_rental(&env, token_id).is_some()
+        {
+            return Err(ContractError::InvalidStatus);
+        }
+
+        if listing.price > 0 {
+            sac::Client::new(&env, &listing.payment_token).transfer(
+                &renter,
+                &owner,
+                &listing.price,
+            );
+        }
+        let rental = Rental {
+            user: renter,
+            expires_at: env.ledger().timestamp().saturating_add(listing.duration),
+        };
+        storage::set_rental(&env, token_id, &rental);
+        publish_rented_event(&env, token_id, &rental, listing.price);
+        Ok(rental)
+    }
+
+    /// Who may use the card's in-game benefits: the renter while a rental
+    /// runs, the owner otherwise. Staking boosts are for owners only, since
+    /// rented-out cards cannot be locked.
+    pub fn user_of(env: Env, token_id: u64) -> Address {
+        let owner = storage::get_owner(&env, token_id);
+        match storage::get_rental(&env, token_id) {
+            Some(rental) if rental.expires_at > env.ledger().timestamp() => rental.user,
+            _ => owner,
+        }
+    }
+
+    /// The running rental of a card, if any
+    pub fn get_rental(env: Env, token_id: u64) -> Option<Rental> {
+        storage::get_rental(&env, token_id)
+            .filter(|rental| rental.expires_at > env.ledger().timestamp())
+    }
+
+    /// Address that receives clawed-back cards. Admin only.
+    pub fn set_custody(env: Env, custody: Address) {
+        storage::get_admin(&env).require_auth();
+        storage::set_custody(&env, &custody);
+    }
+
+    pub fn get_custody(env: Env) -> Option<Address> {
+        storage::get_custody(&env)
+    }
+
+    /// Move a card to the custody address, whether frozen, soulbound or
+    /// rented out, and unfreeze it there. Any rental ends. `reason_hash` points at the off-chain case
+    /// record and is published with the `card_clawback` event. Cards locked
+    /// by another contract must be released first. Admin only.
+    pub fn clawback(env: Env, token_id: u64, reason_hash: BytesN<32>) -> Result<(), ContractError> {
+        storage::get_admin(&env).require_auth();
+        let custody = storage::get_custody(&env).ok_or(ContractError::InvalidConfig)?;
+        if storage::get_locker(&env, token_id).is_some() {
+            return Err(ContractError::TokenNotTransferable);
+        }
+
+        end_rental(&env, token_id);
+        let from = move_token(&env, &custody, token_id);
+        storage::set_frozen(&env, token_id, false);
+        publish_clawback_event(&env, token_id, &from, &custody, &reason_hash);
+        Ok(())
+    }
+
     /// Transfer ownership of a token from one address to another
-    pub fn transfer(env: Env, from: Address, to: Address, token_id: u64) {
+    pub fn transfer(
+        env: Env,
+        from: Address,
+        to: Address,
+        token_id: u64,
+    ) -> Result<(), ContractError> {
         from.require_auth();
 
         let current_owner = storage::get_owner(&env, token_id);
@@ -105,20 +635,152 @@ impl PlayerCardContract {
             panic!("not token owner");
         }
 
-        storage::set_owner(&env, token_id, &to);
+        transfer_token(&env, &to, token_id)
+    }
 
-        let event = NFTMintEvent {
-            token_id: U256::from_u32(&env, token_id as u32),
-            to: to.clone(),
-            token_uri: storage::get_token_uri(&env, token_id),
-            nft_contract: env.current_contract_address(),
-            timestamp: env.ledger().timestamp(),
-            mint_type: Symbol::short("TRANSFER"),
-            metadata: soroban_sdk::Map::new(&env),
-            price: None,
-        };
+    /// Let `spender` transfer one card on the owner's behalf.
+    /// The approval is cleared when the card changes hands.
+    pub fn approve(
+        env: Env,
+        owner: Address,
+        spender: Address,
+        token_id: u64,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+
+        if storage::get_owner(&env, token_id) != owner {
+            return Err(ContractError::Unauthorized);
+        }
+
+        storage::set_approved(&env, token_id, &spender);
+        publish_approval_event(&env, &owner, &spender, token_id);
+        Ok(())
+    }
+
+    /// Grant or revoke `operator` the right to move every card `owner` holds
+    pub fn set_approval_for_all(env: Env, owner: Address, operator: Address, approved: bool) {
+        owner.require_auth();
+
+        storage::set_approval_for_all(&env, &owner, &operator, approved);
+        publish_approval_for_all_event(&env, &owner, &operator, approved);
+    }
+
+    pub fn get_approved(env: Env, token_id: u64) -> Option<Address> {
+        storage::get_approved(&env, token_id)
+    }
+
+    pub fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool {
+        storage::is_approved_for_all(&env, &owner, &operator)
+    }
+
+    /// Transfer on behalf of `from` by its owner, the approved spender or an operator
+    pub fn transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        token_id: u64,
+    ) -> Result<(), ContractError> {
+        spender.require_auth();
+
+        if storage::get_owner(&env, token_id) != from {
+            return Err(ContractError::Unauthorized);
+        }
+        if !PlayerCardToken::is_approved_or_owner(env.clone(), spender, token_id) {
+            return Err(ContractError::Unauthorized);
+        }
 
-        env.events().publish((NFT_MINT_EVENT,), event);
+        transfer_token(&env, &to, token_id)
+    }
+
+    /// Name, symbol and URIs of the whole collection. Cards minted with an
+    /// empty token URI resolve to `base_uri` followed by their id.
+    pub fn set_collection_metadata(
+        env: Env,
+        name: String,
+        symbol: String,
+        base_uri: String,
+        contract_uri: String,
+    ) -> Result<(), ContractError> {
+        storage::get_admin(&env).require_auth();
+        let metadata = CollectionMetadata::new(name, symbol, base_uri, contract_uri)?;
+        storage::set_collection_metadata(&env, &metadata);
+        Ok(())
+    }
+
+    pub fn get_collection_metadata(env: Env) -> Option<CollectionMetadata> {
+        storage::get_collection_metadata(&env)
+    }
+
+    /// Collection name, empty until `set_collection_metadata` is called
+    pub fn name(env: Env) -> String {
+        collection_field(&env, |metadata| metadata.name)
+    }
+
+    pub fn symbol(env: Env) -> String {
+        collection_field(&env, |metadata| metadata.symbol)
+    }
+
+    pub fn base_uri(env: Env) -> String {
+        collection_field(&env, |metadata| metadata.base_uri)
+    }
+
+    pub fn contract_uri(env: Env) -> String {
+        collection_field(&env, |metadata| metadata.contract_uri)
+    }
+
+    /// Override the URI of a single card; an empty `token_uri` drops the
+    /// override so the card falls back to the base URI
+    pub fn set_token_uri(env: Env, token_id: u64, token_uri: String) {
+        storage::get_admin(&env).require_auth();
+        storage::get_owner(&env, token_id);
+        storage::set_token_uri(&env, token_id, &token_uri);
+    }
+
+    /// Set the collection-wide royalty paid to `receiver` on secondary sales
+    pub fn set_default_royalty(env: Env, bps: u32, receiver: Address) -> Result<(), ContractError> {
+        storage::get_admin(&env).require_auth();
+        let royalty = Royalty::new(receiver, bps)?;
+        storage::set_default_royalty(&env, &royalty);
+        Ok(())
+    }
+
+    /// Override the default royalty for a single card
+    pub fn set_token_royalty(
+        env: Env,
+        token_id: u64,
+        bps: u32,
+        receiver: Address,
+    ) -> Result<(), ContractError> {
+        storage::get_admin(&env).require_auth();
+        storage::get_owner(&env, token_id);
+        let royalty = Royalty::new(receiver, bps)?;
+        storage::set_token_royalty(&env, token_id, &royalty);
+        Ok(())
+    }
+
+    /// Drop a card's override so it falls back to the default royalty
+    pub fn reset_token_royalty(env: Env, token_id: u64) {
+        storage::get_admin(&env).require_auth();
+        storage::remove_token_royalty(&env, token_id);
+    }
+
+    /// Royalty receiver and amount owed on a sale of `token_id` at `sale_price`.
+    /// With no royalty configured the admin is returned with a zero amount.
+    pub fn royalty_info(
+        env: Env,
+        token_id: u64,
+        sale_price: i128,
+    ) -> Result<(Address, i128), ContractError> {
+        if sale_price < 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        storage::get_owner(&env, token_id);
+
+        match storage::get_token_royalty(&env, token_id).or(storage::get_default_royalty(&env)) {
+            Some(royalty) => Ok((royalty.receiver.clone(), royalty.amount_for(sale_price))),
+            None => Ok((storage::get_admin(&env), 0)),
+        }
     }
 
     /// Get the owner of a specific token
@@ -126,28 +788,46 @@ impl PlayerCardContract {
         storage::get_owner(&env, token_id)
     }
 
-    /// Get the metadata URI for a specific token
+    /// Get the metadata URI for a specific token: its own URI if it has
+    /// one, else the collection base URI followed by the id
     pub fn token_uri(env: Env, token_id: u64) -> String {
-        storage::get_token_uri(&env, token_id)
+        resolve_token_uri(&env, token_id)
     }
 
-    /// Get total number of tokens minted
+    /// Get total number of cards in existence (minted minus burned)
     pub fn total_supply(env: Env) -> u64 {
-        storage::get_next_token_id(&env) - 1
+        storage::get_total_supply(&env)
     }
 
-    /// Get all tokens owned by a specific address
+    /// Get all tokens owned by a specific address. Reads one entry per card;
+    /// large holders should be listed with `tokens_of_owner_page`.
     pub fn tokens_of_owner(env: Env, owner: Address) -> Vec<u64> {
         storage::get_tokens_of_owner(&env, owner)
     }
 
+    /// Number of cards `owner` holds
+    pub fn balance_of(env: Env, owner: Address) -> u32 {
+        storage::get_balance(&env, &owner)
+    }
+
+    /// Up to `limit` of `owner`'s cards starting at `offset`; `limit` is
+    /// capped at `MAX_PAGE_SIZE`. Order is not stable across transfers.
+    pub fn tokens_of_owner_page(env: Env, owner: Address, offset: u32, limit: u32) -> Vec<u64> {
+        storage::get_tokens_of_owner_page(&env, &owner, offset, limit.min(MAX_PAGE_SIZE))
+    }
+
+    /// Up to `limit` live cards starting at `offset`; `limit` is capped at
+    /// `MAX_PAGE_SIZE`. Order is not stable across burns.
+    pub fn all_tokens_page(env: Env, offset: u32, limit: u32) -> Vec<u64> {
+        storage::get_all_tokens_page(&env, offset, limit.min(MAX_PAGE_SIZE))
+    }
+
     // ===== BACKEND VERIFICATION GETTERS =====
 
     /// Get user's NFT balance (number of tokens owned)
     /// Read-only getter for backend verification
     pub fn get_user_nft_balance(env: Env, user: Address) -> u64 {
-        let tokens = storage::get_tokens_of_owner(&env, user.clone());
-        tokens.len() as u64
+        storage::get_balance(&env, &user) as u64
     }
 
     /// Get user's NFT portfolio summary
@@ -159,7 +839,7 @@ impl PlayerCardContract {
     /// Get NFT contract statistics
     /// Read-only getter for backend verification
     pub fn get_nft_contract_stats(env: Env) -> (u64, Address) {
-        let total_supply = storage::get_next_token_id(&env) - 1;
+        let total_supply = storage::get_total_supply(&env);
         let admin = storage::get_admin(&env);
         (total_supply, admin)
     }
@@ -167,15 +847,14 @@ impl PlayerCardContract {
     /// Check if a token exists
     /// Read-only getter for backend verification
     pub fn token_exists(env: Env, token_id: u64) -> bool {
-        let key = (String::from_str(&env, "TOKEN_OWNER"), token_id);
-        env.storage().instance().has(&key)
+        storage::has_owner(&env, token_id)
     }
 
     /// Get token metadata in a structured format
     /// Read-only getter for backend verification
     pub fn get_token_metadata(env: Env, token_id: u64) -> (Address, String) {
         let owner = storage::get_owner(&env, token_id);
-        let uri = storage::get_token_uri(&env, token_id);
+        let uri = resolve_token_uri(&env, token_id);
         (owner, uri)
     }
 
@@ -198,10 +877,331 @@ impl PlayerCardContract {
 
         let mut token_metadata = Vec::new(&env);
         for token_id in token_ids.iter() {
-            let uri = storage::get_token_uri(&env, token_id);
+            let uri = resolve_token_uri(&env, token_id);
             token_metadata.push_back((token_id, uri));
         }
 
         (balance, token_metadata)
     }
 }
+
+// A card's stored URI wins; an empty one falls back to the collection's
+// base URI, when set.
+fn resolve_token_uri(env: &Env, token_id: u64) -> String {
+    let token_uri = storage::get_token_uri(env, token_id);
+    match storage::get_collection_metadata(env) {
+        Some(metadata) if token_uri.is_empty() => metadata.token_uri(env, token_id),
+        _ => token_uri,
+    }
+}
+
+fn collection_field(env: &Env, field: impl FnOnce(CollectionMetadata) -> String) -> String {
+    storage::get_collection_metadata(env)
+        .map(field)
+        .unwrap_or_else(|| String::from_str(env, ""))
+}
+
+// Assigns the next id to `to` and emits its mint event.
+fn mint_token(
+    env: &Env,
+    to: &Address,
+    token_uri: &String,
+    attributes: Option<CardAttributes>,
+    transferable: bool,
+) -> Result<u64, ContractError> {
+    let token_id = storage::get_next_token_id(env);
+    if let Some(attributes) = attributes {
+        attributes.validate()?;
+        assign_rarity(env, attributes.rarity)?;
+        storage::set_card_attributes(env, token_id, &attributes);
+    }
+    let total_supply = storage::get_total_supply(env);
+    storage::increment_next_token_id(env);
+    storage::set_total_supply(env, total_supply + 1);
+
+    storage::set_owner(env, token_id, to);
+    storage::set_token_uri(env, token_id, token_uri);
+    storage::set_transferable(env, token_id, transferable);
+
+    let event = create_nft_mint_event(
+        env,
+        U256::from_u32(env, token_id as u32),
+        to.clone(),
+        token_uri.clone(),
+        env.current_contract_address(),
+        Symbol::new(env, "PLAYER_CARD"),
+        None,
+    );
+
+    let mut event_with_timestamp = event;
+    event_with_timestamp.timestamp = env.ledger().timestamp();
+
+    publish_nft_event(env, event_with_timestamp);
+
+    Ok(token_id)
+}
+
+fn record_mints(env: &Env, minter: &Address, count: u64) {
+    let minted = storage::get_minter_mints(env, minter) + count;
+    storage::set_minter_mints(env, minter, minted);
+}
+
+// Attributes of the card minted from `inputs`, which share a rarity.
+fn fused_attributes(inputs: &Vec<CardAttributes>) -> Result<CardAttributes, ContractError> {
+    let first = inputs.get_unchecked(0);
+    let rarity = first.rarity.next().ok_or(ContractError::InvalidConfig)?;
+
+    let mut rating_sum = 0;
+    let mut season = 0;
+    let mut edition = 0;
+    for attributes in inputs.iter() {
+        rating_sum += attributes.rating;
+        season = season.max(attributes.season);
+        edition = edition.max(attributes.edition);
+    }
+    let rating = (rating_sum / inputs.len() + FUSION_RATING_BONUS).min(MAX_RATING);
+
+    Ok(CardAttributes {
+        rarity,
+        position: first.position,
+        rating,
+        season,
+        edition,
+    })
+}
+
+// Locked, frozen and rented cards cannot be transferred, burned or fused.
+fn require_movable(env: &Env, token_id: u64) -> Result<(), ContractError> {
+    if storage::get_locker(env, token_id).is_some()
+        || storage::is_frozen(env, token_id)
+        || active_rental(env, token_id).is_some()
+    {
+        return Err(ContractError::TokenNotTransferable);
+    }
+    Ok(())
+}
+
+// The card's running rental. An expired one is cleared on the way, ending
+// the rental on the first call that touches the card after expiry.
+fn active_rental(env: &Env, token_id: u64) -> Option<Rental> {
+    let rental = storage::get_rental(env, token_id)?;
+    if rental.expires_at > env.ledger().timestamp() {
+        return Some(rental);
+    }
+    end_rental(env, token_id);
+    None
+}
+
+fn end_rental(env: &Env, token_id: u64) {
+    if let Some(rental) = storage::get_rental(env, token_id) {
+        storage::remove_rental(env, token_id);
+        publish_rental_ended_event(env, token_id, &rental.user);
+    }
+}
+
+// Counts one more card of `rarity`, failing once its cap is reached.
+fn assign_rarity(env: &Env, rarity: Rarity) -> Result<(), ContractError> {
+    let minted = storage::get_rarity_minted(env, rarity);
+    if storage::get_rarity_cap(env, rarity).is_some_and(|cap| minted >= cap) {
+        return Err(ContractError::ExceedsLimit);
+    }
+    storage::set_rarity_minted(env, rarity, minted + 1);
+    Ok(())
+}
+
+// Clears every per-token entry, shrinks the supply and emits the burn event.
+fn burn_token(env: &Env, token_id: u64) {
+    let owner = storage::get_owner(env, token_id);
+
+    storage::remove_owner(env, token_id);
+    storage::remove_token_uri(env, token_id);
+    storage::remove_approved(env, token_id);
+    storage::remove_token_royalty(env, token_id);
+    storage::remove_card_attributes(env, token_id);
+    storage::set_transferable(env, token_id, true);
+    storage::remove_locker(env, token_id);
+    storage::set_frozen(env, token_id, false);
+    storage::remove_rental_listing(env, token_id);
+    storage::remove_rental(env, token_id);
+    storage::set_total_supply(env, storage::get_total_supply(env) - 1);
+
+    publish_burn_event(
+        env,
+        NFTBurnEvent {
+            version: EVENT_SCHEMA_VERSION,
+            token_id: U256::from_u32(env, token_id as u32),
+            owner,
+            nft_contract: env.current_contract_address(),
+            timestamp: env.ledger().timestamp(),
+        },
+    );
+}
+
+// Checks the card may change hands, then moves it.
+fn transfer_token(env: &Env, to: &Address, token_id: u64) -> Result<(), ContractError> {
+    if !storage::is_transferable(env, token_id) {
+        return Err(ContractError::TokenNotTransferable);
+    }
+    require_movable(env, token_id)?;
+    move_token(env, to, token_id);
+    Ok(())
+}
+
+// Moves the card, drops any single-token approval and emits the transfer
+// event. Returns the previous owner.
+fn move_token(env: &Env, to: &Address, token_id: u64) -> Address {
+    let from = storage::get_owner(env, token_id);
+    storage::set_owner(env, token_id, to);
+    storage::remove_approved(env, token_id);
+
+    publish_transfer_event(
+        env,
+        NFTTransferEvent {
+            version: EVENT_SCHEMA_VERSION,
+            token_id: U256::from_u32(env, token_id as u32),
+            from: from.clone(),
+            to: to.clone(),
+            nft_contract: env.current_contract_address(),
+            timestamp: env.ledger().timestamp(),
+        },
+    );
+    from
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_approval_event(env: &Env, owner: &Address, spender: &Address, token_id: u64) {
+    env.events().publish(
+        (Symbol::new(env, "approval"), owner.clone(), spender.clone()),
+        token_id,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_approval_for_all_event(env: &Env, owner: &Address, operator: &Address, approved: bool) {
+    env.events().publish(
+        (
+            Symbol::new(env, "approval_for_all"),
+            owner.clone(),
+            operator.clone(),
+        ),
+        approved,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_attributes_event(env: &Env, token_id: u64, attributes: &CardAttributes) {
+    env.events().publish(
+        (Symbol::new(env, "attributes_updated"), token_id),
+        attributes.clone(),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_fused_event(env: &Env, owner: &Address, consumed: &Vec<u64>, produced: u64) {
+    env.events().publish(
+        (Symbol::new(env, "CardFused"), owner.clone()),
+        (consumed.clone(), produced),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_transferable_event(env: &Env, token_id: u64, transferable: bool) {
+    env.events().publish(
+        (Symbol::new(env, "transferable_updated"), token_id),
+        transferable,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_lock_event(env: &Env, token_id: u64, locker: Option<Address>) {
+    env.events()
+        .publish((Symbol::new(env, "lock_updated"), token_id), locker);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_rental_listing_event(env: &Env, token_id: u64, listing: Option<RentalListing>) {
+    env.events()
+        .publish((Symbol::new(env, "rental_listing"), token_id), listing);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_rented_event(env: &Env, token_id: u64, rental: &Rental, price: i128) {
+    env.events().publish(
+        (
+            Symbol::new(env, "card_rented"),
+            token_id,
+            rental.user.clone(),
+        ),
+        (price, rental.expires_at),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_rental_ended_event(env: &Env, token_id: u64, user: &Address) {
+    env.events()
+        .publish((Symbol::new(env, "rental_ended"), token_id), user.clone());
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_frozen_event(env: &Env, token_id: u64, frozen: bool) {
+    env.events()
+        .publish((Symbol::new(env, "frozen_updated"), token_id), frozen);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_clawback_event(
+    env: &Env,
+    token_id: u64,
+    from: &Address,
+    custody: &Address,
+    reason_hash: &BytesN<32>,
+) {
+    env.events().publish(
+        (Symbol::new(env, "card_clawback"), token_id, from.clone()),
+        (custody.clone(), reason_hash.clone()),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_minter_event(env: &Env, minter: &Address, allowed: bool) {
+    env.events().publish(
+        (Symbol::new(env, "minter_updated"), minter.clone()),
+        allowed,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_voucher_event(env: &Env, nonce: u64, token_id: u64) {
+    env.events()
+        .publish((Symbol::new(env, "voucher_redeemed"), nonce), token_id);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_initialized_event(env: &Env, admin: &Address) {
+    env.events()
+        .publish((Symbol::new(env, "initialized"),), admin.clone());
+}
+
+fn publish_transfer_event(env: &Env, event: NFTTransferEvent) {
+    let subject = event.token_id.clone();
+    publish_versioned_event(
+        env,
+        (NFT_TRANSFER_EVENT,),
+        NFT_TRANSFER_EVENT,
+        subject,
+        event,
+    );
+}
+
+fn publish_burn_event(env: &Env, event: NFTBurnEvent) {
+    let subject = event.token_id.clone();
+    publish_versioned_event(env, (NFT_BURN_EVENT,), NFT_BURN_EVENT, subject, event);
+}
+
+fn publish_nft_event(env: &Env, event: NFTMintEvent) {
+    let subject = event.to.clone();
+    publish_versioned_event(env, (NFT_MINT_EVENT,), NFT_MINT_EVENT, subject, event);
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,62 @@
+use common::ContractError;
+use soroban_sdk::{contracttype, Env, String};
+
+/// Longest `base_uri` accepted, so composed token URIs fit a fixed buffer.
+pub const MAX_BASE_URI_LEN: u32 = 200;
+
+// Decimal digits of `u64::MAX`.
+const MAX_TOKEN_ID_DIGITS: usize = 20;
+
+/// Collection-level details shown by wallets and marketplaces.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CollectionMetadata {
+    pub name: String,
+    pub symbol: String,
+    /// Prefix of every card's URI that has no override
+    pub base_uri: String,
+    /// Collection-level metadata document
+    pub contract_uri: String,
+}
+
+impl CollectionMetadata {
+    pub fn new(
+        name: String,
+        symbol: String,
+        base_uri: String,
+        contract_uri: String,
+    ) -> Result<Self, ContractError> {
+        if name.is_empty() || symbol.is_empty() || base_uri.len() > MAX_BASE_URI_LEN {
+            return Err(ContractError::InvalidConfig);
+        }
+        Ok(CollectionMetadata {
+            name,
+            symbol,
+            base_uri,
+            contract_uri,
+        })
+    }
+
+    /// `base_uri` followed by `token_id` in decimal.
+    pub fn token_uri(&self, env: &Env, token_id: u64) -> String {
+        let mut buf = [0u8; MAX_BASE_URI_LEN as usize + MAX_TOKEN_ID_DIGITS];
+        let base_len = self.base_uri.len() as usize;
+        self.base_uri.copy_into_slice(&mut buf[..base_len]);
+
+        let mut digits = [0u8; MAX_TOKEN_ID_DIGITS];
+        let mut count = 0;
+        let mut rest = token_id;
+        loop {
+            digits[count] = b'0' + (rest % 10) as u8;
+            count += 1;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+        for (offset, digit) in digits[..count].iter().rev().enumerate() {
+            buf[base_len + offset] = *digit;
+        }
+        String::from_bytes(env, &buf[..base_len + count])
+    }
+}
@@ -0,0 +1,23 @@
+use soroban_sdk::{contracttype, Address};
+
+/// Standing offer to rent a card out. It stays up between rentals until the
+/// owner withdraws it, and lapses if the card changes hands.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RentalListing {
+    pub owner: Address,
+    pub payment_token: Address,
+    /// Paid to the owner up front for each rental
+    pub price: i128,
+    /// Length of each rental, in seconds
+    pub duration: u64,
+}
+
+/// The renter holds the card's `user_of` role until `expires_at`; the owner
+/// keeps ownership throughout.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rental {
+    pub user: Address,
+    pub expires_at: u64,
+}
@@ -0,0 +1,26 @@
+use common::ContractError;
+use soroban_sdk::{contracttype, Address};
+
+/// Royalties are expressed in basis points of the sale price.
+pub const MAX_ROYALTY_BPS: u32 = 10_000;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Royalty {
+    pub receiver: Address,
+    pub bps: u32,
+}
+
+impl Royalty {
+    pub fn new(receiver: Address, bps: u32) -> Result<Self, ContractError> {
+        if bps > MAX_ROYALTY_BPS {
+            return Err(ContractError::InvalidConfig);
+        }
+        Ok(Royalty { receiver, bps })
+    }
+
+    /// Amount owed on `sale_price`, rounded down.
+    pub fn amount_for(&self, sale_price: i128) -> i128 {
+        sale_price * self.bps as i128 / MAX_ROYALTY_BPS as i128
+    }
+}
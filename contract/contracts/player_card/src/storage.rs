@@ -1,39 +1,92 @@
-use soroban_sdk::{Address, Env, String, Vec};
+use soroban_sdk::{contracttype, Address, BytesN, Env, IntoVal, String, TryFromVal, Val, Vec};
 
-const ADMIN: &str = "ADMIN";
-const NEXT_TOKEN_ID: &str = "NEXT_TOKEN_ID";
-const TOKEN_OWNER: &str = "TOKEN_OWNER";
-const TOKEN_URI: &str = "TOKEN_URI";
-const OWNER_TOKENS: &str = "OWNER_TOKENS";
+use crate::{CardAttributes, CollectionMetadata, Rarity, Rental, RentalListing, Royalty};
+
+/// Collection-wide settings, kept in instance storage.
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    NextTokenId,
+    TotalSupply,
+    DefaultRoyalty,
+    CollectionMetadata,
+    RarityCap(Rarity),
+    RarityMinted(Rarity),
+    VoucherSigner,
+    /// Receives clawed-back cards
+    Custody,
+    Minter(Address),
+    MinterMints(Address),
+    /// Length of the `TokenAt` index
+    IndexedTokens,
+}
+
+/// Per-card and per-holder state, kept in persistent storage so the
+/// collection is not bounded by the instance size.
+#[contracttype]
+#[derive(Clone)]
+pub enum TokenKey {
+    Owner(u64),
+    Uri(u64),
+    /// Cards held by an address
+    Balance(Address),
+    /// The holder's cards, by position `0..Balance`
+    OwnerTokenAt(Address, u32),
+    /// Position of a card among its holder's cards
+    OwnerTokenIndex(u64),
+    /// Every live card, by position `0..IndexedTokens`
+    TokenAt(u32),
+    /// Position of a card in `TokenAt`
+    TokenIndex(u64),
+    Royalty(u64),
+    Approval(u64),
+    OperatorApproval(Address, Address),
+    Attributes(u64),
+    Soulbound(u64),
+    Locker(u64),
+    Frozen(u64),
+    RentalListing(u64),
+    Rental(u64),
+    VoucherNonce(u64),
+}
+
+fn get_token_entry<V: TryFromVal<Env, Val>>(env: &Env, key: &TokenKey) -> Option<V> {
+    env.storage().persistent().get(key)
+}
+
+fn set_token_entry<V: IntoVal<Env, Val>>(env: &Env, key: &TokenKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    common::bump_persistent(env, key);
+}
+
+fn remove_token_entry(env: &Env, key: &TokenKey) {
+    env.storage().persistent().remove(key);
+}
 
 pub fn has_admin(env: &Env) -> bool {
-    env.storage().instance().has(&String::from_str(env, ADMIN))
+    env.storage().instance().has(&DataKey::Admin)
 }
 
 pub fn get_admin(env: &Env) -> Address {
-    env.storage()
-        .instance()
-        .get(&String::from_str(env, ADMIN))
-        .unwrap()
+    env.storage().instance().get(&DataKey::Admin).unwrap()
 }
 
 pub fn set_admin(env: &Env, admin: &Address) {
-    env.storage()
-        .instance()
-        .set(&String::from_str(env, ADMIN), admin);
+    env.storage().instance().set(&DataKey::Admin, admin);
 }
 
 pub fn get_next_token_id(env: &Env) -> u64 {
     env.storage()
         .instance()
-        .get(&String::from_str(env, NEXT_TOKEN_ID))
+        .get(&DataKey::NextTokenId)
         .unwrap_or(1u64)
 }
 
 pub fn set_next_token_id(env: &Env, token_id: u64) {
     env.storage()
         .instance()
-        .set(&String::from_str(env, NEXT_TOKEN_ID), &token_id);
+        .set(&DataKey::NextTokenId, &token_id);
 }
 
 pub fn increment_next_token_id(env: &Env) {
@@ -41,60 +94,492 @@ pub fn increment_next_token_id(env: &Env) {
     set_next_token_id(env, current_id + 1);
 }
 
+/// Whether `token_id` is minted and not burned. Reads the same entry as
+/// `get_owner`, so the two always agree.
+pub fn has_owner(env: &Env, token_id: u64) -> bool {
+    env.storage().persistent().has(&TokenKey::Owner(token_id))
+}
+
 pub fn get_owner(env: &Env, token_id: u64) -> Address {
-    let key = (String::from_str(env, TOKEN_OWNER), token_id);
-    env.storage()
-        .instance()
-        .get(&key)
-        .unwrap_or_else(|| panic!("token not found"))
+    let key = TokenKey::Owner(token_id);
+    common::bump_persistent(env, &key);
+    get_token_entry(env, &key).unwrap_or_else(|| panic!("token not found"))
 }
 
 pub fn set_owner(env: &Env, token_id: u64, owner: &Address) {
-    let key = (String::from_str(env, TOKEN_OWNER), token_id);
+    let key = TokenKey::Owner(token_id);
 
-    if let Some(old_owner) = env.storage().instance().get::<_, Address>(&key) {
-        remove_token_from_owner(env, &old_owner, token_id);
+    match get_token_entry::<Address>(env, &key) {
+        Some(old_owner) => remove_token_from_owner(env, &old_owner, token_id),
+        None => add_token_to_index(env, token_id),
     }
 
-    env.storage().instance().set(&key, owner);
+    set_token_entry(env, &key, owner);
     add_token_to_owner(env, owner, token_id);
+    // Collection settings live in the instance; keep it alive while cards move.
+    common::bump_instance(env);
+}
+
+/// Forget a burned token's owner and drop it from both indexes.
+pub fn remove_owner(env: &Env, token_id: u64) {
+    let key = TokenKey::Owner(token_id);
+
+    if let Some(owner) = get_token_entry::<Address>(env, &key) {
+        remove_token_from_owner(env, &owner, token_id);
+        remove_token_from_index(env, token_id);
+    }
+    remove_token_entry(env, &key);
 }
 
 pub fn get_token_uri(env: &Env, token_id: u64) -> String {
-    let key = (String::from_str(env, TOKEN_URI), token_id);
+    get_token_entry(env, &TokenKey::Uri(token_id)).unwrap_or_else(|| panic!("token not found"))
+}
+
+pub fn set_token_uri(env: &Env, token_id: u64, token_uri: &String) {
+    set_token_entry(env, &TokenKey::Uri(token_id), token_uri);
+}
+
+pub fn remove_token_uri(env: &Env, token_id: u64) {
+    remove_token_entry(env, &TokenKey::Uri(token_id));
+}
+
+/// Live cards. Collections minted before supply was tracked fall back to
+/// the number of ids handed out, when nothing had been burned yet.
+pub fn get_total_supply(env: &Env) -> u64 {
     env.storage()
         .instance()
-        .get(&key)
-        .unwrap_or_else(|| panic!("token not found"))
+        .get(&DataKey::TotalSupply)
+        .unwrap_or_else(|| get_next_token_id(env) - 1)
 }
 
-pub fn set_token_uri(env: &Env, token_id: u64, token_uri: &String) {
-    let key = (String::from_str(env, TOKEN_URI), token_id);
-    env.storage().instance().set(&key, token_uri);
+pub fn set_total_supply(env: &Env, total_supply: u64) {
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalSupply, &total_supply);
 }
 
-pub fn get_tokens_of_owner(env: &Env, owner: Address) -> Vec<u64> {
-    let key = (String::from_str(env, OWNER_TOKENS), owner);
+pub fn get_card_attributes(env: &Env, token_id: u64) -> Option<CardAttributes> {
+    get_token_entry(env, &TokenKey::Attributes(token_id))
+}
+
+pub fn set_card_attributes(env: &Env, token_id: u64, attributes: &CardAttributes) {
+    set_token_entry(env, &TokenKey::Attributes(token_id), attributes);
+}
+
+pub fn remove_card_attributes(env: &Env, token_id: u64) {
+    remove_token_entry(env, &TokenKey::Attributes(token_id));
+}
+
+pub fn get_rarity_cap(env: &Env, rarity: Rarity) -> Option<u32> {
+    env.storage().instance().get(&DataKey::RarityCap(rarity))
+}
+
+pub fn set_rarity_cap(env: &Env, rarity: Rarity, cap: Option<u32>) {
+    let key = DataKey::RarityCap(rarity);
+    match cap {
+        Some(cap) => env.storage().instance().set(&key, &cap),
+        None => env.storage().instance().remove(&key),
+    }
+}
+
+/// Cards ever assigned `rarity`. Burning does not free capacity.
+pub fn get_rarity_minted(env: &Env, rarity: Rarity) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::RarityMinted(rarity))
+        .unwrap_or(0)
+}
+
+pub fn set_rarity_minted(env: &Env, rarity: Rarity, minted: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::RarityMinted(rarity), &minted);
+}
+
+/// Cards are transferable unless explicitly marked soulbound.
+pub fn is_transferable(env: &Env, token_id: u64) -> bool {
+    !env.storage()
+        .persistent()
+        .has(&TokenKey::Soulbound(token_id))
+}
+
+pub fn set_transferable(env: &Env, token_id: u64, transferable: bool) {
+    let key = TokenKey::Soulbound(token_id);
+    if transferable {
+        remove_token_entry(env, &key);
+    } else {
+        set_token_entry(env, &key, &true);
+    }
+}
+
+pub fn get_locker(env: &Env, token_id: u64) -> Option<Address> {
+    get_token_entry(env, &TokenKey::Locker(token_id))
+}
+
+pub fn set_locker(env: &Env, token_id: u64, locker: &Address) {
+    set_token_entry(env, &TokenKey::Locker(token_id), locker);
+}
+
+pub fn remove_locker(env: &Env, token_id: u64) {
+    remove_token_entry(env, &TokenKey::Locker(token_id));
+}
+
+pub fn is_frozen(env: &Env, token_id: u64) -> bool {
+    env.storage().persistent().has(&TokenKey::Frozen(token_id))
+}
+
+pub fn set_frozen(env: &Env, token_id: u64, frozen: bool) {
+    let key = TokenKey::Frozen(token_id);
+    if frozen {
+        set_token_entry(env, &key, &true);
+    } else {
+        remove_token_entry(env, &key);
+    }
+}
+
+pub fn get_rental_listing(env: &Env, token_id: u64) -> Option<RentalListing> {
+    get_token_entry(env, &TokenKey::RentalListing(token_id))
+}
+
+pub fn set_rental_listing(env: &Env, token_id: u64, listing: &RentalListing) {
+    set_token_entry(env, &TokenKey::RentalListing(token_id), listing);
+}
+
+pub fn remove_rental_listing(env: &Env, token_id: u64) {
+    remove_token_entry(env, &TokenKey::RentalListing(token_id));
+}
+
+/// The last rental recorded, which may have expired already.
+pub fn get_rental(env: &Env, token_id: u64) -> Option<Rental> {
+    get_token_entry(env, &TokenKey::Rental(token_id))
+}
+
+pub fn set_rental(env: &Env, token_id: u64, rental: &Rental) {
+    set_token_entry(env, &TokenKey::Rental(token_id), rental);
+}
+
+pub fn remove_rental(env: &Env, token_id: u64) {
+    remove_token_entry(env, &TokenKey::Rental(token_id));
+}
+
+pub fn get_custody(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::Custody)
+}
+
+pub fn set_custody(env: &Env, custody: &Address) {
+    env.storage().instance().set(&DataKey::Custody, custody);
+}
+
+pub fn is_minter(env: &Env, minter: &Address) -> bool {
+    env.storage()
+        .instance()
+        .has(&DataKey::Minter(minter.clone()))
+}
+
+pub fn set_minter(env: &Env, minter: &Address, allowed: bool) {
+    let key = DataKey::Minter(minter.clone());
+    if allowed {
+        env.storage().instance().set(&key, &true);
+    } else {
+        env.storage().instance().remove(&key);
+    }
+}
+
+pub fn get_minter_mints(env: &Env, minter: &Address) -> u64 {
     env.storage()
         .instance()
-        .get(&key)
-        .unwrap_or_else(|| Vec::new(env))
+        .get(&DataKey::MinterMints(minter.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_minter_mints(env: &Env, minter: &Address, minted: u64) {
+    env.storage()
+        .instance()
+        .set(&DataKey::MinterMints(minter.clone()), &minted);
+}
+
+pub fn get_voucher_signer(env: &Env) -> Option<BytesN<32>> {
+    env.storage().instance().get(&DataKey::VoucherSigner)
+}
+
+pub fn set_voucher_signer(env: &Env, signer: Option<&BytesN<32>>) {
+    match signer {
+        Some(signer) => env
+            .storage()
+            .instance()
+            .set(&DataKey::VoucherSigner, signer),
+        None => env.storage().instance().remove(&DataKey::VoucherSigner),
+    }
+}
+
+pub fn is_voucher_nonce_used(env: &Env, nonce: u64) -> bool {
+    env.storage()
+        .persistent()
+        .has(&TokenKey::VoucherNonce(nonce))
+}
+
+pub fn set_voucher_nonce_used(env: &Env, nonce: u64) {
+    set_token_entry(env, &TokenKey::VoucherNonce(nonce), &true);
+}
+
+// ===== ENUMERATION =====
+// Both indexes are position -> id maps with an id -> position back
+// reference. Removal moves the last entry into the freed slot, so inserts
+// and removals touch a constant number of entries but do not keep order.
+
+pub fn get_balance(env: &Env, owner: &Address) -> u32 {
+    get_token_entry(env, &TokenKey::Balance(owner.clone())).unwrap_or(0)
+}
+
+/// Up to `limit` of `owner`'s cards, starting at position `offset`.
+pub fn get_tokens_of_owner_page(env: &Env, owner: &Address, offset: u32, limit: u32) -> Vec<u64> {
+    let end = offset.saturating_add(limit).min(get_balance(env, owner));
+    let mut page = Vec::new(env);
+    for index in offset..end {
+        let key = TokenKey::OwnerTokenAt(owner.clone(), index);
+        page.push_back(get_token_entry(env, &key).unwrap());
+    }
+    page
+}
+
+pub fn get_tokens_of_owner(env: &Env, owner: Address) -> Vec<u64> {
+    get_tokens_of_owner_page(env, &owner, 0, u32::MAX)
 }
 
 pub fn add_token_to_owner(env: &Env, owner: &Address, token_id: u64) {
-    let key = (String::from_str(env, OWNER_TOKENS), owner);
-    let mut tokens = get_tokens_of_owner(env, owner.clone());
-    tokens.push_back(token_id);
-    env.storage().instance().set(&key, &tokens);
+    let index = get_balance(env, owner);
+    set_token_entry(
+        env,
+        &TokenKey::OwnerTokenAt(owner.clone(), index),
+        &token_id,
+    );
+    set_token_entry(env, &TokenKey::OwnerTokenIndex(token_id), &index);
+    set_token_entry(env, &TokenKey::Balance(owner.clone()), &(index + 1));
 }
 
 pub fn remove_token_from_owner(env: &Env, owner: &Address, token_id: u64) {
-    let key = (String::from_str(env, OWNER_TOKENS), owner);
-    let mut tokens = get_tokens_of_owner(env, owner.clone());
+    let index_key = TokenKey::OwnerTokenIndex(token_id);
+    let Some(index) = get_token_entry::<u32>(env, &index_key) else {
+        return;
+    };
+    let last = get_balance(env, owner) - 1;
+    if index != last {
+        let moved: u64 =
+            get_token_entry(env, &TokenKey::OwnerTokenAt(owner.clone(), last)).unwrap();
+        set_token_entry(env, &TokenKey::OwnerTokenAt(owner.clone(), index), &moved);
+        set_token_entry(env, &TokenKey::OwnerTokenIndex(moved), &index);
+    }
+    remove_token_entry(env, &TokenKey::OwnerTokenAt(owner.clone(), last));
+    remove_token_entry(env, &index_key);
+    if last == 0 {
+        remove_token_entry(env, &TokenKey::Balance(owner.clone()));
+    } else {
+        set_token_entry(env, &TokenKey::Balance(owner.clone()), &last);
+    }
+}
+
+pub fn get_indexed_tokens(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::IndexedTokens)
+        .unwrap_or(0)
+}
+
+fn set_indexed_tokens(env: &Env, count: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::IndexedTokens, &count);
+}
+
+/// Up to `limit` live cards, starting at position `offset`.
+pub fn get_all_tokens_page(env: &Env, offset: u32, limit: u32) -> Vec<u64> {
+    let end = offset.saturating_add(limit).min(get_indexed_tokens(env));
+    let mut page = Vec::new(env);
+    for index in offset..end {
+        page.push_back(get_token_entry(env, &TokenKey::TokenAt(index)).unwrap());
+    }
+    page
+}
+
+fn add_token_to_index(env: &Env, token_id: u64) {
+    let index = get_indexed_tokens(env);
+    set_token_entry(env, &TokenKey::TokenAt(index), &token_id);
+    set_token_entry(env, &TokenKey::TokenIndex(token_id), &index);
+    set_indexed_tokens(env, index + 1);
+}
+
+fn remove_token_from_index(env: &Env, token_id: u64) {
+    let index_key = TokenKey::TokenIndex(token_id);
+    let Some(index) = get_token_entry::<u32>(env, &index_key) else {
+        return;
+    };
+    let last = get_indexed_tokens(env) - 1;
+    if index != last {
+        let moved: u64 = get_token_entry(env, &TokenKey::TokenAt(last)).unwrap();
+        set_token_entry(env, &TokenKey::TokenAt(index), &moved);
+        set_token_entry(env, &TokenKey::TokenIndex(moved), &index);
+    }
+    remove_token_entry(env, &TokenKey::TokenAt(last));
+    remove_token_entry(env, &index_key);
+    set_indexed_tokens(env, last);
+}
+
+pub fn get_collection_metadata(env: &Env) -> Option<CollectionMetadata> {
+    env.storage().instance().get(&DataKey::CollectionMetadata)
+}
+
+pub fn set_collection_metadata(env: &Env, metadata: &CollectionMetadata) {
+    env.storage()
+        .instance()
+        .set(&DataKey::CollectionMetadata, metadata);
+}
+
+pub fn get_default_royalty(env: &Env) -> Option<Royalty> {
+    env.storage().instance().get(&DataKey::DefaultRoyalty)
+}
+
+pub fn set_default_royalty(env: &Env, royalty: &Royalty) {
+    env.storage()
+        .instance()
+        .set(&DataKey::DefaultRoyalty, royalty);
+}
+
+pub fn get_token_royalty(env: &Env, token_id: u64) -> Option<Royalty> {
+    get_token_entry(env, &TokenKey::Royalty(token_id))
+}
+
+pub fn set_token_royalty(env: &Env, token_id: u64, royalty: &Royalty) {
+    set_token_entry(env, &TokenKey::Royalty(token_id), royalty);
+}
+
+pub fn remove_token_royalty(env: &Env, token_id: u64) {
+    remove_token_entry(env, &TokenKey::Royalty(token_id));
+}
+
+pub fn get_approved(env: &Env, token_id: u64) -> Option<Address> {
+    get_token_entry(env, &TokenKey::Approval(token_id))
+}
+
+pub fn set_approved(env: &Env, token_id: u64, spender: &Address) {
+    set_token_entry(env, &TokenKey::Approval(token_id), spender);
+}
+
+pub fn remove_approved(env: &Env, token_id: u64) {
+    remove_token_entry(env, &TokenKey::Approval(token_id));
+}
+
+pub fn is_approved_for_all(env: &Env, owner: &Address, operator: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&TokenKey::OperatorApproval(owner.clone(), operator.clone()))
+}
+
+pub fn set_approval_for_all(env: &Env, owner: &Address, operator: &Address, approved: bool) {
+    let key = TokenKey::OperatorApproval(owner.clone(), operator.clone());
+    if approved {
+        set_token_entry(env, &key, &true);
+    } else {
+        remove_token_entry(env, &key);
+    }
+}
+
+// ===== LEGACY LAYOUT =====
+// Storage version 1 kept everything in instance storage under
+// `(String, id)` keys. `migrate` moves it to the typed keys above.
+
+pub mod legacy {
+    use super::*;
+
+    const RARITIES: [Rarity; 4] = [
+        Rarity::Common,
+        Rarity::Rare,
+        Rarity::Epic,
+        Rarity::Legendary,
+    ];
+
+    fn take<K, V>(env: &Env, key: &K) -> Option<V>
+    where
+        K: IntoVal<Env, Val>,
+        V: TryFromVal<Env, Val>,
+    {
+        let instance = env.storage().instance();
+        let value = instance.get(key);
+        instance.remove(key);
+        value
+    }
+
+    fn named(env: &Env, name: &str) -> String {
+        String::from_str(env, name)
+    }
+
+    pub fn get_admin(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&named(env, "ADMIN"))
+    }
+
+    /// Move every version 1 entry that can be enumerated. Operator
+    /// approvals, minters and redeemed voucher nonces are keyed by values
+    /// the contract cannot list: approvals and minters must be granted
+    /// again, and old vouchers should be left to expire.
+    pub fn migrate(env: &Env) {
+        if let Some(admin) = take::<_, Address>(env, &named(env, "ADMIN")) {
+            set_admin(env, &admin);
+        }
+        let Some(next_token_id) = take::<_, u64>(env, &named(env, "NEXT_TOKEN_ID")) else {
+            return;
+        };
+        set_next_token_id(env, next_token_id);
+        if let Some(total_supply) = take::<_, u64>(env, &named(env, "TOTAL_SUPPLY")) {
+            set_total_supply(env, total_supply);
+        }
+        if let Some(royalty) = take::<_, Royalty>(env, &named(env, "DEFAULT_ROYALTY")) {
+            set_default_royalty(env, &royalty);
+        }
+        if let Some(signer) = take::<_, BytesN<32>>(env, &named(env, "VOUCHER_SIGNER")) {
+            set_voucher_signer(env, Some(&signer));
+        }
+        for rarity in RARITIES {
+            if let Some(cap) = take::<_, u32>(env, &(named(env, "RARITY_CAP"), rarity)) {
+                set_rarity_cap(env, rarity, Some(cap));
+            }
+            if let Some(minted) = take::<_, u32>(env, &(named(env, "RARITY_MINTED"), rarity)) {
+                set_rarity_minted(env, rarity, minted);
+            }
+        }
+
+        for token_id in 1..next_token_id {
+            migrate_token(env, token_id);
+        }
+    }
+
+    fn migrate_token(env: &Env, token_id: u64) {
+        let Some(owner) = take::<_, Address>(env, &(named(env, "TOKEN_OWNER"), token_id)) else {
+            return;
+        };
+        // The holder's list is rebuilt into the new indexes by `set_owner`
+        env.storage()
+            .instance()
+            .remove(&(named(env, "OWNER_TOKENS"), owner.clone()));
+        set_owner(env, token_id, &owner);
 
-    let index = tokens.iter().position(|id| id == token_id);
-    if let Some(index) = index {
-        tokens.remove(index as u32);
-        env.storage().instance().set(&key, &tokens);
+        if let Some(uri) = take::<_, String>(env, &(named(env, "TOKEN_URI"), token_id)) {
+            set_token_uri(env, token_id, &uri);
+        }
+        if let Some(attributes) =
+            take::<_, CardAttributes>(env, &(named(env, "CARD_ATTRIBUTES"), token_id))
+        {
+            set_card_attributes(env, token_id, &attributes);
+        }
+        if let Some(royalty) = take::<_, Royalty>(env, &(named(env, "TOKEN_ROYALTY"), token_id)) {
+            set_token_royalty(env, token_id, &royalty);
+        }
+        if let Some(spender) = take::<_, Address>(env, &(named(env, "TOKEN_APPROVAL"), token_id)) {
+            set_approved(env, token_id, &spender);
+        }
+        if take::<_, bool>(env, &(named(env, "SOULBOUND"), token_id)).is_some() {
+            set_transferable(env, token_id, false);
+        }
+        if let Some(locker) = take::<_, Address>(env, &(named(env, "TOKEN_LOCKER"), token_id)) {
+            set_locker(env, token_id, &locker);
+        }
     }
 }
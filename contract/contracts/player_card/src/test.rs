@@ -0,0 +1,856 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{
+    testutils::{Address as _, Events, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, BytesN, Env, IntoVal, String, Symbol, Vec,
+};
+
+fn setup(env: &Env) -> (PlayerCardContractClient<'_>, Address) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let contract_id = env.register(PlayerCardContract, ());
+    let client = PlayerCardContractClient::new(env, &contract_id);
+    client.initialize(&admin);
+
+    (client, admin)
+}
+
+fn mint(env: &Env, client: &PlayerCardContractClient<'_>, seed: u8, to: &Address) -> u64 {
+    client.mint(
+        &BytesN::from_array(env, &[seed; 32]),
+        to,
+        &String::from_str(env, "ipfs://card"),
+        &None,
+        &true,
+        &None,
+    )
+}
+
+#[test]
+fn royalty_info_uses_default_and_token_overrides() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let platform = Address::generate(&env);
+    let artist = Address::generate(&env);
+    let card = mint(&env, &client, 1, &owner);
+    let other_card = mint(&env, &client, 2, &owner);
+
+    // Nothing configured yet
+    assert_eq!(client.royalty_info(&card, &10_000), (admin, 0));
+
+    client.set_default_royalty(&500, &platform);
+    assert_eq!(client.royalty_info(&card, &10_000), (platform.clone(), 500));
+
+    client.set_token_royalty(&card, &1_000, &artist);
+    assert_eq!(client.royalty_info(&card, &10_000), (artist, 1_000));
+    assert_eq!(
+        client.royalty_info(&other_card, &10_000),
+        (platform.clone(), 500)
+    );
+
+    client.reset_token_royalty(&card);
+    assert_eq!(client.royalty_info(&card, &999), (platform, 49));
+}
+
+#[test]
+fn royalty_is_capped_at_full_sale_price() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let receiver = Address::generate(&env);
+    let card = mint(&env, &client, 1, &receiver);
+
+    assert_eq!(
+        client.try_set_default_royalty(&(MAX_ROYALTY_BPS + 1), &receiver),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    assert_eq!(
+        client.try_royalty_info(&card, &-1),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
+
+#[test]
+fn approved_spender_can_transfer_once() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let market = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let card = mint(&env, &client, 1, &owner);
+
+    assert_eq!(
+        client.try_transfer_from(&market, &owner, &buyer, &card),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_approve(&buyer, &market, &card),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    client.approve(&owner, &market, &card);
+    assert_eq!(client.get_approved(&card), Some(market.clone()));
+
+    client.transfer_from(&market, &owner, &buyer, &card);
+    assert_eq!(client.owner_of(&card), buyer);
+    assert_eq!(client.get_approved(&card), None);
+
+    // The approval did not survive the transfer
+    assert_eq!(
+        client.try_transfer_from(&market, &buyer, &owner, &card),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn operator_can_transfer_any_card_until_revoked() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let escrow = Address::generate(&env);
+    let first = mint(&env, &client, 1, &owner);
+    let second = mint(&env, &client, 2, &owner);
+
+    client.set_approval_for_all(&owner, &escrow, &true);
+    assert!(client.is_approved_for_all(&owner, &escrow));
+
+    client.transfer_from(&escrow, &owner, &escrow, &first);
+    assert_eq!(client.owner_of(&first), escrow);
+
+    // `from` must be the current owner
+    assert_eq!(
+        client.try_transfer_from(&escrow, &escrow, &owner, &second),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    client.set_approval_for_all(&owner, &escrow, &false);
+    assert!(!client.is_approved_for_all(&owner, &escrow));
+    assert_eq!(
+        client.try_transfer_from(&escrow, &owner, &escrow, &second),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn mint_batch_mints_every_card_under_one_hash() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://card");
+    let hash = BytesN::from_array(&env, &[9u8; 32]);
+
+    let ids = client.mint_batch(
+        &hash,
+        &vec![&env, alice.clone(), bob.clone(), alice.clone()],
+        &vec![&env, uri.clone(), uri.clone(), uri.clone()],
+        &None,
+        &true,
+        &None,
+    );
+    assert_eq!(ids, vec![&env, 1, 2, 3]);
+    assert_eq!(client.tokens_of_owner(&alice), vec![&env, 1, 3]);
+    assert_eq!(client.owner_of(&2), bob);
+    assert!(client.is_mint_operation_executed(&hash));
+
+    assert_eq!(
+        client.try_mint_batch(
+            &hash,
+            &vec![&env, bob.clone()],
+            &vec![&env, uri.clone()],
+            &None,
+            &true,
+            &None
+        ),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+    let other = BytesN::from_array(&env, &[10u8; 32]);
+    assert_eq!(
+        client.try_mint_batch(
+            &other,
+            &vec![&env, bob.clone()],
+            &vec![&env],
+            &None,
+            &true,
+            &None
+        ),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+
+    let mut recipients = Vec::new(&env);
+    let mut uris = Vec::new(&env);
+    for _ in 0..=MAX_MINT_BATCH {
+        recipients.push_back(bob.clone());
+        uris.push_back(uri.clone());
+    }
+    assert_eq!(
+        client.try_mint_batch(&other, &recipients, &uris, &None, &true, &None),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+}
+
+#[test]
+fn burning_clears_the_card_and_shrinks_supply() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let first = mint(&env, &client, 1, &owner);
+    let second = mint(&env, &client, 2, &owner);
+    let third = mint(&env, &client, 3, &stranger);
+    assert_eq!(client.total_supply(), 3);
+
+    assert_eq!(
+        client.try_burn(&stranger, &first),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.burn(&owner, &first);
+    assert!(!client.token_exists(&first));
+    assert_eq!(client.tokens_of_owner(&owner), vec![&env, second]);
+    assert_eq!(client.total_supply(), 2);
+
+    client.force_burn(&third);
+    assert_eq!(client.get_user_nft_balance(&stranger), 0);
+    assert_eq!(client.total_supply(), 1);
+
+    // Ids are never reused
+    assert_eq!(mint(&env, &client, 4, &owner), 4);
+    assert_eq!(client.total_supply(), 2);
+}
+
+#[test]
+fn locked_cards_cannot_be_force_burned() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let locker = Address::generate(&env);
+    let card = mint(&env, &client, 1, &owner);
+
+    client.lock(&owner, &card, &locker);
+    assert_eq!(
+        client.try_force_burn(&card),
+        Err(Ok(ContractError::TokenNotTransferable))
+    );
+    assert!(client.token_exists(&card));
+
+    client.unlock(&locker, &card);
+    client.force_burn(&card);
+    assert!(!client.token_exists(&card));
+}
+
+#[test]
+fn transfers_and_burns_emit_their_own_events() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let card = mint(&env, &client, 1, &owner);
+
+    let token_id = U256::from_u32(&env, card as u32);
+
+    client.transfer(&owner, &buyer, &card);
+    let events = env.events().all();
+    let (contract, topics, data) = events.last().unwrap();
+    assert_eq!(contract, client.address);
+    assert_eq!(
+        topics,
+        (NFT_TRANSFER_EVENT, EVENT_SCHEMA_VERSION, token_id.clone()).into_val(&env)
+    );
+    let event: NFTTransferEvent = data.into_val(&env);
+    assert_eq!(event.version, EVENT_SCHEMA_VERSION);
+    assert_eq!(event.from, owner);
+    assert_eq!(event.to, buyer);
+    assert_eq!(event.token_id, token_id);
+    // Still duplicated under the legacy topic until the window closes
+    let (_, topics, _) = events.get(events.len() - 2).unwrap();
+    assert_eq!(topics, (NFT_TRANSFER_EVENT,).into_val(&env));
+
+    client.set_legacy_events_until(&Some(env.ledger().timestamp()));
+    client.burn(&buyer, &card);
+    let events = env.events().all();
+    let (_, topics, data) = events.last().unwrap();
+    assert_eq!(
+        topics,
+        (NFT_BURN_EVENT, EVENT_SCHEMA_VERSION, token_id.clone()).into_val(&env)
+    );
+    let event: NFTBurnEvent = data.into_val(&env);
+    assert_eq!(event.owner, buyer);
+    assert_eq!(event.token_id, token_id);
+    let legacy = (NFT_BURN_EVENT,).into_val(&env);
+    assert!(!events.iter().any(|(_, topics, _)| topics == legacy));
+}
+
+fn attributes(env: &Env, rarity: Rarity, rating: u32) -> CardAttributes {
+    CardAttributes {
+        rarity,
+        position: Symbol::new(env, "FWD"),
+        rating,
+        season: 2026,
+        edition: 1,
+    }
+}
+
+#[test]
+fn attributes_are_stored_and_rarity_caps_enforced() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://card");
+    client.set_rarity_cap(&Rarity::Legendary, &Some(1));
+
+    let legend = attributes(&env, Rarity::Legendary, 95);
+    let card = client.mint(
+        &BytesN::from_array(&env, &[1; 32]),
+        &owner,
+        &uri,
+        &Some(legend.clone()),
+        &true,
+        &None,
+    );
+    assert_eq!(client.get_card_attributes(&card), Some(legend.clone()));
+    assert_eq!(client.get_rarity_minted(&Rarity::Legendary), 1);
+
+    assert_eq!(
+        client.try_mint(
+            &BytesN::from_array(&env, &[2; 32]),
+            &owner,
+            &uri,
+            &Some(legend.clone()),
+            &true,
+            &None,
+        ),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+    assert_eq!(
+        client.try_mint(
+            &BytesN::from_array(&env, &[2; 32]),
+            &owner,
+            &uri,
+            &Some(attributes(&env, Rarity::Common, MAX_RATING + 1)),
+            &true,
+            &None,
+        ),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+
+    // A plain card can be promoted only while the cap has room
+    let plain = mint(&env, &client, 3, &owner);
+    assert_eq!(client.get_card_attributes(&plain), None);
+    assert_eq!(
+        client.try_update_attributes(&plain, &legend),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+
+    let demoted = attributes(&env, Rarity::Rare, 80);
+    client.update_attributes(&card, &demoted);
+    assert_eq!(client.get_card_attributes(&card), Some(demoted));
+    assert_eq!(client.get_rarity_minted(&Rarity::Legendary), 0);
+    assert_eq!(client.get_rarity_minted(&Rarity::Rare), 1);
+
+    client.update_attributes(&plain, &legend);
+    assert_eq!(client.get_rarity_minted(&Rarity::Legendary), 1);
+}
+
+#[test]
+fn fusing_burns_the_inputs_and_mints_the_next_rarity() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://card");
+
+    let mut cards = Vec::new(&env);
+    for (seed, rating) in [(1u8, 60u32), (2, 70), (3, 80)] {
+        let mut card = attributes(&env, Rarity::Common, rating);
+        card.edition = seed as u32;
+        cards.push_back(client.mint(
+            &BytesN::from_array(&env, &[seed; 32]),
+            &owner,
+            &uri,
+            &Some(card),
+            &true,
+            &None,
+        ));
+    }
+    let rare = client.mint(
+        &BytesN::from_array(&env, &[4; 32]),
+        &owner,
+        &uri,
+        &Some(attributes(&env, Rarity::Rare, 90)),
+        &true,
+        &None,
+    );
+    let hash = BytesN::from_array(&env, &[20; 32]);
+
+    assert_eq!(
+        client.try_fuse(&stranger, &cards, &hash),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    let mixed = vec![&env, cards.get_unchecked(0), cards.get_unchecked(1), rare];
+    assert_eq!(
+        client.try_fuse(&owner, &mixed, &hash),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    let repeated = vec![&env, cards.get_unchecked(0), cards.get_unchecked(0), rare];
+    assert_eq!(
+        client.try_fuse(&owner, &repeated, &hash),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+
+    let fused = client.fuse(&owner, &cards, &hash);
+    assert_eq!(
+        client.get_card_attributes(&fused),
+        Some(CardAttributes {
+            rarity: Rarity::Rare,
+            position: Symbol::new(&env, "FWD"),
+            rating: 70 + FUSION_RATING_BONUS,
+            season: 2026,
+            edition: 3,
+        })
+    );
+    assert_eq!(client.tokens_of_owner(&owner), vec![&env, rare, fused]);
+    assert_eq!(client.total_supply(), 2);
+    assert_eq!(
+        client.try_fuse(&owner, &cards, &hash),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+}
+
+#[test]
+fn soulbound_cards_cannot_change_hands_until_unlocked() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let market = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let badge = client.mint(
+        &BytesN::from_array(&env, &[1; 32]),
+        &owner,
+        &String::from_str(&env, "ipfs://badge"),
+        &None,
+        &false,
+        &None,
+    );
+    assert!(!client.is_transferable(&badge));
+
+    assert_eq!(
+        client.try_transfer(&owner, &buyer, &badge),
+        Err(Ok(ContractError::TokenNotTransferable))
+    );
+    client.set_approval_for_all(&owner, &market, &true);
+    assert_eq!(
+        client.try_transfer_from(&market, &owner, &buyer, &badge),
+        Err(Ok(ContractError::TokenNotTransferable))
+    );
+
+    // Emergency migration to a new wallet
+    client.set_transferable(&badge, &true);
+    client.transfer(&owner, &buyer, &badge);
+    assert_eq!(client.owner_of(&badge), buyer);
+
+    client.set_transferable(&badge, &false);
+    client.burn(&buyer, &badge);
+    assert!(client.is_transferable(&badge));
+}
+
+fn sign_voucher(
+    env: &Env,
+    client: &PlayerCardContractClient<'_>,
+    signer: &SigningKey,
+    voucher: &MintVoucher,
+    card_attributes: &Option<CardAttributes>,
+) -> BytesN<64> {
+    let message: std::vec::Vec<u8> = (
+        client.address.clone(),
+        voucher.clone(),
+        card_attributes.clone(),
+    )
+        .to_xdr(env)
+        .iter()
+        .collect();
+    BytesN::from_array(env, &signer.sign(&message).to_bytes())
+}
+
+#[test]
+fn vouchers_mint_once_until_they_expire() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let signer = SigningKey::from_bytes(&[9u8; 32]);
+
+    let voucher = MintVoucher {
+        to: owner.clone(),
+        uri: String::from_str(&env, "ipfs://voucher"),
+        nonce: 7,
+        expiry: env.ledger().timestamp() + 600,
+    };
+    let rare = Some(attributes(&env, Rarity::Rare, 70));
+    let signature = sign_voucher(&env, &client, &signer, &voucher, &rare);
+    assert_eq!(
+        client.try_mint_with_voucher(&voucher, &rare, &signature),
+        Err(Ok(ContractError::NotInitialized))
+    );
+    client.set_voucher_signer(&Some(BytesN::from_array(
+        &env,
+        &signer.verifying_key().to_bytes(),
+    )));
+
+    // The signature covers every field
+    let mut tampered = voucher.clone();
+    tampered.to = Address::generate(&env);
+    assert!(client
+        .try_mint_with_voucher(&tampered, &rare, &signature)
+        .is_err());
+    let epic = Some(attributes(&env, Rarity::Epic, 70));
+    assert!(client
+        .try_mint_with_voucher(&voucher, &epic, &signature)
+        .is_err());
+
+    let token_id = client.mint_with_voucher(&voucher, &rare, &signature);
+    assert_eq!(client.owner_of(&token_id), owner);
+    assert_eq!(client.get_card_attributes(&token_id), rare);
+    assert!(client.is_voucher_nonce_used(&7));
+    assert_eq!(
+        client.try_mint_with_voucher(&voucher, &rare, &signature),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+
+    let mut late = voucher.clone();
+    late.nonce = 3;
+    let late_signature = sign_voucher(&env, &client, &signer, &late, &None);
+    env.ledger().with_mut(|ledger| ledger.timestamp += 601);
+    assert_eq!(
+        client.try_mint_with_voucher(&late, &None, &late_signature),
+        Err(Ok(ContractError::Expired))
+    );
+}
+
+#[test]
+fn registered_minters_can_mint_and_are_counted() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let minter = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://reward");
+
+    assert_eq!(
+        client.try_mint_from(
+            &minter,
+            &BytesN::from_array(&env, &[1; 32]),
+            &owner,
+            &uri,
+            &None,
+            &true
+        ),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.add_minter(&minter);
+    assert!(client.is_minter(&minter));
+
+    let token_id = client.mint_from(
+        &minter,
+        &BytesN::from_array(&env, &[1; 32]),
+        &owner,
+        &uri,
+        &None,
+        &false,
+    );
+    assert_eq!(client.owner_of(&token_id), owner);
+    assert!(!client.is_transferable(&token_id));
+    assert_eq!(
+        client.try_mint_from(
+            &minter,
+            &BytesN::from_array(&env, &[1; 32]),
+            &owner,
+            &uri,
+            &None,
+            &true
+        ),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+    assert_eq!(client.get_minter_mints(&minter), 1);
+
+    mint(&env, &client, 2, &owner);
+    assert_eq!(client.get_minter_mints(&admin), 1);
+
+    client.remove_minter(&minter);
+    assert_eq!(
+        client.try_mint_from(
+            &minter,
+            &BytesN::from_array(&env, &[3; 32]),
+            &owner,
+            &uri,
+            &None,
+            &true
+        ),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn token_exists_tracks_owner_of_through_burns() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let card = mint(&env, &client, 1, &owner);
+
+    assert!(client.token_exists(&card));
+    assert_eq!(client.owner_of(&card), owner);
+    assert_eq!(client.tokens_of_owner(&owner), vec![&env, card]);
+
+    client.burn(&owner, &card);
+    assert!(!client.token_exists(&card));
+    assert!(client.try_owner_of(&card).is_err());
+    assert_eq!(client.tokens_of_owner(&owner), Vec::new(&env));
+}
+
+#[test]
+fn paged_views_follow_swap_removal_and_balances() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    for seed in 1..=4 {
+        mint(&env, &client, seed, &alice);
+    }
+    assert_eq!(client.balance_of(&alice), 4);
+    assert_eq!(
+        client.tokens_of_owner_page(&alice, &1, &2),
+        vec![&env, 2, 3]
+    );
+    assert_eq!(client.all_tokens_page(&3, &10), vec![&env, 4]);
+
+    // The last card takes the freed slot in both indexes
+    client.transfer(&alice, &bob, &1);
+    client.burn(&alice, &2);
+    assert_eq!(client.balance_of(&alice), 2);
+    assert_eq!(client.balance_of(&bob), 1);
+    assert_eq!(
+        client.tokens_of_owner_page(&alice, &0, &10),
+        vec![&env, 4, 3]
+    );
+    assert_eq!(client.tokens_of_owner_page(&bob, &0, &10), vec![&env, 1]);
+    assert_eq!(client.all_tokens_page(&0, &10), vec![&env, 1, 4, 3]);
+    assert_eq!(client.all_tokens_page(&5, &10), Vec::new(&env));
+    assert_eq!(client.get_user_nft_balance(&alice), 2);
+}
+
+#[test]
+fn token_uris_compose_from_the_base_uri_unless_overridden() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let empty = String::from_str(&env, "");
+    let plain = client.mint(
+        &BytesN::from_array(&env, &[1; 32]),
+        &owner,
+        &empty,
+        &None,
+        &true,
+        &None,
+    );
+    let custom = mint(&env, &client, 2, &owner);
+
+    // No collection metadata yet
+    assert_eq!(client.token_uri(&plain), empty);
+    assert_eq!(client.name(), empty);
+
+    assert_eq!(
+        client.try_set_collection_metadata(&empty, &empty, &empty, &empty),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.set_collection_metadata(
+        &String::from_str(&env, "Renaissance Cards"),
+        &String::from_str(&env, "RCARD"),
+        &String::from_str(&env, "https://cards.example/"),
+        &String::from_str(&env, "https://cards.example/collection.json"),
+    );
+    assert_eq!(client.symbol(), String::from_str(&env, "RCARD"));
+    assert_eq!(
+        client.contract_uri(),
+        String::from_str(&env, "https://cards.example/collection.json")
+    );
+    assert_eq!(
+        client.token_uri(&plain),
+        String::from_str(&env, "https://cards.example/1")
+    );
+    assert_eq!(
+        client.token_uri(&custom),
+        String::from_str(&env, "ipfs://card")
+    );
+
+    client.set_token_uri(&plain, &String::from_str(&env, "ipfs://special"));
+    assert_eq!(
+        client.token_uri(&plain),
+        String::from_str(&env, "ipfs://special")
+    );
+    client.set_token_uri(&custom, &empty);
+    assert_eq!(
+        client.get_token_metadata(&custom),
+        (owner, String::from_str(&env, "https://cards.example/2"))
+    );
+}
+
+#[test]
+fn frozen_cards_stay_put_until_clawed_back_to_custody() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let thief = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let custody = Address::generate(&env);
+    let card = mint(&env, &client, 1, &thief);
+    let reason = BytesN::from_array(&env, &[7; 32]);
+
+    client.freeze_token(&card);
+    assert!(client.is_frozen(&card));
+    assert_eq!(
+        client.try_lock(&thief, &card, &Address::generate(&env)),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_transfer(&thief, &buyer, &card),
+        Err(Ok(ContractError::TokenNotTransferable))
+    );
+    assert_eq!(
+        client.try_burn(&thief, &card),
+        Err(Ok(ContractError::TokenNotTransferable))
+    );
+
+    // Clawback needs somewhere to send the card
+    assert_eq!(
+        client.try_clawback(&card, &reason),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.set_custody(&custody);
+    client.clawback(&card, &reason);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (Symbol::new(&env, "card_clawback"), card, thief.clone()).into_val(&env)
+    );
+    let payload: (Address, BytesN<32>) = data.into_val(&env);
+    assert_eq!(payload, (custody.clone(), reason));
+    assert_eq!(client.owner_of(&card), custody);
+    assert_eq!(client.balance_of(&thief), 0);
+    assert!(!client.is_frozen(&card));
+
+    // Custody can hand it back once resolved
+    client.transfer(&custody, &buyer, &card);
+    client.freeze_token(&card);
+    client.unfreeze_token(&card);
+    client.transfer(&buyer, &custody, &card);
+    assert_eq!(client.owner_of(&card), custody);
+}
+
+#[test]
+fn renters_use_a_card_until_the_rental_expires() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let owner = Address::generate(&env);
+    let renter = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let card = mint(&env, &client, 1, &owner);
+    let payment_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    StellarAssetClient::new(&env, &payment_token).mint(&renter, &1_000);
+
+    assert_eq!(
+        client.try_rent(&renter, &card),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    client.list_for_rent(&owner, &card, &payment_token, &300, &3_600);
+    assert_eq!(
+        client.try_rent(&owner, &card),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+
+    let rental = client.rent(&renter, &card);
+    assert_eq!(rental.expires_at, env.ledger().timestamp() + 3_600);
+    assert_eq!(TokenClient::new(&env, &payment_token).balance(&owner), 300);
+    assert_eq!(client.user_of(&card), renter);
+    assert_eq!(client.owner_of(&card), owner);
+    assert_eq!(
+        client.try_rent(&buyer, &card),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_transfer(&owner, &buyer, &card),
+        Err(Ok(ContractError::TokenNotTransferable))
+    );
+    // Nor can the owner lock it as a boost while it is rented out
+    assert_eq!(
+        client.try_lock(&owner, &card, &Address::generate(&env)),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    // Expiry hands the card back; the next transfer clears the rental
+    env.ledger().with_mut(|l| l.timestamp += 3_600);
+    assert_eq!(client.user_of(&card), owner);
+    assert_eq!(client.get_rental(&card), None);
+    client.transfer(&owner, &buyer, &card);
+    let ended: Vec<Val> = (Symbol::new(&env, "rental_ended"), card).into_val(&env);
+    assert!(env
+        .events()
+        .all()
+        .iter()
+        .any(|(_, topics, _)| topics == ended));
+
+    // The old owner's listing does not carry over to the buyer
+    assert_eq!(client.get_rental_listing(&card), None);
+    assert_eq!(
+        client.try_rent(&renter, &card),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+}
+
+#[test]
+fn migrate_moves_version_1_instance_storage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let contract_id = env.register(PlayerCardContract, ());
+    let client = PlayerCardContractClient::new(&env, &contract_id);
+
+    // Version 1 layout: one soulbound card with attributes
+    env.as_contract(&contract_id, || {
+        let instance = env.storage().instance();
+        let key = |name: &str| String::from_str(&env, name);
+        instance.set(&key("ADMIN"), &admin);
+        instance.set(&key("NEXT_TOKEN_ID"), &2u64);
+        instance.set(&key("TOTAL_SUPPLY"), &1u64);
+        instance.set(&(key("TOKEN_OWNER"), 1u64), &owner);
+        instance.set(&(key("TOKEN_URI"), 1u64), &key("ipfs://legacy"));
+        instance.set(&(key("OWNER_TOKENS"), owner.clone()), &vec![&env, 1u64]);
+        instance.set(&(key("SOULBOUND"), 1u64), &true);
+        instance.set(
+            &(key("CARD_ATTRIBUTES"), 1u64),
+            &attributes(&env, Rarity::Epic, 80),
+        );
+        instance.set(&(key("RARITY_MINTED"), Rarity::Epic), &1u32);
+    });
+
+    assert_eq!(client.migrate(), Some(1));
+    assert_eq!(client.get_version(), CONTRACT_VERSION);
+    assert!(client.token_exists(&1));
+    assert_eq!(client.owner_of(&1), owner);
+    assert_eq!(
+        client.token_uri(&1),
+        String::from_str(&env, "ipfs://legacy")
+    );
+    assert_eq!(client.tokens_of_owner(&owner), vec![&env, 1]);
+    assert_eq!(client.balance_of(&owner), 1);
+    assert_eq!(client.all_tokens_page(&0, &10), vec![&env, 1]);
+    assert!(!client.is_transferable(&1));
+    assert_eq!(client.get_rarity_minted(&Rarity::Epic), 1);
+    assert_eq!(client.get_nft_contract_stats(), (1, admin));
+    assert_eq!(mint(&env, &client, 1, &owner), 2);
+    assert_eq!(client.migrate(), None);
+}
@@ -23,18 +23,16 @@ impl PlayerCardToken {
         let owner = storage::get_owner(&env, token_id);
         owner.require_auth();
 
-        let key = ("approval", token_id);
-        env.storage().instance().set(&key, &approved);
+        storage::set_approved(&env, token_id, &approved);
     }
 
     pub fn get_approved(env: Env, token_id: u64) -> Option<Address> {
-        let key = ("approval", token_id);
-        env.storage().instance().get(&key)
+        storage::get_approved(&env, token_id)
     }
 
     pub fn is_approved_or_owner(env: Env, spender: Address, token_id: u64) -> bool {
         let owner = storage::get_owner(&env, token_id);
-        if spender == owner {
+        if spender == owner || storage::is_approved_for_all(&env, &owner, &spender) {
             return true;
         }
 
@@ -0,0 +1,16 @@
+use soroban_sdk::{contracttype, Address, String};
+
+/// Off-chain permission to mint one card, signed by the voucher signer so
+/// the recipient can submit (and pay for) the mint themselves. The card's
+/// attributes travel next to the voucher and are covered by the same
+/// signature.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MintVoucher {
+    pub to: Address,
+    pub uri: String,
+    /// Any value not redeemed before; vouchers can be used in any order
+    pub nonce: u64,
+    /// Last timestamp at which the voucher can be redeemed
+    pub expiry: u64,
+}
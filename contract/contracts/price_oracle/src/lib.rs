@@ -0,0 +1,281 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, Address, BytesN, Env, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Fixed-point scale of every stored price (7 decimals, matching Stellar assets).
+pub const PRICE_SCALE: i128 = 10_000_000;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum PriceOracleError {
+    Unauthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    InvalidPrice = 4,
+    InvalidAmount = 5,
+    InvalidConfig = 6,
+    PriceNotFound = 7,
+    StalePrice = 8,
+    Overflow = 9,
+}
+
+/// Price of one base unit of a token expressed in base units of the
+/// reference currency, scaled by `PRICE_SCALE`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub updated_at: u64,
+    pub source: Address,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Admin,
+    ReferenceAsset,
+    MaxStaleness,
+    Feeder(Address),
+    Price(Address),
+}
+
+#[contract]
+pub struct PriceOracleContract;
+
+#[contractimpl]
+impl PriceOracleContract {
+    /// `reference_asset` is the currency caps are denominated in; it is
+    /// always priced at exactly `PRICE_SCALE`.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        reference_asset: Address,
+        max_staleness: u64,
+    ) -> Result<(), PriceOracleError> {
+        let storage = env.storage().persistent();
+
+        if storage.has(&DataKey::Admin) {
+            return Err(PriceOracleError::AlreadyInitialized);
+        }
+        if max_staleness == 0 {
+            return Err(PriceOracleError::InvalidConfig);
+        }
+
+        storage.set(&DataKey::Admin, &admin);
+        storage.set(&DataKey::ReferenceAsset, &reference_asset);
+        storage.set(&DataKey::MaxStaleness, &max_staleness);
+        Ok(())
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), PriceOracleError> {
+        Self::require_admin_auth(&env)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env) -> Result<Option<u32>, PriceOracleError> {
+        Self::require_admin_auth(&env)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn set_feeder(env: Env, feeder: Address, allowed: bool) -> Result<(), PriceOracleError> {
+        Self::require_admin_auth(&env)?;
+
+        let storage = env.storage().persistent();
+        if allowed {
+            storage.set(&DataKey::Feeder(feeder), &true);
+        } else {
+            storage.remove(&DataKey::Feeder(feeder));
+        }
+        Ok(())
+    }
+
+    pub fn is_feeder(env: Env, feeder: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Feeder(feeder))
+    }
+
+    pub fn set_max_staleness(env: Env, max_staleness: u64) -> Result<(), PriceOracleError> {
+        Self::require_admin_auth(&env)?;
+        if max_staleness == 0 {
+            return Err(PriceOracleError::InvalidConfig);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::MaxStaleness, &max_staleness);
+        Ok(())
+    }
+
+    /// Publish a fresh price. Callable by the admin or a registered feeder.
+    pub fn set_price(
+        env: Env,
+        source: Address,
+        token: Address,
+        price: i128,
+    ) -> Result<PriceData, PriceOracleError> {
+        source.require_auth();
+        if source != get_admin(&env)? && !Self::is_feeder(env.clone(), source.clone()) {
+            return Err(PriceOracleError::Unauthorized);
+        }
+        if price <= 0 {
+            return Err(PriceOracleError::InvalidPrice);
+        }
+        if token == get_reference_asset(&env)? {
+            return Err(PriceOracleError::InvalidPrice);
+        }
+
+        let data = PriceData {
+            price,
+            updated_at: env.ledger().timestamp(),
+            source,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Price(token.clone()), &data);
+
+        publish_price_updated_event(&env, &token, &data);
+
+        Ok(data)
+    }
+
+    pub fn get_reference_asset(env: Env) -> Result<Address, PriceOracleError> {
+        get_reference_asset(&env)
+    }
+
+    pub fn get_max_staleness(env: Env) -> Result<u64, PriceOracleError> {
+        get_max_staleness(&env)
+    }
+
+    /// Last stored price for `token`, whether or not it is still fresh.
+    pub fn get_price(env: Env, token: Address) -> Result<PriceData, PriceOracleError> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Price(token))
+            .ok_or(PriceOracleError::PriceNotFound)
+    }
+
+    pub fn is_fresh(env: Env, token: Address) -> bool {
+        fresh_price(&env, &token).is_ok()
+    }
+
+    /// Value of `amount` of `token` in reference-currency base units.
+    pub fn to_reference(env: Env, amount: i128, token: Address) -> Result<i128, PriceOracleError> {
+        validate_non_negative(amount)?;
+        mul_div(amount, fresh_price(&env, &token)?, PRICE_SCALE)
+    }
+
+    /// Amount of `token` worth `amount` reference-currency base units.
+    pub fn from_reference(
+        env: Env,
+        amount: i128,
+        token: Address,
+    ) -> Result<i128, PriceOracleError> {
+        validate_non_negative(amount)?;
+        mul_div(amount, PRICE_SCALE, fresh_price(&env, &token)?)
+    }
+
+    /// Convert `amount` of `from_token` into `to_token`, rounding down.
+    /// Fails with `StalePrice` if either leg has not been updated recently.
+    pub fn convert(
+        env: Env,
+        amount: i128,
+        from_token: Address,
+        to_token: Address,
+    ) -> Result<i128, PriceOracleError> {
+        validate_non_negative(amount)?;
+        if from_token == to_token {
+            return Ok(amount);
+        }
+
+        let from_price = fresh_price(&env, &from_token)?;
+        let to_price = fresh_price(&env, &to_token)?;
+        mul_div(amount, from_price, to_price)
+    }
+
+    fn require_admin_auth(env: &Env) -> Result<(), PriceOracleError> {
+        get_admin(env)?.require_auth();
+        Ok(())
+    }
+}
+
+fn get_admin(env: &Env) -> Result<Address, PriceOracleError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Admin)
+        .ok_or(PriceOracleError::NotInitialized)
+}
+
+fn get_reference_asset(env: &Env) -> Result<Address, PriceOracleError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ReferenceAsset)
+        .ok_or(PriceOracleError::NotInitialized)
+}
+
+fn get_max_staleness(env: &Env) -> Result<u64, PriceOracleError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::MaxStaleness)
+        .ok_or(PriceOracleError::NotInitialized)
+}
+
+fn fresh_price(env: &Env, token: &Address) -> Result<i128, PriceOracleError> {
+    if *token == get_reference_asset(env)? {
+        return Ok(PRICE_SCALE);
+    }
+
+    let data: PriceData = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Price(token.clone()))
+        .ok_or(PriceOracleError::PriceNotFound)?;
+
+    let age = env.ledger().timestamp().saturating_sub(data.updated_at);
+    if age > get_max_staleness(env)? {
+        return Err(PriceOracleError::StalePrice);
+    }
+    Ok(data.price)
+}
+
+fn mul_div(amount: i128, numerator: i128, denominator: i128) -> Result<i128, PriceOracleError> {
+    amount
+        .checked_mul(numerator)
+        .and_then(|value| value.checked_div(denominator))
+        .ok_or(PriceOracleError::Overflow)
+}
+
+fn validate_non_negative(amount: i128) -> Result<(), PriceOracleError> {
+    if amount < 0 {
+        return Err(PriceOracleError::InvalidAmount);
+    }
+    Ok(())
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_price_updated_event(env: &Env, token: &Address, data: &PriceData) {
+    env.events().publish(
+        (Symbol::new(env, "price_updated"), token.clone()),
+        (data.price, data.updated_at, data.source.clone()),
+    );
+}
+
+#[cfg(test)]
+mod test;
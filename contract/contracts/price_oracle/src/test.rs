@@ -0,0 +1,119 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, Env,
+};
+
+fn setup(env: &Env) -> (PriceOracleContractClient<'_>, Address, Address) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let usdc = Address::generate(env);
+    let contract_id = env.register(PriceOracleContract, ());
+    let client = PriceOracleContractClient::new(env, &contract_id);
+    client.initialize(&admin, &usdc, &300);
+
+    (client, admin, usdc)
+}
+
+#[test]
+fn initialize_only_once() {
+    let env = Env::default();
+    let (client, admin, usdc) = setup(&env);
+
+    assert_eq!(
+        client.try_initialize(&admin, &usdc, &300),
+        Err(Ok(PriceOracleError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn only_admin_or_feeders_publish_prices() {
+    let env = Env::default();
+    let (client, admin, _usdc) = setup(&env);
+    let xlm = Address::generate(&env);
+    let feeder = Address::generate(&env);
+
+    assert_eq!(
+        client.try_set_price(&feeder, &xlm, &1_200_000),
+        Err(Ok(PriceOracleError::Unauthorized))
+    );
+
+    client.set_feeder(&feeder, &true);
+    let data = client.set_price(&feeder, &xlm, &1_200_000);
+    assert_eq!(data.price, 1_200_000);
+    assert_eq!(data.source, feeder);
+
+    client.set_price(&admin, &xlm, &1_300_000);
+    assert_eq!(client.get_price(&xlm).price, 1_300_000);
+
+    assert_eq!(
+        client.try_set_price(&admin, &xlm, &0),
+        Err(Ok(PriceOracleError::InvalidPrice))
+    );
+}
+
+#[test]
+fn converts_between_assets_through_reference() {
+    let env = Env::default();
+    let (client, admin, usdc) = setup(&env);
+    let xlm = Address::generate(&env);
+    let eurc = Address::generate(&env);
+
+    // 1 XLM = 0.12 USDC, 1 EURC = 1.08 USDC
+    client.set_price(&admin, &xlm, &1_200_000);
+    client.set_price(&admin, &eurc, &10_800_000);
+
+    assert_eq!(client.to_reference(&1_000, &xlm), 120);
+    assert_eq!(client.from_reference(&120, &xlm), 1_000);
+    assert_eq!(client.convert(&1_000, &usdc, &usdc), 1_000);
+    assert_eq!(client.convert(&9_000, &xlm, &eurc), 1_000);
+    assert_eq!(client.convert(&1_000, &eurc, &xlm), 9_000);
+}
+
+#[test]
+fn rejects_stale_and_missing_prices() {
+    let env = Env::default();
+    let (client, admin, usdc) = setup(&env);
+    let xlm = Address::generate(&env);
+
+    assert_eq!(
+        client.try_convert(&100, &xlm, &usdc),
+        Err(Ok(PriceOracleError::PriceNotFound))
+    );
+
+    client.set_price(&admin, &xlm, &1_200_000);
+    assert!(client.is_fresh(&xlm));
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 301;
+    });
+
+    assert!(!client.is_fresh(&xlm));
+    assert_eq!(
+        client.try_convert(&100, &xlm, &usdc),
+        Err(Ok(PriceOracleError::StalePrice))
+    );
+
+    client.set_max_staleness(&600);
+    assert_eq!(client.convert(&100, &xlm, &usdc), 12);
+}
+
+#[test]
+fn rejects_overflowing_conversions() {
+    let env = Env::default();
+    let (client, admin, usdc) = setup(&env);
+    let xlm = Address::generate(&env);
+
+    client.set_price(&admin, &xlm, &1_200_000);
+    assert_eq!(
+        client.try_convert(&i128::MAX, &xlm, &usdc),
+        Err(Ok(PriceOracleError::Overflow))
+    );
+    assert_eq!(
+        client.try_convert(&-1, &xlm, &usdc),
+        Err(Ok(PriceOracleError::InvalidAmount))
+    );
+}
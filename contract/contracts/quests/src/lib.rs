@@ -0,0 +1,406 @@
+#![no_std]
+
+use common::{ensure_not_replayed, init_role, mint_card, Role};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env,
+    String, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum QuestsError {
+    Unauthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    InvalidAmount = 4,
+    InvalidConfig = 5,
+    InvalidStatus = 6,
+    DuplicateOperation = 7,
+    InsufficientRewardPool = 8,
+    QuestNotFound = 9,
+    MintFailed = 10,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum QuestReward {
+    /// Amount of the reward token, paid from the pool
+    Token(i128),
+    /// Soulbound player card minted with this token URI
+    Badge(String),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Quest {
+    pub id: u64,
+    /// Hash of the off-chain completion criteria the backend checks
+    pub criteria_hash: BytesN<32>,
+    pub reward: QuestReward,
+    pub active: bool,
+    pub completions: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuestCompletion {
+    pub quest_id: u64,
+    pub operation_hash: BytesN<32>,
+    pub completed_at: u64,
+    /// Card minted for a `Badge` reward
+    pub badge_id: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    BackendPubkey,
+    Token,
+    Pool,
+    PlayerCard,
+    QuestCount,
+    Quest(u64),
+    Completion(u64, Address),
+}
+
+#[contract]
+pub struct QuestsContract;
+
+#[contractimpl]
+impl QuestsContract {
+    /// `backend_pubkey` attests completions; `Token` rewards are paid in
+    /// `token` from the pool filled by `fund`.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        backend_pubkey: BytesN<32>,
+        token: Address,
+    ) -> Result<(), QuestsError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::BackendPubkey) {
+            return Err(QuestsError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        storage.set(&DataKey::BackendPubkey, &backend_pubkey);
+        storage.set(&DataKey::Token, &token);
+        init_role(&env, Role::Admin, &admin);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), QuestsError> {
+        common::grant_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), QuestsError> {
+        common::revoke_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), QuestsError> {
+        common::renounce_role(&env, &account, role).map_err(role_error)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), QuestsError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, QuestsError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn set_backend_pubkey(
+        env: Env,
+        admin: Address,
+        backend_pubkey: BytesN<32>,
+    ) -> Result<(), QuestsError> {
+        require_role(&env, Role::Admin, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::BackendPubkey, &backend_pubkey);
+        Ok(())
+    }
+
+    /// Player card contract that mints `Badge` rewards. This contract must
+    /// be registered as a minter on it.
+    pub fn set_player_card(
+        env: Env,
+        admin: Address,
+        player_card: Address,
+    ) -> Result<(), QuestsError> {
+        require_role(&env, Role::Admin, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::PlayerCard, &player_card);
+        Ok(())
+    }
+
+    /// Move `amount` of the reward token from `funder` into the pool.
+    pub fn fund(env: Env, funder: Address, amount: i128) -> Result<(), QuestsError> {
+        funder.require_auth();
+        if amount <= 0 {
+            return Err(QuestsError::InvalidAmount);
+        }
+        token::Client::new(&env, &get_token(&env)?).transfer(
+            &funder,
+            env.current_contract_address(),
+            &amount,
+        );
+        set_pool(&env, get_pool(&env) + amount);
+        Ok(())
+    }
+
+    pub fn get_pool(env: Env) -> i128 {
+        get_pool(&env)
+    }
+
+    /// Define a new, active quest. Returns its id.
+    pub fn create_quest(
+        env: Env,
+        admin: Address,
+        criteria_hash: BytesN<32>,
+        reward: QuestReward,
+    ) -> Result<u64, QuestsError> {
+        require_role(&env, Role::Admin, &admin)?;
+        let storage = env.storage().persistent();
+        match reward {
+            QuestReward::Token(amount) if amount <= 0 => {
+                return Err(QuestsError::InvalidAmount);
+            }
+            QuestReward::Badge(_) if !storage.has(&DataKey::PlayerCard) => {
+                return Err(QuestsError::InvalidConfig);
+            }
+            _ => {}
+        }
+
+        let id = storage.get(&DataKey::QuestCount).unwrap_or(0u64) + 1;
+        storage.set(&DataKey::QuestCount, &id);
+        let quest = Quest {
+            id,
+            criteria_hash,
+            reward,
+            active: true,
+            completions: 0,
+        };
+        store_quest(&env, &quest);
+        publish_quest_event(&env, "quest_created", &quest);
+        Ok(id)
+    }
+
+    /// Open or close a quest to new completions.
+    pub fn set_quest_active(
+        env: Env,
+        admin: Address,
+        quest_id: u64,
+        active: bool,
+    ) -> Result<(), QuestsError> {
+        require_role(&env, Role::Admin, &admin)?;
+        let mut quest = get_quest(&env, quest_id)?;
+        quest.active = active;
+        store_quest(&env, &quest);
+        publish_quest_event(&env, "quest_updated", &quest);
+        Ok(())
+    }
+
+    /// Claim the reward of an active quest for `user`. `signature` is the
+    /// backend's ed25519 signature over the XDR encoding of
+    /// `(contract, user, quest_id, operation_hash)`, where `contract` is this
+    /// contract's address. Each user completes a quest at most once, and
+    /// each operation hash is only accepted once.
+    pub fn complete_quest(
+        env: Env,
+        user: Address,
+        quest_id: u64,
+        operation_hash: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<QuestCompletion, QuestsError> {
+        user.require_auth();
+        let mut quest = get_quest(&env, quest_id)?;
+        if !quest.active {
+            return Err(QuestsError::InvalidStatus);
+        }
+        let completion_key = DataKey::Completion(quest_id, user.clone());
+        let storage = env.storage().persistent();
+        if storage.has(&completion_key) {
+            return Err(QuestsError::DuplicateOperation);
+        }
+
+        let backend_pubkey: BytesN<32> = storage
+            .get(&DataKey::BackendPubkey)
+            .ok_or(QuestsError::NotInitialized)?;
+        let message = (
+            env.current_contract_address(),
+            user.clone(),
+            quest_id,
+            operation_hash.clone(),
+        )
+            .to_xdr(&env);
+        env.crypto()
+            .ed25519_verify(&backend_pubkey, &message, &signature);
+        ensure_not_replayed(
+            &env,
+            Symbol::new(&env, "quest_complete"),
+            operation_hash.clone(),
+            None,
+        )
+        .map_err(|_| QuestsError::DuplicateOperation)?;
+
+        let badge_id = match &quest.reward {
+            QuestReward::Token(amount) => {
+                let pool = get_pool(&env);
+                if pool < *amount {
+                    return Err(QuestsError::InsufficientRewardPool);
+                }
+                set_pool(&env, pool - amount);
+                token::Client::new(&env, &get_token(&env)?).transfer(
+                    &env.current_contract_address(),
+                    &user,
+                    amount,
+                );
+                None
+            }
+            QuestReward::Badge(token_uri) => {
+                let player_card: Address = storage
+                    .get(&DataKey::PlayerCard)
+                    .ok_or(QuestsError::InvalidConfig)?;
+                Some(
+                    mint_card(&env, &player_card, &operation_hash, &user, token_uri, false)
+                        .map_err(mint_error)?,
+                )
+            }
+        };
+
+        let completion = QuestCompletion {
+            quest_id,
+            operation_hash,
+            completed_at: env.ledger().timestamp(),
+            badge_id,
+        };
+        storage.set(&completion_key, &completion);
+        common::bump_persistent(&env, &completion_key);
+        quest.completions += 1;
+        store_quest(&env, &quest);
+
+        publish_completion_event(&env, &user, &completion);
+        Ok(completion)
+    }
+
+    pub fn get_quest(env: Env, quest_id: u64) -> Result<Quest, QuestsError> {
+        get_quest(&env, quest_id)
+    }
+
+    pub fn get_quest_count(env: Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::QuestCount)
+            .unwrap_or(0)
+    }
+
+    pub fn get_completion(env: Env, quest_id: u64, user: Address) -> Option<QuestCompletion> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Completion(quest_id, user))
+    }
+
+    pub fn has_completed(env: Env, quest_id: u64, user: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::Completion(quest_id, user))
+    }
+}
+
+fn get_quest(env: &Env, quest_id: u64) -> Result<Quest, QuestsError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Quest(quest_id))
+        .ok_or(QuestsError::QuestNotFound)
+}
+
+fn store_quest(env: &Env, quest: &Quest) {
+    let key = DataKey::Quest(quest.id);
+    env.storage().persistent().set(&key, quest);
+    common::bump_persistent(env, &key);
+}
+
+fn get_token(env: &Env) -> Result<Address, QuestsError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Token)
+        .ok_or(QuestsError::NotInitialized)
+}
+
+fn get_pool(env: &Env) -> i128 {
+    env.storage().persistent().get(&DataKey::Pool).unwrap_or(0)
+}
+
+fn set_pool(env: &Env, amount: i128) {
+    env.storage().persistent().set(&DataKey::Pool, &amount);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_quest_event(env: &Env, topic: &str, quest: &Quest) {
+    env.events()
+        .publish((Symbol::new(env, topic), quest.id), quest.clone());
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_completion_event(env: &Env, user: &Address, completion: &QuestCompletion) {
+    env.events().publish(
+        (
+            Symbol::new(env, "quest_completed"),
+            completion.quest_id,
+            user.clone(),
+        ),
+        completion.clone(),
+    );
+}
+
+fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), QuestsError> {
+    common::require_role(env, role, account).map_err(role_error)
+}
+
+fn role_error(_: common::ContractError) -> QuestsError {
+    QuestsError::Unauthorized
+}
+
+fn mint_error(err: common::ContractError) -> QuestsError {
+    match err {
+        common::ContractError::DuplicateOperation => QuestsError::DuplicateOperation,
+        _ => QuestsError::MintFailed,
+    }
+}
+
+#[cfg(test)]
+mod test;
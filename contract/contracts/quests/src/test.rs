@@ -0,0 +1,177 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use player_card::{PlayerCardContract, PlayerCardContractClient};
+use soroban_sdk::{testutils::Address as _, token, Address, BytesN, Env};
+
+fn setup_test(
+    env: &Env,
+) -> (
+    QuestsContractClient<'_>,
+    Address,
+    SigningKey,
+    token::Client<'_>,
+    Address,
+) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let signer = SigningKey::from_bytes(&[9u8; 32]);
+    let pubkey = BytesN::from_array(env, &signer.verifying_key().to_bytes());
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    token::StellarAssetClient::new(env, &token_id).mint(&admin, &1_000);
+
+    let contract_id = env.register(QuestsContract, ());
+    let client = QuestsContractClient::new(env, &contract_id);
+    client.initialize(&admin, &pubkey, &token_id);
+    client.fund(&admin, &500);
+
+    let token = token::Client::new(env, &token_id);
+    let user = Address::generate(env);
+
+    (client, admin, signer, token, user)
+}
+
+fn op(env: &Env, seed: u8) -> BytesN<32> {
+    BytesN::from_array(env, &[seed; 32])
+}
+
+fn sign(
+    env: &Env,
+    client: &QuestsContractClient,
+    signer: &SigningKey,
+    user: &Address,
+    quest_id: u64,
+    operation_hash: &BytesN<32>,
+) -> BytesN<64> {
+    let message: std::vec::Vec<u8> = (
+        client.address.clone(),
+        user.clone(),
+        quest_id,
+        operation_hash.clone(),
+    )
+        .to_xdr(env)
+        .iter()
+        .collect();
+    BytesN::from_array(env, &signer.sign(&message).to_bytes())
+}
+
+fn complete(
+    env: &Env,
+    client: &QuestsContractClient,
+    signer: &SigningKey,
+    user: &Address,
+    quest_id: u64,
+    seed: u8,
+) -> QuestCompletion {
+    let operation_hash = op(env, seed);
+    let signature = sign(env, client, signer, user, quest_id, &operation_hash);
+    client.complete_quest(user, &quest_id, &operation_hash, &signature)
+}
+
+#[test]
+fn token_quests_pay_once_per_user() {
+    let env = Env::default();
+    let (client, admin, signer, token, user) = setup_test(&env);
+
+    let id = client.create_quest(&admin, &op(&env, 1), &QuestReward::Token(200));
+    assert!(!client.has_completed(&id, &user));
+    let completion = complete(&env, &client, &signer, &user, id, 10);
+    assert_eq!(completion.badge_id, None);
+    assert_eq!(token.balance(&user), 200);
+    assert_eq!(client.get_pool(), 300);
+    assert!(client.has_completed(&id, &user));
+    assert_eq!(client.get_quest(&id).completions, 1);
+
+    // A fresh attestation does not unlock the quest a second time
+    let operation_hash = op(&env, 11);
+    assert_eq!(
+        client.try_complete_quest(
+            &user,
+            &id,
+            &operation_hash,
+            &sign(&env, &client, &signer, &user, id, &operation_hash)
+        ),
+        Err(Ok(QuestsError::DuplicateOperation))
+    );
+
+    // Nor can an attestation be replayed against another quest
+    let other = client.create_quest(&admin, &op(&env, 2), &QuestReward::Token(200));
+    let operation_hash = op(&env, 10);
+    assert_eq!(
+        client.try_complete_quest(
+            &user,
+            &other,
+            &operation_hash,
+            &sign(&env, &client, &signer, &user, other, &operation_hash)
+        ),
+        Err(Ok(QuestsError::DuplicateOperation))
+    );
+}
+
+#[test]
+fn completions_need_a_matching_attestation() {
+    let env = Env::default();
+    let (client, admin, signer, _token, user) = setup_test(&env);
+
+    let id = client.create_quest(&admin, &op(&env, 1), &QuestReward::Token(200));
+    let operation_hash = op(&env, 10);
+    let signature = sign(&env, &client, &signer, &user, id, &operation_hash);
+    assert!(client
+        .try_complete_quest(&user, &id, &op(&env, 11), &signature)
+        .is_err());
+    let (other, ..) = setup_test(&env);
+    let foreign = sign(&env, &other, &signer, &user, id, &operation_hash);
+    assert!(client
+        .try_complete_quest(&user, &id, &operation_hash, &foreign)
+        .is_err());
+    assert_eq!(
+        client.try_complete_quest(&user, &99, &operation_hash, &signature),
+        Err(Ok(QuestsError::QuestNotFound))
+    );
+
+    client.set_quest_active(&admin, &id, &false);
+    assert_eq!(
+        client.try_complete_quest(&user, &id, &operation_hash, &signature),
+        Err(Ok(QuestsError::InvalidStatus))
+    );
+
+    let expensive = client.create_quest(&admin, &op(&env, 2), &QuestReward::Token(600));
+    let signature = sign(&env, &client, &signer, &user, expensive, &operation_hash);
+    assert_eq!(
+        client.try_complete_quest(&user, &expensive, &operation_hash, &signature),
+        Err(Ok(QuestsError::InsufficientRewardPool))
+    );
+}
+
+#[test]
+fn badge_quests_mint_soulbound_cards() {
+    let env = Env::default();
+    let (client, admin, signer, _token, user) = setup_test(&env);
+
+    let reward = QuestReward::Badge(String::from_str(&env, "ipfs://first-win"));
+    assert_eq!(
+        client.try_create_quest(&admin, &op(&env, 1), &reward),
+        Err(Ok(QuestsError::InvalidConfig))
+    );
+    let player_card_id = env.register(PlayerCardContract, ());
+    let player_card = PlayerCardContractClient::new(&env, &player_card_id);
+    player_card.initialize(&admin);
+    player_card.add_minter(&client.address);
+    client.set_player_card(&admin, &player_card_id);
+
+    let id = client.create_quest(&admin, &op(&env, 1), &reward);
+    let badge_id = complete(&env, &client, &signer, &user, id, 10)
+        .badge_id
+        .unwrap();
+    assert_eq!(player_card.owner_of(&badge_id), user);
+    assert!(!player_card.is_transferable(&badge_id));
+    assert_eq!(
+        client.get_completion(&id, &user).unwrap().badge_id,
+        Some(badge_id)
+    );
+}
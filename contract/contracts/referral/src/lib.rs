@@ -0,0 +1,421 @@
+#![no_std]
+
+use common::{
+    ensure_not_replayed, init_role, is_operation_executed, require_role, ContractError, Role,
+};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, token, Address, BytesN, Env, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+pub const MAX_COMMISSION_BPS: u32 = 10_000;
+
+/// Commission rate that applies once a referrer's referred volume reaches
+/// `min_volume`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommissionTier {
+    pub min_volume: i128,
+    pub bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReferrerStats {
+    pub referrals: u32,
+    /// Volume reported for every user this referrer brought in
+    pub referred_volume: i128,
+    pub accrued: i128,
+    pub claimed: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Token,
+    Tiers,
+    Reporter(Address),
+    Referrer(Address),
+    Stats(Address),
+    Pool,
+}
+
+#[contract]
+pub struct ReferralContract;
+
+#[contractimpl]
+impl ReferralContract {
+    /// `token` is the platform token commissions are paid in.
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        token: Address,
+        tiers: Vec<CommissionTier>,
+    ) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::Token) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        validate_tiers(&tiers)?;
+
+        storage.set(&DataKey::Token, &token);
+        storage.set(&DataKey::Tiers, &tiers);
+        init_role(&env, Role::Admin, &admin);
+        init_role(&env, Role::Treasurer, &admin);
+        Ok(())
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Stop duplicating events under their pre-versioning topics from
+    /// `until` on; `None` keeps the duplicates indefinitely.
+    pub fn set_legacy_events_until(
+        env: Env,
+        admin: Address,
+        until: Option<u64>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::set_legacy_events_until(&env, until);
+        Ok(())
+    }
+
+    pub fn get_legacy_events_until(env: Env) -> Option<u64> {
+        common::get_legacy_events_until(&env)
+    }
+
+    /// Tiers must start at volume 0 and be sorted by strictly increasing
+    /// `min_volume`.
+    pub fn set_tiers(
+        env: Env,
+        admin: Address,
+        tiers: Vec<CommissionTier>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        validate_tiers(&tiers)?;
+
+        env.storage().persistent().set(&DataKey::Tiers, &tiers);
+        Ok(())
+    }
+
+    pub fn get_tiers(env: Env) -> Vec<CommissionTier> {
+        get_tiers(&env)
+    }
+
+    /// Allow a platform contract (betting, staking) to report volume.
+    pub fn set_reporter(
+        env: Env,
+        admin: Address,
+        reporter: Address,
+        allowed: bool,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let key = DataKey::Reporter(reporter);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_reporter(env: Env, reporter: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Reporter(reporter))
+    }
+
+    /// Link `user` to `referrer`. A user can be referred only once, never by
+    /// themselves and never by someone they referred.
+    pub fn register_referrer(
+        env: Env,
+        user: Address,
+        referrer: Address,
+    ) -> Result<(), ContractError> {
+        user.require_auth();
+
+        if user == referrer || get_referrer(&env, &referrer) == Some(user.clone()) {
+            return Err(ContractError::SelfReferral);
+        }
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::Referrer(user.clone())) {
+            return Err(ContractError::ReferrerAlreadySet);
+        }
+
+        storage.set(&DataKey::Referrer(user.clone()), &referrer);
+        common::bump_persistent(&env, &DataKey::Referrer(user.clone()));
+        let mut stats = get_stats(&env, &referrer);
+        stats.referrals += 1;
+        set_stats(&env, &referrer, &stats);
+
+        publish_referral_event(&env, &user, &referrer);
+
+        Ok(())
+    }
+
+    pub fn get_referrer(env: Env, user: Address) -> Option<Address> {
+        get_referrer(&env, &user)
+    }
+
+    pub fn get_stats(env: Env, referrer: Address) -> ReferrerStats {
+        get_stats(&env, &referrer)
+    }
+
+    /// Commission rate `referrer` currently earns on new volume.
+    pub fn get_commission_bps(env: Env, referrer: Address) -> u32 {
+        commission_bps(&get_tiers(&env), get_stats(&env, &referrer).referred_volume)
+    }
+
+    /// Hook for authorized reporters: credit `user`'s referrer with
+    /// commission on `amount`. Returns the commission accrued (0 when the
+    /// user was not referred).
+    pub fn report_volume(
+        env: Env,
+        reporter: Address,
+        user: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        reporter.require_auth();
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Reporter(reporter.clone()))
+        {
+            return Err(ContractError::Unauthorized);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        let referrer = match get_referrer(&env, &user) {
+            Some(referrer) => referrer,
+            None => return Ok(0),
+        };
+
+        // The rate is fixed by the tier reached before this volume counts
+        let mut stats = get_stats(&env, &referrer);
+        let bps = commission_bps(&get_tiers(&env), stats.referred_volume);
+        let commission = amount
+            .checked_mul(bps as i128)
+            .map(|scaled| scaled / MAX_COMMISSION_BPS as i128)
+            .ok_or(ContractError::InvalidAmount)?;
+
+        stats.referred_volume = stats
+            .referred_volume
+            .checked_add(amount)
+            .ok_or(ContractError::InvalidAmount)?;
+        stats.accrued = stats
+            .accrued
+            .checked_add(commission)
+            .ok_or(ContractError::InvalidAmount)?;
+        set_stats(&env, &referrer, &stats);
+
+        publish_volume_event(&env, &referrer, &user, amount, commission);
+
+        Ok(commission)
+    }
+
+    /// Top up the balance commissions are paid from.
+    pub fn fund(env: Env, funder: Address, amount: i128) -> Result<i128, ContractError> {
+        funder.require_auth();
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        token::Client::new(&env, &get_token(&env)?).transfer(
+            &funder,
+            env.current_contract_address(),
+            &amount,
+        );
+        let pool = get_pool(&env)
+            .checked_add(amount)
+            .ok_or(ContractError::InvalidAmount)?;
+        set_pool(&env, pool);
+        Ok(pool)
+    }
+
+    pub fn get_pool(env: Env) -> i128 {
+        get_pool(&env)
+    }
+
+    /// Pay out everything `referrer` has accrued. `operation_hash` makes
+    /// retried claims safe: a hash is honoured once.
+    pub fn claim(
+        env: Env,
+        referrer: Address,
+        operation_hash: BytesN<32>,
+    ) -> Result<i128, ContractError> {
+        referrer.require_auth();
+        ensure_not_replayed(&env, claim_scope(&env), operation_hash, None)?;
+
+        let mut stats = get_stats(&env, &referrer);
+        let amount = stats.accrued;
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let pool = get_pool(&env);
+        if pool < amount {
+            return Err(ContractError::InsufficientBalance);
+        }
+
+        stats.accrued = 0;
+        stats.claimed = stats
+            .claimed
+            .checked_add(amount)
+            .ok_or(ContractError::InvalidAmount)?;
+        set_stats(&env, &referrer, &stats);
+        set_pool(&env, pool - amount);
+
+        token::Client::new(&env, &get_token(&env)?).transfer(
+            &env.current_contract_address(),
+            &referrer,
+            &amount,
+        );
+
+        publish_claim_event(&env, &referrer, amount);
+
+        Ok(amount)
+    }
+
+    pub fn is_claim_executed(env: Env, operation_hash: BytesN<32>) -> bool {
+        is_operation_executed(&env, claim_scope(&env), operation_hash)
+    }
+}
+
+fn claim_scope(env: &Env) -> Symbol {
+    Symbol::new(env, "referral_claim")
+}
+
+fn validate_tiers(tiers: &Vec<CommissionTier>) -> Result<(), ContractError> {
+    let mut previous: Option<i128> = None;
+    for tier in tiers.iter() {
+        if tier.bps > MAX_COMMISSION_BPS {
+            return Err(ContractError::InvalidConfig);
+        }
+        let in_order = match previous {
+            None => tier.min_volume == 0,
+            Some(min_volume) => tier.min_volume > min_volume,
+        };
+        if !in_order {
+            return Err(ContractError::InvalidConfig);
+        }
+        previous = Some(tier.min_volume);
+    }
+    if previous.is_none() {
+        return Err(ContractError::InvalidConfig);
+    }
+    Ok(())
+}
+
+fn commission_bps(tiers: &Vec<CommissionTier>, referred_volume: i128) -> u32 {
+    let mut bps = 0;
+    for tier in tiers.iter() {
+        if referred_volume < tier.min_volume {
+            break;
+        }
+        bps = tier.bps;
+    }
+    bps
+}
+
+fn get_token(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Token)
+        .ok_or(ContractError::NotInitialized)
+}
+
+fn get_tiers(env: &Env) -> Vec<CommissionTier> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Tiers)
+        .unwrap_or(Vec::new(env))
+}
+
+fn get_referrer(env: &Env, user: &Address) -> Option<Address> {
+    // Read on every reported stake
+    let key = DataKey::Referrer(user.clone());
+    common::bump_persistent(env, &key);
+    env.storage().persistent().get(&key)
+}
+
+fn get_stats(env: &Env, referrer: &Address) -> ReferrerStats {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Stats(referrer.clone()))
+        .unwrap_or_default()
+}
+
+fn set_stats(env: &Env, referrer: &Address, stats: &ReferrerStats) {
+    let key = DataKey::Stats(referrer.clone());
+    env.storage().persistent().set(&key, stats);
+    common::bump_persistent(env, &key);
+}
+
+fn get_pool(env: &Env) -> i128 {
+    env.storage().persistent().get(&DataKey::Pool).unwrap_or(0)
+}
+
+fn set_pool(env: &Env, pool: i128) {
+    env.storage().persistent().set(&DataKey::Pool, &pool);
+    common::bump_persistent(env, &DataKey::Pool);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_referral_event(env: &Env, user: &Address, referrer: &Address) {
+    env.events().publish(
+        (Symbol::new(env, "referrer_registered"), referrer.clone()),
+        (user.clone(), env.ledger().timestamp()),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_volume_event(
+    env: &Env,
+    referrer: &Address,
+    user: &Address,
+    amount: i128,
+    commission: i128,
+) {
+    env.events().publish(
+        (Symbol::new(env, "referral_volume"), referrer.clone()),
+        (user.clone(), amount, commission),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_claim_event(env: &Env, referrer: &Address, amount: i128) {
+    env.events().publish(
+        (Symbol::new(env, "commission_claimed"), referrer.clone()),
+        amount,
+    );
+}
+
+#[cfg(test)]
+mod test;
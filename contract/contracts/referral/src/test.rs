@@ -0,0 +1,146 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{testutils::Address as _, token, vec, Address, BytesN, Env};
+
+fn tier(min_volume: i128, bps: u32) -> CommissionTier {
+    CommissionTier { min_volume, bps }
+}
+
+fn setup_test(
+    env: &Env,
+) -> (
+    ReferralContractClient<'_>,
+    Address,
+    Address,
+    token::Client<'_>,
+) {
+    env.mock_all_auths();
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let admin = Address::generate(env);
+    token::StellarAssetClient::new(env, &token_id).mint(&admin, &100_000);
+
+    let contract_id = env.register(ReferralContract, ());
+    let client = ReferralContractClient::new(env, &contract_id);
+    // 1% up to 10_000 of referred volume, 2% after that
+    client.initialize(
+        &admin,
+        &token_id,
+        &vec![env, tier(0, 100), tier(10_000, 200)],
+    );
+    let reporter = Address::generate(env);
+    client.set_reporter(&admin, &reporter, &true);
+
+    let token = token::Client::new(env, &token_id);
+
+    (client, admin, reporter, token)
+}
+
+#[test]
+fn referrer_is_registered_once_and_never_self() {
+    let env = Env::default();
+    let (client, _admin, _reporter, _token) = setup_test(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+
+    assert_eq!(
+        client.try_register_referrer(&alice, &alice),
+        Err(Ok(ContractError::SelfReferral))
+    );
+
+    client.register_referrer(&bob, &alice);
+    assert_eq!(client.get_referrer(&bob), Some(alice.clone()));
+    assert_eq!(client.get_stats(&alice).referrals, 1);
+
+    assert_eq!(
+        client.try_register_referrer(&bob, &Address::generate(&env)),
+        Err(Ok(ContractError::ReferrerAlreadySet))
+    );
+    // No referral loops between two accounts
+    assert_eq!(
+        client.try_register_referrer(&alice, &bob),
+        Err(Ok(ContractError::SelfReferral))
+    );
+}
+
+#[test]
+fn reported_volume_accrues_tiered_commission() {
+    let env = Env::default();
+    let (client, _admin, reporter, _token) = setup_test(&env);
+    let referrer = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.register_referrer(&user, &referrer);
+
+    assert_eq!(client.report_volume(&reporter, &user, &10_000), 100);
+    // Tier 2 applies to volume reported after the threshold was reached
+    assert_eq!(client.get_commission_bps(&referrer), 200);
+    assert_eq!(client.report_volume(&reporter, &user, &5_000), 100);
+
+    let stats = client.get_stats(&referrer);
+    assert_eq!(stats.referred_volume, 15_000);
+    assert_eq!(stats.accrued, 200);
+
+    // Unreferred users and unknown reporters earn nobody anything
+    assert_eq!(
+        client.report_volume(&reporter, &Address::generate(&env), &1_000),
+        0
+    );
+    assert_eq!(
+        client.try_report_volume(&Address::generate(&env), &user, &1_000),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn commission_claims_are_idempotent() {
+    let env = Env::default();
+    let (client, admin, reporter, token) = setup_test(&env);
+    let referrer = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.register_referrer(&user, &referrer);
+    client.report_volume(&reporter, &user, &5_000);
+
+    let first = BytesN::from_array(&env, &[1u8; 32]);
+    assert_eq!(
+        client.try_claim(&referrer, &first),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+
+    client.fund(&admin, &1_000);
+    assert_eq!(client.claim(&referrer, &first), 50);
+    assert_eq!(token.balance(&referrer), 50);
+    assert_eq!(client.get_pool(), 950);
+    assert!(client.is_claim_executed(&first));
+
+    client.report_volume(&reporter, &user, &5_000);
+    assert_eq!(
+        client.try_claim(&referrer, &first),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+    assert_eq!(
+        client.claim(&referrer, &BytesN::from_array(&env, &[2u8; 32])),
+        50
+    );
+    assert_eq!(client.get_stats(&referrer).claimed, 100);
+}
+
+#[test]
+fn tiers_must_start_at_zero_and_increase() {
+    let env = Env::default();
+    let (client, admin, _reporter, _token) = setup_test(&env);
+
+    for tiers in [
+        vec![&env],
+        vec![&env, tier(100, 100)],
+        vec![&env, tier(0, 100), tier(0, 200)],
+        vec![&env, tier(0, MAX_COMMISSION_BPS + 1)],
+    ] {
+        assert_eq!(
+            client.try_set_tiers(&admin, &tiers),
+            Err(Ok(ContractError::InvalidConfig))
+        );
+    }
+}
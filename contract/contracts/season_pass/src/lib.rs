@@ -0,0 +1,543 @@
+#![no_std]
+
+use common::{init_role, mint_card, Role};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token, xdr::ToXdr, Address, BytesN, Env,
+    String, Symbol, Val, Vec,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum SeasonPassError {
+    Unauthorized = 1,
+    AlreadyInitialized = 2,
+    InvalidAmount = 3,
+    InvalidConfig = 4,
+    InvalidStatus = 5,
+    Expired = 6,
+    DuplicateOperation = 7,
+    ConditionNotMet = 8,
+    InsufficientRewardPool = 9,
+    SeasonNotFound = 10,
+    TierNotFound = 11,
+    PassNotFound = 12,
+    MintFailed = 13,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PassReward {
+    /// Amount of the season token, paid from the season's pool
+    Token(i128),
+    /// Player card minted with this token URI
+    Card(String),
+}
+
+/// Unlocked once a pass holder has `min_xp` in the season.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PassTier {
+    pub min_xp: u32,
+    pub reward: PassReward,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SeasonStatus {
+    Open,
+    /// No more passes, XP or claims; unclaimed rewards have expired
+    Closed,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Season {
+    pub id: u64,
+    /// Token passes are bought with and `Token` rewards are paid in
+    pub token: Address,
+    pub price: i128,
+    /// Ordered by rising `min_xp`
+    pub tiers: Vec<PassTier>,
+    pub status: SeasonStatus,
+    pub passes_sold: u32,
+    /// Sales and funding not yet paid out as rewards
+    pub pool: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SeasonPass {
+    pub season_id: u64,
+    pub xp: u32,
+    /// Indexes into `Season::tiers` already claimed
+    pub claimed: Vec<u32>,
+    pub purchased_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    PlayerCard,
+    Reporter(Address),
+    SeasonCount,
+    Season(u64),
+    Pass(u64, Address),
+}
+
+#[contract]
+pub struct SeasonPassContract;
+
+#[contractimpl]
+impl SeasonPassContract {
+    pub fn initialize(env: Env, admin: Address) -> Result<(), SeasonPassError> {
+        if env.storage().persistent().has(&DataKey::SeasonCount) {
+            return Err(SeasonPassError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        env.storage().persistent().set(&DataKey::SeasonCount, &0u64);
+        init_role(&env, Role::Admin, &admin);
+        Ok(())
+    }
+
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), SeasonPassError> {
+        common::grant_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), SeasonPassError> {
+        common::revoke_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), SeasonPassError> {
+        common::renounce_role(&env, &account, role).map_err(role_error)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), SeasonPassError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, SeasonPassError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Player card contract that mints `Card` rewards. This contract must
+    /// be registered as a minter on it.
+    pub fn set_player_card(
+        env: Env,
+        admin: Address,
+        player_card: Address,
+    ) -> Result<(), SeasonPassError> {
+        require_role(&env, Role::Admin, &admin)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::PlayerCard, &player_card);
+        Ok(())
+    }
+
+    /// Allow a game contract (betting, spin rewards) to report XP.
+    pub fn set_reporter(
+        env: Env,
+        admin: Address,
+        reporter: Address,
+        allowed: bool,
+    ) -> Result<(), SeasonPassError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let key = DataKey::Reporter(reporter);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_reporter(env: Env, reporter: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Reporter(reporter))
+    }
+
+    /// Open a season selling passes for `price` of `token`. Returns the
+    /// season id.
+    pub fn create_season(
+        env: Env,
+        admin: Address,
+        token: Address,
+        price: i128,
+        tiers: Vec<PassTier>,
+    ) -> Result<u64, SeasonPassError> {
+        require_role(&env, Role::Admin, &admin)?;
+        if price < 0 {
+            return Err(SeasonPassError::InvalidAmount);
+        }
+        validate_tiers(&env, &tiers)?;
+
+        let storage = env.storage().persistent();
+        let id = storage.get(&DataKey::SeasonCount).unwrap_or(0u64) + 1;
+        storage.set(&DataKey::SeasonCount, &id);
+        let season = Season {
+            id,
+            token,
+            price,
+            tiers,
+            status: SeasonStatus::Open,
+            passes_sold: 0,
+            pool: 0,
+        };
+        store_season(&env, &season);
+        publish_season_event(&env, "season_created", &season);
+        Ok(id)
+    }
+
+    /// End a season. Unclaimed rewards expire and what is left of the pool
+    /// can be withdrawn.
+    pub fn close_season(env: Env, admin: Address, season_id: u64) -> Result<(), SeasonPassError> {
+        require_role(&env, Role::Admin, &admin)?;
+        let mut season = get_open_season(&env, season_id)?;
+        season.status = SeasonStatus::Closed;
+        store_season(&env, &season);
+        publish_season_event(&env, "season_closed", &season);
+        Ok(())
+    }
+
+    /// Top up a season's pool so it can cover its `Token` rewards.
+    pub fn fund_season(
+        env: Env,
+        funder: Address,
+        season_id: u64,
+        amount: i128,
+    ) -> Result<(), SeasonPassError> {
+        funder.require_auth();
+        if amount <= 0 {
+            return Err(SeasonPassError::InvalidAmount);
+        }
+        let mut season = get_open_season(&env, season_id)?;
+        token::Client::new(&env, &season.token).transfer(
+            &funder,
+            env.current_contract_address(),
+            &amount,
+        );
+        season.pool += amount;
+        store_season(&env, &season);
+        Ok(())
+    }
+
+    /// Send what is left of a closed season's pool to `to`. Returns the
+    /// amount withdrawn.
+    pub fn withdraw_pool(
+        env: Env,
+        admin: Address,
+        season_id: u64,
+        to: Address,
+    ) -> Result<i128, SeasonPassError> {
+        require_role(&env, Role::Admin, &admin)?;
+        let mut season = get_season(&env, season_id)?;
+        if season.status != SeasonStatus::Closed {
+            return Err(SeasonPassError::InvalidStatus);
+        }
+        let amount = season.pool;
+        season.pool = 0;
+        store_season(&env, &season);
+        if amount > 0 {
+            token::Client::new(&env, &season.token).transfer(
+                &env.current_contract_address(),
+                &to,
+                &amount,
+            );
+        }
+        Ok(amount)
+    }
+
+    pub fn purchase_pass(env: Env, user: Address, season_id: u64) -> Result<(), SeasonPassError> {
+        user.require_auth();
+        let mut season = get_open_season(&env, season_id)?;
+        let key = DataKey::Pass(season_id, user.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(SeasonPassError::DuplicateOperation);
+        }
+
+        if season.price > 0 {
+            token::Client::new(&env, &season.token).transfer(
+                &user,
+                env.current_contract_address(),
+                &season.price,
+            );
+        }
+        season.pool += season.price;
+        season.passes_sold += 1;
+        store_season(&env, &season);
+        store_pass(
+            &env,
+            &user,
+            &SeasonPass {
+                season_id,
+                xp: 0,
+                claimed: Vec::new(&env),
+                purchased_at: env.ledger().timestamp(),
+            },
+        );
+
+        publish_pass_event(&env, "pass_purchased", season_id, &user, season.price);
+        Ok(())
+    }
+
+    /// Hook for authorized reporters: add `xp` to `user`'s pass. Returns
+    /// the pass's new XP total.
+    pub fn report_xp(
+        env: Env,
+        reporter: Address,
+        season_id: u64,
+        user: Address,
+        xp: u32,
+    ) -> Result<u32, SeasonPassError> {
+        reporter.require_auth();
+        if !Self::is_reporter(env.clone(), reporter) {
+            return Err(SeasonPassError::Unauthorized);
+        }
+        if xp == 0 {
+            return Err(SeasonPassError::InvalidAmount);
+        }
+        get_open_season(&env, season_id)?;
+        let mut pass = get_pass(&env, season_id, &user)?;
+        pass.xp = pass.xp.saturating_add(xp);
+        store_pass(&env, &user, &pass);
+
+        publish_pass_event(&env, "pass_xp", season_id, &user, xp as i128);
+        Ok(pass.xp)
+    }
+
+    /// Claim the reward of tier `tier_index` once `user`'s pass has reached
+    /// it. Returns the minted card id for `Card` rewards.
+    pub fn claim_reward(
+        env: Env,
+        user: Address,
+        season_id: u64,
+        tier_index: u32,
+    ) -> Result<Option<u64>, SeasonPassError> {
+        user.require_auth();
+        let mut season = get_season(&env, season_id)?;
+        if season.status == SeasonStatus::Closed {
+            return Err(SeasonPassError::Expired);
+        }
+        let tier = season
+            .tiers
+            .get(tier_index)
+            .ok_or(SeasonPassError::TierNotFound)?;
+        let mut pass = get_pass(&env, season_id, &user)?;
+        if pass.xp < tier.min_xp {
+            return Err(SeasonPassError::ConditionNotMet);
+        }
+        if pass.claimed.contains(tier_index) {
+            return Err(SeasonPassError::DuplicateOperation);
+        }
+        pass.claimed.push_back(tier_index);
+        store_pass(&env, &user, &pass);
+
+        let card_id = match tier.reward {
+            PassReward::Token(amount) => {
+                if season.pool < amount {
+                    return Err(SeasonPassError::InsufficientRewardPool);
+                }
+                season.pool -= amount;
+                store_season(&env, &season);
+                token::Client::new(&env, &season.token).transfer(
+                    &env.current_contract_address(),
+                    &user,
+                    &amount,
+                );
+                None
+            }
+            PassReward::Card(token_uri) => {
+                let player_card: Address = env
+                    .storage()
+                    .persistent()
+                    .get(&DataKey::PlayerCard)
+                    .ok_or(SeasonPassError::InvalidConfig)?;
+                // Unique per contract, season, user and tier, so the card
+                // contract never mints the same reward twice
+                let operation_hash = env
+                    .crypto()
+                    .sha256(
+                        &(
+                            env.current_contract_address(),
+                            season_id,
+                            user.clone(),
+                            tier_index,
+                        )
+                            .to_xdr(&env),
+                    )
+                    .into();
+                Some(
+                    mint_card(&env, &player_card, &operation_hash, &user, &token_uri, true)
+                        .map_err(mint_error)?,
+                )
+            }
+        };
+
+        publish_pass_event(&env, "pass_claimed", season_id, &user, tier_index as i128);
+        Ok(card_id)
+    }
+
+    pub fn get_season(env: Env, season_id: u64) -> Result<Season, SeasonPassError> {
+        get_season(&env, season_id)
+    }
+
+    pub fn get_season_count(env: Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SeasonCount)
+            .unwrap_or(0)
+    }
+
+    pub fn get_pass(env: Env, season_id: u64, user: Address) -> Option<SeasonPass> {
+        get_pass(&env, season_id, &user).ok()
+    }
+
+    /// Tier indexes `user` has unlocked but not yet claimed; empty once the
+    /// season is closed.
+    pub fn get_claimable(env: Env, season_id: u64, user: Address) -> Vec<u32> {
+        let mut claimable = Vec::new(&env);
+        let (Ok(season), Ok(pass)) = (
+            get_season(&env, season_id),
+            get_pass(&env, season_id, &user),
+        ) else {
+            return claimable;
+        };
+        if season.status == SeasonStatus::Closed {
+            return claimable;
+        }
+        for (index, tier) in season.tiers.iter().enumerate() {
+            let index = index as u32;
+            if pass.xp >= tier.min_xp && !pass.claimed.contains(index) {
+                claimable.push_back(index);
+            }
+        }
+        claimable
+    }
+}
+
+fn validate_tiers(env: &Env, tiers: &Vec<PassTier>) -> Result<(), SeasonPassError> {
+    if tiers.is_empty() {
+        return Err(SeasonPassError::InvalidConfig);
+    }
+    let has_player_card = env.storage().persistent().has(&DataKey::PlayerCard);
+    let mut previous: Option<u32> = None;
+    for tier in tiers.iter() {
+        if previous.is_some_and(|min_xp| tier.min_xp <= min_xp) {
+            return Err(SeasonPassError::InvalidConfig);
+        }
+        match tier.reward {
+            PassReward::Token(amount) if amount <= 0 => {
+                return Err(SeasonPassError::InvalidAmount);
+            }
+            PassReward::Card(_) if !has_player_card => {
+                return Err(SeasonPassError::InvalidConfig);
+            }
+            _ => {}
+        }
+        previous = Some(tier.min_xp);
+    }
+    Ok(())
+}
+
+fn get_season(env: &Env, season_id: u64) -> Result<Season, SeasonPassError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Season(season_id))
+        .ok_or(SeasonPassError::SeasonNotFound)
+}
+
+fn get_open_season(env: &Env, season_id: u64) -> Result<Season, SeasonPassError> {
+    let season = get_season(env, season_id)?;
+    if season.status != SeasonStatus::Open {
+        return Err(SeasonPassError::InvalidStatus);
+    }
+    Ok(season)
+}
+
+fn store_season(env: &Env, season: &Season) {
+    let key = DataKey::Season(season.id);
+    env.storage().persistent().set(&key, season);
+    common::bump_persistent(env, &key);
+}
+
+fn get_pass(env: &Env, season_id: u64, user: &Address) -> Result<SeasonPass, SeasonPassError> {
+    let key = DataKey::Pass(season_id, user.clone());
+    common::bump_persistent(env, &key);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .ok_or(SeasonPassError::PassNotFound)
+}
+
+fn store_pass(env: &Env, user: &Address, pass: &SeasonPass) {
+    let key = DataKey::Pass(pass.season_id, user.clone());
+    env.storage().persistent().set(&key, pass);
+    common::bump_persistent(env, &key);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_season_event(env: &Env, topic: &str, season: &Season) {
+    env.events()
+        .publish((Symbol::new(env, topic), season.id), season.clone());
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_pass_event(env: &Env, topic: &str, season_id: u64, user: &Address, value: i128) {
+    env.events()
+        .publish((Symbol::new(env, topic), season_id, user.clone()), value);
+}
+
+fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), SeasonPassError> {
+    common::require_role(env, role, account).map_err(role_error)
+}
+
+fn role_error(_: common::ContractError) -> SeasonPassError {
+    SeasonPassError::Unauthorized
+}
+
+fn mint_error(err: common::ContractError) -> SeasonPassError {
+    match err {
+        common::ContractError::DuplicateOperation => SeasonPassError::DuplicateOperation,
+        _ => SeasonPassError::MintFailed,
+    }
+}
+
+#[cfg(test)]
+mod test;
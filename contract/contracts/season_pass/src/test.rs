@@ -0,0 +1,164 @@
+#![cfg(test)]
+
+use super::*;
+use player_card::{PlayerCardContract, PlayerCardContractClient};
+use soroban_sdk::{testutils::Address as _, token, vec, Address, Env};
+
+fn setup_test(
+    env: &Env,
+) -> (
+    SeasonPassContractClient<'_>,
+    Address,
+    Address,
+    token::Client<'_>,
+    Address,
+) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let contract_id = env.register(SeasonPassContract, ());
+    let client = SeasonPassContractClient::new(env, &contract_id);
+    client.initialize(&admin);
+    let reporter = Address::generate(env);
+    client.set_reporter(&admin, &reporter, &true);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let user = Address::generate(env);
+    token::StellarAssetClient::new(env, &token_id).mint(&user, &1_000);
+
+    let token = token::Client::new(env, &token_id);
+
+    (client, admin, reporter, token, user)
+}
+
+fn token_tiers(env: &Env) -> Vec<PassTier> {
+    vec![
+        env,
+        PassTier {
+            min_xp: 100,
+            reward: PassReward::Token(50),
+        },
+        PassTier {
+            min_xp: 500,
+            reward: PassReward::Token(250),
+        },
+    ]
+}
+
+#[test]
+fn xp_unlocks_claimable_tiers() {
+    let env = Env::default();
+    let (client, admin, reporter, token, user) = setup_test(&env);
+    let season = client.create_season(&admin, &token.address, &200, &token_tiers(&env));
+
+    assert_eq!(
+        client.try_report_xp(&reporter, &season, &user, &100),
+        Err(Ok(SeasonPassError::PassNotFound))
+    );
+    client.purchase_pass(&user, &season);
+    assert_eq!(token.balance(&user), 800);
+    assert_eq!(
+        client.try_purchase_pass(&user, &season),
+        Err(Ok(SeasonPassError::DuplicateOperation))
+    );
+    assert_eq!(
+        client.try_report_xp(&user, &season, &user, &100),
+        Err(Ok(SeasonPassError::Unauthorized))
+    );
+
+    assert_eq!(client.report_xp(&reporter, &season, &user, &120), 120);
+    assert_eq!(client.get_claimable(&season, &user), vec![&env, 0]);
+    assert_eq!(
+        client.try_claim_reward(&user, &season, &1),
+        Err(Ok(SeasonPassError::ConditionNotMet))
+    );
+    assert_eq!(client.claim_reward(&user, &season, &0), None);
+    assert_eq!(token.balance(&user), 850);
+    assert_eq!(
+        client.try_claim_reward(&user, &season, &0),
+        Err(Ok(SeasonPassError::DuplicateOperation))
+    );
+
+    client.report_xp(&reporter, &season, &user, &400);
+    // The pass price alone no longer covers the second tier
+    assert_eq!(
+        client.try_claim_reward(&user, &season, &1),
+        Err(Ok(SeasonPassError::InsufficientRewardPool))
+    );
+    client.fund_season(&user, &season, &100);
+    client.claim_reward(&user, &season, &1);
+    assert_eq!(token.balance(&user), 1_000);
+    assert_eq!(client.get_season(&season).pool, 0);
+    assert_eq!(
+        client.get_pass(&season, &user).unwrap().claimed,
+        vec![&env, 0, 1]
+    );
+    assert_eq!(
+        client.try_claim_reward(&user, &season, &2),
+        Err(Ok(SeasonPassError::TierNotFound))
+    );
+}
+
+#[test]
+fn closing_a_season_expires_unclaimed_rewards() {
+    let env = Env::default();
+    let (client, admin, reporter, token, user) = setup_test(&env);
+    let season = client.create_season(&admin, &token.address, &200, &token_tiers(&env));
+    client.purchase_pass(&user, &season);
+    client.report_xp(&reporter, &season, &user, &100);
+
+    let treasury = Address::generate(&env);
+    assert_eq!(
+        client.try_withdraw_pool(&admin, &season, &treasury),
+        Err(Ok(SeasonPassError::InvalidStatus))
+    );
+    client.close_season(&admin, &season);
+    assert_eq!(client.get_claimable(&season, &user), vec![&env]);
+    assert_eq!(
+        client.try_claim_reward(&user, &season, &0),
+        Err(Ok(SeasonPassError::Expired))
+    );
+    assert_eq!(
+        client.try_report_xp(&reporter, &season, &user, &100),
+        Err(Ok(SeasonPassError::InvalidStatus))
+    );
+    assert_eq!(
+        client.try_purchase_pass(&Address::generate(&env), &season),
+        Err(Ok(SeasonPassError::InvalidStatus))
+    );
+
+    assert_eq!(client.withdraw_pool(&admin, &season, &treasury), 200);
+    assert_eq!(token.balance(&treasury), 200);
+}
+
+#[test]
+fn card_tiers_mint_through_player_card() {
+    let env = Env::default();
+    let (client, admin, reporter, token, user) = setup_test(&env);
+    let tiers = vec![
+        &env,
+        PassTier {
+            min_xp: 10,
+            reward: PassReward::Card(String::from_str(&env, "ipfs://season-1")),
+        },
+    ];
+    assert_eq!(
+        client.try_create_season(&admin, &token.address, &0, &tiers),
+        Err(Ok(SeasonPassError::InvalidConfig))
+    );
+
+    let player_card_id = env.register(PlayerCardContract, ());
+    let player_card = PlayerCardContractClient::new(&env, &player_card_id);
+    player_card.initialize(&admin);
+    player_card.add_minter(&client.address);
+    client.set_player_card(&admin, &player_card_id);
+    let season = client.create_season(&admin, &token.address, &0, &tiers);
+
+    client.purchase_pass(&user, &season);
+    assert_eq!(token.balance(&user), 1_000);
+    client.report_xp(&reporter, &season, &user, &10);
+    let card_id = client.claim_reward(&user, &season, &0).unwrap();
+    assert_eq!(player_card.owner_of(&card_id), user);
+}
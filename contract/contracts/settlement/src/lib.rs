@@ -1,18 +1,61 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Symbol, U256};
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    contract, contractimpl, contracttype, symbol_short, vec,
+    xdr::ToXdr,
+    Address, BytesN, Env, IntoVal, Symbol, Val, Vec,
+};
 
-use common::ContractError;
+use common::{
+    cleanup_operation, ensure_nonce, ensure_not_replayed, get_current_nonce, get_fee_config,
+    get_parlay_info, init_role, is_operation_executed, is_paused, pause_as_role, potential_payout,
+    publish_versioned_event, quote_fee, record_fee, require_not_paused, require_role,
+    resolve_address, set_fee_config, total_fees_collected, unpause_as_role, ContractError,
+    EscrowMode, FeeConfig, Parlay, Role, SettlementExecutedEvent, BALANCE_LEDGER_KEY,
+    EVENT_SCHEMA_VERSION, SETTLEMENT_EXECUTED_EVENT,
+};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
 
 #[contracttype]
-#[derive(Clone)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SettlementRecord {
-    pub bet_id: U256,
+    pub bet_id: u64,
     pub outcome: Symbol,
     pub bettor: Address,
     pub winner: Option<Address>,
+    /// Amount credited to the winner, net of `fee` and `rake`
     pub payout: i128,
+    pub fee: i128,
+    /// Share of the winnings forwarded to the treasury
+    pub rake: i128,
     pub timestamp: u64,
+    /// Idempotency hash the settlement ran under; `None` for nonce-guarded
+    /// settlements
+    pub operation_hash: Option<BytesN<32>>,
+}
+
+/// One bet to settle. `winner` is required for WIN and ignored otherwise.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementInput {
+    pub bet_id: u64,
+    pub bettor: Address,
+    pub winner: Option<Address>,
+    pub bet_amount: i128,
+    pub payout: i128,
+    pub settlement_type: Symbol,
+}
+
+/// House rake on winning settlements: `rake_bps` of the gross winnings
+/// (payout minus the stake) goes to `treasury`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RakeConfig {
+    pub rake_bps: u32,
+    pub treasury: Address,
 }
 
 #[contracttype]
@@ -20,109 +63,1064 @@ pub struct SettlementRecord {
 enum DataKey {
     BackendSigner,
     BalanceLedgerContract,
-    Settled(U256),
+    Settled(u64),
+    MaxBatchSize,
+    UserSettlements(Address),
+    ConfigRegistry,
+    RakeConfig,
+    BettingContract,
+    // Rake paid to a treasury (first) in a token or ledger (second) but not
+    // yet booked there because the treasury was paused
+    PendingRake(Address, Address),
+    Bankroll,
 }
 
+/// Highest rake that can be configured, in basis points of the winnings.
+pub const MAX_RAKE_BPS: u32 = 2_000;
+
+/// Revenue source the treasury books rake under.
+pub const RAKE_REVENUE_SOURCE: Symbol = symbol_short!("rake");
+
+/// Default cap on `settle_bets_batch` entries, adjustable by the backend.
+pub const DEFAULT_MAX_BATCH_SIZE: u32 = 50;
+
+/// Upper bound on the number of records a single
+/// `get_settlements_for_user` call returns.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
 #[contract]
 pub struct SettlementContract;
 
 #[contractimpl]
 impl SettlementContract {
-    pub fn initialize(env: Env, backend_signer: Address, balance_ledger: Address) {
+    pub fn initialize(
+        env: Env,
+        backend_signer: Address,
+        balance_ledger: Address,
+    ) -> Result<(), ContractError> {
         let storage = env.storage().persistent();
+        if storage.has(&DataKey::BackendSigner) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+
         storage.set(&DataKey::BackendSigner, &backend_signer);
         storage.set(&DataKey::BalanceLedgerContract, &balance_ledger);
+        init_role(&env, Role::Admin, &backend_signer);
+        init_role(&env, Role::Pauser, &backend_signer);
+        Ok(())
     }
 
-    fn require_backend_auth(env: &Env) -> Result<(), ContractError> {
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::grant_role(&env, &caller, role, &account)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        common::revoke_role(&env, &caller, role, &account)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), ContractError> {
+        common::renounce_role(&env, &account, role)
+    }
+
+    pub fn set_role_admin(
+        env: Env,
+        caller: Address,
+        role: Role,
+        admin_role: Role,
+    ) -> Result<(), ContractError> {
+        common::set_role_admin(&env, &caller, role, admin_role)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Stop duplicating events under their pre-versioning topics from
+    /// `until` on; `None` keeps the duplicates indefinitely.
+    pub fn set_legacy_events_until(
+        env: Env,
+        admin: Address,
+        until: Option<u64>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::set_legacy_events_until(&env, until);
+        Ok(())
+    }
+
+    pub fn get_legacy_events_until(env: Env) -> Option<u64> {
+        common::get_legacy_events_until(&env)
+    }
+
+    pub fn get_role_admin(env: Env, role: Role) -> Role {
+        common::get_role_admin(&env, role)
+    }
+
+    /// Ledger settlements are applied to, as resolved right now.
+    pub fn get_balance_ledger(env: Env) -> Option<Address> {
+        get_balance_ledger(&env).ok()
+    }
+
+    /// Resolve the ledger through a `config_registry` at call time, so it
+    /// can be redeployed without touching this contract. The address given
+    /// at initialize stays the fallback while the registry has none.
+    pub fn set_config_registry(
+        env: Env,
+        admin: Address,
+        registry: Option<Address>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
         let storage = env.storage().persistent();
-        let backend: Address = storage
-            .get(&DataKey::BackendSigner)
-            .ok_or(ContractError::Unauthorized)?;
-        backend.require_auth();
+        match registry {
+            Some(registry) => storage.set(&DataKey::ConfigRegistry, &registry),
+            None => storage.remove(&DataKey::ConfigRegistry),
+        }
         Ok(())
     }
 
-    pub fn is_settled(env: Env, bet_id: U256) -> bool {
+    pub fn get_config_registry(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::ConfigRegistry)
+    }
+
+    fn require_backend_auth(env: &Env) -> Result<(), ContractError> {
+        get_backend_signer(env)?.require_auth();
+        Ok(())
+    }
+
+    /// Stop all settlements during an incident; getters stay available.
+    pub fn pause(env: Env, pauser: Address) -> Result<(), ContractError> {
+        pause_as_role(&env, &pauser)
+    }
+
+    pub fn unpause(env: Env, admin: Address) -> Result<(), ContractError> {
+        unpause_as_role(&env, &admin)
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        is_paused(&env)
+    }
+
+    pub fn is_settled(env: Env, bet_id: u64) -> bool {
         env.storage().persistent().has(&DataKey::Settled(bet_id))
     }
 
+    pub fn get_settlement(env: Env, bet_id: u64) -> Option<SettlementRecord> {
+        env.storage().persistent().get(&DataKey::Settled(bet_id))
+    }
+
+    /// Page of the settlements `user` took part in as bettor or winner,
+    /// oldest first. `limit` is capped at `MAX_PAGE_SIZE`.
+    pub fn get_settlements_for_user(
+        env: Env,
+        user: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<SettlementRecord> {
+        let bet_ids = get_user_settlements(&env, &user);
+        let storage = env.storage().persistent();
+        let mut page = Vec::new(&env);
+        let end = offset
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(bet_ids.len());
+        for index in offset..end {
+            if let Some(record) = storage.get(&DataKey::Settled(bet_ids.get_unchecked(index))) {
+                page.push_back(record);
+            }
+        }
+        page
+    }
+
+    pub fn get_settlement_count_for_user(env: Env, user: Address) -> u32 {
+        get_user_settlements(&env, &user).len()
+    }
+
+    pub fn is_operation_executed(env: Env, operation_hash: BytesN<32>) -> bool {
+        is_operation_executed(&env, settlement_scope(&env), operation_hash)
+    }
+
+    pub fn cleanup_operation(env: Env, operation_hash: BytesN<32>) -> bool {
+        cleanup_operation(&env, settlement_scope(&env), operation_hash)
+    }
+
     /// Settle a bet. Caller must be backend signer (oracle/admin).
     /// Supports WIN, LOSS, DRAW (refund).
+    ///
+    /// `operation_hash` guards against replays; the ledger updates and the
+    /// settlement record are committed together or not at all.
     pub fn settle_bet(
         env: Env,
-        bet_id: U256,
-        bettor: Address,
-        winner: Option<Address>,
-        bet_amount: i128,
-        payout: i128,
-        settlement_type: Symbol,
+        operation_hash: BytesN<32>,
+        settlement: SettlementInput,
+        ttl_seconds: Option<u64>,
     ) -> Result<(), ContractError> {
         Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
 
-        let storage = env.storage().persistent();
-        if storage.has(&DataKey::Settled(bet_id.clone())) {
-            return Err(ContractError::BetAlreadySettled);
+        ensure_not_replayed(
+            &env,
+            settlement_scope(&env),
+            operation_hash.clone(),
+            ttl_seconds,
+        )?;
+
+        let record = apply_settlement(
+            &env,
+            &get_balance_ledger(&env)?,
+            settlement,
+            Some(operation_hash),
+        )?;
+        publish_bet_settled_event(&env, &record);
+
+        Ok(())
+    }
+
+    /// Same as `settle_bet`, but guarded by the backend signer's next nonce
+    /// instead of an operation hash. See `get_current_nonce`.
+    pub fn settle_bet_with_nonce(
+        env: Env,
+        nonce: u64,
+        settlement: SettlementInput,
+    ) -> Result<(), ContractError> {
+        let backend_signer = get_backend_signer(&env)?;
+        backend_signer.require_auth();
+        require_not_paused(&env)?;
+
+        ensure_nonce(&env, settlement_scope(&env), &backend_signer, nonce)?;
+
+        let record = apply_settlement(&env, &get_balance_ledger(&env)?, settlement, None)?;
+        publish_bet_settled_event(&env, &record);
+
+        Ok(())
+    }
+
+    /// Last nonce `settle_bet_with_nonce` accepted; the next call must use
+    /// this plus one.
+    pub fn get_current_nonce(env: Env) -> Result<u64, ContractError> {
+        Ok(get_current_nonce(
+            &env,
+            settlement_scope(&env),
+            &get_backend_signer(&env)?,
+        ))
+    }
+
+    /// Settle several bets under one idempotency hash. Any failing entry
+    /// (including a bet settled twice within the batch) reverts the whole batch.
+    pub fn settle_bets_batch(
+        env: Env,
+        operation_hash: BytesN<32>,
+        settlements: Vec<SettlementInput>,
+    ) -> Result<Vec<SettlementRecord>, ContractError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+
+        if settlements.is_empty() {
+            return Err(ContractError::InvalidAmount);
         }
+        if settlements.len() > get_max_batch_size(&env) {
+            return Err(ContractError::ExceedsLimit);
+        }
+
+        ensure_not_replayed(&env, settlement_scope(&env), operation_hash.clone(), None)?;
 
-        // Get balance ledger contract address
-        let bal_contract: Address = storage
-            .get(&DataKey::BalanceLedgerContract)
-            .ok_or(ContractError::Unauthorized)?;
+        let balance_ledger = get_balance_ledger(&env)?;
+        let mut records = Vec::new(&env);
+        let mut total_payout: i128 = 0;
+        for settlement in settlements.iter() {
+            let record = apply_settlement(
+                &env,
+                &balance_ledger,
+                settlement,
+                Some(operation_hash.clone()),
+            )?;
+            total_payout = total_payout
+                .checked_add(record.payout)
+                .ok_or(ContractError::InvalidAmount)?;
+            records.push_back(record);
+        }
 
-        let win_sym = Symbol::short("WIN");
-        let loss_sym = Symbol::short("LOSS");
-        let draw_sym = Symbol::short("DRAW");
+        for record in records.iter() {
+            publish_bet_settled_event(&env, &record);
+        }
+        publish_batch_settled_event(&env, &operation_hash, records.len(), total_payout);
 
-        // Perform atomic fund updates by invoking balance ledger contract methods.
+        Ok(records)
+    }
+
+    /// Settle a parlay held by the betting contract. A WIN pays the bettor
+    /// the parlay's potential payout, less the platform fee and rake; a
+    /// LOSS keeps the stake and a DRAW refunds it. Token-escrow parlays
+    /// are paid out of the betting contract; ledger-escrow parlays move on
+    /// the ledger and are then closed there. Needs a betting contract set
+    /// through `set_betting_contract`.
+    pub fn settle_parlay(
+        env: Env,
+        operation_hash: BytesN<32>,
+        parlay_id: u64,
+        settlement_type: Symbol,
+        ttl_seconds: Option<u64>,
+    ) -> Result<Parlay, ContractError> {
+        Self::require_backend_auth(&env)?;
+        require_not_paused(&env)?;
+
+        let (betting, mode) = get_betting_escrow(&env).ok_or(ContractError::NotInitialized)?;
+        ensure_not_replayed(
+            &env,
+            settlement_scope(&env),
+            operation_hash.clone(),
+            ttl_seconds,
+        )?;
+
+        let parlay = get_parlay_info(&env, &betting, parlay_id)?;
+        let win_sym = symbol_short!("WIN");
+        let loss_sym = symbol_short!("LOSS");
+        let draw_sym = symbol_short!("DRAW");
+
+        let mut payout = 0;
+        let mut fee = None;
+        let mut rake = None;
+        let mut net_payout = 0;
         if settlement_type == win_sym {
-            // Winner must be provided
-            let winner_addr = winner.ok_or(ContractError::InvalidBet)?;
-
-            // Deduct locked funds from bettor
-            env.invoke_contract(
-                &bal_contract,
-                &Symbol::new(&env, "apply_delta"),
-                (bettor.clone(), 0i128, -bet_amount),
-            );
-
-            // Credit payout to winner withdrawable
-            env.invoke_contract(
-                &bal_contract,
-                &Symbol::new(&env, "apply_delta"),
-                (winner_addr.clone(), payout, 0i128),
-            );
-        } else if settlement_type == loss_sym {
-            // Remove locked funds from bettor (platform keeps funds)
-            env.invoke_contract(
-                &bal_contract,
-                &Symbol::new(&env, "apply_delta"),
-                (bettor.clone(), 0i128, -bet_amount),
-            );
-        } else if settlement_type == draw_sym {
-            // Refund: move locked funds back to withdrawable
-            env.invoke_contract(
-                &bal_contract,
-                &Symbol::new(&env, "apply_delta"),
-                (bettor.clone(), bet_amount, -bet_amount),
-            );
-        } else {
+            payout = potential_payout(parlay.amount, parlay.combined_odds)?;
+            fee = quote_fee(&env, payout)?;
+            rake = quote_rake(&env, payout - parlay.amount)?;
+            net_payout = payout
+                - fee.as_ref().map_or(0, |(amount, _)| *amount)
+                - rake.as_ref().map_or(0, |(amount, _)| *amount);
+        } else if settlement_type != loss_sym && settlement_type != draw_sym {
             return Err(ContractError::InvalidStatus);
         }
 
-        // Mark settled and store record
-        let record = SettlementRecord {
-            bet_id: bet_id.clone(),
-            outcome: settlement_type.clone(),
-            bettor: bettor.clone(),
-            winner: winner.clone(),
-            payout,
-            timestamp: env.ledger().timestamp(),
+        let bettor = &parlay.bettor;
+        let bankroll = match mode {
+            EscrowMode::Token => get_bankroll(&env),
+            EscrowMode::Ledger(_) => None,
         };
-        storage.set(&DataKey::Settled(bet_id), &record);
+        let mut payouts = Vec::new(&env);
+        if let Some(bankroll) = &bankroll {
+            require_bankroll_token(&env, bankroll, &parlay.token)?;
+            if settlement_type == win_sym && payout > parlay.amount {
+                draw_from_bankroll(&env, bankroll, &betting, payout - parlay.amount)?;
+            } else if settlement_type == loss_sym {
+                payouts.push_back((env.current_contract_address(), parlay.amount));
+            }
+        }
+        match &mode {
+            EscrowMode::Token => {
+                if net_payout > 0 {
+                    payouts.push_back((bettor.clone(), net_payout));
+                }
+                if let Some((amount, fee_collector)) = &fee {
+                    payouts.push_back((fee_collector.clone(), *amount));
+                }
+                if let Some((amount, treasury)) = &rake {
+                    payouts.push_back((treasury.clone(), *amount));
+                }
+                if settlement_type == draw_sym {
+                    payouts.push_back((bettor.clone(), parlay.amount));
+                }
+            }
+            EscrowMode::Ledger(ledger) => {
+                if settlement_type == draw_sym {
+                    apply_delta(&env, ledger, bettor, parlay.amount, -parlay.amount);
+                } else {
+                    apply_delta(&env, ledger, bettor, 0i128, -parlay.amount);
+                }
+                if let Some((amount, fee_collector)) = &fee {
+                    apply_delta(&env, ledger, fee_collector, *amount, 0i128);
+                }
+                if let Some((amount, treasury)) = &rake {
+                    apply_delta(&env, ledger, treasury, *amount, 0i128);
+                }
+                if net_payout > 0 {
+                    apply_delta(&env, ledger, bettor, net_payout, 0i128);
+                }
+            }
+        }
+        let settled = close_parlay(&env, &betting, operation_hash, parlay_id, payouts)?;
+        if let Some(bankroll) = &bankroll {
+            if settlement_type == loss_sym {
+                return_to_bankroll(&env, bankroll, &parlay.token, parlay.amount)?;
+            }
+        }
+
+        // In token mode the fee and rake were paid in the parlay's token;
+        // on the ledger they are credit there
+        let token = match &mode {
+            EscrowMode::Token => Some(parlay.token.clone()),
+            EscrowMode::Ledger(_) => None,
+        };
+        if let Some((amount, fee_collector)) = &fee {
+            record_fee(&env, bettor, token.clone(), *amount, fee_collector)?;
+        }
+        if let Some((amount, treasury)) = &rake {
+            forward_rake(&env, treasury, &parlay.token, *amount)?;
+        }
+        publish_parlay_settled_event(&env, &settled, &settlement_type, net_payout);
 
+        Ok(settled)
+    }
+
+    pub fn set_max_batch_size(
+        env: Env,
+        admin: Address,
+        max_batch_size: u32,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        if max_batch_size == 0 {
+            return Err(ContractError::InvalidConfig);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::MaxBatchSize, &max_batch_size);
+        Ok(())
+    }
+
+    pub fn get_max_batch_size(env: Env) -> u32 {
+        get_max_batch_size(&env)
+    }
+
+    /// Take `fee_bps` of every winning payout and credit it to
+    /// `fee_collector`'s ledger balance, normally the treasury's account.
+    pub fn set_fee_config(
+        env: Env,
+        admin: Address,
+        fee_bps: u32,
+        fee_collector: Address,
+    ) -> Result<FeeConfig, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        set_fee_config(&env, fee_bps, &fee_collector)
+    }
+
+    pub fn get_fee_config(env: Env) -> Option<FeeConfig> {
+        get_fee_config(&env)
+    }
+
+    pub fn total_fees_collected(env: Env) -> i128 {
+        total_fees_collected(&env, None)
+    }
+
+    /// Betting contract whose bets these settlements close. Token-escrow
+    /// bets are paid out of it through `release_escrow`; ledger-escrow bets
+    /// move on the ledger as before and are then marked settled there. This
+    /// contract must be the betting contract's registered settlement
+    /// contract. `None` settles on the ledger alone.
+    pub fn set_betting_contract(
+        env: Env,
+        admin: Address,
+        betting: Option<Address>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let storage = env.storage().persistent();
+        match betting {
+            Some(betting) => storage.set(&DataKey::BettingContract, &betting),
+            None => storage.remove(&DataKey::BettingContract),
+        }
         Ok(())
     }
+
+    pub fn get_betting_contract(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::BettingContract)
+    }
+
+    /// House bankroll behind token-escrow payouts. A WIN draws the winnings
+    /// beyond the stake from it into the betting escrow through
+    /// `draw_payout`, and a LOSS hands the stake to it through
+    /// `return_loss`, so this contract must be one of its hooks. Bets in
+    /// any other token are refused with `TokenNotAllowed` while it is set.
+    /// `None` leaves winnings to be paid out of pooled escrow.
+    pub fn set_bankroll(
+        env: Env,
+        admin: Address,
+        bankroll: Option<Address>,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let storage = env.storage().persistent();
+        match bankroll {
+            Some(bankroll) => storage.set(&DataKey::Bankroll, &bankroll),
+            None => storage.remove(&DataKey::Bankroll),
+        }
+        Ok(())
+    }
+
+    pub fn get_bankroll(env: Env) -> Option<Address> {
+        get_bankroll(&env)
+    }
+
+    /// Take `rake_bps` of the winnings of every WIN settlement and forward
+    /// it to `treasury`: the rake is paid to the treasury, on the ledger or
+    /// out of token escrow, and booked there through `book_revenue`, so
+    /// this contract must be one of the treasury's revenue reporters. A
+    /// `rake_bps` of zero turns the rake off.
+    pub fn set_rake_config(
+        env: Env,
+        admin: Address,
+        rake_bps: u32,
+        treasury: Address,
+    ) -> Result<RakeConfig, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        if rake_bps > MAX_RAKE_BPS {
+            return Err(ContractError::InvalidConfig);
+        }
+
+        let config = RakeConfig { rake_bps, treasury };
+        env.storage()
+            .persistent()
+            .set(&DataKey::RakeConfig, &config);
+        Ok(config)
+    }
+
+    pub fn get_rake_config(env: Env) -> Option<RakeConfig> {
+        env.storage().persistent().get(&DataKey::RakeConfig)
+    }
+
+    /// Rake already paid to `treasury` in `token` (or as credit on the
+    /// ledger whose address is `token`) that is waiting to be booked.
+    pub fn get_pending_rake(env: Env, treasury: Address, token: Address) -> i128 {
+        get_pending_rake(&env, &treasury, &token)
+    }
+
+    /// Book rake that piled up while `treasury` was paused. Anyone can call
+    /// it; the next WIN settlement in the same token does the same. Returns
+    /// the amount booked.
+    pub fn forward_pending_rake(
+        env: Env,
+        treasury: Address,
+        token: Address,
+    ) -> Result<i128, ContractError> {
+        let pending = get_pending_rake(&env, &treasury, &token);
+        if pending > 0 {
+            book_rake(&env, &treasury, &token, pending)?;
+            env.storage()
+                .persistent()
+                .remove(&DataKey::PendingRake(treasury, token));
+        }
+        Ok(pending)
+    }
+}
+
+// Moves the funds for one bet and stores its record. Callers own auth,
+// pause and replay checks.
+fn apply_settlement(
+    env: &Env,
+    bal_contract: &Address,
+    settlement: SettlementInput,
+    operation_hash: Option<BytesN<32>>,
+) -> Result<SettlementRecord, ContractError> {
+    let SettlementInput {
+        bet_id,
+        bettor,
+        winner,
+        bet_amount,
+        payout,
+        settlement_type,
+    } = settlement;
+
+    if bet_amount <= 0 || payout < 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    let storage = env.storage().persistent();
+    if storage.has(&DataKey::Settled(bet_id)) {
+        return Err(ContractError::BetAlreadySettled);
+    }
+
+    let win_sym = symbol_short!("WIN");
+    let loss_sym = symbol_short!("LOSS");
+    let draw_sym = symbol_short!("DRAW");
+
+    // A WIN pays the platform fee on the payout and the rake on the
+    // winnings beyond the stake; the winner gets the rest.
+    let mut fee = None;
+    let mut rake = None;
+    if settlement_type == win_sym {
+        if winner.is_none() {
+            return Err(ContractError::InvalidBet);
+        }
+        fee = quote_fee(env, payout)?;
+        rake = quote_rake(env, payout - bet_amount)?;
+    } else if settlement_type != loss_sym && settlement_type != draw_sym {
+        return Err(ContractError::InvalidStatus);
+    }
+    let fee_amount = fee.as_ref().map_or(0, |(amount, _)| *amount);
+    let rake_amount = rake.as_ref().map_or(0, |(amount, _)| *amount);
+    let net_payout = payout - fee_amount - rake_amount;
+
+    match get_betting_escrow(env) {
+        // Token stakes sit in the betting contract, which pays them out
+        Some((betting, EscrowMode::Token)) => {
+            let mut payouts = Vec::new(env);
+            if settlement_type == win_sym {
+                if net_payout > 0 {
+                    payouts.push_back((winner.clone().unwrap(), net_payout));
+                }
+                if let Some((amount, fee_collector)) = &fee {
+                    payouts.push_back((fee_collector.clone(), *amount));
+                }
+                if let Some((amount, treasury)) = &rake {
+                    payouts.push_back((treasury.clone(), *amount));
+                }
+            } else if settlement_type == draw_sym {
+                payouts.push_back((bettor.clone(), bet_amount));
+            }
+
+            let bankroll = get_bankroll(env);
+            if let Some(bankroll) = &bankroll {
+                if settlement_type == win_sym && payout > bet_amount {
+                    draw_from_bankroll(env, bankroll, &betting, payout - bet_amount)?;
+                } else if settlement_type == loss_sym {
+                    payouts.push_back((env.current_contract_address(), bet_amount));
+                }
+            }
+
+            if payouts.is_empty() {
+                mark_bet_settled(env, &betting, bet_id);
+            } else {
+                let token = release_escrow(env, &betting, bet_id, &settlement_type, payouts)?;
+                if let Some(bankroll) = &bankroll {
+                    require_bankroll_token(env, bankroll, &token)?;
+                    if settlement_type == loss_sym {
+                        return_to_bankroll(env, bankroll, &token, bet_amount)?;
+                    }
+                }
+                if let Some((amount, fee_collector)) = &fee {
+                    let winner = winner.as_ref().unwrap();
+                    record_fee(env, winner, Some(token.clone()), *amount, fee_collector)?;
+                }
+                if let Some((amount, treasury)) = &rake {
+                    forward_rake(env, treasury, &token, *amount)?;
+                }
+            }
+        }
+        // Otherwise the stakes are locked balances on the ledger
+        escrow => {
+            if settlement_type == draw_sym {
+                // Refund: move locked funds back to withdrawable
+                apply_delta(env, bal_contract, &bettor, bet_amount, -bet_amount);
+            } else {
+                // Deduct locked funds from bettor (platform keeps a lost stake)
+                apply_delta(env, bal_contract, &bettor, 0i128, -bet_amount);
+            }
+            if settlement_type == win_sym {
+                let winner = winner.as_ref().unwrap();
+                if let Some((amount, fee_collector)) = &fee {
+                    apply_delta(env, bal_contract, fee_collector, *amount, 0i128);
+                    record_fee(env, winner, None, *amount, fee_collector)?;
+                }
+                if let Some((amount, treasury)) = &rake {
+                    apply_delta(env, bal_contract, treasury, *amount, 0i128);
+                    forward_rake(env, treasury, bal_contract, *amount)?;
+                }
+                apply_delta(env, bal_contract, winner, net_payout, 0i128);
+            }
+            if let Some((betting, _)) = escrow {
+                mark_bet_settled(env, &betting, bet_id);
+            }
+        }
+    }
+
+    // Mark settled and store record
+    let record = SettlementRecord {
+        bet_id,
+        outcome: settlement_type,
+        bettor,
+        winner,
+        payout: net_payout,
+        fee: fee_amount,
+        rake: rake_amount,
+        timestamp: env.ledger().timestamp(),
+        operation_hash,
+    };
+    storage.set(&DataKey::Settled(bet_id), &record);
+    common::bump_persistent(env, &DataKey::Settled(bet_id));
+    publish_settlement_executed_event(env, &record, bet_amount);
+
+    index_settlement(env, &record.bettor, bet_id);
+    if let Some(winner) = &record.winner {
+        if *winner != record.bettor {
+            index_settlement(env, winner, bet_id);
+        }
+    }
+
+    Ok(record)
+}
+
+/// Betting contract whose bets are settled here, with its escrow mode.
+fn get_betting_escrow(env: &Env) -> Option<(Address, EscrowMode)> {
+    let betting: Address = env.storage().persistent().get(&DataKey::BettingContract)?;
+    let mode = env.invoke_contract(&betting, &Symbol::new(env, "get_escrow_mode"), vec![env]);
+    Some((betting, mode))
+}
+
+// Pays a token-escrowed bet out of the betting contract and closes it there.
+// The bet can only be released once, so its id makes a unique operation hash
+// on the betting side even when a batch shares one hash here.
+fn release_escrow(
+    env: &Env,
+    betting: &Address,
+    bet_id: u64,
+    outcome: &Symbol,
+    payouts: Vec<(Address, i128)>,
+) -> Result<Address, ContractError> {
+    let operation_hash: BytesN<32> = env.crypto().sha256(&bet_id.to_xdr(env)).into();
+    match env.try_invoke_contract::<Address, ContractError>(
+        betting,
+        &Symbol::new(env, "release_escrow"),
+        vec![
+            env,
+            operation_hash.into_val(env),
+            bet_id.into_val(env),
+            outcome.into_val(env),
+            payouts.into_val(env),
+        ],
+    ) {
+        Ok(Ok(token)) => Ok(token),
+        Err(Ok(err)) => Err(err),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
+
+// Closes a parlay on the betting contract, paying `payouts` out of its token
+// escrow.
+fn close_parlay(
+    env: &Env,
+    betting: &Address,
+    operation_hash: BytesN<32>,
+    parlay_id: u64,
+    payouts: Vec<(Address, i128)>,
+) -> Result<Parlay, ContractError> {
+    match env.try_invoke_contract::<Parlay, ContractError>(
+        betting,
+        &Symbol::new(env, "settle_parlay"),
+        vec![
+            env,
+            operation_hash.into_val(env),
+            parlay_id.into_val(env),
+            payouts.into_val(env),
+        ],
+    ) {
+        Ok(Ok(parlay)) => Ok(parlay),
+        Err(Ok(err)) => Err(err),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
+
+// Closes a bet on the betting contract once its funds have moved here.
+fn mark_bet_settled(env: &Env, betting: &Address, bet_id: u64) {
+    env.invoke_contract::<Val>(
+        betting,
+        &Symbol::new(env, "mark_bet_settled"),
+        vec![env, bet_id.into_val(env)],
+    );
+}
+
+fn get_bankroll(env: &Env) -> Option<Address> {
+    env.storage().persistent().get(&DataKey::Bankroll)
+}
+
+// Only stakes in the bankroll's own token can be backed by it.
+fn require_bankroll_token(
+    env: &Env,
+    bankroll: &Address,
+    token: &Address,
+) -> Result<(), ContractError> {
+    let bankroll_token: Option<Address> =
+        env.invoke_contract(bankroll, &Symbol::new(env, "get_token"), vec![env]);
+    if bankroll_token.as_ref() != Some(token) {
+        return Err(ContractError::TokenNotAllowed);
+    }
+    Ok(())
+}
+
+// Tops the betting escrow up with winnings beyond the stake, so it can pay
+// the whole win out.
+fn draw_from_bankroll(
+    env: &Env,
+    bankroll: &Address,
+    betting: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    match env.try_invoke_contract::<i128, ContractError>(
+        bankroll,
+        &Symbol::new(env, "draw_payout"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            betting.into_val(env),
+            amount.into_val(env),
+        ],
+    ) {
+        Ok(Ok(_)) => Ok(()),
+        Err(Ok(err)) => Err(err),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
+
+// Hands a lost stake, released from escrow to this contract, on to the
+// bankroll. The bankroll pulls it, so the transfer is pre-authorized for
+// exactly that amount.
+fn return_to_bankroll(
+    env: &Env,
+    bankroll: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: token.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (env.current_contract_address(), bankroll.clone(), amount).into_val(env),
+            },
+            sub_invocations: vec![env],
+        }),
+    ]);
+
+    match env.try_invoke_contract::<i128, ContractError>(
+        bankroll,
+        &Symbol::new(env, "return_loss"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            amount.into_val(env),
+        ],
+    ) {
+        Ok(Ok(_)) => Ok(()),
+        Err(Ok(err)) => Err(err),
+        _ => Err(ContractError::TransferFailed),
+    }
+}
+
+/// Rake owed on `winnings` with the treasury it goes to, rounded down.
+/// `None` while no rake is configured, the bet won nothing beyond its
+/// stake, or the rake rounds to zero.
+fn quote_rake(env: &Env, winnings: i128) -> Result<Option<(i128, Address)>, ContractError> {
+    let config: RakeConfig = match env.storage().persistent().get(&DataKey::RakeConfig) {
+        Some(config) => config,
+        None => return Ok(None),
+    };
+    if winnings <= 0 {
+        return Ok(None);
+    }
+    let rake = winnings
+        .checked_mul(config.rake_bps as i128)
+        .ok_or(ContractError::InvalidAmount)?
+        / 10_000;
+    Ok((rake > 0).then_some((rake, config.treasury)))
+}
+
+// Books rake already paid to the treasury, in `token` or as credit on the
+// ledger whose address is `token`, as revenue there, together with any rake
+// held back earlier. While the treasury is paused the rake is held back
+// instead of failing the settlement; other rejections still fail it.
+fn forward_rake(
+    env: &Env,
+    treasury: &Address,
+    token: &Address,
+    rake: i128,
+) -> Result<(), ContractError> {
+    let key = DataKey::PendingRake(treasury.clone(), token.clone());
+    let total = get_pending_rake(env, treasury, token)
+        .checked_add(rake)
+        .ok_or(ContractError::InvalidAmount)?;
+    let paused = env.invoke_contract::<bool>(treasury, &Symbol::new(env, "is_paused"), vec![env]);
+    if paused {
+        env.storage().persistent().set(&key, &total);
+        common::bump_persistent(env, &key);
+        return Ok(());
+    }
+    book_rake(env, treasury, token, total)?;
+    env.storage().persistent().remove(&key);
+    Ok(())
+}
+
+fn get_pending_rake(env: &Env, treasury: &Address, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PendingRake(treasury.clone(), token.clone()))
+        .unwrap_or(0)
+}
+
+fn book_rake(
+    env: &Env,
+    treasury: &Address,
+    token: &Address,
+    rake: i128,
+) -> Result<(), ContractError> {
+    match env.try_invoke_contract::<i128, ContractError>(
+        treasury,
+        &Symbol::new(env, "book_revenue"),
+        vec![
+            env,
+            env.current_contract_address().into_val(env),
+            RAKE_REVENUE_SOURCE.into_val(env),
+            token.into_val(env),
+            rake.into_val(env),
+        ],
+    ) {
+        Ok(Ok(_)) => Ok(()),
+        Err(Ok(err)) => Err(err),
+        _ => Err(ContractError::Unauthorized),
+    }
+}
+
+fn get_user_settlements(env: &Env, user: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::UserSettlements(user.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn index_settlement(env: &Env, user: &Address, bet_id: u64) {
+    let mut bet_ids = get_user_settlements(env, user);
+    bet_ids.push_back(bet_id);
+    let key = DataKey::UserSettlements(user.clone());
+    env.storage().persistent().set(&key, &bet_ids);
+    common::bump_persistent(env, &key);
+}
+
+fn settlement_scope(env: &Env) -> Symbol {
+    Symbol::new(env, "settlement")
+}
+
+fn apply_delta(
+    env: &Env,
+    balance_ledger: &Address,
+    user: &Address,
+    withdrawable_delta: i128,
+    locked_delta: i128,
+) {
+    env.invoke_contract::<Val>(
+        balance_ledger,
+        &Symbol::new(env, "apply_delta"),
+        vec![
+            env,
+            user.into_val(env),
+            withdrawable_delta.into_val(env),
+            locked_delta.into_val(env),
+            // Settlements carry their own replay protection
+            None::<BytesN<32>>.into_val(env),
+            None::<u64>.into_val(env),
+        ],
+    );
+}
+
+fn get_balance_ledger(env: &Env) -> Result<Address, ContractError> {
+    let storage = env.storage().persistent();
+    if let Some(registry) = storage.get::<_, Address>(&DataKey::ConfigRegistry) {
+        if let Some(ledger) = resolve_address(env, &registry, BALANCE_LEDGER_KEY)? {
+            return Ok(ledger);
+        }
+    }
+    storage
+        .get(&DataKey::BalanceLedgerContract)
+        .ok_or(ContractError::Unauthorized)
+}
+
+fn get_max_batch_size(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::MaxBatchSize)
+        .unwrap_or(DEFAULT_MAX_BATCH_SIZE)
+}
+
+fn get_backend_signer(env: &Env) -> Result<Address, ContractError> {
+    // Read by every settlement
+    common::bump_persistent(env, &DataKey::BackendSigner);
+    env.storage()
+        .persistent()
+        .get(&DataKey::BackendSigner)
+        .ok_or(ContractError::Unauthorized)
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_bet_settled_event(env: &Env, record: &SettlementRecord) {
+    env.events().publish(
+        (Symbol::new(env, "bet_settled"), record.bet_id),
+        record.clone(),
+    );
+}
+
+fn publish_settlement_executed_event(env: &Env, record: &SettlementRecord, bet_amount: i128) {
+    publish_versioned_event(
+        env,
+        (Symbol::new(env, "settlement_executed"), record.bet_id),
+        SETTLEMENT_EXECUTED_EVENT,
+        record.bettor.clone(),
+        SettlementExecutedEvent {
+            version: EVENT_SCHEMA_VERSION,
+            bet_id: record.bet_id,
+            outcome: record.outcome.clone(),
+            bettor: record.bettor.clone(),
+            winner: record.winner.clone(),
+            bet_amount,
+            payout: record.payout,
+            fee: record.fee,
+            rake: record.rake,
+            timestamp: record.timestamp,
+        },
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_parlay_settled_event(env: &Env, parlay: &Parlay, outcome: &Symbol, payout: i128) {
+    env.events().publish(
+        (Symbol::new(env, "parlay_settled"), parlay.parlay_id),
+        (outcome.clone(), parlay.bettor.clone(), payout),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_batch_settled_event(
+    env: &Env,
+    operation_hash: &BytesN<32>,
+    count: u32,
+    total_payout: i128,
+) {
+    env.events().publish(
+        (Symbol::new(env, "settlement_batch"), operation_hash.clone()),
+        (count, total_payout),
+    );
 }
 
 #[cfg(test)]
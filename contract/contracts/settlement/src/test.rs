@@ -1,514 +1,997 @@
 #![cfg(test)]
 
 use super::*;
+use common::MAX_FEE_BPS;
+
+use balance_ledger::{BalanceLedgerContract, BalanceLedgerContractClient};
+use soroban_sdk::{
+    testutils::Address as _, testutils::Events as _, testutils::Ledger, Address, BytesN, Env,
+    TryFromVal,
+};
+
+fn setup_test(
+    env: &Env,
+) -> (
+    SettlementContractClient<'_>,
+    BalanceLedgerContractClient<'_>,
+    Address,
+    Address,
+    Address,
+) {
+    env.mock_all_auths();
+
+    let backend = Address::generate(env);
+    let ledger_id = env.register(BalanceLedgerContract, ());
+    let ledger = BalanceLedgerContractClient::new(env, &ledger_id);
+    ledger.initialize(&backend);
+
+    let contract_id = env.register(SettlementContract, ());
+    let client = SettlementContractClient::new(env, &contract_id);
+    client.initialize(&backend, &ledger_id);
+
+    // Bettor has 1_000 withdrawable with 300 locked in open bets
+    let bettor = Address::generate(env);
+    ledger.set_balance(&bettor, &1_000, &300, &None, &None);
+
+    let winner = Address::generate(env);
 
-use soroban_sdk::{testutils::Address as _, testutils::Ledger, Address, BytesN, Env, U256};
+    (client, ledger, backend, bettor, winner)
+}
+
+fn hash(env: &Env, seed: u8) -> BytesN<32> {
+    BytesN::from_array(env, &[seed; 32])
+}
+
+fn win() -> Symbol {
+    symbol_short!("WIN")
+}
 
 #[test]
-fn rejects_duplicate_operation_ids() {
+fn initialize_only_once() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, ledger, _backend, bettor, _winner) = setup_test(&env);
 
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
-    let operation_hash = BytesN::from_array(&env, &[11u8; 32]);
+    assert_eq!(
+        client.try_initialize(&bettor, &ledger.address),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+    assert_eq!(client.get_balance_ledger(), Some(ledger.address.clone()));
+}
 
-    client.initialize(&backend);
+#[test]
+fn win_debits_locked_stake_and_credits_winner() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, winner) = setup_test(&env);
 
     client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 7),
-        &winner,
-        &1_250,
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: Some(winner.clone()),
+            bet_amount: 100,
+            payout: 250,
+            settlement_type: win(),
+        },
         &None,
     );
 
-    assert_eq!(
-        client.try_settle_bet(
-            &operation_hash,
-            &U256::from_u32(&env, 8),
-            &winner,
-            &1_900,
-            &None,
-        ),
-        Err(Ok(ContractError::DuplicateOperation))
-    );
+    let bettor_balance = ledger.get_balance(&bettor);
+    assert_eq!(bettor_balance.withdrawable, 1_000);
+    assert_eq!(bettor_balance.locked, 200);
+    assert_eq!(ledger.get_withdrawable(&winner), 250);
+
+    let record = client.get_settlement(&1).unwrap();
+    assert_eq!(record.outcome, win());
+    assert_eq!(record.payout, 250);
+    assert!(client.is_settled(&1));
 }
 
 #[test]
-fn supports_ttl_cleanup_for_operations() {
+fn fee_is_taken_from_winning_payouts() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, ledger, backend, bettor, winner) = setup_test(&env);
+    let treasury = Address::generate(&env);
 
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
-    let operation_hash = BytesN::from_array(&env, &[22u8; 32]);
-
-    client.initialize(&backend);
+    assert_eq!(
+        client.try_set_fee_config(&backend, &(MAX_FEE_BPS + 1), &treasury),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.set_fee_config(&backend, &200, &treasury);
 
     client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 5),
-        &winner,
-        &300,
-        &Some(5),
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: Some(winner.clone()),
+            bet_amount: 100,
+            payout: 250,
+            settlement_type: win(),
+        },
+        &None,
     );
-    assert!(client.is_operation_executed(&operation_hash));
 
-    env.ledger().with_mut(|li| {
-        li.timestamp += 6;
-    });
+    // 2% of 250, rounded down, goes to the collector's ledger balance
+    assert_eq!(ledger.get_withdrawable(&winner), 245);
+    assert_eq!(ledger.get_withdrawable(&treasury), 5);
+    let record = client.get_settlement(&1).unwrap();
+    assert_eq!((record.payout, record.fee), (245, 5));
+    assert_eq!(client.total_fees_collected(), 5);
+}
 
-    assert!(client.cleanup_operation(&operation_hash));
-    assert!(!client.is_operation_executed(&operation_hash));
+#[test]
+fn rake_on_winnings_is_forwarded_to_the_treasury() {
+    let env = Env::default();
+    let (client, ledger, backend, bettor, winner) = setup_test(&env);
+    let treasury_id = env.register(treasury::Treasury, ());
+    let treasury = treasury::TreasuryClient::new(&env, &treasury_id);
+    treasury.initialize(&backend);
 
+    assert_eq!(
+        client.try_set_rake_config(&backend, &(MAX_RAKE_BPS + 1), &treasury_id),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.set_fee_config(&backend, &200, &backend);
+    client.set_rake_config(&backend, &1_000, &treasury_id);
+
+    let settlement = SettlementInput {
+        bet_id: 1,
+        bettor: bettor.clone(),
+        winner: Some(winner.clone()),
+        bet_amount: 100,
+        payout: 250,
+        settlement_type: win(),
+    };
+    // The treasury only books rake from its revenue reporters
+    assert_eq!(
+        client.try_settle_bet(&hash(&env, 1), &settlement, &None),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    treasury.set_revenue_reporter(&backend, &client.address, &true);
+    client.settle_bet(&hash(&env, 1), &settlement, &None);
+
+    // 10% of the 150 won beyond the stake, on top of 2% of 250 in fees
+    let event = env
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| {
+            *topics
+                == (
+                    SETTLEMENT_EXECUTED_EVENT,
+                    EVENT_SCHEMA_VERSION,
+                    bettor.clone(),
+                )
+                    .into_val(&env)
+        })
+        .map(|(_, _, data)| SettlementExecutedEvent::try_from_val(&env, &data).unwrap())
+        .unwrap();
+    assert_eq!((event.payout, event.fee, event.rake), (230, 5, 15));
+    assert_eq!(event.payout + event.fee + event.rake, settlement.payout);
+    assert_eq!(ledger.get_withdrawable(&winner), 230);
+    assert_eq!(ledger.get_withdrawable(&treasury_id), 15);
+    assert_eq!(
+        treasury.get_revenue(&RAKE_REVENUE_SOURCE, &ledger.address),
+        15
+    );
+    let record = client.get_settlement(&1).unwrap();
+    assert_eq!((record.payout, record.fee, record.rake), (230, 5, 15));
+
+    // Nothing is won beyond the stake, so no rake
     client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 6),
-        &winner,
-        &450,
-        &Some(5),
+        &hash(&env, 2),
+        &SettlementInput {
+            bet_id: 2,
+            payout: 100,
+            ..settlement.clone()
+        },
+        &None,
+    );
+    let record = client.get_settlement(&2).unwrap();
+    assert_eq!((record.payout, record.fee, record.rake), (98, 2, 0));
+    // A paused treasury holds the rake back instead of failing the payout
+    treasury.pause(&backend);
+    client.settle_bet(
+        &hash(&env, 3),
+        &SettlementInput {
+            bet_id: 3,
+            ..settlement
+        },
+        &None,
+    );
+    assert_eq!(ledger.get_withdrawable(&treasury_id), 30);
+    assert_eq!(client.get_pending_rake(&treasury_id, &ledger.address), 15);
+    assert_eq!(
+        treasury.get_revenue(&RAKE_REVENUE_SOURCE, &ledger.address),
+        15
+    );
+    assert_eq!(
+        client.try_forward_pending_rake(&treasury_id, &ledger.address),
+        Err(Ok(ContractError::Paused))
     );
-}
 
-// ============================================
-// Authorization Tests - Unauthorized Calls
-// ============================================
+    treasury.unpause(&backend);
+    assert_eq!(
+        client.forward_pending_rake(&treasury_id, &ledger.address),
+        15
+    );
+    assert_eq!(client.get_pending_rake(&treasury_id, &ledger.address), 0);
+    assert_eq!(
+        treasury.get_revenue(&RAKE_REVENUE_SOURCE, &ledger.address),
+        30
+    );
+}
 
 #[test]
-#[should_panic(expected = "Unauthorized")]
-fn settle_bet_without_backend_auth_fails() {
+fn nonce_settlements_must_arrive_in_order() {
     let env = Env::default();
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
+    let (client, ledger, _backend, bettor, _winner) = setup_test(&env);
+    let settlement = |bet_id: u64| SettlementInput {
+        bet_id,
+        bettor: bettor.clone(),
+        winner: None,
+        bet_amount: 100,
+        payout: 0,
+        settlement_type: symbol_short!("LOSS"),
+    };
+
+    assert_eq!(client.get_current_nonce(), 0);
+    assert_eq!(
+        client.try_settle_bet_with_nonce(&2, &settlement(1)),
+        Err(Ok(ContractError::InvalidNonce))
+    );
+    client.settle_bet_with_nonce(&1, &settlement(1));
+    assert_eq!(client.get_current_nonce(), 1);
 
-    client.initialize(&backend);
+    assert_eq!(
+        client.try_settle_bet_with_nonce(&1, &settlement(2)),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+    client.settle_bet_with_nonce(&2, &settlement(2));
+    assert!(client.is_settled(&2));
+    assert_eq!(ledger.get_locked(&bettor), 100);
+}
 
-    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+#[test]
+fn loss_burns_locked_stake() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, _winner) = setup_test(&env);
 
-    // Don't mock any auths - should fail with Unauthorized
     client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 1),
-        &winner,
-        &1000,
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: None,
+            bet_amount: 100,
+            payout: 0,
+            settlement_type: symbol_short!("LOSS"),
+        },
         &None,
     );
+
+    let bettor_balance = ledger.get_balance(&bettor);
+    assert_eq!(bettor_balance.withdrawable, 1_000);
+    assert_eq!(bettor_balance.locked, 200);
 }
 
 #[test]
-#[should_panic]
-fn settle_bet_before_initialization_fails() {
+fn draw_refunds_locked_stake() {
     let env = Env::default();
-    env.mock_all_auths();
-
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
-
-    // Don't initialize - try to settle immediately
-    // This will panic because there's no backend signer stored
-    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let (client, ledger, _backend, bettor, _winner) = setup_test(&env);
 
     client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 1),
-        &winner,
-        &1000,
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: None,
+            bet_amount: 100,
+            payout: 0,
+            settlement_type: symbol_short!("DRAW"),
+        },
         &None,
     );
-}
 
-// ============================================
-// Edge Cases
-// ============================================
+    let bettor_balance = ledger.get_balance(&bettor);
+    assert_eq!(bettor_balance.withdrawable, 1_100);
+    assert_eq!(bettor_balance.locked, 200);
+}
 
 #[test]
-fn is_operation_executed_returns_false_for_new_operation() {
+fn win_requires_winner() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, _ledger, _backend, bettor, _winner) = setup_test(&env);
 
-    let backend = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
+    assert_eq!(
+        client.try_settle_bet(
+            &hash(&env, 1),
+            &SettlementInput {
+                bet_id: 1,
+                bettor: bettor.clone(),
+                winner: None,
+                bet_amount: 100,
+                payout: 250,
+                settlement_type: win(),
+            },
+            &None,
+        ),
+        Err(Ok(ContractError::InvalidBet))
+    );
+    assert!(!client.is_operation_executed(&hash(&env, 1)));
+}
 
-    client.initialize(&backend);
+#[test]
+fn rejects_duplicate_operation_ids() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, winner) = setup_test(&env);
 
-    let new_operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+    client.settle_bet(
+        &hash(&env, 11),
+        &SettlementInput {
+            bet_id: 7,
+            bettor: bettor.clone(),
+            winner: Some(winner.clone()),
+            bet_amount: 100,
+            payout: 1_250,
+            settlement_type: win(),
+        },
+        &None,
+    );
 
-    assert!(!client.is_operation_executed(&new_operation_hash));
+    // Same operation hash but different bet_id
+    assert_eq!(
+        client.try_settle_bet(
+            &hash(&env, 11),
+            &SettlementInput {
+                bet_id: 8,
+                bettor: bettor.clone(),
+                winner: Some(winner.clone()),
+                bet_amount: 100,
+                payout: 1_900,
+                settlement_type: win(),
+            },
+            &None,
+        ),
+        Err(Ok(ContractError::DuplicateOperation))
+    );
+    assert_eq!(ledger.get_withdrawable(&winner), 1_250);
 }
 
 #[test]
-fn cleanup_operation_returns_false_for_nonexistent_operation() {
+fn rejects_resettling_a_bet_under_new_operation_hash() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, ledger, _backend, bettor, _winner) = setup_test(&env);
+    let bet_id = 7;
 
-    let backend = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
+    client.settle_bet(
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id,
+            bettor: bettor.clone(),
+            winner: None,
+            bet_amount: 100,
+            payout: 0,
+            settlement_type: symbol_short!("LOSS"),
+        },
+        &None,
+    );
 
-    client.initialize(&backend);
+    assert_eq!(
+        client.try_settle_bet(
+            &hash(&env, 2),
+            &SettlementInput {
+                bet_id,
+                bettor: bettor.clone(),
+                winner: None,
+                bet_amount: 100,
+                payout: 0,
+                settlement_type: symbol_short!("LOSS"),
+            },
+            &None,
+        ),
+        Err(Ok(ContractError::BetAlreadySettled))
+    );
+    assert_eq!(ledger.get_locked(&bettor), 200);
+}
 
-    let nonexistent_hash = BytesN::from_array(&env, &[99u8; 32]);
+#[test]
+fn failed_ledger_update_rolls_back_replay_guard() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, _winner) = setup_test(&env);
+
+    // Bettor only has 300 locked, so the ledger rejects the debit
+    let res = client.try_settle_bet(
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: None,
+            bet_amount: 301,
+            payout: 0,
+            settlement_type: symbol_short!("LOSS"),
+        },
+        &None,
+    );
+    assert!(res.is_err());
 
-    assert!(!client.cleanup_operation(&nonexistent_hash));
+    assert!(!client.is_operation_executed(&hash(&env, 1)));
+    assert!(!client.is_settled(&1));
+    assert_eq!(ledger.get_locked(&bettor), 300);
 }
 
 #[test]
-fn cleanup_operation_returns_false_before_ttl_expires() {
+fn rejects_invalid_amounts_and_outcomes() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, _ledger, _backend, bettor, winner) = setup_test(&env);
+    let bet_id = 1;
 
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
-
-    client.initialize(&backend);
+    assert_eq!(
+        client.try_settle_bet(
+            &hash(&env, 1),
+            &SettlementInput {
+                bet_id,
+                bettor: bettor.clone(),
+                winner: Some(winner.clone()),
+                bet_amount: 100,
+                payout: -500,
+                settlement_type: win(),
+            },
+            &None,
+        ),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_settle_bet(
+            &hash(&env, 1),
+            &SettlementInput {
+                bet_id,
+                bettor: bettor.clone(),
+                winner: None,
+                bet_amount: 0,
+                payout: 0,
+                settlement_type: symbol_short!("LOSS"),
+            },
+            &None,
+        ),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_settle_bet(
+            &hash(&env, 1),
+            &SettlementInput {
+                bet_id,
+                bettor: bettor.clone(),
+                winner: None,
+                bet_amount: 100,
+                payout: 0,
+                settlement_type: symbol_short!("VOID"),
+            },
+            &None,
+        ),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+    assert!(!client.is_operation_executed(&hash(&env, 1)));
+}
 
-    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+#[test]
+fn settle_bet_with_large_payout_succeeds() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, winner) = setup_test(&env);
+    let large_payout: i128 = 1_000_000_000_000;
 
-    // Settle with 100 second TTL
     client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 1),
-        &winner,
-        &1000,
-        &Some(100),
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: u64::MAX,
+            bettor: bettor.clone(),
+            winner: Some(winner.clone()),
+            bet_amount: 100,
+            payout: large_payout,
+            settlement_type: win(),
+        },
+        &None,
     );
 
-    // Advance time but not past TTL
-    env.ledger().with_mut(|li| {
-        li.timestamp += 50;
-    });
-
-    // Cleanup should fail since TTL hasn't expired
-    assert!(!client.cleanup_operation(&operation_hash));
-    assert!(client.is_operation_executed(&operation_hash));
+    assert_eq!(ledger.get_withdrawable(&winner), large_payout);
 }
 
 #[test]
 fn settle_bet_with_zero_payout_succeeds() {
     let env = Env::default();
-    env.mock_all_auths();
-
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
-
-    client.initialize(&backend);
+    let (client, ledger, _backend, bettor, winner) = setup_test(&env);
 
-    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
-
-    // Settle with zero payout (e.g., lost bet)
     client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 1),
-        &winner,
-        &0,
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: Some(winner.clone()),
+            bet_amount: 100,
+            payout: 0,
+            settlement_type: win(),
+        },
         &None,
     );
 
-    assert!(client.is_operation_executed(&operation_hash));
+    assert!(client.is_operation_executed(&hash(&env, 1)));
+    assert_eq!(ledger.get_locked(&bettor), 200);
+    assert_eq!(ledger.get_withdrawable(&winner), 0);
 }
 
 #[test]
-fn settle_bet_with_large_payout_succeeds() {
+fn large_bet_id_handling() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, _ledger, _backend, bettor, _winner) = setup_test(&env);
 
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
+    client.settle_bet(&hash(&env, 1), &loss(u64::MAX, &bettor, 100), &None);
 
-    client.initialize(&backend);
+    assert!(client.is_operation_executed(&hash(&env, 1)));
+    assert!(client.is_settled(&u64::MAX));
+    assert_eq!(client.get_settlement(&u64::MAX).unwrap().bet_id, u64::MAX);
+}
 
-    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
-    let large_payout: i128 = 1_000_000_000_000;
+#[test]
+fn same_operation_hash_different_bet_ids_fails() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, _winner) = setup_test(&env);
 
-    // Settle with large payout
-    client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 1),
-        &winner,
-        &large_payout,
-        &None,
+    client.settle_bet(&hash(&env, 1), &loss(1, &bettor, 100), &None);
+
+    assert_eq!(
+        client.try_settle_bet(&hash(&env, 1), &loss(2, &bettor, 100), &None),
+        Err(Ok(ContractError::DuplicateOperation))
     );
+    assert!(!client.is_settled(&2));
+    assert_eq!(ledger.get_locked(&bettor), 200);
+}
 
-    assert!(client.is_operation_executed(&operation_hash));
+#[test]
+fn multiple_bets_same_winner_different_operation_hashes() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, winner) = setup_test(&env);
+
+    for (seed, payout) in [(1u8, 100i128), (2, 200), (3, 300)] {
+        client.settle_bet(
+            &hash(&env, seed),
+            &SettlementInput {
+                bet_id: seed as u64,
+                bettor: bettor.clone(),
+                winner: Some(winner.clone()),
+                bet_amount: 100,
+                payout,
+                settlement_type: win(),
+            },
+            &None,
+        );
+    }
+
+    assert!(client.is_operation_executed(&hash(&env, 1)));
+    assert!(client.is_operation_executed(&hash(&env, 2)));
+    assert!(client.is_operation_executed(&hash(&env, 3)));
+    assert_eq!(ledger.get_withdrawable(&winner), 600);
+    assert_eq!(ledger.get_locked(&bettor), 0);
 }
 
 #[test]
-fn settle_bet_with_negative_payout_succeeds() {
+fn different_winners_same_operation_hash_isolation() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, ledger, _backend, bettor, winner) = setup_test(&env);
+    let other_winner = Address::generate(&env);
+
+    for (seed, winner, payout) in [(1u8, &winner, 100i128), (2, &other_winner, 200)] {
+        client.settle_bet(
+            &hash(&env, seed),
+            &SettlementInput {
+                bet_id: seed as u64,
+                bettor: bettor.clone(),
+                winner: Some(winner.clone()),
+                bet_amount: 100,
+                payout,
+                settlement_type: win(),
+            },
+            &None,
+        );
+    }
 
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
+    assert!(client.is_operation_executed(&hash(&env, 1)));
+    assert!(client.is_operation_executed(&hash(&env, 2)));
+    assert_eq!(ledger.get_withdrawable(&winner), 100);
+    assert_eq!(ledger.get_withdrawable(&other_winner), 200);
+}
 
-    client.initialize(&backend);
+#[test]
+fn settle_bet_with_zero_ttl_immediate_cleanup() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, _winner) = setup_test(&env);
 
-    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
-    let negative_payout: i128 = -500;
+    // A zero TTL expires the replay record at once
+    client.settle_bet(&hash(&env, 1), &loss(1, &bettor, 100), &Some(0));
+    assert!(!client.is_operation_executed(&hash(&env, 1)));
+    assert!(client.cleanup_operation(&hash(&env, 1)));
 
-    // Settle with negative payout (e.g., penalty)
-    client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 1),
-        &winner,
-        &negative_payout,
-        &None,
+    // The settlement itself still stands, so the bet cannot settle twice
+    assert!(client.is_settled(&1));
+    assert_eq!(
+        client.try_settle_bet(&hash(&env, 1), &loss(1, &bettor, 100), &None),
+        Err(Ok(ContractError::BetAlreadySettled))
     );
+    assert_eq!(ledger.get_locked(&bettor), 200);
+}
 
-    assert!(client.is_operation_executed(&operation_hash));
+#[test]
+fn cleanup_operation_returns_false_before_ttl_expires() {
+    let env = Env::default();
+    let (client, _ledger, _backend, bettor, _winner) = setup_test(&env);
+
+    client.settle_bet(&hash(&env, 1), &loss(1, &bettor, 100), &Some(100));
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 50;
+    });
+
+    assert!(!client.cleanup_operation(&hash(&env, 1)));
+    assert!(client.is_operation_executed(&hash(&env, 1)));
 }
 
 #[test]
-fn settle_bet_with_zero_ttl_immediate_cleanup() {
+fn supports_ttl_cleanup_for_operations() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, _ledger, _backend, bettor, _winner) = setup_test(&env);
+    let operation_hash = hash(&env, 22);
 
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
+    client.settle_bet(
+        &operation_hash,
+        &SettlementInput {
+            bet_id: 5,
+            bettor: bettor.clone(),
+            winner: None,
+            bet_amount: 100,
+            payout: 0,
+            settlement_type: symbol_short!("DRAW"),
+        },
+        &Some(100),
+    );
+    assert!(client.is_operation_executed(&operation_hash));
 
-    client.initialize(&backend);
+    // Not expired yet
+    env.ledger().with_mut(|li| {
+        li.timestamp += 50;
+    });
+    assert!(!client.cleanup_operation(&operation_hash));
+    assert!(client.is_operation_executed(&operation_hash));
 
-    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+    env.ledger().with_mut(|li| {
+        li.timestamp += 51;
+    });
+    assert!(client.cleanup_operation(&operation_hash));
+    assert!(!client.is_operation_executed(&operation_hash));
 
-    // Settle with 0 TTL - operation is immediately expired per is_expired logic
-    // (timestamp - executed_at >= 0 is always true when timestamp >= executed_at)
+    // The hash can be reused for a different bet once cleaned up
     client.settle_bet(
         &operation_hash,
-        &U256::from_u32(&env, 1),
-        &winner,
-        &1000,
-        &Some(0),
+        &SettlementInput {
+            bet_id: 6,
+            bettor: bettor.clone(),
+            winner: None,
+            bet_amount: 100,
+            payout: 0,
+            settlement_type: symbol_short!("DRAW"),
+        },
+        &Some(100),
     );
+}
+
+#[test]
+fn operation_queries_for_unknown_hashes() {
+    let env = Env::default();
+    let (client, _ledger, _backend, _bettor, _winner) = setup_test(&env);
 
-    // With TTL of 0, the operation is considered expired immediately
-    // so it won't be stored (it gets cleaned up during ensure_not_replayed)
-    // This is existing contract behavior, not a bug
+    assert!(!client.is_operation_executed(&hash(&env, 99)));
+    assert!(!client.cleanup_operation(&hash(&env, 99)));
 }
 
 #[test]
-fn multiple_bets_same_winner_different_operation_hashes() {
+fn settle_bet_before_initialization_fails() {
     let env = Env::default();
     env.mock_all_auths();
 
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
     let contract_id = env.register(SettlementContract, ());
     let client = SettlementContractClient::new(&env, &contract_id);
 
-    client.initialize(&backend);
+    assert_eq!(
+        client.try_settle_bet(
+            &hash(&env, 1),
+            &SettlementInput {
+                bet_id: 1,
+                bettor: Address::generate(&env),
+                winner: None,
+                bet_amount: 100,
+                payout: 0,
+                settlement_type: symbol_short!("LOSS"),
+            },
+            &None,
+        ),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
 
-    // Settle multiple bets for the same winner
-    let operation_hash1 = BytesN::from_array(&env, &[1u8; 32]);
-    let operation_hash2 = BytesN::from_array(&env, &[2u8; 32]);
-    let operation_hash3 = BytesN::from_array(&env, &[3u8; 32]);
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn settle_bet_without_backend_auth_fails() {
+    let env = Env::default();
+    let (client, _ledger, _backend, bettor, _winner) = setup_test(&env);
 
+    // Drop the mocked auths; the backend signature is now missing
+    env.set_auths(&[]);
     client.settle_bet(
-        &operation_hash1,
-        &U256::from_u32(&env, 1),
-        &winner,
-        &100,
-        &None,
-    );
-    client.settle_bet(
-        &operation_hash2,
-        &U256::from_u32(&env, 2),
-        &winner,
-        &200,
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: None,
+            bet_amount: 100,
+            payout: 0,
+            settlement_type: symbol_short!("LOSS"),
+        },
         &None,
     );
-    client.settle_bet(
-        &operation_hash3,
-        &U256::from_u32(&env, 3),
-        &winner,
-        &300,
-        &None,
-    );
-
-    assert!(client.is_operation_executed(&operation_hash1));
-    assert!(client.is_operation_executed(&operation_hash2));
-    assert!(client.is_operation_executed(&operation_hash3));
 }
 
 #[test]
-fn same_operation_hash_different_bet_ids_fails() {
+fn pause_blocks_settlement() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, _ledger, backend, bettor, _winner) = setup_test(&env);
 
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
+    client.pause(&backend);
+    assert!(client.is_paused());
+    assert_eq!(
+        client.try_settle_bet(
+            &hash(&env, 1),
+            &SettlementInput {
+                bet_id: 1,
+                bettor: bettor.clone(),
+                winner: None,
+                bet_amount: 100,
+                payout: 0,
+                settlement_type: symbol_short!("LOSS"),
+            },
+            &None,
+        ),
+        Err(Ok(ContractError::Paused))
+    );
 
-    client.initialize(&backend);
+    client.unpause(&backend);
+    assert!(!client.is_paused());
+}
 
-    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+fn loss(bet_id: u64, bettor: &Address, bet_amount: i128) -> SettlementInput {
+    SettlementInput {
+        bet_id,
+        bettor: bettor.clone(),
+        winner: None,
+        bet_amount,
+        payout: 0,
+        settlement_type: symbol_short!("LOSS"),
+    }
+}
 
-    // First settlement succeeds
-    client.settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 1),
-        &winner,
-        &100,
-        &None,
+#[test]
+fn batch_settles_all_bets_under_one_hash() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, winner) = setup_test(&env);
+
+    let settlements = soroban_sdk::vec![
+        &env,
+        SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: Some(winner.clone()),
+            bet_amount: 100,
+            payout: 180,
+            settlement_type: win(),
+        },
+        loss(2, &bettor, 100),
+        SettlementInput {
+            settlement_type: symbol_short!("DRAW"),
+            ..loss(3, &bettor, 50)
+        },
+    ];
+
+    let records = client.settle_bets_batch(&hash(&env, 1), &settlements);
+    assert_eq!(records.len(), 3);
+    assert!(client.is_settled(&2));
+
+    let bettor_balance = ledger.get_balance(&bettor);
+    assert_eq!(bettor_balance.locked, 50);
+    assert_eq!(bettor_balance.withdrawable, 1_050);
+    assert_eq!(ledger.get_withdrawable(&winner), 180);
+
+    assert_eq!(
+        client.try_settle_bets_batch(
+            &hash(&env, 1),
+            &soroban_sdk::vec![&env, loss(4, &bettor, 10)]
+        ),
+        Err(Ok(ContractError::DuplicateOperation))
     );
+}
 
-    // Second settlement with same operation hash but different bet_id should fail
-    let result = client.try_settle_bet(
-        &operation_hash,
-        &U256::from_u32(&env, 2),
-        &winner,
-        &200,
-        &None,
+#[test]
+fn batch_is_all_or_nothing() {
+    let env = Env::default();
+    let (client, ledger, _backend, bettor, _winner) = setup_test(&env);
+
+    // The duplicate bet_id makes the last entry fail
+    let settlements = soroban_sdk::vec![
+        &env,
+        loss(1, &bettor, 100),
+        loss(2, &bettor, 100),
+        loss(1, &bettor, 100),
+    ];
+    assert_eq!(
+        client.try_settle_bets_batch(&hash(&env, 1), &settlements),
+        Err(Ok(ContractError::BetAlreadySettled))
     );
 
-    assert_eq!(result, Err(Ok(ContractError::DuplicateOperation)));
+    assert!(!client.is_settled(&1));
+    assert!(!client.is_operation_executed(&hash(&env, 1)));
+    assert_eq!(ledger.get_locked(&bettor), 300);
 }
 
 #[test]
-fn different_winners_same_operation_hash_isolation() {
+fn batch_size_is_bounded() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, _ledger, backend, bettor, _winner) = setup_test(&env);
+    assert_eq!(client.get_max_batch_size(), DEFAULT_MAX_BATCH_SIZE);
+
+    client.set_max_batch_size(&backend, &2);
+    let settlements = soroban_sdk::vec![
+        &env,
+        loss(1, &bettor, 10),
+        loss(2, &bettor, 10),
+        loss(3, &bettor, 10),
+    ];
+    assert_eq!(
+        client.try_settle_bets_batch(&hash(&env, 1), &settlements),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+    assert_eq!(
+        client.try_settle_bets_batch(&hash(&env, 1), &soroban_sdk::vec![&env]),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_set_max_batch_size(&backend, &0),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+}
 
-    let backend = Address::generate(&env);
-    let winner1 = Address::generate(&env);
-    let winner2 = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
+#[test]
+fn pauser_role_can_pause_without_admin_rights() {
+    let env = Env::default();
+    let (client, _ledger, backend, _bettor, _winner) = setup_test(&env);
+    let pauser = Address::generate(&env);
 
-    client.initialize(&backend);
+    assert_eq!(
+        client.try_pause(&pauser),
+        Err(Ok(ContractError::Unauthorized))
+    );
 
-    let operation_hash1 = BytesN::from_array(&env, &[1u8; 32]);
-    let operation_hash2 = BytesN::from_array(&env, &[2u8; 32]);
+    client.grant_role(&backend, &Role::Pauser, &pauser);
+    client.pause(&pauser);
+    assert!(client.is_paused());
 
-    // Settle bets for different winners
-    client.settle_bet(
-        &operation_hash1,
-        &U256::from_u32(&env, 1),
-        &winner1,
-        &100,
-        &None,
+    assert_eq!(
+        client.try_unpause(&pauser),
+        Err(Ok(ContractError::Unauthorized))
     );
-    client.settle_bet(
-        &operation_hash2,
-        &U256::from_u32(&env, 2),
-        &winner2,
-        &200,
-        &None,
+    assert_eq!(
+        client.try_set_max_batch_size(&pauser, &10),
+        Err(Ok(ContractError::Unauthorized))
     );
 
-    assert!(client.is_operation_executed(&operation_hash1));
-    assert!(client.is_operation_executed(&operation_hash2));
+    client.unpause(&backend);
+    assert!(!client.is_paused());
 }
 
 #[test]
-fn large_bet_id_handling() {
+fn settlements_are_indexed_per_user() {
     let env = Env::default();
-    env.mock_all_auths();
-
-    let backend = Address::generate(&env);
-    let winner = Address::generate(&env);
-    let contract_id = env.register(SettlementContract, ());
-    let client = SettlementContractClient::new(&env, &contract_id);
+    let (client, _ledger, _backend, bettor, winner) = setup_test(&env);
 
-    client.initialize(&backend);
-
-    let operation_hash = BytesN::from_array(&env, &[1u8; 32]);
+    client.settle_bet(
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: Some(winner.clone()),
+            bet_amount: 100,
+            payout: 250,
+            settlement_type: win(),
+        },
+        &None,
+    );
+    client.settle_bet_with_nonce(
+        &1,
+        &SettlementInput {
+            bet_id: 2,
+            bettor: bettor.clone(),
+            winner: None,
+            bet_amount: 100,
+            payout: 0,
+            settlement_type: symbol_short!("LOSS"),
+        },
+    );
 
-    // Use a large U256 value for bet_id
-    let large_bet_id = U256::from_u128(&env, u128::MAX);
+    let record = client.get_settlement(&1).unwrap();
+    assert_eq!(record.operation_hash, Some(hash(&env, 1)));
+    let record = client.get_settlement(&2).unwrap();
+    assert_eq!(record.operation_hash, None);
 
-    client.settle_bet(&operation_hash, &large_bet_id, &winner, &1000, &None);
+    assert_eq!(client.get_settlement_count_for_user(&bettor), 2);
+    let page = client.get_settlements_for_user(&bettor, &1, &10);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get_unchecked(0).bet_id, 2);
 
-    assert!(client.is_operation_executed(&operation_hash));
+    let won = client.get_settlements_for_user(&winner, &0, &10);
+    assert_eq!(won.len(), 1);
+    assert_eq!(won.get_unchecked(0).bet_id, 1);
+    assert_eq!(
+        client
+            .get_settlements_for_user(&Address::generate(&env), &0, &10)
+            .len(),
+        0
+    );
 }
 
 #[test]
-fn test_settle_win_loss_draw() {
-    use soroban_sdk::{testutils::Address as _, Address};
-
+fn balance_ledger_resolves_through_config_registry() {
     let env = Env::default();
-    env.mock_all_auths();
+    let (client, ledger, backend, bettor, _winner) = setup_test(&env);
+
+    // A redeployed ledger registered in the registry takes over
+    let new_ledger_id = env.register(BalanceLedgerContract, ());
+    let new_ledger = BalanceLedgerContractClient::new(&env, &new_ledger_id);
+    new_ledger.initialize(&backend);
+    new_ledger.set_balance(&bettor, &0, &300, &None, &None);
+
+    let registry_id = env.register(config_registry::ConfigRegistryContract, ());
+    let registry = config_registry::ConfigRegistryContractClient::new(&env, &registry_id);
+    registry.initialize(
+        &backend,
+        &0,
+        &soroban_sdk::Map::new(&env),
+        &soroban_sdk::Map::new(&env),
+    );
+    assert_eq!(
+        client.try_set_config_registry(&bettor, &Some(registry_id.clone())),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.set_config_registry(&backend, &Some(registry_id.clone()));
+
+    // Nothing registered yet: the initialize-time ledger is used
+    assert_eq!(client.get_balance_ledger(), Some(ledger.address.clone()));
 
-    let backend = Address::generate(&env);
-
-    // Deploy balance ledger and initialize
-    let bl_contract_id = env.register(balance_ledger::BalanceLedgerContract, ());
-    let bl_client = balance_ledger::BalanceLedgerContractClient::new(&env, &bl_contract_id);
-    bl_client.initialize(&backend);
-
-    // Deploy settlement contract and initialize with balance ledger address
-    let st_contract_id = env.register(SettlementContract, ());
-    let st_client = SettlementContractClient::new(&env, &st_contract_id);
-    let bl_addr = Address::Contract(bl_contract_id.clone());
-    st_client.initialize(&backend, &bl_addr);
-
-    // Prepare bettor and winner
-    let bettor = Address::generate(&env);
-    let winner = Address::generate(&env);
-
-    // Fund and lock bettor funds
-    bl_client.set_balance(&bettor, &1_000, &0);
-    bl_client.lock_funds(&bettor, &100);
-
-    let bet_id = U256::from_u64(42);
-
-    // Settle WIN: bettor locked 100 -> winner gets payout 200
-    let win_sym = soroban_sdk::Symbol::short("WIN");
-    let res = st_client.settle_bet(
-        &bet_id,
-        &bettor,
-        &Some(winner.clone()),
-        &100,
-        &200,
-        &win_sym,
-    );
-    assert!(res.is_ok());
-
-    // Check balances: bettor locked decreased by 100, winner withdrawable increased by 200
-    let bettor_balance = bl_client.get_balance(&bettor);
-    assert_eq!(bettor_balance.locked, 0);
-
-    let winner_balance = bl_client.get_withdrawable(&winner);
-    assert_eq!(winner_balance, 200);
-
-    // Attempt to re-settle same bet -> should fail
-    let res2 = st_client.try_settle_bet(
-        &bet_id,
-        &bettor,
-        &Some(winner.clone()),
-        &100,
-        &200,
-        &win_sym,
-    );
-    assert!(res2.is_err());
-
-    // Test DRAW / refund for another bet
-    let bet_id2 = U256::from_u64(43);
-    bl_client.set_balance(&bettor, &500, &0);
-    bl_client.lock_funds(&bettor, &50);
-    let draw_sym = soroban_sdk::Symbol::short("DRAW");
-    let res3 = st_client.settle_bet(&bet_id2, &bettor, &None, &50, &0, &draw_sym);
-    assert!(res3.is_ok());
-
-    let after_refund = bl_client.get_balance(&bettor);
-    assert_eq!(after_refund.withdrawable, 500);
-    assert_eq!(after_refund.locked, 0);
+    let action = config_registry::ConfigAction::SetAddress(
+        Symbol::new(&env, BALANCE_LEDGER_KEY),
+        Some(new_ledger_id.clone()),
+    );
+    let id = registry.queue_action(&backend, &action);
+    registry.execute_action(&backend, &id, &action);
+    assert_eq!(client.get_balance_ledger(), Some(new_ledger_id));
+
+    client.settle_bet(
+        &hash(&env, 1),
+        &SettlementInput {
+            bet_id: 1,
+            bettor: bettor.clone(),
+            winner: None,
+            bet_amount: 300,
+            payout: 0,
+            settlement_type: symbol_short!("LOSS"),
+        },
+        &None,
+    );
+    assert_eq!(new_ledger.get_locked(&bettor), 0);
+    assert_eq!(ledger.get_locked(&bettor), 300);
 }
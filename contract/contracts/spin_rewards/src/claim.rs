@@ -0,0 +1,91 @@
+use crate::errors::RewardError;
+use crate::events;
+use crate::reward::{get_pending_total, reserve_reward, set_pending_total};
+use crate::storage::{DataKey, PendingReward};
+use soroban_sdk::{token, Address, Env};
+
+/// How long granted rewards wait for a claim unless the admin sets another
+/// period.
+pub const DEFAULT_CLAIM_PERIOD: u64 = 30 * 86_400;
+
+pub fn get_claim_period(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKey::ClaimPeriod)
+        .unwrap_or(DEFAULT_CLAIM_PERIOD)
+}
+
+pub fn get_pending(env: &Env, user: &Address) -> Option<PendingReward> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Pending(user.clone()))
+}
+
+fn set_pending(env: &Env, user: &Address, pending: &PendingReward) {
+    let key = DataKey::Pending(user.clone());
+    env.storage().persistent().set(&key, pending);
+    common::bump_persistent(env, &key);
+}
+
+fn remove_pending(env: &Env, user: &Address, amount: i128) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Pending(user.clone()));
+    set_pending_total(env, get_pending_total(env) - amount);
+}
+
+/// Reserve `amount` from the pool and add it to `user`'s pending rewards.
+/// A balance that has already expired goes back to the pool first.
+pub fn grant(env: &Env, user: &Address, amount: i128) -> Result<PendingReward, RewardError> {
+    expire(env, user);
+    reserve_reward(env, user, amount)?;
+
+    let pending = PendingReward {
+        amount: get_pending(env, user).map_or(0, |pending| pending.amount) + amount,
+        expires_at: env.ledger().timestamp() + get_claim_period(env),
+    };
+    set_pending(env, user, &pending);
+    set_pending_total(env, get_pending_total(env) + amount);
+    events::reward_granted(env, user.clone(), amount, pending.expires_at);
+    Ok(pending)
+}
+
+/// Pay out everything pending for `user`.
+pub fn claim(env: &Env, user: &Address, reward_token: &Address) -> Result<i128, RewardError> {
+    let pending = get_pending(env, user).ok_or(RewardError::NothingToClaim)?;
+    if pending.expires_at <= env.ledger().timestamp() {
+        return Err(RewardError::ClaimExpired);
+    }
+
+    remove_pending(env, user, pending.amount);
+    token::Client::new(env, reward_token).transfer(
+        &env.current_contract_address(),
+        user,
+        &pending.amount,
+    );
+    events::reward_claimed(env, user.clone(), pending.amount);
+    Ok(pending.amount)
+}
+
+/// Return `user`'s pending rewards to the pool if they have expired.
+/// Returns the amount returned.
+pub fn expire(env: &Env, user: &Address) -> i128 {
+    let Some(pending) = get_pending(env, user) else {
+        return 0;
+    };
+    if pending.expires_at > env.ledger().timestamp() {
+        return 0;
+    }
+
+    remove_pending(env, user, pending.amount);
+    let pool: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::PoolBalance)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&DataKey::PoolBalance, &(pool + pending.amount));
+    events::claim_expired(env, user.clone(), pending.amount);
+    pending.amount
+}
@@ -1,11 +1,24 @@
 use soroban_sdk::contracterror;
 
 #[contracterror]
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
 pub enum RewardError {
     NotAuthorized = 1,
     InsufficientPool = 2,
     ExceedsPerSpinCap = 3,
     ExceedsUserCap = 4,
     ExceedsTotalCap = 5,
+    Paused = 6,
+    AlreadyInitialized = 7,
+    NotInitialized = 8,
+    InvalidAmount = 9,
+    DuplicateOperation = 10,
+    InvalidRewardTable = 11,
+    ExceedsPeriodCap = 12,
+    NothingToClaim = 13,
+    ClaimExpired = 14,
+    InvalidConfig = 15,
+    TokenNotAllowed = 16,
+    MintFailed = 17,
 }
@@ -1,15 +1,86 @@
-use soroban_sdk::{symbol_short, Env, Address};
+use crate::storage::RewardConfig;
+use soroban_sdk::{symbol_short, Address, BytesN, Env, Symbol};
 
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
 pub fn reward_distributed(env: &Env, user: Address, amount: i128) {
+    env.events()
+        .publish((symbol_short!("reward"),), (user, amount));
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn pool_funded(env: &Env, funder: Address, amount: i128) {
+    env.events()
+        .publish((symbol_short!("funded"),), (funder, amount));
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn spin_outcome(env: &Env, operation_hash: BytesN<32>, user: Address, tier_index: u32) {
+    env.events().publish(
+        (Symbol::new(env, "spin_outcome"), user),
+        (operation_hash, tier_index),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn reward_granted(env: &Env, user: Address, amount: i128, expires_at: u64) {
+    env.events()
+        .publish((symbol_short!("granted"), user), (amount, expires_at));
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn reward_claimed(env: &Env, user: Address, amount: i128) {
+    env.events()
+        .publish((symbol_short!("claimed"), user), amount);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn claim_expired(env: &Env, user: Address, amount: i128) {
+    env.events()
+        .publish((Symbol::new(env, "claim_expired"), user), amount);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn config_updated(env: &Env, admin: Address, config: RewardConfig) {
+    env.events()
+        .publish((Symbol::new(env, "config_updated"), admin), config);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn pool_withdrawn(env: &Env, to: Address, amount: i128) {
+    env.events()
+        .publish((Symbol::new(env, "pool_withdrawn"), to), amount);
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn token_pool_funded(env: &Env, token: Address, funder: Address, amount: i128) {
+    env.events()
+        .publish((symbol_short!("funded"), token), (funder, amount));
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn token_reward_distributed(env: &Env, token: Address, user: Address, amount: i128) {
+    env.events()
+        .publish((symbol_short!("reward"), token), (user, amount));
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn nft_rewarded(
+    env: &Env,
+    operation_hash: BytesN<32>,
+    user: Address,
+    nft_contract: Address,
+    token_id: u64,
+) {
     env.events().publish(
-        (symbol_short!("reward"),),
-        (user, amount),
+        (Symbol::new(env, "nft_rewarded"), user),
+        (operation_hash, nft_contract, token_id),
     );
 }
 
-pub fn pool_funded(env: &Env, amount: i128) {
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+pub fn multiplier_changed(env: &Env, user: Address, previous_bps: u32, multiplier_bps: u32) {
     env.events().publish(
-        (symbol_short!("funded"),),
-        amount,
+        (Symbol::new(env, "multiplier_changed"), user),
+        (previous_bps, multiplier_bps),
     );
 }
@@ -1,48 +1,639 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, Env, Address};
+use common::{pausable, CardAttributes, Role};
+use soroban_sdk::{contract, contractimpl, token, Address, BytesN, Env, Symbol, Val, Vec};
 
-mod storage;
-mod reward;
+mod claim;
 mod errors;
 mod events;
 mod nft;
+mod reward;
+mod storage;
+mod streak;
+mod table;
+mod tokens;
 
-use storage::{DataKey, RewardConfig};
 use errors::RewardError;
+use storage::{DataKey, PendingReward, PeriodCap, PoolStats, RewardConfig, TokenRewardConfig};
+pub use streak::{SpinStreak, StreakConfig};
+pub use table::*;
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
 
 #[contract]
 pub struct SpinRewards;
 
 #[contractimpl]
 impl SpinRewards {
+    pub fn init(
+        env: Env,
+        admin: Address,
+        backend_signer: Address,
+        config: RewardConfig,
+    ) -> Result<(), RewardError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(RewardError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        config.validate()?;
 
-    pub fn init(env: Env, admin: Address, config: RewardConfig) {
         env.storage().instance().set(&DataKey::Admin, &admin);
+        common::init_role(&env, Role::Admin, &admin);
+        common::init_role(&env, Role::Pauser, &admin);
+        common::init_role(&env, Role::Operator, &backend_signer);
         env.storage().instance().set(&DataKey::Config, &config);
         env.storage().instance().set(&DataKey::PoolBalance, &0i128);
-        env.storage().instance().set(&DataKey::TotalDistributed, &0i128);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalDistributed, &0i128);
+        Ok(())
+    }
+
+    /// Replace the reward config. The reward token can only change while
+    /// the pool and pending claims are empty, since both are held in it.
+    pub fn update_config(
+        env: Env,
+        admin: Address,
+        new_config: RewardConfig,
+    ) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        new_config.validate()?;
+        let config: RewardConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config)
+            .ok_or(RewardError::NotInitialized)?;
+        if new_config.reward_token != config.reward_token
+            && (Self::get_pool_balance(env.clone()) > 0 || reward::get_pending_total(&env) > 0)
+        {
+            return Err(RewardError::InvalidConfig);
+        }
+
+        env.storage().instance().set(&DataKey::Config, &new_config);
+        events::config_updated(&env, admin, new_config);
+        Ok(())
+    }
+
+    /// Same as `update_config`, kept for existing callers.
+    pub fn set_config(env: Env, admin: Address, config: RewardConfig) -> Result<(), RewardError> {
+        Self::update_config(env, admin, config)
+    }
+
+    pub fn get_config(env: Env) -> Option<RewardConfig> {
+        env.storage().instance().get(&DataKey::Config)
+    }
+
+    /// Backend signers are `Role::Operator` holders; rotate them with
+    /// `grant_role` / `revoke_role`.
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), RewardError> {
+        common::grant_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), RewardError> {
+        common::revoke_role(&env, &caller, role, &account).map_err(role_error)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), RewardError> {
+        common::renounce_role(&env, &account, role).map_err(role_error)
+    }
+
+    pub fn set_role_admin(
+        env: Env,
+        caller: Address,
+        role: Role,
+        admin_role: Role,
+    ) -> Result<(), RewardError> {
+        common::set_role_admin(&env, &caller, role, admin_role).map_err(role_error)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn get_role_admin(env: Env, role: Role) -> Role {
+        common::get_role_admin(&env, role)
+    }
+
+    /// Send a share of every reward payout to a jackpot contract that lists
+    /// this contract as a contributor.
+    pub fn set_jackpot(env: Env, admin: Address, jackpot: Address) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        env.storage().instance().set(&DataKey::Jackpot, &jackpot);
+        Ok(())
+    }
+
+    pub fn get_jackpot(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Jackpot)
+    }
+
+    pub fn get_pool_balance(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::PoolBalance)
+            .unwrap_or(0)
+    }
+
+    pub fn get_pool_stats(env: Env) -> Result<PoolStats, RewardError> {
+        let config: RewardConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config)
+            .ok_or(RewardError::NotInitialized)?;
+        Ok(PoolStats {
+            pool_balance: Self::get_pool_balance(env.clone()),
+            pending_total: reward::get_pending_total(&env),
+            total_distributed: env
+                .storage()
+                .instance()
+                .get(&DataKey::TotalDistributed)
+                .unwrap_or(0),
+            token_balance: token::Client::new(&env, &config.reward_token)
+                .balance(&env.current_contract_address()),
+        })
+    }
+
+    /// Send `amount` of the pool to `to`, e.g. to retire a campaign. Pending
+    /// claims are not touched. Works while paused.
+    pub fn withdraw_pool(
+        env: Env,
+        admin: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        if amount <= 0 {
+            return Err(RewardError::InvalidAmount);
+        }
+        let pool = Self::get_pool_balance(env.clone());
+        if amount > pool {
+            return Err(RewardError::InsufficientPool);
+        }
+        let config: RewardConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config)
+            .ok_or(RewardError::NotInitialized)?;
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PoolBalance, &(pool - amount));
+        token::Client::new(&env, &config.reward_token).transfer(
+            &env.current_contract_address(),
+            &to,
+            &amount,
+        );
+        events::pool_withdrawn(&env, to, amount);
+        Ok(())
     }
 
-    pub fn fund_pool(env: Env, amount: i128) {
-        let pool: i128 = env.storage().instance().get(&DataKey::PoolBalance).unwrap_or(0);
-        env.storage().instance().set(&DataKey::PoolBalance, &(pool + amount));
-        events::pool_funded(&env, amount);
+    /// Halt pool funding and reward payouts during an incident.
+    pub fn pause(env: Env, pauser: Address) -> Result<(), RewardError> {
+        pausable::pause_as_role(&env, &pauser).map_err(role_error)
     }
 
+    pub fn unpause(env: Env, admin: Address) -> Result<(), RewardError> {
+        pausable::unpause_as_role(&env, &admin).map_err(role_error)
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        pausable::is_paused(&env)
+    }
+
+    /// Move `amount` of the reward token from `funder` into the pool
+    pub fn fund_pool(env: Env, funder: Address, amount: i128) -> Result<(), RewardError> {
+        funder.require_auth();
+        Self::require_not_paused(&env)?;
+        if amount <= 0 {
+            return Err(RewardError::InvalidAmount);
+        }
+
+        let config: RewardConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config)
+            .ok_or(RewardError::NotInitialized)?;
+        token::Client::new(&env, &config.reward_token).transfer(
+            &funder,
+            env.current_contract_address(),
+            &amount,
+        );
+
+        let pool: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::PoolBalance)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::PoolBalance, &(pool + amount));
+        events::pool_funded(&env, funder, amount);
+        Ok(())
+    }
+
+    /// Pay `user` `amount` of the reward token from the pool. Each
+    /// `operation_hash` pays once, so the backend can safely retry; with
+    /// `ttl_seconds` the record can be dropped with
+    /// `cleanup_reward_operation` once it expires.
     pub fn reward_xlm(
         env: Env,
+        operator: Address,
         user: Address,
         amount: i128,
+        operation_hash: BytesN<32>,
+        ttl_seconds: Option<u64>,
     ) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Operator, &operator)?;
+        Self::require_not_paused(&env)?;
+        common::ensure_not_replayed(
+            &env,
+            Symbol::new(&env, "spin_reward"),
+            operation_hash,
+            ttl_seconds,
+        )
+        .map_err(|_| RewardError::DuplicateOperation)?;
         reward::distribute_xlm(&env, user, amount)
     }
 
+    /// Pay `user` `amount` of `token`: the primary reward token through
+    /// `reward_xlm`'s pool and caps, any other allowed token from its own
+    /// pool. Shares `reward_xlm`'s operation hashes.
+    pub fn reward_token(
+        env: Env,
+        operator: Address,
+        user: Address,
+        token: Address,
+        amount: i128,
+        operation_hash: BytesN<32>,
+        ttl_seconds: Option<u64>,
+    ) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Operator, &operator)?;
+        Self::require_not_paused(&env)?;
+        let config: RewardConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config)
+            .ok_or(RewardError::NotInitialized)?;
+        common::ensure_not_replayed(
+            &env,
+            Symbol::new(&env, "spin_reward"),
+            operation_hash,
+            ttl_seconds,
+        )
+        .map_err(|_| RewardError::DuplicateOperation)?;
+
+        if token == config.reward_token {
+            reward::distribute_xlm(&env, user, amount)
+        } else {
+            tokens::distribute(&env, &user, &token, amount)
+        }
+    }
+
+    /// Allow `token` as an extra reward token with its own caps, update
+    /// them, or delist it with `None`. The primary reward token is
+    /// configured through `update_config` instead.
+    pub fn set_token_config(
+        env: Env,
+        admin: Address,
+        token: Address,
+        config: Option<TokenRewardConfig>,
+    ) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        let primary: RewardConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config)
+            .ok_or(RewardError::NotInitialized)?;
+        if token == primary.reward_token {
+            return Err(RewardError::InvalidConfig);
+        }
+        tokens::set_token_config(&env, &token, config)
+    }
+
+    pub fn get_token_config(env: Env, token: Address) -> Option<TokenRewardConfig> {
+        tokens::get_token_config(&env, &token)
+    }
+
+    /// Extra reward tokens currently allowed
+    pub fn get_reward_tokens(env: Env) -> Vec<Address> {
+        tokens::get_reward_tokens(&env)
+    }
+
+    /// Move `amount` of an allowed extra `token` from `funder` into its pool
+    pub fn fund_pool_for_token(
+        env: Env,
+        funder: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), RewardError> {
+        funder.require_auth();
+        Self::require_not_paused(&env)?;
+        if amount <= 0 {
+            return Err(RewardError::InvalidAmount);
+        }
+        tokens::fund(&env, &funder, &token, amount)
+    }
+
+    pub fn get_token_pool_balance(env: Env, token: Address) -> i128 {
+        tokens::get_token_pool(&env, &token)
+    }
+
+    pub fn is_reward_executed(env: Env, operation_hash: BytesN<32>) -> bool {
+        common::is_operation_executed(&env, Symbol::new(&env, "spin_reward"), operation_hash)
+    }
+
+    pub fn cleanup_reward_operation(env: Env, operation_hash: BytesN<32>) -> bool {
+        common::cleanup_operation(&env, Symbol::new(&env, "spin_reward"), operation_hash)
+    }
+
+    /// Mint `template` to `user`, with optional on-chain `attributes`. This
+    /// contract must be a registered minter on `template.nft_contract`.
+    /// Each `operation_hash` mints once; returns the new card's id, which is
+    /// also kept for `get_nft_reward`.
     pub fn reward_nft(
         env: Env,
-        nft_contract: Address,
+        operator: Address,
         user: Address,
-    ) {
-        nft::mint_nft(&env, nft_contract, user);
+        template: NftTemplate,
+        attributes: Option<CardAttributes>,
+        operation_hash: BytesN<32>,
+    ) -> Result<u64, RewardError> {
+        Self::require_role(&env, Role::Operator, &operator)?;
+        Self::require_not_paused(&env)?;
+        common::ensure_not_replayed(
+            &env,
+            Symbol::new(&env, "spin_nft"),
+            operation_hash.clone(),
+            None,
+        )
+        .map_err(|_| RewardError::DuplicateOperation)?;
+
+        let token_id = nft::mint_nft(&env, &template, &user, attributes, &operation_hash)?;
+        let key = DataKey::NftReward(operation_hash);
+        env.storage().persistent().set(
+            &key,
+            &NftReward {
+                user,
+                nft_contract: template.nft_contract,
+                token_id,
+                rewarded_at: env.ledger().timestamp(),
+            },
+        );
+        common::bump_persistent(&env, &key);
+        Ok(token_id)
+    }
+
+    pub fn get_nft_reward(env: Env, operation_hash: BytesN<32>) -> Option<NftReward> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::NftReward(operation_hash))
     }
+
+    /// Cap what each user can be paid per day or week, on top of the
+    /// lifetime `max_per_user`. `None` lifts the period cap.
+    pub fn set_period_cap(
+        env: Env,
+        admin: Address,
+        cap: Option<PeriodCap>,
+    ) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        match cap {
+            Some(cap) if cap.max_amount <= 0 => return Err(RewardError::InvalidAmount),
+            Some(cap) => env.storage().instance().set(&DataKey::PeriodCap, &cap),
+            None => env.storage().instance().remove(&DataKey::PeriodCap),
+        }
+        Ok(())
+    }
+
+    pub fn get_period_cap(env: Env) -> Option<PeriodCap> {
+        env.storage().instance().get(&DataKey::PeriodCap)
+    }
+
+    /// How much more `user` can be paid right now under the lifetime and
+    /// period caps, e.g. for the game to show a player's headroom.
+    pub fn remaining_allowance(env: Env, user: Address) -> i128 {
+        reward::remaining_allowance(&env, &user)
+    }
+
+    /// Record a reward for `user` to claim later instead of paying it now.
+    /// The amount leaves the pool and counts against the caps at once. Each
+    /// `operation_hash` is granted once.
+    pub fn grant_reward(
+        env: Env,
+        operator: Address,
+        user: Address,
+        amount: i128,
+        operation_hash: BytesN<32>,
+    ) -> Result<PendingReward, RewardError> {
+        Self::require_role(&env, Role::Operator, &operator)?;
+        Self::require_not_paused(&env)?;
+        common::ensure_not_replayed(&env, Symbol::new(&env, "spin_grant"), operation_hash, None)
+            .map_err(|_| RewardError::DuplicateOperation)?;
+        claim::grant(&env, &user, amount)
+    }
+
+    /// Pay out all of `user`'s granted rewards. Returns the amount paid.
+    pub fn claim_rewards(env: Env, user: Address) -> Result<i128, RewardError> {
+        user.require_auth();
+        Self::require_not_paused(&env)?;
+        let config: RewardConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config)
+            .ok_or(RewardError::NotInitialized)?;
+        claim::claim(&env, &user, &config.reward_token)
+    }
+
+    /// Return `user`'s unclaimed rewards to the pool once their claim period
+    /// is over. Anyone may call this; returns the amount returned.
+    pub fn expire_rewards(env: Env, user: Address) -> i128 {
+        claim::expire(&env, &user)
+    }
+
+    pub fn get_pending_rewards(env: Env, user: Address) -> Option<PendingReward> {
+        claim::get_pending(&env, &user)
+    }
+
+    /// Total granted to all users and not yet claimed or expired
+    pub fn get_pending_total(env: Env) -> i128 {
+        reward::get_pending_total(&env)
+    }
+
+    /// How long granted rewards can be claimed, in seconds
+    pub fn set_claim_period(env: Env, admin: Address, seconds: u64) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        if seconds == 0 {
+            return Err(RewardError::InvalidAmount);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::ClaimPeriod, &seconds);
+        Ok(())
+    }
+
+    pub fn get_claim_period(env: Env) -> u64 {
+        claim::get_claim_period(&env)
+    }
+
+    /// Turn streak bonuses on `execute_reward` token tiers on, retune them,
+    /// or switch them off with `None`. Streaks are counted either way.
+    pub fn set_streak_config(
+        env: Env,
+        admin: Address,
+        config: Option<StreakConfig>,
+    ) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        match config {
+            Some(config) => {
+                config.validate()?;
+                env.storage()
+                    .instance()
+                    .set(&DataKey::StreakConfig, &config);
+            }
+            None => env.storage().instance().remove(&DataKey::StreakConfig),
+        }
+        Ok(())
+    }
+
+    pub fn get_streak_config(env: Env) -> Option<StreakConfig> {
+        streak::get_config(&env)
+    }
+
+    /// `user`'s daily spin streak as of now, with missed days taken off
+    pub fn get_streak(env: Env, user: Address) -> SpinStreak {
+        streak::current(&env, &user)
+    }
+
+    /// Replace the tiers `execute_reward` draws from. Outcomes already
+    /// recorded keep the reward they were paid.
+    pub fn set_reward_table(
+        env: Env,
+        admin: Address,
+        tiers: Vec<RewardTier>,
+    ) -> Result<(), RewardError> {
+        Self::require_role(&env, Role::Admin, &admin)?;
+        table::validate_table(&tiers)?;
+        env.storage().instance().set(&DataKey::RewardTable, &tiers);
+        Ok(())
+    }
+
+    pub fn get_reward_table(env: Env) -> Vec<RewardTier> {
+        env.storage()
+            .instance()
+            .get(&DataKey::RewardTable)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Pay `user` whichever reward table tier `random_seed` lands on (see
+    /// `pick_tier`). Token tiers are raised by the user's streak multiplier
+    /// and are subject to the same caps as `reward_xlm`. Each `operation_hash` is executed once, and its
+    /// outcome is kept for `get_spin_outcome`.
+    pub fn execute_reward(
+        env: Env,
+        operator: Address,
+        user: Address,
+        random_seed: BytesN<32>,
+        operation_hash: BytesN<32>,
+    ) -> Result<SpinOutcome, RewardError> {
+        Self::require_role(&env, Role::Operator, &operator)?;
+        Self::require_not_paused(&env)?;
+        let tiers: Vec<RewardTier> = env
+            .storage()
+            .instance()
+            .get(&DataKey::RewardTable)
+            .ok_or(RewardError::InvalidRewardTable)?;
+        common::ensure_not_replayed(
+            &env,
+            Symbol::new(&env, "spin_execute"),
+            operation_hash.clone(),
+            None,
+        )
+        .map_err(|_| RewardError::DuplicateOperation)?;
+
+        let tier_index = table::pick_tier(&tiers, &random_seed);
+        let reward = tiers.get_unchecked(tier_index).reward;
+        let multiplier_bps = streak::record_spin(&env, &user);
+        let card_id = match &reward {
+            TierReward::Nothing => None,
+            TierReward::Token(amount) => {
+                let amount = streak::apply(*amount, multiplier_bps)?;
+                reward::distribute_xlm(&env, user.clone(), amount)?;
+                None
+            }
+            TierReward::Nft(template) => {
+                Some(nft::mint_nft(&env, template, &user, None, &operation_hash)?)
+            }
+        };
+
+        let outcome = SpinOutcome {
+            user: user.clone(),
+            tier_index,
+            reward,
+            card_id,
+            multiplier_bps,
+            executed_at: env.ledger().timestamp(),
+        };
+        let key = DataKey::SpinOutcome(operation_hash.clone());
+        env.storage().persistent().set(&key, &outcome);
+        common::bump_persistent(&env, &key);
+        events::spin_outcome(&env, operation_hash, user, tier_index);
+        Ok(outcome)
+    }
+
+    pub fn get_spin_outcome(env: Env, operation_hash: BytesN<32>) -> Option<SpinOutcome> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SpinOutcome(operation_hash))
+    }
+
+    fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), RewardError> {
+        common::require_role(env, role, account).map_err(role_error)
+    }
+
+    fn require_not_paused(env: &Env) -> Result<(), RewardError> {
+        if pausable::is_paused(env) {
+            return Err(RewardError::Paused);
+        }
+        Ok(())
+    }
+}
+
+fn role_error(_: common::ContractError) -> RewardError {
+    RewardError::NotAuthorized
 }
+
+#[cfg(test)]
+mod test;
@@ -1,13 +1,44 @@
-use soroban_sdk::{Env, Address, Symbol};
+use crate::errors::RewardError;
+use crate::events;
+use crate::table::NftTemplate;
+use common::{mint_card_with_attributes, CardAttributes, ContractError};
+use soroban_sdk::{xdr::ToXdr, Address, BytesN, Env};
 
+/// Mint `template` to `to` for the reward identified by `operation_hash`
+/// and return the new card's id. The card contract sees a hash derived
+/// from this contract and `operation_hash`, so hashes from different
+/// minters never collide there.
 pub fn mint_nft(
     env: &Env,
-    nft_contract: Address,
-    to: Address,
-) {
-    env.invoke_contract(
-        &nft_contract,
-        &Symbol::new(env, "mint"),
-        (to,)
+    template: &NftTemplate,
+    to: &Address,
+    attributes: Option<CardAttributes>,
+    operation_hash: &BytesN<32>,
+) -> Result<u64, RewardError> {
+    let mint_hash: BytesN<32> = env
+        .crypto()
+        .sha256(&(env.current_contract_address(), operation_hash.clone()).to_xdr(env))
+        .into();
+    let token_id = mint_card_with_attributes(
+        env,
+        &template.nft_contract,
+        &mint_hash,
+        to,
+        &template.token_uri,
+        attributes,
+        template.transferable,
+    )
+    .map_err(|err| match err {
+        ContractError::DuplicateOperation => RewardError::DuplicateOperation,
+        _ => RewardError::MintFailed,
+    })?;
+
+    events::nft_rewarded(
+        env,
+        operation_hash.clone(),
+        to.clone(),
+        template.nft_contract.clone(),
+        token_id,
     );
+    Ok(token_id)
 }
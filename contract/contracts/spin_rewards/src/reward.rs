@@ -1,25 +1,56 @@
-use soroban_sdk::{Env, Address};
-use crate::storage::{DataKey, RewardConfig};
 use crate::errors::RewardError;
 use crate::events;
+use crate::storage::{DataKey, PeriodCap, RewardConfig};
+use common::{contribute_to_jackpot, quote_jackpot_contribution};
+use soroban_sdk::{token, Address, Env};
 
-pub fn distribute_xlm(
+pub fn distribute_xlm(env: &Env, user: Address, amount: i128) -> Result<(), RewardError> {
+    let config = reserve_reward(env, &user, amount)?;
+
+    // Pay out of the contract's own balance: native XLM via its SAC, or any
+    // custom reward token
+    token::Client::new(env, &config.reward_token).transfer(
+        &env.current_contract_address(),
+        &user,
+        &amount,
+    );
+    events::reward_distributed(env, user, amount);
+    Ok(())
+}
+
+/// Take a reward of `amount` for `user` out of the pool, checking and
+/// counting it against every cap and paying any jackpot share. The reward
+/// itself stays in the contract for the caller to pay out or hold.
+pub fn reserve_reward(
     env: &Env,
-    user: Address,
+    user: &Address,
     amount: i128,
-) -> Result<(), RewardError> {
-
+) -> Result<RewardConfig, RewardError> {
     let config: RewardConfig = env.storage().instance().get(&DataKey::Config).unwrap();
-    let pool: i128 = env.storage().instance().get(&DataKey::PoolBalance).unwrap_or(0);
-    let total_distributed: i128 =
-        env.storage().instance().get(&DataKey::TotalDistributed).unwrap_or(0);
+    let pool: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::PoolBalance)
+        .unwrap_or(0);
+    let total_distributed: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::TotalDistributed)
+        .unwrap_or(0);
+
+    if amount <= 0 {
+        return Err(RewardError::InvalidAmount);
+    }
 
     if amount > config.max_per_spin {
         return Err(RewardError::ExceedsPerSpinCap);
     }
 
-    let user_total: i128 =
-        env.storage().instance().get(&DataKey::UserRewards(user.clone())).unwrap_or(0);
+    let user_total: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::UserRewards(user.clone()))
+        .unwrap_or(0);
 
     if user_total + amount > config.max_per_user {
         return Err(RewardError::ExceedsUserCap);
@@ -29,22 +60,102 @@ pub fn distribute_xlm(
         return Err(RewardError::ExceedsTotalCap);
     }
 
-    if pool < amount {
+    let period = current_period(env, user);
+    let period_total = match &period {
+        Some((cap, key)) => {
+            let paid: i128 = env.storage().persistent().get(key).unwrap_or(0);
+            if paid + amount > cap.max_amount {
+                return Err(RewardError::ExceedsPeriodCap);
+            }
+            paid
+        }
+        None => 0,
+    };
+
+    // A configured jackpot takes its share of each spin from the pool on top
+    // of the reward itself
+    let jackpot: Option<Address> = env.storage().instance().get(&DataKey::Jackpot);
+    let jackpot_share = match &jackpot {
+        Some(jackpot) => quote_jackpot_contribution(env, jackpot, &config.reward_token, amount)
+            .map_err(|_| RewardError::NotAuthorized)?,
+        None => 0,
+    };
+    let required = amount + jackpot_share;
+
+    // The tracked pool can drift from what the contract really holds (e.g.
+    // tokens moved out by another path), so check both before paying.
+    // Rewards waiting to be claimed are held outside the pool.
+    let held =
+        token::Client::new(env, &config.reward_token).balance(&env.current_contract_address());
+    if pool < required || held - get_pending_total(env) < required {
         return Err(RewardError::InsufficientPool);
     }
 
-    // Transfer native XLM
-    env.invoke_contract(
-        &env.current_contract_address(),
-        &symbol_short!("transfer"),
-        (user.clone(), amount)
-    );
+    if let (Some(jackpot), true) = (&jackpot, jackpot_share > 0) {
+        contribute_to_jackpot(env, jackpot, &config.reward_token, amount, jackpot_share)
+            .map_err(|_| RewardError::NotAuthorized)?;
+    }
 
-    env.storage().instance().set(&DataKey::PoolBalance, &(pool - amount));
-    env.storage().instance().set(&DataKey::UserRewards(user.clone()), &(user_total + amount));
-    env.storage().instance().set(&DataKey::TotalDistributed, &(total_distributed + amount));
+    env.storage()
+        .instance()
+        .set(&DataKey::PoolBalance, &(pool - required));
+    env.storage()
+        .instance()
+        .set(&DataKey::UserRewards(user.clone()), &(user_total + amount));
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalDistributed, &(total_distributed + amount));
 
-    events::reward_distributed(env, user, amount);
+    if let Some((_, key)) = &period {
+        env.storage()
+            .persistent()
+            .set(key, &(period_total + amount));
+        common::bump_persistent(env, key);
+    }
+    Ok(config)
+}
 
-    Ok(())
+// The configured period cap and the key of `user`'s accumulator for the
+// period the ledger is in now.
+fn current_period(env: &Env, user: &Address) -> Option<(PeriodCap, DataKey)> {
+    let cap: PeriodCap = env.storage().instance().get(&DataKey::PeriodCap)?;
+    let index = env.ledger().timestamp() / cap.period.seconds();
+    Some((cap, DataKey::PeriodRewards(user.clone(), index)))
+}
+
+/// What `user` can still be paid now: the smaller of their lifetime and
+/// current-period headroom.
+pub fn remaining_allowance(env: &Env, user: &Address) -> i128 {
+    let Some(config) = env
+        .storage()
+        .instance()
+        .get::<_, RewardConfig>(&DataKey::Config)
+    else {
+        return 0;
+    };
+    let user_total: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::UserRewards(user.clone()))
+        .unwrap_or(0);
+    let mut remaining = config.max_per_user - user_total;
+
+    if let Some((cap, key)) = current_period(env, user) {
+        let paid: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        remaining = remaining.min(cap.max_amount - paid);
+    }
+    remaining.max(0)
+}
+
+pub fn get_pending_total(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::PendingTotal)
+        .unwrap_or(0)
+}
+
+pub fn set_pending_total(env: &Env, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKey::PendingTotal, &amount);
 }
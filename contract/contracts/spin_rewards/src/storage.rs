@@ -1,13 +1,91 @@
-use soroban_sdk::{contracttype, Address};
+use crate::errors::RewardError;
+use soroban_sdk::{contracttype, Address, BytesN};
 
 #[contracttype]
 #[derive(Clone)]
 pub struct RewardConfig {
+    pub reward_token: Address,
     pub max_per_spin: i128,
     pub max_per_user: i128,
     pub total_cap: i128,
 }
 
+impl RewardConfig {
+    pub fn validate(&self) -> Result<(), RewardError> {
+        if self.max_per_spin <= 0 || self.max_per_user <= 0 || self.total_cap <= 0 {
+            return Err(RewardError::InvalidConfig);
+        }
+        Ok(())
+    }
+}
+
+/// Caps of an extra reward token, in that token's units. Each extra token
+/// has its own pool; period caps and the jackpot apply to the primary
+/// `RewardConfig.reward_token` only.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenRewardConfig {
+    pub max_per_spin: i128,
+    pub max_per_user: i128,
+    pub total_cap: i128,
+}
+
+impl TokenRewardConfig {
+    pub fn validate(&self) -> Result<(), RewardError> {
+        if self.max_per_spin <= 0 || self.max_per_user <= 0 || self.total_cap <= 0 {
+            return Err(RewardError::InvalidConfig);
+        }
+        Ok(())
+    }
+}
+
+/// Snapshot of the reward pool for monitoring.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolStats {
+    /// Available for new rewards
+    pub pool_balance: i128,
+    /// Granted and waiting to be claimed
+    pub pending_total: i128,
+    pub total_distributed: i128,
+    /// Reward token the contract actually holds
+    pub token_balance: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RewardPeriod {
+    Daily,
+    Weekly,
+}
+
+impl RewardPeriod {
+    pub fn seconds(self) -> u64 {
+        match self {
+            RewardPeriod::Daily => 86_400,
+            RewardPeriod::Weekly => 7 * 86_400,
+        }
+    }
+}
+
+/// Most any one user can be paid per calendar period. Periods are counted
+/// from the Unix epoch, so every user's allowance resets at the same time.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PeriodCap {
+    pub period: RewardPeriod,
+    pub max_amount: i128,
+}
+
+/// Rewards granted to a user and not yet claimed. Each grant pushes
+/// `expires_at` out by the claim period.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingReward {
+    pub amount: i128,
+    pub expires_at: u64,
+}
+
 #[contracttype]
 pub enum DataKey {
     Admin,
@@ -15,4 +93,28 @@ pub enum DataKey {
     UserRewards(Address),
     Config,
     TotalDistributed,
+    Jackpot,
+    RewardTable,
+    /// Persistent: `SpinOutcome` of an `execute_reward` operation hash
+    SpinOutcome(BytesN<32>),
+    /// Persistent: `NftReward` of a `reward_nft` operation hash
+    NftReward(BytesN<32>),
+    PeriodCap,
+    /// Persistent: paid to a user in one period, by period index
+    PeriodRewards(Address, u64),
+    ClaimPeriod,
+    /// Sum of every user's `PendingReward`, held outside the pool
+    PendingTotal,
+    /// Persistent: a user's `PendingReward`
+    Pending(Address),
+    /// Extra reward tokens, in the order they were allowed
+    RewardTokens,
+    TokenConfig(Address),
+    TokenPool(Address),
+    TokenDistributed(Address),
+    /// Persistent: paid to a user in an extra token: (token, user)
+    TokenUserRewards(Address, Address),
+    StreakConfig,
+    /// Persistent: a user's `SpinStreak`
+    Streak(Address),
 }
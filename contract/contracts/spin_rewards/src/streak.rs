@@ -0,0 +1,130 @@
+use crate::errors::RewardError;
+use crate::events;
+use crate::storage::DataKey;
+use soroban_sdk::{contracttype, Address, Env};
+
+pub const DAY_SECONDS: u64 = 86_400;
+
+/// A multiplier of `BASIS_POINTS` leaves payouts unchanged.
+pub const BASIS_POINTS: u32 = 10_000;
+
+/// Consecutive daily spins raise the multiplier on token tiers by
+/// `bonus_bps_per_day` per day after the first, up to `max_multiplier_bps`.
+/// Every missed day takes one day off the streak instead of resetting it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreakConfig {
+    pub bonus_bps_per_day: u32,
+    pub max_multiplier_bps: u32,
+}
+
+impl StreakConfig {
+    pub fn validate(&self) -> Result<(), RewardError> {
+        if self.max_multiplier_bps < BASIS_POINTS {
+            return Err(RewardError::InvalidConfig);
+        }
+        Ok(())
+    }
+
+    pub fn multiplier_bps(&self, days: u32) -> u32 {
+        let bonus = days
+            .saturating_sub(1)
+            .saturating_mul(self.bonus_bps_per_day);
+        BASIS_POINTS
+            .saturating_add(bonus)
+            .min(self.max_multiplier_bps)
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SpinStreak {
+    /// Streak length in days
+    pub days: u32,
+    /// Day index (`timestamp / DAY_SECONDS`) of the last spin
+    pub last_day: u64,
+    pub multiplier_bps: u32,
+}
+
+pub fn get_config(env: &Env) -> Option<StreakConfig> {
+    env.storage().instance().get(&DataKey::StreakConfig)
+}
+
+fn stored(env: &Env, user: &Address) -> Option<SpinStreak> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Streak(user.clone()))
+}
+
+// `streak` with the days missed before `day` taken off.
+fn decayed(config: &Option<StreakConfig>, mut streak: SpinStreak, day: u64) -> SpinStreak {
+    let missed = day.saturating_sub(streak.last_day).saturating_sub(1);
+    if missed > 0 {
+        streak.days = streak
+            .days
+            .saturating_sub(missed.min(u32::MAX as u64) as u32);
+        streak.multiplier_bps = multiplier_for(config, streak.days);
+    }
+    streak
+}
+
+fn multiplier_for(config: &Option<StreakConfig>, days: u32) -> u32 {
+    config
+        .as_ref()
+        .map_or(BASIS_POINTS, |config| config.multiplier_bps(days))
+}
+
+/// `user`'s streak as of now, with missed days already taken off.
+pub fn current(env: &Env, user: &Address) -> SpinStreak {
+    let day = env.ledger().timestamp() / DAY_SECONDS;
+    match stored(env, user) {
+        Some(streak) => decayed(&get_config(env), streak, day),
+        None => SpinStreak {
+            multiplier_bps: BASIS_POINTS,
+            ..Default::default()
+        },
+    }
+}
+
+/// Count a spin by `user` today and return the multiplier it earns.
+/// Further spins on the same day leave the streak as it is.
+pub fn record_spin(env: &Env, user: &Address) -> u32 {
+    let config = get_config(env);
+    let day = env.ledger().timestamp() / DAY_SECONDS;
+    let previous = stored(env, user);
+    let previous_multiplier = previous
+        .as_ref()
+        .map_or(BASIS_POINTS, |streak| streak.multiplier_bps);
+
+    let days = match previous {
+        Some(streak) if streak.last_day == day => return streak.multiplier_bps,
+        Some(streak) => decayed(&config, streak, day).days.saturating_add(1),
+        None => 1,
+    };
+    let streak = SpinStreak {
+        days,
+        last_day: day,
+        multiplier_bps: multiplier_for(&config, days),
+    };
+
+    let key = DataKey::Streak(user.clone());
+    env.storage().persistent().set(&key, &streak);
+    common::bump_persistent(env, &key);
+    if streak.multiplier_bps != previous_multiplier {
+        events::multiplier_changed(
+            env,
+            user.clone(),
+            previous_multiplier,
+            streak.multiplier_bps,
+        );
+    }
+    streak.multiplier_bps
+}
+
+/// `amount` raised by `multiplier_bps`, rounded down.
+pub fn apply(amount: i128, multiplier_bps: u32) -> Result<i128, RewardError> {
+    amount
+        .checked_mul(multiplier_bps as i128)
+        .map(|scaled| scaled / BASIS_POINTS as i128)
+        .ok_or(RewardError::InvalidAmount)
+}
@@ -0,0 +1,95 @@
+use crate::errors::RewardError;
+use soroban_sdk::{contracttype, Address, BytesN, String, Vec};
+
+/// Upper bound on the number of tiers in a reward table.
+pub const MAX_REWARD_TIERS: u32 = 20;
+
+/// Card minted for an NFT reward. Tier cards are minted without on-chain
+/// attributes.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NftTemplate {
+    pub nft_contract: Address,
+    pub token_uri: String,
+    pub transferable: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TierReward {
+    /// Spin lost; nothing is paid
+    Nothing,
+    /// Amount of the reward token, paid from the pool under the usual caps
+    Token(i128),
+    Nft(NftTemplate),
+}
+
+/// Hit with probability `weight / total weight of the table`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RewardTier {
+    pub weight: u32,
+    pub reward: TierReward,
+}
+
+/// What one `execute_reward` call landed on.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpinOutcome {
+    pub user: Address,
+    pub tier_index: u32,
+    pub reward: TierReward,
+    /// Card minted for an `Nft` tier
+    pub card_id: Option<u64>,
+    /// Streak multiplier applied to a `Token` tier's amount
+    pub multiplier_bps: u32,
+    pub executed_at: u64,
+}
+
+/// Card minted by one `reward_nft` call.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NftReward {
+    pub user: Address,
+    pub nft_contract: Address,
+    pub token_id: u64,
+    pub rewarded_at: u64,
+}
+
+pub fn validate_table(tiers: &Vec<RewardTier>) -> Result<(), RewardError> {
+    if tiers.is_empty() || tiers.len() > MAX_REWARD_TIERS {
+        return Err(RewardError::InvalidRewardTable);
+    }
+    let mut total_weight: u64 = 0;
+    for tier in tiers.iter() {
+        if let TierReward::Token(amount) = tier.reward {
+            if amount <= 0 {
+                return Err(RewardError::InvalidRewardTable);
+            }
+        }
+        total_weight += tier.weight as u64;
+    }
+    if total_weight == 0 {
+        return Err(RewardError::InvalidRewardTable);
+    }
+    Ok(())
+}
+
+/// Index of the tier `random_seed` lands on. Tiers own consecutive ranges
+/// of `[0, total weight)` in table order, and the seed's leading eight
+/// bytes pick a point in that range, so the same seed always hits the
+/// same tier of the same table.
+pub fn pick_tier(tiers: &Vec<RewardTier>, random_seed: &BytesN<32>) -> u32 {
+    let total_weight: u64 = tiers.iter().map(|tier| tier.weight as u64).sum();
+    let mut leading = [0u8; 8];
+    leading.copy_from_slice(&random_seed.to_array()[..8]);
+    let mut point = u64::from_be_bytes(leading) % total_weight;
+
+    for (index, tier) in tiers.iter().enumerate() {
+        if point < tier.weight as u64 {
+            return index as u32;
+        }
+        point -= tier.weight as u64;
+    }
+    unreachable!()
+}
@@ -0,0 +1,623 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger, MockAuth, MockAuthInvoke},
+    token, vec, Address, BytesN, Env, IntoVal, String, Symbol,
+};
+use storage::{DataKey, RewardPeriod};
+
+fn setup_test(
+    env: &Env,
+) -> (
+    SpinRewardsClient<'_>,
+    Address,
+    Address,
+    Address,
+    token::Client<'_>,
+) {
+    env.mock_all_auths();
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let funder = Address::generate(env);
+    token::StellarAssetClient::new(env, &token_id).mint(&funder, &10_000);
+
+    let admin = Address::generate(env);
+    let backend = Address::generate(env);
+    let contract_id = env.register(SpinRewards, ());
+    let client = SpinRewardsClient::new(env, &contract_id);
+    client.init(
+        &admin,
+        &backend,
+        &RewardConfig {
+            reward_token: token_id.clone(),
+            max_per_spin: 100,
+            max_per_user: 1_000,
+            total_cap: 10_000,
+        },
+    );
+
+    let token = token::Client::new(env, &token_id);
+
+    (client, admin, backend, funder, token)
+}
+
+fn op(env: &Env, seed: u8) -> BytesN<32> {
+    BytesN::from_array(env, &[seed; 32])
+}
+
+#[test]
+fn pause_blocks_funding_and_rewards() {
+    let env = Env::default();
+    let (client, admin, backend, funder, _token) = setup_test(&env);
+
+    client.pause(&admin);
+    assert!(client.is_paused());
+    assert_eq!(
+        client.try_fund_pool(&funder, &500),
+        Err(Ok(RewardError::Paused))
+    );
+    assert_eq!(
+        client.try_reward_xlm(&backend, &Address::generate(&env), &10, &op(&env, 1), &None),
+        Err(Ok(RewardError::Paused))
+    );
+
+    client.unpause(&admin);
+    assert!(!client.is_paused());
+    client.fund_pool(&funder, &500);
+}
+
+#[test]
+fn init_only_once() {
+    let env = Env::default();
+    let (client, _admin, backend, _funder, _token) = setup_test(&env);
+    let config = client.get_config().unwrap();
+
+    assert_eq!(
+        client.try_init(&Address::generate(&env), &backend, &config),
+        Err(Ok(RewardError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn fund_pool_transfers_tokens_from_funder() {
+    let env = Env::default();
+    let (client, _admin, _backend, funder, token) = setup_test(&env);
+
+    client.fund_pool(&funder, &2_500);
+    assert_eq!(client.get_pool_balance(), 2_500);
+    assert_eq!(token.balance(&funder), 7_500);
+    assert_eq!(token.balance(&client.address), 2_500);
+
+    assert_eq!(
+        client.try_fund_pool(&funder, &0),
+        Err(Ok(RewardError::InvalidAmount))
+    );
+}
+
+#[test]
+fn reward_xlm_requires_operator_auth() {
+    let env = Env::default();
+    let (client, _admin, backend, funder, _token) = setup_test(&env);
+    let user = Address::generate(&env);
+    client.fund_pool(&funder, &500);
+
+    // Only the user signs; the backend signer's auth is missing
+    env.mock_auths(&[MockAuth {
+        address: &user,
+        invoke: &MockAuthInvoke {
+            contract: &client.address,
+            fn_name: "reward_xlm",
+            args: (&backend, &user, 10i128, op(&env, 1), None::<u64>).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(client
+        .try_reward_xlm(&backend, &user, &10, &op(&env, 1), &None)
+        .is_err());
+    assert_eq!(client.get_pool_balance(), 500);
+}
+
+#[test]
+fn reward_xlm_pays_from_token_balance() {
+    let env = Env::default();
+    let (client, _admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    client.fund_pool(&funder, &150);
+
+    client.reward_xlm(&backend, &user, &100, &op(&env, 1), &None);
+    assert_eq!(token.balance(&user), 100);
+    assert_eq!(token.balance(&client.address), 50);
+    assert_eq!(client.get_pool_balance(), 50);
+
+    assert_eq!(
+        client.try_reward_xlm(&backend, &user, &60, &op(&env, 2), &None),
+        Err(Ok(RewardError::InsufficientPool))
+    );
+}
+
+#[test]
+fn only_operators_pay_rewards() {
+    let env = Env::default();
+    let (client, admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    let operator = Address::generate(&env);
+    client.fund_pool(&funder, &500);
+
+    assert_eq!(
+        client.try_reward_xlm(&operator, &user, &10, &op(&env, 1), &None),
+        Err(Ok(RewardError::NotAuthorized))
+    );
+
+    // Rotate the backend key through the role set
+    client.grant_role(&admin, &Role::Operator, &operator);
+    client.revoke_role(&admin, &Role::Operator, &backend);
+    client.reward_xlm(&operator, &user, &10, &op(&env, 2), &None);
+    assert_eq!(token.balance(&user), 10);
+    assert_eq!(
+        client.try_reward_xlm(&backend, &user, &10, &op(&env, 3), &None),
+        Err(Ok(RewardError::NotAuthorized))
+    );
+
+    // Operators cannot reconfigure the contract
+    let config = client.get_config().unwrap();
+    assert_eq!(
+        client.try_set_config(&operator, &config),
+        Err(Ok(RewardError::NotAuthorized))
+    );
+}
+
+#[test]
+fn jackpot_share_is_paid_from_the_pool() {
+    let env = Env::default();
+    let (client, admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    client.fund_pool(&funder, &1_000);
+
+    let jackpot_id = env.register(jackpot::JackpotContract, ());
+    let jackpot = jackpot::JackpotContractClient::new(&env, &jackpot_id);
+    jackpot.initialize(
+        &admin,
+        &token.address,
+        &BytesN::from_array(&env, &[1u8; 32]),
+        &1_000,
+    );
+    client.set_jackpot(&admin, &jackpot_id);
+
+    // Not yet an authorized contributor
+    assert_eq!(
+        client.try_reward_xlm(&backend, &user, &100, &op(&env, 1), &None),
+        Err(Ok(RewardError::NotAuthorized))
+    );
+
+    jackpot.set_contributor(&admin, &client.address, &true);
+    client.reward_xlm(&backend, &user, &100, &op(&env, 2), &None);
+    assert_eq!(token.balance(&user), 100);
+    assert_eq!(jackpot.get_pool(), 10);
+    assert_eq!(client.get_pool_balance(), 890);
+}
+
+fn seed(env: &Env, point: u8) -> BytesN<32> {
+    let mut bytes = [0u8; 32];
+    bytes[7] = point;
+    BytesN::from_array(env, &bytes)
+}
+
+#[test]
+fn execute_reward_draws_a_tier_from_the_seed() {
+    let env = Env::default();
+    let (client, admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    client.fund_pool(&funder, &1_000);
+    let op = BytesN::from_array(&env, &[1u8; 32]);
+
+    assert_eq!(
+        client.try_execute_reward(&backend, &user, &seed(&env, 0), &op),
+        Err(Ok(RewardError::InvalidRewardTable))
+    );
+    assert_eq!(
+        client.try_set_reward_table(
+            &admin,
+            &vec![
+                &env,
+                RewardTier {
+                    weight: 0,
+                    reward: TierReward::Token(50),
+                },
+            ],
+        ),
+        Err(Ok(RewardError::InvalidRewardTable))
+    );
+    client.set_reward_table(
+        &admin,
+        &vec![
+            &env,
+            RewardTier {
+                weight: 1,
+                reward: TierReward::Nothing,
+            },
+            RewardTier {
+                weight: 2,
+                reward: TierReward::Token(50),
+            },
+            RewardTier {
+                weight: 1,
+                reward: TierReward::Token(500),
+            },
+        ],
+    );
+
+    // Points 1 and 2 of the 4 fall in the second tier
+    let outcome = client.execute_reward(&backend, &user, &seed(&env, 6), &op);
+    assert_eq!(outcome.tier_index, 1);
+    assert_eq!(outcome.reward, TierReward::Token(50));
+    assert_eq!(token.balance(&user), 50);
+    assert_eq!(client.get_spin_outcome(&op), Some(outcome));
+    assert_eq!(
+        client.try_execute_reward(&backend, &user, &seed(&env, 6), &op),
+        Err(Ok(RewardError::DuplicateOperation))
+    );
+
+    let lost = BytesN::from_array(&env, &[2u8; 32]);
+    assert_eq!(
+        client
+            .execute_reward(&backend, &user, &seed(&env, 4), &lost)
+            .reward,
+        TierReward::Nothing
+    );
+
+    // The top tier is over the per-spin cap, so hitting it fails
+    let capped = BytesN::from_array(&env, &[3u8; 32]);
+    assert_eq!(
+        client.try_execute_reward(&backend, &user, &seed(&env, 3), &capped),
+        Err(Ok(RewardError::ExceedsPerSpinCap))
+    );
+    assert_eq!(client.get_spin_outcome(&capped), None);
+    assert_eq!(token.balance(&user), 50);
+}
+
+#[test]
+fn period_cap_resets_with_the_next_day() {
+    let env = Env::default();
+    let (client, admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    client.fund_pool(&funder, &1_000);
+    assert_eq!(client.remaining_allowance(&user), 1_000);
+
+    client.set_period_cap(
+        &admin,
+        &Some(PeriodCap {
+            period: RewardPeriod::Daily,
+            max_amount: 120,
+        }),
+    );
+    client.reward_xlm(&backend, &user, &100, &op(&env, 1), &None);
+    assert_eq!(client.remaining_allowance(&user), 20);
+    assert_eq!(
+        client.try_reward_xlm(&backend, &user, &30, &op(&env, 2), &None),
+        Err(Ok(RewardError::ExceedsPeriodCap))
+    );
+
+    env.ledger().with_mut(|l| l.timestamp += 86_400);
+    assert_eq!(client.remaining_allowance(&user), 120);
+    client.reward_xlm(&backend, &user, &30, &op(&env, 3), &None);
+    assert_eq!(token.balance(&user), 130);
+
+    // Without a period cap only the lifetime cap is left
+    client.set_period_cap(&admin, &None);
+    assert_eq!(client.remaining_allowance(&user), 870);
+}
+
+#[test]
+fn granted_rewards_are_claimed_or_expire_back_to_the_pool() {
+    let env = Env::default();
+    let (client, admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    let late = Address::generate(&env);
+    client.fund_pool(&funder, &1_000);
+    client.set_claim_period(&admin, &3_600);
+
+    let op = BytesN::from_array(&env, &[1u8; 32]);
+    client.grant_reward(&backend, &user, &60, &op);
+    assert_eq!(
+        client.try_grant_reward(&backend, &user, &60, &op),
+        Err(Ok(RewardError::DuplicateOperation))
+    );
+    let pending = client.grant_reward(&backend, &user, &40, &BytesN::from_array(&env, &[2u8; 32]));
+    assert_eq!(pending.amount, 100);
+    client.grant_reward(&backend, &late, &30, &BytesN::from_array(&env, &[3u8; 32]));
+
+    // Nothing moves until the user pulls it
+    assert_eq!(token.balance(&user), 0);
+    assert_eq!(client.get_pool_balance(), 870);
+    assert_eq!(client.get_pending_total(), 130);
+
+    assert_eq!(client.claim_rewards(&user), 100);
+    assert_eq!(token.balance(&user), 100);
+    assert_eq!(
+        client.try_claim_rewards(&user),
+        Err(Ok(RewardError::NothingToClaim))
+    );
+
+    env.ledger().with_mut(|l| l.timestamp += 3_600);
+    assert_eq!(
+        client.try_claim_rewards(&late),
+        Err(Ok(RewardError::ClaimExpired))
+    );
+    assert_eq!(client.expire_rewards(&late), 30);
+    assert_eq!(client.get_pending_rewards(&late), None);
+    assert_eq!(client.get_pool_balance(), 900);
+    assert_eq!(client.get_pending_total(), 0);
+}
+
+#[test]
+fn admin_updates_config_and_withdraws_from_the_pool() {
+    let env = Env::default();
+    let (client, admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    client.fund_pool(&funder, &1_000);
+    client.reward_xlm(&backend, &user, &100, &op(&env, 1), &None);
+
+    let mut config = client.get_config().unwrap();
+    config.max_per_spin = 0;
+    assert_eq!(
+        client.try_update_config(&admin, &config),
+        Err(Ok(RewardError::InvalidConfig))
+    );
+    // The pool is held in the current token
+    config.max_per_spin = 200;
+    config.reward_token = Address::generate(&env);
+    assert_eq!(
+        client.try_update_config(&admin, &config),
+        Err(Ok(RewardError::InvalidConfig))
+    );
+    config.reward_token = token.address.clone();
+    client.update_config(&admin, &config);
+    client.reward_xlm(&backend, &user, &200, &op(&env, 2), &None);
+
+    assert_eq!(
+        client.try_withdraw_pool(&backend, &treasury, &100),
+        Err(Ok(RewardError::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_withdraw_pool(&admin, &treasury, &701),
+        Err(Ok(RewardError::InsufficientPool))
+    );
+    client.withdraw_pool(&admin, &treasury, &600);
+    assert_eq!(token.balance(&treasury), 600);
+    assert_eq!(
+        client.get_pool_stats(),
+        PoolStats {
+            pool_balance: 100,
+            pending_total: 0,
+            total_distributed: 300,
+            token_balance: 100,
+        }
+    );
+}
+
+#[test]
+fn reward_xlm_pays_each_operation_once() {
+    let env = Env::default();
+    let (client, _admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    client.fund_pool(&funder, &500);
+
+    client.reward_xlm(&backend, &user, &50, &op(&env, 1), &None);
+    assert!(client.is_reward_executed(&op(&env, 1)));
+    // A retried transaction is rejected instead of paying twice
+    assert_eq!(
+        client.try_reward_xlm(&backend, &user, &50, &op(&env, 1), &None),
+        Err(Ok(RewardError::DuplicateOperation))
+    );
+    assert_eq!(token.balance(&user), 50);
+
+    client.reward_xlm(&backend, &user, &50, &op(&env, 2), &Some(60));
+    assert!(!client.cleanup_reward_operation(&op(&env, 2)));
+    env.ledger().with_mut(|l| l.timestamp += 61);
+    assert!(!client.is_reward_executed(&op(&env, 2)));
+    assert!(client.cleanup_reward_operation(&op(&env, 2)));
+    assert!(client.is_reward_executed(&op(&env, 1)));
+}
+
+#[test]
+fn extra_reward_tokens_pay_from_their_own_pools() {
+    let env = Env::default();
+    let (client, admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    let usdc = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &usdc).mint(&funder, &500);
+    let caps = TokenRewardConfig {
+        max_per_spin: 40,
+        max_per_user: 60,
+        total_cap: 1_000,
+    };
+
+    assert_eq!(
+        client.try_fund_pool_for_token(&funder, &usdc, &100),
+        Err(Ok(RewardError::TokenNotAllowed))
+    );
+    assert_eq!(
+        client.try_set_token_config(&admin, &token.address, &Some(caps.clone())),
+        Err(Ok(RewardError::InvalidConfig))
+    );
+    client.set_token_config(&admin, &usdc, &Some(caps));
+    assert_eq!(client.get_reward_tokens(), vec![&env, usdc.clone()]);
+    client.fund_pool_for_token(&funder, &usdc, &100);
+    client.fund_pool(&funder, &100);
+
+    client.reward_token(&backend, &user, &usdc, &40, &op(&env, 1), &None);
+    client.reward_token(&backend, &user, &token.address, &40, &op(&env, 2), &None);
+    assert_eq!(token::Client::new(&env, &usdc).balance(&user), 40);
+    assert_eq!(token.balance(&user), 40);
+    assert_eq!(client.get_token_pool_balance(&usdc), 60);
+    assert_eq!(client.get_pool_balance(), 60);
+
+    // Each token has its own caps, and hashes are shared with reward_xlm
+    assert_eq!(
+        client.try_reward_token(&backend, &user, &usdc, &30, &op(&env, 3), &None),
+        Err(Ok(RewardError::ExceedsUserCap))
+    );
+
+    // Token totals live in persistent storage; totals still in the instance
+    // from before the move keep counting
+    let legacy_user = Address::generate(&env);
+    env.as_contract(&client.address, || {
+        let key = DataKey::TokenUserRewards(usdc.clone(), user.clone());
+        assert_eq!(env.storage().persistent().get::<_, i128>(&key), Some(40));
+        assert!(!env.storage().instance().has(&key));
+        env.storage().instance().set(
+            &DataKey::TokenUserRewards(usdc.clone(), legacy_user.clone()),
+            &50i128,
+        );
+    });
+    assert_eq!(
+        client.try_reward_token(&backend, &legacy_user, &usdc, &20, &op(&env, 5), &None),
+        Err(Ok(RewardError::ExceedsUserCap))
+    );
+    client.reward_token(&backend, &legacy_user, &usdc, &10, &op(&env, 6), &None);
+    env.as_contract(&client.address, || {
+        let key = DataKey::TokenUserRewards(usdc.clone(), legacy_user.clone());
+        assert_eq!(env.storage().persistent().get::<_, i128>(&key), Some(60));
+        assert!(!env.storage().instance().has(&key));
+    });
+    assert_eq!(
+        client.try_reward_xlm(&backend, &user, &10, &op(&env, 1), &None),
+        Err(Ok(RewardError::DuplicateOperation))
+    );
+
+    client.set_token_config(&admin, &usdc, &None);
+    assert_eq!(
+        client.try_reward_token(&backend, &user, &usdc, &10, &op(&env, 4), &None),
+        Err(Ok(RewardError::TokenNotAllowed))
+    );
+}
+
+#[test]
+fn nft_rewards_record_the_minted_card() {
+    let env = Env::default();
+    let (client, admin, backend, _funder, _token) = setup_test(&env);
+    let user = Address::generate(&env);
+    let cards_id = env.register(player_card::PlayerCardContract, ());
+    let cards = player_card::PlayerCardContractClient::new(&env, &cards_id);
+    cards.initialize(&admin);
+    let template = NftTemplate {
+        nft_contract: cards_id.clone(),
+        token_uri: String::from_str(&env, "ipfs://spin-card"),
+        transferable: true,
+    };
+    let attributes = CardAttributes {
+        rarity: common::Rarity::Rare,
+        position: Symbol::new(&env, "FWD"),
+        rating: 80,
+        season: 1,
+        edition: 1,
+    };
+
+    // Not yet allowed to mint on the card contract
+    assert_eq!(
+        client.try_reward_nft(
+            &backend,
+            &user,
+            &template,
+            &Some(attributes.clone()),
+            &op(&env, 1)
+        ),
+        Err(Ok(RewardError::MintFailed))
+    );
+    cards.add_minter(&client.address);
+
+    let card = client.reward_nft(
+        &backend,
+        &user,
+        &template,
+        &Some(attributes.clone()),
+        &op(&env, 1),
+    );
+    assert_eq!(cards.owner_of(&card), user);
+    assert_eq!(cards.get_card_attributes(&card), Some(attributes));
+    assert_eq!(client.get_nft_reward(&op(&env, 1)).unwrap().token_id, card);
+    assert_eq!(
+        client.try_reward_nft(&backend, &user, &template, &None, &op(&env, 1)),
+        Err(Ok(RewardError::DuplicateOperation))
+    );
+
+    // Table tiers mint the same way and record the card on the outcome
+    client.set_reward_table(
+        &admin,
+        &vec![
+            &env,
+            RewardTier {
+                weight: 1,
+                reward: TierReward::Nft(template),
+            },
+        ],
+    );
+    let outcome = client.execute_reward(&backend, &user, &seed(&env, 0), &op(&env, 2));
+    let tier_card = outcome.card_id.unwrap();
+    assert_eq!(cards.owner_of(&tier_card), user);
+    assert_eq!(cards.get_card_attributes(&tier_card), None);
+}
+
+#[test]
+fn daily_streaks_raise_token_tiers_and_decay_on_missed_days() {
+    let env = Env::default();
+    let (client, admin, backend, funder, token) = setup_test(&env);
+    let user = Address::generate(&env);
+    client.fund_pool(&funder, &1_000);
+    client.set_reward_table(
+        &admin,
+        &vec![
+            &env,
+            RewardTier {
+                weight: 1,
+                reward: TierReward::Token(40),
+            },
+        ],
+    );
+    assert_eq!(
+        client.try_set_streak_config(
+            &admin,
+            &Some(StreakConfig {
+                bonus_bps_per_day: 2_500,
+                max_multiplier_bps: 9_000,
+            })
+        ),
+        Err(Ok(RewardError::InvalidConfig))
+    );
+    client.set_streak_config(
+        &admin,
+        &Some(StreakConfig {
+            bonus_bps_per_day: 2_500,
+            max_multiplier_bps: 15_000,
+        }),
+    );
+
+    let spin = |hash: u8| client.execute_reward(&backend, &user, &seed(&env, 0), &op(&env, hash));
+    assert_eq!(spin(1).multiplier_bps, 10_000);
+    // A second spin the same day does not extend the streak
+    assert_eq!(spin(2).multiplier_bps, 10_000);
+    env.ledger().with_mut(|l| l.timestamp += 86_400);
+    assert_eq!(spin(3).multiplier_bps, 12_500);
+    env.ledger().with_mut(|l| l.timestamp += 86_400);
+    assert_eq!(spin(4).multiplier_bps, 15_000);
+    env.ledger().with_mut(|l| l.timestamp += 86_400);
+    // Capped
+    assert_eq!(spin(5).multiplier_bps, 15_000);
+    assert_eq!(token.balance(&user), 40 + 40 + 50 + 60 + 60);
+
+    // Two missed days take two days off the four-day streak
+    env.ledger().with_mut(|l| l.timestamp += 3 * 86_400);
+    assert_eq!(client.get_streak(&user).days, 2);
+    assert_eq!(spin(6).multiplier_bps, 15_000);
+    assert_eq!(client.get_streak(&user).days, 3);
+
+    client.set_streak_config(&admin, &None);
+    env.ledger().with_mut(|l| l.timestamp += 86_400);
+    assert_eq!(spin(7).multiplier_bps, 10_000);
+}
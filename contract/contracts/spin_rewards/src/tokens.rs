@@ -0,0 +1,135 @@
+use crate::errors::RewardError;
+use crate::events;
+use crate::storage::{DataKey, TokenRewardConfig};
+use soroban_sdk::{token, Address, Env, Vec};
+
+/// Upper bound on the number of extra reward tokens.
+pub const MAX_REWARD_TOKENS: u32 = 10;
+
+pub fn get_reward_tokens(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::RewardTokens)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+pub fn get_token_config(env: &Env, token: &Address) -> Option<TokenRewardConfig> {
+    env.storage()
+        .instance()
+        .get(&DataKey::TokenConfig(token.clone()))
+}
+
+/// Allow `token` with `config`, or delist it with `None`. A delisted token
+/// keeps its pool, which can no longer pay rewards.
+pub fn set_token_config(
+    env: &Env,
+    token: &Address,
+    config: Option<TokenRewardConfig>,
+) -> Result<(), RewardError> {
+    let mut tokens = get_reward_tokens(env);
+    let position = tokens.first_index_of(token);
+    let key = DataKey::TokenConfig(token.clone());
+    match config {
+        Some(config) => {
+            config.validate()?;
+            if position.is_none() {
+                if tokens.len() >= MAX_REWARD_TOKENS {
+                    return Err(RewardError::InvalidConfig);
+                }
+                tokens.push_back(token.clone());
+            }
+            env.storage().instance().set(&key, &config);
+        }
+        None => {
+            if let Some(position) = position {
+                tokens.remove(position);
+            }
+            env.storage().instance().remove(&key);
+        }
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::RewardTokens, &tokens);
+    Ok(())
+}
+
+pub fn get_token_pool(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TokenPool(token.clone()))
+        .unwrap_or(0)
+}
+
+fn set_token_pool(env: &Env, token: &Address, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKey::TokenPool(token.clone()), &amount);
+}
+
+pub fn fund(env: &Env, funder: &Address, token: &Address, amount: i128) -> Result<(), RewardError> {
+    if get_token_config(env, token).is_none() {
+        return Err(RewardError::TokenNotAllowed);
+    }
+    token::Client::new(env, token).transfer(funder, env.current_contract_address(), &amount);
+    set_token_pool(env, token, get_token_pool(env, token) + amount);
+    events::token_pool_funded(env, token.clone(), funder.clone(), amount);
+    Ok(())
+}
+
+/// Pay `amount` of an extra reward token from its own pool, under its own
+/// caps.
+pub fn distribute(
+    env: &Env,
+    user: &Address,
+    token: &Address,
+    amount: i128,
+) -> Result<(), RewardError> {
+    let config = get_token_config(env, token).ok_or(RewardError::TokenNotAllowed)?;
+    if amount <= 0 {
+        return Err(RewardError::InvalidAmount);
+    }
+    if amount > config.max_per_spin {
+        return Err(RewardError::ExceedsPerSpinCap);
+    }
+
+    let storage = env.storage().instance();
+    let user_key = DataKey::TokenUserRewards(token.clone(), user.clone());
+    let user_total = get_token_user_rewards(env, &user_key);
+    if user_total + amount > config.max_per_user {
+        return Err(RewardError::ExceedsUserCap);
+    }
+    let distributed_key = DataKey::TokenDistributed(token.clone());
+    let distributed: i128 = storage.get(&distributed_key).unwrap_or(0);
+    if distributed + amount > config.total_cap {
+        return Err(RewardError::ExceedsTotalCap);
+    }
+
+    let pool = get_token_pool(env, token);
+    let token_client = token::Client::new(env, token);
+    if pool < amount || token_client.balance(&env.current_contract_address()) < amount {
+        return Err(RewardError::InsufficientPool);
+    }
+
+    token_client.transfer(&env.current_contract_address(), user, &amount);
+    set_token_pool(env, token, pool - amount);
+    set_token_user_rewards(env, &user_key, user_total + amount);
+    storage.set(&distributed_key, &(distributed + amount));
+    events::token_reward_distributed(env, token.clone(), user.clone(), amount);
+    Ok(())
+}
+
+// Totals written before these moved to persistent storage are still read
+// from the instance, and dropped from it on the next write.
+fn get_token_user_rewards(env: &Env, key: &DataKey) -> i128 {
+    env.storage()
+        .persistent()
+        .get(key)
+        .or_else(|| env.storage().instance().get(key))
+        .unwrap_or(0)
+}
+
+fn set_token_user_rewards(env: &Env, key: &DataKey, total: i128) {
+    env.storage().persistent().set(key, &total);
+    common::bump_persistent(env, key);
+    env.storage().instance().remove(key);
+}
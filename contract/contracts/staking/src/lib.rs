@@ -1,11 +1,38 @@
 #![no_std]
 
 use common::errors::ContractError;
-use common::events::{create_stake_event, create_unstake_event, STAKE_EVENT, UNSTAKE_EVENT};
-use soroban_sdk::{contract, contractimpl, token, Address, Env, U256};
+use common::events::{
+    create_stake_event, create_unstake_event, publish_versioned_event, EmergencyUnstakeEvent,
+    StakeSlashedEvent, EMERGENCY_UNSTAKE_EVENT, EVENT_SCHEMA_VERSION, SLASH_EVENT, STAKE_EVENT,
+    UNSTAKE_EVENT,
+};
+use common::guardian::{
+    approve_recovery, cancel_recovery, execute_recovery, get_guardian_config, get_pending_recovery,
+    guardian_pause, set_guardian_config, GuardianConfig, RecoveryRequest,
+};
+use common::pausable::{is_paused, pause, require_not_paused, unpause};
+use common::player_card::{get_card_attributes, lock_card, unlock_card, Rarity};
+use common::referral::report_referral_volume;
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, token, Address, BytesN, Env, Symbol, Val, Vec, U256,
+};
 
+pub mod rewards;
 pub mod storage;
-use storage::{DataKey, StakeData};
+use rewards::{
+    adjust_total_staked, adjust_total_weight, earned, reward_per_token, settle_stake, stake_weight,
+    update_reward_per_token, BASE_MULTIPLIER_BPS,
+};
+use storage::{DataKey, EmergencyConfig, StakeData, TierConfig};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Early-exit penalties are expressed in basis points of the principal.
+pub const MAX_PENALTY_BPS: u32 = 10_000;
+
+/// Upper bound on the number of stakes a single page returns.
+pub const MAX_PAGE_SIZE: u32 = 50;
 
 // Helper to keep per-user active staking duration up to date whenever their
 // total staked amount changes between zero and non-zero.
@@ -48,6 +75,437 @@ fn update_user_active_duration_on_change(
     }
 }
 
+// Takes `amount` off a stake, paying out its rewards so far, and closes it
+// (releasing any boost card) once nothing is left. Rewards the pool cannot
+// cover stay on the stake, or move to the user's unpaid rewards when it
+// closes. Returns the rewards paid.
+fn shrink_stake(
+    env: &Env,
+    user: &Address,
+    stake_id: &U256,
+    mut stake_data: StakeData,
+    amount: i128,
+) -> Result<i128, ContractError> {
+    let stake_key = DataKey::UserStake(user.clone(), stake_id.clone());
+    let remaining = stake_data.amount - amount;
+
+    // Settle rewards at the old weight before shrinking the stake
+    settle_stake(env, &mut stake_data);
+    let old_weight = stake_weight(&stake_data);
+    stake_data.amount = remaining;
+    adjust_total_staked(env, -amount);
+    adjust_total_weight(env, stake_weight(&stake_data) - old_weight);
+    let rewards = pay_rewards(env, user, stake_data.accrued_rewards);
+    if remaining > 0 {
+        stake_data.accrued_rewards -= rewards;
+        store_stake(env, &stake_key, &stake_data);
+    } else {
+        env.storage().persistent().remove(&stake_key);
+        add_unpaid_rewards(env, user, stake_data.accrued_rewards - rewards);
+        if let (Some(token_id), Some(player_card)) =
+            (stake_data.boost_card, &stake_data.boost_card_contract)
+        {
+            // A card contract that can no longer release the card must not
+            // keep the principal locked, so a failed unlock is only reported
+            if unlock_card(env, player_card, token_id).is_err() {
+                #[allow(deprecated)] // keep (topic, user) format for indexer compatibility
+                env.events().publish(
+                    (Symbol::new(env, "boost_unlock_failed"), user.clone()),
+                    (player_card.clone(), token_id),
+                );
+            }
+        }
+        let mut stake_ids = get_user_stake_ids(env, user);
+        if let Some(index) = stake_ids.first_index_of(stake_id) {
+            stake_ids.remove(index);
+        }
+        set_user_stake_ids(env, user, &stake_ids);
+    }
+
+    // Update total stake and per-user active duration
+    let total_key = DataKey::TotalStake(user.clone());
+    let current_total: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
+    let new_total = current_total - amount;
+
+    update_user_active_duration_on_change(env, user, current_total, new_total);
+
+    if new_total > 0 {
+        env.storage().persistent().set(&total_key, &new_total);
+    } else {
+        env.storage().persistent().remove(&total_key);
+    }
+
+    Ok(rewards)
+}
+
+// Shared by full, partial and emergency unstakes; `amount: None` withdraws
+// everything. An emergency withdrawal skips the cooldown and lock and pays
+// the emergency penalty instead of the tier's. Returns the amount left staked
+// and the penalty withheld.
+fn withdraw_stake(
+    env: &Env,
+    user: &Address,
+    stake_id: U256,
+    amount: Option<i128>,
+    emergency: bool,
+) -> Result<(i128, i128), ContractError> {
+    let staking_token: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::StakingToken)
+        .ok_or(ContractError::NotInitialized)?;
+    let cooldown_period: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::CooldownPeriod)
+        .unwrap();
+    let min_stake: i128 = env.storage().instance().get(&DataKey::MinStake).unwrap();
+
+    let stake_key = DataKey::UserStake(user.clone(), stake_id.clone());
+    let stake_data: StakeData = env
+        .storage()
+        .persistent()
+        .get(&stake_key)
+        .ok_or(ContractError::StakeNotFound)?;
+
+    let current_time = env.ledger().timestamp();
+    if !emergency && current_time < stake_data.timestamp + cooldown_period {
+        return Err(ContractError::CooldownNotMet);
+    }
+
+    let amount = amount.unwrap_or(stake_data.amount);
+    if amount <= 0 || amount > stake_data.amount {
+        return Err(ContractError::InvalidAmount);
+    }
+    let remaining = stake_data.amount - amount;
+    if remaining > 0 && remaining < min_stake {
+        return Err(ContractError::BelowMinStake);
+    }
+
+    // Leaving a tier before its lock ends forfeits part of the principal
+    let (penalty_bps, penalty_collector) = if emergency {
+        let config: EmergencyConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::EmergencyConfig)
+            .ok_or(ContractError::InvalidConfig)?;
+        (config.penalty_bps, Some(config.penalty_collector))
+    } else if current_time < stake_data.lock_until {
+        (
+            stake_data.early_exit_penalty_bps,
+            env.storage().instance().get(&DataKey::Treasury),
+        )
+    } else {
+        (0, None)
+    };
+    let penalty = amount * penalty_bps as i128 / MAX_PENALTY_BPS as i128;
+    if penalty > 0 && penalty_collector.is_none() {
+        return Err(ContractError::InvalidConfig);
+    }
+    // Only principal that stayed staked for its full term counts as
+    // referral volume; emergency and early exits never do
+    let matured = !emergency && current_time >= stake_data.lock_until;
+
+    let rewards = shrink_stake(env, user, &stake_id, stake_data, amount)?;
+    if matured {
+        if let Some(referral) = env
+            .storage()
+            .instance()
+            .get::<_, Address>(&DataKey::Referral)
+        {
+            report_referral_volume(env, &referral, user, amount)?;
+        }
+    }
+
+    // Transfer tokens back to user, minus any penalty
+    let token_client = token::Client::new(env, &staking_token);
+    if let (true, Some(collector)) = (penalty > 0, penalty_collector.clone()) {
+        token_client.transfer(&env.current_contract_address(), &collector, &penalty);
+        if !emergency {
+            #[allow(deprecated)] // keep (topic, user) format for indexer compatibility
+            env.events().publish(
+                (Symbol::new(env, "early_exit"), user.clone()),
+                (stake_id.clone(), penalty, collector),
+            );
+        }
+    }
+    token_client.transfer(&env.current_contract_address(), user, &(amount - penalty));
+
+    if let (true, Some(penalty_collector)) = (emergency, penalty_collector) {
+        let event = EmergencyUnstakeEvent {
+            version: EVENT_SCHEMA_VERSION,
+            user: user.clone(),
+            stake_id: stake_id.clone(),
+            amount,
+            forfeited: penalty,
+            penalty_collector,
+            timestamp: current_time,
+        };
+        publish_versioned_event(
+            env,
+            (EMERGENCY_UNSTAKE_EVENT, user.clone()),
+            EMERGENCY_UNSTAKE_EVENT,
+            user.clone(),
+            event,
+        );
+    }
+
+    // Emit Event
+    let mut event = create_unstake_event(
+        user.clone(),
+        amount,
+        staking_token,
+        env.current_contract_address(),
+        stake_id,
+        rewards,
+    );
+    event.timestamp = current_time;
+    event.remaining_amount = remaining;
+    publish_versioned_event(
+        env,
+        (UNSTAKE_EVENT, user.clone()),
+        UNSTAKE_EVENT,
+        user.clone(),
+        event,
+    );
+
+    Ok((remaining, penalty))
+}
+
+// Shared by plain and tiered stakes; `tier_id: None` stakes at the base rate
+// with no lock beyond the cooldown.
+fn open_stake(
+    env: &Env,
+    user: &Address,
+    amount: i128,
+    tier_id: Option<u32>,
+) -> Result<U256, ContractError> {
+    require_not_paused(env)?;
+    if let Some(registry) = env
+        .storage()
+        .instance()
+        .get::<_, Address>(&DataKey::SelfExclusionRegistry)
+    {
+        common::require_not_self_excluded(env, &registry, user)?;
+    }
+
+    let staking_token: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::StakingToken)
+        .ok_or(ContractError::NotInitialized)?;
+    let min_stake: i128 = env.storage().instance().get(&DataKey::MinStake).unwrap();
+
+    if amount < min_stake {
+        return Err(ContractError::BelowMinStake);
+    }
+
+    // Transfer tokens to contract
+    let token_client = token::Client::new(env, &staking_token);
+    token_client.transfer(user, env.current_contract_address(), &amount);
+
+    // Generate stake ID based on user nonce
+    let nonce_key = DataKey::StakeNonce(user.clone());
+    let nonce: u32 = env.storage().persistent().get(&nonce_key).unwrap_or(0);
+    env.storage().persistent().set(&nonce_key, &(nonce + 1));
+    let stake_id = U256::from_u32(env, nonce);
+
+    let timestamp = env.ledger().timestamp();
+    let (lock_until, reward_multiplier_bps, early_exit_penalty_bps) = match tier_id {
+        Some(tier_id) => {
+            let tier = get_tier(env, tier_id)?;
+            (
+                timestamp + tier.lock_duration,
+                tier.reward_multiplier_bps,
+                tier.early_exit_penalty_bps,
+            )
+        }
+        None => (timestamp, BASE_MULTIPLIER_BPS, 0),
+    };
+
+    // Record the stake; it starts earning from the current accumulator
+    let reward_per_token_paid = update_reward_per_token(env);
+    let stake_data = StakeData {
+        amount,
+        timestamp,
+        reward_per_token_paid,
+        accrued_rewards: 0,
+        tier_id,
+        lock_until,
+        reward_multiplier_bps,
+        early_exit_penalty_bps,
+        boost_card: None,
+        boost_card_contract: None,
+        boost_bps: BASE_MULTIPLIER_BPS,
+    };
+    adjust_total_staked(env, amount);
+    adjust_total_weight(env, stake_weight(&stake_data));
+    store_stake(
+        env,
+        &DataKey::UserStake(user.clone(), stake_id.clone()),
+        &stake_data,
+    );
+    let mut stake_ids = get_user_stake_ids(env, user);
+    stake_ids.push_back(stake_id.clone());
+    set_user_stake_ids(env, user, &stake_ids);
+
+    // Update total stake and per-user active duration
+    let total_key = DataKey::TotalStake(user.clone());
+    let current_total: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
+    let new_total = current_total + amount;
+
+    update_user_active_duration_on_change(env, user, current_total, new_total);
+
+    env.storage().persistent().set(&total_key, &new_total);
+
+    // Emit Event
+    let mut event = create_stake_event(
+        user.clone(),
+        amount,
+        staking_token,
+        env.current_contract_address(),
+        stake_id.clone(),
+    );
+    event.timestamp = timestamp;
+    publish_versioned_event(
+        env,
+        (STAKE_EVENT, user.clone()),
+        STAKE_EVENT,
+        user.clone(),
+        event,
+    );
+
+    Ok(stake_id)
+}
+
+fn store_stake(env: &Env, stake_key: &DataKey, stake_data: &StakeData) {
+    env.storage().persistent().set(stake_key, stake_data);
+    common::bump_persistent(env, stake_key);
+    common::bump_instance(env);
+}
+
+fn get_user_stake_ids(env: &Env, user: &Address) -> Vec<U256> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::UserStakeIds(user.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_user_stake_ids(env: &Env, user: &Address, stake_ids: &Vec<U256>) {
+    let key = DataKey::UserStakeIds(user.clone());
+    if stake_ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, stake_ids);
+        common::bump_persistent(env, &key);
+    }
+}
+
+// Slice of the user's open stake ids; `limit` is capped at `MAX_PAGE_SIZE`.
+fn page_user_stake_ids(env: &Env, user: &Address, offset: u32, limit: u32) -> Vec<U256> {
+    let stake_ids = get_user_stake_ids(env, user);
+    let end = offset
+        .saturating_add(limit.min(MAX_PAGE_SIZE))
+        .min(stake_ids.len());
+    let mut page = Vec::new(env);
+    for index in offset..end {
+        page.push_back(stake_ids.get_unchecked(index));
+    }
+    page
+}
+
+fn get_player_card(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::PlayerCard)
+        .ok_or(ContractError::InvalidConfig)
+}
+
+fn get_rarity_boost(env: &Env, rarity: Rarity) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::RarityBoost(rarity))
+        .unwrap_or(BASE_MULTIPLIER_BPS)
+}
+
+fn get_tier(env: &Env, tier_id: u32) -> Result<TierConfig, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Tier(tier_id))
+        .ok_or(ContractError::TierNotFound)
+}
+
+fn get_tier_ids(env: &Env) -> Vec<u32> {
+    env.storage()
+        .instance()
+        .get(&DataKey::TierIds)
+        .unwrap_or(Vec::new(env))
+}
+
+const REWARDS_CLAIMED_EVENT: Symbol = symbol_short!("RWD_CLAIM");
+
+// Pays up to `amount` of the reward token to `user` out of the funded pool.
+// Returns what was paid, which is zero while no reward token is configured
+// and never more than the pool holds.
+fn pay_rewards(env: &Env, user: &Address, amount: i128) -> i128 {
+    if amount <= 0 {
+        return 0;
+    }
+    let reward_token: Option<Address> = env.storage().instance().get(&DataKey::RewardToken);
+    match reward_token {
+        Some(reward_token) => {
+            let pool = get_reward_pool(env);
+            let paid = amount.min(pool);
+            if paid > 0 {
+                let token_client = token::Client::new(env, &reward_token);
+                token_client.transfer(&env.current_contract_address(), user, &paid);
+                set_reward_pool(env, pool - paid);
+            }
+            paid
+        }
+        None => 0,
+    }
+}
+
+fn get_unpaid_rewards(env: &Env, user: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::UnpaidRewards(user.clone()))
+        .unwrap_or(0)
+}
+
+fn add_unpaid_rewards(env: &Env, user: &Address, amount: i128) {
+    if amount > 0 {
+        let key = DataKey::UnpaidRewards(user.clone());
+        env.storage()
+            .persistent()
+            .set(&key, &(get_unpaid_rewards(env, user) + amount));
+        common::bump_persistent(env, &key);
+    }
+}
+
+fn get_reward_pool(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::RewardPool)
+        .unwrap_or(0)
+}
+
+// Emits `reward_pool_low` when a payout takes the pool below the threshold.
+fn set_reward_pool(env: &Env, pool: i128) {
+    let previous = get_reward_pool(env);
+    env.storage().instance().set(&DataKey::RewardPool, &pool);
+
+    let threshold: Option<i128> = env.storage().instance().get(&DataKey::RewardPoolThreshold);
+    if let Some(threshold) = threshold {
+        if pool < threshold && previous >= threshold {
+            #[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+            env.events()
+                .publish((Symbol::new(env, "reward_pool_low"),), (pool, threshold));
+        }
+    }
+}
+
 #[contract]
 pub struct StakingContract;
 
@@ -76,21 +534,56 @@ impl StakingContract {
         Ok(())
     }
 
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(
+        env: Env,
+        admin: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        Self::require_admin(&env, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Stop duplicating events under their pre-versioning topics from
+    /// `until` on; `None` keeps the duplicates indefinitely.
+    pub fn set_legacy_events_until(
+        env: Env,
+        admin: Address,
+        until: Option<u64>,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        common::set_legacy_events_until(&env, until);
+        Ok(())
+    }
+
+    pub fn get_legacy_events_until(env: Env) -> Option<u64> {
+        common::get_legacy_events_until(&env)
+    }
+
     pub fn update_config(
         env: Env,
         admin: Address,
         min_stake: Option<i128>,
         cooldown_period: Option<u64>,
     ) -> Result<(), ContractError> {
-        admin.require_auth();
-        let stored_admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(ContractError::NotInitialized)?;
-        if admin != stored_admin {
-            return Err(ContractError::Unauthorized);
-        }
+        Self::require_admin(&env, &admin)?;
 
         if let Some(min) = min_stake {
             env.storage().instance().set(&DataKey::MinStake, &min);
@@ -107,120 +600,498 @@ impl StakingContract {
     pub fn stake(env: Env, user: Address, amount: i128) -> Result<U256, ContractError> {
         user.require_auth();
 
-        let staking_token: Address = env
-            .storage()
+        open_stake(&env, &user, amount, None)
+    }
+
+    /// Stake under a lock plan. The stake earns the tier's reward multiplier,
+    /// and withdrawing before the lock ends sends the tier's penalty share
+    /// of the withdrawn principal to the treasury.
+    pub fn stake_with_tier(
+        env: Env,
+        user: Address,
+        amount: i128,
+        tier_id: u32,
+    ) -> Result<U256, ContractError> {
+        user.require_auth();
+
+        open_stake(&env, &user, amount, Some(tier_id))
+    }
+
+    pub fn unstake(env: Env, user: Address, stake_id: U256) -> Result<(), ContractError> {
+        user.require_auth();
+        require_not_paused(&env)?;
+
+        withdraw_stake(&env, &user, stake_id, None, false)?;
+        Ok(())
+    }
+
+    /// Withdraw a whole stake immediately, skipping its cooldown and lock,
+    /// and forfeit the configured emergency penalty. Works while paused so
+    /// funds are never trapped during an incident. Returns the amount paid
+    /// back to the user.
+    pub fn emergency_unstake(
+        env: Env,
+        user: Address,
+        stake_id: U256,
+    ) -> Result<i128, ContractError> {
+        user.require_auth();
+
+        let amount = Self::get_stake(env.clone(), user.clone(), stake_id.clone())?.amount;
+        let (_, forfeited) = withdraw_stake(&env, &user, stake_id, None, true)?;
+        Ok(amount - forfeited)
+    }
+
+    /// Penalty applied by `emergency_unstake` and the address it is sent to
+    pub fn set_emergency_config(
+        env: Env,
+        admin: Address,
+        penalty_bps: u32,
+        penalty_collector: Address,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        if penalty_bps > MAX_PENALTY_BPS {
+            return Err(ContractError::InvalidConfig);
+        }
+
+        env.storage().instance().set(
+            &DataKey::EmergencyConfig,
+            &EmergencyConfig {
+                penalty_bps,
+                penalty_collector,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn get_emergency_config(env: Env) -> Option<EmergencyConfig> {
+        env.storage().instance().get(&DataKey::EmergencyConfig)
+    }
+
+    /// Withdraw part of a stake after its cooldown. The rest keeps its
+    /// original timestamp and keeps earning; returns the remaining amount.
+    pub fn unstake_partial(
+        env: Env,
+        user: Address,
+        stake_id: U256,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        user.require_auth();
+        require_not_paused(&env)?;
+
+        withdraw_stake(&env, &user, stake_id, Some(amount), false).map(|(remaining, _)| remaining)
+    }
+
+    /// Configure the reward token and the per-second emission shared by all
+    /// stakers. Rewards earned under the previous rate are kept.
+    pub fn set_reward_config(
+        env: Env,
+        admin: Address,
+        reward_token: Address,
+        reward_rate: i128,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        if reward_rate < 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        update_reward_per_token(&env);
+        env.storage()
             .instance()
-            .get(&DataKey::StakingToken)
-            .ok_or(ContractError::NotInitialized)?;
-        let min_stake: i128 = env.storage().instance().get(&DataKey::MinStake).unwrap();
+            .set(&DataKey::RewardToken, &reward_token);
+        env.storage()
+            .instance()
+            .set(&DataKey::RewardRate, &reward_rate);
+        Ok(())
+    }
 
-        if amount < min_stake {
-            return Err(ContractError::BelowMinStake);
+    /// Create or replace a lock plan. Stakes already opened under the tier
+    /// keep the terms they were opened with.
+    pub fn set_tier(
+        env: Env,
+        admin: Address,
+        tier_id: u32,
+        config: TierConfig,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        if config.reward_multiplier_bps == 0 || config.early_exit_penalty_bps > MAX_PENALTY_BPS {
+            return Err(ContractError::InvalidConfig);
         }
 
-        // Transfer tokens to contract
-        let token_client = token::Client::new(&env, &staking_token);
-        token_client.transfer(&user, env.current_contract_address(), &amount);
+        let mut tier_ids = get_tier_ids(&env);
+        if !tier_ids.contains(tier_id) {
+            tier_ids.push_back(tier_id);
+            env.storage().instance().set(&DataKey::TierIds, &tier_ids);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::Tier(tier_id), &config);
+        Ok(())
+    }
 
-        // Generate stake ID based on user nonce
-        let nonce_key = DataKey::StakeNonce(user.clone());
-        let nonce: u32 = env.storage().persistent().get(&nonce_key).unwrap_or(0);
-        env.storage().persistent().set(&nonce_key, &(nonce + 1));
-        let stake_id = U256::from_u32(&env, nonce);
+    /// Stop offering a tier; existing stakes under it are unaffected.
+    pub fn remove_tier(env: Env, admin: Address, tier_id: u32) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        get_tier(&env, tier_id)?;
 
-        let timestamp = env.ledger().timestamp();
+        let mut tier_ids = get_tier_ids(&env);
+        if let Some(index) = tier_ids.first_index_of(tier_id) {
+            tier_ids.remove(index);
+        }
+        env.storage().instance().set(&DataKey::TierIds, &tier_ids);
+        env.storage().instance().remove(&DataKey::Tier(tier_id));
+        Ok(())
+    }
 
-        // Record the stake
-        let stake_data = StakeData { amount, timestamp };
-        env.storage().persistent().set(
-            &DataKey::UserStake(user.clone(), stake_id.clone()),
-            &stake_data,
-        );
+    pub fn get_tier_config(env: Env, tier_id: u32) -> Result<TierConfig, ContractError> {
+        get_tier(&env, tier_id)
+    }
 
-        // Update total stake and per-user active duration
-        let total_key = DataKey::TotalStake(user.clone());
-        let current_total: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
-        let new_total = current_total + amount;
+    /// Every tier currently offered, for rendering the available plans
+    pub fn get_tiers(env: Env) -> Vec<(u32, TierConfig)> {
+        let mut tiers = Vec::new(&env);
+        for tier_id in get_tier_ids(&env).iter() {
+            tiers.push_back((tier_id, get_tier(&env, tier_id).unwrap()));
+        }
+        tiers
+    }
 
-        update_user_active_duration_on_change(&env, &user, current_total, new_total);
+    /// Address that receives early-exit penalties
+    pub fn set_treasury(env: Env, admin: Address, treasury: Address) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
 
-        env.storage().persistent().set(&total_key, &new_total);
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+        Ok(())
+    }
 
-        // Emit Event
-        let mut event = create_stake_event(
-            user.clone(),
-            amount,
-            staking_token,
-            env.current_contract_address(),
-            stake_id.clone(),
-        );
-        event.timestamp = timestamp;
-        #[allow(deprecated)] // keep (topic, user) format for indexer compatibility
-        env.events().publish((STAKE_EVENT, user.clone()), event);
+    pub fn get_treasury(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Treasury)
+    }
 
-        Ok(stake_id)
+    /// PlayerCard contract whose cards can boost stakes
+    pub fn set_player_card(
+        env: Env,
+        admin: Address,
+        player_card: Address,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PlayerCard, &player_card);
+        Ok(())
     }
 
-    pub fn unstake(env: Env, user: Address, stake_id: U256) -> Result<(), ContractError> {
+    pub fn get_player_card(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PlayerCard)
+    }
+
+    /// Reward boost in bps granted by a card of `rarity`; 10_000 is no boost
+    pub fn set_rarity_boost(
+        env: Env,
+        admin: Address,
+        rarity: Rarity,
+        boost_bps: u32,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        if boost_bps < BASE_MULTIPLIER_BPS {
+            return Err(ContractError::InvalidConfig);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::RarityBoost(rarity), &boost_bps);
+        Ok(())
+    }
+
+    pub fn get_rarity_boost(env: Env, rarity: Rarity) -> u32 {
+        get_rarity_boost(&env, rarity)
+    }
+
+    /// Boost a stake with a PlayerCard `user` owns. The card is locked for
+    /// as long as the stake stays open and released when it is fully
+    /// withdrawn. Only owners can boost: a renter is not the owner, and a
+    /// rented-out card cannot be locked. Returns the boost applied in bps.
+    pub fn apply_boost(
+        env: Env,
+        user: Address,
+        stake_id: U256,
+        token_id: u64,
+    ) -> Result<u32, ContractError> {
         user.require_auth();
+        require_not_paused(&env)?;
+
+        let stake_key = DataKey::UserStake(user.clone(), stake_id.clone());
+        let mut stake_data: StakeData = env
+            .storage()
+            .persistent()
+            .get(&stake_key)
+            .ok_or(ContractError::StakeNotFound)?;
+        if stake_data.boost_card.is_some() {
+            return Err(ContractError::InvalidStatus);
+        }
+
+        let player_card = get_player_card(&env)?;
+        let attributes = get_card_attributes(&env, &player_card, token_id)?
+            .ok_or(ContractError::InvalidConfig)?;
+        lock_card(&env, &player_card, &user, token_id)?;
+
+        // Earn the boost only from now on
+        settle_stake(&env, &mut stake_data);
+        let old_weight = stake_weight(&stake_data);
+        stake_data.boost_card = Some(token_id);
+        stake_data.boost_card_contract = Some(player_card);
+        stake_data.boost_bps = get_rarity_boost(&env, attributes.rarity);
+        adjust_total_weight(&env, stake_weight(&stake_data) - old_weight);
+        store_stake(&env, &stake_key, &stake_data);
+
+        #[allow(deprecated)] // keep (topic, user) format for indexer compatibility
+        env.events().publish(
+            (Symbol::new(&env, "stake_boosted"), user),
+            (stake_id, token_id, stake_data.boost_bps),
+        );
+
+        Ok(stake_data.boost_bps)
+    }
+
+    /// Allow or revoke an address (e.g. a dispute or bond contract) to slash
+    pub fn set_slasher(
+        env: Env,
+        admin: Address,
+        slasher: Address,
+        allowed: bool,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        let key = DataKey::Slasher(slasher);
+        if allowed {
+            env.storage().instance().set(&key, &true);
+        } else {
+            env.storage().instance().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_slasher(env: Env, slasher: Address) -> bool {
+        env.storage().instance().has(&DataKey::Slasher(slasher))
+    }
+
+    /// Take `amount` of a stake's principal and send it to the treasury, so
+    /// staked funds can back operator bonds or dispute outcomes. Ignores the
+    /// cooldown, lock and minimum stake; rewards earned so far are paid to
+    /// the user. `reason_hash` identifies the off-chain ruling. Returns the
+    /// amount left staked.
+    pub fn slash(
+        env: Env,
+        slasher: Address,
+        user: Address,
+        stake_id: U256,
+        amount: i128,
+        reason_hash: BytesN<32>,
+    ) -> Result<i128, ContractError> {
+        slasher.require_auth();
+        if !Self::is_slasher(env.clone(), slasher.clone()) {
+            return Err(ContractError::Unauthorized);
+        }
+        require_not_paused(&env)?;
 
         let staking_token: Address = env
             .storage()
             .instance()
             .get(&DataKey::StakingToken)
             .ok_or(ContractError::NotInitialized)?;
-        let cooldown_period: u64 = env
+        let recipient: Address = env
             .storage()
             .instance()
-            .get(&DataKey::CooldownPeriod)
-            .unwrap();
+            .get(&DataKey::Treasury)
+            .ok_or(ContractError::InvalidConfig)?;
+        let stake_data = Self::get_stake(env.clone(), user.clone(), stake_id.clone())?;
+        if amount <= 0 || amount > stake_data.amount {
+            return Err(ContractError::InvalidAmount);
+        }
+        let remaining = stake_data.amount - amount;
 
-        let stake_key = DataKey::UserStake(user.clone(), stake_id.clone());
+        shrink_stake(&env, &user, &stake_id, stake_data, amount)?;
+        token::Client::new(&env, &staking_token).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &amount,
+        );
+
+        let event = StakeSlashedEvent {
+            version: EVENT_SCHEMA_VERSION,
+            user: user.clone(),
+            stake_id,
+            slasher,
+            amount,
+            remaining_amount: remaining,
+            recipient,
+            reason_hash,
+            timestamp: env.ledger().timestamp(),
+        };
+        publish_versioned_event(&env, (SLASH_EVENT, user.clone()), SLASH_EVENT, user, event);
+
+        Ok(remaining)
+    }
+
+    /// Report new stakes to a referral contract that lists this contract
+    /// as a reporter.
+    pub fn set_referral(env: Env, admin: Address, referral: Address) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage().instance().set(&DataKey::Referral, &referral);
+        Ok(())
+    }
+
+    pub fn get_referral(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Referral)
+    }
+
+    /// Registry (the balance ledger) whose self-excluded users are turned
+    /// away from new stakes with `SelfExcluded`.
+    pub fn set_self_exclusion_registry(
+        env: Env,
+        admin: Address,
+        registry: Address,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+
+        env.storage()
+            .instance()
+            .set(&DataKey::SelfExclusionRegistry, &registry);
+        Ok(())
+    }
+
+    pub fn get_self_exclusion_registry(env: Env) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::SelfExclusionRegistry)
+    }
+
+    /// Add `amount` of the reward token to the pool rewards are paid from
+    pub fn fund_rewards(env: Env, funder: Address, amount: i128) -> Result<i128, ContractError> {
+        funder.require_auth();
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let reward_token: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::RewardToken)
+            .ok_or(ContractError::InvalidConfig)?;
+
+        token::Client::new(&env, &reward_token).transfer(
+            &funder,
+            env.current_contract_address(),
+            &amount,
+        );
+        let pool = get_reward_pool(&env) + amount;
+        set_reward_pool(&env, pool);
+
+        #[allow(deprecated)] // keep (topic, user) format for indexer compatibility
+        env.events().publish(
+            (Symbol::new(&env, "rewards_funded"), funder),
+            (amount, pool),
+        );
+
+        Ok(pool)
+    }
+
+    pub fn get_reward_pool(env: Env) -> i128 {
+        get_reward_pool(&env)
+    }
+
+    /// Pool level below which `reward_pool_low` is emitted
+    pub fn set_reward_pool_threshold(
+        env: Env,
+        admin: Address,
+        threshold: i128,
+    ) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        if threshold < 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::RewardPoolThreshold, &threshold);
+        Ok(())
+    }
+
+    pub fn get_reward_pool_threshold(env: Env) -> Option<i128> {
+        env.storage().instance().get(&DataKey::RewardPoolThreshold)
+    }
+
+    pub fn get_reward_rate(env: Env) -> i128 {
+        rewards::get_reward_rate(&env)
+    }
+
+    pub fn get_total_staked(env: Env) -> i128 {
+        rewards::get_total_staked(&env)
+    }
+
+    pub fn pending_rewards(env: Env, user: Address, stake_id: U256) -> Result<i128, ContractError> {
         let stake_data: StakeData = env
+            .storage()
+            .persistent()
+            .get(&DataKey::UserStake(user, stake_id))
+            .ok_or(ContractError::StakeNotFound)?;
+        Ok(earned(&stake_data, reward_per_token(&env)))
+    }
+
+    /// Transfer a stake's accrued rewards without touching the principal
+    pub fn claim_rewards(env: Env, user: Address, stake_id: U256) -> Result<i128, ContractError> {
+        user.require_auth();
+        require_not_paused(&env)?;
+
+        let stake_key = DataKey::UserStake(user.clone(), stake_id.clone());
+        let mut stake_data: StakeData = env
             .storage()
             .persistent()
             .get(&stake_key)
             .ok_or(ContractError::StakeNotFound)?;
 
-        let current_time = env.ledger().timestamp();
-        if current_time < stake_data.timestamp + cooldown_period {
-            return Err(ContractError::CooldownNotMet);
+        settle_stake(&env, &mut stake_data);
+        if env.storage().instance().has(&DataKey::RewardToken)
+            && get_reward_pool(&env) < stake_data.accrued_rewards
+        {
+            return Err(ContractError::InsufficientRewardPool);
         }
+        let claimed = pay_rewards(&env, &user, stake_data.accrued_rewards);
+        stake_data.accrued_rewards -= claimed;
+        store_stake(&env, &stake_key, &stake_data);
 
-        // Remove the stake
-        env.storage().persistent().remove(&stake_key);
+        #[allow(deprecated)] // keep (topic, user) format for indexer compatibility
+        env.events()
+            .publish((REWARDS_CLAIMED_EVENT, user), (stake_id, claimed));
 
-        // Update total stake and per-user active duration
-        let total_key = DataKey::TotalStake(user.clone());
-        let current_total: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
-        let new_total = current_total - stake_data.amount;
+        Ok(claimed)
+    }
 
-        update_user_active_duration_on_change(&env, &user, current_total, new_total);
+    /// Rewards of `user`'s closed stakes that the pool could not pay when
+    /// they were withdrawn
+    pub fn get_unpaid_rewards(env: Env, user: Address) -> i128 {
+        get_unpaid_rewards(&env, &user)
+    }
 
-        if new_total > 0 {
-            env.storage().persistent().set(&total_key, &new_total);
-        } else {
-            env.storage().persistent().remove(&total_key);
-        }
+    /// Pay out `user`'s unpaid rewards once the pool can cover them
+    pub fn claim_unpaid_rewards(env: Env, user: Address) -> Result<i128, ContractError> {
+        user.require_auth();
+        require_not_paused(&env)?;
 
-        // Transfer tokens back to user
-        let token_client = token::Client::new(&env, &staking_token);
-        token_client.transfer(&env.current_contract_address(), &user, &stake_data.amount);
+        let unpaid = get_unpaid_rewards(&env, &user);
+        if get_reward_pool(&env) < unpaid {
+            return Err(ContractError::InsufficientRewardPool);
+        }
+        let claimed = pay_rewards(&env, &user, unpaid);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::UnpaidRewards(user.clone()));
 
-        // Emit Event
-        let mut event = create_unstake_event(
-            user.clone(),
-            stake_data.amount,
-            staking_token,
-            env.current_contract_address(),
-            stake_id,
-            0, // Rewards are not implemented in this version, hardcode 0
-        );
-        event.timestamp = current_time;
         #[allow(deprecated)] // keep (topic, user) format for indexer compatibility
-        env.events().publish((UNSTAKE_EVENT, user.clone()), event);
+        env.events()
+            .publish((Symbol::new(&env, "unpaid_rewards_claimed"), user), claimed);
 
-        Ok(())
+        Ok(claimed)
     }
 
     pub fn get_total_stake(env: Env, user: Address) -> i128 {
@@ -231,12 +1102,38 @@ impl StakingContract {
     }
 
     pub fn get_stake(env: Env, user: Address, stake_id: U256) -> Result<StakeData, ContractError> {
+        let stake_key = DataKey::UserStake(user, stake_id);
+        common::bump_persistent(&env, &stake_key);
         env.storage()
             .persistent()
-            .get(&DataKey::UserStake(user, stake_id))
+            .get(&stake_key)
             .ok_or(ContractError::StakeNotFound)
     }
 
+    /// Page of `user`'s open stake ids, oldest first
+    pub fn get_user_stake_ids(env: Env, user: Address, offset: u32, limit: u32) -> Vec<U256> {
+        page_user_stake_ids(&env, &user, offset, limit)
+    }
+
+    /// Page of `user`'s open stakes with their details, oldest first
+    pub fn get_user_stakes(
+        env: Env,
+        user: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<(U256, StakeData)> {
+        let mut stakes = Vec::new(&env);
+        for stake_id in page_user_stake_ids(&env, &user, offset, limit).iter() {
+            let stake_data: StakeData = env
+                .storage()
+                .persistent()
+                .get(&DataKey::UserStake(user.clone(), stake_id.clone()))
+                .unwrap();
+            stakes.push_back((stake_id, stake_data));
+        }
+        stakes
+    }
+
     // Returns the cumulative active staking duration for a user in seconds.
     // This is the total time the user has had a non-zero total staked balance.
     pub fn get_user_active_duration(env: Env, user: Address) -> u64 {
@@ -277,6 +1174,82 @@ impl StakingContract {
         let now = env.ledger().timestamp();
         Ok(now - stake_data.timestamp)
     }
+
+    pub fn set_guardians(
+        env: Env,
+        admin: Address,
+        guardians: Vec<Address>,
+        threshold: u32,
+        recovery_delay: u64,
+    ) -> Result<GuardianConfig, ContractError> {
+        Self::require_admin(&env, &admin)?;
+        set_guardian_config(&env, guardians, threshold, recovery_delay)
+    }
+
+    pub fn get_guardians(env: Env) -> Option<GuardianConfig> {
+        get_guardian_config(&env)
+    }
+
+    // Any guardian can freeze staking and unstaking; only the admin resumes it.
+    pub fn guardian_pause(env: Env, guardian: Address) -> Result<(), ContractError> {
+        guardian_pause(&env, &guardian)
+    }
+
+    pub fn pause(env: Env, admin: Address) -> Result<(), ContractError> {
+        pause(&env, &Self::verify_admin(&env, &admin)?);
+        Ok(())
+    }
+
+    pub fn unpause(env: Env, admin: Address) -> Result<(), ContractError> {
+        unpause(&env, &Self::verify_admin(&env, &admin)?);
+        Ok(())
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        is_paused(&env)
+    }
+
+    pub fn approve_recovery(
+        env: Env,
+        guardian: Address,
+        new_admin: Address,
+    ) -> Result<RecoveryRequest, ContractError> {
+        approve_recovery(&env, &guardian, &new_admin)
+    }
+
+    pub fn get_pending_recovery(env: Env) -> Option<RecoveryRequest> {
+        get_pending_recovery(&env)
+    }
+
+    pub fn cancel_recovery(env: Env, admin: Address) -> Result<(), ContractError> {
+        Self::require_admin(&env, &admin)?;
+        cancel_recovery(&env)
+    }
+
+    // Installs the guardian-approved admin once the recovery delay has passed.
+    pub fn execute_recovery(env: Env) -> Result<Address, ContractError> {
+        let new_admin = execute_recovery(&env)?;
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        Ok(new_admin)
+    }
+
+    // Identity check only; the pausable helpers require the admin's auth.
+    fn verify_admin(env: &Env, admin: &Address) -> Result<Address, ContractError> {
+        let stored_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(ContractError::NotInitialized)?;
+        if *admin != stored_admin {
+            return Err(ContractError::Unauthorized);
+        }
+        Ok(stored_admin)
+    }
+
+    fn require_admin(env: &Env, admin: &Address) -> Result<(), ContractError> {
+        Self::verify_admin(env, admin)?.require_auth();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
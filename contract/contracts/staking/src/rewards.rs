@@ -0,0 +1,97 @@
+use crate::storage::{DataKey, StakeData};
+use soroban_sdk::Env;
+
+/// Fixed-point scale for the reward-per-token accumulator.
+pub const REWARD_PRECISION: i128 = 1_000_000_000_000;
+
+/// Multipliers are expressed in basis points; this one earns the base rate.
+pub const BASE_MULTIPLIER_BPS: u32 = 10_000;
+
+// Rewards are emitted at `RewardRate` tokens per second, shared pro rata
+// across the reward weight of everything staked. A stake's weight is its
+// amount scaled by its tier multiplier and card boost. `RewardPerToken` accumulates the
+// reward owed to one unit of weight since launch; each stake remembers the
+// value it last settled at, so its reward is `weight * (current - paid)`.
+
+pub fn get_total_staked(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TotalStaked)
+        .unwrap_or(0)
+}
+
+pub fn get_total_weight(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TotalRewardWeight)
+        .unwrap_or(0)
+}
+
+pub fn stake_weight(stake: &StakeData) -> i128 {
+    let base = BASE_MULTIPLIER_BPS as i128;
+    stake.amount * stake.reward_multiplier_bps as i128 / base * stake.boost_bps as i128 / base
+}
+
+pub fn get_reward_rate(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::RewardRate)
+        .unwrap_or(0)
+}
+
+/// Accumulator value as of the current ledger time, without persisting it.
+pub fn reward_per_token(env: &Env) -> i128 {
+    let stored: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::RewardPerToken)
+        .unwrap_or(0);
+    let total_weight = get_total_weight(env);
+    if total_weight == 0 {
+        return stored;
+    }
+
+    let last_update: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::LastRewardUpdate)
+        .unwrap_or_else(|| env.ledger().timestamp());
+    let elapsed = env.ledger().timestamp().saturating_sub(last_update) as i128;
+    stored + get_reward_rate(env) * elapsed * REWARD_PRECISION / total_weight
+}
+
+/// Persist the accumulator; call before any change to the rate or total weight.
+pub fn update_reward_per_token(env: &Env) -> i128 {
+    let current = reward_per_token(env);
+    env.storage()
+        .instance()
+        .set(&DataKey::RewardPerToken, &current);
+    env.storage()
+        .instance()
+        .set(&DataKey::LastRewardUpdate, &env.ledger().timestamp());
+    current
+}
+
+pub fn earned(stake: &StakeData, reward_per_token: i128) -> i128 {
+    stake.accrued_rewards
+        + stake_weight(stake) * (reward_per_token - stake.reward_per_token_paid) / REWARD_PRECISION
+}
+
+/// Move everything the stake has earned so far into `accrued_rewards`.
+pub fn settle_stake(env: &Env, stake: &mut StakeData) {
+    let current = update_reward_per_token(env);
+    stake.accrued_rewards = earned(stake, current);
+    stake.reward_per_token_paid = current;
+}
+
+pub fn adjust_total_staked(env: &Env, delta: i128) {
+    let total = get_total_staked(env) + delta;
+    env.storage().instance().set(&DataKey::TotalStaked, &total);
+}
+
+pub fn adjust_total_weight(env: &Env, delta: i128) {
+    let total = get_total_weight(env) + delta;
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalRewardWeight, &total);
+}
@@ -1,3 +1,4 @@
+use common::Rarity;
 use soroban_sdk::{contracttype, Address, U256};
 
 #[contracttype]
@@ -12,6 +13,40 @@ pub enum DataKey {
     StakeNonce(Address),         // u32: Nonce used for generating unique stake IDs
     TotalStakeDuration(Address), // u64: Cumulative active staking duration for a user (seconds)
     ActiveSince(Address),        // u64: Timestamp when user last became an active staker
+    RewardToken,                 // Address: Token paid out as staking rewards
+    RewardRate,                  // i128: Reward tokens emitted per second across all stakes
+    RewardPerToken,              // i128: Accumulated reward per staked unit (scaled)
+    LastRewardUpdate,            // u64: Timestamp the accumulator was last persisted
+    TotalStaked,                 // i128: Total amount staked across all users
+    Referral,                    // Address: Referral contract new stakes are reported to
+    Tier(u32),                   // TierConfig: Lock plan stakers can opt into
+    TierIds,                     // Vec<u32>: Every configured tier id, in creation order
+    Treasury,                    // Address: Receives early-exit penalties
+    TotalRewardWeight,           // i128: Sum of multiplier-weighted stake amounts
+    UserStakeIds(Address),       // Vec<U256>: Open stake ids of a user, oldest first
+    EmergencyConfig,             // EmergencyConfig: Penalty for skipping cooldowns and locks
+    PlayerCard,                  // Address: PlayerCard contract whose cards boost stakes
+    RarityBoost(Rarity),         // u32: Reward boost in bps for a card of this rarity
+    Slasher(Address),            // bool: Address allowed to slash stakes
+    RewardPool,                  // i128: Funded reward tokens not yet paid out
+    RewardPoolThreshold,         // i128: Pool level below which a low-pool event fires
+    SelfExclusionRegistry,       // Address: Registry whose self-excluded users cannot stake
+    UnpaidRewards(Address),      // i128: Rewards of a user's closed stakes the pool could not cover
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmergencyConfig {
+    pub penalty_bps: u32,           // Share of the principal forfeited
+    pub penalty_collector: Address, // Receives the forfeited amount
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TierConfig {
+    pub lock_duration: u64,          // Seconds the stake is locked for
+    pub reward_multiplier_bps: u32,  // Reward weight; 10_000 earns the base rate
+    pub early_exit_penalty_bps: u32, // Share of principal withdrawn before unlock sent to the treasury
 }
 
 #[contracttype]
@@ -19,4 +54,13 @@ pub enum DataKey {
 pub struct StakeData {
     pub amount: i128,
     pub timestamp: u64,
+    pub reward_per_token_paid: i128, // Accumulator value at the last settlement
+    pub accrued_rewards: i128,       // Rewards earned but not yet claimed
+    pub tier_id: Option<u32>,        // Tier the stake was opened under, if any
+    pub lock_until: u64,             // Withdrawals before this pay the early-exit penalty
+    pub reward_multiplier_bps: u32,  // Tier multiplier at stake time
+    pub early_exit_penalty_bps: u32, // Tier penalty at stake time
+    pub boost_card: Option<u64>,     // PlayerCard locked to this stake as a boost
+    pub boost_card_contract: Option<Address>, // PlayerCard contract that locked `boost_card`
+    pub boost_bps: u32,              // Card boost; 10_000 when unboosted
 }
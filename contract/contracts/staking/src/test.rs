@@ -1,7 +1,8 @@
-use crate::{StakingContract, StakingContractClient};
+use crate::storage::TierConfig;
+use crate::{StakingContract, StakingContractClient, MAX_PENALTY_BPS};
 use common::errors::ContractError;
 use soroban_sdk::testutils::{Address as _, Ledger};
-use soroban_sdk::{token, Address, Env};
+use soroban_sdk::{token, vec, Address, Env};
 // use common::events::{STAKE_EVENT, UNSTAKE_EVENT}; // for event checking
 
 fn setup_test() -> (
@@ -109,3 +110,540 @@ fn test_stake_not_found() {
     let res = client.try_unstake(&user, &fake_id);
     assert_eq!(res, Err(Ok(ContractError::StakeNotFound)));
 }
+
+#[test]
+fn test_guardian_pause_and_recovery() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &0);
+
+    let guardian = Address::generate(&env);
+    client.set_guardians(
+        &admin,
+        &soroban_sdk::vec![&env, guardian.clone()],
+        &1,
+        &common::guardian::MIN_RECOVERY_DELAY,
+    );
+
+    client.guardian_pause(&guardian);
+    assert_eq!(
+        client.try_stake(&user, &1000),
+        Err(Ok(ContractError::Paused))
+    );
+    client.unpause(&admin);
+    client.stake(&user, &1000);
+
+    // Guardian rotates a lost admin key after the mandatory delay
+    let new_admin = Address::generate(&env);
+    client.approve_recovery(&guardian, &new_admin);
+    env.ledger().with_mut(|li| {
+        li.timestamp += common::guardian::MIN_RECOVERY_DELAY;
+    });
+    assert_eq!(client.execute_recovery(), new_admin);
+
+    let res = client.try_update_config(&admin, &Some(1), &None);
+    assert_eq!(res, Err(Ok(ContractError::Unauthorized)));
+    client.update_config(&new_admin, &Some(1), &None);
+}
+
+#[test]
+fn test_pause_blocks_stake_and_unstake() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &0);
+    let stake_id = client.stake(&user, &1000);
+
+    let res = client.try_pause(&user);
+    assert_eq!(res, Err(Ok(ContractError::Unauthorized)));
+    client.pause(&admin);
+    assert!(client.is_paused());
+
+    assert_eq!(
+        client.try_stake(&user, &1000),
+        Err(Ok(ContractError::Paused))
+    );
+    assert_eq!(
+        client.try_unstake(&user, &stake_id),
+        Err(Ok(ContractError::Paused))
+    );
+    assert_eq!(client.get_total_stake(&user), 1000);
+
+    client.unpause(&admin);
+    env.ledger().with_mut(|li| {
+        li.timestamp += 1;
+    });
+    client.unstake(&user, &stake_id);
+}
+
+fn setup_rewards(
+    env: &Env,
+    client: &StakingContractClient<'static>,
+    admin: &Address,
+    rate: i128,
+) -> token::Client<'static> {
+    let reward_token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    token::StellarAssetClient::new(env, &reward_token_id).mint(admin, &1_000_000_000);
+    client.set_reward_config(admin, &reward_token_id, &rate);
+    client.fund_rewards(admin, &1_000_000_000);
+    token::Client::new(env, &reward_token_id)
+}
+
+#[test]
+fn test_rewards_accrue_pro_rata_by_amount_and_duration() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &0);
+    let reward_token = setup_rewards(&env, &client, &admin, 100);
+
+    let other = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token_client.address).mint(&other, &10_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let stake_a = client.stake(&user, &1000);
+
+    // Alone for 10s: all 1000 emitted tokens go to the first stake
+    env.ledger().with_mut(|li| li.timestamp = 1_010);
+    assert_eq!(client.pending_rewards(&user, &stake_a), 1000);
+    let stake_b = client.stake(&other, &3000);
+
+    // Shared 1:3 for the next 10s
+    env.ledger().with_mut(|li| li.timestamp = 1_020);
+    assert_eq!(client.pending_rewards(&user, &stake_a), 1250);
+    assert_eq!(client.pending_rewards(&other, &stake_b), 750);
+
+    assert_eq!(client.claim_rewards(&user, &stake_a), 1250);
+    assert_eq!(reward_token.balance(&user), 1250);
+    assert_eq!(client.pending_rewards(&user, &stake_a), 0);
+    assert_eq!(client.get_stake(&user, &stake_a).amount, 1000);
+}
+
+#[test]
+fn test_unstake_pays_out_accrued_rewards() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &0);
+    let reward_token = setup_rewards(&env, &client, &admin, 5);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let stake_id = client.stake(&user, &2000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_100);
+    client.unstake(&user, &stake_id);
+    assert_eq!(reward_token.balance(&user), 500);
+    assert_eq!(client.get_total_staked(), 0);
+
+    assert_eq!(
+        client.try_set_reward_config(&user, &reward_token.address, &1),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_unstake_partial() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &86400);
+
+    env.ledger().with_mut(|li| li.timestamp = 100_000);
+    let stake_id = client.stake(&user, &5000);
+
+    assert_eq!(
+        client.try_unstake_partial(&user, &stake_id, &2000),
+        Err(Ok(ContractError::CooldownNotMet))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 200_000);
+    assert_eq!(
+        client.try_unstake_partial(&user, &stake_id, &5001),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    // Would leave less than the minimum stake behind
+    assert_eq!(
+        client.try_unstake_partial(&user, &stake_id, &4500),
+        Err(Ok(ContractError::BelowMinStake))
+    );
+
+    assert_eq!(client.unstake_partial(&user, &stake_id, &2000), 3000);
+    let remaining = client.get_stake(&user, &stake_id);
+    assert_eq!(remaining.amount, 3000);
+    assert_eq!(remaining.timestamp, 100_000);
+    assert_eq!(client.get_total_stake(&user), 3000);
+    assert_eq!(token_client.balance(&user), 100_000_000 - 3000);
+
+    // Withdrawing the rest removes the stake
+    assert_eq!(client.unstake_partial(&user, &stake_id, &3000), 0);
+    assert_eq!(
+        client.try_get_stake(&user, &stake_id),
+        Err(Ok(ContractError::StakeNotFound))
+    );
+    assert_eq!(client.get_total_stake(&user), 0);
+}
+
+#[test]
+fn test_matured_stakes_are_reported_to_the_referral_contract() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &86400);
+
+    let referral_id = env.register(referral::ReferralContract, ());
+    let referral = referral::ReferralContractClient::new(&env, &referral_id);
+    referral.initialize(
+        &admin,
+        &token_client.address,
+        &soroban_sdk::vec![
+            &env,
+            referral::CommissionTier {
+                min_volume: 0,
+                bps: 100,
+            }
+        ],
+    );
+    referral.set_reporter(&admin, &client.address, &true);
+    client.set_referral(&admin, &referral_id);
+
+    let referrer = Address::generate(&env);
+    referral.register_referrer(&user, &referrer);
+    let stake_id = client.stake(&user, &5000);
+    let emergency_stake = client.stake(&user, &5000);
+    // Nothing is credited until the principal has served its term
+    assert_eq!(referral.get_stats(&referrer).referred_volume, 0);
+
+    client.set_emergency_config(&admin, &0, &admin);
+    client.emergency_unstake(&user, &emergency_stake);
+    assert_eq!(referral.get_stats(&referrer).referred_volume, 0);
+
+    env.ledger().with_mut(|li| li.timestamp += 86400);
+    client.unstake(&user, &stake_id);
+    assert_eq!(referral.get_stats(&referrer).referred_volume, 5000);
+    assert_eq!(referral.get_stats(&referrer).accrued, 50);
+}
+
+#[test]
+fn test_self_excluded_user_cannot_stake() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &86400);
+
+    let ledger_id = env.register(balance_ledger::BalanceLedgerContract, ());
+    let ledger = balance_ledger::BalanceLedgerContractClient::new(&env, &ledger_id);
+    ledger.initialize(&admin);
+    client.set_self_exclusion_registry(&admin, &ledger_id);
+    ledger.self_exclude(&user, &86400);
+
+    assert_eq!(
+        client.try_stake(&user, &5000),
+        Err(Ok(ContractError::SelfExcluded))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += 86400);
+    client.stake(&user, &5000);
+    assert_eq!(token_client.balance(&user), 100_000_000 - 5000);
+}
+
+#[test]
+fn test_tiered_stakes_earn_multiplier_and_pay_early_exit_penalty() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &0);
+    let reward_token = setup_rewards(&env, &client, &admin, 100);
+    let treasury = Address::generate(&env);
+    let other = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token_client.address).mint(&other, &10_000);
+
+    let ninety_days = TierConfig {
+        lock_duration: 90 * 86400,
+        reward_multiplier_bps: 30_000,
+        early_exit_penalty_bps: 1_000,
+    };
+    client.set_tier(&admin, &90, &ninety_days);
+    assert_eq!(client.get_tier_config(&90), ninety_days);
+    assert_eq!(client.get_tiers().len(), 1);
+    assert_eq!(
+        client.try_stake_with_tier(&user, &1000, &30),
+        Err(Ok(ContractError::TierNotFound))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let locked = client.stake_with_tier(&user, &1000, &90);
+    let flexible = client.stake(&other, &1000);
+
+    // Equal principal, triple weight: 3:1 split of 1000 emitted tokens
+    env.ledger().with_mut(|li| li.timestamp = 1_010);
+    assert_eq!(client.pending_rewards(&user, &locked), 750);
+    assert_eq!(client.pending_rewards(&other, &flexible), 250);
+
+    // Leaving early needs somewhere to send the penalty
+    assert_eq!(
+        client.try_unstake(&user, &locked),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.set_treasury(&admin, &treasury);
+    client.unstake(&user, &locked);
+    assert_eq!(token_client.balance(&treasury), 100);
+    assert_eq!(token_client.balance(&user), 100_000_000 - 100);
+    assert_eq!(reward_token.balance(&user), 750);
+
+    // After the lock nothing is withheld
+    let relocked = client.stake_with_tier(&user, &1000, &90);
+    env.ledger()
+        .with_mut(|li| li.timestamp = 1_010 + 90 * 86400);
+    client.unstake(&user, &relocked);
+    assert_eq!(token_client.balance(&treasury), 100);
+
+    client.remove_tier(&admin, &90);
+    assert_eq!(client.get_tiers().len(), 0);
+}
+
+#[test]
+fn test_user_stakes_are_enumerable() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &0);
+
+    let first = client.stake(&user, &1000);
+    let second = client.stake(&user, &2000);
+    let third = client.stake(&user, &3000);
+    assert_eq!(
+        client.get_user_stake_ids(&user, &0, &10),
+        vec![&env, first.clone(), second.clone(), third.clone()]
+    );
+
+    client.unstake(&user, &second);
+    client.unstake_partial(&user, &third, &1000);
+    assert_eq!(
+        client.get_user_stake_ids(&user, &1, &10),
+        vec![&env, third.clone()]
+    );
+
+    let stakes = client.get_user_stakes(&user, &0, &1);
+    assert_eq!(stakes.len(), 1);
+    let (stake_id, stake_data) = stakes.get_unchecked(0);
+    assert_eq!(stake_id, first);
+    assert_eq!(stake_data.amount, 1000);
+    assert_eq!(client.get_user_stakes(&user, &5, &10).len(), 0);
+}
+
+#[test]
+fn test_emergency_unstake_skips_cooldown_for_a_penalty() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &86400);
+    let collector = Address::generate(&env);
+
+    let stake_id = client.stake(&user, &10_000);
+    assert_eq!(
+        client.try_emergency_unstake(&user, &stake_id),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    assert_eq!(
+        client.try_set_emergency_config(&admin, &(MAX_PENALTY_BPS + 1), &collector),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.set_emergency_config(&admin, &500, &collector);
+
+    // Still works while staking is paused
+    client.pause(&admin);
+    assert_eq!(client.emergency_unstake(&user, &stake_id), 9_500);
+    assert_eq!(token_client.balance(&collector), 500);
+    assert_eq!(token_client.balance(&user), 100_000_000 - 500);
+    assert_eq!(client.get_total_stake(&user), 0);
+    assert_eq!(
+        client.try_get_stake(&user, &stake_id),
+        Err(Ok(ContractError::StakeNotFound))
+    );
+}
+
+#[test]
+fn test_player_card_boost_locks_the_card_for_the_stake() {
+    use common::player_card::{CardAttributes, Rarity};
+    use player_card::{PlayerCardContract, PlayerCardContractClient};
+    use soroban_sdk::{BytesN, String, Symbol};
+
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &0);
+    setup_rewards(&env, &client, &admin, 100);
+    let other = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token_client.address).mint(&other, &10_000);
+
+    let cards = PlayerCardContractClient::new(&env, &env.register(PlayerCardContract, ()));
+    cards.initialize(&admin);
+    let card = cards.mint(
+        &BytesN::from_array(&env, &[1; 32]),
+        &user,
+        &String::from_str(&env, "ipfs://card"),
+        &Some(CardAttributes {
+            rarity: Rarity::Epic,
+            position: Symbol::new(&env, "GK"),
+            rating: 88,
+            season: 2026,
+            edition: 1,
+        }),
+        &true,
+        &None,
+    );
+    client.set_player_card(&admin, &cards.address);
+    client.set_rarity_boost(&admin, &Rarity::Epic, &20_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let boosted = client.stake(&user, &1000);
+    let plain = client.stake(&other, &1000);
+    assert_eq!(
+        client.try_apply_boost(&other, &plain, &card),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(client.apply_boost(&user, &boosted, &card), 20_000);
+    assert_eq!(cards.get_locker(&card), Some(client.address.clone()));
+    assert_eq!(
+        cards.try_transfer(&user, &other, &card),
+        Err(Ok(ContractError::TokenNotTransferable))
+    );
+
+    // Double weight: 2:1 split of 300 emitted tokens
+    env.ledger().with_mut(|li| li.timestamp = 1_003);
+    assert_eq!(client.pending_rewards(&user, &boosted), 200);
+    assert_eq!(client.pending_rewards(&other, &plain), 100);
+
+    // The card is released on the contract that locked it, even after the
+    // staking admin points at a new card contract
+    client.set_player_card(&admin, &Address::generate(&env));
+    client.unstake(&user, &boosted);
+    assert_eq!(cards.get_locker(&card), None);
+    cards.transfer(&user, &other, &card);
+}
+
+#[test]
+fn test_only_card_owners_can_boost() {
+    use common::player_card::{CardAttributes, Rarity};
+    use player_card::{PlayerCardContract, PlayerCardContractClient};
+    use soroban_sdk::{BytesN, String, Symbol};
+
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &0);
+    let renter = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token_client.address).mint(&renter, &10_000);
+
+    let cards = PlayerCardContractClient::new(&env, &env.register(PlayerCardContract, ()));
+    cards.initialize(&admin);
+    let card = cards.mint(
+        &BytesN::from_array(&env, &[1; 32]),
+        &user,
+        &String::from_str(&env, "ipfs://card"),
+        &Some(CardAttributes {
+            rarity: Rarity::Epic,
+            position: Symbol::new(&env, "GK"),
+            rating: 88,
+            season: 2026,
+            edition: 1,
+        }),
+        &true,
+        &None,
+    );
+    client.set_player_card(&admin, &cards.address);
+    client.set_rarity_boost(&admin, &Rarity::Epic, &20_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    cards.list_for_rent(&user, &card, &token_client.address, &100, &500);
+    cards.rent(&renter, &card);
+    assert_eq!(cards.user_of(&card), renter);
+
+    // The renter uses the card, but does not own it
+    let rented = client.stake(&renter, &1000);
+    assert_eq!(
+        client.try_apply_boost(&renter, &rented, &card),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    // The owner cannot lock it while it is rented out
+    let owned = client.stake(&user, &1000);
+    assert_eq!(
+        client.try_apply_boost(&user, &owned, &card),
+        Err(Ok(ContractError::InvalidStatus))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    assert_eq!(client.apply_boost(&user, &owned, &card), 20_000);
+    assert_eq!(cards.get_locker(&card), Some(client.address.clone()));
+}
+
+#[test]
+fn test_slasher_moves_collateral_to_the_treasury() {
+    use soroban_sdk::BytesN;
+
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &86400);
+    let slasher = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let reason = BytesN::from_array(&env, &[7; 32]);
+
+    let stake_id = client.stake(&user, &5000);
+    assert_eq!(
+        client.try_slash(&slasher, &user, &stake_id, &1000, &reason),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.set_slasher(&admin, &slasher, &true);
+    assert_eq!(
+        client.try_slash(&slasher, &user, &stake_id, &1000, &reason),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.set_treasury(&admin, &treasury);
+    assert_eq!(
+        client.try_slash(&slasher, &user, &stake_id, &5001, &reason),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+
+    // Below the minimum stake and inside the cooldown is fine for a slash
+    assert_eq!(
+        client.slash(&slasher, &user, &stake_id, &4500, &reason),
+        500
+    );
+    assert_eq!(token_client.balance(&treasury), 4500);
+    assert_eq!(client.get_total_stake(&user), 500);
+    assert_eq!(client.get_total_staked(), 500);
+
+    assert_eq!(client.slash(&slasher, &user, &stake_id, &500, &reason), 0);
+    assert_eq!(client.get_user_stake_ids(&user, &0, &10).len(), 0);
+
+    client.set_slasher(&admin, &slasher, &false);
+    assert!(!client.is_slasher(&slasher));
+}
+
+#[test]
+fn test_claims_are_limited_by_the_funded_reward_pool() {
+    let (env, client, admin, user, token_client) = setup_test();
+    client.initialize(&admin, &token_client.address, &1000, &0);
+    let reward_token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let reward_token = token::Client::new(&env, &reward_token_id);
+    token::StellarAssetClient::new(&env, &reward_token_id).mint(&admin, &1_000);
+
+    assert_eq!(
+        client.try_fund_rewards(&admin, &100),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.set_reward_config(&admin, &reward_token_id, &10);
+    assert_eq!(client.fund_rewards(&admin, &150), 150);
+    client.set_reward_pool_threshold(&admin, &100);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let stake_id = client.stake(&user, &1000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_010);
+    assert_eq!(client.claim_rewards(&user, &stake_id), 100);
+    assert_eq!(client.get_reward_pool(), 50);
+
+    // 100 more accrued but only 50 funded: fail cleanly instead of reverting
+    env.ledger().with_mut(|li| li.timestamp = 1_020);
+    assert_eq!(
+        client.try_claim_rewards(&user, &stake_id),
+        Err(Ok(ContractError::InsufficientRewardPool))
+    );
+
+    // Unstaking never traps principal: it pays what the pool covers and
+    // keeps the rest owed
+    client.unstake(&user, &stake_id);
+    assert_eq!(reward_token.balance(&user), 150);
+    assert_eq!(client.get_reward_pool(), 0);
+    assert_eq!(token_client.balance(&user), 100_000_000);
+    assert_eq!(client.get_unpaid_rewards(&user), 50);
+
+    assert_eq!(
+        client.try_claim_unpaid_rewards(&user),
+        Err(Ok(ContractError::InsufficientRewardPool))
+    );
+    client.fund_rewards(&admin, &50);
+    assert_eq!(client.claim_unpaid_rewards(&user), 50);
+    assert_eq!(reward_token.balance(&user), 200);
+    assert_eq!(client.get_unpaid_rewards(&user), 0);
+}
@@ -0,0 +1,217 @@
+#![no_std]
+
+use common::{ActivityStats, ContractError};
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Symbol, Val, Vec};
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Admin,
+    EpochSeconds,
+    Reporter(Address),
+    Totals,
+    EpochStats(u64),
+    ContractTotals(Address),
+}
+
+#[contract]
+pub struct StatsHubContract;
+
+#[contractimpl]
+impl StatsHubContract {
+    pub fn initialize(env: Env, admin: Address, epoch_seconds: u64) -> Result<(), ContractError> {
+        let storage = env.storage().persistent();
+
+        if storage.has(&DataKey::Admin) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        if epoch_seconds == 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        storage.set(&DataKey::Admin, &admin);
+        storage.set(&DataKey::EpochSeconds, &epoch_seconds);
+        Ok(())
+    }
+
+    /// Swap in the uploaded Wasm `new_wasm_hash`, then call `migrate`.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), ContractError> {
+        Self::require_admin_auth(&env)?;
+        common::upgrade_contract(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION`. Returns the version migrated
+    /// from, or `None` if storage was already current.
+    pub fn migrate(env: Env) -> Result<Option<u32>, ContractError> {
+        Self::require_admin_auth(&env)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
+    }
+
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    /// Allow or revoke a platform contract's right to push activity.
+    pub fn set_reporter(env: Env, reporter: Address, allowed: bool) -> Result<(), ContractError> {
+        Self::require_admin_auth(&env)?;
+
+        let storage = env.storage().persistent();
+        if allowed {
+            storage.set(&DataKey::Reporter(reporter.clone()), &true);
+        } else {
+            storage.remove(&DataKey::Reporter(reporter.clone()));
+        }
+
+        publish_reporter_updated_event(&env, &reporter, allowed);
+        Ok(())
+    }
+
+    pub fn is_reporter(env: Env, reporter: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Reporter(reporter))
+    }
+
+    /// Add a reporter's activity delta to the current epoch bucket, the
+    /// reporter's own running totals and the platform-wide totals.
+    pub fn record_activity(
+        env: Env,
+        reporter: Address,
+        delta: ActivityStats,
+    ) -> Result<u64, ContractError> {
+        reporter.require_auth();
+        if !Self::is_reporter(env.clone(), reporter.clone()) {
+            return Err(ContractError::Unauthorized);
+        }
+        if delta.volume < 0 || delta.payouts < 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        let epoch = current_epoch(&env)?;
+        let storage = env.storage().persistent();
+
+        let epoch_key = DataKey::EpochStats(epoch);
+        let epoch_stats = accumulate(&get_stats(&env, &epoch_key), &delta)?;
+        storage.set(&epoch_key, &epoch_stats);
+
+        let contract_key = DataKey::ContractTotals(reporter.clone());
+        let contract_totals = accumulate(&get_stats(&env, &contract_key), &delta)?;
+        storage.set(&contract_key, &contract_totals);
+
+        let totals = accumulate(&get_stats(&env, &DataKey::Totals), &delta)?;
+        storage.set(&DataKey::Totals, &totals);
+
+        publish_activity_recorded_event(&env, &reporter, epoch, &delta);
+
+        Ok(epoch)
+    }
+
+    pub fn get_epoch_seconds(env: Env) -> Result<u64, ContractError> {
+        get_epoch_seconds(&env)
+    }
+
+    pub fn get_current_epoch(env: Env) -> Result<u64, ContractError> {
+        current_epoch(&env)
+    }
+
+    pub fn get_epoch_stats(env: Env, epoch: u64) -> ActivityStats {
+        get_stats(&env, &DataKey::EpochStats(epoch))
+    }
+
+    /// Consecutive epoch buckets starting at `from_epoch`, including empty ones.
+    pub fn get_epoch_range(env: Env, from_epoch: u64, limit: u32) -> Vec<(u64, ActivityStats)> {
+        let mut buckets = Vec::new(&env);
+        for epoch in from_epoch..from_epoch.saturating_add(limit as u64) {
+            buckets.push_back((epoch, get_stats(&env, &DataKey::EpochStats(epoch))));
+        }
+        buckets
+    }
+
+    pub fn get_contract_totals(env: Env, contract: Address) -> ActivityStats {
+        get_stats(&env, &DataKey::ContractTotals(contract))
+    }
+
+    pub fn get_totals(env: Env) -> ActivityStats {
+        get_stats(&env, &DataKey::Totals)
+    }
+
+    fn require_admin_auth(env: &Env) -> Result<(), ContractError> {
+        let admin: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .ok_or(ContractError::NotInitialized)?;
+        admin.require_auth();
+        Ok(())
+    }
+}
+
+fn accumulate(
+    current: &ActivityStats,
+    delta: &ActivityStats,
+) -> Result<ActivityStats, ContractError> {
+    Ok(ActivityStats {
+        bets_placed: current
+            .bets_placed
+            .checked_add(delta.bets_placed)
+            .ok_or(ContractError::InvalidAmount)?,
+        volume: current
+            .volume
+            .checked_add(delta.volume)
+            .ok_or(ContractError::InvalidAmount)?,
+        payouts: current
+            .payouts
+            .checked_add(delta.payouts)
+            .ok_or(ContractError::InvalidAmount)?,
+        mints: current
+            .mints
+            .checked_add(delta.mints)
+            .ok_or(ContractError::InvalidAmount)?,
+    })
+}
+
+fn get_epoch_seconds(env: &Env) -> Result<u64, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::EpochSeconds)
+        .ok_or(ContractError::NotInitialized)
+}
+
+fn current_epoch(env: &Env) -> Result<u64, ContractError> {
+    Ok(env.ledger().timestamp() / get_epoch_seconds(env)?)
+}
+
+fn get_stats(env: &Env, key: &DataKey) -> ActivityStats {
+    env.storage().persistent().get(key).unwrap_or_default()
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_reporter_updated_event(env: &Env, reporter: &Address, allowed: bool) {
+    env.events().publish(
+        (Symbol::new(env, "reporter_updated"), reporter.clone()),
+        allowed,
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_activity_recorded_event(
+    env: &Env,
+    reporter: &Address,
+    epoch: u64,
+    delta: &ActivityStats,
+) {
+    env.events().publish(
+        (Symbol::new(env, "activity_recorded"), reporter.clone()),
+        (epoch, delta.clone()),
+    );
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,139 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, Env,
+};
+
+fn setup(env: &Env) -> (StatsHubContractClient<'_>, Address) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let contract_id = env.register(StatsHubContract, ());
+    let client = StatsHubContractClient::new(env, &contract_id);
+    client.initialize(&admin, &3_600);
+
+    let reporter = Address::generate(env);
+    client.set_reporter(&reporter, &true);
+
+    (client, reporter)
+}
+
+fn bets(count: u64, volume: i128) -> ActivityStats {
+    ActivityStats {
+        bets_placed: count,
+        volume,
+        payouts: 0,
+        mints: 0,
+    }
+}
+
+#[test]
+fn initialize_only_once() {
+    let env = Env::default();
+    let (client, _reporter) = setup(&env);
+
+    assert_eq!(
+        client.try_initialize(&Address::generate(&env), &60),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn rejects_unregistered_reporters() {
+    let env = Env::default();
+    let (client, reporter) = setup(&env);
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_record_activity(&stranger, &bets(1, 100)),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    client.set_reporter(&reporter, &false);
+    assert!(!client.is_reporter(&reporter));
+    assert_eq!(
+        client.try_record_activity(&reporter, &bets(1, 100)),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn buckets_activity_per_epoch() {
+    let env = Env::default();
+    let (client, reporter) = setup(&env);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = 3_600 * 5 + 10;
+    });
+    assert_eq!(client.record_activity(&reporter, &bets(2, 500)), 5);
+    client.record_activity(&reporter, &bets(1, 250));
+
+    env.ledger().with_mut(|li| {
+        li.timestamp = 3_600 * 7;
+    });
+    client.record_activity(
+        &reporter,
+        &ActivityStats {
+            bets_placed: 0,
+            volume: 0,
+            payouts: 900,
+            mints: 3,
+        },
+    );
+
+    assert_eq!(client.get_epoch_stats(&5), bets(3, 750));
+    assert_eq!(client.get_epoch_stats(&6), ActivityStats::default());
+
+    let range = client.get_epoch_range(&5, &3);
+    assert_eq!(range.len(), 3);
+    assert_eq!(range.get(2).unwrap().0, 7);
+    assert_eq!(range.get(2).unwrap().1.payouts, 900);
+}
+
+#[test]
+fn aggregates_platform_and_contract_totals() {
+    let env = Env::default();
+    let (client, betting) = setup(&env);
+
+    let nft = Address::generate(&env);
+    client.set_reporter(&nft, &true);
+
+    client.record_activity(&betting, &bets(4, 1_000));
+    client.record_activity(
+        &nft,
+        &ActivityStats {
+            bets_placed: 0,
+            volume: 0,
+            payouts: 0,
+            mints: 2,
+        },
+    );
+
+    assert_eq!(client.get_contract_totals(&betting), bets(4, 1_000));
+    assert_eq!(client.get_contract_totals(&nft).mints, 2);
+
+    let expected = ActivityStats {
+        bets_placed: 4,
+        volume: 1_000,
+        payouts: 0,
+        mints: 2,
+    };
+    assert_eq!(client.get_totals(), expected);
+    assert_eq!(
+        common::view_functions::get_contract_stats(&env, client.address.clone()),
+        expected
+    );
+}
+
+#[test]
+fn rejects_negative_amounts() {
+    let env = Env::default();
+    let (client, reporter) = setup(&env);
+
+    assert_eq!(
+        client.try_record_activity(&reporter, &bets(1, -5)),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
@@ -1,10 +1,71 @@
+#![no_std]
+
+use common::{init_role, require_not_paused, require_role, ContractError, Role, TimelockEntry};
 use soroban_sdk::{
-    contract, contractimpl, 
-    Env, Address, 
-    symbol_short, Symbol, Event,
-    map, Map
+    contract, contractimpl, contracttype, token, vec, xdr::ToXdr, Address, BytesN, Env, IntoVal,
+    Symbol, Val, Vec,
 };
-use common::storage_keys::*;
+
+/// Storage layout version.
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Upper bound on the number of entries a single `get_revenue_breakdown`
+/// call returns.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Delay applied to timelocked actions until one is configured.
+pub const DEFAULT_TIMELOCK_DELAY: u64 = 86_400;
+
+/// Sensitive changes that only take effect through the timelock.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TimelockAction {
+    GrantRole(Role, Address),
+    RevokeRole(Role, Address),
+    /// Cap on a single withdrawal of the token; `None` lifts it
+    SetWithdrawLimit(Address, Option<i128>),
+    /// Send tokens held beyond what users are owed to the address
+    Sweep(Address, Address),
+    SetDelay(u64),
+    /// Replace this contract's code with the uploaded Wasm
+    Upgrade(BytesN<32>),
+}
+
+/// Tracked liabilities for one token against what the contract holds.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolvencyReport {
+    /// Sum of every user's balance
+    pub tracked_total: i128,
+    /// Tokens actually held by the treasury
+    pub actual_balance: i128,
+    /// `actual_balance - tracked_total`; negative means users are owed
+    /// more than is held
+    pub surplus: i128,
+    pub solvent: bool,
+}
+
+/// Cumulative revenue one source has paid in, per token.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevenueEntry {
+    /// e.g. `betting_fees`, `spin_pool`, `marketplace_fees`
+    pub source: Symbol,
+    pub token: Address,
+    pub total: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    Lock,
+    Balance(Address, Address),
+    TotalBalance(Address),
+    WithdrawLimit(Address),
+    RevenueReporter(Address),
+    Revenue(Symbol, Address),
+    RevenueKeys,
+}
 
 #[contract]
 pub struct Treasury;
@@ -12,139 +73,594 @@ pub struct Treasury;
 #[contractimpl]
 impl Treasury {
     /// Initialize the treasury contract
-    pub fn initialize(env: Env) {
-        if env.storage().instance().has(&TREASURY_LOCK) {
-            panic!("Treasury already initialized");
+    ///
+    /// `admin` receives every role and can delegate them afterwards.
+    pub fn initialize(env: Env, admin: Address) -> Result<(), ContractError> {
+        if env.storage().instance().has(&DataKey::Lock) {
+            return Err(ContractError::AlreadyInitialized);
         }
-        env.storage().instance().set(&TREASURY_LOCK, &false);
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Lock, &false);
+        init_role(&env, Role::Admin, &admin);
+        init_role(&env, Role::Pauser, &admin);
+        init_role(&env, Role::Treasurer, &admin);
+        init_role(&env, Role::Guardian, &admin);
+        common::set_timelock_delay(&env, DEFAULT_TIMELOCK_DELAY);
+        Ok(())
+    }
+
+    /// Signer roles (admin, treasurer, guardian) can only change through
+    /// `queue_action`.
+    pub fn grant_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        require_untimelocked_role(role)?;
+        common::grant_role(&env, &caller, role, &account)
+    }
+
+    pub fn revoke_role(
+        env: Env,
+        caller: Address,
+        role: Role,
+        account: Address,
+    ) -> Result<(), ContractError> {
+        require_untimelocked_role(role)?;
+        common::revoke_role(&env, &caller, role, &account)
+    }
+
+    pub fn renounce_role(env: Env, account: Address, role: Role) -> Result<(), ContractError> {
+        common::renounce_role(&env, &account, role)
+    }
+
+    pub fn set_role_admin(
+        env: Env,
+        caller: Address,
+        role: Role,
+        admin_role: Role,
+    ) -> Result<(), ContractError> {
+        require_untimelocked_role(role)?;
+        common::set_role_admin(&env, &caller, role, admin_role)
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        common::has_role(&env, role, &account)
+    }
+
+    /// Bring storage up to `CONTRACT_VERSION` once a queued `Upgrade` has
+    /// executed. Returns the version migrated from, or `None` if storage was
+    /// already current.
+    pub fn migrate(env: Env, admin: Address) -> Result<Option<u32>, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        Ok(common::complete_migration(&env, CONTRACT_VERSION))
+    }
+
+    pub fn get_version(env: Env) -> u32 {
+        common::get_version(&env)
     }
 
-    /// Deposit funds into the treasury
-    /// 
+    /// Anyone may extend TTLs; see `common::bump_storage_keys`.
+    pub fn bump_storage(env: Env, keys: Vec<Val>) -> u32 {
+        common::bump_storage_keys(&env, &keys)
+    }
+
+    pub fn get_role_admin(env: Env, role: Role) -> Role {
+        common::get_role_admin(&env, role)
+    }
+
+    /// Queue a sensitive change; it can be executed once the timelock delay
+    /// has passed. Requires `Role::Admin`. Returns the action id.
+    pub fn queue_action(
+        env: Env,
+        admin: Address,
+        action: TimelockAction,
+    ) -> Result<u64, ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        validate_action(&action)?;
+
+        Ok(common::queue_timelock_action(
+            &env,
+            &admin,
+            action_hash(&env, &action),
+        ))
+    }
+
+    /// Apply a matured action. `action` must be the one that was queued.
+    pub fn execute_action(
+        env: Env,
+        admin: Address,
+        action_id: u64,
+        action: TimelockAction,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+        common::execute_timelock_action(&env, action_id, &action_hash(&env, &action))?;
+
+        match action {
+            TimelockAction::GrantRole(role, account) => {
+                common::apply_role_change(&env, role, &account, true, &admin);
+            }
+            TimelockAction::RevokeRole(role, account) => {
+                common::apply_role_change(&env, role, &account, false, &admin);
+            }
+            TimelockAction::SetWithdrawLimit(token_address, limit) => {
+                let key = DataKey::WithdrawLimit(token_address);
+                match limit {
+                    Some(limit) => env.storage().persistent().set(&key, &limit),
+                    None => env.storage().persistent().remove(&key),
+                }
+            }
+            TimelockAction::Sweep(token_address, to) => {
+                Self::enter_locked_section(&env)?;
+                let surplus = solvency_report(&env, &token_address).surplus;
+                if surplus > 0 {
+                    token::Client::new(&env, &token_address).transfer(
+                        &env.current_contract_address(),
+                        &to,
+                        &surplus,
+                    );
+                }
+                publish_treasury_event(&env, "Sweep", &to, &token_address, surplus, 0);
+                Self::exit_locked_section(&env);
+            }
+            TimelockAction::SetDelay(delay) => common::set_timelock_delay(&env, delay),
+            TimelockAction::Upgrade(new_wasm_hash) => common::upgrade_contract(&env, new_wasm_hash),
+        }
+        Ok(())
+    }
+
+    /// Drop a queued action before it executes; requires `Role::Guardian`
+    pub fn cancel_action(env: Env, guardian: Address, action_id: u64) -> Result<(), ContractError> {
+        require_role(&env, Role::Guardian, &guardian)?;
+        common::cancel_timelock_action(&env, action_id)
+    }
+
+    pub fn get_queued_action(env: Env, action_id: u64) -> Option<TimelockEntry> {
+        common::get_timelock_action(&env, action_id)
+    }
+
+    pub fn get_timelock_delay(env: Env) -> u64 {
+        common::get_timelock_delay(&env)
+    }
+
+    pub fn get_withdraw_limit(env: Env, token_address: Address) -> Option<i128> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::WithdrawLimit(token_address))
+    }
+
+    /// Stop deposits and withdrawals; requires `Role::Pauser`
+    pub fn pause(env: Env, pauser: Address) -> Result<(), ContractError> {
+        common::pause_as_role(&env, &pauser)
+    }
+
+    /// Resume deposits and withdrawals; requires `Role::Admin`
+    pub fn unpause(env: Env, admin: Address) -> Result<(), ContractError> {
+        common::unpause_as_role(&env, &admin)
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        common::is_paused(&env)
+    }
+
+    /// Deposit tokens into the treasury
+    ///
     /// # Arguments
     /// * `from` - The address depositing funds
+    /// * `token_address` - The token being deposited
     /// * `amount` - The amount to deposit (must be > 0)
-    /// 
+    ///
     /// # Events
-    /// Emits a Deposit event with from address, amount, and new balance
-    pub fn deposit(env: Env, from: Address, amount: i128) {
+    /// Emits a Deposit event with from address, token, amount, and new balance
+    pub fn deposit(
+        env: Env,
+        from: Address,
+        token_address: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
         // Authentication: require caller to be the depositor
         from.require_auth();
-        
+        require_not_paused(&env)?;
+
         // Input validation
         if amount <= 0 {
-            panic!("Deposit amount must be positive");
+            return Err(ContractError::InvalidAmount);
         }
-        
+
         // Reentrancy protection
-        Self::_enter_locked_section(&env);
-        
-        // Get current balance
-        let balance_key = symbol_short!("balance");
-        let current_balance: i128 = env.storage().persistent().get(&from, &balance_key)
-            .unwrap_or(0);
-        
-        // Update balance (prevents overflow in Soroban)
-        let new_balance = current_balance.checked_add(amount)
-            .expect("Balance overflow");
-        
-        env.storage().persistent().set(&from, &balance_key, &new_balance);
-        
-        // Emit event
-        env.events().publish(
-            (Symbol::new(&env, "Deposit"), from.clone()),
-            (amount, new_balance)
-        );
-        
+        Self::enter_locked_section(&env)?;
+
+        // Pull the tokens in before crediting the depositor
+        let token_client = token::Client::new(&env, &token_address);
+        token_client.transfer(&from, env.current_contract_address(), &amount);
+
+        let current_balance = get_balance(&env, &from, &token_address);
+        let new_balance = current_balance
+            .checked_add(amount)
+            .ok_or(ContractError::InvalidAmount)?;
+        set_balance(&env, &from, &token_address, new_balance);
+        adjust_total_balance(&env, &token_address, amount)?;
+
+        publish_treasury_event(&env, "Deposit", &from, &token_address, amount, new_balance);
+
         // Release lock
-        Self::_exit_locked_section(&env);
+        Self::exit_locked_section(&env);
+        Ok(new_balance)
     }
 
-    /// Withdraw funds from the treasury
-    /// 
+    /// Withdraw tokens from the treasury
+    ///
     /// # Arguments
     /// * `to` - The address receiving funds (must be caller)
+    /// * `token_address` - The token being withdrawn
     /// * `amount` - The amount to withdraw (must be > 0)
-    /// 
+    ///
     /// # Events
-    /// Emits a Withdraw event with to address, amount, and new balance
-    pub fn withdraw(env: Env, to: Address, amount: i128) {
+    /// Emits a Withdraw event with to address, token, amount, and new balance
+    pub fn withdraw(
+        env: Env,
+        to: Address,
+        token_address: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
         // Authentication: require caller to be the withdrawer
         to.require_auth();
-        
+        require_not_paused(&env)?;
+
         // Input validation
         if amount <= 0 {
-            panic!("Withdrawal amount must be positive");
+            return Err(ContractError::InvalidAmount);
+        }
+        if let Some(limit) = Self::get_withdraw_limit(env.clone(), token_address.clone()) {
+            if amount > limit {
+                return Err(ContractError::ExceedsLimit);
+            }
         }
-        
+
         // Reentrancy protection
-        Self::_enter_locked_section(&env);
-        
-        // Get current balance
-        let balance_key = symbol_short!("balance");
-        let current_balance: i128 = env.storage().persistent().get(&to, &balance_key)
-            .unwrap_or(0);
-        
+        Self::enter_locked_section(&env)?;
+
         // Check sufficient funds (prevents double spending)
+        let current_balance = get_balance(&env, &to, &token_address);
         if current_balance < amount {
-            panic!("Insufficient balance");
+            return Err(ContractError::InsufficientBalance);
         }
-        
-        // Update balance
+
+        // Debit before paying out
         let new_balance = current_balance - amount;
-        env.storage().persistent().set(&to, &balance_key, &new_balance);
-        
-        // Emit event
-        env.events().publish(
-            (Symbol::new(&env, "Withdraw"), to.clone()),
-            (amount, new_balance)
-        );
-        
+        set_balance(&env, &to, &token_address, new_balance);
+        adjust_total_balance(&env, &token_address, -amount)?;
+
+        let token_client = token::Client::new(&env, &token_address);
+        token_client.transfer(&env.current_contract_address(), &to, &amount);
+
+        publish_treasury_event(&env, "Withdraw", &to, &token_address, amount, new_balance);
+
         // Release lock
-        Self::_exit_locked_section(&env);
+        Self::exit_locked_section(&env);
+        Ok(new_balance)
     }
 
-    /// Get the balance of a specific user
-    /// 
-    /// # Arguments
-    /// * `user` - The address to query
-    /// 
-    /// # Returns
-    /// The current balance of the user
-    pub fn get_balance(env: Env, user: Address) -> i128 {
-        let balance_key = symbol_short!("balance");
-        env.storage().persistent().get(&user, &balance_key)
-            .unwrap_or(0)
+    /// Allow a platform contract (betting, spin rewards, marketplace) to
+    /// report revenue.
+    pub fn set_revenue_reporter(
+        env: Env,
+        admin: Address,
+        reporter: Address,
+        allowed: bool,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Admin, &admin)?;
+
+        let key = DataKey::RevenueReporter(reporter);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Ok(())
+    }
+
+    pub fn is_revenue_reporter(env: Env, reporter: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::RevenueReporter(reporter))
+    }
+
+    /// Pull `amount` of `token_address` from a registered platform contract
+    /// and add it to the cumulative revenue of `source`. Revenue is not
+    /// owed to any user, so it counts toward the solvency surplus. Returns
+    /// the source's new total for the token.
+    pub fn record_revenue(
+        env: Env,
+        reporter: Address,
+        source: Symbol,
+        token_address: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        reporter.require_auth();
+        require_not_paused(&env)?;
+        if !Self::is_revenue_reporter(env.clone(), reporter.clone()) {
+            return Err(ContractError::Unauthorized);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        Self::enter_locked_section(&env)?;
+
+        token::Client::new(&env, &token_address).transfer(
+            &reporter,
+            env.current_contract_address(),
+            &amount,
+        );
+
+        let total = add_revenue(&env, &source, &token_address, amount)?;
+        publish_revenue_event(&env, &source, &token_address, &reporter, amount, total);
+
+        Self::exit_locked_section(&env);
+        Ok(total)
     }
 
-    /// Get total balance held in treasury
-    /// 
-    /// # Returns
-    /// Total balance across all users
-    pub fn get_total_balance(env: Env) -> i128 {
-        // Note: In a production environment, you might want to maintain
-        // a separate total balance counter for efficiency
-        let total = symbol_short!("total");
-        env.storage().instance().get(&total)
+    /// Record revenue a registered platform contract has already paid to
+    /// this contract, either as `token_address` tokens or as credit on the
+    /// balance ledger whose address is `token_address` (e.g. settlement
+    /// rake). No tokens move. Returns the source's new total for the token.
+    pub fn book_revenue(
+        env: Env,
+        reporter: Address,
+        source: Symbol,
+        token_address: Address,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        reporter.require_auth();
+        require_not_paused(&env)?;
+        if !Self::is_revenue_reporter(env.clone(), reporter.clone()) {
+            return Err(ContractError::Unauthorized);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        let total = add_revenue(&env, &source, &token_address, amount)?;
+        publish_revenue_event(&env, &source, &token_address, &reporter, amount, total);
+        Ok(total)
+    }
+
+    /// Ask the balance ledger `ledger` to pay out `amount` of the credit it
+    /// holds for this contract, such as booked rake. The request goes through
+    /// the ledger's usual withdrawal flow: once the ledger backend executes
+    /// it, the withdrawal token lands here as surplus. Requires
+    /// `Role::Treasurer`.
+    pub fn withdraw_ledger_credit(
+        env: Env,
+        treasurer: Address,
+        ledger: Address,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        require_role(&env, Role::Treasurer, &treasurer)?;
+        require_not_paused(&env)?;
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+
+        let treasury = env.current_contract_address();
+        env.invoke_contract::<Val>(
+            &ledger,
+            &Symbol::new(&env, "request_withdrawal"),
+            vec![&env, treasury.into_val(&env), amount.into_val(&env)],
+        );
+        publish_treasury_event(&env, "LedgerWithdraw", &treasurer, &ledger, amount, 0);
+        Ok(())
+    }
+
+    pub fn get_revenue(env: Env, source: Symbol, token_address: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Revenue(source, token_address))
             .unwrap_or(0)
     }
 
+    /// Page of cumulative revenue per (source, token), in the order each
+    /// pair was first recorded. `limit` is capped at `MAX_PAGE_SIZE`.
+    pub fn get_revenue_breakdown(env: Env, offset: u32, limit: u32) -> Vec<RevenueEntry> {
+        let keys = get_revenue_keys(&env);
+        let mut page = Vec::new(&env);
+        let end = offset
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(keys.len());
+        for index in offset..end {
+            let (source, token) = keys.get_unchecked(index);
+            page.push_back(RevenueEntry {
+                total: Self::get_revenue(env.clone(), source.clone(), token.clone()),
+                source,
+                token,
+            });
+        }
+        page
+    }
+
+    /// Get the balance of a specific user for one token
+    pub fn get_balance(env: Env, user: Address, token_address: Address) -> i128 {
+        get_balance(&env, &user, &token_address)
+    }
+
+    /// Get total balance of one token held in treasury across all users
+    pub fn get_total_balance(env: Env, token_address: Address) -> i128 {
+        get_total_for_token(&env, &token_address)
+    }
+
+    /// Sum of every user's balance of `token_address`, kept in step with
+    /// each deposit and withdrawal
+    pub fn get_total_for_token(env: Env, token_address: Address) -> i128 {
+        get_total_for_token(&env, &token_address)
+    }
+
+    /// Compare the tracked total for `token_address` against the tokens the
+    /// contract actually holds
+    pub fn verify_solvency(env: Env, token_address: Address) -> SolvencyReport {
+        solvency_report(&env, &token_address)
+    }
+
     /// Internal function for reentrancy protection - enter locked section
-    fn _enter_locked_section(env: &Env) {
-        let lock_key = TREASURY_LOCK;
-        let is_locked: bool = env.storage().instance().get(&lock_key)
-            .unwrap_or(false);
-        
+    fn enter_locked_section(env: &Env) -> Result<(), ContractError> {
+        let is_locked: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Lock)
+            .ok_or(ContractError::NotInitialized)?;
+
         if is_locked {
-            panic!("Reentrancy detected");
+            return Err(ContractError::InvalidStatus);
         }
-        
-        env.storage().instance().set(&lock_key, &true);
+
+        env.storage().instance().set(&DataKey::Lock, &true);
+        Ok(())
     }
 
     /// Internal function for reentrancy protection - exit locked section
-    fn _exit_locked_section(env: &Env) {
-        let lock_key = TREASURY_LOCK;
-        env.storage().instance().set(&lock_key, &false);
+    fn exit_locked_section(env: &Env) {
+        env.storage().instance().set(&DataKey::Lock, &false);
+    }
+}
+
+fn require_untimelocked_role(role: Role) -> Result<(), ContractError> {
+    match role {
+        Role::Admin | Role::Treasurer | Role::Guardian => Err(ContractError::TimelockRequired),
+        _ => Ok(()),
+    }
+}
+
+fn validate_action(action: &TimelockAction) -> Result<(), ContractError> {
+    match action {
+        TimelockAction::SetWithdrawLimit(_, Some(limit)) if *limit <= 0 => {
+            Err(ContractError::InvalidAmount)
+        }
+        _ => Ok(()),
     }
 }
+
+fn action_hash(env: &Env, action: &TimelockAction) -> BytesN<32> {
+    env.crypto().sha256(&action.clone().to_xdr(env)).into()
+}
+
+fn get_balance(env: &Env, user: &Address, token_address: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Balance(user.clone(), token_address.clone()))
+        .unwrap_or(0)
+}
+
+fn set_balance(env: &Env, user: &Address, token_address: &Address, balance: i128) {
+    let key = DataKey::Balance(user.clone(), token_address.clone());
+    if balance == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &balance);
+        common::bump_persistent(env, &key);
+    }
+}
+
+fn adjust_total_balance(
+    env: &Env,
+    token_address: &Address,
+    delta: i128,
+) -> Result<(), ContractError> {
+    let new_total = get_total_for_token(env, token_address)
+        .checked_add(delta)
+        .ok_or(ContractError::InvalidAmount)?;
+    let key = DataKey::TotalBalance(token_address.clone());
+    env.storage().persistent().set(&key, &new_total);
+    common::bump_persistent(env, &key);
+    Ok(())
+}
+
+fn get_total_for_token(env: &Env, token_address: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TotalBalance(token_address.clone()))
+        .unwrap_or(0)
+}
+
+fn get_revenue_keys(env: &Env) -> Vec<(Symbol, Address)> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RevenueKeys)
+        .unwrap_or(Vec::new(env))
+}
+
+fn add_revenue(
+    env: &Env,
+    source: &Symbol,
+    token_address: &Address,
+    amount: i128,
+) -> Result<i128, ContractError> {
+    let storage = env.storage().persistent();
+    let key = DataKey::Revenue(source.clone(), token_address.clone());
+    let total = match storage.get::<_, i128>(&key) {
+        Some(total) => total,
+        None => {
+            let mut keys = get_revenue_keys(env);
+            keys.push_back((source.clone(), token_address.clone()));
+            storage.set(&DataKey::RevenueKeys, &keys);
+            0
+        }
+    }
+    .checked_add(amount)
+    .ok_or(ContractError::InvalidAmount)?;
+    storage.set(&key, &total);
+    common::bump_persistent(env, &key);
+    common::bump_persistent(env, &DataKey::RevenueKeys);
+    Ok(total)
+}
+
+fn solvency_report(env: &Env, token_address: &Address) -> SolvencyReport {
+    let tracked_total = get_total_for_token(env, token_address);
+    let actual_balance =
+        token::Client::new(env, token_address).balance(&env.current_contract_address());
+    let surplus = actual_balance - tracked_total;
+    SolvencyReport {
+        tracked_total,
+        actual_balance,
+        surplus,
+        solvent: surplus >= 0,
+    }
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_treasury_event(
+    env: &Env,
+    topic: &str,
+    user: &Address,
+    token_address: &Address,
+    amount: i128,
+    new_balance: i128,
+) {
+    env.events().publish(
+        (Symbol::new(env, topic), user.clone(), token_address.clone()),
+        (amount, new_balance),
+    );
+}
+
+#[allow(deprecated)] // keep (topic, payload) format for indexer compatibility
+fn publish_revenue_event(
+    env: &Env,
+    source: &Symbol,
+    token_address: &Address,
+    reporter: &Address,
+    amount: i128,
+    total: i128,
+) {
+    env.events().publish(
+        (
+            Symbol::new(env, "revenue_recorded"),
+            source.clone(),
+            token_address.clone(),
+        ),
+        (reporter.clone(), amount, total),
+    );
+}
+
+#[cfg(test)]
+mod test;
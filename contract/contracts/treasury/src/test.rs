@@ -1,68 +1,407 @@
-#[cfg(test)]
-mod tests {
-    use soroban_sdk::{Env, Address};
-    use crate::Treasury;
-
-    #[test]
-    fn test_initialization() {
-        let env = Env::default();
-        let contract_id = env.register_contract(None, Treasury);
-        let client = TreasuryClient::new(&env, &contract_id);
-        
-        // Should initialize successfully
-        client.initialize();
-        
-        // Should panic on second initialization
-        let result = std::panic::catch_unwind(|| {
-            client.initialize();
-        });
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_deposit_and_withdraw() {
-        let env = Env::default();
-        let contract_id = env.register_contract(None, Treasury);
-        let client = TreasuryClient::new(&env, &contract_id);
-        
-        client.initialize();
-        
-        let user = Address::generate(&env);
-        
-        // Test deposit
-        client.deposit(&user, &100);
-        assert_eq!(client.get_balance(&user), 100);
-        
-        // Test withdraw
-        client.withdraw(&user, &50);
-        assert_eq!(client.get_balance(&user), 50);
-        
-        // Test insufficient balance
-        let result = std::panic::catch_unwind(|| {
-            client.withdraw(&user, &100);
-        });
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_invalid_amounts() {
-        let env = Env::default();
-        let contract_id = env.register_contract(None, Treasury);
-        let client = TreasuryClient::new(&env, &contract_id);
-        
-        client.initialize();
-        let user = Address::generate(&env);
-        
-        // Test zero deposit
-        let result = std::panic::catch_unwind(|| {
-            client.deposit(&user, &0);
-        });
-        assert!(result.is_err());
-        
-        // Test negative deposit
-        let result = std::panic::catch_unwind(|| {
-            client.deposit(&user, &-10);
-        });
-        assert!(result.is_err());
-    }
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, Env, Symbol,
+};
+
+fn setup(env: &Env) -> (TreasuryClient<'_>, token::Client<'_>, Address) {
+    env.mock_all_auths();
+
+    let contract_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(env, &contract_id);
+    client.initialize(&Address::generate(env));
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let user = Address::generate(env);
+    token::StellarAssetClient::new(env, &token_id).mint(&user, &1_000);
+
+    (client, token::Client::new(env, &token_id), user)
+}
+
+#[test]
+fn test_initialization() {
+    let env = Env::default();
+    let (client, _token, _user) = setup(&env);
+
+    assert_eq!(
+        client.try_initialize(&Address::generate(&env)),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn test_deposit_and_withdraw_move_tokens() {
+    let env = Env::default();
+    let (client, token, user) = setup(&env);
+
+    assert_eq!(client.deposit(&user, &token.address, &100), 100);
+    assert_eq!(client.get_balance(&user, &token.address), 100);
+    assert_eq!(client.get_total_balance(&token.address), 100);
+    assert_eq!(token.balance(&user), 900);
+    assert_eq!(token.balance(&client.address), 100);
+
+    assert_eq!(client.withdraw(&user, &token.address, &50), 50);
+    assert_eq!(client.get_balance(&user, &token.address), 50);
+    assert_eq!(client.get_total_balance(&token.address), 50);
+    assert_eq!(token.balance(&user), 950);
+    assert_eq!(token.balance(&client.address), 50);
+
+    assert_eq!(
+        client.try_withdraw(&user, &token.address, &100),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+}
+
+#[test]
+fn test_balances_are_tracked_per_token() {
+    let env = Env::default();
+    let (client, token, user) = setup(&env);
+
+    let other_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &other_id).mint(&user, &500);
+
+    client.deposit(&user, &token.address, &100);
+    client.deposit(&user, &other_id, &300);
+
+    assert_eq!(client.get_balance(&user, &token.address), 100);
+    assert_eq!(client.get_balance(&user, &other_id), 300);
+    assert_eq!(client.get_total_balance(&other_id), 300);
+    assert_eq!(client.get_total_for_token(&token.address), 100);
+    assert_eq!(client.get_total_for_token(&other_id), 300);
+    assert_eq!(
+        client.try_withdraw(&user, &token.address, &101),
+        Err(Ok(ContractError::InsufficientBalance))
+    );
+}
+
+#[test]
+fn test_invalid_amounts() {
+    let env = Env::default();
+    let (client, token, user) = setup(&env);
+
+    assert_eq!(
+        client.try_deposit(&user, &token.address, &0),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_deposit(&user, &token.address, &-10),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_withdraw(&user, &token.address, &0),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
+
+#[test]
+fn test_pause_requires_pauser_role() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let pauser = Address::generate(&env);
+    let contract_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let user = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token_id).mint(&user, &1_000);
+
+    assert_eq!(
+        client.try_pause(&pauser),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.grant_role(&admin, &Role::Pauser, &pauser);
+    client.pause(&pauser);
+
+    assert_eq!(
+        client.try_deposit(&user, &token_id, &100),
+        Err(Ok(ContractError::Paused))
+    );
+    assert_eq!(
+        client.try_unpause(&pauser),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    client.unpause(&admin);
+    assert_eq!(client.deposit(&user, &token_id, &100), 100);
+}
+
+fn warp(env: &Env, seconds: u64) {
+    env.ledger().with_mut(|ledger| ledger.timestamp += seconds);
+}
+
+#[test]
+fn test_signer_changes_go_through_the_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let signer = Address::generate(&env);
+    let contract_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    assert_eq!(
+        client.try_grant_role(&admin, &Role::Treasurer, &signer),
+        Err(Ok(ContractError::TimelockRequired))
+    );
+
+    let action = TimelockAction::GrantRole(Role::Treasurer, signer.clone());
+    let id = client.queue_action(&admin, &action);
+    let entry = client.get_queued_action(&id).unwrap();
+    assert_eq!(
+        entry.executable_at,
+        entry.queued_at + DEFAULT_TIMELOCK_DELAY
+    );
+
+    assert_eq!(
+        client.try_execute_action(&admin, &id, &action),
+        Err(Ok(ContractError::TimelockNotReady))
+    );
+    warp(&env, DEFAULT_TIMELOCK_DELAY);
+    assert_eq!(
+        client.try_execute_action(
+            &admin,
+            &id,
+            &TimelockAction::GrantRole(Role::Admin, signer.clone())
+        ),
+        Err(Ok(ContractError::InvalidConfig))
+    );
+    client.execute_action(&admin, &id, &action);
+
+    assert!(client.has_role(&Role::Treasurer, &signer));
+    assert_eq!(client.get_queued_action(&id), None);
+    assert_eq!(
+        client.try_execute_action(&admin, &id, &action),
+        Err(Ok(ContractError::ActionNotFound))
+    );
+}
+
+#[test]
+fn test_guardian_can_cancel_queued_action() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    let action = TimelockAction::SetDelay(60);
+    let id = client.queue_action(&admin, &action);
+    assert_eq!(
+        client.try_cancel_action(&Address::generate(&env), &id),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    client.cancel_action(&admin, &id);
+
+    warp(&env, DEFAULT_TIMELOCK_DELAY);
+    assert_eq!(
+        client.try_execute_action(&admin, &id, &action),
+        Err(Ok(ContractError::ActionNotFound))
+    );
+    assert_eq!(client.get_timelock_delay(), DEFAULT_TIMELOCK_DELAY);
+}
+
+#[test]
+fn test_withdraw_limit_and_sweep_are_timelocked() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token = token::Client::new(&env, &token_id);
+    let user = Address::generate(&env);
+    let sink = Address::generate(&env);
+    let minter = token::StellarAssetClient::new(&env, &token_id);
+    minter.mint(&user, &1_000);
+    client.deposit(&user, &token_id, &500);
+    // Sent directly, so no user is owed it
+    minter.mint(&contract_id, &70);
+
+    let limit = TimelockAction::SetWithdrawLimit(token_id.clone(), Some(100));
+    let sweep = TimelockAction::Sweep(token_id.clone(), sink.clone());
+    let limit_id = client.queue_action(&admin, &limit);
+    let sweep_id = client.queue_action(&admin, &sweep);
+    warp(&env, DEFAULT_TIMELOCK_DELAY);
+    client.execute_action(&admin, &limit_id, &limit);
+    client.execute_action(&admin, &sweep_id, &sweep);
+
+    assert_eq!(client.get_withdraw_limit(&token_id), Some(100));
+    assert_eq!(
+        client.try_withdraw(&user, &token_id, &101),
+        Err(Ok(ContractError::ExceedsLimit))
+    );
+    assert_eq!(client.withdraw(&user, &token_id, &100), 400);
+
+    assert_eq!(token.balance(&sink), 70);
+    assert_eq!(token.balance(&contract_id), 400);
+}
+
+#[test]
+fn test_verify_solvency_compares_totals_with_holdings() {
+    let env = Env::default();
+    let (client, token, user) = setup(&env);
+    let other = Address::generate(&env);
+    token.transfer(&user, &other, &200);
+
+    client.deposit(&user, &token.address, &300);
+    client.deposit(&other, &token.address, &150);
+    client.withdraw(&user, &token.address, &50);
+    assert_eq!(client.get_total_for_token(&token.address), 400);
+    assert_eq!(
+        client.verify_solvency(&token.address),
+        SolvencyReport {
+            tracked_total: 400,
+            actual_balance: 400,
+            surplus: 0,
+            solvent: true,
+        }
+    );
+
+    // Tokens sent straight to the contract are surplus, not liabilities
+    token.transfer(&user, &client.address, &25);
+    let report = client.verify_solvency(&token.address);
+    assert_eq!(report.surplus, 25);
+    assert!(report.solvent);
+}
+
+#[test]
+fn test_record_revenue_from_registered_contracts() {
+    let env = Env::default();
+    let (_, token, reporter) = setup(&env);
+    let admin = Address::generate(&env);
+    let contract_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    let betting = Symbol::new(&env, "betting_fees");
+    let spins = Symbol::new(&env, "spin_pool");
+    assert_eq!(
+        client.try_record_revenue(&reporter, &betting, &token.address, &10),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    client.set_revenue_reporter(&admin, &reporter, &true);
+    assert_eq!(
+        client.record_revenue(&reporter, &betting, &token.address, &10),
+        10
+    );
+    assert_eq!(
+        client.record_revenue(&reporter, &spins, &token.address, &5),
+        5
+    );
+    assert_eq!(
+        client.record_revenue(&reporter, &betting, &token.address, &20),
+        30
+    );
+    assert_eq!(
+        client.try_record_revenue(&reporter, &betting, &token.address, &0),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+
+    assert_eq!(client.get_revenue(&betting, &token.address), 30);
+    assert_eq!(token.balance(&contract_id), 35);
+    // Revenue is held but owed to nobody
+    assert_eq!(client.verify_solvency(&token.address).surplus, 35);
+
+    let breakdown = client.get_revenue_breakdown(&0, &10);
+    assert_eq!(breakdown.len(), 2);
+    assert_eq!(
+        breakdown.get_unchecked(0),
+        RevenueEntry {
+            source: betting,
+            token: token.address.clone(),
+            total: 30,
+        }
+    );
+    assert_eq!(breakdown.get_unchecked(1).source, spins);
+    assert_eq!(client.get_revenue_breakdown(&1, &10).len(), 1);
+}
+
+#[test]
+fn test_upgrade_is_timelocked() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    let action = TimelockAction::Upgrade(BytesN::from_array(&env, &[1; 32]));
+    let id = client.queue_action(&admin, &action);
+    assert_eq!(
+        client.try_execute_action(&admin, &id, &action),
+        Err(Ok(ContractError::TimelockNotReady))
+    );
+    client.cancel_action(&admin, &id);
+
+    assert_eq!(client.get_version(), CONTRACT_VERSION);
+    assert_eq!(client.migrate(&admin), None);
+}
+
+#[test]
+fn test_ledger_credit_is_withdrawn_through_the_ledger() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(Treasury, ());
+    let client = TreasuryClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    // Ledger withdrawals are paid from a pool the ledger may spend
+    let pool = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&pool, &1_000);
+    let ledger_id = env.register(balance_ledger::BalanceLedgerContract, ());
+    let ledger = balance_ledger::BalanceLedgerContractClient::new(&env, &ledger_id);
+    token.approve(&pool, &ledger_id, &1_000, &1_000);
+    ledger.initialize(&admin);
+    ledger.set_token_allowed(&admin, &token_id, &true);
+    ledger.set_withdrawal_config(&admin, &token_id, &pool);
+    ledger.set_balance_for_token(&contract_id, &token_id, &150, &0, &None, &None);
+
+    assert_eq!(
+        client.try_withdraw_ledger_credit(&Address::generate(&env), &ledger_id, &100),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_withdraw_ledger_credit(&admin, &ledger_id, &0),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    client.withdraw_ledger_credit(&admin, &ledger_id, &100);
+    assert_eq!(
+        ledger.get_pending_withdrawal(&contract_id).unwrap().amount,
+        100
+    );
+
+    ledger.execute_withdrawal(&BytesN::from_array(&env, &[1; 32]), &contract_id, &100);
+    assert_eq!(token.balance(&contract_id), 100);
+    assert_eq!(
+        ledger.get_withdrawable_for_token(&contract_id, &token_id),
+        50
+    );
+    // Nothing is owed to users, so the payout is surplus
+    assert_eq!(client.verify_solvency(&token_id).surplus, 100);
 }